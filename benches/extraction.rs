@@ -0,0 +1,66 @@
+//! Extraction throughput benchmarks for VSIX (zip) and CAB payloads.
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use msvc_kit::installer::{extract_cab, extract_vsix};
+
+/// (file count, file size) combinations: many small headers vs. a few large libs
+const SHAPES: &[(usize, usize)] = &[(256, 4 * 1024), (8, 1024 * 1024)];
+
+fn bench_extract_vsix(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let mut group = c.benchmark_group("extract/vsix");
+    group.sample_size(20);
+    for &(count, size) in SHAPES {
+        let archive = fixtures::write_vsix(
+            &temp_dir.path().join(format!("{}x{}.vsix", count, size)),
+            count,
+            size,
+        );
+        let out_dir = temp_dir.path().join(format!("vsix_out_{}x{}", count, size));
+        group.throughput(Throughput::Bytes((count * size) as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{}x{}", count, size)),
+            |b| {
+                b.iter(|| {
+                    let _ = std::fs::remove_dir_all(&out_dir);
+                    runtime.block_on(extract_vsix(&archive, &out_dir)).unwrap();
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn bench_extract_cab(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let mut group = c.benchmark_group("extract/cab");
+    group.sample_size(20);
+    for &(count, size) in SHAPES {
+        let archive = fixtures::write_cab(
+            &temp_dir.path().join(format!("{}x{}.cab", count, size)),
+            count,
+            size,
+        );
+        let out_dir = temp_dir.path().join(format!("cab_out_{}x{}", count, size));
+        group.throughput(Throughput::Bytes((count * size) as u64));
+        group.bench_function(
+            BenchmarkId::from_parameter(format!("{}x{}", count, size)),
+            |b| {
+                b.iter(|| {
+                    let _ = std::fs::remove_dir_all(&out_dir);
+                    runtime.block_on(extract_cab(&archive, &out_dir)).unwrap();
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_extract_vsix, bench_extract_cab);
+criterion_main!(benches);
@@ -0,0 +1,164 @@
+//! Synthetic fixtures shared by the benchmark suites.
+//!
+//! Everything here is generated deterministically so that runs are comparable
+//! across machines and over time. This module is only compiled for benches.
+
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use msvc_kit::downloader::{DownloadStatus, IndexEntry, PackagePayload, VsManifest};
+
+/// Generate `len` bytes of pseudo-random but reproducible data.
+///
+/// Uses a small xorshift generator so the payload does not compress to nothing
+/// (which would make deflate/MSZIP benchmarks meaningless).
+pub fn payload_bytes(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    let mut data = Vec::with_capacity(len);
+    while data.len() < len {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        data.extend_from_slice(&state.to_le_bytes());
+    }
+    data.truncate(len);
+    data
+}
+
+/// Write a VSIX-style zip archive with `file_count` files of `file_size` bytes
+/// under the `Contents/` prefix, mirroring real MSVC payloads.
+pub fn write_vsix(path: &Path, file_count: usize, file_size: usize) -> PathBuf {
+    let file = File::create(path).expect("create vsix fixture");
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    // Metadata entries that the extractor is expected to skip
+    zip.start_file("[Content_Types].xml", options)
+        .expect("start vsix entry");
+    zip.write_all(b"<Types/>").expect("write vsix entry");
+    zip.start_file("extension.vsixmanifest", options)
+        .expect("start vsix entry");
+    zip.write_all(b"<PackageManifest/>")
+        .expect("write vsix entry");
+
+    for i in 0..file_count {
+        let name = format!("Contents/VC/Tools/MSVC/14.99.0/include/header_{:04}.h", i);
+        zip.start_file(name, options).expect("start vsix entry");
+        zip.write_all(&payload_bytes(file_size, i as u64))
+            .expect("write vsix entry");
+    }
+    zip.finish().expect("finish vsix fixture");
+    path.to_path_buf()
+}
+
+/// Write an MSZIP-compressed cabinet with `file_count` files of `file_size` bytes,
+/// similar to the cabs shipped alongside Windows SDK MSIs.
+pub fn write_cab(path: &Path, file_count: usize, file_size: usize) -> PathBuf {
+    let mut builder = cab::CabinetBuilder::new();
+    {
+        let folder = builder.add_folder(cab::CompressionType::MsZip);
+        for i in 0..file_count {
+            folder.add_file(format!("lib_{:04}.lib", i));
+        }
+    }
+
+    let file = File::create(path).expect("create cab fixture");
+    let mut writer = builder.build(file).expect("build cab fixture");
+    let mut index = 0u64;
+    while let Some(mut entry) = writer.next_file().expect("next cab entry") {
+        entry
+            .write_all(&payload_bytes(file_size, index))
+            .expect("write cab entry");
+        index += 1;
+    }
+    writer.finish().expect("finish cab fixture");
+    path.to_path_buf()
+}
+
+/// Build a synthetic `.vsman` document with `package_count` packages.
+///
+/// Package ids follow the real naming scheme so `find_msvc_packages` and
+/// `find_sdk_packages` exercise their normal matching paths.
+pub fn manifest_json(package_count: usize) -> String {
+    let mut packages = Vec::with_capacity(package_count);
+    for i in 0..package_count {
+        let id = match i % 4 {
+            0 => format!(
+                "Microsoft.VC.14.{}.17.{}.Tools.HostX64.TargetX64.base",
+                40 + i % 5,
+                i
+            ),
+            1 => format!("Microsoft.VC.14.{}.17.{}.CRT.Headers.base", 40 + i % 5, i),
+            2 => format!("Win11SDK_10.0.{}", 22000 + i % 700),
+            _ => format!("Microsoft.VisualStudio.Component.Synthetic.{}", i),
+        };
+        let payloads: Vec<_> = (0..3)
+            .map(|p| {
+                serde_json::json!({
+                    "fileName": format!("payload_{}_{}.vsix", i, p),
+                    "sha256": msvc_kit::downloader::hash::compute_hash(
+                        format!("{}-{}", i, p).as_bytes()
+                    ),
+                    "size": 1024 * (p + 1),
+                    "url": format!("https://example.invalid/{}/{}.vsix", i, p),
+                })
+            })
+            .collect();
+        packages.push(serde_json::json!({
+            "id": id,
+            "version": format!("14.{}.{}", 40 + i % 5, i),
+            "type": "Vsix",
+            "chip": "x64",
+            "payloads": payloads,
+            "dependencies": HashMap::<String, serde_json::Value>::new(),
+        }));
+    }
+
+    serde_json::json!({
+        "manifestVersion": "1.1",
+        "engineVersion": "3.0.0",
+        "packages": packages,
+    })
+    .to_string()
+}
+
+/// Parse a synthetic manifest, panicking on failure (fixtures are trusted).
+pub fn manifest(package_count: usize) -> VsManifest {
+    serde_json::from_str(&manifest_json(package_count)).expect("parse manifest fixture")
+}
+
+/// Build a payload descriptor for index benchmarks.
+pub fn package_payload(i: usize) -> PackagePayload {
+    PackagePayload {
+        file_name: format!("payload_{:05}.vsix", i),
+        url: format!("https://example.invalid/payload_{:05}.vsix", i),
+        size: 4096 + i as u64,
+        sha256: Some(msvc_kit::downloader::hash::compute_hash(
+            i.to_le_bytes().as_slice(),
+        )),
+        mirrors: Vec::new(),
+    }
+}
+
+/// Build a completed index entry for index benchmarks.
+pub fn index_entry(i: usize, dir: &Path) -> IndexEntry {
+    let payload = package_payload(i);
+    IndexEntry {
+        local_path: dir.join(&payload.file_name),
+        file_name: payload.file_name,
+        url: payload.url,
+        size: payload.size,
+        computed_hash: payload.sha256.clone(),
+        sha256: payload.sha256,
+        status: DownloadStatus::Completed,
+        bytes_downloaded: payload.size,
+        hash_verified: true,
+        updated_at: Utc::now(),
+    }
+}
@@ -0,0 +1,39 @@
+//! Hashing throughput benchmarks (in-memory and streaming file hashing).
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use msvc_kit::downloader::hash::{compute_file_hash, compute_hash};
+
+const SIZES: &[usize] = &[64 * 1024, 1024 * 1024, 16 * 1024 * 1024];
+
+fn bench_compute_hash(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash/in_memory");
+    for &size in SIZES {
+        let data = fixtures::payload_bytes(size, 1);
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &data, |b, data| {
+            b.iter(|| compute_hash(data))
+        });
+    }
+    group.finish();
+}
+
+fn bench_compute_file_hash(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let mut group = c.benchmark_group("hash/file");
+    for &size in SIZES {
+        let path = temp_dir.path().join(format!("payload_{}.bin", size));
+        std::fs::write(&path, fixtures::payload_bytes(size, 2)).unwrap();
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &path, |b, path| {
+            b.iter(|| runtime.block_on(compute_file_hash(path)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_compute_hash, bench_compute_file_hash);
+criterion_main!(benches);
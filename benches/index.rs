@@ -0,0 +1,84 @@
+//! Download index (redb) benchmarks for the fast-path lookups and upserts.
+
+mod fixtures;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use msvc_kit::downloader::DownloadIndex;
+
+const ENTRY_COUNT: usize = 1_000;
+
+fn bench_index(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+
+    let mut group = c.benchmark_group("index");
+    group.sample_size(20);
+
+    group.bench_function("upsert_1000", |b| {
+        b.iter_batched(
+            || {
+                let dir = tempfile::tempdir_in(temp_dir.path()).unwrap();
+                let index = runtime
+                    .block_on(DownloadIndex::load(&dir.path().join("index.db")))
+                    .unwrap();
+                (dir, index)
+            },
+            |(dir, mut index)| {
+                runtime.block_on(async {
+                    for i in 0..ENTRY_COUNT {
+                        index
+                            .upsert_entry(&fixtures::index_entry(i, dir.path()))
+                            .await
+                            .unwrap();
+                    }
+                });
+                dir
+            },
+            BatchSize::PerIteration,
+        )
+    });
+
+    // Pre-populated index for the read-side benchmarks
+    let populated = temp_dir.path().join("populated");
+    std::fs::create_dir_all(&populated).unwrap();
+    let mut index = runtime
+        .block_on(DownloadIndex::load(&populated.join("index.db")))
+        .unwrap();
+    runtime.block_on(async {
+        for i in 0..ENTRY_COUNT {
+            index
+                .upsert_entry(&fixtures::index_entry(i, &populated))
+                .await
+                .unwrap();
+        }
+    });
+
+    group.bench_function("get_entry", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i = (i + 1) % ENTRY_COUNT;
+            let name = fixtures::package_payload(i).file_name;
+            runtime.block_on(index.get_entry(&name)).unwrap()
+        })
+    });
+
+    group.bench_function("is_entry_unchanged", |b| {
+        let entry = fixtures::index_entry(42, &populated);
+        b.iter(|| {
+            runtime
+                .block_on(index.is_entry_unchanged(
+                    &entry.file_name,
+                    entry.status.clone(),
+                    entry.size,
+                    &entry.computed_hash,
+                    &entry.local_path,
+                ))
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_index);
+criterion_main!(benches);
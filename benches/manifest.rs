@@ -0,0 +1,53 @@
+//! Manifest parse benchmarks comparing serde_json and simd-json, plus package lookup.
+
+mod fixtures;
+
+use std::collections::HashSet;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use msvc_kit::downloader::VsManifest;
+
+const PACKAGE_COUNTS: &[usize] = &[1_000, 10_000];
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("manifest/parse");
+    for &count in PACKAGE_COUNTS {
+        let json = fixtures::manifest_json(count);
+        group.throughput(Throughput::Bytes(json.len() as u64));
+
+        group.bench_with_input(BenchmarkId::new("serde_json", count), &json, |b, json| {
+            b.iter(|| serde_json::from_str::<VsManifest>(json).unwrap())
+        });
+
+        group.bench_with_input(BenchmarkId::new("simd_json", count), &json, |b, json| {
+            b.iter_batched(
+                || json.as_bytes().to_vec(),
+                |mut bytes| simd_json::from_slice::<VsManifest>(&mut bytes).unwrap(),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_find_packages(c: &mut Criterion) {
+    let manifest = fixtures::manifest(10_000);
+    let include = HashSet::new();
+
+    let mut group = c.benchmark_group("manifest/find");
+    group.bench_function("msvc_packages", |b| {
+        b.iter(|| manifest.find_msvc_packages("14.44", "x64", "x64", &include, &[]))
+    });
+    group.bench_function("latest_versions", |b| {
+        b.iter(|| {
+            (
+                manifest.get_latest_msvc_version(),
+                manifest.get_latest_sdk_version(),
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_find_packages);
+criterion_main!(benches);
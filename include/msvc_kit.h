@@ -0,0 +1,48 @@
+/*
+ * C API of msvc-kit (built with the `ffi` feature)
+ *
+ *   cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * Strings are UTF-8 and NUL-terminated. Returned strings must be released
+ * with msvc_kit_string_free(). On failure, functions return NULL or -1 and
+ * msvc_kit_last_error() describes the error on the calling thread.
+ */
+#ifndef MSVC_KIT_H
+#define MSVC_KIT_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Receives one JSON event object and the user_data given to the download */
+typedef void (*msvc_kit_progress_cb)(const char *event, void *user_data);
+
+/* Library version; static, do not free */
+const char *msvc_kit_version(void);
+
+/* Message of the last failed call on this thread, or NULL; do not free */
+const char *msvc_kit_last_error(void);
+
+/* Release a string returned by msvc-kit */
+void msvc_kit_string_free(char *s);
+
+/* QueryResult of install_dir as JSON; arch may be NULL (host) */
+char *msvc_kit_query_installation(const char *install_dir, const char *arch);
+
+/* Environment variables of install_dir as sorted "KEY=value\n" lines */
+char *msvc_kit_get_env_vars(const char *install_dir, const char *arch);
+
+/*
+ * Download and extract MSVC and the Windows SDK; blocks until done.
+ * options_json is a serialized DownloadOptions. callback may be NULL and is
+ * called from worker threads. On success *result_json (if not NULL) holds
+ * {"msvc": InstallInfo, "sdk": InstallInfo}. Returns 0 or -1.
+ */
+int msvc_kit_download(const char *options_json, msvc_kit_progress_cb callback,
+                      void *user_data, char **result_json);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MSVC_KIT_H */
@@ -1,18 +1,50 @@
 //! msvc-kit CLI - Portable MSVC Build Tools installer and manager
 
+use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use clap::{CommandFactory, Parser, Subcommand};
 use tracing_subscriber::{fmt, prelude::*, EnvFilter};
 
-use msvc_kit::bundle::{generate_bundle_scripts, save_bundle_scripts, BundleLayout};
-use msvc_kit::env::generate_activation_script;
-use msvc_kit::query::{QueryComponent, QueryOptions, QueryProperty};
+use msvc_kit::bundle::{
+    create_multi_arch_bundles, generate_bundle_scripts_with_locale, msvc_wine, payload_bytes,
+    plan_bundle, save_bundle_scripts, write_bundle_manifest, write_dockerfile,
+    write_sandbox_config, BundleLayout, BundleOptions, BundleScripts, BundleStats, LayoutStyle,
+    ProvisionOptions, BUNDLE_MANIFEST_FILE,
+};
+use msvc_kit::diagnostics::DiagnosticsOptions;
+use msvc_kit::downloader::{
+    import_vsconfig, is_match_host, parse_rate, ChunkedDownloads, MsvcDownloader, SdkDownloader,
+};
+use msvc_kit::env::{
+    clang_args, clang_cl_args, create_prompt_shortcut, default_profile_name, default_profiles_dir,
+    detect_active_environment, detect_host_arch, direnv, dotenv, generate_activation_script,
+    get_env_changes, list_profiles, profile_commands, prompt_shortcut_name, read_profile,
+    write_profile, EnvPlan,
+};
+use msvc_kit::installer::{
+    add_components, clean, copy_redist, find_orphans, invalidate_extraction, list_hook_scripts,
+    relocate, repair_component, run_hook_scripts, sync_toolchains, CleanOptions, CleanupPolicy,
+    HookScriptContext, RedistOptions, RepairComponent, SyncAction, SyncOptions, ToolchainsFile,
+};
+use msvc_kit::license::{
+    accept_license, is_license_accepted, LICENSE_ACCEPTANCE_FILE, LICENSE_URL,
+};
+use msvc_kit::preflight::{CheckStatus, PreflightOptions};
+use msvc_kit::query::{
+    check_compat, find_visual_studio, system_windows_kits_root, CompatStatus, QueryComponent,
+    QueryOptions, QueryProperty,
+};
+use msvc_kit::sbom::SbomFormat;
+use msvc_kit::scripts::install_script_context;
 use msvc_kit::version::{list_installed_msvc, list_installed_sdk, Architecture};
+use msvc_kit::winpath::UnixFlavor;
 use msvc_kit::{
     download_msvc, download_sdk, generate_script, get_env_vars, load_config, query_installation,
-    save_config, setup_environment, DownloadOptions, MsvcComponent, MsvcKitConfig, ScriptContext,
-    ShellType,
+    save_config, save_scripts, setup_environment_with_arch, AppPlatform, DownloadOptions,
+    DownloadOptionsBuilder, Locale, MsvcComponent, MsvcKitConfig, ReleaseChannel, ScriptContext,
+    ShellType, ToolKind, VerifyMode, Warning, WarningCode,
 };
 
 /// Portable MSVC Build Tools installer and manager
@@ -42,18 +74,30 @@ enum Commands {
         #[arg(long)]
         msvc_version: Option<String>,
 
-        /// Windows SDK version to download (default: latest)
+        /// Windows SDK version to download (default: latest); match-host picks
+        /// the SDK for this machine's Windows build, or the newest one below it
         #[arg(long)]
         sdk_version: Option<String>,
 
+        /// Versions picked when not pinned: latest, or stable for the newest
+        /// MSVC / SDK combination tested by msvc-kit's CI
+        #[arg(long, env = "MSVC_KIT_CHANNEL", default_value = "latest")]
+        channel: ReleaseChannel,
+
         /// Target directory for installation
         #[arg(short, long)]
         target: Option<PathBuf>,
 
-        /// Target architecture (x64, x86, arm64)
-        #[arg(short, long, default_value = "x64")]
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        /// (e.g. aarch64-pc-windows-msvc)
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
         arch: String,
 
+        /// Host architecture for cross-compilation (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
         /// Skip MSVC download
         #[arg(long)]
         no_msvc: bool,
@@ -62,23 +106,128 @@ enum Commands {
         #[arg(long)]
         no_sdk: bool,
 
-        /// Skip hash verification
+        /// Skip hash verification (same as --verify-mode off, takes precedence)
         #[arg(long)]
         no_verify: bool,
 
+        /// Hash verification mode: enforce, warn (report mismatches and continue) or off
+        /// Defaults to the configured verify_mode
+        #[arg(long, env = "MSVC_KIT_VERIFY_MODE")]
+        verify_mode: Option<String>,
+
         /// Max parallel downloads
         #[arg(long)]
         parallel_downloads: Option<usize>,
 
+        /// Give up on a component's downloads after this many seconds
+        #[arg(long, value_name = "SECS", env = "MSVC_KIT_OVERALL_TIMEOUT")]
+        timeout: Option<u64>,
+
+        /// Give up on a single file after this many seconds
+        #[arg(long, value_name = "SECS", env = "MSVC_KIT_FILE_TIMEOUT")]
+        file_timeout: Option<u64>,
+
+        /// Limit the total download bandwidth (e.g. 500K, 2M; 0 = unlimited)
+        #[arg(long, value_name = "RATE", env = "MSVC_KIT_MAX_DOWNLOAD_RATE")]
+        limit_rate: Option<String>,
+
+        /// Download payloads of 128 MB and more over this many parallel range
+        /// requests (1 = a single connection per payload)
+        #[arg(long, value_name = "N", env = "MSVC_KIT_CONNECTIONS_PER_FILE")]
+        connections_per_file: Option<usize>,
+
         /// Include optional MSVC components (spectre, mfc, atl, asan, uwp, custom:<pattern>)
         /// Can be specified multiple times
         #[arg(long = "include-component", value_name = "COMPONENT")]
         include_components: Vec<String>,
 
+        /// Download CRT libraries for these app platforms (desktop, store, onecore)
+        /// Can be specified multiple times (default: desktop)
+        #[arg(long = "app-platform", value_name = "PLATFORM")]
+        app_platforms: Vec<String>,
+
+        /// Select components and the SDK version from a Visual Studio .vsconfig file
+        /// IDE-only component ids are ignored with a warning
+        #[arg(long, value_name = "FILE")]
+        vsconfig: Option<PathBuf>,
+
         /// Exclude packages matching pattern (case-insensitive substring match)
         /// Can be specified multiple times
         #[arg(long = "exclude-pattern", value_name = "PATTERN")]
         exclude_patterns: Vec<String>,
+
+        /// Only download packages matching pattern (case-insensitive substring match)
+        /// Can be specified multiple times
+        #[arg(long = "only-pattern", value_name = "PATTERN")]
+        only_patterns: Vec<String>,
+
+        /// Download an --only-pattern selection that leaves out the compiler tools
+        #[arg(long, requires = "only_patterns")]
+        allow_broken_selection: bool,
+
+        /// Only extract files matching a glob pattern (e.g. "**/include/**")
+        /// Can be specified multiple times
+        #[arg(long = "extract-filter", value_name = "GLOB")]
+        extract_filters: Vec<String>,
+
+        /// Offline mode: use cached manifests and payloads only, never the network
+        #[arg(long, env = "MSVC_KIT_OFFLINE")]
+        offline: bool,
+
+        /// Install from a Visual Studio offline layout (vs_buildtools.exe --layout)
+        #[arg(long, value_name = "DIR", env = "MSVC_KIT_LAYOUT_DIR")]
+        layout: Option<PathBuf>,
+
+        /// Delete downloads once extracted: keep, remove-after-extract or <days>d
+        /// (only fully downloaded, hash-verified payloads are removed)
+        #[arg(
+            long,
+            value_name = "POLICY",
+            env = "MSVC_KIT_AUTO_CLEANUP",
+            default_value = "keep"
+        )]
+        auto_cleanup: CleanupPolicy,
+
+        /// Fail if two packages extract the same file with different contents
+        #[arg(long)]
+        strict_conflicts: bool,
+
+        /// Extract payloads matching a glob (e.g. "*crt*", default: all) again,
+        /// even if an earlier run already extracted them
+        #[arg(long, value_name = "GLOB", num_args = 0..=1, default_missing_value = "*")]
+        force_extract: Option<String>,
+
+        /// Leave out optional packages (localized satellites, sources) that fail
+        /// to download instead of aborting; they are listed as warnings
+        #[arg(long, env = "MSVC_KIT_CONTINUE_ON_OPTIONAL_FAILURE")]
+        continue_on_optional_failure: bool,
+
+        /// Also try payloads failing hash verification over plain http
+        /// (the hash is still checked, credentials are not sent)
+        #[arg(long, env = "MSVC_KIT_ALLOW_HTTP_FALLBACK")]
+        allow_http_fallback: bool,
+
+        /// Exit with an error if the install produced warnings (version
+        /// fallbacks, unverified files, conflicts, extraction problems)
+        #[arg(long)]
+        deny_warnings: bool,
+
+        /// Only download headers (MSVC *.Headers packages and SDK header installers)
+        #[arg(long, env = "MSVC_KIT_HEADERS_ONLY")]
+        headers_only: bool,
+
+        /// Accept Microsoft license terms and record it in license-acceptance.json
+        #[arg(long, env = "MSVC_KIT_ACCEPT_LICENSE")]
+        accept_license: bool,
+
+        /// Install a pinned MSVC/SDK combination even if it is known not to work
+        #[arg(long)]
+        allow_incompatible: bool,
+
+        /// Show the packages, download size and estimated installed size
+        /// without downloading anything
+        #[arg(long, env = "MSVC_KIT_DRY_RUN")]
+        dry_run: bool,
     },
 
     /// Setup environment variables for MSVC toolchain
@@ -87,15 +236,22 @@ enum Commands {
         #[arg(short, long)]
         dir: Option<PathBuf>,
 
-        /// Target architecture
-        #[arg(short, long, default_value = "x64")]
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        /// (e.g. aarch64-pc-windows-msvc)
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
         arch: String,
 
+        /// Host architecture of the tools (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
         /// Generate activation script instead of modifying environment
         #[arg(long)]
         script: bool,
 
-        /// Shell type for script (cmd, powershell, bash)
+        /// Shell type for script (cmd, powershell, bash, or wsl for bash with
+        /// `/mnt/c` paths)
         #[arg(long, default_value = "powershell")]
         shell: String,
 
@@ -103,9 +259,62 @@ enum Commands {
         #[arg(long, requires = "script", value_name = "PORTABLE_ROOT")]
         portable_root: Option<String>,
 
+        /// Take MSVC from another directory, e.g. a Visual Studio installation
+        /// (`vs` for the newest one found by vswhere)
+        #[arg(long, value_name = "DIR")]
+        msvc_dir: Option<PathBuf>,
+
+        /// Take the Windows SDK from another directory, e.g. a `Windows Kits\10` kit root
+        /// (`system` for the one installed by the Windows SDK installer)
+        #[arg(long, value_name = "DIR")]
+        sdk_dir: Option<PathBuf>,
+
         /// Write to Windows registry (persistent)
         #[arg(long)]
         persistent: bool,
+
+        /// Create a developer prompt shortcut (Start Menu by default, see `[shortcut]` in config)
+        #[arg(long, conflicts_with_all = ["script", "persistent"])]
+        create_shortcut: bool,
+
+        /// Show what would change (table or json) without changing anything;
+        /// with --persistent, against the user environment in the registry
+        #[arg(
+            long,
+            value_name = "FORMAT",
+            num_args = 0..=1,
+            default_missing_value = "table",
+            conflicts_with_all = ["script", "create_shortcut"]
+        )]
+        plan: Option<String>,
+
+        /// Activate even if another Visual Studio environment is already
+        /// active in this shell, layering msvc-kit's paths on top of it
+        #[arg(long)]
+        force: bool,
+
+        /// Keep the Visual Studio environment already active in this shell;
+        /// with --script, emit its variables instead of msvc-kit's
+        #[arg(
+            long,
+            conflicts_with_all = ["force", "persistent", "create_shortcut", "plan", "portable_root"]
+        )]
+        reuse: bool,
+
+        /// Register the installed Windows SDK (KitsRoot10 under `HKLM\...\Windows Kits\Installed Roots`)
+        /// for tools that look it up there; requires an elevated prompt
+        #[arg(
+            long,
+            conflicts_with_all = ["script", "persistent", "create_shortcut", "plan", "reuse"]
+        )]
+        register_sdk: bool,
+
+        /// Remove the registration written by --register-sdk (requires an elevated prompt)
+        #[arg(
+            long,
+            conflicts_with_all = ["register_sdk", "script", "persistent", "create_shortcut", "plan", "reuse"]
+        )]
+        unregister_sdk: bool,
     },
 
     /// List installed versions
@@ -117,6 +326,10 @@ enum Commands {
         /// Show available versions from Microsoft
         #[arg(long)]
         available: bool,
+
+        /// Show disk usage per component, downloads and cache
+        #[arg(long, conflicts_with = "available")]
+        sizes: bool,
     },
 
     /// Remove installed versions
@@ -140,6 +353,145 @@ enum Commands {
         /// Also remove downloaded cache
         #[arg(long)]
         cache: bool,
+
+        /// Remove orphaned temporary files left by interrupted downloads
+        #[arg(long)]
+        orphans: bool,
+
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Do not ask for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Remove unused architectures and components from an existing install
+    Prune {
+        /// Installation directory
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Target architecture to keep (others are removed)
+        /// Can be specified multiple times
+        #[arg(long = "keep-arch", value_name = "ARCH")]
+        keep_arches: Vec<String>,
+
+        /// Optional component to remove (spectre, mfc, atl, asan, uwp, cli, modules, redist, custom:<pattern>)
+        /// Can be specified multiple times
+        #[arg(long = "remove-component", value_name = "COMPONENT")]
+        remove_components: Vec<String>,
+
+        /// Show what would be removed without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Add optional components (spectre, atl, mfc, ...) to an existing install
+    Add {
+        /// Components to add (spectre, mfc, atl, asan, uwp, cli, modules, redist, custom:<pattern>)
+        #[arg(required = true, value_name = "COMPONENT")]
+        components: Vec<String>,
+
+        /// Installation directory
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
+        arch: String,
+
+        /// Host architecture (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
+        /// MSVC version to extend (default: newest installed)
+        #[arg(long)]
+        msvc_version: Option<String>,
+
+        /// Only use cached downloads
+        #[arg(long, env = "MSVC_KIT_OFFLINE")]
+        offline: bool,
+    },
+
+    /// Reinstall one component of an existing install (e.g. deleted CRT headers)
+    Repair {
+        /// Component to reinstall (crt, tools, atl, mfc, msvc, sdk)
+        #[arg(long)]
+        component: String,
+
+        /// Installation directory
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
+        arch: String,
+
+        /// Host architecture (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
+        /// MSVC version to repair (default: newest installed)
+        #[arg(long)]
+        msvc_version: Option<String>,
+
+        /// Windows SDK version to repair (default: newest installed)
+        #[arg(long)]
+        sdk_version: Option<String>,
+
+        /// Only use cached downloads
+        #[arg(long)]
+        offline: bool,
+    },
+
+    /// Install the toolchains listed in a toolchains.toml, optionally removing unlisted ones
+    Sync {
+        /// Toolchains file
+        #[arg(short, long, default_value = msvc_kit::installer::TOOLCHAINS_FILE)]
+        file: PathBuf,
+
+        /// Installation directory
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Default target architecture for toolchains without `arch`
+        #[arg(short, long, default_value = "x64")]
+        arch: String,
+
+        /// Host architecture (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
+        /// Remove installed versions no toolchain refers to
+        #[arg(long)]
+        prune: bool,
+
+        /// Show what would be installed and removed without changing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format (text, json)
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Only use cached downloads
+        #[arg(long, env = "MSVC_KIT_OFFLINE")]
+        offline: bool,
+    },
+
+    /// Move an existing installation to another directory
+    Move {
+        /// New installation directory (must not exist or be empty)
+        new_dir: PathBuf,
+
+        /// Installation directory to move (default: configured install_dir)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
     },
 
     /// Show current configuration
@@ -156,6 +508,10 @@ enum Commands {
         #[arg(long)]
         set_sdk: Option<String>,
 
+        /// Accept Microsoft license terms for future non-interactive runs
+        #[arg(long)]
+        accept_license: bool,
+
         /// Reset configuration to defaults
         #[arg(long)]
         reset: bool,
@@ -167,7 +523,7 @@ enum Commands {
         #[arg(short, long)]
         dir: Option<PathBuf>,
 
-        /// Output format (shell, json)
+        /// Output format (shell, json, clang-args, clang-cl-args, dotenv, direnv)
         #[arg(short, long, default_value = "shell")]
         format: String,
     },
@@ -178,8 +534,9 @@ enum Commands {
         #[arg(short, long)]
         dir: Option<PathBuf>,
 
-        /// Target architecture (x64, x86, arm64)
-        #[arg(short, long, default_value = "x64")]
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        /// (e.g. aarch64-pc-windows-msvc)
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
         arch: String,
 
         /// Component to query (all, msvc, sdk)
@@ -201,6 +558,28 @@ enum Commands {
         /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         format: String,
+
+        /// Resolve a header through the include paths (e.g. windows.h)
+        #[arg(long, value_name = "HEADER")]
+        find_header: Option<String>,
+
+        /// Resolve a library through the lib paths (e.g. kernel32.lib)
+        #[arg(long, value_name = "LIBRARY")]
+        find_library: Option<String>,
+
+        /// List every installed version, not just the selected one
+        #[arg(long)]
+        all_versions: bool,
+
+        /// Take MSVC from another directory, e.g. a Visual Studio installation
+        /// (`vs` for the newest one found by vswhere)
+        #[arg(long, value_name = "DIR")]
+        msvc_dir: Option<PathBuf>,
+
+        /// Take the Windows SDK from another directory, e.g. a `Windows Kits\10` kit root
+        /// (`system` for the one installed by the Windows SDK installer)
+        #[arg(long, value_name = "DIR")]
+        sdk_dir: Option<PathBuf>,
     },
 
     /// Create a portable bundle with MSVC toolchain (downloads components locally)
@@ -209,9 +588,16 @@ enum Commands {
         #[arg(short, long, default_value = "./msvc-bundle")]
         output: PathBuf,
 
-        /// Target architecture (x64, x86, arm64)
-        #[arg(short, long, default_value = "x64")]
-        arch: String,
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        /// (e.g. aarch64-pc-windows-msvc)
+        /// Can be specified multiple times; downloads then run concurrently
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
+        arch: Vec<String>,
+
+        /// With several --arch, create one bundle per architecture in <output>/<arch>
+        /// instead of a single multi-arch bundle
+        #[arg(long)]
+        split: bool,
 
         /// Host architecture for cross-compilation (x64, x86, arm64)
         /// Defaults to current system architecture
@@ -226,13 +612,198 @@ enum Commands {
         #[arg(long)]
         sdk_version: Option<String>,
 
-        /// Accept Microsoft license terms (required)
-        #[arg(long)]
+        /// Accept Microsoft license terms (required unless accepted in config)
+        #[arg(long, env = "MSVC_KIT_ACCEPT_LICENSE")]
         accept_license: bool,
 
         /// Create a zip archive of the bundle
         #[arg(long)]
         zip: bool,
+
+        /// Language of the bundle README and script messages (en, zh-CN)
+        #[arg(long, default_value = "en", env = "MSVC_KIT_LOCALE")]
+        locale: String,
+
+        /// Offline mode: use cached manifests and payloads only, never the network
+        #[arg(long, env = "MSVC_KIT_OFFLINE")]
+        offline: bool,
+
+        /// Show versions, sizes and layout without downloading anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Directory naming convention (native, msvc-wine)
+        /// msvc-wine uses lowercase vc/tools/msvc and kits/10 and adds its
+        /// bin/<arch> wrapper scripts
+        #[arg(long, default_value = "native", env = "MSVC_KIT_LAYOUT_STYLE")]
+        layout_style: String,
+
+        /// Extract payloads matching a glob (e.g. "*crt*", default: all) again,
+        /// even if an earlier run already extracted them into the output directory
+        #[arg(long, value_name = "GLOB", num_args = 0..=1, default_missing_value = "*")]
+        force_extract: Option<String>,
+    },
+
+    /// Generate a Windows Sandbox config or Dockerfile that mounts a bundle
+    Provision {
+        /// Bundle directory to mount
+        #[arg(short, long, default_value = "./msvc-bundle")]
+        bundle: PathBuf,
+
+        /// Write a Windows Sandbox configuration to this file (e.g. msvc.wsb)
+        #[arg(long, value_name = "FILE", required_unless_present = "dockerfile")]
+        wsb: Option<PathBuf>,
+
+        /// Write a Dockerfile for a Windows container to this file
+        #[arg(long, value_name = "FILE")]
+        dockerfile: Option<PathBuf>,
+
+        /// Directory the bundle is mounted at inside the sandbox or container
+        #[arg(long, default_value = msvc_kit::bundle::provision::DEFAULT_MOUNT_DIR)]
+        mount_dir: String,
+
+        /// Working directory inside the sandbox or container
+        #[arg(long, default_value = msvc_kit::bundle::provision::DEFAULT_WORK_DIR)]
+        work_dir: String,
+
+        /// Host directory mapped read-write to the working directory (Windows Sandbox only)
+        #[arg(long)]
+        workspace: Option<PathBuf>,
+
+        /// Base image of the Dockerfile
+        #[arg(long, default_value = msvc_kit::bundle::provision::DEFAULT_BASE_IMAGE)]
+        base_image: String,
+    },
+
+    /// Manage named environment profiles for switching between toolsets
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
+    /// Deploy the VC++ runtime DLLs with an application
+    Redist {
+        #[command(subcommand)]
+        action: RedistAction,
+    },
+
+    /// Compare two installed MSVC (or SDK) versions: headers, libraries, tools and size
+    Diff {
+        /// Older version (e.g. 14.43, or an SDK build number such as 22621)
+        from: String,
+
+        /// Newer version
+        to: String,
+
+        /// Installation directory
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Component to compare (all = MSVC if both versions are installed, else SDK)
+        #[arg(short, long, default_value = "all")]
+        component: String,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        format: String,
+    },
+
+    /// Export a software bill of materials (SBOM) of an installation
+    Sbom {
+        /// Installation directory
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Document format (cyclonedx, spdx)
+        #[arg(short, long, default_value = "cyclonedx")]
+        format: String,
+
+        /// Write the document to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Check prerequisites and write the initial configuration
+    Init {
+        /// Installation directory (default: from config)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Cache directory for downloaded packages (default: from config)
+        #[arg(long)]
+        cache_dir: Option<PathBuf>,
+
+        /// Skip the Visual Studio CDN connectivity check
+        #[arg(long, env = "MSVC_KIT_OFFLINE")]
+        offline: bool,
+    },
+
+    /// Download the newest toolchain into the download cache without installing
+    Prefetch {
+        /// Prefetch the newest MSVC and SDK, ignoring the configured default versions
+        #[arg(long, conflicts_with_all = ["msvc_version", "sdk_version"])]
+        latest: bool,
+
+        /// MSVC version to prefetch (default: configured default, else latest)
+        #[arg(long)]
+        msvc_version: Option<String>,
+
+        /// Windows SDK version to prefetch (default: configured default, else latest)
+        #[arg(long)]
+        sdk_version: Option<String>,
+
+        /// Installation directory whose download cache is primed (default: from config)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
+        arch: String,
+
+        /// Host architecture (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
+        /// Skip MSVC
+        #[arg(long)]
+        no_msvc: bool,
+
+        /// Skip the Windows SDK
+        #[arg(long)]
+        no_sdk: bool,
+
+        /// Total download bandwidth (e.g. 500K, 2M; 0 = unlimited)
+        #[arg(
+            long,
+            value_name = "RATE",
+            default_value = "2M",
+            env = "MSVC_KIT_MAX_DOWNLOAD_RATE"
+        )]
+        limit_rate: String,
+
+        /// Max parallel downloads
+        #[arg(long, default_value_t = 2)]
+        parallel_downloads: usize,
+    },
+
+    /// Collect diagnostics for bug reports
+    Diagnostics {
+        #[command(subcommand)]
+        action: DiagnosticsAction,
+    },
+
+    #[cfg(feature = "serve")]
+    /// Serve install/query/status requests over JSON-RPC
+    Serve {
+        /// Endpoint to listen on: tcp://<addr>:<port> or pipe://<name> (Windows)
+        /// (default: pipe://msvc-kit on Windows, tcp://127.0.0.1:7421 elsewhere)
+        #[arg(short, long)]
+        listen: Option<String>,
+
+        /// Installation directory shared by all requests (default: from config)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
     },
 
     #[cfg(feature = "self-update")]
@@ -242,9 +813,109 @@ enum Commands {
         #[arg(long)]
         check: bool,
 
-        /// Update to a specific version
+        /// Update to a specific version
+        #[arg(long)]
+        version: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileAction {
+    /// Save the environment of an installed toolset as a profile
+    Save {
+        /// Profile name (default: <msvc major.minor>-<arch>, e.g. 14.44-x64)
+        name: Option<String>,
+
+        /// Installation directory (default: from config)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Target architecture (x64, x86, arm64) or Rust target triple
+        /// (e.g. aarch64-pc-windows-msvc)
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
+        arch: String,
+
+        /// Host architecture of the tools (x64, x86, arm64)
+        /// Defaults to native tools if available, else emulated ones (x64 on ARM64)
+        #[arg(long)]
+        host_arch: Option<String>,
+
+        /// MSVC version to use (prefix, e.g. 14.44; default: latest installed)
+        #[arg(long)]
+        msvc_version: Option<String>,
+
+        /// Windows SDK version to use (default: latest installed)
+        #[arg(long)]
+        sdk_version: Option<String>,
+    },
+
+    /// List saved profiles
+    List,
+
+    /// Print shell commands activating a profile, or run a command with it
+    Activate {
+        /// Profile name
+        name: String,
+
+        /// Shell to print commands for (cmd, powershell, bash; default: detected)
+        #[arg(long)]
+        shell: Option<String>,
+
+        /// Command to run with the profile applied instead of printing
+        #[arg(last = true)]
+        command: Vec<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum RedistAction {
+    /// Copy the runtime DLLs (vcruntime140, msvcp140, ...) into an application directory
+    Copy {
+        /// Application directory to copy the DLLs into
+        #[arg(long)]
+        dest: PathBuf,
+
+        /// Architecture of the application (x64, x86, arm64) or Rust target triple
+        #[arg(short, long, visible_alias = "target-triple", default_value = "x64")]
+        arch: String,
+
+        /// Built executables or DLLs; only the runtime DLLs they import are copied
+        #[arg(long, value_name = "FILE")]
+        exe: Vec<PathBuf>,
+
+        /// Copy the debug runtime (vcruntime140d.dll, ...; not redistributable)
+        #[arg(long)]
+        debug: bool,
+
+        /// Without --exe, also copy concrt140 and vccorlib140
+        #[arg(long)]
+        all: bool,
+
+        /// Show what would be copied without copying
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Installation directory (default: from config)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DiagnosticsAction {
+    /// Zip the configuration, download indexes, quarantine metadata and logs
+    Collect {
+        /// Installation directory (default: from config)
+        #[arg(short, long)]
+        dir: Option<PathBuf>,
+
+        /// Archive to write
+        #[arg(short, long, default_value = msvc_kit::diagnostics::DEFAULT_BUNDLE_NAME)]
+        output: PathBuf,
+
+        /// Also include the quarantined payload files (may be large)
         #[arg(long)]
-        version: Option<String>,
+        include_payloads: bool,
     },
 }
 
@@ -264,9 +935,6 @@ async fn main() -> anyhow::Result<()> {
         .with(filter)
         .init();
 
-    // Load configuration
-    let mut config = load_config().unwrap_or_default();
-
     // Handle the case where no subcommand is provided (for winget compatibility)
     let command = match cli.command {
         Some(cmd) => cmd,
@@ -277,24 +945,77 @@ async fn main() -> anyhow::Result<()> {
         }
     };
 
+    // Load configuration (defaults are only used when there is no config file);
+    // `config --reset` is the way out of a broken one, so it never needs it
+    let resetting = matches!(command, Commands::Config { reset: true, .. });
+    let mut config = match load_config() {
+        Ok(config) => config,
+        Err(e) if resetting => {
+            eprintln!("⚠️  Warning: ignoring invalid configuration: {}", e);
+            MsvcKitConfig::default()
+        }
+        Err(e) => return Err(e.into()),
+    };
+    if let Some(path) = config.tls_ca_file.as_ref().filter(|_| !resetting) {
+        if std::env::var_os("MSVC_KIT_TLS_CA_FILE").is_none() {
+            msvc_kit::downloader::set_default_root_certificates(path)?;
+        }
+    }
+
     match command {
         Commands::Download {
             msvc_version,
             sdk_version,
+            channel,
             target,
             arch,
+            host_arch,
             no_msvc,
             no_sdk,
             no_verify,
+            verify_mode,
             parallel_downloads,
+            timeout,
+            file_timeout,
+            limit_rate,
+            connections_per_file,
             include_components,
+            app_platforms,
+            vsconfig,
             exclude_patterns,
+            only_patterns,
+            allow_broken_selection,
+            extract_filters,
+            offline,
+            layout,
+            auto_cleanup,
+            strict_conflicts,
+            force_extract,
+            continue_on_optional_failure,
+            allow_http_fallback,
+            deny_warnings,
+            headers_only,
+            accept_license,
+            allow_incompatible,
+            dry_run,
         } => {
             let target_dir = target.unwrap_or_else(|| config.install_dir.clone());
             let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let host_arch: Option<Architecture> = host_arch
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?;
+
+            let verify_mode = if no_verify {
+                VerifyMode::Off
+            } else {
+                verify_mode
+                    .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                    .transpose()?
+                    .unwrap_or(config.verify_mode)
+            };
 
             // Parse component strings into MsvcComponent enum values
-            let components = include_components
+            let mut components: HashSet<MsvcComponent> = include_components
                 .iter()
                 .filter_map(|s| {
                     s.parse::<MsvcComponent>()
@@ -303,49 +1024,233 @@ async fn main() -> anyhow::Result<()> {
                 })
                 .collect();
 
-            let options = DownloadOptions {
+            let app_platforms: HashSet<AppPlatform> = if app_platforms.is_empty() {
+                AppPlatform::defaults()
+            } else {
+                app_platforms
+                    .iter()
+                    .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                    .collect::<anyhow::Result<_>>()?
+            };
+
+            let mut sdk_version = sdk_version;
+            if let Some(path) = vsconfig {
+                let import = import_vsconfig(&path)?;
+                for id in &import.ignored {
+                    eprintln!("⚠️  Warning: ignoring {} from {}", id, path.display());
+                }
+                components.extend(import.components);
+                if sdk_version.is_none() {
+                    sdk_version = import.sdk_version;
+                }
+                for other in import.architectures.iter().filter(|a| **a != arch) {
+                    println!(
+                        "ℹ️  {} also lists {} tools; download them with --arch {}",
+                        path.display(),
+                        other,
+                        other
+                    );
+                }
+            }
+
+            let options = DownloadOptionsBuilder::from(DownloadOptions {
                 msvc_version,
                 sdk_version,
+                channel,
                 target_dir: target_dir.clone(),
                 arch,
-                host_arch: Some(Architecture::host()),
-                verify_hashes: !no_verify,
+                host_arch,
+                verify_mode,
+                hash_exemptions: config.hash_exemptions.clone(),
                 parallel_downloads: parallel_downloads.unwrap_or(config.parallel_downloads),
                 http_client: None,
+                user_agent: None,
                 progress_handler: None,
                 cache_manager: None,
+                auth_provider: None,
+                version_resolver: None,
                 dry_run: false,
+                offline,
+                layout_dir: layout,
+                headers_only,
                 include_components: components,
+                app_platforms,
                 exclude_patterns,
-            };
+                only_patterns,
+                allow_broken_selection,
+                sdk_installer_patterns: Vec::new(),
+                extract_filters,
+                auto_cleanup,
+                post_install_hooks: Vec::new(),
+                overall_timeout: timeout.map(Duration::from_secs),
+                per_file_timeout: file_timeout.map(Duration::from_secs),
+                max_download_rate: parse_limit_rate(limit_rate.as_deref())?,
+                chunked_downloads: connections_per_file
+                    .filter(|connections| *connections > 1)
+                    .map(|connections| ChunkedDownloads {
+                        connections,
+                        ..Default::default()
+                    }),
+                alternate_endpoints: config.alternate_endpoints.clone(),
+                continue_on_optional_failure,
+                allow_http_fallback,
+            })
+            .try_build()?;
 
             println!("📦 msvc-kit - Downloading MSVC Build Tools\n");
             println!("Target directory: {}", target_dir.display());
-            println!("Architecture: {}", arch);
+            println!("Target architecture: {}", arch);
+            match host_arch {
+                Some(host_arch) => println!("Host architecture: {}", host_arch),
+                None => println!(
+                    "Host architecture: auto (native: {})",
+                    Architecture::native_host()
+                ),
+            }
             println!();
 
+            if dry_run {
+                let size = |bytes: u64| humansize::format_size(bytes, humansize::BINARY);
+                let mut previews = Vec::new();
+                if !no_msvc {
+                    previews.push(MsvcDownloader::new(options.clone()).preview().await?);
+                }
+                if !no_sdk {
+                    previews.push(SdkDownloader::new(options.clone()).preview().await?);
+                }
+                for preview in &previews {
+                    println!("{}", preview.format());
+                    if let Some(host) = &preview.host_arch {
+                        println!("  Host tools: {}", host);
+                    }
+                    for pkg in &preview.packages {
+                        println!(
+                            "  - {} v{} ({} files, {})",
+                            pkg.id,
+                            pkg.version,
+                            pkg.file_count,
+                            size(pkg.size)
+                        );
+                    }
+                }
+                println!(
+                    "\nDownload: {}, estimated installed size: ~{}",
+                    size(previews.iter().map(|p| p.total_size).sum()),
+                    size(previews.iter().map(|p| p.installed_size).sum())
+                );
+                println!("Dry run: nothing was downloaded.");
+                return Ok(());
+            }
+
+            if let Some(pattern) = &force_extract {
+                print_invalidated(&target_dir, pattern).await?;
+            }
+
+            // Interrupted downloads continue where they stopped
+            for summary in msvc_kit::downloader::resume_summary(&target_dir).await? {
+                let skipped = if summary.component == "MSVC" {
+                    no_msvc
+                } else {
+                    no_sdk
+                };
+                if !skipped {
+                    println!("⏯️  {}: {}", summary.component, summary);
+                }
+            }
+
+            let mut installed_msvc = None;
+            let mut installed_sdk = None;
+            let mut warnings = Vec::new();
+
+            // A pinned SDK is checked against the MSVC version as soon as both are known
+            // match-host is only resolved against the manifest while downloading
+            let pinned_sdk = options
+                .sdk_version
+                .clone()
+                .filter(|v| !no_sdk && !is_match_host(v));
+            let mut compat_checked = false;
+            if let (Some(msvc), Some(sdk)) = (&options.msvc_version, &pinned_sdk) {
+                if !no_msvc {
+                    check_version_compat(msvc, sdk, allow_incompatible)?;
+                    compat_checked = true;
+                }
+            }
+
             if !no_msvc {
                 println!("⬇️  Downloading MSVC compiler...");
                 let mut msvc_info = download_msvc(&options).await?;
-                println!("📁 Extracting MSVC packages...");
-                msvc_kit::extract_and_finalize_msvc(&mut msvc_info).await?;
+                if msvc_info.reused {
+                    println!("♻️  Already installed, reusing the existing files");
+                } else {
+                    println!("📁 Extracting MSVC packages...");
+                }
+                let report = msvc_kit::extract_and_finalize_msvc_with_report(
+                    &mut msvc_info,
+                    strict_conflicts,
+                )
+                .await?;
+                print_install_report(&report);
                 println!(
                     "✅ MSVC {} installed to {}",
                     msvc_info.version,
                     target_dir.display()
                 );
+                warnings.extend(msvc_info.warnings);
+                installed_msvc = Some(msvc_info.version);
+            }
+
+            if let (false, Some(msvc), Some(sdk)) = (compat_checked, &installed_msvc, &pinned_sdk) {
+                check_version_compat(msvc, sdk, allow_incompatible)?;
             }
 
             if !no_sdk {
                 println!("\n⬇️  Downloading Windows SDK...");
                 let sdk_info = download_sdk(&options).await?;
-                println!("📁 Extracting SDK packages...");
-                msvc_kit::extract_and_finalize_sdk(&sdk_info).await?;
+                if sdk_info.reused {
+                    println!("♻️  Already installed, reusing the existing files");
+                } else {
+                    println!("📁 Extracting SDK packages...");
+                }
+                let report =
+                    msvc_kit::extract_and_finalize_sdk_with_report(&sdk_info, strict_conflicts)
+                        .await?;
+                print_install_report(&report);
                 println!(
                     "✅ Windows SDK {} installed to {}",
                     sdk_info.version,
                     target_dir.display()
                 );
+                warnings.extend(sdk_info.warnings);
+                warnings.extend(report.to_warnings());
+                installed_sdk = Some(sdk_info.version);
+            }
+
+            if accept_license || is_license_accepted(&config) {
+                accept_license_terms(&target_dir)?;
+            }
+
+            let host_arch = host_arch.unwrap_or_else(|| match &installed_msvc {
+                Some(version) => {
+                    detect_host_arch(&target_dir.join("VC/Tools/MSVC").join(version), arch).arch
+                }
+                None => Architecture::native_host(),
+            });
+            run_post_install_scripts(
+                &config,
+                HookScriptContext {
+                    event: "install".to_string(),
+                    install_dir: target_dir.clone(),
+                    msvc_version: installed_msvc,
+                    sdk_version: installed_sdk,
+                    arch,
+                    host_arch,
+                },
+            )
+            .await?;
+
+            print_warnings(&warnings);
+            if deny_warnings && !warnings.is_empty() {
+                anyhow::bail!("{} warning(s) (--deny-warnings)", warnings.len());
             }
 
             println!("\n🎉 Download complete!");
@@ -359,17 +1264,97 @@ async fn main() -> anyhow::Result<()> {
         Commands::Setup {
             dir,
             arch,
+            host_arch,
             script,
             shell,
             portable_root,
             persistent,
+            create_shortcut,
+            plan,
+            force,
+            reuse,
+            msvc_dir,
+            sdk_dir,
+            register_sdk,
+            unregister_sdk,
         } => {
+            let shell_type = match shell.to_lowercase().as_str() {
+                "cmd" | "bat" => ShellType::Cmd,
+                "powershell" | "ps1" | "pwsh" => ShellType::PowerShell,
+                "bash" | "sh" | "wsl" => ShellType::Bash,
+                _ => ShellType::detect(),
+            };
+            let unix_flavor = if shell.eq_ignore_ascii_case("wsl") {
+                UnixFlavor::Wsl
+            } else {
+                UnixFlavor::Msys
+            };
+            let active = detect_active_environment();
+
+            if reuse {
+                let Some(active) = active else {
+                    anyhow::bail!("No activated Visual Studio environment found to reuse.");
+                };
+                if script {
+                    print!("{}", profile_commands(&active.changes(), shell_type));
+                } else {
+                    println!("✅ Reusing the active {}", active);
+                }
+                return Ok(());
+            }
+
             let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
             let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let host_arch: Option<Architecture> = host_arch
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?;
+
+            // MSVC and SDK may come from other installations (hybrid setup)
+            let roots = QueryOptions {
+                install_dir: install_dir.clone(),
+                msvc_dir: resolve_component_dir(msvc_dir)?,
+                sdk_dir: resolve_component_dir(sdk_dir)?,
+                ..Default::default()
+            };
+            let (msvc_root, sdk_root) = (roots.msvc_root(), roots.sdk_root());
+            let hybrid = msvc_root != install_dir || sdk_root != install_dir;
+            if hybrid && portable_root.is_some() {
+                anyhow::bail!("--portable-root cannot be combined with --msvc-dir or --sdk-dir.");
+            }
 
             // Find installed versions
-            let msvc_versions = list_installed_msvc(&install_dir);
-            let sdk_versions = list_installed_sdk(&install_dir);
+            let msvc_versions = list_installed_msvc(&msvc_root);
+            let sdk_versions = list_installed_sdk(&sdk_root);
+
+            if register_sdk || unregister_sdk {
+                let kits_root = sdk_root.join("Windows Kits").join("10");
+                let versions: Vec<String> =
+                    sdk_versions.iter().map(|v| v.version.clone()).collect();
+                if unregister_sdk {
+                    if msvc_kit::env::unregister_sdk(&kits_root, &versions)? {
+                        println!("✅ Unregistered Windows SDK at {}", kits_root.display());
+                    } else {
+                        println!(
+                            "ℹ️  {} is not the registered Windows SDK root.",
+                            kits_root.display()
+                        );
+                    }
+                } else {
+                    if versions.is_empty() {
+                        anyhow::bail!(
+                            "No Windows SDK installation found in {}. Run 'msvc-kit download' first.",
+                            sdk_root.display()
+                        );
+                    }
+                    msvc_kit::env::register_sdk(&kits_root, &versions)?;
+                    println!(
+                        "✅ Registered Windows SDK {} at {}",
+                        versions.join(", "),
+                        kits_root.display()
+                    );
+                }
+                return Ok(());
+            }
 
             if msvc_versions.is_empty() {
                 anyhow::bail!("No MSVC installation found. Run 'msvc-kit download' first.");
@@ -385,6 +1370,11 @@ async fn main() -> anyhow::Result<()> {
                 install_path: msvc_version.install_path.clone().unwrap(),
                 downloaded_files: vec![],
                 arch,
+                extract_filters: vec![],
+                cleanup: Default::default(),
+                resolution: None,
+                warnings: Vec::new(),
+                reused: false,
             };
 
             let sdk_info = sdk_version.map(|v| msvc_kit::installer::InstallInfo {
@@ -393,18 +1383,48 @@ async fn main() -> anyhow::Result<()> {
                 install_path: v.install_path.clone().unwrap(),
                 downloaded_files: vec![],
                 arch,
+                extract_filters: vec![],
+                cleanup: Default::default(),
+                resolution: None,
+                warnings: Vec::new(),
+                reused: false,
             });
 
-            let env = setup_environment(&msvc_info, sdk_info.as_ref())?;
+            let host_arch =
+                host_arch.unwrap_or_else(|| detect_host_arch(&msvc_info.install_path, arch).arch);
+            let env = setup_environment_with_arch(&msvc_info, sdk_info.as_ref(), host_arch, arch)?;
+
+            // Only the current shell mixes toolchains; registry, shortcut and
+            // plan output are not affected by what is active here
+            let activates_shell = plan.is_none() && !persistent && !create_shortcut;
+            if let Some(active) = active.filter(|a| activates_shell && !a.matches(&env)) {
+                if !force {
+                    anyhow::bail!(
+                        "A {} is already active in this shell.\n\
+                         Activating msvc-kit on top of it mixes both toolchains.\n\
+                         Use --reuse to keep it, --force to layer msvc-kit over it, \
+                         or start a clean shell.",
+                        active
+                    );
+                }
+                eprintln!("⚠️  Warning: layering msvc-kit over the active {}", active);
+            }
 
-            if script {
-                let shell_type = match shell.to_lowercase().as_str() {
-                    "cmd" | "bat" => ShellType::Cmd,
-                    "powershell" | "ps1" | "pwsh" => ShellType::PowerShell,
-                    "bash" | "sh" => ShellType::Bash,
-                    _ => ShellType::detect(),
+            if let Some(format) = plan {
+                let plan = if persistent {
+                    EnvPlan::for_registry(&env)?
+                } else {
+                    EnvPlan::for_process(&env)
                 };
-
+                match format.as_str() {
+                    "json" => println!("{}", serde_json::to_string_pretty(&plan)?),
+                    "table" => print!("{}", plan),
+                    other => anyhow::bail!("Unknown plan format '{}' (table, json)", other),
+                }
+            } else if script && hybrid {
+                // Script contexts assume a single root; emit the resolved variables
+                print!("{}", profile_commands(&get_env_changes(&env), shell_type));
+            } else if script {
                 // Create script context based on whether portable root is specified
                 let ctx = if let Some(ref _portable_root) = portable_root {
                     // Use portable mode with relative paths
@@ -412,21 +1432,47 @@ async fn main() -> anyhow::Result<()> {
                         &env.vc_tools_version,
                         &env.windows_sdk_version,
                         arch,
-                        arch,
+                        host_arch,
                     )
                 } else {
                     // Use absolute mode with actual paths
-                    ScriptContext::absolute(
-                        install_dir.clone(),
-                        &env.vc_tools_version,
-                        &env.windows_sdk_version,
-                        arch,
-                        arch,
-                    )
+                    ScriptContext {
+                        host_arch,
+                        ..install_script_context(&install_dir, arch)?
+                    }
                 };
+                let ctx = ctx.with_unix_flavor(unix_flavor);
 
                 let script_content = generate_script(&ctx, shell_type)?;
                 println!("{}", script_content);
+            } else if create_shortcut {
+                // The shortcut runs a cmd activation script kept in the install directory
+                let content = if hybrid {
+                    format!(
+                        "@echo off\n{}",
+                        profile_commands(&get_env_changes(&env), ShellType::Cmd)
+                    )
+                } else {
+                    let ctx = ScriptContext {
+                        host_arch,
+                        ..install_script_context(&install_dir, arch)?
+                    };
+                    generate_script(&ctx, ShellType::Cmd)?
+                };
+                let script_path = install_dir.join(format!("prompt-{}_{}.bat", host_arch, arch));
+                tokio::fs::write(&script_path, content).await?;
+
+                let name = prompt_shortcut_name(host_arch, arch);
+                let dirs = config.shortcut.dirs();
+                if dirs.is_empty() {
+                    anyhow::bail!(
+                        "Could not determine the shortcut directory; set [shortcut] dir in config."
+                    );
+                }
+                for dir in dirs {
+                    let link = create_prompt_shortcut(&dir, &name, &script_path)?;
+                    println!("✅ Created shortcut: {}", link.display());
+                }
             } else if persistent {
                 #[cfg(windows)]
                 {
@@ -466,23 +1512,67 @@ async fn main() -> anyhow::Result<()> {
 
                 println!("\nFor persistent setup (Windows only):");
                 println!("  msvc-kit setup --persistent");
+                println!("\nFor a developer prompt shortcut (Windows only):");
+                println!("  msvc-kit setup --create-shortcut");
             }
         }
 
-        Commands::List { dir, available } => {
+        Commands::List {
+            dir,
+            available,
+            sizes,
+        } => {
             let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
 
             if available {
                 println!("📋 Fetching available versions from Microsoft...\n");
 
-                let manifest = msvc_kit::downloader::VsManifest::fetch().await?;
+                let versions = msvc_kit::list_available_versions().await?;
+                let channel = &versions.channel;
 
-                if let Some(msvc) = manifest.get_latest_msvc_version() {
+                if let Some(ref vs) = channel.product_display_version {
+                    match channel.build_version {
+                        Some(ref build) => println!("Visual Studio: {} (build {})", vs, build),
+                        None => println!("Visual Studio: {}", vs),
+                    }
+                }
+                if let Some(date) = channel.release_date {
+                    println!("Channel released: {}", date.format("%Y-%m-%d"));
+                }
+                if let Some(ref msvc) = versions.latest_msvc {
                     println!("Latest MSVC version: {}", msvc);
                 }
-                if let Some(sdk) = manifest.get_latest_sdk_version() {
+                if let Some(ref sdk) = versions.latest_sdk {
                     println!("Latest Windows SDK version: {}", sdk);
                 }
+            } else if sizes {
+                // Manifests are cached in `<cache_dir>/manifests`, like the download cache manager does
+                let manifest_cache = config
+                    .cache_dir
+                    .as_ref()
+                    .map(|dir| dir.join("manifests"))
+                    .unwrap_or_else(msvc_kit::downloader::cache::default_manifest_cache_dir);
+                let usage =
+                    msvc_kit::query::disk_usage_with_cache_dir(&install_dir, &manifest_cache)?;
+                let size = |bytes: u64| humansize::format_size(bytes, humansize::BINARY);
+
+                println!("💾 Disk usage in {}\n", install_dir.display());
+                if !usage.msvc.is_empty() {
+                    println!("MSVC Compiler: {}", size(usage.msvc_total()));
+                    for v in &usage.msvc {
+                        println!("  - {:<20} {:>12}", v.version, size(v.bytes));
+                    }
+                }
+                if !usage.sdk.is_empty() {
+                    println!("Windows SDK: {}", size(usage.sdk_total()));
+                    for v in &usage.sdk {
+                        println!("  - {:<20} {:>12}", v.version, size(v.bytes));
+                    }
+                }
+                println!("{:<16}{}", "Downloads:", size(usage.downloads));
+                println!("{:<16}{}", "Manifest cache:", size(usage.cache));
+                println!("{:<16}{}", "Other:", size(usage.other));
+                println!("\nTotal: {}", size(usage.total()));
             } else {
                 println!("📋 Installed versions in {}\n", install_dir.display());
 
@@ -516,62 +1606,353 @@ async fn main() -> anyhow::Result<()> {
             sdk_version,
             all,
             cache,
+            orphans,
+            dry_run,
+            yes,
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let options = CleanOptions {
+                all,
+                msvc_version,
+                sdk_version,
+                cache,
+                orphans,
+                dry_run: true,
+            };
+
+            // Collect first, so the list can be shown and confirmed
+            let plan = clean(&install_dir, &options).await?;
+            for missing in &plan.not_found {
+                println!("⚠️  {} not found", missing);
+            }
+            if !orphans && !all {
+                let found = find_orphans(&install_dir);
+                if !found.is_empty() {
+                    println!(
+                        "ℹ️  {} orphaned temporary file(s) found; add --orphans to remove them",
+                        found.len()
+                    );
+                }
+            }
+            if plan.removed.is_empty() {
+                println!("Nothing to remove.");
+                return Ok(());
+            }
+
+            let size = humansize::format_size(plan.bytes_freed, humansize::BINARY);
+            for path in &plan.removed {
+                println!(
+                    "  {} {}",
+                    if dry_run { "would remove" } else { "remove" },
+                    path.display()
+                );
+            }
+            if dry_run {
+                println!(
+                    "\n🔍 Dry run: {} path(s), {} would be freed",
+                    plan.removed.len(),
+                    size
+                );
+                return Ok(());
+            }
+
+            if !yes
+                && !confirm(&format!(
+                    "Remove {} path(s) ({})?",
+                    plan.removed.len(),
+                    size
+                ))?
+            {
+                println!("Aborted.");
+                return Ok(());
+            }
+
+            let report = clean(
+                &install_dir,
+                &CleanOptions {
+                    dry_run: false,
+                    ..options
+                },
+            )
+            .await?;
+            println!(
+                "✅ Removed {} path(s), freed {}",
+                report.removed.len(),
+                humansize::format_size(report.bytes_freed, humansize::BINARY)
+            );
+        }
+
+        Commands::Prune {
+            dir,
+            keep_arches,
+            remove_components,
+            dry_run,
         } => {
             let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let keep_arches = keep_arches
+                .iter()
+                .map(|a| a.parse::<Architecture>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            let remove_components = remove_components
+                .iter()
+                .map(|c| c.parse::<MsvcComponent>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+
+            if keep_arches.is_empty() && remove_components.is_empty() {
+                anyhow::bail!("Nothing to prune: pass --keep-arch and/or --remove-component");
+            }
 
-            if all {
-                println!("🗑️  Removing all installed versions...");
+            let options = msvc_kit::installer::PruneOptions {
+                keep_arches,
+                remove_components,
+                dry_run,
+                ..Default::default()
+            };
+            let report = msvc_kit::installer::prune(&install_dir, &options).await?;
+            let freed = humansize::format_size(report.bytes_freed, humansize::BINARY);
 
-                if install_dir.exists() {
-                    tokio::fs::remove_dir_all(&install_dir).await?;
-                    println!("✅ Removed {}", install_dir.display());
+            if dry_run {
+                for path in &report.removed {
+                    println!("  would remove {}", path.display());
                 }
+                println!(
+                    "\n🔍 Dry run: {} file(s), {} would be freed",
+                    report.removed.len(),
+                    freed
+                );
             } else {
-                if let Some(version) = msvc_version {
-                    let msvc_path = install_dir
-                        .join("VC")
-                        .join("Tools")
-                        .join("MSVC")
-                        .join(&version);
-                    if msvc_path.exists() {
-                        tokio::fs::remove_dir_all(&msvc_path).await?;
-                        println!("✅ Removed MSVC {}", version);
-                    } else {
-                        println!("⚠️  MSVC {} not found", version);
-                    }
+                println!(
+                    "✅ Removed {} file(s), freed {} ({} receipt(s) updated)",
+                    report.removed.len(),
+                    freed,
+                    report.receipts_updated
+                );
+            }
+        }
+
+        Commands::Add {
+            components,
+            dir,
+            arch,
+            host_arch,
+            msvc_version,
+            offline,
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let components = components
+                .iter()
+                .map(|c| c.parse::<MsvcComponent>())
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|e| anyhow::anyhow!(e))?;
+            let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let host_arch: Option<Architecture> = host_arch
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?;
+
+            let mut builder = DownloadOptions::builder()
+                .arch(arch)
+                .verify_mode(config.verify_mode)
+                .hash_exemptions(config.hash_exemptions.clone())
+                .alternate_endpoints(config.alternate_endpoints.clone())
+                .offline(offline);
+            if let Some(host_arch) = host_arch {
+                builder = builder.host_arch(host_arch);
+            }
+            if let Some(version) = msvc_version {
+                builder = builder.msvc_version(version);
+            }
+            let options = builder.try_build()?;
+
+            let report = add_components(&install_dir, components, &options).await?;
+            println!(
+                "✅ Added {} to MSVC {} in {} ({} payload(s))",
+                report.components.join(", "),
+                report.msvc_version,
+                install_dir.display(),
+                report.payloads.len()
+            );
+        }
+
+        Commands::Repair {
+            component,
+            dir,
+            arch,
+            host_arch,
+            msvc_version,
+            sdk_version,
+            offline,
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let component: RepairComponent =
+                component.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let host_arch: Option<Architecture> = host_arch
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?;
+
+            let mut builder = DownloadOptions::builder()
+                .target_dir(&install_dir)
+                .arch(arch)
+                .verify_mode(config.verify_mode)
+                .hash_exemptions(config.hash_exemptions.clone())
+                .alternate_endpoints(config.alternate_endpoints.clone())
+                .offline(offline);
+            if let Some(host_arch) = host_arch {
+                builder = builder.host_arch(host_arch);
+            }
+            if let Some(version) = msvc_version {
+                builder = builder.msvc_version(version);
+            }
+            if let Some(version) = sdk_version {
+                builder = builder.sdk_version(version);
+            }
+            let options = builder.try_build()?;
+
+            println!("🔧 Repairing {} in {}...", component, install_dir.display());
+            let report = repair_component(component, &options).await?;
+            if let Some(version) = &report.msvc_version {
+                println!("  MSVC {}", version);
+            }
+            if let Some(version) = &report.sdk_version {
+                println!("  Windows SDK {}", version);
+            }
+            println!("  {} payload(s) extracted again", report.payloads.len());
+
+            if report.is_repaired() {
+                println!("✅ {} repaired", component);
+            } else {
+                for path in report.missing.iter().take(20) {
+                    println!("  missing: {}", path.display());
                 }
+                anyhow::bail!(
+                    "{} file(s) of {} are still missing after the repair",
+                    report.missing.len(),
+                    component
+                );
+            }
+        }
 
-                if let Some(version) = sdk_version {
-                    let sdk_path = install_dir
-                        .join("Windows Kits")
-                        .join("10")
-                        .join("Include")
-                        .join(&version);
-                    if sdk_path.exists() {
-                        // Remove SDK version from all subdirectories
-                        for subdir in ["Include", "Lib", "bin"] {
-                            let path = install_dir
-                                .join("Windows Kits")
-                                .join("10")
-                                .join(subdir)
-                                .join(&version);
-                            if path.exists() {
-                                tokio::fs::remove_dir_all(&path).await?;
-                            }
-                        }
-                        println!("✅ Removed Windows SDK {}", version);
-                    } else {
-                        println!("⚠️  Windows SDK {} not found", version);
+        Commands::Sync {
+            file,
+            dir,
+            arch,
+            host_arch,
+            prune,
+            dry_run,
+            format,
+            offline,
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let host_arch: Option<Architecture> = host_arch
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?;
+            if !matches!(format.as_str(), "text" | "json") {
+                anyhow::bail!("Unknown format: {} (expected text or json)", format);
+            }
+
+            let toolchains = ToolchainsFile::load(&file).await?;
+            let mut builder = DownloadOptions::builder()
+                .target_dir(&install_dir)
+                .arch(arch)
+                .verify_mode(config.verify_mode)
+                .hash_exemptions(config.hash_exemptions.clone())
+                .alternate_endpoints(config.alternate_endpoints.clone())
+                .offline(offline);
+            if let Some(host_arch) = host_arch {
+                builder = builder.host_arch(host_arch);
+            }
+            let options = builder.try_build()?;
+            let report =
+                sync_toolchains(&toolchains, &options, &SyncOptions { prune, dry_run }).await?;
+
+            if format == "json" {
+                println!("{}", serde_json::to_string_pretty(&report)?);
+                return Ok(());
+            }
+            for status in &report.toolchains {
+                let action = match status.action {
+                    SyncAction::Present => "present",
+                    SyncAction::Installed => "installed",
+                    SyncAction::WouldInstall => "would install",
+                };
+                println!(
+                    "  {:<16} {:<6} MSVC {:<14} SDK {:<14} {}",
+                    status.name,
+                    status.arch,
+                    status.msvc_version.as_deref().unwrap_or("-"),
+                    status.sdk_version.as_deref().unwrap_or("-"),
+                    action
+                );
+            }
+            for removed in &report.removed {
+                println!(
+                    "  {} {}",
+                    if dry_run { "would remove" } else { "removed" },
+                    removed
+                );
+            }
+            if !report.changed() {
+                println!("✅ All {} toolchain(s) up to date", report.toolchains.len());
+            } else if dry_run {
+                println!("\n🔍 Dry run: nothing changed in {}", install_dir.display());
+            } else {
+                println!(
+                    "✅ {} synced ({} freed)",
+                    install_dir.display(),
+                    humansize::format_size(report.bytes_freed, humansize::BINARY)
+                );
+            }
+        }
+
+        Commands::Move { new_dir, dir } => {
+            let moving_config = dir.is_none();
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            println!(
+                "📦 Moving {} to {}...",
+                install_dir.display(),
+                new_dir.display()
+            );
+            let report = relocate(&install_dir, &new_dir).await?;
+            if report.copied {
+                println!("  copied across volumes and removed the old tree");
+            }
+            for script in &report.scripts_rewritten {
+                println!("  rewrote {}", script.display());
+            }
+            if report.index_entries_updated > 0 {
+                println!(
+                    "  updated {} download index entries",
+                    report.index_entries_updated
+                );
+            }
+
+            let old_root = std::path::absolute(&config.install_dir)?;
+            if moving_config || old_root == report.from {
+                let mut cache_dir = None;
+                if let Some(dir) = &config.cache_dir {
+                    if let Ok(rel) = std::path::absolute(dir)?.strip_prefix(&report.from) {
+                        cache_dir = Some(report.to.join(rel));
                     }
                 }
+                msvc_kit::config::update(|cfg| {
+                    cfg.install_dir = report.to.clone();
+                    // Keep the stored (unexpanded) cache_dir unless it moved
+                    if cache_dir.is_some() {
+                        cfg.cache_dir = cache_dir;
+                    }
+                })?;
+                println!("  install_dir set to {}", report.to.display());
             }
 
-            if cache {
-                let cache_dir = install_dir.join("downloads");
-                if cache_dir.exists() {
-                    tokio::fs::remove_dir_all(&cache_dir).await?;
-                    println!("✅ Removed download cache");
-                }
+            for warning in &report.warnings {
+                println!("⚠️  {}", warning);
+            }
+            match report.tools.get(&ToolKind::Cl) {
+                Some(cl) => println!("✅ Moved; cl.exe at {}", cl.display()),
+                None => println!("✅ Moved to {}", report.to.display()),
             }
         }
 
@@ -579,23 +1960,29 @@ async fn main() -> anyhow::Result<()> {
             set_dir,
             set_msvc,
             set_sdk,
+            accept_license,
             reset,
         } => {
             if reset {
                 config = MsvcKitConfig::default();
                 save_config(&config)?;
                 println!("✅ Configuration reset to defaults");
-            } else if set_dir.is_some() || set_msvc.is_some() || set_sdk.is_some() {
-                if let Some(dir) = set_dir {
-                    config.install_dir = dir;
-                }
-                if let Some(msvc) = set_msvc {
-                    config.default_msvc_version = Some(msvc);
-                }
-                if let Some(sdk) = set_sdk {
-                    config.default_sdk_version = Some(sdk);
-                }
-                save_config(&config)?;
+            } else if set_dir.is_some() || set_msvc.is_some() || set_sdk.is_some() || accept_license
+            {
+                config = msvc_kit::config::update(|cfg| {
+                    if let Some(dir) = set_dir {
+                        cfg.install_dir = dir;
+                    }
+                    if let Some(msvc) = set_msvc {
+                        cfg.default_msvc_version = Some(msvc);
+                    }
+                    if let Some(sdk) = set_sdk {
+                        cfg.default_sdk_version = Some(sdk);
+                    }
+                    if accept_license {
+                        cfg.license_accepted = true;
+                    }
+                })?;
                 println!("✅ Configuration updated");
             }
 
@@ -606,12 +1993,29 @@ async fn main() -> anyhow::Result<()> {
                 config.default_msvc_version.as_deref().unwrap_or("latest")
             );
             println!(
-                "  Default SDK version: {}",
-                config.default_sdk_version.as_deref().unwrap_or("latest")
+                "  Default SDK version: {}",
+                config.default_sdk_version.as_deref().unwrap_or("latest")
+            );
+            println!("  Default architecture: {}", config.default_arch);
+            println!("  Verify mode: {}", config.verify_mode);
+            if !config.hash_exemptions.is_empty() {
+                println!("  Hash exemptions: {}", config.hash_exemptions.join(", "));
+            }
+            if !config.alternate_endpoints.is_empty() {
+                println!(
+                    "  Alternate endpoints: {}",
+                    config.alternate_endpoints.join(", ")
+                );
+            }
+            println!("  Parallel downloads: {}", config.parallel_downloads);
+            println!("  License accepted: {}", config.license_accepted);
+            if let Some(path) = &config.tls_ca_file {
+                println!("  TLS CA file: {}", path.display());
+            }
+            println!(
+                "  Post-install scripts: {}",
+                config.post_install.scripts_dir().display()
             );
-            println!("  Default architecture: {}", config.default_arch);
-            println!("  Verify hashes: {}", config.verify_hashes);
-            println!("  Parallel downloads: {}", config.parallel_downloads);
         }
 
         Commands::Bundle {
@@ -622,13 +2026,19 @@ async fn main() -> anyhow::Result<()> {
             sdk_version,
             accept_license,
             zip,
+            locale,
+            offline,
+            split,
+            dry_run,
+            layout_style,
+            force_extract,
         } => {
-            if !accept_license {
+            if !dry_run && !accept_license && !is_license_accepted(&config) {
                 println!("⚠️  License Agreement Required\n");
                 println!(
                     "The MSVC compiler and Windows SDK are subject to Microsoft's license terms:"
                 );
-                println!("  https://visualstudio.microsoft.com/license-terms/\n");
+                println!("  {}\n", LICENSE_URL);
                 println!("By using --accept-license, you confirm that you have read and accepted");
                 println!("Microsoft's Visual Studio License Terms.\n");
                 println!("Usage:");
@@ -638,117 +2048,277 @@ async fn main() -> anyhow::Result<()> {
                 );
             }
 
-            let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let mut arches: Vec<Architecture> = Vec::new();
+            for a in &arch {
+                let a: Architecture = a.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+                if !arches.contains(&a) {
+                    arches.push(a);
+                }
+            }
             let host_arch: Architecture = host_arch
                 .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
                 .transpose()?
                 .unwrap_or_else(Architecture::host);
+            let locale: Locale = locale.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let layout_style: LayoutStyle = layout_style
+                .parse()
+                .map_err(|e: String| anyhow::anyhow!(e))?;
+            let arch_list = arches
+                .iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
 
             println!("📦 msvc-kit - Creating Portable MSVC Bundle\n");
             println!("Output directory: {}", output.display());
-            println!("Target architecture: {}", arch);
+            println!("Target architecture: {}", arch_list);
             println!("Host architecture: {}", host_arch);
             println!();
 
+            if dry_run {
+                for &arch in &arches {
+                    let options = BundleOptions {
+                        output_dir: if split {
+                            output.join(arch.to_string())
+                        } else {
+                            output.clone()
+                        },
+                        arch,
+                        host_arch,
+                        msvc_version: msvc_version.clone(),
+                        sdk_version: sdk_version.clone(),
+                        locale,
+                        offline,
+                        dry_run: true,
+                        layout_style,
+                        ..Default::default()
+                    };
+                    println!("{}\n", plan_bundle(&options).await?.format());
+                }
+                println!("Dry run: nothing was downloaded.");
+                return Ok(());
+            }
+
             // Create output directory
             tokio::fs::create_dir_all(&output).await?;
 
-            // Download options - download directly to bundle root (not runtime/)
-            let options = DownloadOptions {
-                msvc_version: msvc_version.clone(),
-                sdk_version: sdk_version.clone(),
-                target_dir: output.clone(),
-                arch,
-                host_arch: Some(host_arch),
-                verify_hashes: true,
-                parallel_downloads: config.parallel_downloads,
-                http_client: None,
-                progress_handler: None,
-                cache_manager: None,
-                dry_run: false,
-                include_components: Default::default(),
-                exclude_patterns: Default::default(),
-            };
+            if let Some(pattern) = &force_extract {
+                print_invalidated(&output, pattern).await?;
+                if split {
+                    for arch in &arches {
+                        print_invalidated(&output.join(arch.to_string()), pattern).await?;
+                    }
+                }
+            }
 
-            // Download and extract MSVC
-            println!("⬇️  Downloading MSVC compiler...");
-            let mut msvc_info = download_msvc(&options).await?;
-            println!("📁 Extracting MSVC packages...");
-            msvc_kit::extract_and_finalize_msvc(&mut msvc_info).await?;
-            let msvc_ver = msvc_info.version.clone();
-            println!("✅ MSVC {} installed", msvc_ver);
-
-            // Download and extract SDK
-            println!("\n⬇️  Downloading Windows SDK...");
-            let sdk_info = download_sdk(&options).await?;
-            println!("📁 Extracting SDK packages...");
-            msvc_kit::extract_and_finalize_sdk(&sdk_info).await?;
-            let sdk_ver = sdk_info.version.clone();
-            println!("✅ Windows SDK {} installed", sdk_ver);
-
-            // Create bundle layout
-            let layout = BundleLayout::from_root_with_versions(
-                &output, &msvc_ver, &sdk_ver, arch, host_arch,
-            )?;
+            let started = Instant::now();
+            let mut warnings = Vec::new();
+            // Layout, scripts and payload bytes of each bundle (of all
+            // architectures in a multi-arch bundle)
+            let bundles: Vec<(BundleLayout, BundleScripts, u64)> = if arches.len() == 1 && !split {
+                let arch = arches[0];
+
+                // Download options - download directly to bundle root (not runtime/)
+                let options = DownloadOptionsBuilder::from(DownloadOptions {
+                    msvc_version: msvc_version.clone(),
+                    sdk_version: sdk_version.clone(),
+                    channel: Default::default(),
+                    target_dir: output.clone(),
+                    arch,
+                    host_arch: Some(host_arch),
+                    verify_mode: VerifyMode::Enforce,
+                    hash_exemptions: Vec::new(),
+                    parallel_downloads: config.parallel_downloads,
+                    http_client: None,
+                    user_agent: None,
+                    progress_handler: None,
+                    cache_manager: None,
+                    auth_provider: None,
+                    version_resolver: None,
+                    dry_run: false,
+                    offline,
+                    layout_dir: None,
+                    headers_only: false,
+                    include_components: Default::default(),
+                    app_platforms: AppPlatform::defaults(),
+                    exclude_patterns: Default::default(),
+                    only_patterns: Default::default(),
+                    allow_broken_selection: false,
+                    sdk_installer_patterns: Vec::new(),
+                    extract_filters: Default::default(),
+                    auto_cleanup: Default::default(),
+                    post_install_hooks: Vec::new(),
+                    overall_timeout: None,
+                    per_file_timeout: None,
+                    max_download_rate: None,
+                    chunked_downloads: None,
+                    alternate_endpoints: config.alternate_endpoints.clone(),
+                    continue_on_optional_failure: false,
+                    allow_http_fallback: false,
+                })
+                .try_build()?;
+
+                // Download and extract MSVC
+                println!("⬇️  Downloading MSVC compiler...");
+                let mut msvc_info = download_msvc(&options).await?;
+                let mut download_bytes = payload_bytes(&[&msvc_info]);
+                println!("📁 Extracting MSVC packages...");
+                msvc_kit::extract_and_finalize_msvc(&mut msvc_info).await?;
+                println!("✅ MSVC {} installed", msvc_info.version);
+                warnings.extend(msvc_info.warnings.iter().cloned());
 
-            // Generate and save activation scripts (includes README)
-            let scripts = generate_bundle_scripts(&layout)?;
-            save_bundle_scripts(&layout, &scripts).await?;
+                // Download and extract SDK
+                println!("\n⬇️  Downloading Windows SDK...");
+                let sdk_info = download_sdk(&options).await?;
+                download_bytes += payload_bytes(&[&sdk_info]);
+                println!("📁 Extracting SDK packages...");
+                let report =
+                    msvc_kit::extract_and_finalize_sdk_with_report(&sdk_info, false).await?;
+                println!("✅ Windows SDK {} installed", sdk_info.version);
+                warnings.extend(sdk_info.warnings.iter().cloned());
+                warnings.extend(report.to_warnings());
+
+                // Create bundle layout
+                let layout = BundleLayout::from_root_with_versions(
+                    &output,
+                    &msvc_info.version,
+                    &sdk_info.version,
+                    arch,
+                    host_arch,
+                )?
+                .with_style(layout_style);
+                if layout_style == LayoutStyle::MsvcWine {
+                    msvc_wine::convert_to_msvc_wine(&output)?;
+                    msvc_wine::save_msvc_wine_scripts(&layout).await?;
+                }
+                let scripts = generate_bundle_scripts_with_locale(&layout, locale)?;
+                vec![(layout, scripts, download_bytes)]
+            } else {
+                println!("⬇️  Downloading {} concurrently...", arch_list);
+                let options = BundleOptions {
+                    output_dir: output.clone(),
+                    host_arch,
+                    msvc_version: msvc_version.clone(),
+                    sdk_version: sdk_version.clone(),
+                    parallel_downloads: config.parallel_downloads,
+                    locale,
+                    offline,
+                    layout_style,
+                    ..Default::default()
+                };
+                let results = create_multi_arch_bundles(options, &arches, split).await?;
+                for result in &results {
+                    println!(
+                        "✅ {}: MSVC {}, Windows SDK {} installed",
+                        result.layout.arch, result.msvc_info.version, result.sdk_info.version
+                    );
+                    warnings.extend(result.warnings.iter().cloned());
+                }
+                results
+                    .into_iter()
+                    .map(|r| (r.layout, r.scripts, r.stats.download_bytes))
+                    .collect()
+            };
 
-            // Copy msvc-kit executable
+            // In a multi-arch bundle, setup.* activates the first architecture
+            // and setup-<arch>.* each architecture
+            let shared_root = !split && bundles.len() > 1;
             let exe_name = if cfg!(windows) {
                 "msvc-kit.exe"
             } else {
                 "msvc-kit"
             };
-            let current_exe = std::env::current_exe()?;
-            let target_exe = output.join(exe_name);
-            tokio::fs::copy(&current_exe, &target_exe).await?;
 
+            for (i, (layout, scripts, _)) in bundles.iter().enumerate() {
+                let first_in_root = !shared_root || i == 0;
+
+                // Generate and save activation scripts (includes README)
+                if first_in_root {
+                    save_bundle_scripts(layout, scripts).await?;
+                    accept_license_terms(&layout.root)?;
+
+                    // Copy msvc-kit executable
+                    let current_exe = std::env::current_exe()?;
+                    tokio::fs::copy(&current_exe, layout.root.join(exe_name)).await?;
+                }
+                if shared_root {
+                    let base_name = format!("setup-{}", layout.arch);
+                    save_scripts(scripts, &layout.root, &base_name).await?;
+                }
+
+                run_post_install_scripts(
+                    &config,
+                    HookScriptContext {
+                        event: "bundle".to_string(),
+                        install_dir: layout.root.clone(),
+                        msvc_version: Some(layout.msvc_version.clone()),
+                        sdk_version: Some(layout.sdk_version.clone()),
+                        arch: layout.arch,
+                        host_arch,
+                    },
+                )
+                .await?;
+            }
+
+            print_warnings(&warnings);
             println!("\n✅ Bundle created successfully!");
-            println!("\nContents:");
-            println!("  {}/", output.display());
-            println!("  ├── {}", exe_name);
-            println!("  ├── setup.bat");
-            println!("  ├── setup.ps1");
-            println!("  ├── setup.sh");
-            println!("  ├── README.txt");
-            println!("  ├── VC/Tools/MSVC/{}/", msvc_ver);
-            println!("  └── Windows Kits/10/");
+            for (i, (layout, _, download_bytes)) in bundles.iter().enumerate() {
+                if shared_root && i > 0 {
+                    continue;
+                }
+                // Measured again: the scripts and the executable are part of the bundle
+                let stats =
+                    BundleStats::collect(&layout.root, *download_bytes, started.elapsed()).await?;
+                write_bundle_manifest(layout, &stats).await?;
+                println!("\n📊 {}", stats);
+                println!("\nContents:");
+                println!("  {}/", layout.root.display());
+                println!("  ├── {}", exe_name);
+                println!("  ├── setup.bat");
+                println!("  ├── setup.ps1");
+                println!("  ├── setup.sh");
+                if shared_root {
+                    for (other, _, _) in &bundles {
+                        println!("  ├── setup-{}.bat / .ps1 / .sh", other.arch);
+                    }
+                }
+                println!("  ├── README.txt");
+                println!("  ├── MsvcKit.psm1");
+                println!("  ├── {}", BUNDLE_MANIFEST_FILE);
+                println!("  ├── {}", LICENSE_ACCEPTANCE_FILE);
+                if layout.style == LayoutStyle::MsvcWine {
+                    println!("  ├── bin/{}/ (msvcenv.sh, cl, link, ...)", layout.arch);
+                    println!("  ├── vc/tools/msvc/{}/", layout.msvc_version);
+                    println!("  └── kits/10/");
+                } else {
+                    println!("  ├── VC/Tools/MSVC/{}/", layout.msvc_version);
+                    println!("  └── Windows Kits/10/");
+                }
+            }
 
             if zip {
                 println!("\n📦 Creating zip archive...");
-                let zip_name = format!(
-                    "msvc-kit-bundle-{}-{}-{}.zip",
-                    msvc_ver.replace('.', "_"),
-                    sdk_ver.replace('.', "_"),
-                    arch
-                );
-                let zip_path = output.parent().unwrap_or(&output).join(&zip_name);
-
-                #[cfg(windows)]
-                {
-                    let output_str = output.display().to_string();
-                    let zip_str = zip_path.display().to_string();
-                    let status = std::process::Command::new("powershell")
-                        .args([
-                            "-NoProfile",
-                            "-Command",
-                            &format!(
-                                "Compress-Archive -Path '{}\\*' -DestinationPath '{}' -Force",
-                                output_str, zip_str
-                            ),
-                        ])
-                        .status()?;
-                    if status.success() {
-                        println!("✅ Created: {}", zip_path.display());
-                    } else {
-                        println!("⚠️  Failed to create zip archive");
+                for (i, (layout, _, _)) in bundles.iter().enumerate() {
+                    if shared_root && i > 0 {
+                        continue;
                     }
-                }
-                #[cfg(not(windows))]
-                {
-                    println!("⚠️  Zip creation is only supported on Windows");
+                    let arch_name = if shared_root {
+                        arches
+                            .iter()
+                            .map(|a| a.to_string())
+                            .collect::<Vec<_>>()
+                            .join("-")
+                    } else {
+                        layout.arch.to_string()
+                    };
+                    let zip_name = format!(
+                        "msvc-kit-bundle-{}-{}-{}.zip",
+                        layout.msvc_version.replace('.', "_"),
+                        layout.sdk_version.replace('.', "_"),
+                        arch_name
+                    );
+                    let zip_path = output.parent().unwrap_or(&output).join(&zip_name);
+                    create_zip_archive(&layout.root, &zip_path)?;
                 }
             }
 
@@ -763,6 +2333,11 @@ async fn main() -> anyhow::Result<()> {
             msvc_version,
             sdk_version,
             format,
+            find_header,
+            find_library,
+            all_versions,
+            msvc_dir,
+            sdk_dir,
         } => {
             let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
             let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
@@ -775,7 +2350,8 @@ async fn main() -> anyhow::Result<()> {
                 .install_dir(&install_dir)
                 .arch(arch)
                 .component(component)
-                .property(property);
+                .property(property)
+                .all_versions(all_versions);
 
             let options = if let Some(ref ver) = msvc_version {
                 options.msvc_version(ver)
@@ -789,9 +2365,60 @@ async fn main() -> anyhow::Result<()> {
                 options
             };
 
+            let options = match resolve_component_dir(msvc_dir)? {
+                Some(dir) => options.msvc_dir(dir),
+                None => options,
+            };
+
+            let options = match resolve_component_dir(sdk_dir)? {
+                Some(dir) => options.sdk_dir(dir),
+                None => options,
+            };
+
             let options = options.build();
             let result = query_installation(&options)?;
 
+            if find_header.is_some() || find_library.is_some() {
+                let lookups = [
+                    find_header.map(|name| {
+                        let path = result.find_header(&name);
+                        (name, path, result.all_include_paths())
+                    }),
+                    find_library.map(|name| {
+                        let path = result.find_library(&name);
+                        (name, path, result.all_lib_paths())
+                    }),
+                ];
+
+                let mut found = serde_json::Map::new();
+                let mut missing = Vec::new();
+                for (name, path, search_paths) in lookups.into_iter().flatten() {
+                    if format != "json" {
+                        match path {
+                            Some(ref path) => println!("{}", path.display()),
+                            None => {
+                                eprintln!("❌ '{}' not found. Searched:", name);
+                                for dir in search_paths {
+                                    eprintln!("   {}", dir.display());
+                                }
+                            }
+                        }
+                    }
+                    if path.is_none() {
+                        missing.push(name.clone());
+                    }
+                    found.insert(name, serde_json::json!(path));
+                }
+
+                if format == "json" {
+                    println!("{}", serde_json::to_string_pretty(&found)?);
+                }
+                if !missing.is_empty() {
+                    anyhow::bail!("Not found: {}", missing.join(", "));
+                }
+                return Ok(());
+            }
+
             match format.as_str() {
                 "json" => {
                     // JSON output: filter by property
@@ -833,6 +2460,16 @@ async fn main() -> anyhow::Result<()> {
                                     serde_json::Value::String(v.to_string()),
                                 );
                             }
+                            for (key, infos) in [
+                                ("msvc_versions", &result.msvc_versions),
+                                ("sdk_versions", &result.sdk_versions),
+                            ] {
+                                if !infos.is_empty() {
+                                    let list: Vec<&str> =
+                                        infos.iter().map(|i| i.version.as_str()).collect();
+                                    versions.insert(key.to_string(), serde_json::json!(list));
+                                }
+                            }
                             serde_json::to_string_pretty(&versions)?
                         }
                         QueryProperty::Include => {
@@ -878,7 +2515,7 @@ async fn main() -> anyhow::Result<()> {
                         }
                         QueryProperty::Tools => {
                             let mut sorted_tools: Vec<_> = result.tools.iter().collect();
-                            sorted_tools.sort_by_key(|(k, _)| k.as_str());
+                            sorted_tools.sort_by_key(|(k, _)| k.name());
                             for (name, path) in sorted_tools {
                                 println!("{}={}", name, path.display());
                             }
@@ -890,6 +2527,16 @@ async fn main() -> anyhow::Result<()> {
                             if let Some(v) = result.sdk_version() {
                                 println!("sdk={}", v);
                             }
+                            for (key, infos) in [
+                                ("msvc_versions", &result.msvc_versions),
+                                ("sdk_versions", &result.sdk_versions),
+                            ] {
+                                if !infos.is_empty() {
+                                    let list: Vec<&str> =
+                                        infos.iter().map(|i| i.version.as_str()).collect();
+                                    println!("{}={}", key, list.join(","));
+                                }
+                            }
                         }
                         QueryProperty::Include => {
                             for path in result.all_include_paths() {
@@ -904,6 +2551,14 @@ async fn main() -> anyhow::Result<()> {
                     }
                 }
             }
+
+            // The full result carries its warnings; single properties keep
+            // stdout parseable
+            if property != QueryProperty::All {
+                for warning in &result.warnings {
+                    eprintln!("⚠️  {}", warning);
+                }
+            }
         }
 
         Commands::Env { dir, format } => {
@@ -924,6 +2579,11 @@ async fn main() -> anyhow::Result<()> {
                 install_path: msvc_version.install_path.clone().unwrap(),
                 downloaded_files: vec![],
                 arch: config.default_arch,
+                extract_filters: vec![],
+                cleanup: Default::default(),
+                resolution: None,
+                warnings: Vec::new(),
+                reused: false,
             };
 
             let sdk_info = sdk_version.map(|v| msvc_kit::installer::InstallInfo {
@@ -932,15 +2592,38 @@ async fn main() -> anyhow::Result<()> {
                 install_path: v.install_path.clone().unwrap(),
                 downloaded_files: vec![],
                 arch: config.default_arch,
+                extract_filters: vec![],
+                cleanup: Default::default(),
+                resolution: None,
+                warnings: Vec::new(),
+                reused: false,
             });
 
-            let env = setup_environment(&msvc_info, sdk_info.as_ref())?;
+            let env = setup_environment_with_arch(
+                &msvc_info,
+                sdk_info.as_ref(),
+                detect_host_arch(&msvc_info.install_path, config.default_arch).arch,
+                config.default_arch,
+            )?;
             let vars = get_env_vars(&env);
 
             match format.as_str() {
                 "json" => {
                     println!("{}", serde_json::to_string_pretty(&vars)?);
                 }
+                // One argument per line so paths with spaces survive
+                "clang-args" => {
+                    for arg in clang_args(&env) {
+                        println!("{}", arg);
+                    }
+                }
+                "clang-cl-args" => {
+                    for arg in clang_cl_args(&env) {
+                        println!("{}", arg);
+                    }
+                }
+                "dotenv" => print!("{}", dotenv(&env)),
+                "direnv" => print!("{}", direnv(&env)),
                 _ => {
                     for (key, value) in &vars {
                         println!("{}={}", key, value);
@@ -949,6 +2632,402 @@ async fn main() -> anyhow::Result<()> {
             }
         }
 
+        Commands::Provision {
+            bundle,
+            wsb,
+            dockerfile,
+            mount_dir,
+            work_dir,
+            workspace,
+            base_image,
+        } => {
+            let options = ProvisionOptions {
+                mount_dir,
+                work_dir,
+                workspace,
+                base_image,
+            };
+
+            if let Some(path) = wsb {
+                write_sandbox_config(&bundle, &path, &options).await?;
+                println!("✅ Windows Sandbox config written to {}", path.display());
+                println!("   Open it to start a sandbox with the toolchain activated");
+            }
+            if let Some(path) = dockerfile {
+                write_dockerfile(&bundle, &path, &options).await?;
+                println!("✅ Dockerfile written to {}", path.display());
+                println!("   See the header comments for the docker build/run commands");
+            }
+        }
+
+        Commands::Redist {
+            action:
+                RedistAction::Copy {
+                    dest,
+                    arch,
+                    exe,
+                    debug,
+                    all,
+                    dry_run,
+                    dir,
+                },
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let options = RedistOptions {
+                arch: arch.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+                debug,
+                binaries: exe,
+                all,
+                dry_run,
+            };
+            let report = copy_redist(&install_dir, &dest, &options)?;
+            println!(
+                "📦 VC++ runtime {} from {}",
+                report.version,
+                report.source_dir.display()
+            );
+            for dll in &report.copied {
+                println!(
+                    "  {} {}",
+                    if dry_run { "would copy" } else { "copied" },
+                    dll.display()
+                );
+            }
+            if report.copied.is_empty() {
+                println!("  nothing to copy: no runtime DLL is imported");
+            }
+        }
+
+        Commands::Diff {
+            from,
+            to,
+            dir,
+            component,
+            format,
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let component: QueryComponent =
+                component.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let diff = msvc_kit::query::diff_versions(&install_dir, component, &from, &to)?;
+            match format.as_str() {
+                "json" => println!("{}", serde_json::to_string_pretty(&diff)?),
+                "text" => print!("{}", diff),
+                other => anyhow::bail!("Unknown format '{}' (text, json)", other),
+            }
+        }
+
+        Commands::Sbom {
+            dir,
+            format,
+            output,
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let format: SbomFormat = format.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let document = msvc_kit::sbom::export(&install_dir, format).await?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, document)?;
+                    println!("📄 {} SBOM written to {}", format, path.display());
+                }
+                None => println!("{}", document),
+            }
+        }
+
+        Commands::Init {
+            dir,
+            cache_dir,
+            offline,
+        } => {
+            println!("🔎 Checking prerequisites...\n");
+            let report = msvc_kit::preflight::run(&PreflightOptions {
+                install_dir: dir.clone().unwrap_or_else(|| config.install_dir.clone()),
+                cache_dir: cache_dir.clone().or_else(|| config.cache_dir.clone()),
+                check_network: !offline,
+                ..Default::default()
+            })
+            .await;
+            for check in &report.checks {
+                let icon = match check.status {
+                    CheckStatus::Pass => "✅",
+                    CheckStatus::Warn => "⚠️ ",
+                    CheckStatus::Fail => "❌",
+                    CheckStatus::Skipped => "⏭️ ",
+                };
+                println!("  {} {:<12} {}", icon, check.name, check.detail);
+            }
+            println!();
+
+            if !report.is_ready() {
+                anyhow::bail!("Prerequisites not met; fix the failed checks and run init again");
+            }
+
+            let config_path = msvc_kit::config::get_config_path();
+            let existed = config_path.exists();
+            // Only the flags given are stored; the rest keep their unexpanded values
+            let updated = msvc_kit::config::update(|cfg| {
+                if let Some(dir) = dir {
+                    cfg.install_dir = dir;
+                }
+                if cache_dir.is_some() {
+                    cfg.cache_dir = cache_dir;
+                }
+            })?;
+            if !existed {
+                save_config(&updated)?;
+            }
+            println!(
+                "✅ Ready. Configuration written to {}",
+                config_path.display()
+            );
+            println!("   Next: msvc-kit download");
+        }
+
+        Commands::Prefetch {
+            latest,
+            msvc_version,
+            sdk_version,
+            dir,
+            arch,
+            host_arch,
+            no_msvc,
+            no_sdk,
+            limit_rate,
+            parallel_downloads,
+        } => {
+            let target_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let arch: Architecture = arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+            let host_arch: Option<Architecture> = host_arch
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?;
+            let (msvc_version, sdk_version) = if latest {
+                (None, None)
+            } else {
+                (
+                    msvc_version.or_else(|| config.default_msvc_version.clone()),
+                    sdk_version.or_else(|| config.default_sdk_version.clone()),
+                )
+            };
+
+            let mut builder = DownloadOptions::builder()
+                .target_dir(&target_dir)
+                .arch(arch)
+                .verify_mode(config.verify_mode)
+                .hash_exemptions(config.hash_exemptions.clone())
+                .alternate_endpoints(config.alternate_endpoints.clone())
+                .parallel_downloads(parallel_downloads);
+            if let Some(host_arch) = host_arch {
+                builder = builder.host_arch(host_arch);
+            }
+            if let Some(version) = msvc_version {
+                builder = builder.msvc_version(version);
+            }
+            if let Some(version) = sdk_version {
+                builder = builder.sdk_version(version);
+            }
+            let rate = parse_limit_rate(Some(&limit_rate))?;
+            if let Some(rate) = rate {
+                builder = builder.max_download_rate(rate);
+            }
+            let options = builder.try_build()?;
+
+            println!("📦 msvc-kit - Prefetching into {}", target_dir.display());
+            match rate {
+                Some(rate) => println!(
+                    "Bandwidth limit: {}/s\n",
+                    humansize::format_size(rate, humansize::BINARY)
+                ),
+                None => println!("Bandwidth limit: none\n"),
+            }
+
+            if !no_msvc {
+                let info = download_msvc(&options).await?;
+                println!(
+                    "✅ MSVC {} cached ({} files)",
+                    info.version,
+                    info.downloaded_files.len()
+                );
+            }
+            if !no_sdk {
+                let info = download_sdk(&options).await?;
+                println!(
+                    "✅ Windows SDK {} cached ({} files)",
+                    info.version,
+                    info.downloaded_files.len()
+                );
+            }
+            println!("\nRun 'msvc-kit download' to install from the cache.");
+        }
+
+        Commands::Diagnostics {
+            action:
+                DiagnosticsAction::Collect {
+                    dir,
+                    output,
+                    include_payloads,
+                },
+        } => {
+            let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+            let report = msvc_kit::diagnostics::collect(
+                &DiagnosticsOptions {
+                    install_dir,
+                    config_path: Some(msvc_kit::config::get_config_path()),
+                    include_payloads,
+                },
+                &output,
+            )
+            .await?;
+            for name in &report.files {
+                println!("  {}", name);
+            }
+            for (path, reason) in &report.skipped {
+                println!("  ⚠️  skipped {}: {}", path.display(), reason);
+            }
+            println!(
+                "📦 Diagnostics written to {} ({} files)",
+                report.output.display(),
+                report.files.len()
+            );
+        }
+
+        Commands::Profile { action } => {
+            let profiles_dir = default_profiles_dir();
+            match action {
+                ProfileAction::Save {
+                    name,
+                    dir,
+                    arch,
+                    host_arch,
+                    msvc_version,
+                    sdk_version,
+                } => {
+                    let install_dir = dir.unwrap_or_else(|| config.install_dir.clone());
+                    let arch: Architecture =
+                        arch.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+                    let host_arch: Option<Architecture> = host_arch
+                        .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                        .transpose()?;
+
+                    let msvc = list_installed_msvc(&install_dir)
+                        .into_iter()
+                        .find(|v| {
+                            msvc_version
+                                .as_deref()
+                                .is_none_or(|p| v.version.starts_with(p))
+                        })
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "No matching MSVC installation found in {}",
+                                install_dir.display()
+                            )
+                        })?;
+                    let sdk = list_installed_sdk(&install_dir).into_iter().find(|v| {
+                        sdk_version
+                            .as_deref()
+                            .is_none_or(|p| v.version.starts_with(p))
+                    });
+
+                    let msvc_info = msvc_kit::installer::InstallInfo {
+                        component_type: "msvc".to_string(),
+                        version: msvc.version.clone(),
+                        install_path: msvc.install_path.clone().unwrap(),
+                        downloaded_files: vec![],
+                        arch,
+                        extract_filters: vec![],
+                        cleanup: Default::default(),
+                        resolution: None,
+                        warnings: Vec::new(),
+                        reused: false,
+                    };
+                    let sdk_info = sdk.map(|v| msvc_kit::installer::InstallInfo {
+                        component_type: "sdk".to_string(),
+                        version: v.version.clone(),
+                        install_path: v.install_path.clone().unwrap(),
+                        downloaded_files: vec![],
+                        arch,
+                        extract_filters: vec![],
+                        cleanup: Default::default(),
+                        resolution: None,
+                        warnings: Vec::new(),
+                        reused: false,
+                    });
+
+                    let host_arch = host_arch
+                        .unwrap_or_else(|| detect_host_arch(&msvc_info.install_path, arch).arch);
+                    let env = setup_environment_with_arch(
+                        &msvc_info,
+                        sdk_info.as_ref(),
+                        host_arch,
+                        arch,
+                    )?;
+                    let name = name.unwrap_or_else(|| default_profile_name(&env));
+                    let path = write_profile(&profiles_dir, &name, &env)?;
+                    println!("✅ Profile '{}' saved to {}", name, path.display());
+                    println!("   Activate it with: msvc-kit profile activate {}", name);
+                }
+                ProfileAction::List => {
+                    let names = list_profiles(&profiles_dir);
+                    if names.is_empty() {
+                        println!("No profiles in {}", profiles_dir.display());
+                    }
+                    for name in names {
+                        println!("{}", name);
+                    }
+                }
+                ProfileAction::Activate {
+                    name,
+                    shell,
+                    command,
+                } => {
+                    let changes = read_profile(&profiles_dir, &name)?;
+                    if let Some((program, args)) = command.split_first() {
+                        let status = std::process::Command::new(program)
+                            .args(args)
+                            .envs(changes.iter().map(|change| {
+                                let current = std::env::var(change.name()).ok();
+                                (change.name().to_string(), change.merge(current.as_deref()))
+                            }))
+                            .status()?;
+                        std::process::exit(status.code().unwrap_or(1));
+                    }
+
+                    let shell_type = match shell.as_deref().map(str::to_lowercase).as_deref() {
+                        Some("cmd" | "bat") => ShellType::Cmd,
+                        Some("powershell" | "ps1" | "pwsh") => ShellType::PowerShell,
+                        Some("bash" | "sh") => ShellType::Bash,
+                        _ => ShellType::detect(),
+                    };
+                    print!("{}", profile_commands(&changes, shell_type));
+                }
+            }
+        }
+
+        #[cfg(feature = "serve")]
+        Commands::Serve { listen, dir } => {
+            let endpoint: msvc_kit::serve::Endpoint = listen
+                .map(|s| s.parse().map_err(|e: String| anyhow::anyhow!(e)))
+                .transpose()?
+                .unwrap_or_default();
+            let daemon = msvc_kit::serve::Daemon::new(
+                DownloadOptions::builder()
+                    .target_dir(dir.unwrap_or_else(|| config.install_dir.clone()))
+                    .arch(config.default_arch)
+                    .verify_mode(config.verify_mode)
+                    .hash_exemptions(config.hash_exemptions.clone())
+                    .alternate_endpoints(config.alternate_endpoints.clone())
+                    .parallel_downloads(config.parallel_downloads)
+                    .try_build()?,
+            );
+            println!(
+                "🛰️  Serving {} on {} (Ctrl+C to stop)",
+                daemon.install_dir().display(),
+                endpoint
+            );
+            tokio::select! {
+                result = std::sync::Arc::new(daemon).serve(&endpoint) => result?,
+                _ = tokio::signal::ctrl_c() => println!("Stopped"),
+            }
+        }
+
         #[cfg(feature = "self-update")]
         Commands::Update { check, version } => {
             let current_version = env!("CARGO_PKG_VERSION");
@@ -963,6 +3042,14 @@ async fn main() -> anyhow::Result<()> {
 
             let mut updater = axoupdater::AxoUpdater::new_for("msvc-kit");
             updater.set_release_source(source);
+            // Trusts the configured CA certificates; no timeout for the installer download
+            updater.set_client(
+                msvc_kit::downloader::HttpClientConfig {
+                    timeout: None,
+                    ..Default::default()
+                }
+                .build(),
+            );
             updater
                 .set_current_version(
                     current_version
@@ -1023,3 +3110,181 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+/// Zip the contents of `dir` into `zip_path`
+fn create_zip_archive(dir: &std::path::Path, zip_path: &std::path::Path) -> anyhow::Result<()> {
+    #[cfg(windows)]
+    {
+        let status = std::process::Command::new("powershell")
+            .args([
+                "-NoProfile",
+                "-Command",
+                &format!(
+                    "Compress-Archive -Path '{}\\*' -DestinationPath '{}' -Force",
+                    dir.display(),
+                    zip_path.display()
+                ),
+            ])
+            .status()?;
+        if status.success() {
+            println!("✅ Created: {}", zip_path.display());
+        } else {
+            println!("⚠️  Failed to create zip archive");
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        let _ = (dir, zip_path);
+        println!("⚠️  Zip creation is only supported on Windows");
+    }
+    Ok(())
+}
+
+/// Record license acceptance in `dir`
+fn accept_license_terms(dir: &std::path::Path) -> anyhow::Result<()> {
+    let acceptance = accept_license(dir)?;
+    println!(
+        "📝 License acceptance by {} recorded in {}",
+        acceptance.user,
+        dir.join(LICENSE_ACCEPTANCE_FILE).display()
+    );
+    Ok(())
+}
+
+/// Run the configured post-install.d scripts, reporting each outcome
+async fn run_post_install_scripts(
+    config: &MsvcKitConfig,
+    ctx: HookScriptContext,
+) -> anyhow::Result<()> {
+    let dir = config.post_install.scripts_dir();
+    if list_hook_scripts(&dir).is_empty() {
+        return Ok(());
+    }
+    println!("\n🔧 Running post-install scripts from {}", dir.display());
+    for outcome in run_hook_scripts(&config.post_install, &ctx).await? {
+        let name = outcome
+            .script
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if outcome.success() {
+            println!("   ✅ {}", name);
+        } else if outcome.timed_out {
+            println!("   ⚠️  {} (timed out)", name);
+        } else {
+            println!("   ⚠️  {} (exit code {:?})", name, outcome.exit_code);
+        }
+    }
+    Ok(())
+}
+
+/// Ask a yes/no question on the terminal (default: no)
+///
+/// Fails when stdin is not a terminal, so scripts have to pass `--yes`.
+fn confirm(question: &str) -> anyhow::Result<bool> {
+    use std::io::{IsTerminal, Write};
+
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!("Refusing to delete without confirmation; pass --yes in non-interactive use");
+    }
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Resolve `--msvc-dir` / `--sdk-dir`, expanding `vs` and `system`
+fn resolve_component_dir(dir: Option<PathBuf>) -> anyhow::Result<Option<PathBuf>> {
+    match dir {
+        Some(dir) if dir.as_os_str() == "vs" => find_visual_studio()
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("No Visual Studio installation with MSVC found")),
+        Some(dir) if dir.as_os_str() == "system" => system_windows_kits_root()
+            .map(Some)
+            .ok_or_else(|| anyhow::anyhow!("No Windows SDK installation found")),
+        other => Ok(other),
+    }
+}
+
+/// Bandwidth limit from a `--limit-rate` value; `0` means unlimited
+fn parse_limit_rate(value: Option<&str>) -> anyhow::Result<Option<u64>> {
+    let rate = value
+        .map(|s| parse_rate(s).map_err(|e: String| anyhow::anyhow!(e)))
+        .transpose()?;
+    Ok(rate.filter(|rate| *rate > 0))
+}
+
+/// Warn about a pinned MSVC/SDK pair that is not supported; fail unless allowed
+fn check_version_compat(msvc: &str, sdk: &str, allow_incompatible: bool) -> anyhow::Result<()> {
+    let report = check_compat(msvc, sdk);
+    let reason = report.reason.as_deref().unwrap_or_default();
+    match report.status {
+        CompatStatus::Supported => {}
+        CompatStatus::Untested => {
+            eprintln!(
+                "⚠️  Warning: MSVC {} with SDK {} is untested: {}",
+                msvc, sdk, reason
+            );
+        }
+        CompatStatus::Unsupported if allow_incompatible => {
+            eprintln!(
+                "⚠️  Warning: MSVC {} with SDK {} is unsupported: {} (continuing, --allow-incompatible)",
+                msvc, sdk, reason
+            );
+        }
+        CompatStatus::Unsupported => {
+            anyhow::bail!(
+                "MSVC {} with SDK {} is unsupported: {}\nPick SDK {} or newer, or pass --allow-incompatible",
+                msvc,
+                sdk,
+                reason,
+                report.min_sdk.as_deref().unwrap_or("10.0.19041.0")
+            );
+        }
+    }
+    Ok(())
+}
+
+fn print_install_report(report: &msvc_kit::InstallReport) {
+    if !report.cleanup.removed.is_empty() {
+        println!(
+            "🧹 Removed {} extracted download(s), reclaimed {}",
+            report.cleanup.removed.len(),
+            humansize::format_size(report.cleanup.bytes_reclaimed, humansize::BINARY)
+        );
+    }
+}
+
+/// Drop the extraction markers of payloads matching `pattern` in `dir`
+async fn print_invalidated(dir: &std::path::Path, pattern: &str) -> anyhow::Result<()> {
+    let invalidated = invalidate_extraction(dir, pattern).await?;
+    if !invalidated.is_empty() {
+        println!(
+            "🔁 {} payload(s) will be extracted again into {}",
+            invalidated.len(),
+            dir.display()
+        );
+    }
+    Ok(())
+}
+
+/// Warnings collected during an operation, printed once at its end
+fn print_warnings(warnings: &[Warning]) {
+    if warnings.is_empty() {
+        return;
+    }
+    println!("\n⚠️  {} warning(s):", warnings.len());
+    for warning in warnings {
+        println!("   {}", warning);
+    }
+    if warnings.iter().any(|w| w.code == WarningCode::FileConflict) {
+        println!("   (use --strict-conflicts to fail on file conflicts)");
+    }
+    if warnings
+        .iter()
+        .any(|w| w.code == WarningCode::OptionalPackageFailed)
+    {
+        println!("   (run the download again to retry the optional packages left out)");
+    }
+}
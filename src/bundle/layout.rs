@@ -4,11 +4,109 @@
 
 use crate::env::{get_env_vars, MsvcEnvironment};
 use crate::error::{MsvcKitError, Result};
+use crate::query::QueryComponent;
 use crate::version::Architecture;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+/// A critical piece of a bundle that is missing on disk
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LayoutIssue {
+    /// Component providing the piece ([`QueryComponent::Msvc`] or [`QueryComponent::Sdk`])
+    pub component: QueryComponent,
+    /// What is missing, e.g. `cl.exe` or `UCRT headers`
+    pub piece: String,
+    /// Expected path of the missing file
+    pub path: PathBuf,
+}
+
+impl std::fmt::Display for LayoutIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} not found: {}", self.piece, self.path.display())
+    }
+}
+
+/// Directory naming convention of a bundle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutStyle {
+    /// Visual Studio names: `VC/Tools/MSVC`, `Windows Kits/10/Include`
+    #[default]
+    Native,
+    /// Lowercase [msvc-wine](https://github.com/mstorsjo/msvc-wine) names:
+    /// `vc/tools/msvc`, `kits/10/include`, plus `bin/<arch>` wrapper scripts
+    MsvcWine,
+}
+
+/// Directories renamed by [`LayoutStyle::MsvcWine`], native name first
+///
+/// Everything below them (version directories, `bin/Host<arch>`, file
+/// names) keeps its case.
+pub const MSVC_WINE_RENAMES: &[(&str, &str)] = &[
+    ("VC", "vc"),
+    ("Tools", "tools"),
+    ("MSVC", "msvc"),
+    ("Windows Kits", "kits"),
+    ("Include", "include"),
+    ("Lib", "lib"),
+];
+
+impl LayoutStyle {
+    /// Name of the directory called `native` in a Visual Studio install
+    pub fn dir_name(&self, native: &'static str) -> &'static str {
+        match self {
+            LayoutStyle::Native => native,
+            LayoutStyle::MsvcWine => MSVC_WINE_RENAMES
+                .iter()
+                .find(|(from, _)| *from == native)
+                .map_or(native, |(_, to)| to),
+        }
+    }
+}
+
+impl std::fmt::Display for LayoutStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutStyle::Native => write!(f, "native"),
+            LayoutStyle::MsvcWine => write!(f, "msvc-wine"),
+        }
+    }
+}
+
+impl std::str::FromStr for LayoutStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "native" => Ok(LayoutStyle::Native),
+            "msvc-wine" | "msvcwine" => Ok(LayoutStyle::MsvcWine),
+            _ => Err(format!(
+                "Unknown layout style: {} (expected native or msvc-wine)",
+                s
+            )),
+        }
+    }
+}
+
+/// Whether `path` exists, comparing its file name case-insensitively
+///
+/// SDK files do not use consistent casing (`Windows.h`, `kernel32.Lib`).
+fn exists_ignore_case(path: &Path) -> bool {
+    if path.exists() {
+        return true;
+    }
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return false;
+    };
+    let name = name.to_string_lossy();
+    std::fs::read_dir(parent)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .any(|e| e.file_name().to_string_lossy().eq_ignore_ascii_case(&name))
+}
+
 /// Bundle directory layout
 ///
 /// Represents the structure of a portable MSVC toolchain bundle and provides
@@ -43,12 +141,16 @@ pub struct BundleLayout {
     pub arch: Architecture,
     /// Host architecture
     pub host_arch: Architecture,
+    /// Directory naming convention
+    #[serde(default)]
+    pub style: LayoutStyle,
 }
 
 impl BundleLayout {
     /// Create a bundle layout from root directory by auto-discovering versions
     ///
     /// Scans the directory structure to find installed MSVC and SDK versions.
+    /// A root with `kits/10` and no `Windows Kits/10` is an msvc-wine layout.
     ///
     /// # Example
     ///
@@ -61,13 +163,24 @@ impl BundleLayout {
     /// ```
     pub fn from_root<P: AsRef<Path>>(root: P) -> Result<Self> {
         let root = root.as_ref().to_path_buf();
+        let style = if root.join("kits").join("10").is_dir()
+            && !root.join("Windows Kits").join("10").is_dir()
+        {
+            LayoutStyle::MsvcWine
+        } else {
+            LayoutStyle::Native
+        };
+        let dir = |native| style.dir_name(native);
 
         // Discover MSVC version
-        let msvc_tools_dir = root.join("VC").join("Tools").join("MSVC");
+        let msvc_tools_dir = root.join(dir("VC")).join(dir("Tools")).join(dir("MSVC"));
         let msvc_version = Self::discover_version(&msvc_tools_dir)?;
 
         // Discover SDK version
-        let sdk_include_dir = root.join("Windows Kits").join("10").join("Include");
+        let sdk_include_dir = root
+            .join(dir("Windows Kits"))
+            .join("10")
+            .join(dir("Include"));
         let sdk_version = Self::discover_version(&sdk_include_dir)?;
 
         // Default to host architecture
@@ -80,6 +193,7 @@ impl BundleLayout {
             sdk_version,
             arch,
             host_arch,
+            style,
         })
     }
 
@@ -97,9 +211,16 @@ impl BundleLayout {
             sdk_version: sdk_version.to_string(),
             arch,
             host_arch,
+            style: LayoutStyle::Native,
         })
     }
 
+    /// The same bundle with `style` directory names
+    pub fn with_style(mut self, style: LayoutStyle) -> Self {
+        self.style = style;
+        self
+    }
+
     /// Discover version from a directory containing version subdirectories
     fn discover_version(dir: &Path) -> Result<String> {
         if !dir.exists() {
@@ -142,19 +263,18 @@ impl BundleLayout {
 
     /// Get VC installation directory
     ///
-    /// Returns: `{root}/VC`
+    /// Returns: `{root}/VC` (`{root}/vc` in the msvc-wine style)
     pub fn vc_dir(&self) -> PathBuf {
-        self.root.join("VC")
+        self.root.join(self.style.dir_name("VC"))
     }
 
     /// Get VC Tools installation directory
     ///
     /// Returns: `{root}/VC/Tools/MSVC/{version}`
     pub fn vc_tools_dir(&self) -> PathBuf {
-        self.root
-            .join("VC")
-            .join("Tools")
-            .join("MSVC")
+        self.vc_dir()
+            .join(self.style.dir_name("Tools"))
+            .join(self.style.dir_name("MSVC"))
             .join(&self.msvc_version)
     }
 
@@ -182,13 +302,27 @@ impl BundleLayout {
             .join(self.arch.msvc_target_dir())
     }
 
+    /// Get the host-native VC binary directory
+    ///
+    /// Returns: `{root}/VC/Tools/MSVC/{version}/bin/Host{host}/{host}`. Cross
+    /// compilers load some DLLs from here, so it follows [`vc_bin_dir`](Self::vc_bin_dir)
+    /// in `PATH` when host and target differ.
+    pub fn vc_host_bin_dir(&self) -> PathBuf {
+        self.vc_tools_dir()
+            .join("bin")
+            .join(self.host_arch.msvc_host_dir())
+            .join(self.host_arch.msvc_target_dir())
+    }
+
     // ==================== SDK Paths ====================
 
     /// Get Windows SDK root directory
     ///
-    /// Returns: `{root}/Windows Kits/10`
+    /// Returns: `{root}/Windows Kits/10` (`{root}/kits/10` in the msvc-wine style)
     pub fn sdk_dir(&self) -> PathBuf {
-        self.root.join("Windows Kits").join("10")
+        self.root
+            .join(self.style.dir_name("Windows Kits"))
+            .join("10")
     }
 
     /// Get SDK include directory for a specific component
@@ -196,7 +330,7 @@ impl BundleLayout {
     /// Returns: `{root}/Windows Kits/10/Include/{version}/{component}`
     pub fn sdk_include_dir(&self, component: &str) -> PathBuf {
         self.sdk_dir()
-            .join("Include")
+            .join(self.style.dir_name("Include"))
             .join(&self.sdk_version)
             .join(component)
     }
@@ -217,7 +351,7 @@ impl BundleLayout {
     /// Returns: `{root}/Windows Kits/10/Lib/{version}/{component}/{arch}`
     pub fn sdk_lib_dir(&self, component: &str) -> PathBuf {
         self.sdk_dir()
-            .join("Lib")
+            .join(self.style.dir_name("Lib"))
             .join(&self.sdk_version)
             .join(component)
             .join(self.arch.to_string())
@@ -230,12 +364,13 @@ impl BundleLayout {
 
     /// Get SDK binary directory
     ///
-    /// Returns: `{root}/Windows Kits/10/bin/{version}/{arch}`
+    /// Returns: `{root}/Windows Kits/10/bin/{version}/{host}`; the SDK tools
+    /// run on the host whatever the target.
     pub fn sdk_bin_dir(&self) -> PathBuf {
         self.sdk_dir()
             .join("bin")
             .join(&self.sdk_version)
-            .join(self.arch.to_string())
+            .join(self.host_arch.to_string())
     }
 
     // ==================== Tool Paths ====================
@@ -288,7 +423,12 @@ impl BundleLayout {
 
     /// Get all binary paths
     pub fn bin_paths(&self) -> Vec<PathBuf> {
-        vec![self.vc_bin_dir(), self.sdk_bin_dir()]
+        let mut paths = vec![self.vc_bin_dir()];
+        if self.host_arch != self.arch {
+            paths.push(self.vc_host_bin_dir());
+        }
+        paths.push(self.sdk_bin_dir());
+        paths
     }
 
     /// Get INCLUDE environment variable value
@@ -371,6 +511,61 @@ impl BundleLayout {
         Ok(())
     }
 
+    /// Check the bundle for missing critical pieces
+    ///
+    /// Unlike [`verify`](Self::verify), which stops at the first missing
+    /// directory, this checks for one key file each of the compiler, linker,
+    /// VC headers and libraries, UCRT and Windows headers and libraries and
+    /// the resource compiler, and reports every one that is missing with its
+    /// expected path. An empty list means the bundle is usable.
+    pub fn validate(&self) -> Vec<LayoutIssue> {
+        let checks = [
+            (QueryComponent::Msvc, "cl.exe", self.cl_exe_path()),
+            (QueryComponent::Msvc, "link.exe", self.link_exe_path()),
+            (
+                QueryComponent::Msvc,
+                "VC headers",
+                self.vc_include_dir().join("vcruntime.h"),
+            ),
+            (
+                QueryComponent::Msvc,
+                "VC libraries",
+                self.vc_lib_dir().join("msvcrt.lib"),
+            ),
+            (
+                QueryComponent::Sdk,
+                "UCRT headers",
+                self.sdk_include_dir("ucrt").join("corecrt.h"),
+            ),
+            (
+                QueryComponent::Sdk,
+                "Windows headers",
+                self.sdk_include_dir("um").join("windows.h"),
+            ),
+            (
+                QueryComponent::Sdk,
+                "UCRT libraries",
+                self.sdk_lib_dir("ucrt").join("ucrt.lib"),
+            ),
+            (
+                QueryComponent::Sdk,
+                "Windows libraries",
+                self.sdk_lib_dir("um").join("kernel32.lib"),
+            ),
+            (QueryComponent::Sdk, "rc.exe", self.rc_exe_path()),
+        ];
+
+        checks
+            .into_iter()
+            .filter(|(_, _, path)| !exists_ignore_case(path))
+            .map(|(component, piece, path)| LayoutIssue {
+                component,
+                piece: piece.to_string(),
+                path,
+            })
+            .collect()
+    }
+
     /// Export layout to JSON
     pub fn to_json(&self) -> serde_json::Value {
         serde_json::json!({
@@ -379,6 +574,7 @@ impl BundleLayout {
             "sdk_version": self.sdk_version,
             "arch": self.arch.to_string(),
             "host_arch": self.host_arch.to_string(),
+            "style": self.style,
             "paths": {
                 "vc_dir": self.vc_dir(),
                 "vc_tools_dir": self.vc_tools_dir(),
@@ -416,6 +612,7 @@ mod tests {
             sdk_version: "10.0.26100.0".to_string(),
             arch: Architecture::X64,
             host_arch: Architecture::X64,
+            style: LayoutStyle::Native,
         };
 
         assert_eq!(
@@ -430,6 +627,65 @@ mod tests {
             layout.sdk_bin_dir(),
             PathBuf::from("C:/msvc-bundle/Windows Kits/10/bin/10.0.26100.0/x64")
         );
+        assert_eq!(layout.bin_paths().len(), 2);
+    }
+
+    #[test]
+    fn test_bundle_layout_cross_bin_paths() {
+        let layout = BundleLayout {
+            root: PathBuf::from("C:/msvc-bundle"),
+            msvc_version: "14.44.34823".to_string(),
+            sdk_version: "10.0.26100.0".to_string(),
+            arch: Architecture::X86,
+            host_arch: Architecture::X64,
+            style: LayoutStyle::Native,
+        };
+
+        assert_eq!(
+            layout.bin_paths(),
+            vec![
+                PathBuf::from("C:/msvc-bundle/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x86"),
+                PathBuf::from("C:/msvc-bundle/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64"),
+                PathBuf::from("C:/msvc-bundle/Windows Kits/10/bin/10.0.26100.0/x64"),
+            ]
+        );
+        assert!(layout.vc_lib_dir().ends_with("lib/x86"));
+    }
+
+    #[test]
+    fn test_bundle_layout_validate() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layout = BundleLayout::from_root_with_versions(
+            temp_dir.path(),
+            "14.44.34823",
+            "10.0.26100.0",
+            Architecture::X64,
+            Architecture::X64,
+        )
+        .unwrap();
+        assert_eq!(layout.validate().len(), 9);
+
+        for file in [
+            layout.cl_exe_path(),
+            layout.link_exe_path(),
+            layout.vc_include_dir().join("vcruntime.h"),
+            layout.vc_lib_dir().join("msvcrt.lib"),
+            layout.sdk_include_dir("ucrt").join("corecrt.h"),
+            layout.sdk_include_dir("um").join("Windows.h"),
+            layout.sdk_lib_dir("um").join("kernel32.Lib"),
+            layout.rc_exe_path(),
+        ] {
+            std::fs::create_dir_all(file.parent().unwrap()).unwrap();
+            std::fs::write(file, "").unwrap();
+        }
+
+        let issues = layout.validate();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].component, QueryComponent::Sdk);
+        assert_eq!(issues[0].path, layout.sdk_lib_dir("ucrt").join("ucrt.lib"));
+        assert!(issues[0]
+            .to_string()
+            .starts_with("UCRT libraries not found: "));
     }
 
     #[test]
@@ -440,6 +696,7 @@ mod tests {
             sdk_version: "10.0.26100.0".to_string(),
             arch: Architecture::X64,
             host_arch: Architecture::X64,
+            style: LayoutStyle::Native,
         };
 
         let include = layout.include_env();
@@ -37,11 +37,16 @@
 //!                 └── {arch}/         # rc.exe, etc.
 //! ```
 //!
+//! With [`LayoutStyle::MsvcWine`], the directories are named the way
+//! [msvc-wine](https://github.com/mstorsjo/msvc-wine) names them
+//! (`vc/tools/msvc`, `kits/10/include`, `kits/10/lib`) and `bin/{arch}` holds
+//! its `msvcenv.sh` and tool wrappers; see [`msvc_wine`].
+//!
 //! # Example
 //!
 //! ```rust,no_run
-//! use msvc_kit::bundle::{BundleLayout, BundleOptions, create_bundle};
-//! use msvc_kit::Architecture;
+//! use msvc_kit::bundle::{BundleLayout, BundleOptions, LayoutStyle, create_bundle};
+//! use msvc_kit::{Architecture, Locale};
 //! use std::path::PathBuf;
 //!
 //! #[tokio::main]
@@ -54,6 +59,11 @@
 //!         msvc_version: None,  // Use latest
 //!         sdk_version: None,   // Use latest
 //!         parallel_downloads: 8,
+//!         post_install_hooks: vec![],
+//!         locale: Locale::En,
+//!         offline: false,
+//!         dry_run: false,
+//!         layout_style: LayoutStyle::Native,
 //!     };
 //!     
 //!     let result = create_bundle(options).await?;
@@ -68,19 +78,46 @@
 //! ```
 
 mod layout;
+pub mod msvc_wine;
+mod plan;
+pub mod provision;
+mod repair;
 pub mod scripts;
+mod stats;
 
-pub use layout::BundleLayout;
-pub use scripts::{generate_bundle_scripts, save_bundle_scripts, BundleScripts};
+pub use layout::{BundleLayout, LayoutIssue, LayoutStyle, MSVC_WINE_RENAMES};
+pub use plan::{plan_bundle, BundlePlan};
+pub use provision::{
+    render_dockerfile, render_sandbox_config, write_dockerfile, write_sandbox_config,
+    ProvisionOptions,
+};
+pub use repair::RepairReport;
+pub use scripts::{
+    generate_bundle_scripts, generate_bundle_scripts_with_locale, save_bundle_scripts,
+    BundleScripts,
+};
+pub use stats::{payload_bytes, write_bundle_manifest, BundleStats, BUNDLE_MANIFEST_FILE};
 
-use crate::downloader::{download_msvc, download_sdk, DownloadOptions};
+use crate::downloader::{
+    download_msvc, download_sdk, AppPlatform, BoxedCacheManager, BoxedProgressHandler,
+    DownloadOptions, FileSystemCacheManager, IndicatifProgressHandler, VerifyMode,
+};
 use crate::error::{MsvcKitError, Result};
-use crate::installer::InstallInfo;
+use crate::installer::{run_post_install_hooks, BoxedPostInstallHook, InstallInfo};
+use crate::scripts::Locale;
 use crate::version::Architecture;
+use crate::warning::Warning;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
 
 /// Options for creating a bundle
-#[derive(Debug, Clone)]
+///
+/// Serializable like [`DownloadOptions`]; the hooks are skipped and missing
+/// fields take their [`Default`] values.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BundleOptions {
     /// Output directory for the bundle
     pub output_dir: PathBuf,
@@ -94,6 +131,36 @@ pub struct BundleOptions {
     pub sdk_version: Option<String>,
     /// Number of parallel downloads
     pub parallel_downloads: usize,
+    /// Hooks run once the bundle has been extracted
+    #[serde(skip)]
+    pub post_install_hooks: Vec<BoxedPostInstallHook>,
+    /// Language of the README and activation script messages
+    pub locale: Locale,
+    /// Use cached manifests and payloads only, never the network
+    pub offline: bool,
+    /// Only resolve versions and packages; see [`BundleResult::plan`]
+    pub dry_run: bool,
+    /// Directory naming convention; [`LayoutStyle::MsvcWine`] also writes
+    /// msvc-wine's `bin/{arch}` wrapper scripts
+    pub layout_style: LayoutStyle,
+}
+
+impl std::fmt::Debug for BundleOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BundleOptions")
+            .field("output_dir", &self.output_dir)
+            .field("arch", &self.arch)
+            .field("host_arch", &self.host_arch)
+            .field("msvc_version", &self.msvc_version)
+            .field("sdk_version", &self.sdk_version)
+            .field("parallel_downloads", &self.parallel_downloads)
+            .field("post_install_hooks", &self.post_install_hooks.len())
+            .field("locale", &self.locale)
+            .field("offline", &self.offline)
+            .field("dry_run", &self.dry_run)
+            .field("layout_style", &self.layout_style)
+            .finish()
+    }
 }
 
 impl Default for BundleOptions {
@@ -105,6 +172,11 @@ impl Default for BundleOptions {
             msvc_version: None,
             sdk_version: None,
             parallel_downloads: 8,
+            post_install_hooks: Vec::new(),
+            locale: Locale::default(),
+            offline: false,
+            dry_run: false,
+            layout_style: LayoutStyle::default(),
         }
     }
 }
@@ -120,6 +192,13 @@ pub struct BundleResult {
     pub sdk_info: InstallInfo,
     /// Generated scripts
     pub scripts: BundleScripts,
+    /// Download plan, set instead of downloading when `dry_run` is enabled
+    pub plan: Option<BundlePlan>,
+    /// Warnings of both components, MSVC first
+    pub warnings: Vec<Warning>,
+    /// Size of the bundle and time taken, also written to
+    /// [`BUNDLE_MANIFEST_FILE`]; all zero for a dry run
+    pub stats: BundleStats,
 }
 
 /// Create a portable MSVC toolchain bundle
@@ -127,6 +206,13 @@ pub struct BundleResult {
 /// Downloads MSVC and Windows SDK components and organizes them into
 /// a portable bundle structure.
 ///
+/// With `options.dry_run`, nothing is downloaded or written: the result
+/// describes the bundle that would be created and carries the
+/// [`BundlePlan`] in [`BundleResult::plan`].
+///
+/// Otherwise the finished bundle is measured into [`BundleResult::stats`]
+/// and described in `bundle.json` at its root.
+///
 /// # Arguments
 ///
 /// * `options` - Bundle creation options
@@ -155,53 +241,290 @@ pub struct BundleResult {
 /// }
 /// ```
 pub async fn create_bundle(options: BundleOptions) -> Result<BundleResult> {
+    if options.dry_run {
+        let plan = plan::plan_for(&options, &options.output_dir, options.arch).await?;
+        return Ok(planned_result(plan));
+    }
+    let started = Instant::now();
+
     // Create output directory
     tokio::fs::create_dir_all(&options.output_dir)
         .await
         .map_err(MsvcKitError::Io)?;
 
     // Download options - download directly to bundle root
-    let download_opts = DownloadOptions {
+    let download_opts = bundle_download_options(&options, &options.output_dir, options.arch);
+
+    // Download MSVC and SDK, then extract both
+    let mut msvc_info = download_msvc(&download_opts).await?;
+    let mut sdk_info = download_sdk(&download_opts).await?;
+    let download_bytes = payload_bytes(&[&msvc_info, &sdk_info]);
+    extract_components(&mut msvc_info, &mut sdk_info).await?;
+
+    let mut result = finish_bundle(
+        &options,
+        &options.output_dir,
+        options.arch,
+        msvc_info,
+        sdk_info,
+    )
+    .await?;
+    record_stats(&mut result, download_bytes, started, true).await?;
+    Ok(result)
+}
+
+/// Create bundles for several target architectures in one run
+///
+/// Downloads for all architectures run concurrently, each with its own line
+/// in a combined progress view. Payloads go through the shared payload cache,
+/// so architecture-neutral packages (headers, CRT sources, ...) are only
+/// downloaded once.
+///
+/// With `split`, each architecture gets its own bundle in
+/// `output_dir/<arch>`; otherwise all architectures are installed into one
+/// multi-arch bundle in `output_dir`. `options.arch` is ignored.
+/// `options.dry_run` returns one planned result per architecture.
+///
+/// Each result carries the [`BundleStats`] of its root, measured once all
+/// architectures are extracted. A multi-arch bundle gets one `bundle.json`,
+/// describing the first architecture like its `setup.*` scripts, with the
+/// stats of the whole bundle.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::bundle::{create_multi_arch_bundles, BundleOptions};
+/// use msvc_kit::Architecture;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let options = BundleOptions {
+///         output_dir: "./msvc-bundle".into(),
+///         ..Default::default()
+///     };
+///     let results =
+///         create_multi_arch_bundles(options, &[Architecture::X64, Architecture::Arm64], true)
+///             .await?;
+///     for result in results {
+///         println!("{} bundle: {:?}", result.layout.arch, result.layout.root);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn create_multi_arch_bundles(
+    options: BundleOptions,
+    arches: &[Architecture],
+    split: bool,
+) -> Result<Vec<BundleResult>> {
+    let mut targets: Vec<Architecture> = Vec::with_capacity(arches.len());
+    for &arch in arches {
+        if !targets.contains(&arch) {
+            targets.push(arch);
+        }
+    }
+
+    if options.dry_run {
+        let mut results = Vec::with_capacity(targets.len());
+        for &arch in &targets {
+            let root = bundle_root(&options.output_dir, arch, split);
+            results.push(planned_result(plan::plan_for(&options, &root, arch).await?));
+        }
+        return Ok(results);
+    }
+    let started = Instant::now();
+
+    let cache: BoxedCacheManager = Arc::new(FileSystemCacheManager::default_cache_dir());
+    let multi = indicatif::MultiProgress::new();
+
+    let downloads = targets.iter().map(|&arch| {
+        let root = bundle_root(&options.output_dir, arch, split);
+        let progress: BoxedProgressHandler =
+            Arc::new(IndicatifProgressHandler::in_multi(&multi, arch.to_string()));
+        let mut download_opts = bundle_download_options(&options, &root, arch);
+        download_opts.cache_manager = Some(cache.clone());
+        download_opts.progress_handler = Some(progress);
+
+        async move {
+            tokio::fs::create_dir_all(&root)
+                .await
+                .map_err(MsvcKitError::Io)?;
+            let msvc_info = download_msvc(&download_opts).await?;
+            let sdk_info = download_sdk(&download_opts).await?;
+            Ok::<_, MsvcKitError>((root, arch, msvc_info, sdk_info))
+        }
+    });
+    let downloaded = futures::future::try_join_all(downloads).await?;
+
+    // Extract one architecture at a time: a multi-arch bundle shares its
+    // architecture-neutral files between architectures
+    let mut results = Vec::with_capacity(downloaded.len());
+    let mut download_bytes = Vec::with_capacity(downloaded.len());
+    for (root, arch, mut msvc_info, mut sdk_info) in downloaded {
+        download_bytes.push(payload_bytes(&[&msvc_info, &sdk_info]));
+        extract_components(&mut msvc_info, &mut sdk_info).await?;
+        results.push(finish_bundle(&options, &root, arch, msvc_info, sdk_info).await?);
+    }
+    // A multi-arch bundle is measured as a whole, including every download
+    let shared_bytes: u64 = download_bytes.iter().sum();
+    for (i, (result, bytes)) in results.iter_mut().zip(download_bytes).enumerate() {
+        let bytes = if split { bytes } else { shared_bytes };
+        record_stats(result, bytes, started, split || i == 0).await?;
+    }
+    Ok(results)
+}
+
+/// Measure the bundle of `result`, writing its `bundle.json` if `write_manifest`
+async fn record_stats(
+    result: &mut BundleResult,
+    download_bytes: u64,
+    started: Instant,
+    write_manifest: bool,
+) -> Result<()> {
+    result.stats =
+        BundleStats::collect(&result.layout.root, download_bytes, started.elapsed()).await?;
+    if write_manifest {
+        write_bundle_manifest(&result.layout, &result.stats).await?;
+    }
+    Ok(())
+}
+
+/// Root of the `arch` bundle in a multi-arch run
+fn bundle_root(output_dir: &Path, arch: Architecture, split: bool) -> PathBuf {
+    if split {
+        output_dir.join(arch.to_string())
+    } else {
+        output_dir.to_path_buf()
+    }
+}
+
+/// Result of a dry run, describing the bundle `plan` would create
+fn planned_result(plan: BundlePlan) -> BundleResult {
+    let info = |component_type: &str, version: &str, install_path: PathBuf| InstallInfo {
+        component_type: component_type.to_string(),
+        version: version.to_string(),
+        install_path,
+        downloaded_files: Vec::new(),
+        arch: plan.layout.arch,
+        extract_filters: Default::default(),
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
+    };
+    BundleResult {
+        msvc_info: info(
+            "msvc",
+            &plan.layout.msvc_version,
+            plan.layout.vc_tools_dir(),
+        ),
+        sdk_info: info("sdk", &plan.layout.sdk_version, plan.layout.sdk_dir()),
+        layout: plan.layout.clone(),
+        scripts: plan.scripts.clone(),
+        plan: Some(plan),
+        warnings: Vec::new(),
+        stats: BundleStats::default(),
+    }
+}
+
+/// Download options installing directly into a bundle root
+fn bundle_download_options(
+    options: &BundleOptions,
+    root: &Path,
+    arch: Architecture,
+) -> DownloadOptions {
+    DownloadOptions {
         msvc_version: options.msvc_version.clone(),
         sdk_version: options.sdk_version.clone(),
-        target_dir: options.output_dir.clone(),
-        arch: options.arch,
+        channel: Default::default(),
+        target_dir: root.to_path_buf(),
+        arch,
         host_arch: Some(options.host_arch),
-        verify_hashes: true,
+        verify_mode: VerifyMode::Enforce,
+        hash_exemptions: Vec::new(),
         parallel_downloads: options.parallel_downloads,
         http_client: None,
+        user_agent: None,
         progress_handler: None,
         cache_manager: None,
+        auth_provider: None,
+        version_resolver: None,
         dry_run: false,
+        offline: options.offline,
+        layout_dir: None,
+        headers_only: false,
         include_components: Default::default(),
+        app_platforms: AppPlatform::defaults(),
         exclude_patterns: Default::default(),
-    };
-
-    // Download and extract MSVC
-    let mut msvc_info = download_msvc(&download_opts).await?;
-    crate::installer::extract_and_finalize_msvc(&mut msvc_info).await?;
+        only_patterns: Default::default(),
+        allow_broken_selection: false,
+        sdk_installer_patterns: Vec::new(),
+        extract_filters: Default::default(),
+        auto_cleanup: Default::default(),
+        post_install_hooks: Vec::new(),
+        overall_timeout: None,
+        per_file_timeout: None,
+        max_download_rate: None,
+        chunked_downloads: None,
+        alternate_endpoints: Vec::new(),
+        continue_on_optional_failure: false,
+        allow_http_fallback: false,
+    }
+}
 
-    // Download and extract SDK
-    let sdk_info = download_sdk(&download_opts).await?;
-    crate::installer::extract_and_finalize_sdk(&sdk_info).await?;
+/// Extract downloaded MSVC and SDK packages, recording extraction warnings
+async fn extract_components(msvc_info: &mut InstallInfo, sdk_info: &mut InstallInfo) -> Result<()> {
+    crate::installer::extract_and_finalize_msvc(msvc_info).await?;
+    let report = crate::installer::extract_and_finalize_sdk_with_report(sdk_info, false).await?;
+    sdk_info.warnings.extend(report.to_warnings());
+    Ok(())
+}
 
+/// Run the hooks and generate the scripts of an extracted bundle
+async fn finish_bundle(
+    options: &BundleOptions,
+    root: &Path,
+    arch: Architecture,
+    msvc_info: InstallInfo,
+    sdk_info: InstallInfo,
+) -> Result<BundleResult> {
     // Create bundle layout from the installed files
     let layout = BundleLayout::from_root_with_versions(
-        &options.output_dir,
+        root,
         &msvc_info.version,
         &sdk_info.version,
-        options.arch,
+        arch,
         options.host_arch,
-    )?;
+    )?
+    .with_style(options.layout_style);
+    if options.layout_style == LayoutStyle::MsvcWine {
+        msvc_wine::convert_to_msvc_wine(root)?;
+        msvc_wine::save_msvc_wine_scripts(&layout).await?;
+    }
+
+    run_post_install_hooks(
+        &options.post_install_hooks,
+        &[&msvc_info, &sdk_info],
+        &layout.to_msvc_environment(),
+    )
+    .await?;
 
     // Generate activation scripts
-    let scripts = generate_bundle_scripts(&layout)?;
+    let scripts = generate_bundle_scripts_with_locale(&layout, options.locale)?;
 
+    let warnings = msvc_info
+        .warnings
+        .iter()
+        .chain(&sdk_info.warnings)
+        .cloned()
+        .collect();
     Ok(BundleResult {
         layout,
         msvc_info,
         sdk_info,
         scripts,
+        plan: None,
+        warnings,
+        stats: BundleStats::default(),
     })
 }
 
@@ -248,19 +571,56 @@ mod tests {
         let download_opts = DownloadOptions {
             msvc_version: opts.msvc_version.clone(),
             sdk_version: opts.sdk_version.clone(),
+            channel: Default::default(),
             target_dir: opts.output_dir.clone(),
             arch: opts.arch,
             host_arch: Some(opts.host_arch),
-            verify_hashes: true,
+            verify_mode: VerifyMode::Enforce,
+            hash_exemptions: Vec::new(),
             parallel_downloads: opts.parallel_downloads,
             http_client: None,
+            user_agent: None,
             progress_handler: None,
             cache_manager: None,
+            auth_provider: None,
+            version_resolver: None,
             dry_run: false,
+            offline: false,
+            layout_dir: None,
+            headers_only: false,
             include_components: Default::default(),
+            app_platforms: AppPlatform::defaults(),
             exclude_patterns: Default::default(),
+            only_patterns: Default::default(),
+            allow_broken_selection: false,
+            sdk_installer_patterns: Vec::new(),
+            extract_filters: Default::default(),
+            auto_cleanup: Default::default(),
+            post_install_hooks: Vec::new(),
+            overall_timeout: None,
+            per_file_timeout: None,
+            max_download_rate: None,
+            chunked_downloads: None,
+            alternate_endpoints: Vec::new(),
+            continue_on_optional_failure: false,
+            allow_http_fallback: false,
         };
         assert!(download_opts.cache_manager.is_none());
         assert!(!download_opts.dry_run);
     }
+
+    #[test]
+    fn test_bundle_download_options_for_arch() {
+        let opts = BundleOptions {
+            offline: true,
+            ..Default::default()
+        };
+        let root = opts.output_dir.join("arm64");
+        let download_opts = bundle_download_options(&opts, &root, Architecture::Arm64);
+        assert_eq!(download_opts.target_dir, root);
+        assert_eq!(download_opts.arch, Architecture::Arm64);
+        assert_eq!(download_opts.host_arch, Some(opts.host_arch));
+        assert!(download_opts.offline);
+        assert!(download_opts.cache_manager.is_none());
+    }
 }
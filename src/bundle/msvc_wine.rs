@@ -0,0 +1,315 @@
+//! msvc-wine compatible bundles
+//!
+//! [msvc-wine](https://github.com/mstorsjo/msvc-wine) installs the toolchain
+//! with lowercase directory names (`vc/tools/msvc`, `kits/10`) and puts
+//! wrapper scripts into `bin/<arch>` that run the tools under Wine, with
+//! `INCLUDE` and `LIB` set by `bin/<arch>/msvcenv.sh`. Linux cross-compile
+//! setups written against it (`CC=/opt/msvc/bin/x64/cl`, CMake toolchain
+//! files sourcing `msvcenv.sh`) work unchanged on a bundle created with
+//! [`LayoutStyle::MsvcWine`].
+
+use std::path::{Path, PathBuf};
+
+use super::{BundleLayout, LayoutStyle};
+use crate::error::{MsvcKitError, Result};
+
+/// Directory below the bundle root holding the per-architecture wrappers
+pub const WRAPPER_DIR: &str = "bin";
+
+/// Environment script sourced by every wrapper
+pub const ENV_SCRIPT_NAME: &str = "msvcenv.sh";
+
+/// Wrapped tools from the MSVC `bin/Host<host>/<arch>` directory
+pub const MSVC_TOOLS: &[&str] = &["cl", "link", "lib", "dumpbin", "editbin", "nmake"];
+
+/// Wrapped tools from the Windows SDK `bin/<version>/<host>` directory
+pub const SDK_TOOLS: &[&str] = &["rc", "mt", "midl"];
+
+/// Renames, parent directory first, turning a native layout into an msvc-wine one
+const RENAMES: &[(&str, &str)] = &[
+    ("", "VC"),
+    ("vc", "Tools"),
+    ("vc/tools", "MSVC"),
+    ("", "Windows Kits"),
+    ("kits/10", "Include"),
+    ("kits/10", "Lib"),
+];
+
+/// Directory holding the wrappers of `layout`: `{root}/bin/{arch}`
+pub fn wrapper_dir(layout: &BundleLayout) -> PathBuf {
+    layout.root.join(WRAPPER_DIR).join(layout.arch.to_string())
+}
+
+/// Rename the native directories below `root` to their msvc-wine names
+///
+/// Directories already in msvc-wine form are left alone; when both forms
+/// exist (a second architecture extracted into a converted multi-arch
+/// bundle), the native one is merged into the msvc-wine one.
+pub fn convert_to_msvc_wine(root: &Path) -> Result<()> {
+    for (parent, native) in RENAMES {
+        let parent = root.join(parent);
+        let renamed = LayoutStyle::MsvcWine.dir_name(native);
+        if has_entry(&parent, native)? {
+            rename_dir(&parent, native, renamed)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `dir` has an entry named exactly `name`, whatever the file system's case rules
+fn has_entry(dir: &Path, name: &str) -> Result<bool> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Ok(false);
+    };
+    for entry in entries {
+        if entry.map_err(MsvcKitError::Io)?.file_name() == name {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn rename_dir(parent: &Path, from: &str, to: &str) -> Result<()> {
+    let source = parent.join(from);
+    if has_entry(parent, to)? {
+        return merge_dir(&source, &parent.join(to));
+    }
+    // Go through a temporary name: on case-insensitive file systems `VC` and
+    // `vc` are the same entry and a direct rename may keep the old case
+    let temp = parent.join(format!("{}.msvc-kit-rename", to));
+    std::fs::rename(&source, &temp).map_err(MsvcKitError::Io)?;
+    std::fs::rename(&temp, parent.join(to)).map_err(MsvcKitError::Io)
+}
+
+/// Move everything in `source` into `target`, replacing files, then remove `source`
+fn merge_dir(source: &Path, target: &Path) -> Result<()> {
+    for entry in std::fs::read_dir(source).map_err(MsvcKitError::Io)? {
+        let entry = entry.map_err(MsvcKitError::Io)?;
+        let destination = target.join(entry.file_name());
+        if entry.file_type().map_err(MsvcKitError::Io)?.is_dir() && destination.is_dir() {
+            merge_dir(&entry.path(), &destination)?;
+        } else {
+            std::fs::rename(entry.path(), &destination).map_err(MsvcKitError::Io)?;
+        }
+    }
+    std::fs::remove_dir(source).map_err(MsvcKitError::Io)
+}
+
+/// `msvcenv.sh` of `layout`, setting the variables msvc-wine's script sets
+///
+/// The bundle root is found relative to the script, so the bundle can be
+/// moved; Windows paths go through Wine's `z:` drive.
+pub fn render_msvcenv(layout: &BundleLayout) -> String {
+    format!(
+        r#"#!/usr/bin/env bash
+# msvc-wine compatible environment, generated by msvc-kit
+# MSVC {msvc}, SDK {sdk}, {arch}
+BASE_UNIX="$(cd "$(dirname "${{BASH_SOURCE[0]}}")/../.." && pwd)"
+BASE="z:${{BASE_UNIX//\//\\}}"
+MSVCVER={msvc}
+SDKVER={sdk}
+ARCH={arch}
+MSVCDIR="$BASE\\vc\\tools\\msvc\\$MSVCVER"
+SDKINCLUDE="$BASE\\kits\\10\\include\\$SDKVER"
+SDKLIB="$BASE\\kits\\10\\lib\\$SDKVER"
+BINDIR="$BASE_UNIX/vc/tools/msvc/$MSVCVER/bin/{host_dir}/{target_dir}"
+SDKBINDIR="$BASE_UNIX/kits/10/bin/$SDKVER/{host}"
+export INCLUDE="$MSVCDIR\\include;$SDKINCLUDE\\shared;$SDKINCLUDE\\ucrt;$SDKINCLUDE\\um;$SDKINCLUDE\\winrt;$SDKINCLUDE\\cppwinrt"
+export LIB="$MSVCDIR\\lib\\$ARCH;$SDKLIB\\ucrt\\$ARCH;$SDKLIB\\um\\$ARCH"
+export LIBPATH="$LIB"
+"#,
+        msvc = layout.msvc_version,
+        sdk = layout.sdk_version,
+        arch = layout.arch,
+        host = layout.host_arch,
+        host_dir = layout.host_arch.msvc_host_dir(),
+        target_dir = layout.arch.msvc_target_dir(),
+    )
+}
+
+/// Wrapper running `tool.exe` from `$BINDIR` or `$SDKBINDIR` under Wine (`$WINE`, default `wine`)
+pub fn render_wrapper(tool: &str, bin_dir_var: &str) -> String {
+    format!(
+        "#!/usr/bin/env bash\n\
+         . \"$(dirname \"${{BASH_SOURCE[0]}}\")/{}\"\n\
+         exec \"${{WINE:-wine}}\" \"${}/{}.exe\" \"$@\"\n",
+        ENV_SCRIPT_NAME, bin_dir_var, tool
+    )
+}
+
+/// Write `msvcenv.sh` and the tool wrappers of `layout` into [`wrapper_dir`]
+///
+/// Returns the written files, which are made executable outside Windows.
+pub async fn save_msvc_wine_scripts(layout: &BundleLayout) -> Result<Vec<PathBuf>> {
+    let dir = wrapper_dir(layout);
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(MsvcKitError::Io)?;
+
+    let mut files = vec![(dir.join(ENV_SCRIPT_NAME), render_msvcenv(layout))];
+    for tool in MSVC_TOOLS {
+        files.push((dir.join(tool), render_wrapper(tool, "BINDIR")));
+    }
+    for tool in SDK_TOOLS {
+        files.push((dir.join(tool), render_wrapper(tool, "SDKBINDIR")));
+    }
+
+    let mut written = Vec::with_capacity(files.len());
+    for (path, contents) in files {
+        tokio::fs::write(&path, contents)
+            .await
+            .map_err(MsvcKitError::Io)?;
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))
+                .await
+                .map_err(MsvcKitError::Io)?;
+        }
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Rewrite the native directory names in rendered activation scripts
+pub(super) fn rewrite_script_paths(script: &str) -> String {
+    let mut script = script.to_string();
+    for sep in ['\\', '/'] {
+        let replacements = [
+            (
+                format!("{s}VC{s}Tools{s}MSVC{s}", s = sep),
+                format!("{s}vc{s}tools{s}msvc{s}", s = sep),
+            ),
+            (format!("{}VC\"", sep), format!("{}vc\"", sep)),
+            (
+                format!("{s}Windows Kits{s}10{s}Include{s}", s = sep),
+                format!("{s}kits{s}10{s}include{s}", s = sep),
+            ),
+            (
+                format!("{s}Windows Kits{s}10{s}Lib{s}", s = sep),
+                format!("{s}kits{s}10{s}lib{s}", s = sep),
+            ),
+            (
+                format!("{s}Windows Kits{s}10", s = sep),
+                format!("{s}kits{s}10", s = sep),
+            ),
+        ];
+        for (from, to) in &replacements {
+            script = script.replace(from, to);
+        }
+    }
+    script
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::Architecture;
+
+    fn write(path: &Path) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"x").unwrap();
+    }
+
+    fn names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_convert_to_msvc_wine() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        write(&root.join("VC/Tools/MSVC/14.44.34823/include/vector"));
+        write(&root.join("Windows Kits/10/Include/10.0.26100.0/um/windows.h"));
+        write(&root.join("Windows Kits/10/Lib/10.0.26100.0/um/x64/kernel32.lib"));
+        write(&root.join("Windows Kits/10/bin/10.0.26100.0/x64/rc.exe"));
+
+        convert_to_msvc_wine(root).unwrap();
+        assert_eq!(names(root), vec!["kits", "vc"]);
+        assert_eq!(names(&root.join("kits/10")), vec!["bin", "include", "lib"]);
+        assert!(root
+            .join("vc/tools/msvc/14.44.34823/include/vector")
+            .is_file());
+
+        let layout = BundleLayout::from_root(root).unwrap();
+        assert_eq!(layout.style, LayoutStyle::MsvcWine);
+        assert_eq!(layout.msvc_version, "14.44.34823");
+        assert!(layout.sdk_include_dir("um").join("windows.h").is_file());
+
+        // A second architecture extracted with native names is merged in
+        write(&root.join("VC/Tools/MSVC/14.44.34823/lib/arm64/libcmt.lib"));
+        write(&root.join("Windows Kits/10/Lib/10.0.26100.0/um/arm64/kernel32.lib"));
+        convert_to_msvc_wine(root).unwrap();
+        assert_eq!(names(root), vec!["kits", "vc"]);
+        assert_eq!(
+            names(&root.join("vc/tools/msvc/14.44.34823")),
+            vec!["include", "lib"]
+        );
+        assert_eq!(
+            names(&root.join("kits/10/lib/10.0.26100.0/um")),
+            vec!["arm64", "x64"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_save_msvc_wine_scripts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let layout = BundleLayout::from_root_with_versions(
+            temp_dir.path(),
+            "14.44.34823",
+            "10.0.26100.0",
+            Architecture::Arm64,
+            Architecture::X64,
+        )
+        .unwrap()
+        .with_style(LayoutStyle::MsvcWine);
+
+        let written = save_msvc_wine_scripts(&layout).await.unwrap();
+        assert_eq!(written.len(), 1 + MSVC_TOOLS.len() + SDK_TOOLS.len());
+
+        let dir = temp_dir.path().join("bin").join("arm64");
+        let env = std::fs::read_to_string(dir.join("msvcenv.sh")).unwrap();
+        assert!(env.contains("MSVCVER=14.44.34823\n"));
+        assert!(env.contains(r#"BINDIR="$BASE_UNIX/vc/tools/msvc/$MSVCVER/bin/Hostx64/arm64""#));
+        assert!(env.contains(r#"SDKBINDIR="$BASE_UNIX/kits/10/bin/$SDKVER/x64""#));
+        assert!(env.contains(r#"export LIB="$MSVCDIR\\lib\\$ARCH;"#));
+
+        let cl = std::fs::read_to_string(dir.join("cl")).unwrap();
+        assert!(cl.ends_with("exec \"${WINE:-wine}\" \"$BINDIR/cl.exe\" \"$@\"\n"));
+        let rc = std::fs::read_to_string(dir.join("rc")).unwrap();
+        assert!(rc.contains("\"$SDKBINDIR/rc.exe\""));
+
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(dir.join("cl"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+
+    #[test]
+    fn test_rewrite_script_paths() {
+        let bash = r#"export VCINSTALLDIR="$BUNDLE_ROOT/VC"
+MSVC_KIT_INCLUDE="$BUNDLE_ROOT/VC/Tools/MSVC/14.44/include;$BUNDLE_ROOT/Windows Kits/10/Include/10.0.26100.0/um"
+export WindowsSdkDir="$BUNDLE_ROOT/Windows Kits/10""#;
+        let rewritten = rewrite_script_paths(bash);
+        assert!(rewritten.contains(r#"VCINSTALLDIR="$BUNDLE_ROOT/vc""#));
+        assert!(rewritten.contains("$BUNDLE_ROOT/vc/tools/msvc/14.44/include"));
+        assert!(rewritten.contains("$BUNDLE_ROOT/kits/10/include/10.0.26100.0/um"));
+        assert!(rewritten.contains(r#"WindowsSdkDir="$BUNDLE_ROOT/kits/10""#));
+
+        let cmd = r#"set "MSVC_KIT_LIB=%BUNDLE_ROOT%\Windows Kits\10\Lib\10.0.26100.0\um\x64""#;
+        assert_eq!(
+            rewrite_script_paths(cmd),
+            r#"set "MSVC_KIT_LIB=%BUNDLE_ROOT%\kits\10\lib\10.0.26100.0\um\x64""#
+        );
+    }
+}
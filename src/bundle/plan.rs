@@ -0,0 +1,204 @@
+//! Bundle dry run
+//!
+//! [`plan_bundle`] resolves the versions and packages a bundle would contain
+//! from the manifests only, so the download size, the size on disk and the
+//! resulting layout can be checked before any payload is fetched.
+
+use std::path::Path;
+
+use super::{bundle_download_options, generate_bundle_scripts_with_locale, BundleOptions};
+use super::{BundleLayout, BundleScripts};
+use crate::downloader::{DownloadPreview, MsvcDownloader, SdkDownloader};
+use crate::error::Result;
+use crate::scripts::script_file_names;
+use crate::version::Architecture;
+
+/// What [`create_bundle`](super::create_bundle) would download and write
+#[derive(Debug, Clone)]
+pub struct BundlePlan {
+    /// Layout of the bundle, with the resolved versions
+    pub layout: BundleLayout,
+    /// MSVC packages to download
+    pub msvc: DownloadPreview,
+    /// Windows SDK packages to download
+    pub sdk: DownloadPreview,
+    /// Activation scripts that would be written to the bundle root
+    pub script_names: Vec<String>,
+    /// Generated activation scripts
+    pub scripts: BundleScripts,
+}
+
+impl BundlePlan {
+    /// Total download size in bytes
+    pub fn download_size(&self) -> u64 {
+        self.msvc.total_size + self.sdk.total_size
+    }
+
+    /// Estimated bytes on disk once extracted
+    pub fn installed_size(&self) -> u64 {
+        self.msvc.installed_size + self.sdk.installed_size
+    }
+
+    /// Format the plan as a human-readable string
+    pub fn format(&self) -> String {
+        let layout = &self.layout;
+        let lines = [
+            format!(
+                "Bundle {} ({} -> {})",
+                layout.root.display(),
+                layout.host_arch,
+                layout.arch
+            ),
+            format!("  {}", self.msvc.format()),
+            format!("  {}", self.sdk.format()),
+            format!(
+                "  Download: {}, on disk: ~{}",
+                humansize::format_size(self.download_size(), humansize::BINARY),
+                humansize::format_size(self.installed_size(), humansize::BINARY)
+            ),
+            format!("  MSVC:    {}", layout.vc_tools_dir().display()),
+            format!("  SDK:     {}", layout.sdk_dir().display()),
+            format!("  cl.exe:  {}", layout.cl_exe_path().display()),
+            format!("  Scripts: {}", self.script_names.join(", ")),
+        ];
+        lines.join("\n")
+    }
+}
+
+/// Plan a bundle without downloading any payload
+///
+/// Only the Visual Studio and SDK manifests are fetched (or read from the
+/// cache with `options.offline`). The MSVC version in the layout is the full
+/// toolset version from the manifest, e.g. `14.44.34823`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::bundle::{plan_bundle, BundleOptions};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let plan = plan_bundle(&BundleOptions::default()).await?;
+///     println!("{}", plan.format());
+///     Ok(())
+/// }
+/// ```
+pub async fn plan_bundle(options: &BundleOptions) -> Result<BundlePlan> {
+    plan_for(options, &options.output_dir, options.arch).await
+}
+
+/// Plan the bundle of `arch` at `root`
+pub(super) async fn plan_for(
+    options: &BundleOptions,
+    root: &Path,
+    arch: Architecture,
+) -> Result<BundlePlan> {
+    let download_opts = bundle_download_options(options, root, arch);
+    let msvc = MsvcDownloader::new(download_opts.clone()).preview().await?;
+    let sdk = SdkDownloader::new(download_opts).preview().await?;
+
+    let layout = BundleLayout::from_root_with_versions(
+        root,
+        &msvc_full_version(&msvc),
+        &sdk.version,
+        arch,
+        options.host_arch,
+    )?
+    .with_style(options.layout_style);
+    let scripts = generate_bundle_scripts_with_locale(&layout, options.locale)?;
+    Ok(BundlePlan {
+        script_names: script_file_names(&scripts, "setup"),
+        layout,
+        msvc,
+        sdk,
+        scripts,
+    })
+}
+
+/// Full toolset version: the preview version is often a prefix (`14.44`)
+fn msvc_full_version(preview: &DownloadPreview) -> String {
+    preview
+        .packages
+        .iter()
+        .find(|p| p.id.starts_with("Microsoft.VC.") && p.id.contains(".Tools."))
+        .map(|p| p.version.clone())
+        .unwrap_or_else(|| preview.version.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::PackagePreview;
+    use std::path::PathBuf;
+
+    fn preview(component: &str, version: &str, packages: Vec<PackagePreview>) -> DownloadPreview {
+        DownloadPreview {
+            component: component.to_string(),
+            version: version.to_string(),
+            package_count: packages.len(),
+            file_count: packages.iter().map(|p| p.file_count).sum(),
+            total_size: packages.iter().map(|p| p.size).sum(),
+            installed_size: packages.iter().map(|p| p.size * 3).sum(),
+            packages,
+            host_arch: None,
+        }
+    }
+
+    #[test]
+    fn test_bundle_plan_format() {
+        let msvc = preview(
+            "MSVC",
+            "14.44",
+            vec![
+                PackagePreview {
+                    id: "Microsoft.VC.14.44.17.14.CRT.Headers.base".to_string(),
+                    version: "14.44.17.14".to_string(),
+                    file_count: 1,
+                    size: 1024,
+                },
+                PackagePreview {
+                    id: "Microsoft.VC.14.44.17.14.Tools.HostX64.TargetX64.base".to_string(),
+                    version: "14.44.34823".to_string(),
+                    file_count: 2,
+                    size: 2048,
+                },
+            ],
+        );
+        assert_eq!(msvc_full_version(&msvc), "14.44.34823");
+
+        let sdk = preview("Windows SDK", "10.0.26100.0", Vec::new());
+        let layout = BundleLayout::from_root_with_versions(
+            PathBuf::from("bundle"),
+            &msvc_full_version(&msvc),
+            &sdk.version,
+            Architecture::X64,
+            Architecture::X64,
+        )
+        .unwrap();
+        let scripts = generate_bundle_scripts_with_locale(&layout, Default::default()).unwrap();
+        let plan = BundlePlan {
+            script_names: script_file_names(&scripts, "setup"),
+            layout,
+            msvc,
+            sdk,
+            scripts,
+        };
+
+        assert_eq!(plan.download_size(), 3072);
+        assert_eq!(plan.installed_size(), 9216);
+        assert_eq!(
+            plan.script_names,
+            vec![
+                "setup.bat",
+                "setup.ps1",
+                "setup.sh",
+                "README.txt",
+                "MsvcKit.psm1"
+            ]
+        );
+        let text = plan.format();
+        assert!(text.contains("Download: 3 KiB, on disk: ~9 KiB"));
+        assert!(text.contains("14.44.34823"));
+        assert!(text.contains("setup.bat, setup.ps1"));
+    }
+}
@@ -0,0 +1,246 @@
+//! Clean build environments from a bundle
+//!
+//! Generates a Windows Sandbox configuration (`.wsb`) or a Dockerfile that
+//! mounts an existing bundle and runs its `setup.bat`, so a throwaway build
+//! environment can be started from a bundle without installing anything.
+
+use std::path::{Path, PathBuf};
+
+use askama::Template;
+
+use super::BundleLayout;
+use crate::error::{MsvcKitError, Result};
+
+/// Default Windows container base image
+pub const DEFAULT_BASE_IMAGE: &str = "mcr.microsoft.com/windows/servercore:ltsc2022";
+
+/// Default directory the bundle is mounted at
+pub const DEFAULT_MOUNT_DIR: &str = r"C:\msvc-kit";
+
+/// Default working directory inside the sandbox or container
+pub const DEFAULT_WORK_DIR: &str = r"C:\src";
+
+/// Options for provisioning files
+#[derive(Debug, Clone)]
+pub struct ProvisionOptions {
+    /// Directory the bundle is mounted at inside the sandbox or container
+    pub mount_dir: String,
+    /// Working directory inside the sandbox or container
+    pub work_dir: String,
+    /// Host directory mapped read-write to `work_dir` (Windows Sandbox only)
+    pub workspace: Option<PathBuf>,
+    /// Base image of the Dockerfile
+    pub base_image: String,
+}
+
+impl Default for ProvisionOptions {
+    fn default() -> Self {
+        Self {
+            mount_dir: DEFAULT_MOUNT_DIR.to_string(),
+            work_dir: DEFAULT_WORK_DIR.to_string(),
+            workspace: None,
+            base_image: DEFAULT_BASE_IMAGE.to_string(),
+        }
+    }
+}
+
+/// Windows Sandbox configuration template
+#[derive(Template)]
+#[template(path = "sandbox.wsb.txt")]
+struct SandboxTemplate<'a> {
+    msvc_version: &'a str,
+    sdk_version: &'a str,
+    file_name: &'a str,
+    bundle_dir: String,
+    mount_dir: String,
+    workspace: Option<String>,
+    work_dir: String,
+    start_dir: String,
+    setup_script: String,
+}
+
+/// Dockerfile template
+#[derive(Template)]
+#[template(path = "Dockerfile.txt")]
+struct DockerfileTemplate<'a> {
+    msvc_version: &'a str,
+    sdk_version: &'a str,
+    file_name: &'a str,
+    bundle_dir: String,
+    mount_dir: &'a str,
+    work_dir: &'a str,
+    base_image: &'a str,
+    setup_script_json: String,
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Absolute, Windows-style path of the bundle on the host
+fn host_path(path: &Path) -> Result<String> {
+    let absolute = std::path::absolute(path)?;
+    Ok(absolute.display().to_string().replace('/', "\\"))
+}
+
+fn setup_script(mount_dir: &str) -> String {
+    format!("{}\\setup.bat", mount_dir.trim_end_matches('\\'))
+}
+
+/// Check that `bundle_dir` is a bundle and return its layout
+fn bundle_layout(bundle_dir: &Path) -> Result<BundleLayout> {
+    if !bundle_dir.join("setup.bat").exists() {
+        return Err(MsvcKitError::InstallPath(format!(
+            "{} is not a bundle (setup.bat not found)",
+            bundle_dir.display()
+        )));
+    }
+    BundleLayout::from_root(bundle_dir)
+}
+
+fn file_name(output: &Path) -> &str {
+    output.file_name().and_then(|n| n.to_str()).unwrap_or("")
+}
+
+/// Render a Windows Sandbox configuration mounting `bundle_dir`
+///
+/// The bundle is mapped read-only to `options.mount_dir` and a command prompt
+/// with `setup.bat` run opens at logon. `output` is only used for the usage
+/// comment.
+pub fn render_sandbox_config(
+    bundle_dir: &Path,
+    output: &Path,
+    options: &ProvisionOptions,
+) -> Result<String> {
+    let layout = bundle_layout(bundle_dir)?;
+    let workspace = options
+        .workspace
+        .as_deref()
+        .map(|w| host_path(w).map(|p| xml_escape(&p)))
+        .transpose()?;
+    let start_dir = if workspace.is_some() {
+        &options.work_dir
+    } else {
+        &options.mount_dir
+    };
+
+    let template = SandboxTemplate {
+        msvc_version: &layout.msvc_version,
+        sdk_version: &layout.sdk_version,
+        file_name: file_name(output),
+        bundle_dir: xml_escape(&host_path(bundle_dir)?),
+        mount_dir: xml_escape(&options.mount_dir),
+        workspace,
+        work_dir: xml_escape(&options.work_dir),
+        start_dir: xml_escape(start_dir),
+        setup_script: xml_escape(&setup_script(&options.mount_dir)),
+    };
+    template
+        .render()
+        .map_err(|e| MsvcKitError::Other(format!("Failed to render sandbox config: {}", e)))
+}
+
+/// Render a Dockerfile for a Windows container using `bundle_dir`
+///
+/// The bundle is not copied into the image; the generated comments show the
+/// `docker run -v` mount. The entrypoint runs `setup.bat` before the command.
+pub fn render_dockerfile(
+    bundle_dir: &Path,
+    output: &Path,
+    options: &ProvisionOptions,
+) -> Result<String> {
+    let layout = bundle_layout(bundle_dir)?;
+    let template = DockerfileTemplate {
+        msvc_version: &layout.msvc_version,
+        sdk_version: &layout.sdk_version,
+        file_name: file_name(output),
+        bundle_dir: host_path(bundle_dir)?,
+        mount_dir: &options.mount_dir,
+        work_dir: &options.work_dir,
+        base_image: &options.base_image,
+        setup_script_json: setup_script(&options.mount_dir).replace('\\', "\\\\"),
+    };
+    template
+        .render()
+        .map_err(|e| MsvcKitError::Other(format!("Failed to render Dockerfile: {}", e)))
+}
+
+/// Write a Windows Sandbox configuration for `bundle_dir` to `output`
+pub async fn write_sandbox_config(
+    bundle_dir: &Path,
+    output: &Path,
+    options: &ProvisionOptions,
+) -> Result<()> {
+    let content = render_sandbox_config(bundle_dir, output, options)?;
+    tokio::fs::write(output, content).await?;
+    Ok(())
+}
+
+/// Write a Dockerfile for `bundle_dir` to `output`
+pub async fn write_dockerfile(
+    bundle_dir: &Path,
+    output: &Path,
+    options: &ProvisionOptions,
+) -> Result<()> {
+    let content = render_dockerfile(bundle_dir, output, options)?;
+    tokio::fs::write(output, content).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_bundle(root: &Path) {
+        std::fs::create_dir_all(root.join("VC/Tools/MSVC/14.44.34823")).unwrap();
+        std::fs::create_dir_all(root.join("Windows Kits/10/Include/10.0.26100.0")).unwrap();
+        std::fs::write(root.join("setup.bat"), "@echo off").unwrap();
+    }
+
+    #[test]
+    fn test_render_sandbox_config() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let bundle = temp_dir.path().join("R&D bundle");
+        fake_bundle(&bundle);
+
+        let options = ProvisionOptions {
+            workspace: Some(temp_dir.path().join("src")),
+            ..Default::default()
+        };
+        let wsb = render_sandbox_config(&bundle, Path::new("msvc.wsb"), &options).unwrap();
+        assert!(wsb.contains("R&amp;D bundle</HostFolder>"));
+        assert!(wsb.contains(r"<SandboxFolder>C:\msvc-kit</SandboxFolder>"));
+        assert!(wsb.contains(r"<SandboxFolder>C:\src</SandboxFolder>"));
+        assert!(wsb.contains(r#"/d "C:\src" cmd.exe /k "C:\msvc-kit\setup.bat""#));
+        assert!(wsb.contains("MSVC: 14.44.34823, SDK: 10.0.26100.0"));
+    }
+
+    #[test]
+    fn test_render_dockerfile() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fake_bundle(temp_dir.path());
+
+        let dockerfile = render_dockerfile(
+            temp_dir.path(),
+            Path::new("Dockerfile"),
+            &ProvisionOptions::default(),
+        )
+        .unwrap();
+        assert!(dockerfile.starts_with("# escape=`"));
+        assert!(dockerfile.contains(&format!("FROM {}", DEFAULT_BASE_IMAGE)));
+        assert!(dockerfile.contains(r#""C:\\msvc-kit\\setup.bat", "&&"]"#));
+    }
+
+    #[test]
+    fn test_render_requires_bundle() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let result = render_dockerfile(
+            temp_dir.path(),
+            Path::new("Dockerfile"),
+            &ProvisionOptions::default(),
+        );
+        assert!(matches!(result, Err(MsvcKitError::InstallPath(_))));
+    }
+}
@@ -0,0 +1,199 @@
+//! Repairing damaged bundles
+//!
+//! Every extracted payload has a receipt listing the files it wrote (see
+//! [`PackageReceipt`]). A payload whose files went missing is re-extracted by
+//! dropping its receipt; the download step then only fetches payloads that
+//! are neither in the bundle's `downloads/` directory nor in the shared
+//! payload cache.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::{bundle_download_options, BundleLayout, BundleOptions, LayoutIssue};
+use crate::downloader::{download_msvc, download_sdk, FileSystemCacheManager};
+use crate::error::Result;
+use crate::installer::{
+    extract_and_finalize_msvc_with_report, extract_and_finalize_sdk_with_report, PackageReceipt,
+    RECEIPT_DIR_NAME,
+};
+use crate::query::QueryComponent;
+
+/// Result of [`BundleLayout::repair`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairReport {
+    /// Missing pieces found before the repair
+    pub issues: Vec<LayoutIssue>,
+    /// Payloads whose receipts listed missing files
+    pub damaged_packages: Vec<String>,
+    /// Components that were downloaded and extracted again
+    pub repaired: Vec<QueryComponent>,
+    /// Packages extracted during the repair
+    pub extracted: usize,
+    /// Pieces still missing afterwards
+    pub remaining: Vec<LayoutIssue>,
+}
+
+impl RepairReport {
+    /// Whether the bundle is complete after the repair
+    pub fn is_repaired(&self) -> bool {
+        self.remaining.is_empty()
+    }
+}
+
+/// Payloads with files missing on disk, with the component they belong to
+async fn damaged_receipts(root: &Path) -> Result<Vec<(String, QueryComponent)>> {
+    let receipt_dir = root.join(RECEIPT_DIR_NAME);
+    if !receipt_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut damaged = Vec::new();
+    let mut entries = tokio::fs::read_dir(&receipt_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let Some(receipt) = PackageReceipt::read(&entry.path()).await else {
+            continue;
+        };
+        if let Some(missing) = receipt.files.iter().find(|f| !root.join(&f.path).exists()) {
+            let component = if missing.path.starts_with("VC/") {
+                QueryComponent::Msvc
+            } else {
+                QueryComponent::Sdk
+            };
+            damaged.push((receipt.package, component));
+        }
+    }
+    damaged.sort_by(|a, b| a.0.cmp(&b.0));
+    Ok(damaged)
+}
+
+impl BundleLayout {
+    /// Re-download and re-extract the packages of a damaged bundle
+    ///
+    /// Looks for missing pieces ([`validate`](Self::validate)) and for
+    /// payloads whose extracted files are gone. Only the affected components
+    /// are processed, only damaged or never-extracted payloads are extracted
+    /// again, and payloads still on disk or in the shared cache are not
+    /// downloaded again.
+    ///
+    /// The versions and architectures come from the layout; from `options`,
+    /// only the download settings (`parallel_downloads`, `offline`) are used.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use msvc_kit::bundle::{BundleLayout, BundleOptions};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> anyhow::Result<()> {
+    ///     let layout = BundleLayout::from_root("./msvc-bundle")?;
+    ///     for issue in layout.validate() {
+    ///         println!("{}", issue);
+    ///     }
+    ///     let report = layout.repair(&BundleOptions::default()).await?;
+    ///     println!("Repaired: {}", report.is_repaired());
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn repair(&self, options: &BundleOptions) -> Result<RepairReport> {
+        let issues = self.validate();
+        let damaged = damaged_receipts(&self.root).await?;
+
+        let components: Vec<QueryComponent> = [QueryComponent::Msvc, QueryComponent::Sdk]
+            .into_iter()
+            .filter(|&component| {
+                issues.iter().any(|issue| issue.component == component)
+                    || damaged.iter().any(|(_, c)| *c == component)
+            })
+            .collect();
+
+        let mut report = RepairReport {
+            issues,
+            damaged_packages: damaged.iter().map(|(name, _)| name.clone()).collect(),
+            ..Default::default()
+        };
+        if components.is_empty() {
+            return Ok(report);
+        }
+
+        for (name, _) in &damaged {
+            tokio::fs::remove_file(PackageReceipt::path_for(&self.root, name)).await?;
+        }
+
+        // The layout holds the full toolset version; the manifest uses the prefix
+        let msvc_prefix: Vec<&str> = self.msvc_version.splitn(3, '.').take(2).collect();
+        let mut download_opts = bundle_download_options(options, &self.root, self.arch);
+        download_opts.msvc_version = Some(msvc_prefix.join("."));
+        download_opts.sdk_version = Some(self.sdk_version.clone());
+        download_opts.host_arch = Some(self.host_arch);
+        download_opts.cache_manager = Some(Arc::new(FileSystemCacheManager::default_cache_dir()));
+
+        for component in components {
+            let extracted = match component {
+                QueryComponent::Msvc => {
+                    let mut info = download_msvc(&download_opts).await?;
+                    extract_and_finalize_msvc_with_report(&mut info, false).await?
+                }
+                QueryComponent::Sdk | QueryComponent::All => {
+                    let info = download_sdk(&download_opts).await?;
+                    extract_and_finalize_sdk_with_report(&info, false).await?
+                }
+            };
+            report.extracted += extracted.extracted;
+            report.repaired.push(component);
+        }
+
+        report.remaining = self.validate();
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::ReceiptEntry;
+
+    #[tokio::test]
+    async fn test_damaged_receipts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join(RECEIPT_DIR_NAME)).unwrap();
+        std::fs::create_dir_all(root.join("VC/Tools/MSVC/14.44.34823/include")).unwrap();
+        std::fs::write(root.join("VC/Tools/MSVC/14.44.34823/include/a.h"), "").unwrap();
+
+        let receipt = |package: &str, path: &str| PackageReceipt {
+            package: package.to_string(),
+            files: vec![ReceiptEntry {
+                path: path.to_string(),
+                sha256: "00".to_string(),
+                size: 0,
+            }],
+        };
+        for (package, path) in [
+            ("intact.vsix", "VC/Tools/MSVC/14.44.34823/include/a.h"),
+            (
+                "tools.vsix",
+                "VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64/cl.exe",
+            ),
+            (
+                "ucrt.msi",
+                "Windows Kits/10/Include/10.0.26100.0/ucrt/corecrt.h",
+            ),
+        ] {
+            receipt(package, path)
+                .write(&PackageReceipt::path_for(root, package))
+                .await
+                .unwrap();
+        }
+
+        let damaged = damaged_receipts(root).await.unwrap();
+        assert_eq!(
+            damaged,
+            vec![
+                ("tools.vsix".to_string(), QueryComponent::Msvc),
+                ("ucrt.msi".to_string(), QueryComponent::Sdk),
+            ]
+        );
+    }
+}
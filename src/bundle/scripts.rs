@@ -3,9 +3,9 @@
 //! This module provides bundle-specific script generation by delegating
 //! to the unified `scripts` module.
 
-use super::BundleLayout;
+use super::{BundleLayout, LayoutStyle};
 use crate::error::Result;
-use crate::scripts::{self, GeneratedScripts, ScriptContext};
+use crate::scripts::{self, GeneratedScripts, Locale, ScriptContext};
 
 /// Generated bundle scripts (re-export for backward compatibility)
 pub type BundleScripts = GeneratedScripts;
@@ -15,14 +15,29 @@ pub type BundleScripts = GeneratedScripts;
 /// Creates portable scripts that use relative paths so the bundle
 /// can be moved to any location.
 pub fn generate_bundle_scripts(layout: &BundleLayout) -> Result<BundleScripts> {
+    generate_bundle_scripts_with_locale(layout, Locale::default())
+}
+
+/// Generate activation scripts and README for a bundle in the given locale
+pub fn generate_bundle_scripts_with_locale(
+    layout: &BundleLayout,
+    locale: Locale,
+) -> Result<BundleScripts> {
     let ctx = ScriptContext::portable(
         &layout.msvc_version,
         &layout.sdk_version,
         layout.arch,
         layout.host_arch,
-    );
+    )
+    .with_locale(locale);
 
-    scripts::generate_portable_scripts(&ctx)
+    let mut scripts = scripts::generate_portable_scripts(&ctx)?;
+    if layout.style == LayoutStyle::MsvcWine {
+        for script in [&mut scripts.cmd, &mut scripts.powershell, &mut scripts.bash] {
+            *script = super::msvc_wine::rewrite_script_paths(script);
+        }
+    }
+    Ok(scripts)
 }
 
 /// Save bundle scripts to the bundle directory
@@ -43,6 +58,7 @@ mod tests {
             sdk_version: "10.0.26100.0".to_string(),
             arch: Architecture::X64,
             host_arch: Architecture::X64,
+            style: LayoutStyle::Native,
         }
     }
 
@@ -69,6 +85,7 @@ mod tests {
             sdk_version: "10.0.26100.0".to_string(),
             arch: Architecture::Arm64,
             host_arch: Architecture::X64,
+            style: LayoutStyle::Native,
         };
 
         let scripts = generate_bundle_scripts(&layout).unwrap();
@@ -85,6 +102,7 @@ mod tests {
             sdk_version: "10.0.26100.0".to_string(),
             arch: Architecture::X86,
             host_arch: Architecture::X86,
+            style: LayoutStyle::Native,
         };
 
         let scripts = generate_bundle_scripts(&layout).unwrap();
@@ -112,6 +130,7 @@ mod tests {
             sdk_version: "10.0.26100.0".to_string(),
             arch: Architecture::X64,
             host_arch: Architecture::X64,
+            style: LayoutStyle::Native,
         };
 
         let scripts = generate_bundle_scripts(&layout).unwrap();
@@ -122,6 +141,7 @@ mod tests {
         assert!(temp_dir.path().join("setup.ps1").exists());
         assert!(temp_dir.path().join("setup.sh").exists());
         assert!(temp_dir.path().join("README.txt").exists());
+        assert!(temp_dir.path().join("MsvcKit.psm1").exists());
 
         // Verify content
         let cmd_content = std::fs::read_to_string(temp_dir.path().join("setup.bat")).unwrap();
@@ -0,0 +1,184 @@
+//! Bundle statistics and the `bundle.json` manifest
+//!
+//! Bundle creation measures what it produced, so callers no longer walk the
+//! bundle again to report it: [`BundleStats`] are attached to
+//! [`BundleResult`](super::BundleResult) and written, together with the
+//! layout, into `bundle.json` at the bundle root.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::BundleLayout;
+use crate::downloader::format_duration;
+use crate::error::Result;
+use crate::installer::InstallInfo;
+
+/// File at the bundle root describing the bundle
+pub const BUNDLE_MANIFEST_FILE: &str = "bundle.json";
+
+/// Size of a bundle and what creating it took
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleStats {
+    /// Size of all files in the bundle
+    pub total_bytes: u64,
+    /// Number of files in the bundle
+    pub file_count: usize,
+    /// Size of the payloads the bundle was extracted from
+    pub download_bytes: u64,
+    /// Time from the start of the creation until the bundle was complete
+    #[serde(rename = "duration_secs", with = "secs_f64")]
+    pub duration: Duration,
+}
+
+impl BundleStats {
+    /// Measure the bundle at `root`
+    ///
+    /// `bundle.json` itself is not counted.
+    pub async fn collect(root: &Path, download_bytes: u64, duration: Duration) -> Result<Self> {
+        let mut stats = Self {
+            download_bytes,
+            duration,
+            ..Default::default()
+        };
+        let manifest = root.join(BUNDLE_MANIFEST_FILE);
+        let mut pending = vec![root.to_path_buf()];
+
+        while let Some(current) = pending.pop() {
+            let mut entries = tokio::fs::read_dir(&current).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                let file_type = entry.file_type().await?;
+                if file_type.is_dir() {
+                    pending.push(path);
+                } else if file_type.is_file() && path != manifest {
+                    stats.total_bytes += entry.metadata().await?.len();
+                    stats.file_count += 1;
+                }
+            }
+        }
+        Ok(stats)
+    }
+}
+
+impl fmt::Display for BundleStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = |bytes: u64| humansize::format_size(bytes, humansize::BINARY);
+        write!(
+            f,
+            "{} in {} files, from {} of payloads, created in {}",
+            size(self.total_bytes),
+            self.file_count,
+            size(self.download_bytes),
+            format_duration(self.duration)
+        )
+    }
+}
+
+/// Size of the payloads downloaded for `infos`
+///
+/// Call before extraction: cleanup may remove the payloads afterwards.
+pub fn payload_bytes(infos: &[&InstallInfo]) -> u64 {
+    infos
+        .iter()
+        .flat_map(|info| &info.downloaded_files)
+        .filter_map(|file| std::fs::metadata(file).ok())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+/// Write `bundle.json` with the layout and `stats` to the bundle root
+///
+/// Returns the path of the written file.
+pub async fn write_bundle_manifest(layout: &BundleLayout, stats: &BundleStats) -> Result<PathBuf> {
+    let mut manifest = layout.to_json();
+    manifest["stats"] = serde_json::to_value(stats)?;
+    let path = layout.root.join(BUNDLE_MANIFEST_FILE);
+    tokio::fs::write(&path, serde_json::to_vec_pretty(&manifest)?).await?;
+    Ok(path)
+}
+
+/// Durations as fractional seconds
+mod secs_f64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        value: &Duration,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_f64(value.as_secs_f64())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Duration, D::Error> {
+        let secs = f64::deserialize(deserializer)?;
+        Duration::try_from_secs_f64(secs).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::Architecture;
+
+    #[tokio::test]
+    async fn test_collect_and_write_manifest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("VC/Tools/MSVC/14.44.34823/bin")).unwrap();
+        std::fs::write(root.join("VC/Tools/MSVC/14.44.34823/bin/cl.exe"), b"12345").unwrap();
+        std::fs::write(root.join("setup.bat"), b"@echo off").unwrap();
+
+        let payload = root.join("crt.vsix");
+        std::fs::write(&payload, b"payload").unwrap();
+        let info = InstallInfo {
+            component_type: "msvc".to_string(),
+            version: "14.44.34823".to_string(),
+            install_path: root.to_path_buf(),
+            downloaded_files: vec![payload.clone(), root.join("gone.vsix")],
+            arch: Architecture::X64,
+            extract_filters: Vec::new(),
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        };
+        let download_bytes = payload_bytes(&[&info]);
+        assert_eq!(download_bytes, 7);
+        std::fs::remove_file(&payload).unwrap();
+
+        let stats = BundleStats::collect(root, download_bytes, Duration::from_millis(65_500))
+            .await
+            .unwrap();
+        assert_eq!((stats.total_bytes, stats.file_count), (14, 2));
+        assert_eq!(
+            stats.to_string(),
+            "14 B in 2 files, from 7 B of payloads, created in 1m 05s"
+        );
+
+        let layout = BundleLayout {
+            root: root.to_path_buf(),
+            msvc_version: "14.44.34823".to_string(),
+            sdk_version: "10.0.26100.0".to_string(),
+            arch: Architecture::X64,
+            host_arch: Architecture::X64,
+            style: Default::default(),
+        };
+        let path = write_bundle_manifest(&layout, &stats).await.unwrap();
+        let json: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(json["msvc_version"], "14.44.34823");
+        assert_eq!(json["stats"]["file_count"], 2);
+        assert_eq!(json["stats"]["duration_secs"], 65.5);
+        let parsed: BundleStats = serde_json::from_value(json["stats"].clone()).unwrap();
+        assert_eq!(parsed, stats);
+
+        // The manifest does not count itself
+        let again = BundleStats::collect(root, 0, Duration::ZERO).await.unwrap();
+        assert_eq!(again.file_count, 2);
+    }
+}
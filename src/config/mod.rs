@@ -1,9 +1,25 @@
 //! Configuration management for msvc-kit
+//!
+//! Several processes may update the configuration at once (e.g. parallel CI
+//! provisioning jobs). [`save_config`] and [`update`] hold an exclusive lock
+//! on `config.toml.lock` while writing and replace the file atomically, and
+//! [`update`] re-reads the file under the lock so concurrent changes are not
+//! lost.
+//!
+//! Path settings may reference environment variables, expanded by
+//! [`load_config`] (see [`expand_env_vars`]):
+//!
+//! ```toml
+//! install_dir = "${RUNNER_TEMP}/msvc"
+//! cache_dir = "%LOCALAPPDATA%/msvc-kit/cache"
+//! ```
 
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fs::File;
+use std::path::{Path, PathBuf};
 
-use crate::error::Result;
+use crate::downloader::VerifyMode;
+use crate::error::{MsvcKitError, Result};
 use crate::version::Architecture;
 
 /// Main configuration structure for msvc-kit
@@ -21,14 +37,44 @@ pub struct MsvcKitConfig {
     /// Default architecture
     pub default_arch: Architecture,
 
-    /// Whether to verify file hashes after download
-    pub verify_hashes: bool,
+    /// How payload hashes are verified after download
+    ///
+    /// Also read from the former boolean `verify_hashes` key.
+    #[serde(alias = "verify_hashes")]
+    pub verify_mode: VerifyMode,
+
+    /// Payload URL patterns exempt from hash verification, for proxies
+    /// known to rewrite payloads (case-insensitive substring match)
+    #[serde(default)]
+    pub hash_exemptions: Vec<String>,
+
+    /// Base URLs tried when a payload fails on its manifest URL, e.g. another
+    /// CDN endpoint or an internal mirror
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alternate_endpoints: Vec<String>,
 
     /// Number of parallel downloads
     pub parallel_downloads: usize,
 
     /// Cache directory for downloaded packages
     pub cache_dir: Option<PathBuf>,
+
+    /// Microsoft license terms accepted for non-interactive use
+    #[serde(default)]
+    pub license_accepted: bool,
+
+    /// Scripts run by the CLI after a successful install or bundle
+    #[serde(default)]
+    pub post_install: PostInstallConfig,
+
+    /// Developer prompt shortcuts created by `msvc-kit setup --create-shortcut`
+    #[serde(default)]
+    pub shortcut: ShortcutConfig,
+
+    /// PEM file with extra root certificates to trust, e.g. the CA of a
+    /// TLS-intercepting proxy (`MSVC_KIT_TLS_CA_FILE` takes precedence)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls_ca_file: Option<PathBuf>,
 }
 
 impl Default for MsvcKitConfig {
@@ -39,9 +85,118 @@ impl Default for MsvcKitConfig {
             default_msvc_version: None,
             default_sdk_version: None,
             default_arch: Architecture::X64,
-            verify_hashes: true,
+            verify_mode: VerifyMode::Enforce,
+            hash_exemptions: Vec::new(),
+            alternate_endpoints: Vec::new(),
             parallel_downloads: 4,
             cache_dir: Some(base_dir.join("cache")),
+            license_accepted: false,
+            post_install: PostInstallConfig::default(),
+            shortcut: ShortcutConfig::default(),
+            tls_ca_file: None,
+        }
+    }
+}
+
+/// What to do when a post-install script fails or times out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailurePolicy {
+    /// Stop and fail the command
+    #[default]
+    Fail,
+    /// Log a warning and run the remaining scripts
+    Warn,
+    /// Run the remaining scripts silently
+    Ignore,
+}
+
+/// Configuration of the `post-install.d` scripts directory
+///
+/// Every executable in the directory is run in file name order after
+/// `msvc-kit download` or `msvc-kit bundle` succeeds. A missing directory
+/// runs nothing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PostInstallConfig {
+    /// Scripts directory (None = `post-install.d` next to `config.toml`)
+    pub scripts_dir: Option<PathBuf>,
+
+    /// Timeout per script, in seconds
+    pub timeout_secs: u64,
+
+    /// Behavior when a script exits non-zero or times out
+    pub on_failure: HookFailurePolicy,
+}
+
+impl Default for PostInstallConfig {
+    fn default() -> Self {
+        Self {
+            scripts_dir: None,
+            timeout_secs: 300,
+            on_failure: HookFailurePolicy::Fail,
+        }
+    }
+}
+
+impl PostInstallConfig {
+    /// Effective scripts directory
+    pub fn scripts_dir(&self) -> PathBuf {
+        self.scripts_dir.clone().unwrap_or_else(|| {
+            get_config_path()
+                .parent()
+                .map(|p| p.join("post-install.d"))
+                .unwrap_or_else(|| PathBuf::from("post-install.d"))
+        })
+    }
+}
+
+/// Where developer prompt shortcuts are created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ShortcutLocation {
+    /// Start Menu, in an `msvc-kit` folder
+    #[default]
+    StartMenu,
+    /// Desktop
+    Desktop,
+    /// Both Start Menu and Desktop
+    Both,
+}
+
+/// Configuration of developer prompt shortcuts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShortcutConfig {
+    /// Standard location(s) for the shortcut
+    pub location: ShortcutLocation,
+
+    /// Custom directory, used instead of `location` when set
+    pub dir: Option<PathBuf>,
+}
+
+impl ShortcutConfig {
+    /// Directories the shortcut is written to
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        if let Some(ref dir) = self.dir {
+            return vec![dir.clone()];
+        }
+
+        let start_menu = directories::BaseDirs::new().map(|d| {
+            d.config_dir()
+                .join("Microsoft")
+                .join("Windows")
+                .join("Start Menu")
+                .join("Programs")
+                .join("msvc-kit")
+        });
+        let desktop =
+            directories::UserDirs::new().and_then(|d| d.desktop_dir().map(|p| p.to_path_buf()));
+
+        match self.location {
+            ShortcutLocation::StartMenu => start_menu.into_iter().collect(),
+            ShortcutLocation::Desktop => desktop.into_iter().collect(),
+            ShortcutLocation::Both => start_menu.into_iter().chain(desktop).collect(),
         }
     }
 }
@@ -85,29 +240,195 @@ pub fn get_config_path() -> PathBuf {
 /// Load configuration from disk
 ///
 /// If the configuration file doesn't exist, returns default configuration.
+/// Environment variables in path settings are expanded.
 pub fn load_config() -> Result<MsvcKitConfig> {
-    let config_path = get_config_path();
+    let mut config = read_config(&get_config_path())?;
+    config.expand_env_vars()?;
+    Ok(config)
+}
 
-    if config_path.exists() {
-        let content = std::fs::read_to_string(&config_path)?;
-        let config: MsvcKitConfig = toml::from_str(&content)?;
-        return Ok(config);
+/// Expand `${VAR}`, `${VAR:-default}` and `%VAR%` references in `value`
+///
+/// `lookup` resolves variable names. A `%` not followed by a variable name
+/// and a closing `%` is kept as is. An unset variable without a default is
+/// an error, rather than silently producing a path like `/msvc`.
+pub fn expand_env_vars(
+    value: &str,
+    lookup: impl Fn(&str) -> Option<String>,
+) -> std::result::Result<String, String> {
+    let is_name = |name: &str| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(pos) = rest.find(['$', '%']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        let (reference, default, len) = if let Some(body) = tail.strip_prefix("${") {
+            let end = body
+                .find('}')
+                .ok_or_else(|| format!("unterminated '${{' in '{}'", value))?;
+            match body[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default), end + 3),
+                None => (&body[..end], None, end + 3),
+            }
+        } else if let Some(body) = tail.strip_prefix('%') {
+            match body.find('%').filter(|&end| is_name(&body[..end])) {
+                Some(end) => (&body[..end], None, end + 2),
+                None => {
+                    out.push('%');
+                    rest = body;
+                    continue;
+                }
+            }
+        } else {
+            out.push('$');
+            rest = &tail[1..];
+            continue;
+        };
+        if !is_name(reference) {
+            return Err(format!(
+                "invalid variable name '{}' in '{}'",
+                reference, value
+            ));
+        }
+        match lookup(reference).or_else(|| default.map(str::to_string)) {
+            Some(expanded) => out.push_str(&expanded),
+            None => {
+                return Err(format!(
+                    "environment variable {} is not set (in '{}')",
+                    reference, value
+                ))
+            }
+        }
+        rest = &tail[len..];
     }
+    out.push_str(rest);
+    Ok(out)
+}
 
-    Ok(MsvcKitConfig::default())
+impl MsvcKitConfig {
+    /// Expand environment variables in the path settings
+    ///
+    /// Applies to `install_dir`, `cache_dir`, `post_install.scripts_dir`,
+    /// `shortcut.dir` and `tls_ca_file`. See [`expand_env_vars`] for the syntax.
+    pub fn expand_env_vars(&mut self) -> Result<()> {
+        let expand = |key: &str, path: &mut PathBuf| -> Result<()> {
+            let Some(value) = path.to_str() else {
+                return Ok(());
+            };
+            let expanded = expand_env_vars(value, |name| std::env::var(name).ok())
+                .map_err(|e| MsvcKitError::Config(format!("{}: {}", key, e)))?;
+            *path = PathBuf::from(expanded);
+            Ok(())
+        };
+        expand("install_dir", &mut self.install_dir)?;
+        if let Some(dir) = &mut self.cache_dir {
+            expand("cache_dir", dir)?;
+        }
+        if let Some(dir) = &mut self.post_install.scripts_dir {
+            expand("post_install.scripts_dir", dir)?;
+        }
+        if let Some(dir) = &mut self.shortcut.dir {
+            expand("shortcut.dir", dir)?;
+        }
+        if let Some(path) = &mut self.tls_ca_file {
+            expand("tls_ca_file", path)?;
+        }
+        Ok(())
+    }
 }
 
 /// Save configuration to disk (TOML format)
+///
+/// Overwrites changes other processes made since the configuration was
+/// loaded; use [`update`] to change individual settings.
 pub fn save_config(config: &MsvcKitConfig) -> Result<()> {
     let config_path = get_config_path();
+    let _lock = lock_config(&config_path)?;
+    write_config(&config_path, config)
+}
+
+/// Callback receiving the previous and the new configuration
+pub type ConfigChangeCallback<'a> = &'a dyn Fn(&MsvcKitConfig, &MsvcKitConfig);
+
+/// Change the configuration on disk in a single locked read-modify-write
+///
+/// `f` sees the settings as stored, with environment variable references
+/// unexpanded. Returns the updated configuration with variables expanded,
+/// like [`load_config`]. The file is only written when `f` changed something.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// let config = msvc_kit::config::update(|cfg| {
+///     cfg.default_msvc_version = Some("14.44".to_string());
+/// })?;
+/// println!("{}", config.install_dir.display());
+/// # Ok::<(), msvc_kit::MsvcKitError>(())
+/// ```
+pub fn update(f: impl FnOnce(&mut MsvcKitConfig)) -> Result<MsvcKitConfig> {
+    update_at(&get_config_path(), f, None)
+}
+
+/// [`update`] for the configuration file at `path`
+///
+/// `on_change` is called with the previous and the new configuration after
+/// a change was written, while the lock is still held.
+pub fn update_at(
+    path: &Path,
+    f: impl FnOnce(&mut MsvcKitConfig),
+    on_change: Option<ConfigChangeCallback<'_>>,
+) -> Result<MsvcKitConfig> {
+    let _lock = lock_config(path)?;
+    let old = read_config(path)?;
+    let mut new = old.clone();
+    f(&mut new);
+
+    if toml::to_string_pretty(&new)? != toml::to_string_pretty(&old)? {
+        write_config(path, &new)?;
+        if let Some(on_change) = on_change {
+            on_change(&old, &new);
+        }
+    }
+    new.expand_env_vars()?;
+    Ok(new)
+}
 
-    // Ensure parent directory exists
-    if let Some(parent) = config_path.parent() {
+/// Read the configuration at `path`, or the defaults if it does not exist
+fn read_config(path: &Path) -> Result<MsvcKitConfig> {
+    if path.exists() {
+        let content = std::fs::read_to_string(path)?;
+        return Ok(toml::from_str(&content)?);
+    }
+    Ok(MsvcKitConfig::default())
+}
+
+/// Exclusive lock next to the configuration file, released on drop
+fn lock_config(path: &Path) -> Result<File> {
+    if let Some(parent) = path.parent() {
         std::fs::create_dir_all(parent)?;
     }
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let file = File::options()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+    file.lock()?;
+    Ok(file)
+}
 
+/// Write `config` to a temporary file and move it over `path`
+///
+/// Readers never see a partially written file.
+fn write_config(path: &Path, config: &MsvcKitConfig) -> Result<()> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
     let content = toml::to_string_pretty(config)?;
-    std::fs::write(&config_path, content)?;
+    std::fs::write(&tmp_path, content)?;
+    std::fs::rename(&tmp_path, path)?;
     Ok(())
 }
 
@@ -137,7 +458,7 @@ mod tests {
     #[test]
     fn test_default_config() {
         let config = MsvcKitConfig::default();
-        assert!(config.verify_hashes);
+        assert_eq!(config.verify_mode, VerifyMode::Enforce);
         assert_eq!(config.parallel_downloads, 4);
         assert_eq!(config.default_arch, Architecture::X64);
     }
@@ -149,15 +470,118 @@ mod tests {
 
         // Verify it contains expected TOML keys
         assert!(toml_str.contains("install_dir"));
-        assert!(toml_str.contains("verify_hashes"));
+        assert!(toml_str.contains("verify_mode"));
         assert!(toml_str.contains("parallel_downloads"));
 
         // Verify round-trip
         let parsed: MsvcKitConfig = toml::from_str(&toml_str).unwrap();
-        assert_eq!(parsed.verify_hashes, config.verify_hashes);
+        assert_eq!(parsed.verify_mode, config.verify_mode);
         assert_eq!(parsed.parallel_downloads, config.parallel_downloads);
     }
 
+    #[test]
+    fn test_post_install_config_defaults() {
+        // Older config files have no [post_install] table
+        let config = MsvcKitConfig::default();
+        let mut toml_str = toml::to_string_pretty(&config).unwrap();
+        let table = toml_str.find("[post_install]").unwrap();
+        toml_str.truncate(table);
+        let parsed: MsvcKitConfig = toml::from_str(&toml_str).unwrap();
+        assert_eq!(parsed.post_install, PostInstallConfig::default());
+
+        let parsed: PostInstallConfig = toml::from_str("on_failure = \"warn\"").unwrap();
+        assert_eq!(parsed.on_failure, HookFailurePolicy::Warn);
+        assert_eq!(parsed.timeout_secs, 300);
+        assert!(parsed.scripts_dir().ends_with("post-install.d"));
+    }
+
+    #[test]
+    fn test_shortcut_config() {
+        let parsed: ShortcutConfig = toml::from_str("location = \"both\"").unwrap();
+        assert_eq!(parsed.location, ShortcutLocation::Both);
+        let parsed: ShortcutConfig = toml::from_str("location = \"start-menu\"").unwrap();
+        assert_eq!(parsed, ShortcutConfig::default());
+
+        let custom = ShortcutConfig {
+            location: ShortcutLocation::Both,
+            dir: Some(PathBuf::from("C:/Tools/Shortcuts")),
+        };
+        assert_eq!(custom.dirs(), [PathBuf::from("C:/Tools/Shortcuts")]);
+    }
+
+    #[test]
+    fn test_update_concurrent() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("config.toml");
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    update_at(&path, |cfg| cfg.parallel_downloads += 1, None).unwrap();
+                });
+            }
+        });
+        assert_eq!(read_config(&path).unwrap().parallel_downloads, 4 + 8);
+
+        let changes = std::sync::Mutex::new(Vec::new());
+        let record = |old: &MsvcKitConfig, new: &MsvcKitConfig| {
+            changes.lock().unwrap().push((
+                old.default_sdk_version.clone(),
+                new.default_sdk_version.clone(),
+            ));
+        };
+        for _ in 0..2 {
+            update_at(
+                &path,
+                |cfg| cfg.default_sdk_version = Some("10.0.26100.0".to_string()),
+                Some(&record),
+            )
+            .unwrap();
+        }
+        // The second update changed nothing, so no notification
+        assert_eq!(
+            changes.into_inner().unwrap(),
+            vec![(None, Some("10.0.26100.0".to_string()))]
+        );
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let lookup = |name: &str| (name == "RUNNER_TEMP").then(|| "/tmp/runner".to_string());
+
+        assert_eq!(
+            expand_env_vars("${RUNNER_TEMP}/msvc", lookup).unwrap(),
+            "/tmp/runner/msvc"
+        );
+        assert_eq!(
+            expand_env_vars("%RUNNER_TEMP%\\msvc", lookup).unwrap(),
+            "/tmp/runner\\msvc"
+        );
+        assert_eq!(
+            expand_env_vars("${UNSET:-C:/msvc}/cache", lookup).unwrap(),
+            "C:/msvc/cache"
+        );
+        assert_eq!(
+            expand_env_vars("C:/50% off/$dir", lookup).unwrap(),
+            "C:/50% off/$dir"
+        );
+
+        // Unset variables without a default are errors, not empty strings
+        let err = expand_env_vars("${UNSET}/msvc", lookup).unwrap_err();
+        assert!(err.contains("UNSET"));
+        assert!(expand_env_vars("%UNSET%/msvc", lookup).is_err());
+        assert!(expand_env_vars("${RUNNER_TEMP/msvc", lookup).is_err());
+
+        let mut config = MsvcKitConfig {
+            install_dir: PathBuf::from("${MSVC_KIT_TEST_UNSET_VARIABLE}/msvc"),
+            ..Default::default()
+        };
+        assert!(matches!(
+            config.expand_env_vars(),
+            Err(MsvcKitError::Config(msg)) if msg.starts_with("install_dir:")
+        ));
+    }
+
     #[test]
     fn test_default_cache_dir_is_set() {
         let config = MsvcKitConfig::default();
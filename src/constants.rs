@@ -14,6 +14,14 @@ pub mod download {
     /// Maximum number of retry attempts for failed downloads
     pub const MAX_RETRIES: usize = 4;
 
+    /// Retries (and throttled responses) tolerated on an endpoint before
+    /// failing over to the next alternate endpoint
+    pub const ENDPOINT_RETRIES: usize = 1;
+
+    /// Downloads from one URL that may fail hash verification before a
+    /// payload's next alternate URL is tried
+    pub const HASH_MISMATCH_RETRIES: usize = 2;
+
     /// Default number of parallel downloads
     pub const DEFAULT_PARALLEL_DOWNLOADS: usize = 4;
 
@@ -28,6 +36,19 @@ pub mod download {
 
     /// Minimum concurrency level
     pub const MIN_CONCURRENCY: usize = 2;
+
+    /// Maximum number of throttled responses (HTTP 429/503) tolerated per file.
+    /// Throttling has its own budget so rate limiting does not burn `MAX_RETRIES`.
+    pub const MAX_THROTTLE_RETRIES: usize = 8;
+
+    /// Upper bound for a single `Retry-After` wait in seconds
+    pub const MAX_RETRY_AFTER_SECS: u64 = 120;
+
+    /// Default number of range requests per payload for chunked downloads
+    pub const DEFAULT_CHUNK_CONNECTIONS: usize = 4;
+
+    /// Default size from which payloads are downloaded in chunks (128 MB)
+    pub const DEFAULT_CHUNKED_MIN_SIZE: u64 = 128 * 1024 * 1024;
 }
 
 /// Progress display configuration
@@ -0,0 +1,273 @@
+//! Diagnostics bundle for bug reports
+//!
+//! [`collect`] zips what is needed to investigate a failed download into a
+//! single archive: an environment summary, the configuration, a JSON export
+//! of every download index, the quarantined payload metadata (see
+//! [`QuarantineRecord`](crate::downloader::QuarantineRecord)) and any log
+//! files below the install directory. `msvc-kit diagnostics collect` writes
+//! it for attaching to an issue.
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::downloader::{tls_backend_name, DownloadIndex, QUARANTINE_DIR_NAME};
+use crate::error::Result;
+use crate::sbom::find_indexes;
+
+/// Default archive name
+pub const DEFAULT_BUNDLE_NAME: &str = "msvc-kit-diagnostics.zip";
+
+/// What [`collect`] puts into the archive
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticsOptions {
+    /// Installation directory
+    pub install_dir: PathBuf,
+    /// Configuration file to include, if it exists
+    pub config_path: Option<PathBuf>,
+    /// Also include the quarantined payloads, not just their metadata
+    pub include_payloads: bool,
+}
+
+/// Archive written by [`collect`]
+#[derive(Debug, Clone, Default)]
+pub struct DiagnosticsReport {
+    /// Path of the archive
+    pub output: PathBuf,
+    /// Names of the files in the archive
+    pub files: Vec<String>,
+    /// Files that could not be read, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// `environment.json` in the archive
+#[derive(Debug, Serialize)]
+struct Environment<'a> {
+    msvc_kit_version: &'a str,
+    os: &'a str,
+    arch: &'a str,
+    tls_backend: &'a str,
+    install_dir: String,
+    config_path: Option<String>,
+}
+
+/// Log files in `install_dir` and its `logs/` directory
+fn find_logs(install_dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![install_dir.join("logs")];
+    let is_log = |path: &Path| path.extension().is_some_and(|ext| ext == "log");
+    for entry in std::fs::read_dir(install_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+    {
+        if is_log(&entry.path()) && entry.path().is_file() {
+            found.push(entry.path());
+        }
+    }
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if is_log(&path) {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Archive name of `path` below `base`, with forward slashes
+fn archive_name(prefix: &str, base: &Path, path: &Path) -> String {
+    let relative = path.strip_prefix(base).unwrap_or(path);
+    format!(
+        "{}/{}",
+        prefix,
+        relative.to_string_lossy().replace('\\', "/")
+    )
+}
+
+/// Write a diagnostics archive of `options.install_dir` to `output`
+///
+/// Missing parts (no config file, no downloads yet) are left out. Index
+/// databases that cannot be opened, e.g. because a download is running, are
+/// listed in [`DiagnosticsReport::skipped`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::diagnostics::{self, DiagnosticsOptions};
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// let report = diagnostics::collect(
+///     &DiagnosticsOptions {
+///         install_dir: "C:/msvc-kit".into(),
+///         ..Default::default()
+///     },
+///     "msvc-kit-diagnostics.zip".as_ref(),
+/// )
+/// .await?;
+/// println!("{} files in {}", report.files.len(), report.output.display());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn collect(options: &DiagnosticsOptions, output: &Path) -> Result<DiagnosticsReport> {
+    let install_dir = &options.install_dir;
+    let mut files: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut skipped = Vec::new();
+
+    let config_path = options.config_path.as_ref().filter(|p| p.is_file());
+    let environment = Environment {
+        msvc_kit_version: env!("CARGO_PKG_VERSION"),
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        tls_backend: tls_backend_name(),
+        install_dir: install_dir.display().to_string(),
+        config_path: config_path.map(|p| p.display().to_string()),
+    };
+    files.push((
+        "environment.json".to_string(),
+        serde_json::to_vec_pretty(&environment)?,
+    ));
+
+    if let Some(path) = config_path {
+        match std::fs::read(path) {
+            Ok(content) => files.push(("config.toml".to_string(), content)),
+            Err(e) => skipped.push((path.clone(), e.to_string())),
+        }
+    }
+
+    let downloads = install_dir.join("downloads");
+    let export_path =
+        std::env::temp_dir().join(format!("msvc-kit-diagnostics-{}.json", std::process::id()));
+    for index_path in find_indexes(install_dir) {
+        let exported = async {
+            let index = DownloadIndex::load(&index_path).await?;
+            index.export(&export_path).await?;
+            Ok::<_, crate::MsvcKitError>(tokio::fs::read(&export_path).await?)
+        }
+        .await;
+        match exported {
+            Ok(content) => {
+                let name = archive_name("index", &downloads, &index_path);
+                files.push((name.replace("index.db", "index.json"), content));
+            }
+            Err(e) => skipped.push((index_path, e.to_string())),
+        }
+    }
+    let _ = tokio::fs::remove_file(&export_path).await;
+
+    let quarantine = install_dir.join(QUARANTINE_DIR_NAME);
+    let mut quarantined: Vec<PathBuf> = std::fs::read_dir(&quarantine)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            options.include_payloads || path.extension().is_some_and(|ext| ext == "json")
+        })
+        .collect();
+    quarantined.sort();
+
+    let logs = find_logs(install_dir);
+    let log_dir = install_dir.join("logs");
+    for path in quarantined.iter().chain(&logs) {
+        let name = if path.starts_with(&quarantine) {
+            archive_name(QUARANTINE_DIR_NAME, &quarantine, path)
+        } else if path.starts_with(&log_dir) {
+            archive_name("logs", &log_dir, path)
+        } else {
+            archive_name("logs", install_dir, path)
+        };
+        match std::fs::read(path) {
+            Ok(content) => files.push((name, content)),
+            Err(e) => skipped.push((path.clone(), e.to_string())),
+        }
+    }
+
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(output)?);
+    let zip_options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for (name, content) in &files {
+        zip.start_file(name.as_str(), zip_options)?;
+        zip.write_all(content)?;
+    }
+    zip.finish()?;
+
+    Ok(DiagnosticsReport {
+        output: output.to_path_buf(),
+        files: files.into_iter().map(|(name, _)| name).collect(),
+        skipped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::PackagePayload;
+
+    #[tokio::test]
+    async fn test_collect() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path().join("msvc-kit");
+
+        let download_dir = install_dir.join("downloads/msvc/14.44");
+        let mut index = DownloadIndex::load(&download_dir.join("index.db"))
+            .await
+            .unwrap();
+        let payload = PackagePayload {
+            file_name: "a.vsix".to_string(),
+            url: "https://example.com/a.vsix".to_string(),
+            size: 1,
+            sha256: None,
+            mirrors: Vec::new(),
+        };
+        index
+            .mark_completed(&payload, download_dir.join("a.vsix"), None)
+            .await
+            .unwrap();
+        drop(index);
+
+        let quarantine = install_dir.join(QUARANTINE_DIR_NAME);
+        std::fs::create_dir_all(&quarantine).unwrap();
+        std::fs::write(quarantine.join("bad-a.vsix"), b"bad").unwrap();
+        std::fs::write(quarantine.join("bad-a.vsix.json"), b"{}").unwrap();
+        std::fs::create_dir_all(install_dir.join("logs")).unwrap();
+        std::fs::write(install_dir.join("logs/download.log"), b"log").unwrap();
+        let config_path = temp_dir.path().join("config.toml");
+        std::fs::write(&config_path, b"verify_hashes = true\n").unwrap();
+
+        let output = temp_dir.path().join(DEFAULT_BUNDLE_NAME);
+        let report = collect(
+            &DiagnosticsOptions {
+                install_dir: install_dir.clone(),
+                config_path: Some(config_path),
+                include_payloads: false,
+            },
+            &output,
+        )
+        .await
+        .unwrap();
+
+        assert!(report.skipped.is_empty());
+        let archive = zip::ZipArchive::new(std::fs::File::open(&output).unwrap()).unwrap();
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "config.toml",
+                "environment.json",
+                "index/msvc/14.44/index.json",
+                "logs/download.log",
+                "quarantine/bad-a.vsix.json",
+            ]
+        );
+    }
+}
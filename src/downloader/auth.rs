@@ -0,0 +1,351 @@
+//! Authentication hooks for private mirrors
+//!
+//! When payload or manifest URLs point at an internal mirror that requires
+//! credentials, an [`AuthProvider`] supplies per-request headers. Providers
+//! decide per URL, so credentials are never sent to hosts they were not meant for.
+//! Plain `http` URLs never get the headers, except on the loopback interface.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use reqwest::Url;
+
+use crate::error::{MsvcKitError, Result};
+
+/// Supplies authentication headers for outgoing requests
+///
+/// Implementations are called before every manifest and payload request.
+/// Return an empty [`HeaderMap`] for URLs that need no credentials.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use async_trait::async_trait;
+/// use msvc_kit::downloader::AuthProvider;
+/// use reqwest::header::HeaderMap;
+///
+/// struct EnvTokenAuth;
+///
+/// #[async_trait]
+/// impl AuthProvider for EnvTokenAuth {
+///     async fn headers_for(&self, url: &str) -> msvc_kit::Result<HeaderMap> {
+///         let mut headers = HeaderMap::new();
+///         if url.starts_with("https://mirror.example.com/") {
+///             let token = std::env::var("MIRROR_TOKEN").unwrap_or_default();
+///             headers.insert("Authorization", format!("Bearer {}", token).parse().unwrap());
+///         }
+///         Ok(headers)
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait AuthProvider: Send + Sync {
+    /// Headers to attach to a request for `url`
+    async fn headers_for(&self, url: &str) -> Result<HeaderMap>;
+}
+
+/// Type alias for boxed auth provider
+pub type BoxedAuthProvider = Arc<dyn AuthProvider>;
+
+/// Built-in provider sending a fixed `Authorization` header to URLs under a prefix
+///
+/// A URL is under the prefix when scheme, host and port are equal and its
+/// path continues the prefix path at a `/` boundary, so
+/// `https://mirror.example.com` does not match `https://mirror.example.com.evil.tld`.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use msvc_kit::downloader::StaticTokenAuth;
+/// use msvc_kit::DownloadOptions;
+///
+/// let auth = StaticTokenAuth::bearer("https://mirror.example.com/", "secret");
+/// let options = DownloadOptions::builder()
+///     .auth_provider(Arc::new(auth))
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct StaticTokenAuth {
+    url_prefix: String,
+    authorization: String,
+}
+
+impl StaticTokenAuth {
+    /// Send `Authorization: Bearer <token>` to URLs under `url_prefix`
+    pub fn bearer(url_prefix: impl Into<String>, token: impl AsRef<str>) -> Self {
+        Self {
+            url_prefix: url_prefix.into(),
+            authorization: format!("Bearer {}", token.as_ref()),
+        }
+    }
+
+    /// Send HTTP Basic credentials to URLs under `url_prefix`
+    pub fn basic(
+        url_prefix: impl Into<String>,
+        username: impl AsRef<str>,
+        password: impl AsRef<str>,
+    ) -> Self {
+        let credentials = format!("{}:{}", username.as_ref(), password.as_ref());
+        Self {
+            url_prefix: url_prefix.into(),
+            authorization: format!("Basic {}", base64_encode(credentials.as_bytes())),
+        }
+    }
+
+    /// URL prefix the credentials are scoped to
+    pub fn url_prefix(&self) -> &str {
+        &self.url_prefix
+    }
+}
+
+impl std::fmt::Debug for StaticTokenAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the credentials
+        f.debug_struct("StaticTokenAuth")
+            .field("url_prefix", &self.url_prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AuthProvider for StaticTokenAuth {
+    async fn headers_for(&self, url: &str) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        let prefix = Url::parse(&self.url_prefix).map_err(|e| {
+            MsvcKitError::Config(format!(
+                "Invalid auth URL prefix '{}': {}",
+                self.url_prefix, e
+            ))
+        })?;
+        if Url::parse(url).is_ok_and(|url| is_under_prefix(&prefix, &url)) {
+            let mut value = HeaderValue::from_str(&self.authorization)
+                .map_err(|e| MsvcKitError::Config(format!("Invalid auth token: {}", e)))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+}
+
+/// Attach the provider's headers (if any) to a request
+///
+/// Requests over plain `http` to another host are sent without them, so
+/// credentials never cross the network in clear text.
+pub(crate) async fn authorize(
+    request: reqwest::RequestBuilder,
+    auth: Option<&BoxedAuthProvider>,
+    url: &str,
+) -> Result<reqwest::RequestBuilder> {
+    match auth {
+        Some(provider) if is_secure_transport(url) => {
+            Ok(request.headers(provider.headers_for(url).await?))
+        }
+        Some(_) => {
+            tracing::debug!("Not sending credentials over plain http: {}", url);
+            Ok(request)
+        }
+        None => Ok(request),
+    }
+}
+
+/// Whether `url` has the origin of `prefix` and a path at or below its path
+fn is_under_prefix(prefix: &Url, url: &Url) -> bool {
+    if prefix.scheme() != url.scheme()
+        || prefix.host_str() != url.host_str()
+        || prefix.port_or_known_default() != url.port_or_known_default()
+    {
+        return false;
+    }
+    let base = prefix.path();
+    let path = url.path();
+    if base.ends_with('/') {
+        return path.starts_with(base);
+    }
+    path.strip_prefix(base)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// Whether `url` is `https`, or plain `http` to the local machine
+fn is_secure_transport(url: &str) -> bool {
+    if url.starts_with("https://") {
+        return true;
+    }
+    let Some(rest) = url.strip_prefix("http://") else {
+        return false;
+    };
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback())
+}
+
+/// Standard base64 (RFC 4648) with padding, used for Basic credentials
+fn base64_encode(input: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut out = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        out.push(TABLE[(n >> 18) as usize & 63] as char);
+        out.push(TABLE[(n >> 12) as usize & 63] as char);
+        out.push(if chunk.len() > 1 {
+            TABLE[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        out.push(if chunk.len() > 2 {
+            TABLE[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"user:pass"), "dXNlcjpwYXNz");
+    }
+
+    #[tokio::test]
+    async fn test_static_token_scoped_to_prefix() {
+        let auth = StaticTokenAuth::bearer("https://mirror.example.com/", "secret");
+
+        let headers = auth
+            .headers_for("https://mirror.example.com/vs/payload.vsix")
+            .await
+            .unwrap();
+        assert_eq!(headers.get(AUTHORIZATION).unwrap(), "Bearer secret");
+
+        let headers = auth
+            .headers_for("https://download.visualstudio.microsoft.com/payload.vsix")
+            .await
+            .unwrap();
+        assert!(headers.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_static_token_rejects_lookalike_urls() {
+        let auth = StaticTokenAuth::bearer("https://mirror.example.com/vs", "secret");
+
+        for (url, sent) in [
+            ("https://mirror.example.com/vs", true),
+            ("https://mirror.example.com/vs/payload.vsix", true),
+            ("https://MIRROR.example.com:443/vs/payload.vsix", true),
+            ("https://mirror.example.com.evil.tld/vs/payload.vsix", false),
+            ("https://mirror.example.com/vs2/payload.vsix", false),
+            ("https://mirror.example.com:8443/vs/payload.vsix", false),
+            ("http://mirror.example.com/vs/payload.vsix", false),
+            ("https://evil.tld/?https://mirror.example.com/vs", false),
+        ] {
+            let headers = auth.headers_for(url).await.unwrap();
+            assert_eq!(headers.contains_key(AUTHORIZATION), sent, "{}", url);
+        }
+
+        let host_only = StaticTokenAuth::bearer("https://mirror.example.com", "secret");
+        for (url, sent) in [
+            ("https://mirror.example.com/payload.vsix", true),
+            ("https://mirror.example.com.evil.tld/payload.vsix", false),
+        ] {
+            let headers = host_only.headers_for(url).await.unwrap();
+            assert_eq!(headers.contains_key(AUTHORIZATION), sent, "{}", url);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_token_invalid_prefix() {
+        let auth = StaticTokenAuth::bearer("mirror.example.com", "secret");
+        assert!(auth
+            .headers_for("https://mirror.example.com/a")
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn test_secure_transport() {
+        assert!(is_secure_transport("https://mirror.example.com/a"));
+        assert!(is_secure_transport("http://127.0.0.1:8080/a"));
+        assert!(is_secure_transport("http://localhost/a"));
+        assert!(is_secure_transport("http://[::1]:9000/a"));
+        assert!(!is_secure_transport("http://mirror.example.com/a"));
+        assert!(!is_secure_transport("http://127.0.0.1.example.com/a"));
+        assert!(!is_secure_transport(
+            "http://localhost@mirror.example.com/a"
+        ));
+        assert!(!is_secure_transport("ftp://localhost/a"));
+    }
+
+    #[tokio::test]
+    async fn test_authorize_skips_plain_http() {
+        struct AnyUrlAuth;
+
+        #[async_trait]
+        impl AuthProvider for AnyUrlAuth {
+            async fn headers_for(&self, _url: &str) -> Result<HeaderMap> {
+                let mut headers = HeaderMap::new();
+                headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer secret"));
+                Ok(headers)
+            }
+        }
+
+        let auth: BoxedAuthProvider = Arc::new(AnyUrlAuth);
+        let client = reqwest::Client::new();
+
+        for (url, sent) in [
+            ("https://mirror.example.com/a", true),
+            ("http://mirror.example.com/a", false),
+            ("http://127.0.0.1:8080/a", true),
+        ] {
+            let request = authorize(client.get(url), Some(&auth), url)
+                .await
+                .unwrap()
+                .build()
+                .unwrap();
+            assert_eq!(
+                request.headers().contains_key(AUTHORIZATION),
+                sent,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_basic_auth() {
+        let auth = StaticTokenAuth::basic("https://mirror.example.com/", "user", "pass");
+        let headers = auth
+            .headers_for("https://mirror.example.com/a")
+            .await
+            .unwrap();
+        assert_eq!(headers.get(AUTHORIZATION).unwrap(), "Basic dXNlcjpwYXNz");
+    }
+
+    #[test]
+    fn test_debug_hides_credentials() {
+        let auth = StaticTokenAuth::bearer("https://mirror.example.com/", "secret");
+        let debug = format!("{:?}", auth);
+        assert!(debug.contains("mirror.example.com"));
+        assert!(!debug.contains("secret"));
+    }
+}
@@ -12,6 +12,7 @@ use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
+use super::auth::{authorize, BoxedAuthProvider};
 use crate::constants::progress as progress_const;
 use crate::error::{MsvcKitError, Result};
 
@@ -140,6 +141,31 @@ pub async fn fetch_bytes_with_cache(
     spinner: &ProgressBar,
     label: &str,
     fingerprint_name: &str,
+) -> Result<(Vec<u8>, bool)> {
+    fetch_bytes_with_cache_auth(
+        client,
+        url,
+        cache_file,
+        spinner,
+        label,
+        fingerprint_name,
+        None,
+    )
+    .await
+}
+
+/// Fetch bytes from URL with caching support, attaching headers from an
+/// [`AuthProvider`](super::AuthProvider) to every request.
+///
+/// See [`fetch_bytes_with_cache`] for the caching behavior.
+pub async fn fetch_bytes_with_cache_auth(
+    client: &reqwest::Client,
+    url: &str,
+    cache_file: &Path,
+    spinner: &ProgressBar,
+    label: &str,
+    fingerprint_name: &str,
+    auth: Option<&BoxedAuthProvider>,
 ) -> Result<(Vec<u8>, bool)> {
     if let Some(parent) = cache_file.parent() {
         tokio::fs::create_dir_all(parent).await?;
@@ -153,7 +179,8 @@ pub async fn fetch_bytes_with_cache(
     // This follows the "file name + size" fingerprint idea (best-effort; not cryptographically strong).
     if let Some(ref cached) = cached_bytes {
         let cached_len = cached.len() as u64;
-        if let Ok(head) = client.head(url).send().await {
+        let head = authorize(client.head(url), auth, url).await?;
+        if let Ok(head) = head.send().await {
             if head.status().is_success() {
                 if let Some(remote_len) = head.content_length() {
                     if remote_len == cached_len {
@@ -185,7 +212,7 @@ pub async fn fetch_bytes_with_cache(
     // Conditional request: prefer ETag/Last-Modified if we have it.
     if let (Some(meta), Some(cached)) = (meta, cached_bytes.clone()) {
         if meta.url == url {
-            let mut req = client.get(url);
+            let mut req = authorize(client.get(url), auth, url).await?;
             if let Some(ref etag) = meta.etag {
                 req = req.header(IF_NONE_MATCH, etag);
             }
@@ -233,7 +260,7 @@ pub async fn fetch_bytes_with_cache(
     }
 
     // No usable cache: fetch fully
-    let resp = client.get(url).send().await?;
+    let resp = authorize(client.get(url), auth, url).await?.send().await?;
     if !resp.status().is_success() {
         return Err(MsvcKitError::Other(format!(
             "Failed to fetch {}: HTTP {}",
@@ -0,0 +1,247 @@
+//! Release channels and the tested ("known good") version combinations
+//!
+//! The latest channel takes the newest MSVC and Windows SDK from the
+//! manifest. The stable channel takes the newest MSVC / SDK pair that the
+//! project CI has built with and that the manifest still offers. The list
+//! ships with the crate and is refreshed from the repository at most once a
+//! day; the cached copy is used offline.
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::VsManifest;
+use crate::error::Result;
+
+/// Known-good combinations embedded at build time
+const EMBEDDED_KNOWN_GOOD: &str = include_str!("known_good.json");
+
+/// Where the stable channel list is refreshed from
+pub const KNOWN_GOOD_URL: &str =
+    "https://raw.githubusercontent.com/loonghao/msvc-kit/main/src/downloader/known_good.json";
+
+/// Cached copy of the list in the manifest cache directory
+pub const KNOWN_GOOD_FILE: &str = "known-good.json";
+
+/// Age after which the cached list is refreshed
+const KNOWN_GOOD_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Which versions are selected when none is pinned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    /// Newest versions in the manifest
+    #[default]
+    Latest,
+    /// Newest tested MSVC / Windows SDK combination
+    Stable,
+}
+
+impl std::fmt::Display for ReleaseChannel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReleaseChannel::Latest => write!(f, "latest"),
+            ReleaseChannel::Stable => write!(f, "stable"),
+        }
+    }
+}
+
+impl std::str::FromStr for ReleaseChannel {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "latest" => Ok(ReleaseChannel::Latest),
+            "stable" => Ok(ReleaseChannel::Stable),
+            _ => Err(format!(
+                "Unknown channel: {} (expected latest or stable)",
+                s
+            )),
+        }
+    }
+}
+
+/// How the MSVC version to download is chosen
+///
+/// Combines [`DownloadOptions::msvc_version`](super::DownloadOptions::msvc_version)
+/// and [`DownloadOptions::channel`](super::DownloadOptions::channel); see
+/// [`DownloadOptions::msvc_selector`](super::DownloadOptions::msvc_selector).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MsvcVersionSelector {
+    /// Newest version in the manifest
+    Latest,
+    /// Newest tested combination, see [`KnownGoodCombo`]
+    Stable,
+    /// A version or version prefix, e.g. `14.44`
+    Version(String),
+}
+
+impl std::fmt::Display for MsvcVersionSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MsvcVersionSelector::Latest => write!(f, "latest"),
+            MsvcVersionSelector::Stable => write!(f, "stable"),
+            MsvcVersionSelector::Version(version) => write!(f, "{}", version),
+        }
+    }
+}
+
+impl std::str::FromStr for MsvcVersionSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" => Err("Empty MSVC version".to_string()),
+            "latest" => Ok(MsvcVersionSelector::Latest),
+            "stable" => Ok(MsvcVersionSelector::Stable),
+            _ => Ok(MsvcVersionSelector::Version(s.trim().to_string())),
+        }
+    }
+}
+
+/// An MSVC / Windows SDK pair tested by the project CI
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KnownGoodCombo {
+    /// MSVC version prefix, e.g. `14.44`
+    pub msvc: String,
+    /// Full Windows SDK version, e.g. `10.0.26100.0`
+    pub sdk: String,
+}
+
+/// Serialized list, newest combination first
+#[derive(Debug, Deserialize)]
+struct KnownGoodList {
+    combos: Vec<KnownGoodCombo>,
+}
+
+fn parse_known_good(json: &str) -> Result<Vec<KnownGoodCombo>> {
+    let list: KnownGoodList = serde_json::from_str(json)?;
+    Ok(list.combos)
+}
+
+/// Combinations shipped with this version of msvc-kit, newest first
+pub fn embedded_known_good() -> Vec<KnownGoodCombo> {
+    parse_known_good(EMBEDDED_KNOWN_GOOD).expect("embedded known_good.json is valid")
+}
+
+/// Load the known-good combinations, newest first
+///
+/// With a `client`, a cached list older than a day (or a missing one) is
+/// refreshed from [`KNOWN_GOOD_URL`] first. Falls back to the cached list and
+/// then to [`embedded_known_good`] if refreshing or parsing fails.
+pub async fn load_known_good(
+    client: Option<&reqwest::Client>,
+    cache_dir: &Path,
+) -> Vec<KnownGoodCombo> {
+    let cache = cache_dir.join(KNOWN_GOOD_FILE);
+    let stale = std::fs::metadata(&cache)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_none_or(|age| age > KNOWN_GOOD_MAX_AGE);
+
+    if let (Some(client), true) = (client, stale) {
+        match refresh_known_good(client, cache_dir).await {
+            Ok(combos) => return combos,
+            Err(e) => tracing::debug!("Could not refresh known-good versions: {}", e),
+        }
+    }
+
+    match tokio::fs::read_to_string(&cache).await {
+        Ok(json) => match parse_known_good(&json) {
+            Ok(combos) => return combos,
+            Err(e) => tracing::warn!("Ignoring invalid {:?}: {}", cache, e),
+        },
+        Err(_) => tracing::debug!("No cached known-good versions at {:?}", cache),
+    }
+    embedded_known_good()
+}
+
+/// Download the current list and store it in `cache_dir`
+pub async fn refresh_known_good(
+    client: &reqwest::Client,
+    cache_dir: &Path,
+) -> Result<Vec<KnownGoodCombo>> {
+    let json = client
+        .get(KNOWN_GOOD_URL)
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let combos = parse_known_good(&json)?;
+    tokio::fs::create_dir_all(cache_dir).await?;
+    tokio::fs::write(cache_dir.join(KNOWN_GOOD_FILE), json).await?;
+    Ok(combos)
+}
+
+/// Newest combination whose MSVC and SDK are both offered by `manifest`
+pub fn select_stable(combos: &[KnownGoodCombo], manifest: &VsManifest) -> Option<KnownGoodCombo> {
+    combos
+        .iter()
+        .find(|combo| {
+            manifest.resolve_msvc_version(&combo.msvc).is_some()
+                && manifest.resolve_sdk_version(&combo.sdk).is_some()
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_known_good() {
+        let combos = embedded_known_good();
+        assert!(!combos.is_empty());
+        assert!(combos
+            .iter()
+            .all(|c| c.msvc.starts_with("14.") && c.sdk.starts_with("10.0.")));
+    }
+
+    #[test]
+    fn test_selector_parse() {
+        assert_eq!(
+            "stable".parse::<MsvcVersionSelector>(),
+            Ok(MsvcVersionSelector::Stable)
+        );
+        assert_eq!(
+            "Latest".parse::<MsvcVersionSelector>(),
+            Ok(MsvcVersionSelector::Latest)
+        );
+        assert_eq!(
+            "14.44".parse::<MsvcVersionSelector>(),
+            Ok(MsvcVersionSelector::Version("14.44".to_string()))
+        );
+        assert_eq!(
+            "stable".parse::<ReleaseChannel>(),
+            Ok(ReleaseChannel::Stable)
+        );
+        assert!("beta".parse::<ReleaseChannel>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_known_good_prefers_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            load_known_good(None, temp_dir.path()).await,
+            embedded_known_good()
+        );
+
+        std::fs::write(
+            temp_dir.path().join(KNOWN_GOOD_FILE),
+            r#"{"version":1,"combos":[{"msvc":"14.50","sdk":"10.0.26100.0"}]}"#,
+        )
+        .unwrap();
+        let combos = load_known_good(None, temp_dir.path()).await;
+        assert_eq!(combos.len(), 1);
+        assert_eq!(combos[0].msvc, "14.50");
+
+        std::fs::write(temp_dir.path().join(KNOWN_GOOD_FILE), "not json").unwrap();
+        assert_eq!(
+            load_known_good(None, temp_dir.path()).await,
+            embedded_known_good()
+        );
+    }
+}
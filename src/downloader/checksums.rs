@@ -0,0 +1,253 @@
+//! `SHA256SUMS` generation and verification for download directories
+//!
+//! The file uses the format understood by `sha256sum -c` (GNU coreutils) and
+//! `shasum -a 256 -c`, so payloads copied between machines can be validated
+//! with standard tooling as well as with msvc-kit itself.
+
+use std::path::{Path, PathBuf};
+
+use super::chunked::is_part_file;
+use super::hash::{compute_file_hash, hashes_match};
+use crate::error::{MsvcKitError, Result};
+
+/// Default checksums file name written into a download directory
+pub const CHECKSUMS_FILE_NAME: &str = "SHA256SUMS";
+
+/// Files that are local bookkeeping or unfinished downloads rather than payloads
+fn is_excluded(name: &str) -> bool {
+    name == CHECKSUMS_FILE_NAME
+        || name == "index.db"
+        || name.ends_with(".part")
+        || is_part_file(Path::new(name))
+}
+
+/// Result of verifying a directory against its `SHA256SUMS` file
+#[derive(Debug, Clone, Default)]
+pub struct ChecksumReport {
+    /// Files whose hash matched
+    pub verified: Vec<PathBuf>,
+    /// Files whose hash differs: (relative path, expected, actual)
+    pub mismatched: Vec<(PathBuf, String, String)>,
+    /// Files listed in `SHA256SUMS` but not present on disk
+    pub missing: Vec<PathBuf>,
+}
+
+impl ChecksumReport {
+    /// Whether every listed file is present and matches
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty()
+    }
+}
+
+/// Recursively collect payload files under `dir`, as sorted relative paths
+async fn collect_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() {
+                let name = entry.file_name();
+                if is_excluded(&name.to_string_lossy()) {
+                    continue;
+                }
+                if let Ok(rel) = path.strip_prefix(dir) {
+                    files.push(rel.to_path_buf());
+                }
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Render a relative path with forward slashes, as expected by `sha256sum`
+fn to_checksum_path(rel: &Path) -> String {
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Write a `SHA256SUMS` file covering every payload under `dir`
+///
+/// Paths are relative to `dir` and use forward slashes. The download index
+/// database and partial downloads are skipped.
+///
+/// # Returns
+///
+/// Path to the written checksums file
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::path::Path;
+/// use msvc_kit::downloader::write_checksums_file;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let sums = write_checksums_file(Path::new("msvc-kit/downloads")).await?;
+///     println!("Wrote {}", sums.display());
+///     Ok(())
+/// }
+/// ```
+pub async fn write_checksums_file(dir: &Path) -> Result<PathBuf> {
+    let files = collect_files(dir).await?;
+
+    let mut content = String::new();
+    for rel in &files {
+        let hash = compute_file_hash(&dir.join(rel)).await?;
+        content.push_str(&format!("{}  {}\n", hash, to_checksum_path(rel)));
+    }
+
+    let sums_path = dir.join(CHECKSUMS_FILE_NAME);
+    tokio::fs::write(&sums_path, content).await?;
+    tracing::debug!("Wrote {} checksums to {}", files.len(), sums_path.display());
+    Ok(sums_path)
+}
+
+/// Parse a `SHA256SUMS` file into (hash, relative path) pairs
+///
+/// Accepts both text (`hash  path`) and binary (`hash *path`) markers.
+fn parse_checksums(content: &str) -> Result<Vec<(String, PathBuf)>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let (hash, rest) = line.split_once(' ').ok_or_else(|| {
+                MsvcKitError::Other(format!(
+                    "Malformed {} line {}: {}",
+                    CHECKSUMS_FILE_NAME,
+                    i + 1,
+                    line
+                ))
+            })?;
+            let path = rest
+                .strip_prefix(' ')
+                .or_else(|| rest.strip_prefix('*'))
+                .unwrap_or(rest);
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(MsvcKitError::Other(format!(
+                    "Invalid SHA256 on {} line {}: {}",
+                    CHECKSUMS_FILE_NAME,
+                    i + 1,
+                    hash
+                )));
+            }
+            Ok((hash.to_string(), PathBuf::from(path)))
+        })
+        .collect()
+}
+
+/// Verify the payloads under `dir` against its `SHA256SUMS` file
+///
+/// Returns a report instead of failing on the first mismatch so callers can
+/// show every problem at once. Errors are only returned when the checksums
+/// file itself cannot be read or parsed.
+pub async fn verify_checksums_file(dir: &Path) -> Result<ChecksumReport> {
+    let content = tokio::fs::read_to_string(dir.join(CHECKSUMS_FILE_NAME)).await?;
+    let entries = parse_checksums(&content)?;
+
+    let mut report = ChecksumReport::default();
+    for (expected, rel) in entries {
+        let path = dir.join(&rel);
+        if !path.is_file() {
+            report.missing.push(rel);
+            continue;
+        }
+        let actual = compute_file_hash(&path).await?;
+        if hashes_match(&actual, &expected) {
+            report.verified.push(rel);
+        } else {
+            report.mismatched.push((rel, expected, actual));
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn create_payloads(dir: &Path) {
+        tokio::fs::create_dir_all(dir.join("msvc")).await.unwrap();
+        tokio::fs::write(dir.join("a.vsix"), b"hello world")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("msvc").join("b.cab"), b"payload")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("index.db"), b"db").await.unwrap();
+        // Leftovers of interrupted single and ranged downloads
+        tokio::fs::write(dir.join("c.cab.part"), b"partial")
+            .await
+            .unwrap();
+        tokio::fs::write(dir.join("c.cab.part3"), b"partial")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_checksums_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        create_payloads(temp_dir.path()).await;
+
+        let sums = write_checksums_file(temp_dir.path()).await.unwrap();
+        let content = std::fs::read_to_string(sums).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9  a.vsix"
+        );
+        assert!(lines[1].ends_with("  msvc/b.cab"));
+        assert!(!content.contains("index.db"));
+        assert!(!content.contains(".part"));
+    }
+
+    #[tokio::test]
+    async fn test_verify_checksums_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        create_payloads(temp_dir.path()).await;
+        write_checksums_file(temp_dir.path()).await.unwrap();
+
+        let report = verify_checksums_file(temp_dir.path()).await.unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.verified.len(), 2);
+
+        tokio::fs::write(temp_dir.path().join("a.vsix"), b"tampered")
+            .await
+            .unwrap();
+        tokio::fs::remove_file(temp_dir.path().join("msvc").join("b.cab"))
+            .await
+            .unwrap();
+
+        let report = verify_checksums_file(temp_dir.path()).await.unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.mismatched.len(), 1);
+        assert_eq!(report.mismatched[0].0, PathBuf::from("a.vsix"));
+        assert_eq!(report.missing, vec![PathBuf::from("msvc/b.cab")]);
+    }
+
+    #[test]
+    fn test_parse_checksums_binary_marker() {
+        let hash = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+        let entries = parse_checksums(&format!("{} *a.vsix\n\n", hash)).unwrap();
+        assert_eq!(entries, vec![(hash.to_string(), PathBuf::from("a.vsix"))]);
+    }
+
+    #[test]
+    fn test_parse_checksums_invalid() {
+        assert!(parse_checksums("not-a-hash  a.vsix").is_err());
+        assert!(parse_checksums("nospace").is_err());
+    }
+}
@@ -0,0 +1,189 @@
+//! Parallel ranged downloads of large payloads
+//!
+//! A payload is normally fetched over one connection, which leaves most of
+//! the bandwidth unused on high-latency links when the payload is large
+//! (some SDK MSIs exceed 1 GB). With [`ChunkedDownloads`] set, payloads above
+//! a size threshold are split into byte ranges fetched concurrently into part
+//! files, which are merged and hashed once all of them are complete. Servers
+//! that do not answer with `206 Partial Content` get the payload downloaded
+//! in one piece instead.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::constants::download as dl_const;
+use crate::constants::hash::HASH_BUFFER_SIZE;
+use crate::error::Result;
+
+/// Settings for splitting large payloads into parallel range requests
+///
+/// # Example
+///
+/// ```rust
+/// use msvc_kit::downloader::ChunkedDownloads;
+/// use msvc_kit::DownloadOptions;
+///
+/// let options = DownloadOptions::builder()
+///     .chunked_downloads(ChunkedDownloads {
+///         connections: 8,
+///         ..Default::default()
+///     })
+///     .build();
+/// assert_eq!(options.chunked_downloads.unwrap().connections, 8);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChunkedDownloads {
+    /// Concurrent range requests per payload
+    pub connections: usize,
+    /// Payloads smaller than this many bytes are downloaded in one piece
+    pub min_size: u64,
+}
+
+impl Default for ChunkedDownloads {
+    fn default() -> Self {
+        Self {
+            connections: dl_const::DEFAULT_CHUNK_CONNECTIONS,
+            min_size: dl_const::DEFAULT_CHUNKED_MIN_SIZE,
+        }
+    }
+}
+
+impl ChunkedDownloads {
+    /// Inclusive byte ranges to request for a payload of `size` bytes
+    ///
+    /// Empty when the payload should be downloaded in one piece.
+    pub fn ranges(&self, size: u64) -> Vec<(u64, u64)> {
+        let count = (self.connections as u64).min(size);
+        if count < 2 || size < self.min_size {
+            return Vec::new();
+        }
+        let chunk = size.div_ceil(count);
+        (0..size)
+            .step_by(chunk as usize)
+            .map(|start| (start, (start + chunk).min(size) - 1))
+            .collect()
+    }
+}
+
+/// Parse a `Content-Range` header (`bytes 0-1023/4096`) into start, end and total
+pub(crate) fn parse_content_range(value: &str) -> Option<(u64, u64, u64)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (span, total) = range.split_once('/')?;
+    let (start, end) = span.split_once('-')?;
+    Some((
+        start.trim().parse().ok()?,
+        end.trim().parse().ok()?,
+        total.trim().parse().ok()?,
+    ))
+}
+
+/// File receiving range `index` of the payload downloaded to `path`
+pub(crate) fn part_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(".part{}", index));
+    path.with_file_name(name)
+}
+
+/// Whether `path` is a part file of a ranged download (`{file}.partN`)
+pub(crate) fn is_part_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(|e| e.strip_prefix("part"))
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Remove the part files of `count` ranges (best effort)
+pub(crate) async fn remove_parts(path: &Path, count: usize) {
+    for index in 0..count {
+        let _ = tokio::fs::remove_file(part_path(path, index)).await;
+    }
+}
+
+/// Concatenate the `count` part files into `path`, returning the SHA256 of the result
+///
+/// The parts are removed once merged.
+pub(crate) async fn merge_parts(path: &Path, count: usize) -> Result<String> {
+    let mut file = tokio::fs::File::create(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    for index in 0..count {
+        let mut part = tokio::fs::File::open(part_path(path, index)).await?;
+        loop {
+            let read = part.read(&mut buffer).await?;
+            if read == 0 {
+                break;
+            }
+            file.write_all(&buffer[..read]).await?;
+            hasher.update(&buffer[..read]);
+        }
+    }
+    file.flush().await?;
+    remove_parts(path, count).await;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        let chunked = ChunkedDownloads {
+            connections: 4,
+            min_size: 10,
+        };
+        assert_eq!(chunked.ranges(10), vec![(0, 2), (3, 5), (6, 8), (9, 9)]);
+        assert_eq!(chunked.ranges(13), vec![(0, 3), (4, 7), (8, 11), (12, 12)]);
+        assert!(chunked.ranges(9).is_empty());
+
+        let single = ChunkedDownloads {
+            connections: 1,
+            min_size: 0,
+        };
+        assert!(single.ranges(1 << 30).is_empty());
+
+        let defaults = ChunkedDownloads::default();
+        assert!(defaults.ranges(64 * 1024 * 1024).is_empty());
+        assert_eq!(
+            defaults.ranges(1 << 30).len(),
+            dl_const::DEFAULT_CHUNK_CONNECTIONS
+        );
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(
+            parse_content_range("bytes 0-1023/4096"),
+            Some((0, 1023, 4096))
+        );
+        assert_eq!(parse_content_range("bytes 0-1023/*"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+    }
+
+    #[tokio::test]
+    async fn test_merge_parts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("big.msi");
+        for (index, body) in ["payload ", "in three ", "parts"].iter().enumerate() {
+            std::fs::write(part_path(&path, index), body).unwrap();
+        }
+        assert!(part_path(&path, 1).ends_with("big.msi.part1"));
+        assert!(is_part_file(&part_path(&path, 12)));
+        assert!(!is_part_file(&path));
+        assert!(!is_part_file(Path::new("big.msi.partial")));
+
+        let hash = merge_parts(&path, 3).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "payload in three parts"
+        );
+        assert_eq!(
+            hash,
+            crate::downloader::compute_hash(b"payload in three parts")
+        );
+        assert!(!part_path(&path, 0).exists());
+    }
+}
@@ -1,24 +1,41 @@
 //! Common download functionality shared between MSVC and SDK downloaders
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
 };
 use std::time::{Duration, Instant};
 
+use chrono::Utc;
 use futures::{stream, StreamExt};
+use reqwest::header::{CONTENT_RANGE, ETAG, LAST_MODIFIED, RANGE, RETRY_AFTER};
 use reqwest::{Client, StatusCode};
 use sha2::{Digest, Sha256};
 use tokio::{io::AsyncWriteExt, sync::RwLock, time::sleep};
 use tracing::debug;
 
-use super::hash::compute_file_hash;
+use super::auth::{authorize, BoxedAuthProvider};
+use super::channel::{load_known_good, select_stable, KnownGoodCombo, ReleaseChannel};
+use super::chunked::{self, ChunkedDownloads};
+use super::endpoints::{EndpointPool, EndpointStats};
+use super::hash::{compute_file_hash, is_hash_exempt};
+use super::layout::VsLayout;
+#[cfg(debug_assertions)]
+use super::progress::OrderCheckingHandler;
 use super::progress::{BoxedProgressHandler, IndicatifProgressHandler};
+use super::quarantine::{quarantine_payload, QuarantineRequest, QUARANTINE_DIR_NAME};
+use super::rate::RateLimiter;
+use super::resolution::{DefaultVersionResolver, VersionResolver};
 use super::traits::BoxedCacheManager;
-use super::{DownloadIndex, DownloadOptions, DownloadStatus, Package, PackagePayload};
+use super::{
+    DownloadIndex, DownloadOptions, DownloadStatus, Package, PackagePayload, PackagePreview,
+    PayloadProvenance, ResolutionReport, ResumeSummary, RunProgress, VerifyMode, VsManifest,
+};
 use crate::constants::download as dl_const;
 use crate::error::{MsvcKitError, Result};
+use crate::warning::{Warning, WarningCode};
 
 /// Common downloader with shared functionality
 pub struct CommonDownloader {
@@ -29,10 +46,221 @@ pub struct CommonDownloader {
     pub cache_manager: Option<BoxedCacheManager>,
 }
 
+/// A throttled response (HTTP 429/503) observed while downloading
+#[derive(Debug, Clone)]
+pub struct ThrottleEvent {
+    /// File that was being downloaded
+    pub file_name: String,
+    /// HTTP status code returned by the server
+    pub status: u16,
+    /// Wait requested by the server via `Retry-After`, if any
+    pub retry_after: Option<Duration>,
+    /// Time actually waited before retrying
+    pub waited: Duration,
+}
+
+/// A hash mismatch accepted because of [`VerifyMode::Warn`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashMismatchEvent {
+    /// Package the payload belongs to
+    pub package_id: String,
+    /// Payload file name
+    pub file_name: String,
+    /// SHA256 from the manifest
+    pub expected: String,
+    /// SHA256 of the file that was kept
+    pub actual: String,
+}
+
+/// An optional package left out because one of its payloads failed
+///
+/// Only recorded with
+/// [`continue_on_optional_failure`](DownloadOptions::continue_on_optional_failure).
+#[derive(Debug, Clone)]
+pub struct OptionalFailure {
+    /// Package that was left out
+    pub package_id: String,
+    /// Payload that failed
+    pub file_name: String,
+    /// Why the payload failed
+    pub error: String,
+}
+
+/// Summary of a `download_packages` run
+#[derive(Debug, Clone, Default)]
+pub struct DownloadReport {
+    /// Number of files downloaded
+    pub downloaded: usize,
+    /// Number of files skipped (already cached)
+    pub skipped: usize,
+    /// Bytes transferred over the network
+    pub bytes_transferred: u64,
+    /// Throttling events, in the order they occurred
+    pub throttle_events: Vec<ThrottleEvent>,
+    /// Hash mismatches kept in warn mode, in the order they were found
+    pub hash_mismatches: Vec<HashMismatchEvent>,
+    /// Request counters of every endpoint payloads were fetched from
+    pub endpoints: Vec<EndpointStats>,
+    /// Optional packages left out after a failed payload, to retry later
+    pub failed_optional: Vec<OptionalFailure>,
+}
+
+impl DownloadReport {
+    /// Total time spent waiting on throttled responses
+    pub fn total_throttle_wait(&self) -> Duration {
+        self.throttle_events.iter().map(|e| e.waited).sum()
+    }
+
+    /// Number of payloads that failed on an endpoint before another served them
+    pub fn failovers(&self) -> usize {
+        self.endpoints.iter().map(|e| e.failures).sum()
+    }
+
+    /// Ids of the optional packages left out, without duplicates
+    pub fn failed_optional_packages(&self) -> Vec<&str> {
+        let mut ids: Vec<&str> = Vec::new();
+        for failure in &self.failed_optional {
+            if !ids.contains(&failure.package_id.as_str()) {
+                ids.push(&failure.package_id);
+            }
+        }
+        ids
+    }
+
+    /// Payloads kept despite a hash mismatch and optional packages left out,
+    /// as [`Warning`]s
+    pub fn warnings(&self) -> Vec<Warning> {
+        let mismatches = self.hash_mismatches.iter().map(|m| {
+            Warning::new(
+                WarningCode::UnverifiedFile,
+                format!(
+                    "hash mismatch kept (package {}): expected {}, got {}",
+                    m.package_id, m.expected, m.actual
+                ),
+            )
+            .with_context(&m.file_name)
+        });
+        let failures = self.failed_optional.iter().map(|f| {
+            Warning::new(
+                WarningCode::OptionalPackageFailed,
+                format!(
+                    "optional package left out, {} failed: {}",
+                    f.file_name, f.error
+                ),
+            )
+            .with_context(&f.package_id)
+        });
+        mismatches.chain(failures).collect()
+    }
+}
+
+type ThrottleLog = Arc<Mutex<Vec<ThrottleEvent>>>;
+type MismatchLog = Arc<Mutex<Vec<HashMismatchEvent>>>;
+
+/// Per-run state shared by every payload task (cheap to clone)
+#[derive(Clone)]
+struct PayloadContext {
+    client: Client,
+    progress: BoxedProgressHandler,
+    auth: Option<BoxedAuthProvider>,
+    throttle_log: ThrottleLog,
+    mismatch_log: MismatchLog,
+    verify_mode: VerifyMode,
+    hash_exemptions: Arc<Vec<String>>,
+    /// Package id of each payload file, for mismatch reports
+    package_ids: Arc<HashMap<String, String>>,
+    offline: bool,
+    component: String,
+    per_file_timeout: Option<Duration>,
+    /// Shared payload store (`<cache_dir>/payloads`), keyed by SHA256
+    payload_cache: Option<PathBuf>,
+    /// Payloads available in the offline layout, keyed by file name
+    layout_payloads: Arc<HashMap<String, PathBuf>>,
+    /// Where payloads failing verification in enforce mode are moved
+    quarantine_dir: PathBuf,
+    /// Bandwidth limit shared by all transfers
+    rate_limiter: Option<Arc<RateLimiter>>,
+    /// Parallel range requests for large payloads
+    chunked: Option<ChunkedDownloads>,
+    /// Candidate endpoints and their health
+    endpoints: Arc<EndpointPool>,
+    /// Retry payloads failing verification over plain `http`
+    allow_http_fallback: bool,
+}
+
+impl PayloadContext {
+    /// Verification mode for a payload, taking exemptions into account
+    fn verify_mode_for(&self, payload: &PackagePayload) -> VerifyMode {
+        if is_hash_exempt(&payload.url, &self.hash_exemptions) {
+            VerifyMode::Off
+        } else {
+            self.verify_mode
+        }
+    }
+
+    /// Log and record a mismatch accepted in warn mode
+    fn record_hash_mismatch(&self, payload: &PackagePayload, expected: &str, actual: &str) {
+        let package_id = self
+            .package_ids
+            .get(&payload.file_name)
+            .cloned()
+            .unwrap_or_default();
+        tracing::warn!(
+            "Hash mismatch for {} (package {}): expected {}, got {}; keeping it (verify mode: warn)",
+            payload.file_name,
+            package_id,
+            expected,
+            actual
+        );
+        self.mismatch_log.lock().unwrap().push(HashMismatchEvent {
+            package_id,
+            file_name: payload.file_name.clone(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        });
+    }
+
+    /// Move a payload rejected in enforce mode into the quarantine directory
+    ///
+    /// Falls back to deleting the file if it cannot be quarantined, so a bad
+    /// copy is never picked up by a later run.
+    async fn quarantine(
+        &self,
+        payload: &PackagePayload,
+        path: &Path,
+        expected: &str,
+        actual: &str,
+        response: Option<(String, BTreeMap<String, String>)>,
+    ) {
+        let (final_url, headers) = match response {
+            Some((final_url, headers)) => (Some(final_url), headers),
+            None => (None, BTreeMap::new()),
+        };
+        let request = QuarantineRequest {
+            payload,
+            package_id: self
+                .package_ids
+                .get(&payload.file_name)
+                .cloned()
+                .unwrap_or_default(),
+            expected,
+            actual,
+            final_url,
+            headers,
+        };
+        if let Err(e) = quarantine_payload(&self.quarantine_dir, path, request).await {
+            tracing::warn!("Could not quarantine {}: {}", path.display(), e);
+            let _ = tokio::fs::remove_file(path).await;
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum PayloadOutcome {
     Skipped,
     Downloaded,
+    /// Cancelled after `per_file_timeout`, recorded as partial
+    TimedOut,
 }
 
 #[derive(Debug)]
@@ -53,6 +281,46 @@ impl CommonDownloader {
         }
     }
 
+    /// Tested MSVC / Windows SDK pair for the stable channel (None = latest channel)
+    ///
+    /// Fails if the manifest offers none of the known-good combinations.
+    pub(crate) async fn stable_combo(
+        &self,
+        manifest: &VsManifest,
+    ) -> Result<Option<KnownGoodCombo>> {
+        if self.options.channel != ReleaseChannel::Stable {
+            return Ok(None);
+        }
+        let online = !self.options.offline && self.options.layout_dir.is_none();
+        let combos = load_known_good(
+            Some(&self.client).filter(|_| online),
+            &self.manifest_cache_dir(),
+        )
+        .await;
+        select_stable(&combos, manifest).map(Some).ok_or_else(|| {
+            MsvcKitError::VersionNotFound(
+                "None of the known-good MSVC / Windows SDK combinations is in the manifest"
+                    .to_string(),
+            )
+        })
+    }
+
+    /// Resolver from the options, or [`DefaultVersionResolver`]
+    pub(crate) fn version_resolver(&self) -> &dyn VersionResolver {
+        match &self.options.version_resolver {
+            Some(resolver) => resolver.as_ref(),
+            None => &DefaultVersionResolver,
+        }
+    }
+
+    /// Log the selected version and pass it to the progress handler
+    pub(crate) fn report_resolution(&self, report: &ResolutionReport) {
+        tracing::info!("Resolved {}", report);
+        if let Some(handler) = &self.progress_handler {
+            handler.on_version_resolved(report);
+        }
+    }
+
     /// Set a custom progress handler
     pub fn with_progress_handler(mut self, handler: BoxedProgressHandler) -> Self {
         self.progress_handler = Some(handler);
@@ -76,6 +344,31 @@ impl CommonDownloader {
         }
     }
 
+    /// Load the VS manifest honoring the offline, auth and cache settings.
+    ///
+    /// The manifest is fetched with the downloader's HTTP client, so a custom
+    /// client or user agent applies to manifest requests as well.
+    ///
+    /// With a layout directory its `Catalog.json` is used. In offline mode
+    /// only the cached manifest is used; otherwise the manifest is fetched
+    /// (falling back to the cache if the network is down).
+    pub async fn load_manifest(&self) -> Result<VsManifest> {
+        if let Some(ref layout_dir) = self.options.layout_dir {
+            return VsLayout::open(layout_dir).await?.load_manifest().await;
+        }
+        let cache_dir = self.manifest_cache_dir();
+        if self.options.offline {
+            VsManifest::load_cached(&cache_dir).await
+        } else {
+            VsManifest::fetch_with_client(
+                &cache_dir,
+                &self.client,
+                self.options.auth_provider.as_ref(),
+            )
+            .await
+        }
+    }
+
     /// Download packages with progress display and local index for fast skip
     pub async fn download_packages(
         &self,
@@ -83,8 +376,29 @@ impl CommonDownloader {
         download_dir: &Path,
         component_name: &str,
     ) -> Result<Vec<PathBuf>> {
+        self.download_packages_with_report(packages, download_dir, component_name)
+            .await
+            .map(|(files, _)| files)
+    }
+
+    /// Download packages like [`download_packages`](Self::download_packages),
+    /// also returning a [`DownloadReport`] with counters and throttling events.
+    pub async fn download_packages_with_report(
+        &self,
+        packages: &[Package],
+        download_dir: &Path,
+        component_name: &str,
+    ) -> Result<(Vec<PathBuf>, DownloadReport)> {
         let all_payloads: Vec<PackagePayload> =
             packages.iter().flat_map(|p| p.payloads.clone()).collect();
+        let mut package_ids = HashMap::new();
+        for package in packages {
+            for payload in &package.payloads {
+                package_ids
+                    .entry(payload.file_name.clone())
+                    .or_insert_with(|| package.id.clone());
+            }
+        }
 
         let total_files = all_payloads.len();
         let total_size: u64 = all_payloads.iter().map(|p| p.size).sum();
@@ -94,15 +408,30 @@ impl CommonDownloader {
             .progress_handler
             .clone()
             .unwrap_or_else(|| Arc::new(IndicatifProgressHandler::new(total_size)));
+        #[cfg(debug_assertions)]
+        let progress_handler = OrderCheckingHandler::wrap(progress_handler);
 
         let index_path = download_dir.join("index.db");
         let index = DownloadIndex::load(&index_path).await?;
         let index = Arc::new(RwLock::new(index));
 
+        let layout_payloads = match self.options.layout_dir {
+            Some(ref layout_dir) => VsLayout::open(layout_dir).await?.find_payloads(packages),
+            None => HashMap::new(),
+        };
+
         // Calculate completed files from index
-        let (completed_bytes, completed_count) = self
+        let (completed_bytes, completed_count, mut missing) = self
             .calculate_initial_progress(&all_payloads, download_dir, &index)
             .await?;
+        missing.retain(|file_name| !layout_payloads.contains_key(file_name));
+
+        if self.options.offline && !missing.is_empty() {
+            return Err(MsvcKitError::OfflinePayloadsMissing {
+                component: component_name.to_string(),
+                files: missing,
+            });
+        }
 
         tracing::info!(
             "Index pre-scan: completed={} ({}), remaining={}, total_files={}, total_size={}",
@@ -114,130 +443,365 @@ impl CommonDownloader {
         );
 
         // Initialize progress
+        let previews: Vec<PackagePreview> = packages.iter().map(PackagePreview::from).collect();
+        progress_handler.on_packages_resolved(component_name, &previews);
         progress_handler.on_start(component_name, total_files, total_size);
         progress_handler.on_progress(completed_bytes);
 
-        let processed = Arc::new(AtomicUsize::new(0));
-        let skipped = Arc::new(AtomicUsize::new(0));
-        let downloaded = Arc::new(AtomicUsize::new(0));
-
-        let max_concurrency = self.options.parallel_downloads.max(1);
-        let mut current_concurrency = max_concurrency;
-
-        let mut downloaded_files = Vec::with_capacity(all_payloads.len());
-        let mut index_pos = 0;
-
-        // Track consecutive low-throughput batches for smarter adaptation
-        let mut low_throughput_streak = 0usize;
-
-        while index_pos < all_payloads.len() {
-            let end = (index_pos + current_concurrency).min(all_payloads.len());
-            let batch: Vec<_> = all_payloads[index_pos..end].to_vec();
-
-            let batch_start = Instant::now();
-            let mut batch_bytes = 0u64;
-
-            let results = stream::iter(batch.into_iter().map(|payload| {
-                let progress = progress_handler.clone();
-                let verify_hashes = self.options.verify_hashes;
-                let index = index.clone();
-                let client = self.client.clone();
-                let download_dir = download_dir.to_path_buf();
-                async move {
-                    download_single_payload_with_handler(
-                        &client,
-                        &payload,
-                        &download_dir,
-                        &index,
-                        &progress,
-                        verify_hashes,
-                    )
-                    .await
-                }
-            }))
-            .buffer_unordered(current_concurrency)
-            .collect::<Vec<_>>()
-            .await;
-
-            for res in results {
-                match res {
-                    Ok(r) => {
-                        processed.fetch_add(1, Ordering::Relaxed);
-
-                        match r.outcome {
-                            PayloadOutcome::Skipped => {
-                                skipped.fetch_add(1, Ordering::Relaxed);
+        // Every run started above ends in exactly one on_complete or on_error
+        let result = async {
+            // Totals and throughput survive an interruption, for the next run's ETA
+            let previous_run = index.read().await.run_progress(component_name).await?;
+            let mut run = RunProgress {
+                component: component_name.to_string(),
+                total_files,
+                total_bytes: total_size,
+                completed_files: completed_count,
+                completed_bytes,
+                bytes_per_sec: previous_run.map_or(0, |r| r.bytes_per_sec),
+                updated_at: Utc::now(),
+            };
+            if completed_count > 0 && !run.is_finished() {
+                let summary = ResumeSummary::from_run(download_dir, &run);
+                tracing::info!("{}: {}", component_name, summary);
+                progress_handler.on_resume(&summary);
+            }
+            index.write().await.record_run_progress(&run).await?;
+            let run_start = Instant::now();
+
+            let throttle_log: ThrottleLog = Arc::new(Mutex::new(Vec::new()));
+            let mismatch_log: MismatchLog = Arc::new(Mutex::new(Vec::new()));
+            let mut bytes_transferred = 0u64;
+            let ctx = PayloadContext {
+                client: self.client.clone(),
+                progress: progress_handler.clone(),
+                auth: self.options.auth_provider.clone(),
+                throttle_log: throttle_log.clone(),
+                mismatch_log: mismatch_log.clone(),
+                verify_mode: self.options.verify_mode,
+                hash_exemptions: Arc::new(self.options.hash_exemptions.clone()),
+                package_ids: Arc::new(package_ids),
+                offline: self.options.offline,
+                component: component_name.to_string(),
+                per_file_timeout: self.options.per_file_timeout,
+                payload_cache: self
+                    .cache_manager
+                    .as_ref()
+                    .map(|cm| cm.cache_dir().join("payloads")),
+                layout_payloads: Arc::new(layout_payloads),
+                quarantine_dir: self.options.target_dir.join(QUARANTINE_DIR_NAME),
+                rate_limiter: self
+                    .options
+                    .max_download_rate
+                    .map(|rate| Arc::new(RateLimiter::new(rate))),
+                chunked: self.options.chunked_downloads,
+                endpoints: Arc::new(EndpointPool::new(&self.options.alternate_endpoints)),
+                allow_http_fallback: self.options.allow_http_fallback,
+            };
+            let endpoints = ctx.endpoints.clone();
+
+            let processed = Arc::new(AtomicUsize::new(0));
+            let skipped = Arc::new(AtomicUsize::new(0));
+            let downloaded = Arc::new(AtomicUsize::new(0));
+
+            let max_concurrency = self.options.parallel_downloads.max(1);
+            let mut current_concurrency = max_concurrency;
+
+            let mut downloaded_files = Vec::with_capacity(all_payloads.len());
+            let mut index_pos = 0;
+
+            // Track consecutive low-throughput batches for smarter adaptation
+            let mut low_throughput_streak = 0usize;
+
+            // Packages whose failures are recorded instead of aborting the run
+            let optional_ids: HashSet<&str> = if self.options.continue_on_optional_failure {
+                packages
+                    .iter()
+                    .filter(|p| p.optional)
+                    .map(|p| p.id.as_str())
+                    .collect()
+            } else {
+                HashSet::new()
+            };
+            let mut failed_optional: Vec<OptionalFailure> = Vec::new();
+
+            let deadline = self
+                .options
+                .overall_timeout
+                .map(|limit| (tokio::time::Instant::now() + limit, limit));
+
+            while index_pos < all_payloads.len() {
+                let end = (index_pos + current_concurrency).min(all_payloads.len());
+                let batch: Vec<_> = all_payloads[index_pos..end].to_vec();
+                let mut in_flight: Vec<PackagePayload> = batch.clone();
+
+                let batch_start = Instant::now();
+                let mut batch_bytes = 0u64;
+
+                let mut results = stream::iter(batch.into_iter().map(|payload| {
+                    let ctx = ctx.clone();
+                    let index = index.clone();
+                    let download_dir = download_dir.to_path_buf();
+                    async move {
+                        let result = download_single_payload_with_handler(
+                            &ctx,
+                            &payload,
+                            &download_dir,
+                            &index,
+                        )
+                        .await;
+                        (payload.file_name, result)
+                    }
+                }))
+                .buffer_unordered(current_concurrency);
+
+                // (file that timed out or None for the overall timeout, limit)
+                let mut timed_out: Option<(Option<String>, Duration)> = None;
+                loop {
+                    let next = match deadline {
+                        Some((at, limit)) => {
+                            match tokio::time::timeout_at(at, results.next()).await {
+                                Ok(next) => next,
+                                Err(_) => {
+                                    timed_out = Some((None, limit));
+                                    break;
+                                }
                             }
-                            PayloadOutcome::Downloaded => {
-                                downloaded.fetch_add(1, Ordering::Relaxed);
+                        }
+                        None => results.next().await,
+                    };
+                    let Some((file_name, res)) = next else {
+                        break;
+                    };
+                    let size = in_flight
+                        .iter()
+                        .find(|p| p.file_name == file_name)
+                        .map_or(0, |p| p.size);
+                    in_flight.retain(|p| p.file_name != file_name);
+
+                    match res {
+                        Ok(r) => {
+                            processed.fetch_add(1, Ordering::Relaxed);
+
+                            match r.outcome {
+                                PayloadOutcome::Skipped => {
+                                    skipped.fetch_add(1, Ordering::Relaxed);
+                                }
+                                PayloadOutcome::Downloaded => {
+                                    downloaded.fetch_add(1, Ordering::Relaxed);
+                                    run.completed_files += 1;
+                                    run.completed_bytes += size;
+                                }
+                                PayloadOutcome::TimedOut => {
+                                    let limit = self.options.per_file_timeout.unwrap_or_default();
+                                    timed_out = Some((Some(file_name), limit));
+                                    break;
+                                }
+                            }
+
+                            downloaded_files.push(r.path);
+                            batch_bytes += r.transferred;
+                            bytes_transferred += r.transferred;
+                        }
+                        Err(e) => {
+                            let package_id = ctx.package_ids.get(&file_name).cloned();
+                            match package_id {
+                                Some(package_id) if optional_ids.contains(package_id.as_str()) => {
+                                    processed.fetch_add(1, Ordering::Relaxed);
+                                    tracing::warn!(
+                                        "{}: leaving out optional package {} ({} failed: {})",
+                                        component_name,
+                                        package_id,
+                                        file_name,
+                                        e
+                                    );
+                                    failed_optional.push(OptionalFailure {
+                                        package_id,
+                                        file_name,
+                                        error: e.to_string(),
+                                    });
+                                }
+                                _ => return Err(e),
                             }
                         }
+                    }
+                }
 
-                        downloaded_files.push(r.path);
-                        batch_bytes += r.transferred;
+                if let Some((file, limit)) = timed_out {
+                    // Cancel the remaining transfers and keep what they wrote as partial
+                    drop(results);
+                    run.updated_at = Utc::now();
+                    index.write().await.record_run_progress(&run).await?;
+                    for payload in &in_flight {
+                        mark_partial_on_disk(
+                            &index,
+                            payload,
+                            &download_dir.join(&payload.file_name),
+                        )
+                        .await;
                     }
-                    Err(e) => {
-                        progress_handler.on_error(&e.to_string());
-                        return Err(e);
+
+                    let mut pending: Vec<String> =
+                        in_flight.into_iter().map(|p| p.file_name).collect();
+                    if let Some(ref file) = file {
+                        pending.push(file.clone());
                     }
+                    pending.extend(all_payloads[end..].iter().map(|p| p.file_name.clone()));
+
+                    let report = DownloadReport {
+                        downloaded: downloaded.load(Ordering::Relaxed),
+                        skipped: skipped.load(Ordering::Relaxed),
+                        bytes_transferred,
+                        throttle_events: std::mem::take(&mut *throttle_log.lock().unwrap()),
+                        hash_mismatches: std::mem::take(&mut *mismatch_log.lock().unwrap()),
+                        endpoints: endpoints.stats(),
+                        failed_optional,
+                    };
+                    let err = MsvcKitError::DownloadTimeout {
+                        file,
+                        limit,
+                        pending,
+                        report: Box::new(report),
+                    };
+                    tracing::warn!("{}: {}", component_name, err);
+                    return Err(err);
                 }
-            }
 
-            // Update summary message
-            let p = processed.load(Ordering::Relaxed);
-            let s = skipped.load(Ordering::Relaxed);
-            let d = downloaded.load(Ordering::Relaxed);
-            progress_handler.on_message(&format!(
-                "{}/{} files | dl {} | skip {} | conc {}",
-                p, total_files, d, s, current_concurrency
-            ));
-
-            let batch_duration = batch_start.elapsed().as_secs_f64().max(0.001);
-            let throughput_mbps = (batch_bytes as f64 / batch_duration) / 1_000_000.0;
-
-            // Smarter adaptive heuristic using constants
-            if throughput_mbps < dl_const::LOW_THROUGHPUT_MBPS {
-                low_throughput_streak += 1;
-                if low_throughput_streak >= dl_const::LOW_THROUGHPUT_STREAK_THRESHOLD
-                    && current_concurrency > dl_const::MIN_CONCURRENCY
-                {
-                    current_concurrency -= 1;
+                // Update summary message
+                let p = processed.load(Ordering::Relaxed);
+                let s = skipped.load(Ordering::Relaxed);
+                let d = downloaded.load(Ordering::Relaxed);
+                progress_handler.on_message(&format!(
+                    "{}/{} files | dl {} | skip {} | conc {}",
+                    p, total_files, d, s, current_concurrency
+                ));
+
+                let batch_duration = batch_start.elapsed().as_secs_f64().max(0.001);
+                let throughput_mbps = (batch_bytes as f64 / batch_duration) / 1_000_000.0;
+
+                // Smarter adaptive heuristic using constants
+                if throughput_mbps < dl_const::LOW_THROUGHPUT_MBPS {
+                    low_throughput_streak += 1;
+                    if low_throughput_streak >= dl_const::LOW_THROUGHPUT_STREAK_THRESHOLD
+                        && current_concurrency > dl_const::MIN_CONCURRENCY
+                    {
+                        current_concurrency -= 1;
+                        low_throughput_streak = 0;
+                    }
+                } else if throughput_mbps > dl_const::HIGH_THROUGHPUT_MBPS {
                     low_throughput_streak = 0;
+                    if current_concurrency < max_concurrency {
+                        current_concurrency += 1;
+                    }
+                } else {
+                    low_throughput_streak = low_throughput_streak.saturating_sub(1);
                 }
-            } else if throughput_mbps > dl_const::HIGH_THROUGHPUT_MBPS {
-                low_throughput_streak = 0;
-                if current_concurrency < max_concurrency {
-                    current_concurrency += 1;
+
+                debug!(
+                    "Batch {}-{} throughput {:.1} MB/s, next concurrency {} (max {})",
+                    index_pos, end, throughput_mbps, current_concurrency, max_concurrency
+                );
+
+                if bytes_transferred > 0 {
+                    let elapsed = run_start.elapsed().as_secs_f64().max(0.001);
+                    run.bytes_per_sec = (bytes_transferred as f64 / elapsed) as u64;
                 }
-            } else {
-                low_throughput_streak = low_throughput_streak.saturating_sub(1);
+                run.updated_at = Utc::now();
+                index.write().await.record_run_progress(&run).await?;
+
+                index_pos = end;
             }
 
-            debug!(
-                "Batch {}-{} throughput {:.1} MB/s, next concurrency {} (max {})",
-                index_pos, end, throughput_mbps, current_concurrency, max_concurrency
-            );
+            // Payloads of a failed optional package are not installed at all
+            let failed_ids: HashSet<&str> = failed_optional
+                .iter()
+                .map(|f| f.package_id.as_str())
+                .collect();
+            if !failed_ids.is_empty() {
+                downloaded_files.retain(|path| {
+                    path.file_name()
+                        .and_then(|name| ctx.package_ids.get(&*name.to_string_lossy()))
+                        .is_none_or(|id| !failed_ids.contains(id.as_str()))
+                });
+            }
 
-            index_pos = end;
-        }
+            // Package IDs and versions for the SBOM export
+            {
+                let mut idx = index.write().await;
+                run.completed_files = total_files;
+                run.completed_bytes = total_size;
+                run.updated_at = Utc::now();
+                idx.record_run_progress(&run).await?;
+                for package in packages {
+                    if !failed_ids.contains(package.id.as_str()) {
+                        idx.record_package(&package.into()).await?;
+                    }
+                }
+            }
 
-        progress_handler.on_complete(
-            downloaded.load(Ordering::Relaxed),
-            skipped.load(Ordering::Relaxed),
-        );
+            let report = DownloadReport {
+                downloaded: downloaded.load(Ordering::Relaxed),
+                skipped: skipped.load(Ordering::Relaxed),
+                bytes_transferred,
+                throttle_events: std::mem::take(&mut *throttle_log.lock().unwrap()),
+                hash_mismatches: std::mem::take(&mut *mismatch_log.lock().unwrap()),
+                endpoints: endpoints.stats(),
+                failed_optional,
+            };
+
+            if !report.hash_mismatches.is_empty() {
+                tracing::warn!(
+                    "{}: kept {} payload(s) with mismatched hashes (verify mode: warn)",
+                    component_name,
+                    report.hash_mismatches.len()
+                );
+            }
 
-        Ok(downloaded_files)
+            if !report.throttle_events.is_empty() {
+                tracing::warn!(
+                    "{}: server throttled {} request(s), waited {:?} in total",
+                    component_name,
+                    report.throttle_events.len(),
+                    report.total_throttle_wait()
+                );
+            }
+
+            if report.failovers() > 0 {
+                for endpoint in &report.endpoints {
+                    tracing::warn!(
+                        "{}: endpoint {} served {} payload(s), failed {} time(s){}",
+                        component_name,
+                        endpoint.base,
+                        endpoint.successes,
+                        endpoint.failures,
+                        if endpoint.healthy { "" } else { " (unhealthy)" }
+                    );
+                }
+            }
+
+            Ok::<_, MsvcKitError>((downloaded_files, report))
+        }
+        .await;
+
+        match &result {
+            Ok((_, report)) => progress_handler.on_complete(report.downloaded, report.skipped),
+            Err(e) => progress_handler.on_error(&e.to_string()),
+        }
+        result
     }
 
     /// Calculate initial progress from already downloaded files
+    ///
+    /// Returns (completed bytes, completed count, file names still to download).
     async fn calculate_initial_progress(
         &self,
         payloads: &[PackagePayload],
         download_dir: &Path,
         index: &Arc<RwLock<DownloadIndex>>,
-    ) -> Result<(u64, usize)> {
+    ) -> Result<(u64, usize, Vec<String>)> {
         let mut completed_bytes = 0u64;
         let mut completed_count = 0usize;
+        let mut missing = Vec::new();
         let mut debug_logged = 0usize;
 
         for payload in payloads {
@@ -246,13 +810,14 @@ impl CommonDownloader {
                 idx.get_entry(&payload.file_name).await?
             };
             let path = download_dir.join(&payload.file_name);
+            let verify = self.options.verify_mode_for(&payload.url).is_enabled();
 
             // Check index for completed files (fast path - trust index with computed_hash)
             if let Some(ref entry) = cached {
                 if entry.status == DownloadStatus::Completed {
                     if let Some(ref computed) = entry.computed_hash {
                         let expected = payload.sha256.as_deref();
-                        if self.options.verify_hashes {
+                        if verify {
                             if let Some(exp) = expected {
                                 if !computed.eq_ignore_ascii_case(exp) && debug_logged < 10 {
                                     tracing::debug!(
@@ -284,9 +849,9 @@ impl CommonDownloader {
                             );
                             debug_logged += 1;
                         }
-                    } else if entry.hash_verified || !self.options.verify_hashes {
+                    } else if entry.hash_verified || !verify {
                         // hash_verified: trust index entry with verified hash
-                        // !verify_hashes: skip hash check, trust size match
+                        // !verify: skip hash check, trust size match
                         let check_path = if tokio::fs::metadata(&path).await.is_ok() {
                             &path
                         } else {
@@ -308,6 +873,7 @@ impl CommonDownloader {
                 if meta.len() == payload.size {
                     completed_bytes += payload.size;
                     completed_count += 1;
+                    continue;
                 } else if debug_logged < 10 {
                     tracing::debug!(
                         "File exists without matching index size, will redownload: file={} path={:?} actual={} expect={}",
@@ -319,25 +885,28 @@ impl CommonDownloader {
                     debug_logged += 1;
                 }
             }
+
+            missing.push(payload.file_name.clone());
         }
 
         if debug_logged >= 10 {
             tracing::debug!("Logged first 10 mismatch/missing cases; more may exist");
         }
 
-        Ok((completed_bytes, completed_count))
+        Ok((completed_bytes, completed_count, missing))
     }
 }
 
 /// Download a single payload file with progress handler
 async fn download_single_payload_with_handler(
-    client: &Client,
+    ctx: &PayloadContext,
     payload: &PackagePayload,
     download_dir: &Path,
     index: &Arc<RwLock<DownloadIndex>>,
-    progress: &BoxedProgressHandler,
-    verify_hashes: bool,
 ) -> Result<PayloadResult> {
+    let progress = &ctx.progress;
+    let verify_mode = ctx.verify_mode_for(payload);
+    let verify_hashes = verify_mode.is_enabled();
     let file_path = download_dir.join(&payload.file_name);
 
     // Fast path: check index for completed file with computed hash
@@ -358,16 +927,40 @@ async fn download_single_payload_with_handler(
                 if let Some(ref computed) = entry.computed_hash {
                     if verify_hashes {
                         if let Some(expected) = payload.sha256.as_deref() {
-                            if !computed.eq_ignore_ascii_case(expected) {
+                            if !computed.eq_ignore_ascii_case(expected)
+                                && verify_mode == VerifyMode::Warn
+                            {
+                                ctx.record_hash_mismatch(payload, expected, computed);
+                                progress.on_file_complete(&payload.file_name, "cached");
+                                return Ok(PayloadResult {
+                                    path: check_path,
+                                    transferred: 0,
+                                    outcome: PayloadOutcome::Skipped,
+                                });
+                            } else if !computed.eq_ignore_ascii_case(expected) {
                                 tracing::warn!(
                                     "Cached hash mismatch for {}, re-downloading",
                                     payload.file_name
                                 );
-                                {
+                                let provenance = {
                                     let mut idx = index.write().await;
+                                    let provenance =
+                                        idx.provenance(&payload.file_name).await.ok().flatten();
                                     let _ = idx.remove(&payload.file_name).await;
-                                }
-                                let _ = tokio::fs::remove_file(&check_path).await;
+                                    provenance
+                                };
+                                let response = provenance.map(|p| {
+                                    let headers =
+                                        [("etag", p.etag), ("last-modified", p.last_modified)]
+                                            .into_iter()
+                                            .filter_map(|(name, value)| {
+                                                Some((name.to_string(), value?))
+                                            })
+                                            .collect();
+                                    (p.final_url, headers)
+                                });
+                                ctx.quarantine(payload, &check_path, expected, computed, response)
+                                    .await;
                             } else {
                                 tracing::debug!(
                                     "Skipping {} (indexed hash, verified)",
@@ -420,10 +1013,15 @@ async fn download_single_payload_with_handler(
 
             if verify_hashes {
                 if let Some(expected_hash) = &payload.sha256 {
-                    if !computed_hash.eq_ignore_ascii_case(expected_hash) {
+                    let mismatch = !computed_hash.eq_ignore_ascii_case(expected_hash);
+                    if mismatch && verify_mode == VerifyMode::Enforce {
                         tracing::warn!("Hash mismatch for {}, re-downloading", payload.file_name);
-                        let _ = tokio::fs::remove_file(&file_path).await;
+                        ctx.quarantine(payload, &file_path, expected_hash, &computed_hash, None)
+                            .await;
                     } else {
+                        if mismatch {
+                            ctx.record_hash_mismatch(payload, expected_hash, &computed_hash);
+                        }
                         {
                             let mut idx = index.write().await;
                             idx.mark_completed(payload, file_path.clone(), Some(computed_hash))
@@ -478,24 +1076,178 @@ async fn download_single_payload_with_handler(
         }
     }
 
-    // Download the file with streaming hash computation
+    // Shared payload store: concurrent downloads of the same payload (e.g. the
+    // architecture-neutral packages of a multi-arch bundle) wait for each other
+    // and the later ones link the stored copy instead of downloading it again
+    let shared = match (&ctx.payload_cache, payload.sha256.as_deref()) {
+        (Some(dir), Some(sha256)) => Some((dir.join(sha256.to_lowercase()), sha256)),
+        _ => None,
+    };
+    let _payload_guard = match shared {
+        Some((_, sha256)) => Some(lock_payload(sha256).await),
+        None => None,
+    };
+    if let Some((ref stored, _)) = shared {
+        if link_stored_payload(stored, payload.size, &file_path).await {
+            {
+                let mut idx = index.write().await;
+                idx.mark_completed(payload, file_path.clone(), payload.sha256.clone())
+                    .await?;
+            }
+            tracing::debug!("Linked {} from shared payload cache", payload.file_name);
+            progress.on_progress(payload.size);
+            progress.on_file_complete(&payload.file_name, "shared cache");
+            return Ok(PayloadResult {
+                path: file_path,
+                transferred: 0,
+                outcome: PayloadOutcome::Skipped,
+            });
+        }
+    }
+
+    if let Some(source) = ctx.layout_payloads.get(&payload.file_name) {
+        if link_stored_payload(source, payload.size, &file_path).await {
+            let computed_hash = compute_file_hash(&file_path).await?;
+            let mismatch = verify_hashes
+                && payload
+                    .sha256
+                    .as_deref()
+                    .is_some_and(|expected| !computed_hash.eq_ignore_ascii_case(expected));
+            if mismatch && verify_mode == VerifyMode::Enforce {
+                tracing::warn!(
+                    "Layout copy of {} fails verification, downloading it instead",
+                    payload.file_name
+                );
+                let _ = tokio::fs::remove_file(&file_path).await;
+            } else {
+                if let (true, Some(expected)) = (mismatch, payload.sha256.as_deref()) {
+                    ctx.record_hash_mismatch(payload, expected, &computed_hash);
+                }
+                {
+                    let mut idx = index.write().await;
+                    idx.mark_completed(payload, file_path.clone(), Some(computed_hash))
+                        .await?;
+                }
+                tracing::debug!("Linked {} from {}", payload.file_name, source.display());
+                progress.on_progress(payload.size);
+                progress.on_file_complete(&payload.file_name, "layout");
+                return Ok(PayloadResult {
+                    path: file_path,
+                    transferred: 0,
+                    outcome: PayloadOutcome::Skipped,
+                });
+            }
+        }
+    }
+
+    if ctx.offline {
+        return Err(MsvcKitError::OfflinePayloadsMissing {
+            component: ctx.component.clone(),
+            files: vec![payload.file_name.clone()],
+        });
+    }
+
+    // Download the file with streaming hash computation. A download failing
+    // verification is retried, then replaced from the payload's alternate URLs
     debug!("Downloading: {}", payload.file_name);
     progress.on_file_start(&payload.file_name, payload.size);
-    let download_result =
-        download_file_with_streaming_hash(client, payload, &file_path, progress).await?;
-
-    // Use the hash computed during download (no need to re-read the file)
-    let computed_hash = download_result.computed_hash;
+    let sources: Vec<String> = std::iter::once(payload.url.clone())
+        .chain(payload.alternate_urls(ctx.allow_http_fallback))
+        .collect();
+    let mut attempt = 0usize;
+    // Hash of the last copy that failed verification
+    let mut mismatch: Option<String> = None;
+    let (download_result, computed_hash) = loop {
+        let Some(source) = sources.get(attempt / dl_const::HASH_MISMATCH_RETRIES) else {
+            return Err(MsvcKitError::HashMismatch {
+                file: payload.file_name.clone(),
+                expected: payload.sha256.clone().unwrap_or_default(),
+                actual: mismatch.unwrap_or_default(),
+            });
+        };
+        let from = PackagePayload {
+            url: source.clone(),
+            ..payload.clone()
+        };
+        let download = download_file_with_streaming_hash(ctx, &from, &file_path);
+        let result = match ctx.per_file_timeout {
+            Some(limit) => match tokio::time::timeout(limit, download).await {
+                Ok(result) => result,
+                Err(_) => {
+                    tracing::warn!("{} timed out after {:?}", payload.file_name, limit);
+                    if let Some(chunked) = ctx.chunked {
+                        chunked::remove_parts(&file_path, chunked.ranges(payload.size).len()).await;
+                    }
+                    mark_partial_on_disk(index, payload, &file_path).await;
+                    return Ok(PayloadResult {
+                        path: file_path,
+                        transferred: 0,
+                        outcome: PayloadOutcome::TimedOut,
+                    });
+                }
+            },
+            None => download.await,
+        };
+        let mut download_result = match result {
+            Ok(result) => result,
+            // An unreachable alternate is skipped, the mismatch stays the error
+            Err(e @ MsvcKitError::DownloadNetwork { .. }) if mismatch.is_some() => {
+                tracing::warn!("{} not available from {}: {}", payload.file_name, source, e);
+                attempt = (attempt / dl_const::HASH_MISMATCH_RETRIES + 1)
+                    * dl_const::HASH_MISMATCH_RETRIES;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if *source != payload.url {
+            download_result.provenance.url = payload.url.clone();
+            download_result.provenance.fallback_url = Some(source.clone());
+        }
 
-    if verify_hashes {
-        if let Some(expected_hash) = &payload.sha256 {
-            if !computed_hash.eq_ignore_ascii_case(expected_hash) {
-                return Err(MsvcKitError::HashMismatch {
-                    file: payload.file_name.clone(),
-                    expected: expected_hash.clone(),
-                    actual: computed_hash,
-                });
+        // Use the hash computed during download (no need to re-read the file)
+        let computed_hash = download_result.computed_hash.clone();
+        let expected_hash = match payload.sha256.as_deref() {
+            Some(expected) if verify_hashes && !computed_hash.eq_ignore_ascii_case(expected) => {
+                expected
             }
+            _ => break (download_result, computed_hash),
+        };
+        if verify_mode == VerifyMode::Warn {
+            ctx.record_hash_mismatch(payload, expected_hash, &computed_hash);
+            break (download_result, computed_hash);
+        }
+        ctx.quarantine(
+            &from,
+            &file_path,
+            expected_hash,
+            &computed_hash,
+            Some((
+                download_result.provenance.final_url.clone(),
+                download_result.headers.clone(),
+            )),
+        )
+        .await;
+        mismatch = Some(computed_hash);
+
+        attempt += 1;
+        match sources.get(attempt / dl_const::HASH_MISMATCH_RETRIES) {
+            Some(next) if next == source => tracing::warn!(
+                "{} failed verification, downloading it again",
+                payload.file_name
+            ),
+            Some(next) => tracing::warn!(
+                "{} failed verification from {}, downloading it from {}",
+                payload.file_name,
+                source,
+                next
+            ),
+            None => {}
+        }
+    };
+
+    if let Some((ref stored, sha256)) = shared {
+        if computed_hash.eq_ignore_ascii_case(sha256) {
+            store_payload(&file_path, stored).await;
         }
     }
 
@@ -504,6 +1256,8 @@ async fn download_single_payload_with_handler(
         let mut idx = index.write().await;
         idx.mark_completed(payload, file_path.clone(), Some(computed_hash))
             .await?;
+        idx.record_provenance(&payload.file_name, &download_result.provenance)
+            .await?;
     }
 
     progress.on_file_complete(&payload.file_name, "downloaded");
@@ -515,27 +1269,189 @@ async fn download_single_payload_with_handler(
     })
 }
 
+/// Record a cancelled transfer as partial with the bytes it wrote (best effort)
+async fn mark_partial_on_disk(
+    index: &Arc<RwLock<DownloadIndex>>,
+    payload: &PackagePayload,
+    path: &Path,
+) {
+    let Ok(meta) = tokio::fs::metadata(path).await else {
+        return;
+    };
+    let mut idx = index.write().await;
+    if let Err(e) = idx
+        .mark_partial(payload, path.to_path_buf(), meta.len())
+        .await
+    {
+        tracing::debug!("Could not record partial {}: {}", payload.file_name, e);
+    }
+}
+
+/// Hold the process-wide lock for a payload hash
+async fn lock_payload(sha256: &str) -> tokio::sync::OwnedMutexGuard<()> {
+    static LOCKS: std::sync::OnceLock<
+        Mutex<std::collections::HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+    > = std::sync::OnceLock::new();
+    let lock = LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap()
+        .entry(sha256.to_lowercase())
+        .or_default()
+        .clone();
+    lock.lock_owned().await
+}
+
+/// Hard link (or copy) a stored payload to `dest`; false if it is not stored
+async fn link_stored_payload(stored: &Path, size: u64, dest: &Path) -> bool {
+    match tokio::fs::metadata(stored).await {
+        Ok(meta) if meta.len() == size => {}
+        _ => return false,
+    }
+    if let Some(parent) = dest.parent() {
+        if tokio::fs::create_dir_all(parent).await.is_err() {
+            return false;
+        }
+    }
+    let _ = tokio::fs::remove_file(dest).await;
+    tokio::fs::hard_link(stored, dest).await.is_ok() || tokio::fs::copy(stored, dest).await.is_ok()
+}
+
+/// Add a verified download to the shared payload store (best effort)
+async fn store_payload(file: &Path, stored: &Path) {
+    if tokio::fs::metadata(stored).await.is_ok() {
+        return;
+    }
+    let result = async {
+        if let Some(parent) = stored.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        if tokio::fs::hard_link(file, stored).await.is_err() {
+            // Copy under a temporary name so a partial copy is never linked
+            let partial = stored.with_extension("partial");
+            tokio::fs::copy(file, &partial).await?;
+            tokio::fs::rename(&partial, stored).await?;
+        }
+        std::io::Result::Ok(())
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::debug!("Could not store {} in payload cache: {}", file.display(), e);
+    }
+}
+
 /// Result of streaming download with computed hash
 struct StreamingDownloadResult {
     /// SHA256 hash computed during download
     computed_hash: String,
+    /// Where the payload was served from
+    provenance: PayloadProvenance,
+    /// Response headers, kept for quarantine metadata
+    headers: BTreeMap<String, String>,
+}
+
+/// Parse a `Retry-After` header value (delta-seconds or HTTP-date)
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let delta = date.signed_duration_since(chrono::Utc::now());
+    Some(delta.to_std().unwrap_or(Duration::ZERO))
+}
+
+/// Whether a status code signals server-side throttling
+fn is_throttle_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::SERVICE_UNAVAILABLE
 }
 
 /// Download a single file with progress handler and streaming hash computation
 /// This computes the SHA256 hash while downloading, avoiding a second file read.
+///
+/// With alternate endpoints, a network failure moves on to the next candidate
+/// URL; every endpoint but the last gets `ENDPOINT_RETRIES` retries and
+/// throttled responses instead of `MAX_RETRIES` / `MAX_THROTTLE_RETRIES`, so a
+/// failing endpoint is left quickly.
 async fn download_file_with_streaming_hash(
-    client: &Client,
+    ctx: &PayloadContext,
+    payload: &PackagePayload,
+    path: &Path,
+) -> Result<StreamingDownloadResult> {
+    let candidates = ctx.endpoints.candidates(&payload.url);
+    let last = candidates.len() - 1;
+
+    for (i, (base, url)) in candidates.iter().enumerate() {
+        let retries = if i == last {
+            (dl_const::MAX_RETRIES, dl_const::MAX_THROTTLE_RETRIES)
+        } else {
+            (dl_const::ENDPOINT_RETRIES, dl_const::ENDPOINT_RETRIES)
+        };
+        match download_from_url(ctx, payload, url, path, retries).await {
+            Ok(result) => {
+                ctx.endpoints.record_success(base);
+                return Ok(result);
+            }
+            Err(e @ MsvcKitError::DownloadNetwork { .. }) => {
+                ctx.endpoints.record_failure(base);
+                if i == last {
+                    return Err(e);
+                }
+                tracing::warn!(
+                    "{} failed on {} ({}), trying {}",
+                    payload.file_name,
+                    base,
+                    e,
+                    candidates[i + 1].0
+                );
+            }
+            Err(e) => return Err(e),
+        }
+    }
+    unreachable!("a payload has at least one candidate URL")
+}
+
+/// Download a payload from one URL
+///
+/// `retries` is `(max_retries, max_throttle_retries)`. Throttled responses
+/// (429/503) honor `Retry-After` and use the separate throttle budget, so rate
+/// limiting does not exhaust the retries meant for genuine failures.
+async fn download_from_url(
+    ctx: &PayloadContext,
     payload: &PackagePayload,
+    url: &str,
     path: &Path,
-    progress: &BoxedProgressHandler,
+    (max_retries, max_throttle_retries): (usize, usize),
 ) -> Result<StreamingDownloadResult> {
-    for attempt in 0..=dl_const::MAX_RETRIES {
-        let response = match client.get(&payload.url).send().await {
+    let progress = &ctx.progress;
+    let mut attempt = 0usize;
+    let mut throttled = 0usize;
+    // Bytes passed to `on_progress` so far; discarded ranges and retried
+    // bodies only report what goes beyond it
+    let reported = AtomicU64::new(0);
+
+    let ranges = ctx
+        .chunked
+        .map(|c| c.ranges(payload.size))
+        .unwrap_or_default();
+    if !ranges.is_empty() {
+        let retries = (max_retries, max_throttle_retries);
+        match download_chunked(ctx, payload, url, path, &ranges, retries, &reported).await? {
+            Some(result) => return Ok(result),
+            None => tracing::debug!(
+                "{} is not served in ranges, downloading it over one connection",
+                payload.file_name
+            ),
+        }
+    }
+
+    let mut reported = reported.into_inner();
+    'download: loop {
+        let request = authorize(ctx.client.get(url), ctx.auth.as_ref(), url).await?;
+        let response = match request.send().await {
             Ok(resp) => resp,
             Err(e) => {
-                if attempt < dl_const::MAX_RETRIES
-                    && (e.is_connect() || e.is_timeout() || e.is_body())
-                {
+                if attempt < max_retries && (e.is_connect() || e.is_timeout() || e.is_body()) {
                     let backoff = Duration::from_secs(1 << attempt);
                     tracing::warn!(
                         "Retrying {} (request error: {}, attempt {}, backoff {:?})",
@@ -544,22 +1460,28 @@ async fn download_file_with_streaming_hash(
                         attempt + 1,
                         backoff
                     );
+                    attempt += 1;
                     sleep(backoff).await;
                     continue;
                 }
                 return Err(MsvcKitError::DownloadNetwork {
                     file: payload.file_name.clone(),
-                    url: payload.url.clone(),
+                    url: url.to_string(),
                     source: e,
                 });
             }
         };
 
-        if (response.status().is_server_error()
-            || response.status() == StatusCode::TOO_MANY_REQUESTS)
-            && attempt < dl_const::MAX_RETRIES
+        let status = response.status();
+        if is_throttle_status(status) && throttled < max_throttle_retries {
+            wait_throttled(ctx, payload, &response, throttled).await;
+            throttled += 1;
+            continue;
+        }
+
+        if (status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS)
+            && attempt < max_retries
         {
-            let status = response.status();
             let backoff = Duration::from_secs(1 << attempt);
             tracing::warn!(
                 "Retrying {} (status {}, attempt {}, backoff {:?})",
@@ -568,14 +1490,15 @@ async fn download_file_with_streaming_hash(
                 attempt + 1,
                 backoff
             );
+            attempt += 1;
             sleep(backoff).await;
             continue;
         }
 
-        if !response.status().is_success() {
+        if !status.is_success() {
             return Err(MsvcKitError::DownloadNetwork {
                 file: payload.file_name.clone(),
-                url: payload.url.clone(),
+                url: url.to_string(),
                 source: response.error_for_status().unwrap_err(),
             });
         }
@@ -584,9 +1507,34 @@ async fn download_file_with_streaming_hash(
             tokio::fs::create_dir_all(parent).await?;
         }
 
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let mut provenance = PayloadProvenance {
+            url: payload.url.clone(),
+            final_url: response.url().to_string(),
+            downloaded_at: Utc::now(),
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            fallback_url: None,
+        };
+
+        let headers: BTreeMap<String, String> = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+
         let mut file = tokio::fs::File::create(path).await?;
         let mut hasher = Sha256::new();
         let mut stream = response.bytes_stream();
+        let mut written = 0u64;
 
         while let Some(item) = stream.next().await {
             match item {
@@ -594,13 +1542,17 @@ async fn download_file_with_streaming_hash(
                     // Write to file and update hash simultaneously
                     file.write_all(&chunk).await?;
                     hasher.update(&chunk);
-                    progress.on_progress(chunk.len() as u64);
+                    written += chunk.len() as u64;
+                    report_new_bytes(progress, &mut reported, written);
+                    if let Some(limiter) = &ctx.rate_limiter {
+                        limiter.acquire(chunk.len()).await;
+                    }
                 }
                 Err(e) => {
                     // Body streaming error - retry
                     let _ = tokio::fs::remove_file(path).await;
 
-                    if attempt < dl_const::MAX_RETRIES {
+                    if attempt < max_retries {
                         let backoff = Duration::from_secs(1 << attempt);
                         tracing::warn!(
                             "Retrying {} (body read error: {}, attempt {}, backoff {:?})",
@@ -609,13 +1561,14 @@ async fn download_file_with_streaming_hash(
                             attempt + 1,
                             backoff
                         );
+                        attempt += 1;
                         sleep(backoff).await;
-                        continue;
+                        continue 'download;
                     }
 
                     return Err(MsvcKitError::DownloadNetwork {
                         file: payload.file_name.clone(),
-                        url: payload.url.clone(),
+                        url: url.to_string(),
                         source: e,
                     });
                 }
@@ -626,12 +1579,268 @@ async fn download_file_with_streaming_hash(
 
         // Compute final hash
         let computed_hash = hex::encode(hasher.finalize());
-        return Ok(StreamingDownloadResult { computed_hash });
+        provenance.downloaded_at = Utc::now();
+        return Ok(StreamingDownloadResult {
+            computed_hash,
+            provenance,
+            headers,
+        });
     }
+}
 
-    Err(MsvcKitError::Other(format!(
-        "Download failed for {} after {} retries",
+/// Wait out a throttled (429/503) response to a request for `payload`
+///
+/// Honors `Retry-After` (capped at `MAX_RETRY_AFTER_SECS`), falling back to
+/// exponential backoff on the `throttled` responses seen so far, and reports
+/// the wait to the progress handler and the run's throttle log.
+async fn wait_throttled(
+    ctx: &PayloadContext,
+    payload: &PackagePayload,
+    response: &reqwest::Response,
+    throttled: usize,
+) {
+    let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after);
+    let fallback = Duration::from_secs(1 << throttled.min(6));
+    let wait = retry_after
+        .unwrap_or(fallback)
+        .min(Duration::from_secs(dl_const::MAX_RETRY_AFTER_SECS));
+
+    tracing::warn!(
+        "Throttled on {} (status {}, retry-after {:?}, waiting {:?})",
         payload.file_name,
-        dl_const::MAX_RETRIES
-    )))
+        status,
+        retry_after,
+        wait
+    );
+    ctx.progress
+        .on_throttled(&payload.file_name, status.as_u16(), wait);
+    ctx.throttle_log.lock().unwrap().push(ThrottleEvent {
+        file_name: payload.file_name.clone(),
+        status: status.as_u16(),
+        retry_after,
+        waited: wait,
+    });
+    sleep(wait).await;
+}
+
+/// Pass the bytes of `written` beyond `reported` to `on_progress`
+///
+/// A transfer that restarts counts `written` from zero again, so bytes are
+/// reported once however often they are downloaded.
+fn report_new_bytes(progress: &BoxedProgressHandler, reported: &mut u64, written: u64) -> u64 {
+    let new = written.saturating_sub(*reported);
+    if new > 0 {
+        progress.on_progress(new);
+        *reported = written;
+    }
+    new
+}
+
+/// Download `payload` from `url` as parallel byte ranges merged into `path`
+///
+/// Returns `None`, with no part files left behind, when the server does not
+/// answer a range request with the requested `206 Partial Content` slice.
+/// `reported` sums the bytes passed to `on_progress` over all ranges.
+async fn download_chunked(
+    ctx: &PayloadContext,
+    payload: &PackagePayload,
+    url: &str,
+    path: &Path,
+    ranges: &[(u64, u64)],
+    retries: (usize, usize),
+    reported: &AtomicU64,
+) -> Result<Option<StreamingDownloadResult>> {
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let transfers = ranges.iter().enumerate().map(|(index, &range)| {
+        download_range(
+            ctx,
+            payload,
+            url,
+            chunked::part_path(path, index),
+            range,
+            retries,
+            reported,
+        )
+    });
+    let responses = match futures::future::try_join_all(transfers).await {
+        Ok(responses) => responses,
+        Err(e) => {
+            chunked::remove_parts(path, ranges.len()).await;
+            return Err(e);
+        }
+    };
+    let Some(responses) = responses.into_iter().collect::<Option<Vec<_>>>() else {
+        chunked::remove_parts(path, ranges.len()).await;
+        return Ok(None);
+    };
+
+    let computed_hash = chunked::merge_parts(path, ranges.len()).await?;
+    let (final_url, headers) = responses.into_iter().next().unwrap_or_default();
+    let header = |name: &str| headers.get(name).cloned();
+    let provenance = PayloadProvenance {
+        url: payload.url.clone(),
+        final_url,
+        downloaded_at: Utc::now(),
+        etag: header(ETAG.as_str()),
+        last_modified: header(LAST_MODIFIED.as_str()),
+        fallback_url: None,
+    };
+    Ok(Some(StreamingDownloadResult {
+        computed_hash,
+        provenance,
+        headers,
+    }))
+}
+
+/// Download the inclusive byte `range` of `payload` into `part`
+///
+/// Returns the final URL and response headers, or `None` if the server
+/// answered with anything but the requested slice. Retries and throttled
+/// responses are handled as in [`download_from_url`]; a retried body only
+/// reports the bytes beyond those reported before, added to `reported`.
+async fn download_range(
+    ctx: &PayloadContext,
+    payload: &PackagePayload,
+    url: &str,
+    part: PathBuf,
+    (start, end): (u64, u64),
+    (max_retries, max_throttle_retries): (usize, usize),
+    reported: &AtomicU64,
+) -> Result<Option<(String, BTreeMap<String, String>)>> {
+    let progress = &ctx.progress;
+    let mut attempt = 0usize;
+    let mut throttled = 0usize;
+    let mut range_reported = 0u64;
+
+    'range: loop {
+        let request = ctx
+            .client
+            .get(url)
+            .header(RANGE, format!("bytes={}-{}", start, end));
+        let request = authorize(request, ctx.auth.as_ref(), url).await?;
+        let response = match request.send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                if attempt < max_retries && (e.is_connect() || e.is_timeout() || e.is_body()) {
+                    let backoff = Duration::from_secs(1 << attempt);
+                    tracing::warn!(
+                        "Retrying {} bytes {}-{} (request error: {}, attempt {}, backoff {:?})",
+                        payload.file_name,
+                        start,
+                        end,
+                        e,
+                        attempt + 1,
+                        backoff
+                    );
+                    attempt += 1;
+                    sleep(backoff).await;
+                    continue;
+                }
+                return Err(MsvcKitError::DownloadNetwork {
+                    file: payload.file_name.clone(),
+                    url: url.to_string(),
+                    source: e,
+                });
+            }
+        };
+
+        let status = response.status();
+        if is_throttle_status(status) && throttled < max_throttle_retries {
+            wait_throttled(ctx, payload, &response, throttled).await;
+            throttled += 1;
+            continue;
+        }
+        if (status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS)
+            && attempt < max_retries
+        {
+            let backoff = Duration::from_secs(1 << attempt);
+            tracing::warn!(
+                "Retrying {} bytes {}-{} (status {}, attempt {}, backoff {:?})",
+                payload.file_name,
+                start,
+                end,
+                status,
+                attempt + 1,
+                backoff
+            );
+            attempt += 1;
+            sleep(backoff).await;
+            continue;
+        }
+
+        let served = response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(chunked::parse_content_range);
+        if status != StatusCode::PARTIAL_CONTENT || served != Some((start, end, payload.size)) {
+            tracing::debug!(
+                "{} bytes {}-{}: status {}, content range {:?}",
+                payload.file_name,
+                start,
+                end,
+                status,
+                served
+            );
+            return Ok(None);
+        }
+
+        let final_url = response.url().to_string();
+        let headers: BTreeMap<String, String> = response
+            .headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((name.as_str().to_string(), value.to_str().ok()?.to_string()))
+            })
+            .collect();
+
+        let mut file = tokio::fs::File::create(&part).await?;
+        let mut stream = response.bytes_stream();
+        let mut written = 0u64;
+        while let Some(item) = stream.next().await {
+            match item {
+                Ok(chunk) => {
+                    file.write_all(&chunk).await?;
+                    written += chunk.len() as u64;
+                    let new = report_new_bytes(progress, &mut range_reported, written);
+                    reported.fetch_add(new, Ordering::Relaxed);
+                    if let Some(limiter) = &ctx.rate_limiter {
+                        limiter.acquire(chunk.len()).await;
+                    }
+                }
+                Err(e) => {
+                    if attempt < max_retries {
+                        let backoff = Duration::from_secs(1 << attempt);
+                        tracing::warn!(
+                            "Retrying {} bytes {}-{} (body read error: {}, attempt {}, backoff {:?})",
+                            payload.file_name,
+                            start,
+                            end,
+                            e,
+                            attempt + 1,
+                            backoff
+                        );
+                        attempt += 1;
+                        sleep(backoff).await;
+                        continue 'range;
+                    }
+                    return Err(MsvcKitError::DownloadNetwork {
+                        file: payload.file_name.clone(),
+                        url: url.to_string(),
+                        source: e,
+                    });
+                }
+            }
+        }
+        file.flush().await?;
+        return Ok(Some((final_url, headers)));
+    }
 }
@@ -10,7 +10,7 @@ pub fn test_progress_handler() -> Arc<dyn super::progress::ProgressHandler> {
 
 #[tokio::test]
 async fn download_options_builder_works() {
-    use super::DownloadOptions;
+    use super::{DownloadOptions, VerifyMode};
     use crate::version::Architecture;
 
     let options = DownloadOptions::builder()
@@ -23,19 +23,19 @@ async fn download_options_builder_works() {
     assert_eq!(options.target_dir.to_str().unwrap(), "/tmp/test");
     assert_eq!(options.arch, Architecture::X64);
     assert_eq!(options.parallel_downloads, 8);
-    assert!(!options.verify_hashes);
+    assert_eq!(options.verify_mode, VerifyMode::Off);
 }
 
 #[tokio::test]
 async fn download_options_default_values() {
-    use super::DownloadOptions;
+    use super::{DownloadOptions, VerifyMode};
     use crate::constants::download::DEFAULT_PARALLEL_DOWNLOADS;
 
     let options = DownloadOptions::default();
 
     assert!(options.msvc_version.is_none());
     assert!(options.sdk_version.is_none());
-    assert!(options.verify_hashes);
+    assert_eq!(options.verify_mode, VerifyMode::Enforce);
     assert_eq!(options.parallel_downloads, DEFAULT_PARALLEL_DOWNLOADS);
     assert!(options.http_client.is_none());
     assert!(options.progress_handler.is_none());
@@ -86,6 +86,7 @@ async fn create_http_client_with_config_works() {
         user_agent: "test-agent/1.0".to_string(),
         connect_timeout: Some(Duration::from_secs(10)),
         timeout: Some(Duration::from_secs(60)),
+        root_certificates: Vec::new(),
     };
 
     let client = create_http_client_with_config(&config);
@@ -169,3 +170,1305 @@ async fn download_options_builder_sets_cache_manager() {
     let cm = options.cache_manager.unwrap();
     assert_eq!(cm.cache_dir(), temp_dir.path());
 }
+
+#[test]
+fn parse_retry_after_values() {
+    use super::common::parse_retry_after;
+    use std::time::Duration;
+
+    assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+    assert_eq!(parse_retry_after(" 0 "), Some(Duration::ZERO));
+    // HTTP-date in the past means "retry now"
+    assert_eq!(
+        parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(parse_retry_after("soon"), None);
+}
+
+#[tokio::test]
+async fn download_packages_honors_retry_after() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadOptions, Package, PackagePayload};
+
+    let body = b"payload body";
+    let mut server = mockito::Server::new_async().await;
+    let throttled = server
+        .mock("GET", "/payload.vsix")
+        .with_status(429)
+        .with_header("Retry-After", "0")
+        .expect(1)
+        .create_async()
+        .await;
+    let ok = server
+        .mock("GET", "/payload.vsix")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .progress_handler(test_progress_handler())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: format!("{}/payload.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let (files, report) = downloader
+        .download_packages_with_report(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+
+    throttled.assert_async().await;
+    ok.assert_async().await;
+    assert_eq!(files.len(), 1);
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(report.bytes_transferred, body.len() as u64);
+    assert_eq!(report.throttle_events.len(), 1);
+    assert_eq!(report.throttle_events[0].status, 429);
+    assert_eq!(
+        report.throttle_events[0].retry_after,
+        Some(std::time::Duration::ZERO)
+    );
+}
+
+#[tokio::test]
+async fn download_packages_records_provenance() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadIndex, DownloadOptions, Package, PackagePayload};
+
+    let body = b"redirected payload";
+    let mut server = mockito::Server::new_async().await;
+    let redirect = server
+        .mock("GET", "/payload.vsix")
+        .with_status(302)
+        .with_header("Location", &format!("{}/blob/payload.vsix", server.url()))
+        .create_async()
+        .await;
+    let blob = server
+        .mock("GET", "/blob/payload.vsix")
+        .with_status(200)
+        .with_header("ETag", "\"0x8DC1\"")
+        .with_header("Last-Modified", "Wed, 01 Jan 2025 00:00:00 GMT")
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .progress_handler(test_progress_handler())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    let url = format!("{}/payload.vsix", server.url());
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: url.clone(),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+    downloader
+        .download_packages_with_report(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+    redirect.assert_async().await;
+    blob.assert_async().await;
+
+    let index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+        .await
+        .unwrap();
+    let provenance = index.provenance("payload.vsix").await.unwrap().unwrap();
+    assert_eq!(provenance.url, url);
+    assert_eq!(
+        provenance.final_url,
+        format!("{}/blob/payload.vsix", server.url())
+    );
+    assert_eq!(provenance.etag.as_deref(), Some("\"0x8DC1\""));
+    assert_eq!(
+        provenance.last_modified.as_deref(),
+        Some("Wed, 01 Jan 2025 00:00:00 GMT")
+    );
+
+    let export_path = temp_dir.path().join("index.json");
+    index.export(&export_path).await.unwrap();
+    let document: super::IndexExport =
+        serde_json::from_slice(&std::fs::read(&export_path).unwrap()).unwrap();
+    assert_eq!(document.entries[0].provenance.as_ref(), Some(&provenance));
+}
+
+#[tokio::test]
+async fn download_packages_fails_over_to_alternate_endpoint() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadIndex, DownloadOptions, Package, PackagePayload};
+
+    let body = b"mirrored payload";
+    let mut primary = mockito::Server::new_async().await;
+    let failing = primary
+        .mock("GET", "/download/payload.vsix")
+        .with_status(500)
+        .expect(1 + crate::constants::download::ENDPOINT_RETRIES)
+        .create_async()
+        .await;
+    let mut mirror = mockito::Server::new_async().await;
+    let served = mirror
+        .mock("GET", "/vs/download/payload.vsix")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .progress_handler(test_progress_handler())
+        .alternate_endpoint(format!("{}/vs/", mirror.url()))
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    let url = format!("{}/download/payload.vsix", primary.url());
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: url.clone(),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let (files, report) = downloader
+        .download_packages_with_report(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+    failing.assert_async().await;
+    served.assert_async().await;
+    assert_eq!(std::fs::read(&files[0]).unwrap(), body);
+    assert_eq!(report.failovers(), 1);
+
+    let primary_stats = report
+        .endpoints
+        .iter()
+        .find(|e| e.base == format!("{}/", primary.url()))
+        .unwrap();
+    assert_eq!((primary_stats.successes, primary_stats.failures), (0, 1));
+    let mirror_stats = report
+        .endpoints
+        .iter()
+        .find(|e| e.base == format!("{}/vs/", mirror.url()))
+        .unwrap();
+    assert_eq!((mirror_stats.successes, mirror_stats.failures), (1, 0));
+
+    // Provenance keeps the manifest URL and records where it was served from
+    let index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+        .await
+        .unwrap();
+    let provenance = index.provenance("payload.vsix").await.unwrap().unwrap();
+    assert_eq!(provenance.url, url);
+    assert_eq!(
+        provenance.final_url,
+        format!("{}/vs/download/payload.vsix", mirror.url())
+    );
+}
+
+#[tokio::test]
+async fn download_packages_sends_auth_headers() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadOptions, Package, PackagePayload, StaticTokenAuth};
+
+    let body = b"mirrored payload";
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/mirror/payload.vsix")
+        .match_header("authorization", "Bearer secret")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let auth = StaticTokenAuth::bearer(format!("{}/mirror/", server.url()), "secret");
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .auth_provider(Arc::new(auth))
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: format!("{}/mirror/payload.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: None,
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let files = downloader
+        .download_packages(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+
+    mock.assert_async().await;
+    assert_eq!(std::fs::read(&files[0]).unwrap(), body);
+}
+
+#[tokio::test]
+async fn download_packages_sends_branded_user_agent() {
+    use super::common::CommonDownloader;
+    use super::{DownloadOptions, Package, PackagePayload};
+
+    let body = b"branded payload";
+    let user_agent = format!("vx/0.5.0 msvc-kit/{}", env!("CARGO_PKG_VERSION"));
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/payload.vsix")
+        .match_header("user-agent", user_agent.as_str())
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .branding("vx", "0.5.0")
+        .build();
+    assert_eq!(options.user_agent.as_deref(), Some(user_agent.as_str()));
+    let client = options.client();
+    let downloader = CommonDownloader::with_client(options, client)
+        .with_progress_handler(test_progress_handler());
+
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: format!("{}/payload.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: None,
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    downloader
+        .download_packages(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+    mock.assert_async().await;
+}
+
+#[tokio::test]
+async fn download_packages_offline_reports_missing_payloads() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadOptions, Package, PackagePayload};
+    use crate::error::MsvcKitError;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    std::fs::write(temp_dir.path().join("cached.vsix"), b"cached").unwrap();
+
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .verify_hashes(false)
+        .offline(true)
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+
+    let payload = |name: &str, size: u64| PackagePayload {
+        file_name: name.to_string(),
+        url: format!("https://example.invalid/{}", name),
+        size,
+        sha256: None,
+        mirrors: Vec::new(),
+    };
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![payload("cached.vsix", 6), payload("missing.vsix", 10)],
+        total_size: 16,
+        install_size: 0,
+        optional: false,
+    };
+
+    let err = downloader
+        .download_packages(std::slice::from_ref(&package), temp_dir.path(), "Test")
+        .await
+        .unwrap_err();
+    match err {
+        MsvcKitError::OfflinePayloadsMissing { component, files } => {
+            assert_eq!(component, "Test");
+            assert_eq!(files, vec!["missing.vsix".to_string()]);
+        }
+        other => panic!("unexpected error: {other}"),
+    }
+
+    // Once every payload is cached, offline mode succeeds without the network
+    std::fs::write(temp_dir.path().join("missing.vsix"), b"0123456789").unwrap();
+    let files = downloader
+        .download_packages(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+    assert_eq!(files.len(), 2);
+}
+
+#[test]
+fn download_options_builder_sets_offline() {
+    use super::DownloadOptions;
+
+    let options = DownloadOptions::builder().offline(true).build();
+    assert!(options.offline);
+}
+
+#[tokio::test]
+async fn download_packages_shares_payloads_through_cache() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::traits::FileSystemCacheManager;
+    use super::{DownloadOptions, Package, PackagePayload};
+
+    let body = b"neutral payload";
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/neutral.vsix")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let cache_dir = tempfile::TempDir::new().unwrap();
+    let package = Package {
+        id: "Test.Neutral".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "neutral.vsix".to_string(),
+            url: format!("{}/neutral.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    // Two concurrent downloads into different directories, as in a split
+    // multi-arch bundle
+    let dirs = [
+        tempfile::TempDir::new().unwrap(),
+        tempfile::TempDir::new().unwrap(),
+    ];
+    let runs = dirs.iter().map(|dir| {
+        let options = DownloadOptions::builder()
+            .target_dir(dir.path())
+            .cache_manager(Arc::new(FileSystemCacheManager::new(cache_dir.path())))
+            .build();
+        let downloader = CommonDownloader::with_client(options.clone(), create_http_client())
+            .with_progress_handler(test_progress_handler())
+            .with_cache_manager(options.cache_manager.clone().unwrap());
+        let package = package.clone();
+        async move {
+            downloader
+                .download_packages_with_report(&[package], dir.path(), "Test")
+                .await
+        }
+    });
+    let results = futures::future::try_join_all(runs).await.unwrap();
+
+    mock.assert_async().await;
+    let downloaded: usize = results.iter().map(|(_, r)| r.downloaded).sum();
+    let skipped: usize = results.iter().map(|(_, r)| r.skipped).sum();
+    assert_eq!((downloaded, skipped), (1, 1));
+    for (files, _) in &results {
+        assert_eq!(std::fs::read(&files[0]).unwrap(), body);
+    }
+}
+
+#[tokio::test]
+async fn download_packages_verify_mode_warn_and_exemptions() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadOptions, Package, PackagePayload, VerifyMode};
+    use crate::MsvcKitError;
+
+    // A proxy re-signed the payload, so its hash no longer matches the manifest
+    let body = b"re-signed payload";
+    let expected = super::hash::compute_hash(b"original payload");
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/payload.vsix")
+        .with_status(200)
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: format!("{}/payload.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: Some(expected.clone()),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+    let download = |mode: VerifyMode, exemptions: Vec<String>| {
+        let package = package.clone();
+        async move {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let options = DownloadOptions::builder()
+                .target_dir(temp_dir.path())
+                .verify_mode(mode)
+                .hash_exemptions(exemptions)
+                .progress_handler(test_progress_handler())
+                .build();
+            let downloader = CommonDownloader::with_client(options, create_http_client())
+                .with_progress_handler(test_progress_handler());
+            downloader
+                .download_packages_with_report(&[package], temp_dir.path(), "Test")
+                .await
+                .map(|(_, report)| report)
+        }
+    };
+
+    let err = download(VerifyMode::Enforce, vec![]).await.unwrap_err();
+    assert!(matches!(err, MsvcKitError::HashMismatch { .. }));
+
+    let report = download(VerifyMode::Warn, vec![]).await.unwrap();
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(report.hash_mismatches.len(), 1);
+    assert_eq!(report.hash_mismatches[0].package_id, "Test.Package");
+    assert_eq!(report.hash_mismatches[0].expected, expected);
+    assert_eq!(
+        report.hash_mismatches[0].actual,
+        super::hash::compute_hash(body)
+    );
+    let warnings = report.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, crate::WarningCode::UnverifiedFile);
+
+    let host = server.host_with_port();
+    let report = download(VerifyMode::Enforce, vec![host]).await.unwrap();
+    assert_eq!(report.downloaded, 1);
+    assert!(report.hash_mismatches.is_empty());
+}
+
+#[tokio::test]
+async fn download_packages_quarantines_hash_mismatches() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{
+        list_quarantine, DownloadOptions, Package, PackagePayload, VerifyMode, QUARANTINE_DIR_NAME,
+    };
+    use crate::MsvcKitError;
+
+    let body = b"tampered payload";
+    let expected = super::hash::compute_hash(b"original payload");
+    let mut server = mockito::Server::new_async().await;
+    let _mock = server
+        .mock("GET", "/payload.vsix")
+        .with_status(200)
+        .with_header("etag", "\"v1\"")
+        .with_body(body)
+        .create_async()
+        .await;
+
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: format!("{}/payload.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: Some(expected.clone()),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let download_dir = temp_dir.path().join("downloads");
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .verify_mode(VerifyMode::Enforce)
+        .progress_handler(test_progress_handler())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+
+    let err = downloader
+        .download_packages_with_report(std::slice::from_ref(&package), &download_dir, "Test")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, MsvcKitError::HashMismatch { .. }));
+    assert!(!download_dir.join("payload.vsix").exists());
+
+    let actual = super::hash::compute_hash(body);
+    let records = list_quarantine(temp_dir.path()).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].package_id, "Test.Package");
+    assert_eq!(records[0].expected, expected);
+    assert_eq!(records[0].actual, actual);
+    assert_eq!(records[0].size, body.len() as u64);
+    assert_eq!(records[0].headers["etag"], "\"v1\"");
+    let quarantined = temp_dir
+        .path()
+        .join(QUARANTINE_DIR_NAME)
+        .join(&records[0].quarantined_file);
+    assert_eq!(std::fs::read(quarantined).unwrap(), body);
+
+    // A bad copy left on disk by an older version is quarantined too
+    std::fs::write(download_dir.join("payload.vsix"), b"stale payload...").unwrap();
+    let _ = downloader
+        .download_packages_with_report(&[package], &download_dir, "Test")
+        .await;
+    assert_eq!(list_quarantine(temp_dir.path()).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn download_packages_timeouts_return_partial_report() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadIndex, DownloadOptions, DownloadStatus, Package, PackagePayload};
+    use crate::MsvcKitError;
+    use std::time::Duration;
+
+    let fast = b"fast payload";
+    let mut server = mockito::Server::new_async().await;
+    let _fast = server
+        .mock("GET", "/fast.vsix")
+        .with_status(200)
+        .with_body(fast)
+        .create_async()
+        .await;
+
+    // A stuck connection: headers and a few bytes, then nothing
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let stuck_url = format!("http://{}", listener.local_addr().unwrap());
+    let stuck = tokio::spawn(async move {
+        use tokio::io::AsyncWriteExt;
+        let mut connections = Vec::new();
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 20\r\n\r\npartial")
+                .await;
+            connections.push(socket);
+        }
+    });
+
+    for per_file in [true, false] {
+        let payload = |name: &str, base: &str, size: usize| PackagePayload {
+            file_name: name.to_string(),
+            url: format!("{}/{}", base, name),
+            size: size as u64,
+            sha256: None,
+            mirrors: Vec::new(),
+        };
+        let package = Package {
+            id: "Test.Package".to_string(),
+            version: "1.0".to_string(),
+            package_type: "Vsix".to_string(),
+            chip: None,
+            payloads: vec![
+                payload("fast.vsix", &server.url(), fast.len()),
+                payload("slow.vsix", &stuck_url, 20),
+                payload("never.vsix", &stuck_url, 10),
+            ],
+            total_size: 0,
+            install_size: 0,
+            optional: false,
+        };
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let builder = DownloadOptions::builder()
+            .target_dir(temp_dir.path())
+            .parallel_downloads(1)
+            .progress_handler(test_progress_handler());
+        let options = if per_file {
+            builder.per_file_timeout(Duration::from_millis(300))
+        } else {
+            builder.overall_timeout(Duration::from_millis(300))
+        }
+        .build();
+        let downloader = CommonDownloader::with_client(options, create_http_client())
+            .with_progress_handler(test_progress_handler());
+
+        let err = downloader
+            .download_packages_with_report(std::slice::from_ref(&package), temp_dir.path(), "Test")
+            .await
+            .unwrap_err();
+        let MsvcKitError::DownloadTimeout {
+            file,
+            limit,
+            pending,
+            report,
+        } = err
+        else {
+            panic!("expected a timeout, got {:?}", err);
+        };
+        assert_eq!(limit, Duration::from_millis(300));
+        assert_eq!(file.is_some(), per_file);
+        assert_eq!(pending, ["slow.vsix", "never.vsix"]);
+        assert_eq!(report.downloaded, 1);
+
+        // Finished payloads stay completed, the cancelled one is not
+        let index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+            .await
+            .unwrap();
+        let fast_entry = index.get_entry("fast.vsix").await.unwrap().unwrap();
+        assert_eq!(fast_entry.status, DownloadStatus::Completed);
+        if let Some(slow_entry) = index.get_entry("slow.vsix").await.unwrap() {
+            assert_eq!(slow_entry.status, DownloadStatus::Partial);
+        }
+    }
+
+    stuck.abort();
+}
+
+#[tokio::test]
+async fn download_packages_resumes_with_recorded_progress() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::progress::ProgressHandler;
+    use super::{DownloadIndex, DownloadOptions, Package, PackagePayload, ResumeSummary};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct ResumeRecorder(Mutex<Vec<ResumeSummary>>);
+
+    impl ProgressHandler for ResumeRecorder {
+        fn on_start(&self, _component: &str, _total_files: usize, _total_bytes: u64) {}
+        fn on_file_start(&self, _file_name: &str, _file_size: u64) {}
+        fn on_progress(&self, _bytes: u64) {}
+        fn on_file_complete(&self, _file_name: &str, _outcome: &str) {}
+        fn on_complete(&self, _downloaded: usize, _skipped: usize) {}
+        fn on_error(&self, _error: &str) {}
+        fn on_resume(&self, summary: &ResumeSummary) {
+            self.0.lock().unwrap().push(summary.clone());
+        }
+    }
+
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/second.vsix")
+        .with_status(200)
+        .with_body(b"second payload")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    // The first payload was downloaded by an interrupted run
+    std::fs::write(temp_dir.path().join("first.vsix"), b"first payload!").unwrap();
+    let payload = |name: &str, body: &[u8]| PackagePayload {
+        file_name: name.to_string(),
+        url: format!("{}/{}", server.url(), name),
+        size: body.len() as u64,
+        sha256: Some(super::hash::compute_hash(body)),
+        mirrors: Vec::new(),
+    };
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![
+            payload("first.vsix", b"first payload!"),
+            payload("second.vsix", b"second payload"),
+        ],
+        total_size: 28,
+        install_size: 0,
+        optional: false,
+    };
+    {
+        let mut index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+            .await
+            .unwrap();
+        index
+            .record_run_progress(&super::RunProgress {
+                component: "Test".to_string(),
+                total_files: 2,
+                total_bytes: 28,
+                completed_files: 1,
+                completed_bytes: 14,
+                bytes_per_sec: 7,
+                updated_at: chrono::Utc::now(),
+            })
+            .await
+            .unwrap();
+    }
+
+    let recorder = Arc::new(ResumeRecorder::default());
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(recorder.clone());
+    downloader
+        .download_packages(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+    mock.assert_async().await;
+
+    let summaries = recorder.0.lock().unwrap().clone();
+    assert_eq!(summaries.len(), 1);
+    assert_eq!(summaries[0].completed_bytes, 14);
+    assert_eq!(summaries[0].percent(), 50.0);
+    assert_eq!(summaries[0].eta(), Some(Duration::from_secs(2)));
+
+    let index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+        .await
+        .unwrap();
+    let run = index.run_progress("Test").await.unwrap().unwrap();
+    assert!(run.is_finished());
+    assert_eq!(run.completed_bytes, 28);
+}
+
+#[tokio::test]
+async fn download_packages_continues_on_optional_failure() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{DownloadOptions, Package, PackagePayload};
+    use crate::WarningCode;
+
+    let mut server = mockito::Server::new_async().await;
+    let _tools = server
+        .mock("GET", "/tools.vsix")
+        .with_status(200)
+        .with_body(b"tools")
+        .create_async()
+        .await;
+    let _satellite = server
+        .mock("GET", "/satellite.vsix")
+        .with_status(200)
+        .with_body(b"corrupted")
+        .create_async()
+        .await;
+
+    let package = |id: &str, file_name: &str, body: &[u8], optional: bool| Package {
+        id: id.to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: file_name.to_string(),
+            url: format!("{}/{}", server.url(), file_name),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional,
+    };
+    let packages = vec![
+        package("Test.Tools", "tools.vsix", b"tools", false),
+        package("Test.Tools.Res", "satellite.vsix", b"expected", true),
+    ];
+
+    // Without the option an optional failure still fails the run
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    assert!(downloader
+        .download_packages_with_report(&packages, temp_dir.path(), "Test")
+        .await
+        .is_err());
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .continue_on_optional_failure(true)
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    let (files, report) = downloader
+        .download_packages_with_report(&packages, temp_dir.path(), "Test")
+        .await
+        .unwrap();
+    assert_eq!(files, vec![temp_dir.path().join("tools.vsix")]);
+    assert_eq!(report.failed_optional_packages(), vec!["Test.Tools.Res"]);
+    assert_eq!(report.failed_optional[0].file_name, "satellite.vsix");
+
+    let warnings = report.warnings();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].code, WarningCode::OptionalPackageFailed);
+    assert_eq!(warnings[0].context.as_deref(), Some("Test.Tools.Res"));
+
+    // An essential package failing is still fatal
+    let mut essential = packages.clone();
+    essential[1].optional = false;
+    assert!(downloader
+        .download_packages_with_report(&essential, temp_dir.path(), "Test")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn download_packages_in_ranges() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{ChunkedDownloads, DownloadOptions, Package, PackagePayload};
+
+    let body = b"payload body";
+    let mut server = mockito::Server::new_async().await;
+    let mut ranges = Vec::new();
+    for (start, end) in [(0, 3), (4, 7), (8, 11)] {
+        let mock = server
+            .mock("GET", "/ranged.msi")
+            .match_header("range", format!("bytes={}-{}", start, end).as_str())
+            .with_status(206)
+            .with_header(
+                "Content-Range",
+                &format!("bytes {}-{}/{}", start, end, body.len()),
+            )
+            .with_body(&body[start..=end])
+            .expect(1)
+            .create_async()
+            .await;
+        ranges.push(mock);
+    }
+    // Ranges are ignored here: three range requests, then one plain download
+    let whole = server
+        .mock("GET", "/whole.msi")
+        .with_status(200)
+        .with_body(body)
+        .expect(4)
+        .create_async()
+        .await;
+
+    let package = |file_name: &str| Package {
+        id: format!("Test.{}", file_name),
+        version: "1.0".to_string(),
+        package_type: "Msi".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: file_name.to_string(),
+            url: format!("{}/{}", server.url(), file_name),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .chunked_downloads(ChunkedDownloads {
+            connections: 3,
+            min_size: 1,
+        })
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    let (files, report) = downloader
+        .download_packages_with_report(
+            &[package("ranged.msi"), package("whole.msi")],
+            temp_dir.path(),
+            "Test",
+        )
+        .await
+        .unwrap();
+
+    for mock in &ranges {
+        mock.assert_async().await;
+    }
+    whole.assert_async().await;
+    assert_eq!(report.downloaded, 2);
+    assert_eq!(files.len(), 2);
+    for file in &files {
+        assert_eq!(std::fs::read(file).unwrap(), body);
+    }
+    assert!(!temp_dir.path().join("ranged.msi.part0").exists());
+    assert!(!temp_dir.path().join("whole.msi.part0").exists());
+}
+
+#[tokio::test]
+async fn download_packages_reports_discarded_ranges_once() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::progress::RecordingProgressHandler;
+    use super::{ChunkedDownloads, DownloadEvent, DownloadOptions, Package, PackagePayload};
+
+    let body = b"payload body";
+    let mut server = mockito::Server::new_async().await;
+    // The first range is served, the second is not: the ranges are discarded
+    let first = server
+        .mock("GET", "/mixed.msi")
+        .match_header("range", "bytes=0-5")
+        .with_status(206)
+        .with_header("Content-Range", &format!("bytes 0-5/{}", body.len()))
+        .with_body(&body[..6])
+        .expect(1)
+        .create_async()
+        .await;
+    let second = server
+        .mock("GET", "/mixed.msi")
+        .match_header("range", "bytes=6-11")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+    let whole = server
+        .mock("GET", "/mixed.msi")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let package = Package {
+        id: "Test.Mixed".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Msi".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "mixed.msi".to_string(),
+            url: format!("{}/mixed.msi", server.url()),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let recorder = Arc::new(RecordingProgressHandler::new());
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .chunked_downloads(ChunkedDownloads {
+            connections: 2,
+            min_size: 1,
+        })
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(recorder.clone());
+    let (files, _) = downloader
+        .download_packages_with_report(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+
+    first.assert_async().await;
+    second.assert_async().await;
+    whole.assert_async().await;
+    assert_eq!(std::fs::read(&files[0]).unwrap(), body);
+    let reported: u64 = recorder
+        .take_events()
+        .into_iter()
+        .filter_map(|recorded| match recorded.event {
+            DownloadEvent::Progress { bytes } => Some(bytes),
+            _ => None,
+        })
+        .sum();
+    assert_eq!(reported, body.len() as u64);
+}
+
+#[tokio::test]
+async fn download_packages_reports_in_call_order() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::progress::RecordingProgressHandler;
+    use super::{DownloadEvent, DownloadOptions, Package, PackagePayload};
+
+    let body = b"ordered payload";
+    let mut server = mockito::Server::new_async().await;
+    let mock = server
+        .mock("GET", "/ordered.cab")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+    let package = Package {
+        id: "Test.Ordered".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "ordered.cab".to_string(),
+            url: format!("{}/ordered.cab", server.url()),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let recorder = Arc::new(RecordingProgressHandler::new());
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(recorder.clone());
+    // Downloaded, then found on disk by a second run with the same handler
+    for _ in 0..2 {
+        downloader
+            .download_packages_with_report(std::slice::from_ref(&package), temp_dir.path(), "Test")
+            .await
+            .unwrap();
+    }
+    mock.assert_async().await;
+
+    assert_eq!(recorder.check_order(), Ok(()));
+    let events: Vec<DownloadEvent> = recorder
+        .take_events()
+        .into_iter()
+        .map(|recorded| recorded.event)
+        .collect();
+    let count = |pred: fn(&DownloadEvent) -> bool| events.iter().filter(|e| pred(e)).count();
+    assert_eq!(count(|e| matches!(e, DownloadEvent::Started { .. })), 2);
+    assert_eq!(count(|e| matches!(e, DownloadEvent::FileStarted { .. })), 1);
+    assert_eq!(count(|e| matches!(e, DownloadEvent::Error(_))), 0);
+    assert!(matches!(
+        events.last(),
+        Some(DownloadEvent::Completed {
+            downloaded: 0,
+            skipped: 1
+        })
+    ));
+}
+
+#[tokio::test]
+async fn download_packages_falls_back_to_mirror_on_hash_mismatch() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{list_quarantine, DownloadIndex, DownloadOptions, Package, PackagePayload};
+
+    let body = b"original payload";
+    let mut server = mockito::Server::new_async().await;
+    // One CDN edge keeps serving a corrupted copy
+    let edge = server
+        .mock("GET", "/edge/payload.vsix")
+        .with_status(200)
+        .with_body(b"corrupt payload!")
+        .expect(2)
+        .create_async()
+        .await;
+    let unreachable = server
+        .mock("GET", "/down/payload.vsix")
+        .with_status(404)
+        .expect(1)
+        .create_async()
+        .await;
+    let mirror = server
+        .mock("GET", "/mirror/payload.vsix")
+        .with_status(200)
+        .with_body(body)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let package = Package {
+        id: "Test.Package".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Vsix".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "payload.vsix".to_string(),
+            url: format!("{}/edge/payload.vsix", server.url()),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: vec![
+                format!("{}/down/payload.vsix", server.url()),
+                format!("{}/mirror/payload.vsix", server.url()),
+            ],
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let download_dir = temp_dir.path().join("downloads");
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    let (files, report) = downloader
+        .download_packages_with_report(&[package], &download_dir, "Test")
+        .await
+        .unwrap();
+
+    edge.assert_async().await;
+    unreachable.assert_async().await;
+    mirror.assert_async().await;
+    assert_eq!(report.downloaded, 1);
+    assert_eq!(std::fs::read(&files[0]).unwrap(), body);
+    assert_eq!(list_quarantine(temp_dir.path()).unwrap().len(), 1);
+
+    let index = DownloadIndex::load(&download_dir.join("index.db"))
+        .await
+        .unwrap();
+    let provenance = index.provenance("payload.vsix").await.unwrap().unwrap();
+    assert_eq!(
+        provenance.url,
+        format!("{}/edge/payload.vsix", server.url())
+    );
+    assert_eq!(
+        provenance.fallback_url,
+        Some(format!("{}/mirror/payload.vsix", server.url()))
+    );
+}
+
+#[tokio::test]
+async fn download_ranges_honor_retry_after() {
+    use super::common::CommonDownloader;
+    use super::http::create_http_client;
+    use super::{ChunkedDownloads, DownloadOptions, Package, PackagePayload};
+
+    let body = b"payload body";
+    let mut server = mockito::Server::new_async().await;
+    let throttled = server
+        .mock("GET", "/ranged.msi")
+        .match_header("range", "bytes=0-5")
+        .with_status(503)
+        .with_header("Retry-After", "0")
+        .expect(1)
+        .create_async()
+        .await;
+    let mut ranges = Vec::new();
+    for (start, end) in [(0, 5), (6, 11)] {
+        let mock = server
+            .mock("GET", "/ranged.msi")
+            .match_header("range", format!("bytes={}-{}", start, end).as_str())
+            .with_status(206)
+            .with_header(
+                "Content-Range",
+                &format!("bytes {}-{}/{}", start, end, body.len()),
+            )
+            .with_body(&body[start..=end])
+            .expect(1)
+            .create_async()
+            .await;
+        ranges.push(mock);
+    }
+
+    let package = Package {
+        id: "Test.Ranged".to_string(),
+        version: "1.0".to_string(),
+        package_type: "Msi".to_string(),
+        chip: None,
+        payloads: vec![PackagePayload {
+            file_name: "ranged.msi".to_string(),
+            url: format!("{}/ranged.msi", server.url()),
+            size: body.len() as u64,
+            sha256: Some(super::hash::compute_hash(body)),
+            mirrors: Vec::new(),
+        }],
+        total_size: body.len() as u64,
+        install_size: 0,
+        optional: false,
+    };
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions::builder()
+        .target_dir(temp_dir.path())
+        .chunked_downloads(ChunkedDownloads {
+            connections: 2,
+            min_size: 1,
+        })
+        .build();
+    let downloader = CommonDownloader::with_client(options, create_http_client())
+        .with_progress_handler(test_progress_handler());
+    let (files, report) = downloader
+        .download_packages_with_report(&[package], temp_dir.path(), "Test")
+        .await
+        .unwrap();
+
+    throttled.assert_async().await;
+    for mock in &ranges {
+        mock.assert_async().await;
+    }
+    assert_eq!(std::fs::read(&files[0]).unwrap(), body);
+    assert_eq!(report.throttle_events.len(), 1);
+    assert_eq!(report.throttle_events[0].status, 503);
+    assert_eq!(
+        report.throttle_events[0].retry_after,
+        Some(std::time::Duration::ZERO)
+    );
+}
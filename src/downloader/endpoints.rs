@@ -0,0 +1,268 @@
+//! Alternate download endpoints with failover
+//!
+//! The primary payload host (`download.visualstudio.microsoft.com`)
+//! occasionally serves errors while other CDN endpoints or an internal mirror
+//! still work. With [`DownloadOptions::alternate_endpoints`] set, every payload
+//! URL has one candidate per endpoint: the manifest URL first, then the same
+//! path on each alternate base URL. A payload that fails on one endpoint is
+//! retried on the next.
+//!
+//! Endpoints are tracked per download run: after
+//! [`UNHEALTHY_AFTER_FAILURES`] consecutive failures an endpoint is tried last
+//! until [`UNHEALTHY_COOLDOWN`] has passed, so the remaining payloads do not
+//! wait on its retries. The counters end up in
+//! [`DownloadReport::endpoints`](super::DownloadReport::endpoints).
+//!
+//! [`DownloadOptions::alternate_endpoints`]: super::DownloadOptions::alternate_endpoints
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+/// Consecutive failures after which an endpoint is tried last
+pub const UNHEALTHY_AFTER_FAILURES: usize = 3;
+
+/// How long an unhealthy endpoint is tried last before it gets another chance
+pub const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Request counters of one download endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointStats {
+    /// Base URL, e.g. `https://download.visualstudio.microsoft.com/`
+    pub base: String,
+    /// Payloads downloaded from the endpoint
+    pub successes: usize,
+    /// Payloads that failed on the endpoint (after its retries)
+    pub failures: usize,
+    /// Whether the endpoint was healthy at the end of the run
+    pub healthy: bool,
+}
+
+#[derive(Debug, Default)]
+struct Health {
+    successes: usize,
+    failures: usize,
+    consecutive_failures: usize,
+    unhealthy_since: Option<Instant>,
+}
+
+impl Health {
+    fn is_healthy(&self) -> bool {
+        self.unhealthy_since
+            .is_none_or(|since| since.elapsed() >= UNHEALTHY_COOLDOWN)
+    }
+}
+
+/// Candidate endpoints of a download run, with their health
+#[derive(Debug, Default)]
+pub(crate) struct EndpointPool {
+    alternates: Vec<String>,
+    health: Mutex<BTreeMap<String, Health>>,
+}
+
+impl EndpointPool {
+    /// Create a pool from alternate base URLs (the primary comes from each payload URL)
+    pub(crate) fn new(alternates: &[String]) -> Self {
+        Self {
+            alternates: alternates
+                .iter()
+                .map(|base| normalize_base(base))
+                .filter(|base| !base.is_empty())
+                .collect(),
+            health: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// `(base, url)` candidates for a payload URL, healthy endpoints first
+    ///
+    /// Without alternates, or for a URL without a recognizable origin, the
+    /// only candidate is the URL itself.
+    pub(crate) fn candidates(&self, url: &str) -> Vec<(String, String)> {
+        let Some((base, path)) = self.split(url) else {
+            return vec![(url.to_string(), url.to_string())];
+        };
+        if self.alternates.is_empty() {
+            return vec![(base, url.to_string())];
+        }
+
+        let mut bases = vec![base];
+        for alternate in &self.alternates {
+            if !bases.contains(alternate) {
+                bases.push(alternate.clone());
+            }
+        }
+
+        // Stable sort: the manifest / configured order is kept within each group
+        let health = self.health.lock().unwrap();
+        bases.sort_by_key(|b| !health.get(b).is_none_or(Health::is_healthy));
+        bases
+            .into_iter()
+            .map(|b| {
+                let url = format!("{}{}", b, path);
+                (b, url)
+            })
+            .collect()
+    }
+
+    /// Record a payload downloaded from `base`
+    pub(crate) fn record_success(&self, base: &str) {
+        let mut health = self.health.lock().unwrap();
+        let entry = health.entry(base.to_string()).or_default();
+        entry.successes += 1;
+        entry.consecutive_failures = 0;
+        entry.unhealthy_since = None;
+    }
+
+    /// Record a payload that failed on `base`
+    pub(crate) fn record_failure(&self, base: &str) {
+        let mut health = self.health.lock().unwrap();
+        let entry = health.entry(base.to_string()).or_default();
+        entry.failures += 1;
+        entry.consecutive_failures += 1;
+        if entry.consecutive_failures >= UNHEALTHY_AFTER_FAILURES {
+            if entry.is_healthy() {
+                tracing::warn!(
+                    "Endpoint {} failed {} times in a row, trying it last",
+                    base,
+                    entry.consecutive_failures
+                );
+            }
+            entry.unhealthy_since = Some(Instant::now());
+        }
+    }
+
+    /// Counters of every endpoint used so far
+    pub(crate) fn stats(&self) -> Vec<EndpointStats> {
+        self.health
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(base, h)| EndpointStats {
+                base: base.clone(),
+                successes: h.successes,
+                failures: h.failures,
+                healthy: h.is_healthy(),
+            })
+            .collect()
+    }
+
+    /// Split a URL into its endpoint base and the path relative to it
+    ///
+    /// A URL already pointing at an alternate is split at that alternate, so
+    /// mirrors with a path prefix map onto each other.
+    fn split(&self, url: &str) -> Option<(String, String)> {
+        let lower = url.to_lowercase();
+        for alternate in &self.alternates {
+            if lower.starts_with(&alternate.to_lowercase()) {
+                return Some((alternate.clone(), url[alternate.len()..].to_string()));
+            }
+        }
+
+        let scheme_end = url.find("://")? + 3;
+        let host_end = url[scheme_end..].find('/')? + scheme_end;
+        if host_end == scheme_end {
+            return None;
+        }
+        Some((
+            normalize_base(&url[..host_end]),
+            url[host_end + 1..].to_string(),
+        ))
+    }
+}
+
+/// Trim a base URL and make it end with exactly one `/`
+fn normalize_base(base: &str) -> String {
+    let base = base.trim().trim_end_matches('/');
+    if base.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", base)
+    }
+}
+
+/// Whether `base` can be used as an alternate endpoint (an http(s) URL with a host)
+pub(crate) fn is_valid_endpoint(base: &str) -> bool {
+    let base = base.trim().to_lowercase();
+    ["https://", "http://"].iter().any(|scheme| {
+        base.strip_prefix(scheme)
+            .is_some_and(|rest| !rest.is_empty() && !rest.starts_with('/'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &str =
+        "https://download.visualstudio.microsoft.com/download/pr/abc/payload.vsix";
+
+    #[test]
+    fn test_candidates_without_alternates() {
+        let pool = EndpointPool::new(&[]);
+        assert_eq!(
+            pool.candidates(PAYLOAD),
+            vec![(
+                "https://download.visualstudio.microsoft.com/".to_string(),
+                PAYLOAD.to_string()
+            )]
+        );
+        assert_eq!(pool.candidates("not a url").len(), 1);
+    }
+
+    #[test]
+    fn test_candidates_with_alternates() {
+        let pool = EndpointPool::new(&[
+            "https://mirror.corp/vs".to_string(),
+            " https://download.visualstudio.microsoft.com/ ".to_string(),
+        ]);
+        let urls: Vec<String> = pool.candidates(PAYLOAD).into_iter().map(|c| c.1).collect();
+        assert_eq!(
+            urls,
+            vec![
+                PAYLOAD.to_string(),
+                "https://mirror.corp/vs/download/pr/abc/payload.vsix".to_string(),
+            ]
+        );
+
+        // A mirror URL maps back onto the other endpoints
+        let urls: Vec<String> = pool
+            .candidates("https://mirror.corp/vs/download/pr/abc/payload.vsix")
+            .into_iter()
+            .map(|c| c.1)
+            .collect();
+        assert_eq!(urls[1], PAYLOAD);
+    }
+
+    #[test]
+    fn test_unhealthy_endpoint_tried_last() {
+        let pool = EndpointPool::new(&["https://mirror.corp/".to_string()]);
+        let primary = "https://download.visualstudio.microsoft.com/";
+        for _ in 0..UNHEALTHY_AFTER_FAILURES {
+            pool.record_failure(primary);
+        }
+        pool.record_success("https://mirror.corp/");
+
+        let candidates = pool.candidates(PAYLOAD);
+        assert_eq!(candidates[0].0, "https://mirror.corp/");
+        assert_eq!(candidates[1].0, primary);
+
+        let stats = pool.stats();
+        let primary_stats = stats.iter().find(|s| s.base == primary).unwrap();
+        assert_eq!(primary_stats.failures, UNHEALTHY_AFTER_FAILURES);
+        assert!(!primary_stats.healthy);
+
+        pool.record_success(primary);
+        assert_eq!(pool.candidates(PAYLOAD)[0].0, primary);
+    }
+
+    #[test]
+    fn test_is_valid_endpoint() {
+        assert!(is_valid_endpoint("https://mirror.corp/vs/"));
+        assert!(is_valid_endpoint("HTTP://10.0.0.1:8080"));
+        assert!(!is_valid_endpoint("mirror.corp"));
+        assert!(!is_valid_endpoint("https://"));
+        assert!(!is_valid_endpoint("ftp://mirror.corp/"));
+    }
+}
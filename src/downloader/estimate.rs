@@ -0,0 +1,239 @@
+//! Installed size estimation for download previews
+//!
+//! Payload sizes only tell how much is downloaded. [`SizeEstimator`] turns
+//! them into bytes on disk, per package from the best source available:
+//! files already extracted into the install directory (from the extraction
+//! receipts), the manifest install size, or the download size scaled by a
+//! typical extraction ratio of the package type. Ratios observed on packages
+//! already installed replace the defaults for their type.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::Package;
+use crate::installer::PackageReceipt;
+
+/// Typical extracted / download size ratios by package type (lowercase)
+pub const DEFAULT_EXTRACTION_RATIOS: &[(&str, f64)] =
+    &[("vsix", 2.6), ("msi", 1.8), ("zip", 2.4), ("exe", 1.0)];
+
+/// Ratio for package types without a default
+pub const FALLBACK_EXTRACTION_RATIO: f64 = 2.0;
+
+/// Where an installed size estimate comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SizeSource {
+    /// Files already extracted into the install directory
+    Installed,
+    /// Install size declared by the manifest
+    Manifest,
+    /// Download size times the extraction ratio of the package type
+    Ratio,
+}
+
+/// Estimated bytes on disk of one package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SizeEstimate {
+    /// Estimated bytes on disk
+    pub bytes: u64,
+    /// How the estimate was obtained
+    pub source: SizeSource,
+}
+
+/// Estimates installed sizes of packages before they are downloaded
+///
+/// # Example
+///
+/// ```rust
+/// use msvc_kit::downloader::SizeEstimator;
+///
+/// let estimator = SizeEstimator::new().with_ratio("Vsix", 3.0);
+/// assert_eq!(estimator.ratio("vsix"), 3.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SizeEstimator {
+    /// Extraction ratios by lowercase package type
+    ratios: HashMap<String, f64>,
+    /// Extracted bytes of payloads found in the install directory, by file name
+    installed: HashMap<String, u64>,
+}
+
+impl Default for SizeEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SizeEstimator {
+    /// Estimator using [`DEFAULT_EXTRACTION_RATIOS`]
+    pub fn new() -> Self {
+        Self {
+            ratios: DEFAULT_EXTRACTION_RATIOS
+                .iter()
+                .map(|(kind, ratio)| (kind.to_string(), *ratio))
+                .collect(),
+            installed: HashMap::new(),
+        }
+    }
+
+    /// Override the extraction ratio of a package type
+    pub fn with_ratio(mut self, package_type: &str, ratio: f64) -> Self {
+        self.ratios.insert(package_type.to_lowercase(), ratio);
+        self
+    }
+
+    /// Extraction ratio used for a package type
+    pub fn ratio(&self, package_type: &str) -> f64 {
+        self.ratios
+            .get(&package_type.to_lowercase())
+            .copied()
+            .unwrap_or(FALLBACK_EXTRACTION_RATIO)
+    }
+
+    /// Refine the estimator with the payloads of `packages` already extracted
+    /// into `install_dir`
+    ///
+    /// Those packages are estimated with their actual size on disk, and the
+    /// ratios they show replace the defaults of their package types. Receipts
+    /// without a file list (older versions, MSI extraction) are ignored.
+    pub async fn refine_from_install(mut self, install_dir: &Path, packages: &[Package]) -> Self {
+        // (download bytes, extracted bytes) of fully installed packages, by type
+        let mut observed: HashMap<String, (u64, u64)> = HashMap::new();
+
+        for package in packages {
+            let mut extracted = Some(0u64);
+            for payload in &package.payloads {
+                let path = PackageReceipt::path_for(install_dir, &payload.file_name);
+                match PackageReceipt::read(&path).await {
+                    Some(receipt) if !receipt.files.is_empty() => {
+                        let size = receipt.total_size();
+                        self.installed.insert(payload.file_name.clone(), size);
+                        extracted = extracted.map(|total| total + size);
+                    }
+                    _ => extracted = None,
+                }
+            }
+            if let (Some(extracted), true) = (extracted, package.total_size > 0) {
+                let entry = observed
+                    .entry(package.package_type.to_lowercase())
+                    .or_default();
+                entry.0 += package.total_size;
+                entry.1 += extracted;
+            }
+        }
+
+        for (kind, (downloaded, extracted)) in observed {
+            if downloaded > 0 && extracted > 0 {
+                self.ratios
+                    .insert(kind, extracted as f64 / downloaded as f64);
+            }
+        }
+        self
+    }
+
+    /// Estimate the bytes on disk of a package
+    pub fn estimate(&self, package: &Package) -> SizeEstimate {
+        let installed: Option<u64> = package
+            .payloads
+            .iter()
+            .map(|p| self.installed.get(&p.file_name).copied())
+            .sum();
+        match installed {
+            Some(bytes) if !package.payloads.is_empty() => SizeEstimate {
+                bytes,
+                source: SizeSource::Installed,
+            },
+            _ if package.install_size > 0 => SizeEstimate {
+                bytes: package.install_size,
+                source: SizeSource::Manifest,
+            },
+            _ => SizeEstimate {
+                bytes: (package.total_size as f64 * self.ratio(&package.package_type)).round()
+                    as u64,
+                source: SizeSource::Ratio,
+            },
+        }
+    }
+
+    /// Estimated bytes on disk of all `packages`
+    pub fn estimate_total(&self, packages: &[Package]) -> u64 {
+        packages.iter().map(|p| self.estimate(p).bytes).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::PackagePayload;
+    use crate::installer::{ReceiptEntry, RECEIPT_DIR_NAME};
+
+    fn package(id: &str, package_type: &str, size: u64, install_size: u64) -> Package {
+        Package {
+            id: id.to_string(),
+            version: "1.0".to_string(),
+            package_type: package_type.to_string(),
+            chip: None,
+            payloads: vec![PackagePayload {
+                file_name: format!("{}.vsix", id),
+                url: format!("https://example.com/{}.vsix", id),
+                size,
+                sha256: None,
+                mirrors: Vec::new(),
+            }],
+            total_size: size,
+            install_size,
+            optional: false,
+        }
+    }
+
+    #[test]
+    fn test_estimate_sources() {
+        let estimator = SizeEstimator::new();
+
+        let estimate = estimator.estimate(&package("a", "Vsix", 1000, 4000));
+        assert_eq!(estimate.bytes, 4000);
+        assert_eq!(estimate.source, SizeSource::Manifest);
+
+        let estimate = estimator.estimate(&package("b", "Vsix", 1000, 0));
+        assert_eq!(estimate.bytes, 2600);
+        assert_eq!(estimate.source, SizeSource::Ratio);
+
+        let estimate = estimator.estimate(&package("c", "Nupkg", 1000, 0));
+        assert_eq!(estimate.bytes, 2000);
+    }
+
+    #[tokio::test]
+    async fn test_refine_from_install() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(RECEIPT_DIR_NAME)).unwrap();
+        let receipt = PackageReceipt {
+            package: "a.vsix".to_string(),
+            files: vec![ReceiptEntry {
+                path: "VC/include/a.h".to_string(),
+                sha256: "00".to_string(),
+                size: 4000,
+            }],
+        };
+        receipt
+            .write(&PackageReceipt::path_for(temp_dir.path(), "a.vsix"))
+            .await
+            .unwrap();
+
+        let packages = vec![package("a", "Vsix", 1000, 0), package("b", "Vsix", 500, 0)];
+        let estimator = SizeEstimator::new()
+            .refine_from_install(temp_dir.path(), &packages)
+            .await;
+
+        let installed = estimator.estimate(&packages[0]);
+        assert_eq!(installed.bytes, 4000);
+        assert_eq!(installed.source, SizeSource::Installed);
+
+        // The observed 4x ratio replaces the default for Vsix packages
+        assert_eq!(estimator.ratio("vsix"), 4.0);
+        assert_eq!(estimator.estimate(&packages[1]).bytes, 2000);
+        assert_eq!(estimator.estimate_total(&packages), 6000);
+    }
+}
@@ -0,0 +1,255 @@
+//! Stream of download events
+//!
+//! An alternative to implementing [`ProgressHandler`]: the download runs in a
+//! background task and its progress is delivered as typed [`DownloadEvent`]s,
+//! so async consumers can poll it from their own `select!` or UI loop.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use futures::Stream;
+use tokio::task::JoinHandle;
+
+use super::progress::ProgressHandler;
+use super::{
+    DownloadOptions, MsvcDownloader, PackagePreview, ResolutionReport, ResumeSummary, SdkDownloader,
+};
+use crate::error::Result;
+use crate::installer::InstallInfo;
+
+/// Event emitted while downloading a component
+#[derive(Debug)]
+pub enum DownloadEvent {
+    /// Version selected for download
+    VersionResolved(ResolutionReport),
+    /// Packages selected for download
+    PackagesResolved {
+        component: String,
+        packages: Vec<PackagePreview>,
+    },
+    /// Payload download started
+    Started {
+        component: String,
+        total_files: usize,
+        total_bytes: u64,
+    },
+    /// Payloads of an interrupted run were found on disk
+    Resumed(ResumeSummary),
+    /// A file transfer started
+    FileStarted { file_name: String, size: u64 },
+    /// Bytes transferred (incremental)
+    Progress { bytes: u64 },
+    /// A file is done ("downloaded", "cached", "size match", ...)
+    FileCompleted { file_name: String, outcome: String },
+    /// The server throttled a request
+    Throttled {
+        file_name: String,
+        status: u16,
+        wait: Duration,
+    },
+    /// Summary message
+    Message(String),
+    /// A file failed
+    Error(String),
+    /// All payloads were processed
+    Completed { downloaded: usize, skipped: usize },
+    /// The download finished; always the last event
+    Finished(Result<InstallInfo>),
+}
+
+/// Stream of [`DownloadEvent`]s returned by [`download_msvc_stream`] and
+/// [`download_sdk_stream`]
+///
+/// Dropping the stream cancels the download.
+pub struct DownloadEventStream {
+    receiver: UnboundedReceiver<DownloadEvent>,
+    task: JoinHandle<()>,
+}
+
+impl Stream for DownloadEventStream {
+    type Item = DownloadEvent;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl Drop for DownloadEventStream {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Progress handler forwarding callbacks to a channel
+struct ChannelProgressHandler {
+    sender: UnboundedSender<DownloadEvent>,
+}
+
+impl ChannelProgressHandler {
+    fn send(&self, event: DownloadEvent) {
+        // The receiver is gone only when the stream was dropped
+        let _ = self.sender.unbounded_send(event);
+    }
+}
+
+impl ProgressHandler for ChannelProgressHandler {
+    fn on_version_resolved(&self, report: &ResolutionReport) {
+        self.send(DownloadEvent::VersionResolved(report.clone()));
+    }
+
+    fn on_packages_resolved(&self, component: &str, packages: &[PackagePreview]) {
+        self.send(DownloadEvent::PackagesResolved {
+            component: component.to_string(),
+            packages: packages.to_vec(),
+        });
+    }
+
+    fn on_start(&self, component: &str, total_files: usize, total_bytes: u64) {
+        self.send(DownloadEvent::Started {
+            component: component.to_string(),
+            total_files,
+            total_bytes,
+        });
+    }
+
+    fn on_resume(&self, summary: &ResumeSummary) {
+        self.send(DownloadEvent::Resumed(summary.clone()));
+    }
+
+    fn on_file_start(&self, file_name: &str, file_size: u64) {
+        self.send(DownloadEvent::FileStarted {
+            file_name: file_name.to_string(),
+            size: file_size,
+        });
+    }
+
+    fn on_progress(&self, bytes: u64) {
+        self.send(DownloadEvent::Progress { bytes });
+    }
+
+    fn on_file_complete(&self, file_name: &str, outcome: &str) {
+        self.send(DownloadEvent::FileCompleted {
+            file_name: file_name.to_string(),
+            outcome: outcome.to_string(),
+        });
+    }
+
+    fn on_complete(&self, downloaded: usize, skipped: usize) {
+        self.send(DownloadEvent::Completed {
+            downloaded,
+            skipped,
+        });
+    }
+
+    fn on_error(&self, error: &str) {
+        self.send(DownloadEvent::Error(error.to_string()));
+    }
+
+    fn on_message(&self, message: &str) {
+        self.send(DownloadEvent::Message(message.to_string()));
+    }
+
+    fn on_throttled(&self, file_name: &str, status: u16, wait: Duration) {
+        self.send(DownloadEvent::Throttled {
+            file_name: file_name.to_string(),
+            status,
+            wait,
+        });
+    }
+}
+
+/// Run `download` in a background task with its progress sent to a stream
+fn spawn_download<F, Fut>(options: &DownloadOptions, download: F) -> DownloadEventStream
+where
+    F: FnOnce(DownloadOptions) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<InstallInfo>> + Send,
+{
+    let (sender, receiver) = unbounded();
+    let mut options = options.clone();
+    options.progress_handler = Some(Arc::new(ChannelProgressHandler {
+        sender: sender.clone(),
+    }));
+    let task = tokio::spawn(async move {
+        let result = download(options).await;
+        let _ = sender.unbounded_send(DownloadEvent::Finished(result));
+    });
+    DownloadEventStream { receiver, task }
+}
+
+/// Download MSVC components, reporting progress as a stream of events
+///
+/// The download starts immediately in a background task, so this must be called
+/// within a Tokio runtime; `options.progress_handler` is replaced by the stream. The last event is [`DownloadEvent::Finished`]
+/// with the result [`download_msvc`](super::download_msvc) would return.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use futures::StreamExt;
+/// use msvc_kit::{download_msvc_stream, DownloadEvent, DownloadOptions};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let mut events = download_msvc_stream(&DownloadOptions::default());
+///     while let Some(event) = events.next().await {
+///         match event {
+///             DownloadEvent::FileCompleted { file_name, outcome } => {
+///                 println!("{}: {}", file_name, outcome)
+///             }
+///             DownloadEvent::Finished(result) => {
+///                 println!("Installed to: {:?}", result?.install_path)
+///             }
+///             _ => {}
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn download_msvc_stream(options: &DownloadOptions) -> DownloadEventStream {
+    spawn_download(options, |options| async move {
+        MsvcDownloader::new(options).download().await
+    })
+}
+
+/// Download Windows SDK components, reporting progress as a stream of events
+///
+/// See [`download_msvc_stream`].
+pub fn download_sdk_stream(options: &DownloadOptions) -> DownloadEventStream {
+    spawn_download(options, |options| async move {
+        SdkDownloader::new(options).download().await
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn test_events_end_with_finished() {
+        let events = spawn_download(&DownloadOptions::default(), |options| async move {
+            let handler = options.progress_handler.unwrap();
+            handler.on_start("MSVC", 1, 10);
+            handler.on_progress(10);
+            handler.on_file_complete("a.vsix", "downloaded");
+            Err(crate::error::MsvcKitError::Cancelled)
+        });
+        let events: Vec<DownloadEvent> = events.collect().await;
+
+        assert_eq!(events.len(), 4);
+        assert!(matches!(
+            events[0],
+            DownloadEvent::Started { total_files: 1, .. }
+        ));
+        assert!(matches!(events[1], DownloadEvent::Progress { bytes: 10 }));
+        assert!(matches!(
+            &events[2],
+            DownloadEvent::FileCompleted { outcome, .. } if outcome == "downloaded"
+        ));
+        assert!(matches!(events[3], DownloadEvent::Finished(Err(_))));
+    }
+}
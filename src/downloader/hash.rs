@@ -4,12 +4,102 @@
 
 use std::path::Path;
 
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use tokio::{fs::File, io::AsyncReadExt};
 
 use crate::constants::hash as hash_const;
 use crate::error::Result;
 
+/// How payload SHA256 hashes are checked against the manifest
+///
+/// Serialized as `"enforce"`, `"warn"` or `"off"`. Booleans are accepted when
+/// reading, so configs written with the former `verify_hashes = true/false`
+/// keep working.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum VerifyMode {
+    /// A mismatch fails the download
+    #[default]
+    Enforce,
+    /// A mismatch is logged and reported, and the payload is kept
+    Warn,
+    /// Hashes are not checked
+    Off,
+}
+
+impl VerifyMode {
+    /// Whether hashes are checked at all
+    pub fn is_enabled(self) -> bool {
+        self != VerifyMode::Off
+    }
+}
+
+impl std::fmt::Display for VerifyMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyMode::Enforce => write!(f, "enforce"),
+            VerifyMode::Warn => write!(f, "warn"),
+            VerifyMode::Off => write!(f, "off"),
+        }
+    }
+}
+
+impl std::str::FromStr for VerifyMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "enforce" | "true" | "1" | "yes" => Ok(VerifyMode::Enforce),
+            "warn" => Ok(VerifyMode::Warn),
+            "off" | "false" | "0" | "no" => Ok(VerifyMode::Off),
+            _ => Err(format!(
+                "Unknown verify mode: {} (expected enforce, warn or off)",
+                s
+            )),
+        }
+    }
+}
+
+impl From<bool> for VerifyMode {
+    fn from(verify: bool) -> Self {
+        if verify {
+            VerifyMode::Enforce
+        } else {
+            VerifyMode::Off
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VerifyMode {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Bool(bool),
+            Str(String),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Bool(verify) => Ok(verify.into()),
+            Repr::Str(s) => s.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// Whether `url` matches one of the hash exemption `patterns`
+///
+/// Patterns are case-insensitive substrings of the payload URL, typically the
+/// host of a proxy known to rewrite payloads (e.g. `"mirror.corp.example"`).
+pub fn is_hash_exempt(url: &str, patterns: &[String]) -> bool {
+    let url = url.to_lowercase();
+    patterns
+        .iter()
+        .map(|p| p.trim().to_lowercase())
+        .any(|p| !p.is_empty() && url.contains(&p))
+}
+
 /// Compute SHA256 hash of a file using streaming (memory-efficient)
 ///
 /// This function reads the file in chunks to avoid loading the entire file
@@ -104,4 +194,34 @@ mod tests {
         assert!(hashes_match("abc123", "ABC123"));
         assert!(!hashes_match("abc123", "abc124"));
     }
+
+    #[test]
+    fn test_verify_mode_parse() {
+        assert_eq!("warn".parse::<VerifyMode>().unwrap(), VerifyMode::Warn);
+        assert_eq!(
+            "ENFORCE".parse::<VerifyMode>().unwrap(),
+            VerifyMode::Enforce
+        );
+        assert_eq!("false".parse::<VerifyMode>().unwrap(), VerifyMode::Off);
+        assert!("strict".parse::<VerifyMode>().is_err());
+
+        // Legacy boolean values
+        let mode: VerifyMode = serde_json::from_str("false").unwrap();
+        assert_eq!(mode, VerifyMode::Off);
+        let mode: VerifyMode = serde_json::from_str("\"warn\"").unwrap();
+        assert_eq!(mode, VerifyMode::Warn);
+    }
+
+    #[test]
+    fn test_is_hash_exempt() {
+        let patterns = vec!["Mirror.Corp".to_string(), " ".to_string()];
+        assert!(is_hash_exempt(
+            "https://mirror.corp/vs/payload.vsix",
+            &patterns
+        ));
+        assert!(!is_hash_exempt(
+            "https://download.visualstudio.microsoft.com/payload.vsix",
+            &patterns
+        ));
+    }
 }
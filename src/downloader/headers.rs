@@ -0,0 +1,130 @@
+//! Package and payload selection for headers-only downloads
+//!
+//! MSVC ships its headers in dedicated `*.Headers` packages. The Windows SDK
+//! is a single package of MSI installers plus the CAB files they reference;
+//! only the header installers and their CABs are kept.
+
+use super::manifest::{Package, PackagePayload};
+
+/// Extract filter applied in headers-only mode unless filters are given
+pub(crate) const HEADERS_EXTRACT_FILTER: &str = "**/include/**";
+
+/// Whether an MSVC package only provides headers (e.g. `Microsoft.VC.14.44.CRT.Headers.base`)
+pub(crate) fn is_msvc_header_package(id: &str) -> bool {
+    id.to_lowercase().contains(".headers")
+}
+
+/// Whether an SDK payload is a header installer
+/// (e.g. `Installers\Windows SDK Desktop Headers x64-x86_en-us.msi`)
+pub(crate) fn is_sdk_header_installer(file_name: &str) -> bool {
+    let name = file_name.to_lowercase();
+    name.ends_with(".msi") && name.contains("headers")
+}
+
+/// Copy of `package` keeping only the payloads matching `keep`
+pub(crate) fn filter_payloads(
+    package: &Package,
+    keep: impl Fn(&PackagePayload) -> bool,
+) -> Package {
+    let payloads: Vec<PackagePayload> = package
+        .payloads
+        .iter()
+        .filter(|p| keep(p))
+        .cloned()
+        .collect();
+    let total_size: u64 = payloads.iter().map(|p| p.size).sum();
+    // The manifest only has the size of the whole package; scale it
+    let install_size = if package.total_size == 0 {
+        0
+    } else {
+        (package.install_size as u128 * total_size as u128 / package.total_size as u128) as u64
+    };
+    Package {
+        total_size,
+        install_size,
+        payloads,
+        ..package.clone()
+    }
+}
+
+/// CAB payloads of `package` referenced by any of the given MSI installers
+///
+/// MSI files list their cabinets by file name in the Media table, which is
+/// stored as plain text in the string pool, so a byte search is sufficient.
+pub(crate) fn referenced_cabs(package: &Package, installers: &[Vec<u8>]) -> Package {
+    filter_payloads(package, |payload| {
+        let name = cab_name(&payload.file_name);
+        name.to_lowercase().ends_with(".cab")
+            && installers
+                .iter()
+                .any(|msi| contains_bytes(msi, name.as_bytes()))
+    })
+}
+
+/// File name of a payload without its `Installers\` directory
+pub(crate) fn cab_name(file_name: &str) -> &str {
+    file_name.rsplit(['\\', '/']).next().unwrap_or(file_name)
+}
+
+fn contains_bytes(haystack: &[u8], needle: &[u8]) -> bool {
+    !needle.is_empty() && haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(file_name: &str) -> PackagePayload {
+        PackagePayload {
+            file_name: file_name.to_string(),
+            url: format!("https://example.com/{}", file_name),
+            size: 10,
+            sha256: None,
+            mirrors: Vec::new(),
+        }
+    }
+
+    fn sdk_package() -> Package {
+        let payloads = vec![
+            payload("Installers\\Windows SDK Desktop Headers x64-x86_en-us.msi"),
+            payload("Installers\\Windows SDK Desktop Libs x64-x86_en-us.msi"),
+            payload("Installers\\0a1b2c3d.cab"),
+            payload("Installers\\ffeeddcc.cab"),
+        ];
+        Package {
+            id: "Win11SDK_10.0.26100".to_string(),
+            version: "26100.1742".to_string(),
+            package_type: "Msi".to_string(),
+            chip: None,
+            total_size: payloads.iter().map(|p| p.size).sum(),
+            install_size: 400,
+            optional: false,
+            payloads,
+        }
+    }
+
+    #[test]
+    fn test_is_msvc_header_package() {
+        assert!(is_msvc_header_package(
+            "Microsoft.VC.14.44.CRT.Headers.base"
+        ));
+        assert!(is_msvc_header_package("Microsoft.VC.14.44.ATL.Headers"));
+        assert!(!is_msvc_header_package(
+            "Microsoft.VC.14.44.Tools.HostX64.TargetX64.base"
+        ));
+    }
+
+    #[test]
+    fn test_sdk_header_selection() {
+        let package = sdk_package();
+        let installers = filter_payloads(&package, |p| is_sdk_header_installer(&p.file_name));
+        assert_eq!(installers.payloads.len(), 1);
+        assert_eq!(installers.total_size, 10);
+        assert_eq!(installers.install_size, 100);
+
+        let msi = b"...Media\0ffeeddcc.cab\0...".to_vec();
+        let cabs = referenced_cabs(&package, &[msi]);
+        assert_eq!(cabs.payloads.len(), 1);
+        assert_eq!(cabs.payloads[0].file_name, "Installers\\ffeeddcc.cab");
+    }
+}
@@ -12,12 +12,24 @@
 //!   Requires `cmake` and `NASM` to be installed on Windows.
 //!
 //! See: <https://github.com/loonghao/msvc-kit/issues/44>
+//!
+//! ## Custom CA certificates
+//!
+//! Networks that intercept TLS need their root CA trusted in addition to
+//! the system roots. Certificates from the PEM file named by the
+//! `MSVC_KIT_TLS_CA_FILE` environment variable, or set with
+//! [`set_default_root_certificates`] (the CLI uses the `tls_ca_file`
+//! setting), are added to every client built from
+//! [`HttpClientConfig::default`].
 
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
 use std::time::Duration;
 
-use reqwest::Client;
+use reqwest::{Certificate, Client};
 
 use crate::constants::USER_AGENT;
+use crate::error::{MsvcKitError, Result};
 
 // Compile-time check: at least one TLS backend must be enabled.
 #[cfg(not(any(feature = "native-tls", feature = "rustls-tls")))]
@@ -47,8 +59,74 @@ pub fn tls_backend_name() -> &'static str {
     }
 }
 
-/// HTTP client configuration options
+/// User agent identifying an application built on msvc-kit
+///
+/// Produces `{product}/{version} msvc-kit/{crate_version}`, so servers and
+/// proxies see both the embedding tool and the library doing the requests.
+pub fn branded_user_agent(product: &str, version: &str) -> String {
+    format!("{}/{} {}", product, version, USER_AGENT)
+}
+
+/// Root certificates set with [`set_default_root_certificates`]
+static DEFAULT_ROOT_CERTIFICATES: RwLock<Option<Vec<Certificate>>> = RwLock::new(None);
+
+/// Load the certificates of a PEM file (one or more `BEGIN CERTIFICATE` blocks)
+pub fn load_root_certificates(path: &Path) -> Result<Vec<Certificate>> {
+    let pem = std::fs::read(path).map_err(|e| {
+        MsvcKitError::Config(format!(
+            "Cannot read CA certificate file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    let certificates = Certificate::from_pem_bundle(&pem).map_err(|e| {
+        MsvcKitError::Config(format!(
+            "Invalid CA certificate file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    if certificates.is_empty() {
+        return Err(MsvcKitError::Config(format!(
+            "No certificates found in {}",
+            path.display()
+        )));
+    }
+    Ok(certificates)
+}
+
+/// Trust the certificates of the PEM file `path` in all default HTTP clients
+///
+/// Takes precedence over `MSVC_KIT_TLS_CA_FILE`. Affects clients built
+/// afterwards from [`HttpClientConfig::default`], including manifest
+/// fetching.
+pub fn set_default_root_certificates(path: &Path) -> Result<()> {
+    let certificates = load_root_certificates(path)?;
+    *DEFAULT_ROOT_CERTIFICATES.write().unwrap() = Some(certificates);
+    Ok(())
+}
 
+/// Certificates added to [`HttpClientConfig::default`]
+fn default_root_certificates() -> Vec<Certificate> {
+    if let Some(certificates) = DEFAULT_ROOT_CERTIFICATES.read().unwrap().as_ref() {
+        return certificates.clone();
+    }
+    // Loaded once, so a broken file is reported once
+    static FROM_ENV: OnceLock<Vec<Certificate>> = OnceLock::new();
+    FROM_ENV
+        .get_or_init(|| {
+            let Some(path) = std::env::var_os("MSVC_KIT_TLS_CA_FILE").map(PathBuf::from) else {
+                return Vec::new();
+            };
+            load_root_certificates(&path).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring MSVC_KIT_TLS_CA_FILE: {}", e);
+                Vec::new()
+            })
+        })
+        .clone()
+}
+
+/// HTTP client configuration options
 #[derive(Debug, Clone)]
 pub struct HttpClientConfig {
     /// User agent string
@@ -57,6 +135,8 @@ pub struct HttpClientConfig {
     pub connect_timeout: Option<Duration>,
     /// Request timeout
     pub timeout: Option<Duration>,
+    /// Root certificates trusted in addition to the system roots
+    pub root_certificates: Vec<Certificate>,
 }
 
 impl Default for HttpClientConfig {
@@ -65,6 +145,7 @@ impl Default for HttpClientConfig {
             user_agent: USER_AGENT.to_string(),
             connect_timeout: Some(Duration::from_secs(30)),
             timeout: Some(Duration::from_secs(300)),
+            root_certificates: default_root_certificates(),
         }
     }
 }
@@ -78,6 +159,13 @@ impl HttpClientConfig {
         }
     }
 
+    /// Create a new configuration branded for `product`
+    ///
+    /// The user agent is derived with [`branded_user_agent`].
+    pub fn with_product(product: &str, version: &str) -> Self {
+        Self::with_user_agent(branded_user_agent(product, version))
+    }
+
     /// Set connection timeout
     pub fn connect_timeout(mut self, timeout: Duration) -> Self {
         self.connect_timeout = Some(timeout);
@@ -90,6 +178,13 @@ impl HttpClientConfig {
         self
     }
 
+    /// Trust the certificates of a PEM file, e.g. a corporate root CA
+    pub fn add_root_certificate_pem(mut self, path: impl AsRef<Path>) -> Result<Self> {
+        self.root_certificates
+            .extend(load_root_certificates(path.as_ref())?);
+        Ok(self)
+    }
+
     /// Build the HTTP client with these settings
     pub fn build(&self) -> Client {
         create_http_client_with_config(self)
@@ -146,6 +241,9 @@ pub fn create_http_client_with_config(config: &HttpClientConfig) -> Client {
         builder = builder.use_rustls_tls();
     }
 
+    if !config.root_certificates.is_empty() {
+        builder = builder.tls_certs_merge(config.root_certificates.iter().cloned());
+    }
     if let Some(timeout) = config.connect_timeout {
         builder = builder.connect_timeout(timeout);
     }
@@ -179,6 +277,16 @@ mod tests {
         assert_eq!(config.timeout, Some(Duration::from_secs(60)));
     }
 
+    #[test]
+    fn test_branded_user_agent() {
+        let config = HttpClientConfig::with_product("vx", "0.5.0");
+        assert_eq!(
+            config.user_agent,
+            format!("vx/0.5.0 msvc-kit/{}", env!("CARGO_PKG_VERSION"))
+        );
+        assert_eq!(config.timeout, HttpClientConfig::default().timeout);
+    }
+
     #[test]
     fn test_create_client() {
         let client = create_http_client();
@@ -208,6 +316,39 @@ mod tests {
             .expect("request build should succeed");
     }
 
+    /// Self-signed test CA
+    const TEST_CA_PEM: &str = "\
+-----BEGIN CERTIFICATE-----\n\
+MIIBjDCCATOgAwIBAgIUUNiH8uJvpiSsi/IqSo6PNHsSa8EwCgYIKoZIzj0EAwIw\n\
+GzEZMBcGA1UEAwwQbXN2Yy1raXQgdGVzdCBDQTAgFw0yNjEwMTYxODM3MzJaGA8y\n\
+MTI2MDkyMjE4MzczMlowGzEZMBcGA1UEAwwQbXN2Yy1raXQgdGVzdCBDQTBZMBMG\n\
+ByqGSM49AgEGCCqGSM49AwEHA0IABM9BiJaOTlFOemWsCOzrxHaO1Em19pt+ppFN\n\
+uZzNg/FtkceGlkU1ooh3hrg0fOb5R2iNvTDq1ejfqV1bv0a5vYWjUzBRMB0GA1Ud\n\
+DgQWBBT7ATB4NOW2fWyrZfraeSG/LMdJVDAfBgNVHSMEGDAWgBT7ATB4NOW2fWyr\n\
+ZfraeSG/LMdJVDAPBgNVHRMBAf8EBTADAQH/MAoGCCqGSM49BAMCA0cAMEQCIDAB\n\
+aJZyoDjqrH37UtzlFePRs9jspoxsh7NHxdoLDDrzAiAcdrR2Z2Nlx42CRueLzyoK\n\
+ti+el8DPSB8N3bkc+bve/g==\n\
+-----END CERTIFICATE-----\n";
+
+    #[test]
+    fn test_add_root_certificate_pem() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("ca.pem");
+        std::fs::write(&path, TEST_CA_PEM.repeat(2)).unwrap();
+
+        let config = HttpClientConfig::with_user_agent("msvc-kit/test")
+            .add_root_certificate_pem(&path)
+            .unwrap();
+        assert_eq!(config.root_certificates.len(), 2);
+        drop(config.build());
+
+        std::fs::write(&path, "not a certificate").unwrap();
+        assert!(HttpClientConfig::default()
+            .add_root_certificate_pem(&path)
+            .is_err());
+        assert!(load_root_certificates(&temp_dir.path().join("missing.pem")).is_err());
+    }
+
     #[test]
     fn test_tls_backend_name() {
         let backend = tls_backend_name();
@@ -6,10 +6,21 @@ use redb::{Database, ReadableDatabase, ReadableTable, ReadableTableMetadata, Tab
 use serde::{Deserialize, Serialize};
 use tokio::task;
 
+use crate::downloader::hash::{compute_file_hash, compute_hash, hashes_match};
 use crate::error::{MsvcKitError, Result};
 
 const TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("download_index");
 
+/// Provenance records, JSON-encoded so they can grow without breaking
+/// the bincode-encoded entries of existing databases
+const PROVENANCE_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("download_provenance");
+
+/// Manifest packages the payloads belong to, JSON-encoded
+const PACKAGES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("download_packages");
+
+/// Totals of the last download run per component, JSON-encoded
+const RUNS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("download_runs");
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum DownloadStatus {
     Completed,
@@ -35,6 +46,200 @@ pub struct IndexEntry {
     pub updated_at: DateTime<Utc>,
 }
 
+/// Where and when a payload was downloaded
+///
+/// Recorded for every payload fetched from the network; payloads found on
+/// disk or linked from the shared payload cache keep the record of their
+/// original download, if any.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PayloadProvenance {
+    /// URL from the manifest
+    pub url: String,
+    /// URL the payload was served from, after redirects
+    pub final_url: String,
+    /// When the download finished
+    pub downloaded_at: DateTime<Utc>,
+    /// `ETag` response header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` response header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// Alternate URL the payload came from after `url` failed verification,
+    /// see [`PackagePayload::alternate_urls`](crate::downloader::PackagePayload::alternate_urls)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback_url: Option<String>,
+}
+
+/// Manifest package whose payloads were downloaded into the directory
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IndexedPackage {
+    /// Package ID, e.g. `Microsoft.VC.14.44.17.14.CRT.Headers.base`
+    pub id: String,
+    /// Package version
+    pub version: String,
+    /// Architecture of the package, if not neutral
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chip: Option<String>,
+    /// Payload file names
+    pub payloads: Vec<String>,
+}
+
+impl From<&crate::downloader::Package> for IndexedPackage {
+    fn from(package: &crate::downloader::Package) -> Self {
+        Self {
+            id: package.id.clone(),
+            version: package.version.clone(),
+            chip: package.chip.clone(),
+            payloads: package
+                .payloads
+                .iter()
+                .map(|p| p.file_name.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Totals and throughput of the last download run into a directory
+///
+/// Written while downloading, so a run interrupted and restarted later
+/// knows how far it got and how fast the network was.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RunProgress {
+    /// Component name, e.g. `MSVC`
+    pub component: String,
+    /// Payloads in the run
+    pub total_files: usize,
+    /// Bytes of all payloads in the run
+    pub total_bytes: u64,
+    /// Payloads already on disk
+    pub completed_files: usize,
+    /// Bytes of the payloads already on disk
+    pub completed_bytes: u64,
+    /// Average network throughput in bytes per second (0 if unknown)
+    pub bytes_per_sec: u64,
+    /// When the record was written
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RunProgress {
+    /// Whether every payload of the run is on disk
+    pub fn is_finished(&self) -> bool {
+        self.completed_files >= self.total_files
+    }
+}
+
+/// `format` value of an exported index document
+pub const INDEX_EXPORT_FORMAT: &str = "msvc-kit-download-index";
+
+/// Current version of the exported index document
+pub const INDEX_EXPORT_VERSION: u32 = 1;
+
+/// Portable, self-describing copy of a download index
+///
+/// Written by [`DownloadIndex::export`] as pretty-printed JSON:
+///
+/// ```json
+/// {
+///   "format": "msvc-kit-download-index",
+///   "version": 1,
+///   "exported_at": "2025-01-01T00:00:00Z",
+///   "entries_sha256": "<sha256 of the JSON-encoded entries array>",
+///   "entries": [
+///     {
+///       "file_name": "payload.vsix",
+///       "url": "https://...",
+///       "size": 1024,
+///       "sha256": "...",
+///       "computed_hash": "...",
+///       "path": "payload.vsix",
+///       "status": "Completed",
+///       "bytes_downloaded": 1024,
+///       "hash_verified": true,
+///       "updated_at": "2025-01-01T00:00:00Z",
+///       "provenance": {
+///         "url": "https://...",
+///         "final_url": "https://...",
+///         "downloaded_at": "2025-01-01T00:00:00Z",
+///         "etag": "\"0x8DC...\"",
+///         "last_modified": "Wed, 01 Jan 2025 00:00:00 GMT"
+///       }
+///     }
+///   ]
+/// }
+/// ```
+///
+/// `path` is relative to the downloads directory (the directory holding
+/// `index.db`) and always uses `/`, so the document stays valid when the
+/// directory is moved to another machine. `provenance` is only present for
+/// payloads downloaded since provenance was recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexExport {
+    /// Always [`INDEX_EXPORT_FORMAT`]
+    pub format: String,
+    /// Document version, see [`INDEX_EXPORT_VERSION`]
+    pub version: u32,
+    /// When the document was written
+    pub exported_at: DateTime<Utc>,
+    /// SHA256 of the JSON-encoded `entries`, checked on import
+    pub entries_sha256: String,
+    /// Exported entries, sorted by file name
+    pub entries: Vec<ExportedEntry>,
+}
+
+/// One entry of an [`IndexExport`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ExportedEntry {
+    pub file_name: String,
+    pub url: String,
+    pub size: u64,
+    pub sha256: Option<String>,
+    pub computed_hash: Option<String>,
+    /// Path relative to the downloads directory, `/`-separated
+    pub path: String,
+    pub status: DownloadStatus,
+    pub bytes_downloaded: u64,
+    pub hash_verified: bool,
+    pub updated_at: DateTime<Utc>,
+    /// Download provenance, see [`DownloadIndex::provenance`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<PayloadProvenance>,
+}
+
+/// Outcome of [`DownloadIndex::import`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexImportReport {
+    /// Entries imported with their exported status
+    pub imported: Vec<String>,
+    /// Completed entries whose file did not match and were imported as partial
+    pub downgraded: Vec<String>,
+    /// Entries whose file is missing from the downloads directory
+    pub missing: Vec<String>,
+}
+
+/// Read the run record of `component` from `db`
+fn read_run(db: &impl ReadableDatabase, component: &str) -> Result<Option<RunProgress>> {
+    let tx = db
+        .begin_read()
+        .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+    let table = match tx.open_table(RUNS_TABLE) {
+        Ok(t) => t,
+        Err(_) => return Ok(None),
+    };
+    let maybe_bytes = table
+        .get(component)
+        .map_err(|e| MsvcKitError::Database(e.to_string()))?
+        .map(|value| value.value().to_vec());
+    match maybe_bytes {
+        Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+fn entries_checksum(entries: &[ExportedEntry]) -> Result<String> {
+    Ok(compute_hash(&serde_json::to_vec(entries)?))
+}
+
 /// redb-based download index (single-file, crash-safe)
 pub struct DownloadIndex {
     db: Arc<Database>,
@@ -239,6 +444,9 @@ impl DownloadIndex {
                 if let Ok(mut table) = tx.open_table(TABLE) {
                     let _ = table.remove(key.as_str());
                 }
+                if let Ok(mut table) = tx.open_table(PROVENANCE_TABLE) {
+                    let _ = table.remove(key.as_str());
+                }
             }
             tx.commit()
                 .map_err(|e| MsvcKitError::Database(e.to_string()))?;
@@ -249,6 +457,164 @@ impl DownloadIndex {
         result
     }
 
+    /// Provenance of `file_name`, if it was recorded when downloading it
+    pub async fn provenance(&self, file_name: &str) -> Result<Option<PayloadProvenance>> {
+        let db = self.db.clone();
+        let key = file_name.to_string();
+        task::spawn_blocking(move || -> Result<Option<PayloadProvenance>> {
+            let tx = db
+                .begin_read()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            let table = match tx.open_table(PROVENANCE_TABLE) {
+                Ok(t) => t,
+                Err(_) => return Ok(None),
+            };
+            let maybe_bytes = table
+                .get(key.as_str())
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?
+                .map(|value| value.value().to_vec());
+            match maybe_bytes {
+                Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+                None => Ok(None),
+            }
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Record where and when `file_name` was downloaded
+    pub async fn record_provenance(
+        &mut self,
+        file_name: &str,
+        provenance: &PayloadProvenance,
+    ) -> Result<()> {
+        let db = self.db.clone();
+        let key = file_name.to_string();
+        let bytes = serde_json::to_vec(provenance)?;
+        task::spawn_blocking(move || -> Result<()> {
+            let tx = db
+                .begin_write()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            {
+                let mut table = tx
+                    .open_table(PROVENANCE_TABLE)
+                    .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+                table
+                    .insert(key.as_str(), bytes.as_slice())
+                    .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            }
+            tx.commit()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            Ok(())
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Record that the payloads of `package` belong to it
+    pub async fn record_package(&mut self, package: &IndexedPackage) -> Result<()> {
+        let db = self.db.clone();
+        let key = package.id.clone();
+        let bytes = serde_json::to_vec(package)?;
+        task::spawn_blocking(move || -> Result<()> {
+            let tx = db
+                .begin_write()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            {
+                let mut table = tx
+                    .open_table(PACKAGES_TABLE)
+                    .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+                table
+                    .insert(key.as_str(), bytes.as_slice())
+                    .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            }
+            tx.commit()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            Ok(())
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Recorded packages, sorted by ID
+    ///
+    /// Empty for directories downloaded before packages were recorded.
+    pub async fn packages(&self) -> Result<Vec<IndexedPackage>> {
+        let db = self.db.clone();
+        task::spawn_blocking(move || -> Result<Vec<IndexedPackage>> {
+            let tx = db
+                .begin_read()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            let table = match tx.open_table(PACKAGES_TABLE) {
+                Ok(t) => t,
+                Err(_) => return Ok(Vec::new()),
+            };
+            let mut packages = Vec::new();
+            for item in table
+                .iter()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?
+            {
+                let (_, val) = item.map_err(|e| MsvcKitError::Database(e.to_string()))?;
+                packages.push(serde_json::from_slice(val.value())?);
+            }
+            Ok(packages)
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Last recorded run of `component`, if any
+    pub async fn run_progress(&self, component: &str) -> Result<Option<RunProgress>> {
+        let db = self.db.clone();
+        let key = component.to_string();
+        task::spawn_blocking(move || read_run(db.as_ref(), &key))
+            .await
+            .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Last recorded run of `component` in the index database at `path`
+    ///
+    /// Opens the database read-only: unlike [`load`](Self::load), a missing,
+    /// corrupt or locked database is reported as an error and never
+    /// recreated, so this is safe on an index another process is writing.
+    pub async fn read_run_progress(path: &Path, component: &str) -> Result<Option<RunProgress>> {
+        let path = path.to_string_lossy().replace('\\', "/");
+        let key = component.to_string();
+        task::spawn_blocking(move || -> Result<Option<RunProgress>> {
+            let db = Database::builder()
+                .open_read_only(path.as_str())
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            read_run(&db, &key)
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Record the progress of the current run of `run.component`
+    pub async fn record_run_progress(&mut self, run: &RunProgress) -> Result<()> {
+        let db = self.db.clone();
+        let key = run.component.clone();
+        let bytes = serde_json::to_vec(run)?;
+        task::spawn_blocking(move || -> Result<()> {
+            let tx = db
+                .begin_write()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            {
+                let mut table = tx
+                    .open_table(RUNS_TABLE)
+                    .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+                table
+                    .insert(key.as_str(), bytes.as_slice())
+                    .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            }
+            tx.commit()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            Ok(())
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
     /// Check if entry exists and is identical (fast skip)
     pub async fn is_entry_unchanged(
         &self,
@@ -355,4 +721,185 @@ impl DownloadIndex {
         // redb transactions are durable; no dirty tracking needed
         false
     }
+
+    /// All entries, sorted by file name
+    pub async fn entries(&self) -> Result<Vec<IndexEntry>> {
+        let db = self.db.clone();
+        task::spawn_blocking(move || -> Result<Vec<IndexEntry>> {
+            let tx = db
+                .begin_read()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?;
+            let table = match tx.open_table(TABLE) {
+                Ok(t) => t,
+                Err(_) => return Ok(Vec::new()),
+            };
+            let mut entries = Vec::new();
+            for item in table
+                .iter()
+                .map_err(|e| MsvcKitError::Database(e.to_string()))?
+            {
+                let (_, val) = item.map_err(|e| MsvcKitError::Database(e.to_string()))?;
+                let entry: IndexEntry =
+                    bincode::serde::decode_from_slice(val.value(), bincode::config::standard())
+                        .map_err(|e| MsvcKitError::Database(e.to_string()))?
+                        .0;
+                entries.push(entry);
+            }
+            Ok(entries)
+        })
+        .await
+        .map_err(|je| MsvcKitError::Database(je.to_string()))?
+    }
+
+    /// Rewrite local paths under `old_root` to point below `new_root`
+    ///
+    /// Used after the directory holding the downloads was moved. Returns the
+    /// number of updated entries.
+    pub async fn rebase(&mut self, old_root: &Path, new_root: &Path) -> Result<usize> {
+        let mut updated = 0;
+        for mut entry in self.entries().await? {
+            if let Ok(rel) = entry.local_path.strip_prefix(old_root) {
+                entry.local_path = new_root.join(rel);
+                self.upsert_entry(&entry).await?;
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Directory the index lives in; local paths are exported relative to it
+    fn base_dir(&self) -> &Path {
+        self.path.parent().unwrap_or(Path::new(""))
+    }
+
+    /// Export the index to `path` as an [`IndexExport`] JSON document
+    ///
+    /// Returns the number of exported entries.
+    pub async fn export(&self, path: &Path) -> Result<usize> {
+        let base = self.base_dir();
+        let mut entries = Vec::new();
+        for entry in self.entries().await? {
+            let relative = entry
+                .local_path
+                .strip_prefix(base)
+                .unwrap_or(&entry.local_path);
+            entries.push(ExportedEntry {
+                path: relative.to_string_lossy().replace('\\', "/"),
+                provenance: self.provenance(&entry.file_name).await?,
+                file_name: entry.file_name,
+                url: entry.url,
+                size: entry.size,
+                sha256: entry.sha256,
+                computed_hash: entry.computed_hash,
+                status: entry.status,
+                bytes_downloaded: entry.bytes_downloaded,
+                hash_verified: entry.hash_verified,
+                updated_at: entry.updated_at,
+            });
+        }
+
+        let document = IndexExport {
+            format: INDEX_EXPORT_FORMAT.to_string(),
+            version: INDEX_EXPORT_VERSION,
+            exported_at: Utc::now(),
+            entries_sha256: entries_checksum(&entries)?,
+            entries,
+        };
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(path, serde_json::to_vec_pretty(&document)?).await?;
+        Ok(document.entries.len())
+    }
+
+    /// Import an [`IndexExport`] document written by [`export`](Self::export)
+    ///
+    /// Entry paths are resolved against this index's directory and checked
+    /// against the files on disk:
+    /// - entries whose file is missing are not imported
+    /// - completed entries whose size or hash does not match are imported as
+    ///   partial, so the next download resumes or replaces them
+    /// - partial entries keep the number of bytes actually on disk
+    ///
+    /// Fails if the document has an unknown format or version, or if its
+    /// entries do not match `entries_sha256`.
+    pub async fn import(&mut self, path: &Path) -> Result<IndexImportReport> {
+        let content = tokio::fs::read(path).await?;
+        let document: IndexExport = serde_json::from_slice(&content)?;
+        if document.format != INDEX_EXPORT_FORMAT || document.version > INDEX_EXPORT_VERSION {
+            return Err(MsvcKitError::Serialization(format!(
+                "{} is not a supported download index export (format {:?}, version {})",
+                path.display(),
+                document.format,
+                document.version
+            )));
+        }
+        let actual = entries_checksum(&document.entries)?;
+        if !hashes_match(&actual, &document.entries_sha256) {
+            return Err(MsvcKitError::HashMismatch {
+                file: path.display().to_string(),
+                expected: document.entries_sha256,
+                actual,
+            });
+        }
+
+        let base = self.base_dir().to_path_buf();
+        let mut report = IndexImportReport::default();
+        for exported in document.entries {
+            let local_path = base.join(&exported.path);
+            let Ok(metadata) = tokio::fs::metadata(&local_path).await else {
+                report.missing.push(exported.file_name);
+                continue;
+            };
+            let on_disk = metadata.len();
+
+            let mut entry = IndexEntry {
+                file_name: exported.file_name,
+                url: exported.url,
+                size: exported.size,
+                sha256: exported.sha256,
+                computed_hash: exported.computed_hash,
+                local_path,
+                status: exported.status,
+                bytes_downloaded: exported.bytes_downloaded,
+                hash_verified: exported.hash_verified,
+                updated_at: exported.updated_at,
+            };
+
+            let downgraded = match entry.status {
+                DownloadStatus::Completed => {
+                    let intact = on_disk == entry.size
+                        && match entry.computed_hash.as_deref() {
+                            Some(expected) => {
+                                let actual = compute_file_hash(&entry.local_path).await?;
+                                hashes_match(&actual, expected)
+                            }
+                            None => true,
+                        };
+                    if !intact {
+                        entry.status = DownloadStatus::Partial;
+                        entry.computed_hash = None;
+                        entry.hash_verified = false;
+                        entry.bytes_downloaded = on_disk.min(entry.size);
+                    }
+                    !intact
+                }
+                DownloadStatus::Partial => {
+                    entry.bytes_downloaded = on_disk.min(entry.size);
+                    false
+                }
+            };
+
+            self.upsert_entry(&entry).await?;
+            if let Some(provenance) = &exported.provenance {
+                self.record_provenance(&entry.file_name, provenance).await?;
+            }
+            if downgraded {
+                report.downgraded.push(entry.file_name);
+            } else {
+                report.imported.push(entry.file_name);
+            }
+        }
+        Ok(report)
+    }
 }
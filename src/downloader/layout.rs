@@ -0,0 +1,210 @@
+//! Visual Studio offline layouts as a package source
+//!
+//! `vs_buildtools.exe --layout <dir>` writes the package manifest as
+//! `Catalog.json` and stores each package in a folder named after its
+//! identity (`<id>,version=<version>[,chip=...][,language=...]`) holding the
+//! payload files. [`VsLayout`] reads such a directory so msvc-kit can resolve
+//! packages from it and take payloads from disk instead of downloading them.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::manifest::{Package, PackagePayload, VsManifest};
+use crate::error::{MsvcKitError, Result};
+
+/// Package manifest of a layout
+pub const LAYOUT_CATALOG_FILE: &str = "Catalog.json";
+
+/// A Visual Studio offline layout directory
+#[derive(Debug, Clone)]
+pub struct VsLayout {
+    root: PathBuf,
+    /// Package folders keyed by lowercase `(id, version)`
+    package_dirs: HashMap<(String, String), Vec<PathBuf>>,
+}
+
+impl VsLayout {
+    /// Open a layout, indexing its package folders
+    ///
+    /// Fails if `root` has no `Catalog.json`.
+    pub async fn open(root: &Path) -> Result<Self> {
+        let catalog = root.join(LAYOUT_CATALOG_FILE);
+        if !catalog.is_file() {
+            return Err(MsvcKitError::Other(format!(
+                "{} is not a Visual Studio layout (no {})",
+                root.display(),
+                LAYOUT_CATALOG_FILE
+            )));
+        }
+
+        let mut package_dirs: HashMap<(String, String), Vec<PathBuf>> = HashMap::new();
+        let mut entries = tokio::fs::read_dir(root).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+            let name = entry.file_name();
+            if let Some(key) = parse_package_dir(&name.to_string_lossy()) {
+                package_dirs.entry(key).or_default().push(entry.path());
+            }
+        }
+        tracing::debug!(
+            "Layout {} has {} package folders",
+            root.display(),
+            package_dirs.values().map(Vec::len).sum::<usize>()
+        );
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            package_dirs,
+        })
+    }
+
+    /// Root directory of the layout
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Parse the layout's package manifest
+    pub async fn load_manifest(&self) -> Result<VsManifest> {
+        let catalog = self.root.join(LAYOUT_CATALOG_FILE);
+        tracing::info!("Using VS layout package manifest: {:?}", catalog);
+        VsManifest::from_file(&catalog).await
+    }
+
+    /// Path of a payload in the layout, if present with the expected size
+    ///
+    /// Packages that differ only in chip or language share an id and version,
+    /// so every matching folder is tried.
+    pub fn payload_path(&self, package: &Package, payload: &PackagePayload) -> Option<PathBuf> {
+        let key = (package.id.to_lowercase(), package.version.to_lowercase());
+        let relative: PathBuf = payload.file_name.split(['\\', '/']).collect();
+        self.package_dirs.get(&key)?.iter().find_map(|dir| {
+            let path = dir.join(&relative);
+            match std::fs::metadata(&path) {
+                Ok(meta) if meta.is_file() && (payload.size == 0 || meta.len() == payload.size) => {
+                    Some(path)
+                }
+                _ => None,
+            }
+        })
+    }
+
+    /// Payloads of `packages` found in the layout, keyed by file name
+    pub fn find_payloads(&self, packages: &[Package]) -> HashMap<String, PathBuf> {
+        let mut found = HashMap::new();
+        for package in packages {
+            for payload in &package.payloads {
+                if let Some(path) = self.payload_path(package, payload) {
+                    found.entry(payload.file_name.clone()).or_insert(path);
+                }
+            }
+        }
+        found
+    }
+}
+
+/// Lowercase `(id, version)` of a layout package folder name
+fn parse_package_dir(name: &str) -> Option<(String, String)> {
+    let mut parts = name.split(',');
+    let id = parts.next()?.trim();
+    let version = parts.find_map(|part| part.trim().strip_prefix("version="))?;
+    if id.is_empty() || version.is_empty() {
+        return None;
+    }
+    Some((id.to_lowercase(), version.to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(id: &str, version: &str, file_name: &str, size: u64) -> Package {
+        Package {
+            id: id.to_string(),
+            version: version.to_string(),
+            package_type: "Vsix".to_string(),
+            chip: None,
+            payloads: vec![PackagePayload {
+                file_name: file_name.to_string(),
+                url: format!("https://example.com/{}", file_name),
+                size,
+                sha256: None,
+                mirrors: Vec::new(),
+            }],
+            total_size: size,
+            install_size: 0,
+            optional: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_package_dir() {
+        assert_eq!(
+            parse_package_dir("Microsoft.VC.14.44.CRT.Headers.base,version=14.44.34823"),
+            Some((
+                "microsoft.vc.14.44.crt.headers.base".to_string(),
+                "14.44.34823".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_package_dir("Win11SDK_10.0.26100,version=10.0.26100.1,chip=x64,language=en-US"),
+            Some((
+                "win11sdk_10.0.26100".to_string(),
+                "10.0.26100.1".to_string()
+            ))
+        );
+        assert_eq!(parse_package_dir("certificates"), None);
+    }
+
+    #[tokio::test]
+    async fn test_open_requires_catalog() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(VsLayout::open(temp_dir.path()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_payloads() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::write(
+            root.join(LAYOUT_CATALOG_FILE),
+            r#"{"manifestVersion":"1.1","packages":[]}"#,
+        )
+        .unwrap();
+        let dir = root.join("Microsoft.VC.Tools,version=14.44.1,chip=x64");
+        std::fs::create_dir_all(dir.join("Installers")).unwrap();
+        std::fs::write(dir.join("payload.vsix"), b"1234").unwrap();
+        std::fs::write(dir.join("Installers").join("sdk.msi"), b"12").unwrap();
+
+        let layout = VsLayout::open(root).await.unwrap();
+        assert!(layout.load_manifest().await.unwrap().packages.is_empty());
+
+        let packages = [
+            package("Microsoft.VC.Tools", "14.44.1", "payload.vsix", 4),
+            package("Microsoft.VC.Tools", "14.44.1", "Installers\\sdk.msi", 2),
+            // Wrong size
+            package("Microsoft.VC.Tools", "14.44.1", "payload.vsix", 5),
+            // Other version
+            package("Microsoft.VC.Tools", "14.44.2", "payload.vsix", 4),
+        ];
+        assert_eq!(
+            layout.payload_path(&packages[0], &packages[0].payloads[0]),
+            Some(dir.join("payload.vsix"))
+        );
+        assert_eq!(
+            layout.payload_path(&packages[1], &packages[1].payloads[0]),
+            Some(dir.join("Installers").join("sdk.msi"))
+        );
+        assert!(layout
+            .payload_path(&packages[2], &packages[2].payloads[0])
+            .is_none());
+        assert!(layout
+            .payload_path(&packages[3], &packages[3].payloads[0])
+            .is_none());
+
+        let found = layout.find_payloads(&packages);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found["payload.vsix"], dir.join("payload.vsix"));
+    }
+}
@@ -2,20 +2,26 @@
 //!
 //! Responsible for downloading both the channel manifest and the actual
 //! Visual Studio package manifest (vsman), exposing helpers to look up MSVC
-//! toolset and Windows SDK packages.
+//! toolset and Windows SDK packages. Saved manifests can be parsed offline
+//! with [`VsManifest::from_slice`] and [`VsManifest::from_file`].
 
+use chrono::{DateTime, Utc};
+use indicatif::ProgressBar;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::time::{Duration, Instant};
 
+use super::auth::BoxedAuthProvider;
 use super::cache::{
-    create_spinner, default_manifest_cache_dir, fetch_bytes_with_cache, url_basename,
+    create_spinner, default_manifest_cache_dir, fetch_bytes_with_cache_auth, meta_path_for,
+    read_meta, url_basename,
 };
-use super::MsvcComponent;
-use crate::constants::{USER_AGENT, VS_CHANNEL_URL};
+use super::{AppPlatform, HttpClientConfig, MsvcComponent};
+use crate::constants::VS_CHANNEL_URL;
 use crate::error::{MsvcKitError, Result};
+use crate::version::Architecture;
 
 /// Channel manifest structure (top-level)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,10 +43,24 @@ pub struct ChannelManifest {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ChannelInfo {
+    /// Visual Studio product version shown to users (e.g. "17.12.3")
     #[serde(default)]
     pub product_display_version: Option<String>,
+    /// Full build version of the release (e.g. "17.12.35527.113")
     #[serde(default)]
     pub build_version: Option<String>,
+    /// Semantic product version (e.g. "17.12.3+35527.113")
+    #[serde(default)]
+    pub product_semantic_version: Option<String>,
+    /// Product line version (e.g. "2022")
+    #[serde(default)]
+    pub product_line_version: Option<String>,
+    /// Release date of the channel manifest
+    ///
+    /// Not part of the channel JSON; taken from the `Last-Modified` header
+    /// recorded when the channel manifest was downloaded.
+    #[serde(default)]
+    pub release_date: Option<DateTime<Utc>>,
 }
 
 /// Channel item entry
@@ -86,6 +106,9 @@ pub struct VsPackage {
     pub machine_arch: Option<String>,
     #[serde(default)]
     pub product_arch: Option<String>,
+    /// Bytes on disk once installed, per drive (`targetDrive`, `sharedDrive`, ...)
+    #[serde(default)]
+    pub install_sizes: HashMap<String, u64>,
 }
 
 /// Payload information
@@ -98,6 +121,9 @@ pub struct Payload {
     #[serde(default)]
     pub size: Option<u64>,
     pub url: String,
+    /// Other URLs serving the same file
+    #[serde(default)]
+    pub mirrors: Vec<String>,
 }
 
 /// Simplified package info returned to downloaders
@@ -109,6 +135,48 @@ pub struct Package {
     pub chip: Option<String>,
     pub payloads: Vec<PackagePayload>,
     pub total_size: u64,
+    /// Bytes on disk once installed (0 if the manifest does not say)
+    pub install_size: u64,
+    /// Whether the toolchain works without this package, see [`is_optional_package`]
+    pub optional: bool,
+}
+
+/// Whether `id` names `arch` after `prefix` (e.g. `.x64`, `targetarm`)
+///
+/// `arm` does not match `arm64`, so 32-bit ARM selections do not pick up
+/// ARM64 packages.
+fn contains_arch(id: &str, prefix: &str, arch: &str) -> bool {
+    let needle = format!("{}{}", prefix, arch);
+    id.match_indices(&needle)
+        .any(|(i, _)| arch != "arm" || !id[i + needle.len()..].starts_with("64"))
+}
+
+/// Whether a package is non-essential for building
+///
+/// Localized satellite packages (a `language` other than `neutral` or
+/// `en-US`) only hold translated messages, and `*.Source` packages only hold
+/// sources for debugging. English resource packages stay essential: `cl.exe`
+/// loads its messages from them.
+pub fn is_optional_package(id: &str, language: Option<&str>) -> bool {
+    let localized = language.is_some_and(|lang| {
+        !lang.eq_ignore_ascii_case("neutral") && !lang.eq_ignore_ascii_case("en-us")
+    });
+    localized
+        || id
+            .rsplit('.')
+            .next()
+            .is_some_and(|last| last.eq_ignore_ascii_case("source"))
+}
+
+impl Package {
+    /// Bytes on disk once installed, falling back to the download size
+    pub fn installed_size(&self) -> u64 {
+        if self.install_size > 0 {
+            self.install_size
+        } else {
+            self.total_size
+        }
+    }
 }
 
 /// Payload ready for download
@@ -118,37 +186,71 @@ pub struct PackagePayload {
     pub url: String,
     pub size: u64,
     pub sha256: Option<String>,
+    /// Other URLs serving the same file, listed by the manifest
+    pub mirrors: Vec<String>,
 }
 
-impl VsManifest {
-    /// Fetch and parse the latest VS manifest (cached).
+impl PackagePayload {
+    /// URLs to download the payload from when `url` keeps failing verification
     ///
-    /// The cache is stored under the OS-specific cache directory.
-    pub async fn fetch() -> Result<Self> {
-        let cache_dir = default_manifest_cache_dir();
-        Self::fetch_with_cache_dir(&cache_dir).await
+    /// The manifest's mirrors and, with `allow_http`, `url` over plain
+    /// `http`: a copy that fails its hash usually comes from one bad CDN
+    /// edge, and the other scheme is often served by another. The hash is
+    /// checked either way, but the request itself is visible on the network,
+    /// hence opt-in (see [`DownloadOptions::allow_http_fallback`](super::DownloadOptions::allow_http_fallback)).
+    pub fn alternate_urls(&self, allow_http: bool) -> Vec<String> {
+        let mut urls: Vec<String> = Vec::new();
+        let http = self
+            .url
+            .strip_prefix("https://")
+            .filter(|_| allow_http)
+            .map(|rest| format!("http://{}", rest));
+        for url in self.mirrors.iter().cloned().chain(http) {
+            if url != self.url && !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        urls
     }
+}
 
-    /// Fetch and parse the latest VS manifest using a specific cache directory.
-    pub async fn fetch_with_cache_dir(cache_dir: &Path) -> Result<Self> {
-        let client = reqwest::Client::builder()
-            .user_agent(USER_AGENT)
-            .build()
-            .map_err(|e| MsvcKitError::Other(format!("Failed to create HTTP client: {}", e)))?;
+impl ChannelManifest {
+    /// Parse a channel manifest from raw JSON bytes.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
 
-        // Step 1: Fetch channel manifest (cached)
+    /// Fetch the channel manifest using a specific cache directory.
+    ///
+    /// The returned manifest's [`ChannelInfo::release_date`] is filled from the
+    /// cached `Last-Modified` header when available.
+    pub async fn fetch_with_cache_dir(cache_dir: &Path) -> Result<Self> {
+        let client = manifest_http_client()?;
         let channel_name = url_basename(VS_CHANNEL_URL);
         let spinner = create_spinner(&format!("Fetching channel manifest: {}", channel_name));
+        let channel = Self::fetch_with_spinner(&client, cache_dir, &spinner, None).await;
+        spinner.finish_and_clear();
+        channel
+    }
+
+    async fn fetch_with_spinner(
+        client: &reqwest::Client,
+        cache_dir: &Path,
+        spinner: &ProgressBar,
+        auth: Option<&BoxedAuthProvider>,
+    ) -> Result<Self> {
+        let channel_name = url_basename(VS_CHANNEL_URL);
         tracing::debug!("Fetching channel manifest from {}", VS_CHANNEL_URL);
 
         let channel_cache = cache_dir.join("channel.json");
-        let (channel_bytes, channel_cached) = fetch_bytes_with_cache(
-            &client,
+        let (channel_bytes, channel_cached) = fetch_bytes_with_cache_auth(
+            client,
             VS_CHANNEL_URL,
             &channel_cache,
-            &spinner,
+            spinner,
             &format!("Downloading channel manifest: {}", channel_name),
             &channel_name,
+            auth,
         )
         .await?;
 
@@ -157,33 +259,53 @@ impl VsManifest {
         }
 
         spinner.set_message(format!("Parsing channel manifest: {}", channel_name));
-        let channel_manifest: ChannelManifest = serde_json::from_slice(&channel_bytes)?;
+        let channel_manifest = Self::from_slice(&channel_bytes)?;
+        Ok(channel_manifest
+            .with_cached_release_date(&channel_cache)
+            .await)
+    }
 
-        // Show channel info if available
-        if let Some(ref info) = channel_manifest.info {
-            if let Some(ref version) = info.product_display_version {
-                spinner.set_message(format!("Found Visual Studio {} channel", version));
-                tokio::time::sleep(Duration::from_millis(300)).await;
-            }
-        }
+    /// Load the channel manifest from the cache only, without any network access.
+    pub async fn load_cached(cache_dir: &Path) -> Result<Self> {
+        let channel_cache = cache_dir.join("channel.json");
+        let bytes = tokio::fs::read(&channel_cache).await.map_err(|_| {
+            MsvcKitError::Offline(format!(
+                "no cached channel manifest at {}",
+                channel_cache.display()
+            ))
+        })?;
+        let channel_manifest = Self::from_slice(&bytes)?;
+        Ok(channel_manifest
+            .with_cached_release_date(&channel_cache)
+            .await)
+    }
+
+    /// Fill `info.release_date` from the cached `Last-Modified` header
+    async fn with_cached_release_date(mut self, channel_cache: &Path) -> Self {
+        let release_date = read_meta(&meta_path_for(channel_cache))
+            .await
+            .and_then(|meta| meta.last_modified)
+            .and_then(|lm| DateTime::parse_from_rfc2822(&lm).ok())
+            .map(|dt| dt.with_timezone(&Utc));
+        self.info
+            .get_or_insert_with(ChannelInfo::default)
+            .release_date = release_date;
+        self
+    }
 
-        let manifest_item = channel_manifest
+    /// URL and file name of the VS package manifest referenced by this channel
+    pub fn vs_manifest_location(&self) -> Result<(String, String)> {
+        let manifest_item = self
             .channel_items
             .iter()
             .find(|item| item.id == "Microsoft.VisualStudio.Manifests.VisualStudio")
-            .ok_or_else(|| {
-                spinner.finish_and_clear();
-                MsvcKitError::Other("Manifest entry missing in channel".to_string())
-            })?;
+            .ok_or_else(|| MsvcKitError::Other("Manifest entry missing in channel".to_string()))?;
 
         let manifest_url = manifest_item
             .payloads
             .first()
             .map(|p| p.url.clone())
-            .ok_or_else(|| {
-                spinner.finish_and_clear();
-                MsvcKitError::Other("Manifest URL missing".to_string())
-            })?;
+            .ok_or_else(|| MsvcKitError::Other("Manifest URL missing".to_string()))?;
 
         let manifest_file_name = manifest_item
             .payloads
@@ -197,6 +319,158 @@ impl VsManifest {
             })
             .unwrap_or_else(|| url_basename(&manifest_url));
 
+        Ok((manifest_url, manifest_file_name))
+    }
+
+    /// Channel metadata (VS product/build version and release date)
+    pub fn channel_info(&self) -> ChannelInfo {
+        self.info.clone().unwrap_or_default()
+    }
+}
+
+/// Fetch the Visual Studio channel metadata (product/build version, release date).
+///
+/// Uses the same on-disk cache as [`VsManifest::fetch`], so this is cheap after
+/// a manifest has been fetched once.
+pub async fn fetch_channel_info() -> Result<ChannelInfo> {
+    let cache_dir = default_manifest_cache_dir();
+    Ok(ChannelManifest::fetch_with_cache_dir(&cache_dir)
+        .await?
+        .channel_info())
+}
+
+fn manifest_http_client() -> Result<reqwest::Client> {
+    // No timeouts, as before; keeps the custom root certificates
+    let config = HttpClientConfig {
+        connect_timeout: None,
+        timeout: None,
+        ..Default::default()
+    };
+    Ok(config.build())
+}
+
+impl VsManifest {
+    /// Fetch and parse the latest VS manifest (cached).
+    ///
+    /// The cache is stored under the OS-specific cache directory.
+    pub async fn fetch() -> Result<Self> {
+        let cache_dir = default_manifest_cache_dir();
+        Self::fetch_with_cache_dir(&cache_dir).await
+    }
+
+    /// Fetch and parse the latest VS manifest using a specific cache directory.
+    pub async fn fetch_with_cache_dir(cache_dir: &Path) -> Result<Self> {
+        Ok(Self::fetch_with_channel_info(cache_dir).await?.0)
+    }
+
+    /// Fetch and parse the latest VS manifest, attaching headers from an
+    /// [`AuthProvider`](super::AuthProvider) to every manifest request.
+    pub async fn fetch_with_auth(
+        cache_dir: &Path,
+        auth: Option<&BoxedAuthProvider>,
+    ) -> Result<Self> {
+        let client = manifest_http_client()?;
+        Self::fetch_with_client(cache_dir, &client, auth).await
+    }
+
+    /// Fetch and parse the latest VS manifest with a caller-provided HTTP client
+    ///
+    /// Lets the manifest requests share the user agent, proxy and TLS settings
+    /// of the client used for the payloads.
+    pub async fn fetch_with_client(
+        cache_dir: &Path,
+        client: &reqwest::Client,
+        auth: Option<&BoxedAuthProvider>,
+    ) -> Result<Self> {
+        Ok(Self::fetch_impl(cache_dir, client, auth).await?.0)
+    }
+
+    /// Fetch and parse the latest VS manifest together with the channel metadata
+    /// it was resolved from.
+    pub async fn fetch_with_channel_info(cache_dir: &Path) -> Result<(Self, ChannelInfo)> {
+        let client = manifest_http_client()?;
+        Self::fetch_impl(cache_dir, &client, None).await
+    }
+
+    /// Load the VS manifest from the cache only, without any network access.
+    ///
+    /// Fails with [`MsvcKitError::Offline`] when the channel or package
+    /// manifest has never been cached in `cache_dir`.
+    pub async fn load_cached(cache_dir: &Path) -> Result<Self> {
+        Ok(Self::load_cached_with_channel_info(cache_dir).await?.0)
+    }
+
+    async fn load_cached_with_channel_info(cache_dir: &Path) -> Result<(Self, ChannelInfo)> {
+        let channel = ChannelManifest::load_cached(cache_dir).await?;
+        let (_, manifest_file_name) = channel.vs_manifest_location()?;
+
+        let vsman_cache = cache_dir.join("vsman").join(&manifest_file_name);
+        if !vsman_cache.is_file() {
+            return Err(MsvcKitError::Offline(format!(
+                "no cached package manifest at {}",
+                vsman_cache.display()
+            )));
+        }
+
+        tracing::info!("Using cached VS package manifest: {:?}", vsman_cache);
+        let manifest = Self::from_file(&vsman_cache).await?;
+        Ok((manifest, channel.channel_info()))
+    }
+
+    /// Fetch from the network, falling back to the cached manifest when the
+    /// network is unreachable.
+    async fn fetch_impl(
+        cache_dir: &Path,
+        client: &reqwest::Client,
+        auth: Option<&BoxedAuthProvider>,
+    ) -> Result<(Self, ChannelInfo)> {
+        match Self::fetch_network(cache_dir, client, auth).await {
+            Err(e @ (MsvcKitError::Network(_) | MsvcKitError::DownloadNetwork { .. })) => {
+                match Self::load_cached_with_channel_info(cache_dir).await {
+                    Ok(cached) => {
+                        tracing::warn!("Network unavailable ({}), using cached manifest", e);
+                        Ok(cached)
+                    }
+                    Err(_) => Err(e),
+                }
+            }
+            result => result,
+        }
+    }
+
+    async fn fetch_network(
+        cache_dir: &Path,
+        client: &reqwest::Client,
+        auth: Option<&BoxedAuthProvider>,
+    ) -> Result<(Self, ChannelInfo)> {
+        // Step 1: Fetch channel manifest (cached)
+        let channel_name = url_basename(VS_CHANNEL_URL);
+        let spinner = create_spinner(&format!("Fetching channel manifest: {}", channel_name));
+        let channel_manifest =
+            match ChannelManifest::fetch_with_spinner(client, cache_dir, &spinner, auth).await {
+                Ok(channel) => channel,
+                Err(e) => {
+                    spinner.finish_and_clear();
+                    return Err(e);
+                }
+            };
+
+        // Show channel info if available
+        if let Some(ref info) = channel_manifest.info {
+            if let Some(ref version) = info.product_display_version {
+                spinner.set_message(format!("Found Visual Studio {} channel", version));
+                tokio::time::sleep(Duration::from_millis(300)).await;
+            }
+        }
+
+        let (manifest_url, manifest_file_name) = match channel_manifest.vs_manifest_location() {
+            Ok(location) => location,
+            Err(e) => {
+                spinner.finish_and_clear();
+                return Err(e);
+            }
+        };
+
         tracing::info!(
             "VS package manifest: {} ({})",
             manifest_file_name,
@@ -211,13 +485,14 @@ impl VsManifest {
             manifest_file_name
         ));
 
-        let (manifest_bytes, vsman_cached) = fetch_bytes_with_cache(
-            &client,
+        let (manifest_bytes, vsman_cached) = fetch_bytes_with_cache_auth(
+            client,
             &manifest_url,
             &vsman_cache,
             &spinner,
             &download_label,
             &manifest_file_name,
+            auth,
         )
         .await?;
 
@@ -247,15 +522,9 @@ impl VsManifest {
             }
         });
 
-        let manifest: VsManifest = tokio::task::spawn_blocking(move || {
-            // Use simd-json for faster parsing (2-5x faster than serde_json)
-            let mut bytes = manifest_bytes;
-            simd_json::from_slice(&mut bytes)
-        })
-        .await
-        .map_err(|e| MsvcKitError::Other(format!("Failed to join parsing task: {}", e)))??;
-
+        let manifest = Self::parse_owned_blocking(manifest_bytes).await;
         let _ = done_tx.send(());
+        let manifest = manifest?;
 
         spinner.finish_with_message(format!(
             "✓ Loaded manifest with {} packages",
@@ -266,7 +535,36 @@ impl VsManifest {
             "Loaded VS manifest with {} packages",
             manifest.packages.len()
         );
-        Ok(manifest)
+        Ok((manifest, channel_manifest.channel_info()))
+    }
+
+    /// Parse a VS package manifest (`.vsman`) from raw JSON bytes.
+    ///
+    /// This performs no network access and no caching, which makes it suitable
+    /// for offline tooling and tests working from a saved manifest.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        Self::parse_owned(bytes.to_vec())
+    }
+
+    /// Read and parse a VS package manifest (`.vsman`) from a file on disk.
+    ///
+    /// Parsing runs on a blocking thread since real manifests are tens of MB.
+    pub async fn from_file(path: &Path) -> Result<Self> {
+        let bytes = tokio::fs::read(path).await?;
+        Self::parse_owned_blocking(bytes).await
+    }
+
+    /// Parse manifest bytes in place (simd-json mutates its input buffer).
+    fn parse_owned(mut bytes: Vec<u8>) -> Result<Self> {
+        // Use simd-json for faster parsing (2-5x faster than serde_json)
+        Ok(simd_json::from_slice(&mut bytes)?)
+    }
+
+    /// Parse manifest bytes on a blocking thread.
+    async fn parse_owned_blocking(bytes: Vec<u8>) -> Result<Self> {
+        tokio::task::spawn_blocking(move || Self::parse_owned(bytes))
+            .await
+            .map_err(|e| MsvcKitError::Other(format!("Failed to join parsing task: {}", e)))?
     }
 
     /// Get latest MSVC toolset version prefix (e.g. "14.42")
@@ -319,6 +617,9 @@ impl VsManifest {
     /// * `target_arch` - Target architecture (e.g., "x64")
     /// * `include_components` - Optional components to include (e.g., Spectre)
     /// * `exclude_patterns` - Package ID patterns to exclude
+    ///
+    /// Only the desktop CRT variants are included; see
+    /// [`find_msvc_packages_for_platforms`](Self::find_msvc_packages_for_platforms).
     pub fn find_msvc_packages(
         &self,
         version_prefix: &str,
@@ -326,6 +627,30 @@ impl VsManifest {
         target_arch: &str,
         include_components: &HashSet<MsvcComponent>,
         exclude_patterns: &[String],
+    ) -> Vec<Package> {
+        self.find_msvc_packages_for_platforms(
+            version_prefix,
+            host_arch,
+            target_arch,
+            include_components,
+            &AppPlatform::defaults(),
+            exclude_patterns,
+        )
+    }
+
+    /// Find MSVC packages, keeping the CRT variants of `app_platforms`
+    ///
+    /// Packages specific to an application platform (`CRT.x64.Store`,
+    /// `CRT.x64.OneCore.Desktop`, ...) are only included when their platform
+    /// is selected. Store variants are also included with [`MsvcComponent::Uwp`].
+    pub fn find_msvc_packages_for_platforms(
+        &self,
+        version_prefix: &str,
+        host_arch: &str,
+        target_arch: &str,
+        include_components: &HashSet<MsvcComponent>,
+        app_platforms: &HashSet<AppPlatform>,
+        exclude_patterns: &[String],
     ) -> Vec<Package> {
         let version_prefix = format!("Microsoft.VC.{}.", version_prefix);
         let host = host_arch.to_lowercase();
@@ -360,8 +685,8 @@ impl VsManifest {
                 // Tool packages: must match both host and target architecture
                 // e.g., Microsoft.VC.14.44.Tools.HostX64.TargetX64
                 let is_tool = id.contains("tools")
-                    && id.contains(&format!("host{}", host))
-                    && id.contains(&format!("target{}", target));
+                    && contains_arch(&id, "host", &host)
+                    && contains_arch(&id, "target", &target);
 
                 if is_tool {
                     return true;
@@ -385,6 +710,15 @@ impl VsManifest {
 
                 let is_arch_filtered = is_crt || is_runtime || is_cli || is_modules || is_redist;
 
+                if let Some(platform) = AppPlatform::of_package(&id).filter(|_| is_arch_filtered) {
+                    let selected = app_platforms.contains(&platform)
+                        || (platform == AppPlatform::Store
+                            && include_components.contains(&MsvcComponent::Uwp));
+                    if !selected {
+                        return false;
+                    }
+                }
+
                 if is_arch_filtered {
                     // Check if package ID contains architecture suffix
                     // Architecture-neutral packages (like CRT.Headers, CRT.Source) should be included
@@ -397,11 +731,7 @@ impl VsManifest {
 
                     if has_arch_in_id {
                         // Package has architecture in ID - must match target
-                        let matches_target = id.contains(&format!(".{}", target))
-                            || id.contains(&format!(".{}.desktop", target))
-                            || id.contains(&format!(".{}.store", target))
-                            || id.contains(&format!(".{}.uwp", target));
-                        return matches_target;
+                        return contains_arch(&id, ".", &target);
                     }
 
                     // Also check chip field if present
@@ -463,7 +793,7 @@ impl VsManifest {
                 if has_arch_in_id {
                     // Package has architecture in ID - must match target
                     // Allow x86 packages when targeting x64 (needed for 32-bit compatibility)
-                    let matches_target = id.contains(&format!("_{}", target))
+                    let matches_target = contains_arch(&id, "_", &target)
                         || (target == "x64" && id.contains("_x86"));
                     if !matches_target {
                         return false;
@@ -488,6 +818,46 @@ impl VsManifest {
             .collect()
     }
 
+    /// List the host/target tool combinations available for an MSVC version
+    ///
+    /// Pairs are taken from `Microsoft.VC.<version>.Tools.Host<host>.Target<target>`
+    /// package IDs and returned lowercase, e.g. `("x64", "arm64")`.
+    pub fn msvc_host_targets(&self, version_prefix: &str) -> Vec<(String, String)> {
+        let prefix = format!("microsoft.vc.{}.", version_prefix.to_lowercase());
+        let mut pairs: Vec<(String, String)> = self
+            .packages
+            .iter()
+            .filter_map(|pkg| {
+                let id = pkg.id.to_lowercase();
+                let rest = id.strip_prefix(&prefix)?;
+                let mut parts = rest.split('.').skip_while(|p| *p != "tools").skip(1);
+                let host = parts.next()?.strip_prefix("host")?.to_string();
+                let target = parts.next()?.strip_prefix("target")?.to_string();
+                Some((host, target))
+            })
+            .collect();
+
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Target architectures the MSVC toolset `version_prefix` has compilers for
+    ///
+    /// Recent toolsets no longer ship 32-bit ARM compilers, so
+    /// [`Architecture::Arm`] is only listed for older ones. Empty if the
+    /// version is not in the manifest.
+    pub fn msvc_target_archs(&self, version_prefix: &str) -> Vec<Architecture> {
+        let mut archs: Vec<Architecture> = self
+            .msvc_host_targets(version_prefix)
+            .into_iter()
+            .filter_map(|(_, target)| target.parse().ok())
+            .collect();
+        archs.sort_by_key(|arch| arch.to_string());
+        archs.dedup();
+        archs
+    }
+
     /// List all available MSVC version prefixes
     pub fn list_msvc_versions(&self) -> Vec<String> {
         let mut versions: Vec<String> = self
@@ -575,6 +945,19 @@ impl VsManifest {
         })
     }
 
+    /// Newest SDK version whose build number is at most `build`
+    ///
+    /// For example, Windows build 22631 selects "10.0.22621.0" if the
+    /// manifest lists no 22631 SDK.
+    pub fn sdk_version_for_build(&self, build: u32) -> Option<String> {
+        self.list_sdk_versions()
+            .into_iter()
+            .filter_map(|v| Some((v.split('.').nth(2)?.parse::<u32>().ok()?, v)))
+            .filter(|(b, _)| *b <= build)
+            .max()
+            .map(|(_, v)| v)
+    }
+
     fn vs_package_to_package(&self, pkg: &VsPackage) -> Package {
         let payloads: Vec<PackagePayload> = pkg
             .payloads
@@ -584,6 +967,7 @@ impl VsManifest {
                 url: p.url.clone(),
                 size: p.size.unwrap_or(0),
                 sha256: p.sha256.clone(),
+                mirrors: p.mirrors.clone(),
             })
             .collect();
 
@@ -596,6 +980,8 @@ impl VsManifest {
             chip: pkg.chip.clone(),
             payloads,
             total_size,
+            install_size: pkg.install_sizes.values().sum(),
+            optional: is_optional_package(&pkg.id, pkg.language.as_deref()),
         }
     }
 }
@@ -629,12 +1015,58 @@ mod tests {
             url: "https://example.com/test.vsix".into(),
             size: 2048,
             sha256: Some("abc123".into()),
+            mirrors: Vec::new(),
         };
 
         assert_eq!(payload.file_name, "test.vsix");
         assert_eq!(payload.size, 2048);
     }
 
+    #[test]
+    fn package_payload_alternate_urls() {
+        let payload = PackagePayload {
+            file_name: "test.vsix".into(),
+            url: "https://example.com/test.vsix".into(),
+            size: 2048,
+            sha256: None,
+            mirrors: vec![
+                "https://mirror.example.com/test.vsix".into(),
+                "https://example.com/test.vsix".into(),
+                "http://example.com/test.vsix".into(),
+            ],
+        };
+        assert_eq!(
+            payload.alternate_urls(true),
+            vec![
+                "https://mirror.example.com/test.vsix".to_string(),
+                "http://example.com/test.vsix".to_string(),
+            ]
+        );
+
+        // Without the opt-in, plain http only appears when the manifest lists it
+        let https_only = PackagePayload {
+            mirrors: vec!["https://mirror.example.com/test.vsix".into()],
+            ..payload.clone()
+        };
+        assert_eq!(
+            https_only.alternate_urls(false),
+            vec!["https://mirror.example.com/test.vsix".to_string()]
+        );
+
+        let plain = PackagePayload {
+            url: "http://example.com/test.vsix".into(),
+            mirrors: Vec::new(),
+            ..payload
+        };
+        assert!(plain.alternate_urls(true).is_empty());
+
+        let parsed: Payload = serde_json::from_str(
+            r#"{"fileName": "a.cab", "url": "https://a/a.cab", "mirrors": ["https://b/a.cab"]}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.mirrors, vec!["https://b/a.cab".to_string()]);
+    }
+
     #[test]
     fn test_normalize_sdk_version() {
         // Normal version with trailing .0
@@ -671,6 +1103,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // Tools for other architectures (should be filtered out for x64)
                 VsPackage {
@@ -683,6 +1116,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.Tools.HostX64.TargetX86.base".to_string(),
@@ -694,6 +1128,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // CRT Headers (architecture-neutral, should always be included)
                 VsPackage {
@@ -706,6 +1141,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // CRT with architecture suffix (should be filtered)
                 VsPackage {
@@ -718,6 +1154,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.CRT.ARM64.Desktop".to_string(),
@@ -729,6 +1166,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.CRT.x86.Desktop".to_string(),
@@ -740,6 +1178,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // MFC packages with architecture
                 VsPackage {
@@ -752,6 +1191,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.MFC.ARM64".to_string(),
@@ -763,6 +1203,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // ATL packages with architecture
                 VsPackage {
@@ -775,6 +1216,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.ATL.ARM64".to_string(),
@@ -786,6 +1228,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // Spectre-mitigated libraries (should be filtered out)
                 VsPackage {
@@ -798,6 +1241,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.MFC.x64.Spectre".to_string(),
@@ -809,6 +1253,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // C++/CLI support packages (opt-in only)
                 VsPackage {
@@ -821,6 +1266,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.CLI.ARM64".to_string(),
@@ -832,6 +1278,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // C++ Modules packages (opt-in only)
                 VsPackage {
@@ -844,6 +1291,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // C++ Redistributable packages (opt-in only)
                 VsPackage {
@@ -856,6 +1304,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Microsoft.VC.14.44.Redist.ARM64".to_string(),
@@ -867,6 +1316,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // Older version tools
                 VsPackage {
@@ -879,6 +1329,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // SDK packages with different architectures
                 VsPackage {
@@ -891,6 +1342,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Win11SDK_10.0.26100_arm64".to_string(),
@@ -902,6 +1354,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 VsPackage {
                     id: "Win10SDK_10.0.22621".to_string(),
@@ -913,6 +1366,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
                 // SDK neutral package (should always be included)
                 VsPackage {
@@ -925,6 +1379,7 @@ mod tests {
                     dependencies: HashMap::new(),
                     machine_arch: None,
                     product_arch: None,
+                    install_sizes: HashMap::new(),
                 },
             ],
         }
@@ -1003,6 +1458,181 @@ mod tests {
         assert_eq!(not_found, None);
     }
 
+    #[test]
+    fn test_select_stable() {
+        use crate::downloader::{select_stable, KnownGoodCombo};
+
+        let manifest = create_test_manifest();
+        let combo = |msvc: &str, sdk: &str| KnownGoodCombo {
+            msvc: msvc.to_string(),
+            sdk: sdk.to_string(),
+        };
+        let combos = [
+            combo("14.50", "10.0.26100.0"),
+            combo("14.44", "10.0.99999.0"),
+            combo("14.43", "10.0.22621.0"),
+        ];
+        assert_eq!(
+            select_stable(&combos, &manifest),
+            Some(combo("14.43", "10.0.22621.0"))
+        );
+        assert_eq!(select_stable(&combos[..2], &manifest), None);
+    }
+
+    #[test]
+    fn test_resolution_reports() {
+        use crate::downloader::resolution::{resolve_msvc, resolve_sdk};
+        use crate::downloader::MatchKind;
+
+        let manifest = create_test_manifest();
+
+        let report = resolve_msvc(&manifest, Some("14.44"), "14.44");
+        assert_eq!(report.full_version.as_deref(), Some("14.44.34823"));
+        assert_eq!(report.kind, MatchKind::Prefix);
+        assert_eq!(
+            resolve_msvc(&manifest, None, "14.44").kind,
+            MatchKind::Latest
+        );
+        assert_eq!(
+            resolve_msvc(&manifest, Some("14.99"), "14.99").kind,
+            MatchKind::Unlisted
+        );
+
+        let report = resolve_sdk(&manifest, Some("26100")).unwrap();
+        assert_eq!(report.resolved, "10.0.26100.0");
+        assert_eq!(report.kind, MatchKind::BuildNumber);
+        let report = resolve_sdk(&manifest, None).unwrap();
+        assert_eq!(report.resolved, "10.0.26100.0");
+        assert_eq!(report.kind, MatchKind::Latest);
+        let report = resolve_sdk(&manifest, Some("10.0.99999.0")).unwrap();
+        assert_eq!(report.resolved, "10.0.99999.0");
+        assert_eq!(report.kind, MatchKind::Unlisted);
+        assert_eq!(
+            report.warning().map(|w| w.code),
+            Some(crate::WarningCode::FallbackVersion)
+        );
+    }
+
+    #[test]
+    fn test_version_resolver() {
+        use crate::downloader::{
+            DefaultVersionResolver, MatchKind, ResolutionReport, VersionResolver,
+        };
+
+        let manifest = create_test_manifest();
+        let report = DefaultVersionResolver
+            .resolve_msvc(&manifest, Some("14.43"))
+            .unwrap();
+        assert_eq!(report.resolved, "14.43");
+        assert_eq!(report.kind, MatchKind::Prefix);
+        let report = DefaultVersionResolver
+            .resolve_msvc(&manifest, None)
+            .unwrap();
+        assert_eq!(report.resolved, "14.44");
+        let report = DefaultVersionResolver
+            .resolve_sdk(&manifest, Some("26100"))
+            .unwrap();
+        assert_eq!(report.resolved, "10.0.26100.0");
+
+        // A policy capping MSVC at 14.43 unless a version is requested
+        struct Capped;
+        impl VersionResolver for Capped {
+            fn resolve_msvc(
+                &self,
+                manifest: &VsManifest,
+                requested: Option<&str>,
+            ) -> crate::Result<ResolutionReport> {
+                DefaultVersionResolver.resolve_msvc(manifest, requested.or(Some("14.43")))
+            }
+
+            fn resolve_sdk(
+                &self,
+                manifest: &VsManifest,
+                requested: Option<&str>,
+            ) -> crate::Result<ResolutionReport> {
+                DefaultVersionResolver.resolve_sdk(manifest, requested)
+            }
+        }
+        assert_eq!(
+            Capped.resolve_msvc(&manifest, None).unwrap().resolved,
+            "14.43"
+        );
+        assert_eq!(
+            Capped
+                .resolve_msvc(&manifest, Some("14.44"))
+                .unwrap()
+                .resolved,
+            "14.44"
+        );
+    }
+
+    #[test]
+    fn test_contains_arch() {
+        assert!(contains_arch(
+            "microsoft.vc.14.29.crt.arm.desktop",
+            ".",
+            "arm"
+        ));
+        assert!(!contains_arch(
+            "microsoft.vc.14.29.crt.arm64.desktop",
+            ".",
+            "arm"
+        ));
+        assert!(contains_arch(
+            "microsoft.vc.14.29.crt.arm64.desktop",
+            ".",
+            "arm64"
+        ));
+        assert!(!contains_arch("tools.hostx64.targetarm64", "target", "arm"));
+        assert!(contains_arch("win10sdk_10.0.19041_arm", "_", "arm"));
+    }
+
+    #[test]
+    fn test_is_optional_package() {
+        assert!(is_optional_package(
+            "Microsoft.VC.14.44.17.14.Tools.HostX64.TargetX64.Res",
+            Some("de-DE")
+        ));
+        assert!(is_optional_package(
+            "Microsoft.VC.14.44.17.14.CRT.Source",
+            None
+        ));
+        assert!(!is_optional_package(
+            "Microsoft.VC.14.44.17.14.Tools.HostX64.TargetX64.Res",
+            Some("en-US")
+        ));
+        assert!(!is_optional_package(
+            "Microsoft.VC.14.44.17.14.CRT.Headers",
+            Some("neutral")
+        ));
+        assert!(!is_optional_package("Win11SDK_10.0.26100", None));
+    }
+
+    #[test]
+    fn test_sdk_version_for_build() {
+        use crate::downloader::resolution::resolve_sdk_for_build;
+        use crate::downloader::MatchKind;
+
+        let manifest = create_test_manifest();
+        assert_eq!(
+            manifest.sdk_version_for_build(22631).as_deref(),
+            Some("10.0.22621.0")
+        );
+        assert_eq!(
+            manifest.sdk_version_for_build(26100).as_deref(),
+            Some("10.0.26100.0")
+        );
+        assert_eq!(manifest.sdk_version_for_build(19045), None);
+
+        let report = resolve_sdk_for_build(&manifest, 26200).unwrap();
+        assert_eq!(report.resolved, "10.0.26100.0");
+        assert_eq!(report.kind, MatchKind::HostBuild);
+        assert_eq!(
+            report.to_string(),
+            "Windows SDK match-host -> 10.0.26100.0 (host build match)"
+        );
+    }
+
     #[test]
     fn test_find_msvc_packages() {
         let manifest = create_test_manifest();
@@ -1110,6 +1740,67 @@ mod tests {
             .any(|p| p.id == "Microsoft.VC.14.44.CRT.x64.Desktop.Spectre"));
     }
 
+    #[test]
+    fn test_find_msvc_packages_app_platforms() {
+        let mut manifest = create_test_manifest();
+        let desktop = manifest
+            .packages
+            .iter()
+            .find(|p| p.id == "Microsoft.VC.14.44.CRT.x64.Desktop")
+            .unwrap()
+            .clone();
+        for id in [
+            "Microsoft.VC.14.44.CRT.x64.Store",
+            "Microsoft.VC.14.44.CRT.x64.OneCore.Desktop",
+        ] {
+            manifest.packages.push(VsPackage {
+                id: id.to_string(),
+                ..desktop.clone()
+            });
+        }
+        let ids = |components: &HashSet<MsvcComponent>, platforms: &[AppPlatform]| {
+            manifest
+                .find_msvc_packages_for_platforms(
+                    "14.44",
+                    "x64",
+                    "x64",
+                    components,
+                    &platforms.iter().copied().collect(),
+                    &[],
+                )
+                .into_iter()
+                .map(|p| p.id)
+                .filter(|id| id.starts_with("Microsoft.VC.14.44.CRT.x64"))
+                .collect::<Vec<_>>()
+        };
+
+        // Desktop only by default
+        let none = HashSet::new();
+        assert_eq!(
+            manifest
+                .find_msvc_packages("14.44", "x64", "x64", &none, &[])
+                .iter()
+                .filter(|p| p.id.starts_with("Microsoft.VC.14.44.CRT.x64."))
+                .map(|p| p.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Microsoft.VC.14.44.CRT.x64.Desktop"]
+        );
+        assert_eq!(
+            ids(&none, &[AppPlatform::OneCore]),
+            vec!["Microsoft.VC.14.44.CRT.x64.OneCore.Desktop"]
+        );
+        assert_eq!(
+            ids(
+                &HashSet::from([MsvcComponent::Uwp]),
+                &[AppPlatform::Desktop]
+            ),
+            vec![
+                "Microsoft.VC.14.44.CRT.x64.Desktop",
+                "Microsoft.VC.14.44.CRT.x64.Store"
+            ]
+        );
+    }
+
     #[test]
     fn test_find_msvc_packages_exclude_patterns() {
         let manifest = create_test_manifest();
@@ -1212,6 +1903,24 @@ mod tests {
             .any(|p| p.id == "Microsoft.VC.14.44.Redist.ARM64"));
     }
 
+    #[test]
+    fn test_msvc_host_targets() {
+        let manifest = create_test_manifest();
+
+        let pairs = manifest.msvc_host_targets("14.44");
+        assert!(pairs.contains(&("x64".to_string(), "x64".to_string())));
+        assert!(pairs.contains(&("x64".to_string(), "arm64".to_string())));
+        assert!(!pairs.contains(&("arm64".to_string(), "arm64".to_string())));
+
+        assert!(manifest.msvc_host_targets("14.99").is_empty());
+
+        assert_eq!(
+            manifest.msvc_target_archs("14.44"),
+            vec![Architecture::Arm64, Architecture::X64, Architecture::X86]
+        );
+        assert!(manifest.msvc_target_archs("14.99").is_empty());
+    }
+
     #[test]
     fn test_find_msvc_packages_arm64_target() {
         let manifest = create_test_manifest();
@@ -1309,4 +2018,116 @@ mod tests {
             .iter()
             .any(|p| p.id == "Win11SDK_10.0.26100_Headers"));
     }
+
+    #[test]
+    fn test_from_slice_roundtrip() {
+        let manifest = create_test_manifest();
+        let bytes = serde_json::to_vec(&manifest).unwrap();
+
+        let parsed = VsManifest::from_slice(&bytes).unwrap();
+        assert_eq!(parsed.manifest_version, manifest.manifest_version);
+        assert_eq!(parsed.packages.len(), manifest.packages.len());
+        assert_eq!(
+            parsed.get_latest_msvc_version(),
+            manifest.get_latest_msvc_version()
+        );
+    }
+
+    #[test]
+    fn test_from_slice_invalid_json() {
+        assert!(VsManifest::from_slice(b"not json").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_from_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("VisualStudio.vsman");
+        let manifest = create_test_manifest();
+        std::fs::write(&path, serde_json::to_vec(&manifest).unwrap()).unwrap();
+
+        let parsed = VsManifest::from_file(&path).await.unwrap();
+        assert_eq!(parsed.packages.len(), manifest.packages.len());
+        assert_eq!(
+            parsed.get_latest_sdk_version(),
+            manifest.get_latest_sdk_version()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_from_file_missing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let result = VsManifest::from_file(&temp_dir.path().join("missing.vsman")).await;
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_channel_manifest_info() {
+        let json = br#"{
+            "manifestVersion": "1.1",
+            "info": {
+                "productDisplayVersion": "17.12.3",
+                "buildVersion": "17.12.35527.113",
+                "productSemanticVersion": "17.12.3+35527.113",
+                "productLineVersion": "2022"
+            },
+            "channelItems": []
+        }"#;
+
+        let channel = ChannelManifest::from_slice(json).unwrap();
+        let info = channel.channel_info();
+        assert_eq!(info.product_display_version.as_deref(), Some("17.12.3"));
+        assert_eq!(info.build_version.as_deref(), Some("17.12.35527.113"));
+        assert_eq!(
+            info.product_semantic_version.as_deref(),
+            Some("17.12.3+35527.113")
+        );
+        assert_eq!(info.product_line_version.as_deref(), Some("2022"));
+        assert!(info.release_date.is_none());
+    }
+
+    #[test]
+    fn test_channel_manifest_info_missing() {
+        let json = br#"{"manifestVersion": "1.1", "channelItems": []}"#;
+        let info = ChannelManifest::from_slice(json).unwrap().channel_info();
+        assert!(info.product_display_version.is_none());
+        assert!(info.build_version.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_load_cached() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let channel = br#"{
+            "manifestVersion": "1.1",
+            "info": {"productDisplayVersion": "17.12.3"},
+            "channelItems": [{
+                "id": "Microsoft.VisualStudio.Manifests.VisualStudio",
+                "type": "Manifest",
+                "payloads": [{
+                    "fileName": "VisualStudio.vsman",
+                    "url": "https://example.invalid/VisualStudio.vsman"
+                }]
+            }]
+        }"#;
+        std::fs::write(temp_dir.path().join("channel.json"), channel).unwrap();
+        std::fs::create_dir_all(temp_dir.path().join("vsman")).unwrap();
+        let manifest = create_test_manifest();
+        std::fs::write(
+            temp_dir.path().join("vsman").join("VisualStudio.vsman"),
+            serde_json::to_vec(&manifest).unwrap(),
+        )
+        .unwrap();
+
+        let (parsed, info) = VsManifest::load_cached_with_channel_info(temp_dir.path())
+            .await
+            .unwrap();
+        assert_eq!(parsed.packages.len(), manifest.packages.len());
+        assert_eq!(info.product_display_version.as_deref(), Some("17.12.3"));
+    }
+
+    #[tokio::test]
+    async fn test_load_cached_missing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let result = VsManifest::load_cached(temp_dir.path()).await;
+        assert!(matches!(result, Err(MsvcKitError::Offline(_))));
+    }
 }
@@ -1,25 +1,45 @@
 //! Download functionality for MSVC and Windows SDK components
 
+mod auth;
 pub mod cache;
+mod channel;
+mod checksums;
+mod chunked;
 mod common;
+mod endpoints;
+mod estimate;
+mod events;
 pub mod hash;
+mod headers;
 pub mod http;
 mod index;
+mod layout;
 mod manifest;
 mod msvc;
 pub mod progress;
+mod quarantine;
+mod rate;
+mod resolution;
+mod resume;
+mod reuse;
 mod sdk;
+mod target_dir;
 mod traits;
+mod vsconfig;
 
 #[cfg(test)]
 mod common_tests;
 
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use crate::error::Result;
-use crate::installer::InstallInfo;
-use crate::version::Architecture;
+use crate::bundle::BundleLayout;
+use crate::env::detect_host_arch;
+use crate::error::{OptionsError, Result};
+use crate::installer::{run_post_install_hooks, BoxedPostInstallHook, CleanupPolicy, InstallInfo};
+use crate::version::{Architecture, HostArchSelection};
 
 /// Optional MSVC component categories that can be included in downloads.
 ///
@@ -71,6 +91,16 @@ pub enum MsvcComponent {
     Custom(String),
 }
 
+impl MsvcComponent {
+    /// Lowercase fragment identifying this component's package IDs
+    pub(crate) fn id_fragment(&self) -> String {
+        match self {
+            MsvcComponent::Custom(pattern) => pattern.to_lowercase(),
+            other => format!(".{}", other),
+        }
+    }
+}
+
 impl std::fmt::Display for MsvcComponent {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -101,8 +131,8 @@ impl std::str::FromStr for MsvcComponent {
             "modules" => Ok(MsvcComponent::Modules),
             "redist" | "redistributable" => Ok(MsvcComponent::Redist),
             other => {
-                if let Some(pattern) = other.strip_prefix("custom:") {
-                    Ok(MsvcComponent::Custom(pattern.to_string()))
+                if other.starts_with("custom:") {
+                    Ok(MsvcComponent::Custom(s["custom:".len()..].to_string()))
                 } else {
                     Err(format!(
                         "Unknown component '{}'. Valid: spectre, mfc, atl, asan, uwp, cli, modules, redist, custom:<pattern>",
@@ -114,31 +144,179 @@ impl std::str::FromStr for MsvcComponent {
     }
 }
 
+/// Serialized as its string form (`"spectre"`, `"custom:<pattern>"`, ...)
+impl Serialize for MsvcComponent {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for MsvcComponent {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Application platforms the CRT libraries are downloaded for.
+///
+/// The CRT ships separate libraries for classic desktop applications, Store
+/// (UWP) applications and OneCore (`onecore.lib`, Windows IoT/Nano Server)
+/// targets. Only [`AppPlatform::Desktop`] is downloaded by default.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::{AppPlatform, DownloadOptions};
+///
+/// let options = DownloadOptions::builder()
+///     .app_platform(AppPlatform::OneCore)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Copy, Hash, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppPlatform {
+    /// Classic desktop applications (`CRT.<arch>.Desktop`)
+    Desktop,
+    /// Store/UWP applications (`CRT.<arch>.Store`)
+    #[serde(alias = "uwp")]
+    Store,
+    /// OneCore targets (`CRT.<arch>.OneCore.Desktop`)
+    OneCore,
+}
+
+impl AppPlatform {
+    /// Platforms downloaded when none are configured
+    pub fn defaults() -> HashSet<AppPlatform> {
+        HashSet::from([AppPlatform::Desktop])
+    }
+
+    /// Platform a lowercase package ID is specific to, if any
+    pub(crate) fn of_package(id: &str) -> Option<Self> {
+        let segments: Vec<&str> = id.split('.').collect();
+        if segments.contains(&"onecore") {
+            Some(AppPlatform::OneCore)
+        } else if segments.contains(&"store") || segments.contains(&"uwp") {
+            Some(AppPlatform::Store)
+        } else if segments.contains(&"desktop") {
+            Some(AppPlatform::Desktop)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for AppPlatform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppPlatform::Desktop => write!(f, "desktop"),
+            AppPlatform::Store => write!(f, "store"),
+            AppPlatform::OneCore => write!(f, "onecore"),
+        }
+    }
+}
+
+impl std::str::FromStr for AppPlatform {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "desktop" => Ok(AppPlatform::Desktop),
+            "store" | "uwp" => Ok(AppPlatform::Store),
+            "onecore" => Ok(AppPlatform::OneCore),
+            _ => Err(format!(
+                "Unknown app platform '{}'. Valid: desktop, store, onecore",
+                s
+            )),
+        }
+    }
+}
+
+pub use auth::{AuthProvider, BoxedAuthProvider, StaticTokenAuth};
+#[cfg_attr(not(feature = "unstable"), doc(hidden))]
+pub use channel::{
+    embedded_known_good, load_known_good, refresh_known_good, select_stable, KnownGoodCombo,
+    KNOWN_GOOD_FILE, KNOWN_GOOD_URL,
+};
+pub use channel::{MsvcVersionSelector, ReleaseChannel};
+pub use checksums::{
+    verify_checksums_file, write_checksums_file, ChecksumReport, CHECKSUMS_FILE_NAME,
+};
+pub(crate) use chunked::is_part_file;
+pub use chunked::ChunkedDownloads;
+// Low-level building blocks outside the stable surface (see `crate::prelude`)
+#[cfg_attr(not(feature = "unstable"), doc(hidden))]
 pub use common::CommonDownloader;
-pub use hash::{compute_file_hash, compute_hash, hashes_match};
+pub use common::{DownloadReport, HashMismatchEvent, OptionalFailure, ThrottleEvent};
+pub use endpoints::EndpointStats;
+pub use estimate::{
+    SizeEstimate, SizeEstimator, SizeSource, DEFAULT_EXTRACTION_RATIOS, FALLBACK_EXTRACTION_RATIO,
+};
+pub use events::{download_msvc_stream, download_sdk_stream, DownloadEvent, DownloadEventStream};
+pub use hash::{compute_file_hash, compute_hash, hashes_match, is_hash_exempt, VerifyMode};
 pub use http::{
-    create_http_client, create_http_client_with_config, tls_backend_name, HttpClientConfig,
+    branded_user_agent, create_http_client, create_http_client_with_config, load_root_certificates,
+    set_default_root_certificates, tls_backend_name, HttpClientConfig,
+};
+pub use index::{
+    DownloadIndex, DownloadStatus, ExportedEntry, IndexEntry, IndexExport, IndexImportReport,
+    IndexedPackage, PayloadProvenance, RunProgress, INDEX_EXPORT_FORMAT, INDEX_EXPORT_VERSION,
 };
-pub use index::{DownloadIndex, DownloadStatus, IndexEntry};
+pub use layout::{VsLayout, LAYOUT_CATALOG_FILE};
+pub use manifest::{fetch_channel_info, is_optional_package, ChannelInfo};
+#[cfg_attr(not(feature = "unstable"), doc(hidden))]
 pub use manifest::{ChannelManifest, Package, PackagePayload, VsManifest};
+#[cfg_attr(not(feature = "unstable"), doc(hidden))]
 pub use msvc::MsvcDownloader;
 pub use progress::{
     BoxedProgressHandler, IndicatifProgressHandler, NoopProgressHandler, ProgressHandler,
+    RecordedEvent, RecordingProgressHandler,
 };
+pub use quarantine::{list_quarantine, QuarantineRecord, QUARANTINE_DIR_NAME, RECORDED_HEADERS};
+pub use rate::parse_rate;
+pub use resolution::{
+    host_windows_build, is_match_host, BoxedVersionResolver, DefaultVersionResolver, MatchKind,
+    ResolutionReport, VersionResolver, MATCH_HOST,
+};
+pub(crate) use resume::format_duration;
+pub use resume::{resume_summary, ResumeSummary};
+pub use reuse::{InstallStamp, INSTALL_STAMP_SUFFIX};
+#[cfg_attr(not(feature = "unstable"), doc(hidden))]
 pub use sdk::SdkDownloader;
+pub use target_dir::{check_target_dir, check_target_path, RESERVED_NAMES};
 pub use traits::{
     BoxedCacheManager, CacheManager, ComponentDownloader, ComponentType, FileSystemCacheManager,
 };
+pub use vsconfig::{import_vsconfig, parse_vsconfig, VsConfigImport};
 
 /// Options for downloading MSVC/SDK components
-#[derive(Clone)]
+///
+/// Serializable for config-file driven or remote invocation. The HTTP client,
+/// handlers, providers and hooks are runtime objects and are skipped; fields
+/// missing from the input take their [`Default`] values. Timeouts are in
+/// seconds.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DownloadOptions {
     /// MSVC version to download (None = latest)
     pub msvc_version: Option<String>,
 
-    /// Windows SDK version to download (None = latest)
+    /// Windows SDK version to download (None = latest, [`MATCH_HOST`] = the
+    /// SDK for the running Windows build)
     pub sdk_version: Option<String>,
 
+    /// Which versions to pick when `msvc_version` / `sdk_version` are not set
+    ///
+    /// [`ReleaseChannel::Stable`] selects the newest MSVC / Windows SDK pair
+    /// tested by the project CI instead of the newest manifest entries.
+    pub channel: ReleaseChannel,
+
     /// Target directory for installation
     pub target_dir: PathBuf,
 
@@ -148,24 +326,74 @@ pub struct DownloadOptions {
     /// Host architecture (for cross-compilation)
     pub host_arch: Option<Architecture>,
 
-    /// Whether to verify file hashes
-    pub verify_hashes: bool,
+    /// How payload hashes are checked against the manifest
+    pub verify_mode: VerifyMode,
+
+    /// URL patterns exempt from hash verification (case-insensitive substring match)
+    ///
+    /// For proxies known to re-sign or rewrite payloads. Matching payloads are
+    /// downloaded as with [`VerifyMode::Off`], see [`is_hash_exempt`].
+    pub hash_exemptions: Vec<String>,
 
     /// Number of parallel downloads
     pub parallel_downloads: usize,
 
     /// Custom HTTP client (None = create default)
+    #[serde(skip)]
     pub http_client: Option<reqwest::Client>,
 
+    /// User agent of the default HTTP client (None = [`USER_AGENT`](crate::constants::USER_AGENT))
+    ///
+    /// Tools embedding msvc-kit can brand their requests with
+    /// [`DownloadOptionsBuilder::branding`]. Ignored when `http_client` is set.
+    pub user_agent: Option<String>,
+
     /// Custom progress handler (None = use default indicatif)
+    #[serde(skip)]
     pub progress_handler: Option<BoxedProgressHandler>,
 
     /// Custom cache manager (None = use default file system cache)
+    #[serde(skip)]
     pub cache_manager: Option<BoxedCacheManager>,
 
+    /// Authentication provider for private mirrors (None = no credentials)
+    ///
+    /// Applied to manifest and payload requests; the provider decides per URL
+    /// which headers (if any) to send.
+    #[serde(skip)]
+    pub auth_provider: Option<BoxedAuthProvider>,
+
+    /// Version selection policy (None = [`DefaultVersionResolver`])
+    ///
+    /// Lets embedders restrict or redirect the versions installed, e.g. to
+    /// those approved by internal QA. See [`VersionResolver`].
+    #[serde(skip)]
+    pub version_resolver: Option<BoxedVersionResolver>,
+
     /// Dry-run mode: preview what would be downloaded without actually downloading
     pub dry_run: bool,
 
+    /// Offline mode: never touch the network, use cached manifests and payloads only.
+    ///
+    /// Fails with [`MsvcKitError::OfflinePayloadsMissing`](crate::MsvcKitError::OfflinePayloadsMissing)
+    /// listing every payload that is not already in the download cache.
+    pub offline: bool,
+
+    /// Visual Studio offline layout (`vs_buildtools.exe --layout`) to install from
+    ///
+    /// Packages are resolved from the layout's `Catalog.json` instead of the
+    /// online manifest, and payloads found in the layout are linked or copied
+    /// rather than downloaded. Payloads missing from it are still downloaded
+    /// unless `offline` is set. See [`VsLayout`].
+    pub layout_dir: Option<PathBuf>,
+
+    /// Headers-only mode: download only the packages that provide `include`
+    /// trees (MSVC `*.Headers` packages and the Windows SDK header installers).
+    ///
+    /// Intended for code-intelligence tooling such as clangd or bindgen that
+    /// never compiles or links. The result cannot build binaries.
+    pub headers_only: bool,
+
     /// Additional MSVC components to include (default: empty = standard install).
     ///
     /// By default, the standard toolchain (Tools, CRT, MFC, ATL) is downloaded.
@@ -174,11 +402,128 @@ pub struct DownloadOptions {
     /// See [`MsvcComponent`] for available component categories.
     pub include_components: HashSet<MsvcComponent>,
 
+    /// Application platforms to download CRT libraries for (default: desktop).
+    ///
+    /// Store variants are also downloaded when [`MsvcComponent::Uwp`] is
+    /// included. See [`AppPlatform`].
+    pub app_platforms: HashSet<AppPlatform>,
+
     /// Package ID patterns to exclude (case-insensitive substring match).
     ///
     /// Any package whose ID contains one of these patterns will be excluded
     /// from the download, providing fine-grained control over package selection.
     pub exclude_patterns: Vec<String>,
+
+    /// Package ID patterns to allow exclusively (empty = no restriction).
+    ///
+    /// When set, only MSVC packages whose ID contains one of these patterns
+    /// (case-insensitive substring match) are downloaded. Fails with
+    /// [`OptionsError::BrokenSelection`] if no compiler tools package is left,
+    /// unless `allow_broken_selection` is set.
+    pub only_patterns: Vec<String>,
+
+    /// Download an `only_patterns` selection even if it drops the compiler tools
+    pub allow_broken_selection: bool,
+
+    /// Windows SDK installer name patterns to allow exclusively (empty = all).
+    ///
+    /// When set, only SDK installers (`.msi`) whose file name contains one of
+    /// these patterns (case-insensitive), e.g. `universal crt`, are downloaded,
+    /// together with the CAB files they reference.
+    pub sdk_installer_patterns: Vec<String>,
+
+    /// Glob patterns limiting which files are extracted (empty = all files).
+    ///
+    /// Matched against paths relative to the install directory, see
+    /// [`ExtractFilter`](crate::installer::ExtractFilter). In headers-only mode
+    /// this defaults to `**/include/**`.
+    pub extract_filters: Vec<String>,
+
+    /// What to do with downloaded payloads once they are extracted
+    ///
+    /// Applied by the `extract_and_finalize_*` functions; only payloads that
+    /// finished downloading, passed hash verification and were extracted are
+    /// deleted. See [`CleanupPolicy`](crate::installer::CleanupPolicy).
+    pub auto_cleanup: CleanupPolicy,
+
+    /// Hooks run by [`run_post_install_hooks`](Self::run_post_install_hooks)
+    /// once MSVC and the Windows SDK have been extracted and finalized.
+    #[serde(skip)]
+    pub post_install_hooks: Vec<BoxedPostInstallHook>,
+
+    /// Time limit for downloading the payloads of one component (None = no limit)
+    ///
+    /// On expiry, transfers in flight are cancelled and
+    /// [`MsvcKitError::DownloadTimeout`](crate::MsvcKitError::DownloadTimeout)
+    /// is returned with the partial report.
+    #[serde(with = "optional_secs")]
+    pub overall_timeout: Option<Duration>,
+
+    /// Time limit for a single payload transfer, retries included (None = no limit)
+    #[serde(with = "optional_secs")]
+    pub per_file_timeout: Option<Duration>,
+
+    /// Total download bandwidth in bytes per second (None = unlimited)
+    ///
+    /// Shared by all parallel transfers, e.g. to warm the cache in the
+    /// background without saturating the network.
+    pub max_download_rate: Option<u64>,
+
+    /// Download large payloads as parallel byte ranges (None = one connection per payload)
+    ///
+    /// Helps on high-latency links where a single connection cannot use the
+    /// available bandwidth. Servers that ignore range requests fall back to a
+    /// single connection; the merged file is hash-verified as usual.
+    pub chunked_downloads: Option<ChunkedDownloads>,
+
+    /// Base URLs serving the same payload paths as the manifest URLs
+    ///
+    /// A payload that keeps failing on its manifest URL is retried on each
+    /// of these in turn, e.g. another CDN endpoint or an internal mirror
+    /// (`https://mirror.corp/vs/`). Endpoints failing repeatedly are tried
+    /// last for the rest of the run; see [`DownloadReport::endpoints`].
+    pub alternate_endpoints: Vec<String>,
+
+    /// Keep going when an optional package fails to download
+    ///
+    /// Packages classified as optional by
+    /// [`is_optional_package`](manifest::is_optional_package), such as
+    /// localized satellites, are then left out of the install instead of
+    /// failing it. The failures are listed in [`DownloadReport::failed_optional`]
+    /// and as [`WarningCode::OptionalPackageFailed`](crate::WarningCode::OptionalPackageFailed)
+    /// warnings; the next run retries them.
+    pub continue_on_optional_failure: bool,
+
+    /// Retry payloads failing hash verification over plain `http`
+    ///
+    /// In [`VerifyMode::Enforce`], a payload whose copies keep failing
+    /// verification is downloaded again from the mirrors the manifest lists
+    /// for it. With this set, its `https` URL is then also tried as `http`,
+    /// which is often served by another CDN edge. The hash is still checked;
+    /// credentials from the [`AuthProvider`] are never sent over `http`.
+    pub allow_http_fallback: bool,
+}
+
+/// Serde representation of an optional [`Duration`] as whole seconds
+mod optional_secs {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Duration>,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match value {
+            Some(duration) => serializer.serialize_some(&duration.as_secs()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+    }
 }
 
 impl std::fmt::Debug for DownloadOptions {
@@ -189,14 +534,39 @@ impl std::fmt::Debug for DownloadOptions {
             .field("target_dir", &self.target_dir)
             .field("arch", &self.arch)
             .field("host_arch", &self.host_arch)
-            .field("verify_hashes", &self.verify_hashes)
+            .field("verify_mode", &self.verify_mode)
+            .field("hash_exemptions", &self.hash_exemptions)
             .field("parallel_downloads", &self.parallel_downloads)
             .field("http_client", &self.http_client.is_some())
+            .field("user_agent", &self.user_agent)
             .field("progress_handler", &self.progress_handler.is_some())
             .field("cache_manager", &self.cache_manager.is_some())
+            .field("auth_provider", &self.auth_provider.is_some())
+            .field("version_resolver", &self.version_resolver.is_some())
             .field("dry_run", &self.dry_run)
+            .field("offline", &self.offline)
+            .field("layout_dir", &self.layout_dir)
+            .field("channel", &self.channel)
+            .field("headers_only", &self.headers_only)
             .field("include_components", &self.include_components)
+            .field("app_platforms", &self.app_platforms)
             .field("exclude_patterns", &self.exclude_patterns)
+            .field("only_patterns", &self.only_patterns)
+            .field("allow_broken_selection", &self.allow_broken_selection)
+            .field("sdk_installer_patterns", &self.sdk_installer_patterns)
+            .field("extract_filters", &self.extract_filters)
+            .field("auto_cleanup", &self.auto_cleanup)
+            .field("post_install_hooks", &self.post_install_hooks.len())
+            .field("overall_timeout", &self.overall_timeout)
+            .field("per_file_timeout", &self.per_file_timeout)
+            .field("max_download_rate", &self.max_download_rate)
+            .field("chunked_downloads", &self.chunked_downloads)
+            .field("alternate_endpoints", &self.alternate_endpoints)
+            .field(
+                "continue_on_optional_failure",
+                &self.continue_on_optional_failure,
+            )
+            .field("allow_http_fallback", &self.allow_http_fallback)
             .finish()
     }
 }
@@ -216,16 +586,69 @@ impl Default for DownloadOptions {
             .and_then(|s| s.parse().ok())
             .unwrap_or(DEFAULT_PARALLEL_DOWNLOADS);
 
-        let verify_hashes = std::env::var("MSVC_KIT_VERIFY_HASHES")
+        // MSVC_KIT_VERIFY_MODE takes precedence over the boolean MSVC_KIT_VERIFY_HASHES
+        let verify_mode = std::env::var("MSVC_KIT_VERIFY_MODE")
             .ok()
-            .map(|s| !matches!(s.to_lowercase().as_str(), "0" | "false" | "no"))
-            .unwrap_or(true);
+            .and_then(|s| s.parse().ok())
+            .or_else(|| {
+                std::env::var("MSVC_KIT_VERIFY_HASHES")
+                    .ok()
+                    .map(|s| (!matches!(s.to_lowercase().as_str(), "0" | "false" | "no")).into())
+            })
+            .unwrap_or_default();
+
+        // Parse MSVC_KIT_HASH_EXEMPTIONS env var (comma-separated)
+        let hash_exemptions = std::env::var("MSVC_KIT_HASH_EXEMPTIONS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Parse MSVC_KIT_ALTERNATE_ENDPOINTS env var (comma-separated)
+        let alternate_endpoints = std::env::var("MSVC_KIT_ALTERNATE_ENDPOINTS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
 
         let dry_run = std::env::var("MSVC_KIT_DRY_RUN")
             .ok()
             .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
             .unwrap_or(false);
 
+        let offline = std::env::var("MSVC_KIT_OFFLINE")
+            .ok()
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let allow_http_fallback = std::env::var("MSVC_KIT_ALLOW_HTTP_FALLBACK")
+            .ok()
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
+        let channel = std::env::var("MSVC_KIT_CHANNEL")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+
+        let layout_dir = std::env::var("MSVC_KIT_LAYOUT_DIR")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .map(PathBuf::from);
+
+        let headers_only = std::env::var("MSVC_KIT_HEADERS_ONLY")
+            .ok()
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false);
+
         // Parse MSVC_KIT_INCLUDE_COMPONENTS env var (comma-separated)
         let include_components = std::env::var("MSVC_KIT_INCLUDE_COMPONENTS")
             .ok()
@@ -236,6 +659,33 @@ impl Default for DownloadOptions {
             })
             .unwrap_or_default();
 
+        // Parse MSVC_KIT_APP_PLATFORMS env var (comma-separated)
+        let app_platforms = std::env::var("MSVC_KIT_APP_PLATFORMS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .filter_map(|p| p.trim().parse::<AppPlatform>().ok())
+                    .collect::<HashSet<_>>()
+            })
+            .filter(|platforms| !platforms.is_empty())
+            .unwrap_or_else(AppPlatform::defaults);
+
+        let auto_cleanup = std::env::var("MSVC_KIT_AUTO_CLEANUP")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or_default();
+
+        // Parse MSVC_KIT_EXTRACT_FILTERS env var (comma-separated)
+        let extract_filters = std::env::var("MSVC_KIT_EXTRACT_FILTERS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
         // Parse MSVC_KIT_EXCLUDE_PATTERNS env var (comma-separated)
         let exclude_patterns = std::env::var("MSVC_KIT_EXCLUDE_PATTERNS")
             .ok()
@@ -247,20 +697,90 @@ impl Default for DownloadOptions {
             })
             .unwrap_or_default();
 
+        // Timeouts in seconds
+        // Parse MSVC_KIT_ONLY_PATTERNS env var (comma-separated)
+        let only_patterns = std::env::var("MSVC_KIT_ONLY_PATTERNS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Parse MSVC_KIT_SDK_INSTALLER_PATTERNS env var (comma-separated)
+        let sdk_installer_patterns = std::env::var("MSVC_KIT_SDK_INSTALLER_PATTERNS")
+            .ok()
+            .map(|s| {
+                s.split(',')
+                    .map(|p| p.trim().to_string())
+                    .filter(|p| !p.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let overall_timeout = std::env::var("MSVC_KIT_OVERALL_TIMEOUT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs);
+        let per_file_timeout = std::env::var("MSVC_KIT_FILE_TIMEOUT")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs);
+
+        // Bandwidth limit, e.g. 2M
+        let max_download_rate = std::env::var("MSVC_KIT_MAX_DOWNLOAD_RATE")
+            .ok()
+            .and_then(|s| rate::parse_rate(&s).ok())
+            .filter(|rate| *rate > 0);
+
+        // Range requests per large payload, e.g. 8
+        let chunked_downloads = std::env::var("MSVC_KIT_CONNECTIONS_PER_FILE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .filter(|connections: &usize| *connections > 1)
+            .map(|connections| ChunkedDownloads {
+                connections,
+                ..Default::default()
+            });
+
         Self {
             msvc_version: std::env::var("MSVC_KIT_MSVC_VERSION").ok(),
             sdk_version: std::env::var("MSVC_KIT_SDK_VERSION").ok(),
+            channel,
             target_dir,
             arch: Architecture::host(),
             host_arch: None,
-            verify_hashes,
+            verify_mode,
+            hash_exemptions,
             parallel_downloads,
             http_client: None,
+            user_agent: None,
             progress_handler: None,
             cache_manager: None,
+            auth_provider: None,
+            version_resolver: None,
             dry_run,
+            offline,
+            layout_dir,
+            headers_only,
             include_components,
+            app_platforms,
             exclude_patterns,
+            only_patterns,
+            allow_broken_selection: false,
+            sdk_installer_patterns,
+            extract_filters,
+            auto_cleanup,
+            post_install_hooks: Vec::new(),
+            overall_timeout,
+            per_file_timeout,
+            max_download_rate,
+            chunked_downloads,
+            alternate_endpoints,
+            continue_on_optional_failure: false,
+            allow_http_fallback,
         }
     }
 }
@@ -270,12 +790,134 @@ impl DownloadOptions {
     pub fn builder() -> DownloadOptionsBuilder {
         DownloadOptionsBuilder::default()
     }
+
+    /// How the MSVC version is chosen, from `msvc_version` and `channel`
+    pub fn msvc_selector(&self) -> MsvcVersionSelector {
+        match (&self.msvc_version, self.channel) {
+            (Some(version), _) => MsvcVersionSelector::Version(version.clone()),
+            (None, ReleaseChannel::Stable) => MsvcVersionSelector::Stable,
+            (None, ReleaseChannel::Latest) => MsvcVersionSelector::Latest,
+        }
+    }
+
+    /// The custom HTTP client, or a default one using the configured user agent
+    pub(crate) fn client(&self) -> reqwest::Client {
+        match (&self.http_client, &self.user_agent) {
+            (Some(client), _) => client.clone(),
+            (None, Some(user_agent)) => HttpClientConfig::with_user_agent(user_agent).build(),
+            (None, None) => create_http_client(),
+        }
+    }
+
+    /// Check the options for values that cannot produce a useful download
+    pub fn validate(&self) -> std::result::Result<(), OptionsError> {
+        if self.parallel_downloads == 0 {
+            return Err(OptionsError::ZeroParallelDownloads);
+        }
+        if self.target_dir.as_os_str().is_empty() {
+            return Err(OptionsError::EmptyTargetDir);
+        }
+        check_target_path(&self.target_dir)?;
+        if [self.overall_timeout, self.per_file_timeout].contains(&Some(Duration::ZERO)) {
+            return Err(OptionsError::ZeroTimeout);
+        }
+        if self.max_download_rate == Some(0) {
+            return Err(OptionsError::ZeroDownloadRate);
+        }
+        if self.chunked_downloads.is_some_and(|c| c.connections == 0) {
+            return Err(OptionsError::ZeroConnections);
+        }
+        if let Some(endpoint) = self
+            .alternate_endpoints
+            .iter()
+            .find(|e| !endpoints::is_valid_endpoint(e))
+        {
+            return Err(OptionsError::InvalidEndpoint(endpoint.clone()));
+        }
+        for (component, version) in [("MSVC", &self.msvc_version), ("SDK", &self.sdk_version)] {
+            if version.as_deref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(OptionsError::EmptyVersion {
+                    component: component.to_string(),
+                });
+            }
+        }
+
+        for pattern in &self.exclude_patterns {
+            let pattern = pattern.trim().to_lowercase();
+            if pattern.is_empty() {
+                return Err(OptionsError::EmptyExcludePattern);
+            }
+            for component in &self.include_components {
+                if component.id_fragment().contains(&pattern) {
+                    return Err(OptionsError::ConflictingPattern {
+                        component: component.to_string(),
+                        pattern,
+                    });
+                }
+            }
+        }
+        if self.only_patterns.iter().any(|p| p.trim().is_empty()) {
+            return Err(OptionsError::EmptyOnlyPattern);
+        }
+        if self.extract_filters.iter().any(|p| p.trim().is_empty()) {
+            return Err(OptionsError::EmptyExtractFilter);
+        }
+
+        Ok(())
+    }
+
+    /// Run the registered post-install hooks for a finalized install
+    ///
+    /// Call this after [`extract_and_finalize_msvc`](crate::extract_and_finalize_msvc)
+    /// and [`extract_and_finalize_sdk`](crate::extract_and_finalize_sdk). The
+    /// hooks receive the environment of the install in `target_dir`.
+    pub async fn run_post_install_hooks(
+        &self,
+        msvc_info: &InstallInfo,
+        sdk_info: &InstallInfo,
+    ) -> Result<()> {
+        if self.post_install_hooks.is_empty() {
+            return Ok(());
+        }
+        let env = BundleLayout::from_root_with_versions(
+            &self.target_dir,
+            &msvc_info.version,
+            &sdk_info.version,
+            self.arch,
+            self.host_arch
+                .unwrap_or_else(|| detect_host_arch(&msvc_info.install_path, self.arch).arch),
+        )?
+        .to_msvc_environment();
+        run_post_install_hooks(&self.post_install_hooks, &[msvc_info, sdk_info], &env).await
+    }
+
+    /// Verification mode for a payload URL, taking exemptions into account
+    pub fn verify_mode_for(&self, url: &str) -> VerifyMode {
+        if is_hash_exempt(url, &self.hash_exemptions) {
+            VerifyMode::Off
+        } else {
+            self.verify_mode
+        }
+    }
+
+    /// Extract filters to record on the produced [`InstallInfo`]
+    ///
+    /// Headers-only mode without explicit filters keeps the include trees.
+    pub(crate) fn effective_extract_filters(&self) -> Vec<String> {
+        if self.headers_only && self.extract_filters.is_empty() {
+            vec![headers::HEADERS_EXTRACT_FILTER.to_string()]
+        } else {
+            self.extract_filters.clone()
+        }
+    }
 }
 
 /// Builder for DownloadOptions
 #[derive(Default)]
 pub struct DownloadOptionsBuilder {
     options: DownloadOptions,
+    /// Error of a `target` call, reported by `try_build`
+    target_error: Option<String>,
 }
 
 impl DownloadOptionsBuilder {
@@ -291,6 +933,42 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Set the release channel used for versions that are not pinned
+    pub fn channel(mut self, channel: ReleaseChannel) -> Self {
+        self.options.channel = channel;
+        self
+    }
+
+    /// Choose the MSVC version by selector
+    ///
+    /// [`MsvcVersionSelector::Stable`] also picks the Windows SDK of the tested
+    /// combination unless [`sdk_version`](Self::sdk_version) is set.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use msvc_kit::{DownloadOptions, MsvcVersionSelector};
+    ///
+    /// let options = DownloadOptions::builder()
+    ///     .msvc_selector(MsvcVersionSelector::Stable)
+    ///     .build();
+    /// assert_eq!(options.msvc_selector(), MsvcVersionSelector::Stable);
+    /// ```
+    pub fn msvc_selector(mut self, selector: MsvcVersionSelector) -> Self {
+        match selector {
+            MsvcVersionSelector::Latest => {
+                self.options.msvc_version = None;
+                self.options.channel = ReleaseChannel::Latest;
+            }
+            MsvcVersionSelector::Stable => {
+                self.options.msvc_version = None;
+                self.options.channel = ReleaseChannel::Stable;
+            }
+            MsvcVersionSelector::Version(version) => self.options.msvc_version = Some(version),
+        }
+        self
+    }
+
     /// Set target directory
     pub fn target_dir(mut self, dir: impl Into<PathBuf>) -> Self {
         self.options.target_dir = dir.into();
@@ -303,15 +981,71 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Set target architecture from a Rust target triple
+    ///
+    /// See [`Architecture::from_rust_triple`]. An unsupported triple leaves the
+    /// architecture unchanged and makes
+    /// [`try_build`](DownloadOptionsBuilder::try_build) fail with
+    /// [`OptionsError::UnsupportedTarget`].
+    ///
+    /// ```rust
+    /// use msvc_kit::{Architecture, DownloadOptions};
+    ///
+    /// let options = DownloadOptions::builder()
+    ///     .target("aarch64-pc-windows-msvc")
+    ///     .build();
+    /// assert_eq!(options.arch, Architecture::Arm64);
+    /// ```
+    pub fn target(mut self, triple: &str) -> Self {
+        match Architecture::from_rust_triple(triple) {
+            Ok(arch) => self.options.arch = arch,
+            Err(e) => self.target_error = Some(e),
+        }
+        self
+    }
+
     /// Set host architecture
     pub fn host_arch(mut self, arch: Architecture) -> Self {
         self.options.host_arch = Some(arch);
         self
     }
 
-    /// Set hash verification
+    /// Enable or disable hash verification
+    ///
+    /// Shorthand for [`verify_mode`](Self::verify_mode) with
+    /// [`VerifyMode::Enforce`] or [`VerifyMode::Off`].
     pub fn verify_hashes(mut self, verify: bool) -> Self {
-        self.options.verify_hashes = verify;
+        self.options.verify_mode = verify.into();
+        self
+    }
+
+    /// Set the hash verification mode
+    pub fn verify_mode(mut self, mode: VerifyMode) -> Self {
+        self.options.verify_mode = mode;
+        self
+    }
+
+    /// Exempt payload URLs matching a pattern from hash verification
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use msvc_kit::{DownloadOptions, VerifyMode};
+    ///
+    /// // Report mismatches everywhere, skip checks for the rewriting proxy
+    /// let options = DownloadOptions::builder()
+    ///     .verify_mode(VerifyMode::Warn)
+    ///     .hash_exemption("proxy.corp.example")
+    ///     .build();
+    /// ```
+    pub fn hash_exemption(mut self, pattern: impl Into<String>) -> Self {
+        self.options.hash_exemptions.push(pattern.into());
+        self
+    }
+
+    /// Exempt payload URLs matching any of the given patterns
+    pub fn hash_exemptions(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
+        self.options.hash_exemptions.extend(patterns);
         self
     }
 
@@ -327,6 +1061,19 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Set the user agent of the default HTTP client
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.options.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Brand requests as `{product}/{version} msvc-kit/{crate_version}`
+    ///
+    /// See [`branded_user_agent`].
+    pub fn branding(self, product: &str, version: &str) -> Self {
+        self.user_agent(branded_user_agent(product, version))
+    }
+
     /// Set custom progress handler
     pub fn progress_handler(mut self, handler: BoxedProgressHandler) -> Self {
         self.options.progress_handler = Some(handler);
@@ -339,12 +1086,115 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Set authentication provider for private mirrors
+    pub fn auth_provider(mut self, provider: BoxedAuthProvider) -> Self {
+        self.options.auth_provider = Some(provider);
+        self
+    }
+
+    /// Set the policy selecting MSVC and Windows SDK versions
+    pub fn version_resolver(mut self, resolver: BoxedVersionResolver) -> Self {
+        self.options.version_resolver = Some(resolver);
+        self
+    }
+
     /// Enable dry-run mode (preview without downloading)
     pub fn dry_run(mut self, dry_run: bool) -> Self {
         self.options.dry_run = dry_run;
         self
     }
 
+    /// Enable offline mode (cached manifests and payloads only, no network)
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.options.offline = offline;
+        self
+    }
+
+    /// Install from a Visual Studio offline layout directory
+    pub fn layout_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.layout_dir = Some(dir.into());
+        self
+    }
+
+    /// Limit the time spent downloading the payloads of one component
+    pub fn overall_timeout(mut self, timeout: Duration) -> Self {
+        self.options.overall_timeout = Some(timeout);
+        self
+    }
+
+    /// Limit the time spent on a single payload transfer
+    pub fn per_file_timeout(mut self, timeout: Duration) -> Self {
+        self.options.per_file_timeout = Some(timeout);
+        self
+    }
+
+    /// Limit the total download bandwidth, in bytes per second
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use msvc_kit::downloader::parse_rate;
+    /// use msvc_kit::DownloadOptions;
+    ///
+    /// let options = DownloadOptions::builder()
+    ///     .max_download_rate(parse_rate("2M").unwrap())
+    ///     .build();
+    /// assert_eq!(options.max_download_rate, Some(2 * 1024 * 1024));
+    /// ```
+    pub fn max_download_rate(mut self, bytes_per_sec: u64) -> Self {
+        self.options.max_download_rate = Some(bytes_per_sec);
+        self
+    }
+
+    /// Download large payloads as parallel byte ranges
+    pub fn chunked_downloads(mut self, chunked: ChunkedDownloads) -> Self {
+        self.options.chunked_downloads = Some(chunked);
+        self
+    }
+
+    /// Add an alternate base URL for payload downloads
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use msvc_kit::DownloadOptions;
+    ///
+    /// // Fall back to the internal mirror when the Microsoft CDN fails
+    /// let options = DownloadOptions::builder()
+    ///     .alternate_endpoint("https://mirror.corp.example/vs/")
+    ///     .try_build()
+    ///     .unwrap();
+    /// assert_eq!(options.alternate_endpoints.len(), 1);
+    /// ```
+    pub fn alternate_endpoint(mut self, base: impl Into<String>) -> Self {
+        self.options.alternate_endpoints.push(base.into());
+        self
+    }
+
+    /// Add several alternate base URLs for payload downloads
+    pub fn alternate_endpoints(mut self, bases: impl IntoIterator<Item = String>) -> Self {
+        self.options.alternate_endpoints.extend(bases);
+        self
+    }
+
+    /// Skip optional packages that fail to download instead of failing the install
+    pub fn continue_on_optional_failure(mut self, enabled: bool) -> Self {
+        self.options.continue_on_optional_failure = enabled;
+        self
+    }
+
+    /// Retry payloads failing hash verification over plain `http`
+    pub fn allow_http_fallback(mut self, enabled: bool) -> Self {
+        self.options.allow_http_fallback = enabled;
+        self
+    }
+
+    /// Enable headers-only mode (include trees only, no tools or libraries)
+    pub fn headers_only(mut self, headers_only: bool) -> Self {
+        self.options.headers_only = headers_only;
+        self
+    }
+
     /// Include an optional MSVC component category.
     ///
     /// Components like Spectre-mitigated libraries are excluded by default.
@@ -383,6 +1233,28 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Also download the CRT libraries for an application platform.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use msvc_kit::{AppPlatform, DownloadOptions};
+    ///
+    /// let options = DownloadOptions::builder()
+    ///     .app_platform(AppPlatform::Store)
+    ///     .build();
+    /// ```
+    pub fn app_platform(mut self, platform: AppPlatform) -> Self {
+        self.options.app_platforms.insert(platform);
+        self
+    }
+
+    /// Replace the application platforms to download CRT libraries for.
+    pub fn app_platforms(mut self, platforms: impl IntoIterator<Item = AppPlatform>) -> Self {
+        self.options.app_platforms = platforms.into_iter().collect();
+        self
+    }
+
     /// Exclude packages matching a pattern (case-insensitive substring match).
     ///
     /// Any package whose ID contains the pattern will be excluded from download.
@@ -402,14 +1274,153 @@ impl DownloadOptionsBuilder {
         self
     }
 
+    /// Exclude packages matching any of the given patterns
+    pub fn exclude_patterns(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
+        self.options.exclude_patterns.extend(patterns);
+        self
+    }
+
+    /// Only download packages matching a pattern (case-insensitive substring match).
+    ///
+    /// Can be called multiple times; packages matching any pattern are kept.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use msvc_kit::DownloadOptions;
+    ///
+    /// let options = DownloadOptions::builder()
+    ///     .only_pattern(".tools.hostx64.targetx64")
+    ///     .only_pattern(".crt.headers")
+    ///     .build();
+    /// ```
+    pub fn only_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.options.only_patterns.push(pattern.into());
+        self
+    }
+
+    /// Only download packages matching any of the given patterns
+    pub fn only_patterns(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
+        self.options.only_patterns.extend(patterns);
+        self
+    }
+
+    /// Download an `only_patterns` selection that drops the compiler tools
+    pub fn allow_broken_selection(mut self, allow: bool) -> Self {
+        self.options.allow_broken_selection = allow;
+        self
+    }
+
+    /// Only download Windows SDK installers whose name contains `pattern`
+    /// (can be called multiple times)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use msvc_kit::DownloadOptions;
+    ///
+    /// // Just the Universal CRT headers and libraries
+    /// let options = DownloadOptions::builder()
+    ///     .sdk_installer_pattern("universal crt")
+    ///     .build();
+    /// ```
+    pub fn sdk_installer_pattern(mut self, pattern: impl Into<String>) -> Self {
+        self.options.sdk_installer_patterns.push(pattern.into());
+        self
+    }
+
+    /// Only extract files matching a glob pattern (can be called multiple times)
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use msvc_kit::DownloadOptions;
+    ///
+    /// let options = DownloadOptions::builder()
+    ///     .extract_filter("**/include/**")
+    ///     .extract_filter("VC/Tools/MSVC/*/lib/x64/**")
+    ///     .build();
+    /// ```
+    pub fn extract_filter(mut self, pattern: impl Into<String>) -> Self {
+        self.options.extract_filters.push(pattern.into());
+        self
+    }
+
+    /// Register a hook to run after installation
+    ///
+    /// See [`PostInstallHook`](crate::installer::PostInstallHook).
+    pub fn post_install_hook(mut self, hook: BoxedPostInstallHook) -> Self {
+        self.options.post_install_hooks.push(hook);
+        self
+    }
+
+    /// Only extract files matching any of the given glob patterns
+    pub fn extract_filters(mut self, patterns: impl IntoIterator<Item = String>) -> Self {
+        self.options.extract_filters.extend(patterns);
+        self
+    }
+
+    /// Delete downloaded payloads after extraction according to `policy`
+    pub fn auto_cleanup(mut self, policy: CleanupPolicy) -> Self {
+        self.options.auto_cleanup = policy;
+        self
+    }
+
     /// Build the options
-    pub fn build(self) -> DownloadOptions {
+    ///
+    /// Never fails: `parallel_downloads` is clamped to at least 1, an empty
+    /// `target_dir` falls back to `msvc-kit` and empty exclude patterns, only
+    /// patterns and extract filters are dropped. Use [`try_build`](Self::try_build) to reject such values.
+    pub fn build(mut self) -> DownloadOptions {
+        let options = &mut self.options;
+        options.parallel_downloads = options.parallel_downloads.max(1);
+        if options.target_dir.as_os_str().is_empty() {
+            options.target_dir = PathBuf::from("msvc-kit");
+        }
+        options.exclude_patterns.retain(|p| !p.trim().is_empty());
+        options.only_patterns.retain(|p| !p.trim().is_empty());
+        options
+            .sdk_installer_patterns
+            .retain(|p| !p.trim().is_empty());
+        options.extract_filters.retain(|p| !p.trim().is_empty());
         self.options
     }
+
+    /// Build the options, validating them first
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use msvc_kit::{DownloadOptions, OptionsError};
+    ///
+    /// let err = DownloadOptions::builder()
+    ///     .parallel_downloads(0)
+    ///     .try_build()
+    ///     .unwrap_err();
+    /// assert_eq!(err, OptionsError::ZeroParallelDownloads);
+    /// ```
+    pub fn try_build(self) -> std::result::Result<DownloadOptions, OptionsError> {
+        if let Some(e) = self.target_error {
+            return Err(OptionsError::UnsupportedTarget(e));
+        }
+        self.options.validate()?;
+        Ok(self.options)
+    }
+}
+
+impl From<DownloadOptions> for DownloadOptionsBuilder {
+    /// Start a builder from existing options, e.g. to validate them with
+    /// [`try_build`](DownloadOptionsBuilder::try_build)
+    fn from(options: DownloadOptions) -> Self {
+        Self {
+            options,
+            target_error: None,
+        }
+    }
 }
 
 /// Preview information for dry-run mode
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadPreview {
     /// Component type (MSVC or SDK)
     pub component: String,
@@ -421,12 +1432,21 @@ pub struct DownloadPreview {
     pub file_count: usize,
     /// Total size in bytes
     pub total_size: u64,
+    /// Estimated bytes on disk once extracted, see [`SizeEstimator`]
+    ///
+    /// Packages already extracted into the target directory count with their
+    /// actual size, others with their manifest install size or, without one,
+    /// their download size scaled by the extraction ratio of their type.
+    pub installed_size: u64,
     /// List of packages with their sizes
     pub packages: Vec<PackagePreview>,
+    /// Host architecture of the selected tools and how it was chosen (MSVC only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_arch: Option<HostArchSelection>,
 }
 
 /// Preview information for a single package
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackagePreview {
     /// Package ID
     pub id: String,
@@ -438,14 +1458,32 @@ pub struct PackagePreview {
     pub size: u64,
 }
 
+impl From<&Package> for PackagePreview {
+    fn from(package: &Package) -> Self {
+        Self {
+            id: package.id.clone(),
+            version: package.version.clone(),
+            file_count: package.payloads.len(),
+            size: package.total_size,
+        }
+    }
+}
+
 impl DownloadPreview {
     /// Format the preview as a human-readable string
     pub fn format(&self) -> String {
         let size_str = humansize::format_size(self.total_size, humansize::BINARY);
-        format!(
+        let mut line = format!(
             "{} v{}: {} packages, {} files, {}",
             self.component, self.version, self.package_count, self.file_count, size_str
-        )
+        );
+        if self.installed_size > 0 {
+            line.push_str(&format!(
+                " (~{} installed)",
+                humansize::format_size(self.installed_size, humansize::BINARY)
+            ));
+        }
+        line
     }
 }
 
@@ -511,7 +1549,7 @@ pub async fn download_all(options: &DownloadOptions) -> Result<(InstallInfo, Ins
 }
 
 /// Information about available versions from Microsoft servers
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AvailableVersions {
     /// Available MSVC toolset versions (short format, e.g., "14.44")
     pub msvc_versions: Vec<String>,
@@ -521,6 +1559,8 @@ pub struct AvailableVersions {
     pub latest_msvc: Option<String>,
     /// Latest SDK version
     pub latest_sdk: Option<String>,
+    /// Visual Studio channel metadata the versions were resolved from
+    pub channel: ChannelInfo,
 }
 
 /// Fetch available MSVC and Windows SDK versions from Microsoft servers
@@ -537,6 +1577,7 @@ pub struct AvailableVersions {
 /// async fn main() -> anyhow::Result<()> {
 ///     let versions = list_available_versions().await?;
 ///     
+///     println!("Visual Studio: {:?}", versions.channel.product_display_version);
 ///     println!("Latest MSVC: {:?}", versions.latest_msvc);
 ///     println!("Latest SDK: {:?}", versions.latest_sdk);
 ///     
@@ -553,12 +1594,14 @@ pub struct AvailableVersions {
 /// }
 /// ```
 pub async fn list_available_versions() -> Result<AvailableVersions> {
-    let manifest = VsManifest::fetch().await?;
+    let cache_dir = cache::default_manifest_cache_dir();
+    let (manifest, channel) = VsManifest::fetch_with_channel_info(&cache_dir).await?;
 
     Ok(AvailableVersions {
         msvc_versions: manifest.list_msvc_versions(),
         sdk_versions: manifest.list_sdk_versions(),
         latest_msvc: manifest.get_latest_msvc_version(),
         latest_sdk: manifest.get_latest_sdk_version(),
+        channel,
     })
 }
@@ -2,14 +2,16 @@
 
 use async_trait::async_trait;
 
-use super::http::create_http_client;
+use super::headers::is_msvc_header_package;
+use super::manifest::{Package, VsManifest};
 use super::traits::{ComponentDownloader, ComponentType};
 use super::{
-    common::CommonDownloader, DownloadOptions, DownloadPreview, PackagePreview, VsManifest,
+    check_target_dir, common::CommonDownloader, reuse, DownloadOptions, DownloadPreview,
+    PackagePreview, SizeEstimator,
 };
-use crate::error::{MsvcKitError, Result};
+use crate::error::{MsvcKitError, OptionsError, Result};
 use crate::installer::InstallInfo;
-use crate::version::Architecture;
+use crate::version::{select_host_arch, Architecture, HostArchSelection};
 
 /// MSVC downloader
 pub struct MsvcDownloader {
@@ -19,10 +21,7 @@ pub struct MsvcDownloader {
 impl MsvcDownloader {
     /// Create a new MSVC downloader
     pub fn new(options: DownloadOptions) -> Self {
-        let client = options
-            .http_client
-            .clone()
-            .unwrap_or_else(create_http_client);
+        let client = options.client();
         let progress_handler = options.progress_handler.clone();
         let cache_manager = options.cache_manager.clone();
 
@@ -37,52 +36,94 @@ impl MsvcDownloader {
         Self { downloader }
     }
 
+    /// Select the packages to download, honouring headers-only mode and only patterns
+    fn find_packages(
+        &self,
+        manifest: &VsManifest,
+        version: &str,
+        host_arch: &str,
+        target_arch: &str,
+    ) -> Result<Vec<Package>> {
+        let options = &self.downloader.options;
+        let mut packages = manifest.find_msvc_packages_for_platforms(
+            version,
+            host_arch,
+            target_arch,
+            &options.include_components,
+            &options.app_platforms,
+            &options.exclude_patterns,
+        );
+        if options.headers_only {
+            packages.retain(|p| is_msvc_header_package(&p.id));
+        }
+        if !options.only_patterns.is_empty() {
+            let patterns: Vec<String> = options
+                .only_patterns
+                .iter()
+                .map(|p| p.trim().to_lowercase())
+                .collect();
+            packages.retain(|p| {
+                let id = p.id.to_lowercase();
+                patterns.iter().any(|pattern| id.contains(pattern))
+            });
+            let tools = format!(
+                ".tools.host{}.target{}",
+                host_arch.to_lowercase(),
+                target_arch.to_lowercase()
+            );
+            let has_tools = packages
+                .iter()
+                .any(|p| p.id.to_lowercase().contains(&tools));
+            if !has_tools && !options.headers_only && !options.allow_broken_selection {
+                return Err(OptionsError::BrokenSelection {
+                    host: host_arch.to_string(),
+                    target: target_arch.to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(packages)
+    }
+
+    /// MSVC version prefix requested explicitly or by the stable channel
+    ///
+    /// The flag is set when the stable channel chose the version.
+    async fn requested_version(&self, manifest: &VsManifest) -> Result<(Option<String>, bool)> {
+        if let Some(version) = &self.downloader.options.msvc_version {
+            return Ok((Some(version.clone()), false));
+        }
+        let stable = self.downloader.stable_combo(manifest).await?;
+        let from_stable = stable.is_some();
+        Ok((stable.map(|combo| combo.msvc), from_stable))
+    }
+
     /// Preview what would be downloaded (dry-run mode)
     pub async fn preview(&self) -> Result<DownloadPreview> {
-        let manifest = VsManifest::fetch().await?;
+        let manifest = self.downloader.load_manifest().await?;
 
-        let available_versions = manifest.list_msvc_versions();
+        let (requested, _) = self.requested_version(&manifest).await?;
         let version = self
             .downloader
-            .options
-            .msvc_version
-            .clone()
-            .or_else(|| manifest.get_latest_msvc_version())
-            .ok_or_else(|| {
-                MsvcKitError::VersionNotFound(format!(
-                    "No MSVC version found. Available: {:?}",
-                    available_versions
-                ))
-            })?;
-
-        let host_arch = self
-            .downloader
-            .options
-            .host_arch
-            .unwrap_or(Architecture::host())
-            .to_string();
+            .version_resolver()
+            .resolve_msvc(&manifest, requested.as_deref())?
+            .resolved;
+
+        let host = host_selection(&self.downloader.options, &manifest, &version);
+        let host_arch = host.arch.to_string();
         let target_arch = self.downloader.options.arch.to_string();
 
-        let packages = manifest.find_msvc_packages(
-            &version,
-            &host_arch,
-            &target_arch,
-            &self.downloader.options.include_components,
-            &self.downloader.options.exclude_patterns,
-        );
+        check_host_target(&manifest, &version, &host_arch, &target_arch)?;
+        let packages = self.find_packages(&manifest, &version, &host_arch, &target_arch)?;
 
         let file_count: usize = packages.iter().map(|p| p.payloads.len()).sum();
         let total_size: u64 = packages.iter().map(|p| p.total_size).sum();
+        let installed_size = SizeEstimator::new()
+            .refine_from_install(&self.downloader.options.target_dir, &packages)
+            .await
+            .estimate_total(&packages);
 
-        let package_previews: Vec<PackagePreview> = packages
-            .iter()
-            .map(|p| PackagePreview {
-                id: p.id.clone(),
-                version: p.version.clone(),
-                file_count: p.payloads.len(),
-                size: p.total_size,
-            })
-            .collect();
+        let package_previews: Vec<PackagePreview> =
+            packages.iter().map(PackagePreview::from).collect();
 
         Ok(DownloadPreview {
             component: "MSVC".to_string(),
@@ -90,7 +131,9 @@ impl MsvcDownloader {
             package_count: packages.len(),
             file_count,
             total_size,
+            installed_size,
             packages: package_previews,
+            host_arch: Some(host),
         })
     }
 
@@ -115,56 +158,58 @@ impl MsvcDownloader {
                 install_path: self.downloader.options.target_dir.clone(),
                 downloaded_files: vec![],
                 arch: self.downloader.options.arch,
+                extract_filters: self.downloader.options.effective_extract_filters(),
+                cleanup: self.downloader.options.auto_cleanup,
+                resolution: None,
+                warnings: Vec::new(),
+                reused: false,
             });
         }
 
-        // Use custom cache dir if a cache_manager was injected
-        let cache_dir = self.downloader.manifest_cache_dir();
-        let manifest = VsManifest::fetch_with_cache_dir(&cache_dir).await?;
+        // Fail before downloading if nothing can be extracted there
+        check_target_dir(&self.downloader.options.target_dir)?;
+
+        // An equivalent install is already there; skip the manifest entirely
+        let options = &self.downloader.options;
+        if let Some(info) =
+            reuse::find_reusable(options, "msvc", options.msvc_version.as_deref()).await
+        {
+            return Ok(info);
+        }
+
+        // Uses the custom cache dir if a cache_manager was injected
+        let manifest = self.downloader.load_manifest().await?;
 
         // List available versions for debugging
         let available_versions = manifest.list_msvc_versions();
         tracing::debug!("Available MSVC versions: {:?}", available_versions);
 
         // Determine version to download
-        let version = self
+        let (requested, from_stable) = self.requested_version(&manifest).await?;
+        let mut resolution = self
             .downloader
-            .options
-            .msvc_version
-            .clone()
-            .or_else(|| manifest.get_latest_msvc_version())
-            .ok_or_else(|| {
-                MsvcKitError::VersionNotFound(format!(
-                    "No MSVC version found. Available: {:?}",
-                    available_versions
-                ))
-            })?;
-
-        tracing::info!("Selected MSVC version: {}", version);
+            .version_resolver()
+            .resolve_msvc(&manifest, requested.as_deref())?;
+        if from_stable {
+            resolution = resolution.chosen_by_stable_channel();
+        }
+        self.downloader.report_resolution(&resolution);
+        let version = resolution.resolved.clone();
 
         // Determine architectures
-        let host_arch = self
-            .downloader
-            .options
-            .host_arch
-            .unwrap_or(Architecture::host())
-            .to_string();
+        let host = host_selection(&self.downloader.options, &manifest, &version);
+        let host_arch = host.arch.to_string();
         let target_arch = self.downloader.options.arch.to_string();
 
         tracing::info!(
             "Host architecture: {}, Target architecture: {}",
-            host_arch,
+            host,
             target_arch
         );
 
         // Find packages to download
-        let packages = manifest.find_msvc_packages(
-            &version,
-            &host_arch,
-            &target_arch,
-            &self.downloader.options.include_components,
-            &self.downloader.options.exclude_patterns,
-        );
+        check_host_target(&manifest, &version, &host_arch, &target_arch)?;
+        let packages = self.find_packages(&manifest, &version, &host_arch, &target_arch)?;
 
         if packages.is_empty() {
             return Err(MsvcKitError::ComponentNotFound(format!(
@@ -208,22 +253,35 @@ impl MsvcDownloader {
         );
 
         // Download all packages
-        let downloaded_files = self
+        let (downloaded_files, report) = self
             .downloader
-            .download_packages(&packages, &download_dir, "MSVC")
+            .download_packages_with_report(&packages, &download_dir, "MSVC")
             .await?;
+        let warnings: Vec<_> = resolution
+            .warning()
+            .into_iter()
+            .chain(report.warnings())
+            .collect();
 
         tracing::info!("Downloaded {} MSVC packages", downloaded_files.len());
 
         // Return InstallInfo with target_dir as install_path (not extracted yet)
         // The version is a prefix (e.g., "14.44"), full version will be determined after extraction
-        Ok(InstallInfo {
+        let info = InstallInfo {
             component_type: "msvc".to_string(),
             version: version.clone(),
             install_path: self.downloader.options.target_dir.clone(),
             downloaded_files,
             arch: self.downloader.options.arch,
-        })
+            extract_filters: self.downloader.options.effective_extract_filters(),
+            cleanup: self.downloader.options.auto_cleanup,
+            resolution: Some(resolution),
+            warnings,
+            reused: false,
+        };
+        let options = &self.downloader.options;
+        reuse::record_install(options, options.msvc_version.clone(), &info).await;
+        Ok(info)
     }
 
     /// Download MSVC components
@@ -232,6 +290,72 @@ impl MsvcDownloader {
     }
 }
 
+/// Host tools to download for `version`
+///
+/// Without `options.host_arch`, native tools are preferred (`Hostarm64` on
+/// Windows on ARM) when the manifest has them for the target, see
+/// [`select_host_arch`].
+fn host_selection(
+    options: &DownloadOptions,
+    manifest: &VsManifest,
+    version: &str,
+) -> HostArchSelection {
+    let target = options.arch.to_string();
+    let pairs = manifest.msvc_host_targets(version);
+    select_host_arch(options.host_arch, Architecture::native_host(), |host| {
+        let host = host.to_string();
+        pairs.iter().any(|(h, t)| *h == host && *t == target)
+    })
+}
+
+/// Fail early if the manifest has no tools for the host/target combination
+///
+/// Without this, an unsupported pair (e.g. an arm64 host for an old MSVC
+/// version) silently downloads only the CRT and libraries. A target no host
+/// has tools for (32-bit ARM on recent toolsets) fails with
+/// [`MsvcKitError::UnsupportedArchForToolset`].
+fn check_host_target(
+    manifest: &VsManifest,
+    version: &str,
+    host_arch: &str,
+    target_arch: &str,
+) -> Result<()> {
+    let pairs = manifest.msvc_host_targets(version);
+    if !pairs.is_empty()
+        && !pairs
+            .iter()
+            .any(|(_, t)| t.eq_ignore_ascii_case(target_arch))
+    {
+        if let Ok(arch) = target_arch.parse() {
+            return Err(MsvcKitError::UnsupportedArchForToolset {
+                toolset: version.to_string(),
+                arch,
+                supported: manifest.msvc_target_archs(version),
+            });
+        }
+    }
+    let supported = pairs
+        .iter()
+        .any(|(h, t)| h.eq_ignore_ascii_case(host_arch) && t.eq_ignore_ascii_case(target_arch));
+
+    // An empty list means the version was not found; that is reported later
+    if pairs.is_empty() || supported {
+        return Ok(());
+    }
+
+    let available: Vec<String> = pairs
+        .iter()
+        .map(|(h, t)| format!("host {} -> target {}", h, t))
+        .collect();
+    Err(MsvcKitError::ComponentNotFound(format!(
+        "MSVC {} has no tools for host {} targeting {}. Available: {}",
+        version,
+        host_arch,
+        target_arch,
+        available.join(", ")
+    )))
+}
+
 #[async_trait]
 impl ComponentDownloader for MsvcDownloader {
     async fn download(&self) -> Result<InstallInfo> {
@@ -273,4 +397,99 @@ mod tests {
         let cache_dir = downloader.downloader.manifest_cache_dir();
         assert_eq!(cache_dir, temp_dir.path().join("manifests"));
     }
+
+    #[test]
+    fn find_packages_only_patterns() {
+        let manifest: VsManifest = serde_json::from_value(serde_json::json!({
+            "manifestVersion": "1.0",
+            "packages": [
+                {"id": "Microsoft.VC.14.44.Tools.HostX64.TargetX64.base", "version": "14.44.34823", "type": "Vsix", "chip": "x64"},
+                {"id": "Microsoft.VC.14.44.CRT.Headers", "version": "14.44.34823", "type": "Vsix"},
+                {"id": "Microsoft.VC.14.44.MFC.x64", "version": "14.44.34823", "type": "Vsix", "chip": "x64"}
+            ]
+        }))
+        .unwrap();
+        let find = |options: DownloadOptions| {
+            MsvcDownloader::new(options)
+                .find_packages(&manifest, "14.44", "x64", "x64")
+                .map(|packages| packages.into_iter().map(|p| p.id).collect::<Vec<_>>())
+        };
+
+        let ids = find(
+            DownloadOptions::builder()
+                .only_pattern(".Tools.")
+                .only_pattern("crt.headers")
+                .build(),
+        )
+        .unwrap();
+        assert_eq!(
+            ids,
+            vec![
+                "Microsoft.VC.14.44.Tools.HostX64.TargetX64.base",
+                "Microsoft.VC.14.44.CRT.Headers"
+            ]
+        );
+
+        let err = find(DownloadOptions::builder().only_pattern(".mfc").build()).unwrap_err();
+        assert!(matches!(
+            err,
+            MsvcKitError::InvalidOptions(OptionsError::BrokenSelection { .. })
+        ));
+
+        let ids = find(
+            DownloadOptions::builder()
+                .only_pattern(".mfc")
+                .allow_broken_selection(true)
+                .build(),
+        )
+        .unwrap();
+        assert_eq!(ids, vec!["Microsoft.VC.14.44.MFC.x64"]);
+    }
+
+    #[test]
+    fn arm32_target_selection() {
+        let manifest: VsManifest = serde_json::from_value(serde_json::json!({
+            "manifestVersion": "1.0",
+            "packages": [
+                {"id": "Microsoft.VC.14.29.16.11.Tools.HostX64.TargetARM.base", "version": "14.29.30156", "type": "Vsix", "chip": "x64"},
+                {"id": "Microsoft.VC.14.29.16.11.Tools.HostX64.TargetARM64.base", "version": "14.29.30156", "type": "Vsix", "chip": "x64"},
+                {"id": "Microsoft.VC.14.29.16.11.CRT.ARM.Desktop", "version": "14.29.30156", "type": "Vsix"},
+                {"id": "Microsoft.VC.14.29.16.11.CRT.ARM64.Desktop", "version": "14.29.30156", "type": "Vsix"},
+                {"id": "Microsoft.VC.14.44.17.14.Tools.HostX64.TargetARM64.base", "version": "14.44.34823", "type": "Vsix", "chip": "x64"},
+                {"id": "Microsoft.VC.14.44.17.14.Tools.HostX64.TargetX64.base", "version": "14.44.34823", "type": "Vsix", "chip": "x64"}
+            ]
+        }))
+        .unwrap();
+
+        // Legacy toolsets: ARM packages only, not the ARM64 ones
+        check_host_target(&manifest, "14.29", "x64", "arm").unwrap();
+        let ids: Vec<String> = MsvcDownloader::new(DownloadOptions::default())
+            .find_packages(&manifest, "14.29", "x64", "arm")
+            .unwrap()
+            .into_iter()
+            .map(|p| p.id)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "Microsoft.VC.14.29.16.11.Tools.HostX64.TargetARM.base",
+                "Microsoft.VC.14.29.16.11.CRT.ARM.Desktop"
+            ]
+        );
+
+        // Recent toolsets dropped ARM
+        let err = check_host_target(&manifest, "14.44", "x64", "arm").unwrap_err();
+        match err {
+            MsvcKitError::UnsupportedArchForToolset {
+                toolset,
+                arch,
+                supported,
+            } => {
+                assert_eq!(toolset, "14.44");
+                assert_eq!(arch, Architecture::Arm);
+                assert_eq!(supported, vec![Architecture::Arm64, Architecture::X64]);
+            }
+            other => panic!("unexpected error: {other}"),
+        }
+    }
 }
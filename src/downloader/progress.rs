@@ -3,13 +3,39 @@
 //! This module provides abstractions for progress reporting,
 //! allowing external integrations (like vx) to implement custom UI.
 
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{DownloadEvent, PackagePreview, ResolutionReport, ResumeSummary};
+
+/// How long the default handler estimates the ETA of a resumed download from
+/// earlier runs, before its own rate measurement takes over
+const RESUMED_ETA_WARMUP: Duration = Duration::from_secs(10);
 
 /// Progress handler trait for download operations
 ///
 /// Implement this trait to provide custom progress UI.
 /// The default implementation uses `indicatif` for terminal progress bars.
 ///
+/// # Call order
+///
+/// Each component download (MSVC, then the Windows SDK with the same
+/// handler) is one run, with these guarantees:
+///
+/// 1. `on_version_resolved` and `on_packages_resolved` come before the run starts
+/// 2. `on_start` starts the run, before any other call of the run
+/// 3. `on_resume`, `on_file_start`, `on_progress`, `on_file_complete` and
+///    `on_throttled` only come between `on_start` and the end of the run;
+///    calls for different files interleave, as payloads download in parallel
+/// 4. exactly one `on_complete` (success) or `on_error` (failure) ends the
+///    run, and no call of the run follows it
+///
+/// `on_message` may come at any time. A file does not always get an
+/// `on_file_start`: payloads already on disk only report `on_file_complete`.
+/// Debug builds of msvc-kit assert this order; in tests,
+/// [`RecordingProgressHandler::check_order`] checks it.
+///
 /// # Example
 ///
 /// ```rust,no_run
@@ -44,6 +70,25 @@ use std::sync::Arc;
 /// }
 /// ```
 pub trait ProgressHandler: Send + Sync {
+    /// Called once the version to download is selected, before any package is resolved
+    ///
+    /// # Arguments
+    /// * `report` - Requested and selected version, and how they matched
+    fn on_version_resolved(&self, report: &ResolutionReport) {
+        // Default: no-op
+        let _ = report;
+    }
+
+    /// Called with the packages selected for download, before `on_start`
+    ///
+    /// # Arguments
+    /// * `component` - Component name (e.g., "MSVC", "Windows SDK")
+    /// * `packages` - Packages that will be downloaded
+    fn on_packages_resolved(&self, component: &str, packages: &[PackagePreview]) {
+        // Default: no-op
+        let _ = (component, packages);
+    }
+
     /// Called when download starts
     ///
     /// # Arguments
@@ -52,6 +97,19 @@ pub trait ProgressHandler: Send + Sync {
     /// * `total_bytes` - Total size in bytes
     fn on_start(&self, component: &str, total_files: usize, total_bytes: u64);
 
+    /// Called after `on_start` when payloads of an interrupted run are already on disk
+    ///
+    /// The completed bytes have been reported through `on_progress` without
+    /// being transferred; `summary` has the throughput of earlier runs for
+    /// an ETA until the current run has measured its own.
+    ///
+    /// # Arguments
+    /// * `summary` - How far earlier runs got
+    fn on_resume(&self, summary: &ResumeSummary) {
+        // Default: no-op
+        let _ = summary;
+    }
+
     /// Called when a file download starts
     ///
     /// # Arguments
@@ -93,27 +151,78 @@ pub trait ProgressHandler: Send + Sync {
         // Default: no-op
         let _ = message;
     }
+
+    /// Called when the server throttles a request (HTTP 429/503)
+    ///
+    /// # Arguments
+    /// * `file_name` - Name of the throttled file
+    /// * `status` - HTTP status code returned by the server
+    /// * `wait` - Time the downloader will wait before retrying
+    fn on_throttled(&self, file_name: &str, status: u16, wait: Duration) {
+        // Default: no-op
+        let _ = (file_name, status, wait);
+    }
 }
 
 /// Default progress handler using indicatif
 pub struct IndicatifProgressHandler {
     progress_bar: indicatif::ProgressBar,
+    /// Throughput of earlier runs of a resumed download (0 if none)
+    resumed_rate: Arc<AtomicU64>,
 }
 
 impl IndicatifProgressHandler {
     /// Create a new indicatif progress handler
     pub fn new(total_bytes: u64) -> Self {
-        use indicatif::{ProgressBar, ProgressStyle};
+        use indicatif::{HumanDuration, ProgressBar, ProgressState, ProgressStyle};
 
+        let resumed_rate = Arc::new(AtomicU64::new(0));
+        let rate = resumed_rate.clone();
         let pb = ProgressBar::new(total_bytes);
         pb.set_style(
             ProgressStyle::default_bar()
                 .template("{spinner:.green} [{elapsed_precise}] {wide_bar:.cyan/blue} {bytes}/{total_bytes} @ {bytes_per_sec} ETA {eta} | {msg}")
                 .unwrap()
+                .with_key("eta", move |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+                    let rate = rate.load(Ordering::Relaxed);
+                    let eta = if rate > 0 && state.elapsed() < RESUMED_ETA_WARMUP {
+                        let remaining = state.len().unwrap_or(0).saturating_sub(state.pos());
+                        Duration::from_secs(remaining.div_ceil(rate))
+                    } else {
+                        state.eta()
+                    };
+                    let _ = write!(w, "{:#}", HumanDuration(eta));
+                })
                 .progress_chars("##-"),
         );
 
-        Self { progress_bar: pb }
+        Self {
+            progress_bar: pb,
+            resumed_rate,
+        }
+    }
+
+    /// Create a handler whose bar is one line of a combined view
+    ///
+    /// Used when several downloads run concurrently (e.g. one per architecture);
+    /// `label` prefixes the line. The bar is resized on every `on_start`, so the
+    /// handler can be shared by consecutive downloads.
+    pub fn in_multi(multi: &indicatif::MultiProgress, label: impl Into<String>) -> Self {
+        use indicatif::{ProgressBar, ProgressStyle};
+
+        let pb = multi.add(ProgressBar::new(0));
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{prefix:>6.bold} {spinner:.green} [{elapsed_precise}] {wide_bar:.cyan/blue} {bytes}/{total_bytes} @ {bytes_per_sec} | {msg}")
+                .unwrap()
+                .progress_chars("##-"),
+        );
+        pb.set_prefix(label.into());
+
+        Self {
+            progress_bar: pb,
+            resumed_rate: Arc::new(AtomicU64::new(0)),
+        }
     }
 
     /// Get the underlying progress bar
@@ -124,6 +233,10 @@ impl IndicatifProgressHandler {
 
 impl ProgressHandler for IndicatifProgressHandler {
     fn on_start(&self, component: &str, total_files: usize, total_bytes: u64) {
+        if self.progress_bar.length() != Some(total_bytes) {
+            self.progress_bar.reset();
+            self.progress_bar.set_length(total_bytes);
+        }
         self.progress_bar.set_message(format!(
             "{}: {} files, total {}",
             component,
@@ -132,6 +245,13 @@ impl ProgressHandler for IndicatifProgressHandler {
         ));
     }
 
+    fn on_resume(&self, summary: &ResumeSummary) {
+        // The bytes already on disk must not count towards the measured rate
+        self.progress_bar.reset_eta();
+        self.resumed_rate
+            .store(summary.bytes_per_sec.unwrap_or(0), Ordering::Relaxed);
+    }
+
     fn on_file_start(&self, file_name: &str, _file_size: u64) {
         self.progress_bar.set_message(file_name.to_string());
     }
@@ -157,6 +277,15 @@ impl ProgressHandler for IndicatifProgressHandler {
     fn on_message(&self, message: &str) {
         self.progress_bar.set_message(message.to_string());
     }
+
+    fn on_throttled(&self, file_name: &str, status: u16, wait: Duration) {
+        self.progress_bar.set_message(format!(
+            "{}: throttled (HTTP {}), waiting {}s",
+            file_name,
+            status,
+            wait.as_secs()
+        ));
+    }
 }
 
 /// No-op progress handler for silent operation
@@ -171,6 +300,326 @@ impl ProgressHandler for NoopProgressHandler {
     fn on_error(&self, _error: &str) {}
 }
 
+/// A [`ProgressHandler`] call recorded by [`RecordingProgressHandler`]
+#[derive(Debug)]
+pub struct RecordedEvent {
+    /// Time since the handler was created
+    pub at: Duration,
+    /// The call, as the [`DownloadEvent`] a download stream would emit for it
+    pub event: DownloadEvent,
+}
+
+/// Progress handler recording every call with a timestamp
+///
+/// For tests of code that drives downloads or embeds their progress in a UI:
+/// inspect what was reported, and check it against the [call
+/// order](ProgressHandler#call-order) with [`check_order`](Self::check_order).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use msvc_kit::downloader::RecordingProgressHandler;
+/// use msvc_kit::{download_msvc, DownloadEvent, DownloadOptions};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let recorder = Arc::new(RecordingProgressHandler::new());
+///     let options = DownloadOptions::builder()
+///         .progress_handler(recorder.clone())
+///         .build();
+///     download_msvc(&options).await?;
+///
+///     recorder.check_order().unwrap();
+///     for recorded in recorder.take_events() {
+///         if let DownloadEvent::FileCompleted { file_name, outcome } = recorded.event {
+///             println!("{:?} {}: {}", recorded.at, file_name, outcome);
+///         }
+///     }
+///     Ok(())
+/// }
+/// ```
+pub struct RecordingProgressHandler {
+    created: Instant,
+    events: Mutex<Vec<RecordedEvent>>,
+}
+
+impl Default for RecordingProgressHandler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RecordingProgressHandler {
+    /// Create a handler with no recorded events
+    pub fn new() -> Self {
+        Self {
+            created: Instant::now(),
+            events: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn record(&self, event: DownloadEvent) {
+        let at = self.created.elapsed();
+        self.events
+            .lock()
+            .unwrap()
+            .push(RecordedEvent { at, event });
+    }
+
+    /// Number of recorded events
+    pub fn len(&self) -> usize {
+        self.events.lock().unwrap().len()
+    }
+
+    /// Whether nothing was recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove and return the recorded events, oldest first
+    pub fn take_events(&self) -> Vec<RecordedEvent> {
+        std::mem::take(&mut *self.events.lock().unwrap())
+    }
+
+    /// Check the recorded events against the [call order](ProgressHandler#call-order)
+    ///
+    /// Returns the first violation, naming the position of the offending event.
+    pub fn check_order(&self) -> std::result::Result<(), String> {
+        let events = self.events.lock().unwrap();
+        let mut state = RunState::default();
+        for (position, recorded) in events.iter().enumerate() {
+            state = state
+                .after(Call::of(&recorded.event))
+                .map_err(|reason| format!("event {}: {}", position, reason))?;
+        }
+        Ok(())
+    }
+}
+
+impl ProgressHandler for RecordingProgressHandler {
+    fn on_version_resolved(&self, report: &ResolutionReport) {
+        self.record(DownloadEvent::VersionResolved(report.clone()));
+    }
+
+    fn on_packages_resolved(&self, component: &str, packages: &[PackagePreview]) {
+        self.record(DownloadEvent::PackagesResolved {
+            component: component.to_string(),
+            packages: packages.to_vec(),
+        });
+    }
+
+    fn on_start(&self, component: &str, total_files: usize, total_bytes: u64) {
+        self.record(DownloadEvent::Started {
+            component: component.to_string(),
+            total_files,
+            total_bytes,
+        });
+    }
+
+    fn on_resume(&self, summary: &ResumeSummary) {
+        self.record(DownloadEvent::Resumed(summary.clone()));
+    }
+
+    fn on_file_start(&self, file_name: &str, file_size: u64) {
+        self.record(DownloadEvent::FileStarted {
+            file_name: file_name.to_string(),
+            size: file_size,
+        });
+    }
+
+    fn on_progress(&self, bytes: u64) {
+        self.record(DownloadEvent::Progress { bytes });
+    }
+
+    fn on_file_complete(&self, file_name: &str, outcome: &str) {
+        self.record(DownloadEvent::FileCompleted {
+            file_name: file_name.to_string(),
+            outcome: outcome.to_string(),
+        });
+    }
+
+    fn on_complete(&self, downloaded: usize, skipped: usize) {
+        self.record(DownloadEvent::Completed {
+            downloaded,
+            skipped,
+        });
+    }
+
+    fn on_error(&self, error: &str) {
+        self.record(DownloadEvent::Error(error.to_string()));
+    }
+
+    fn on_message(&self, message: &str) {
+        self.record(DownloadEvent::Message(message.to_string()));
+    }
+
+    fn on_throttled(&self, file_name: &str, status: u16, wait: Duration) {
+        self.record(DownloadEvent::Throttled {
+            file_name: file_name.to_string(),
+            status,
+            wait,
+        });
+    }
+}
+
+/// Where a run is, for checking the [call order](ProgressHandler#call-order)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum RunState {
+    /// No run started yet
+    #[default]
+    Idle,
+    /// Between `on_start` and the end of the run
+    Running,
+    /// The last run ended with `on_complete` or `on_error`
+    Finished,
+}
+
+/// Kind of a [`ProgressHandler`] call, by where it may occur
+#[derive(Debug, Clone, Copy)]
+enum Call {
+    /// Only outside a run
+    Setup(&'static str),
+    /// `on_start`
+    Start,
+    /// Only within a run
+    InRun(&'static str),
+    /// `on_complete` or `on_error`, ending a run
+    End(&'static str),
+    /// Anywhere
+    Any,
+}
+
+impl Call {
+    fn of(event: &DownloadEvent) -> Self {
+        match event {
+            DownloadEvent::VersionResolved(_) => Call::Setup("on_version_resolved"),
+            DownloadEvent::PackagesResolved { .. } => Call::Setup("on_packages_resolved"),
+            DownloadEvent::Started { .. } => Call::Start,
+            DownloadEvent::Resumed(_) => Call::InRun("on_resume"),
+            DownloadEvent::FileStarted { .. } => Call::InRun("on_file_start"),
+            DownloadEvent::Progress { .. } => Call::InRun("on_progress"),
+            DownloadEvent::FileCompleted { .. } => Call::InRun("on_file_complete"),
+            DownloadEvent::Throttled { .. } => Call::InRun("on_throttled"),
+            DownloadEvent::Completed { .. } => Call::End("on_complete"),
+            DownloadEvent::Error(_) => Call::End("on_error"),
+            DownloadEvent::Message(_) | DownloadEvent::Finished(_) => Call::Any,
+        }
+    }
+}
+
+impl RunState {
+    /// State after `call`, or how `call` breaks the call order
+    fn after(self, call: Call) -> std::result::Result<Self, String> {
+        match (call, self) {
+            (Call::Setup(name), RunState::Running) => Err(format!("{} during a run", name)),
+            (Call::Start, RunState::Running) => {
+                Err("on_start before the previous run ended".to_string())
+            }
+            (Call::Start, _) => Ok(RunState::Running),
+            (Call::InRun(name), RunState::Idle | RunState::Finished) => Err(format!(
+                "{} outside a run (before on_start or after on_complete / on_error)",
+                name
+            )),
+            (Call::End(name), RunState::Idle | RunState::Finished) => {
+                Err(format!("{} without a running download", name))
+            }
+            (Call::End(_), RunState::Running) => Ok(RunState::Finished),
+            _ => Ok(self),
+        }
+    }
+}
+
+/// Forwards to `inner`, asserting the [call order](ProgressHandler#call-order)
+///
+/// Wrapped around the handler of every run in debug builds, so a change
+/// breaking the guarantees fails msvc-kit's own tests.
+#[cfg(debug_assertions)]
+pub(crate) struct OrderCheckingHandler {
+    inner: BoxedProgressHandler,
+    state: Mutex<RunState>,
+}
+
+#[cfg(debug_assertions)]
+impl OrderCheckingHandler {
+    pub(crate) fn wrap(inner: BoxedProgressHandler) -> BoxedProgressHandler {
+        Arc::new(Self {
+            inner,
+            state: Mutex::new(RunState::default()),
+        })
+    }
+
+    fn check(&self, call: Call) {
+        let mut state = self.state.lock().unwrap();
+        let next = state.after(call);
+        debug_assert!(
+            next.is_ok(),
+            "ProgressHandler call order violated: {}",
+            next.clone().unwrap_err()
+        );
+        if let Ok(next) = next {
+            *state = next;
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+impl ProgressHandler for OrderCheckingHandler {
+    fn on_version_resolved(&self, report: &ResolutionReport) {
+        self.check(Call::Setup("on_version_resolved"));
+        self.inner.on_version_resolved(report);
+    }
+
+    fn on_packages_resolved(&self, component: &str, packages: &[PackagePreview]) {
+        self.check(Call::Setup("on_packages_resolved"));
+        self.inner.on_packages_resolved(component, packages);
+    }
+
+    fn on_start(&self, component: &str, total_files: usize, total_bytes: u64) {
+        self.check(Call::Start);
+        self.inner.on_start(component, total_files, total_bytes);
+    }
+
+    fn on_resume(&self, summary: &ResumeSummary) {
+        self.check(Call::InRun("on_resume"));
+        self.inner.on_resume(summary);
+    }
+
+    fn on_file_start(&self, file_name: &str, file_size: u64) {
+        self.check(Call::InRun("on_file_start"));
+        self.inner.on_file_start(file_name, file_size);
+    }
+
+    fn on_progress(&self, bytes: u64) {
+        self.check(Call::InRun("on_progress"));
+        self.inner.on_progress(bytes);
+    }
+
+    fn on_file_complete(&self, file_name: &str, outcome: &str) {
+        self.check(Call::InRun("on_file_complete"));
+        self.inner.on_file_complete(file_name, outcome);
+    }
+
+    fn on_complete(&self, downloaded: usize, skipped: usize) {
+        self.check(Call::End("on_complete"));
+        self.inner.on_complete(downloaded, skipped);
+    }
+
+    fn on_error(&self, error: &str) {
+        self.check(Call::End("on_error"));
+        self.inner.on_error(error);
+    }
+
+    fn on_message(&self, message: &str) {
+        self.inner.on_message(message);
+    }
+
+    fn on_throttled(&self, file_name: &str, status: u16, wait: Duration) {
+        self.check(Call::InRun("on_throttled"));
+        self.inner.on_throttled(file_name, status, wait);
+    }
+}
+
 /// Type alias for boxed progress handler
 pub type BoxedProgressHandler = Arc<dyn ProgressHandler>;
 
@@ -183,3 +632,71 @@ pub fn default_progress_handler(total_bytes: u64) -> BoxedProgressHandler {
 pub fn noop_progress_handler() -> BoxedProgressHandler {
     Arc::new(NoopProgressHandler)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_handler_check_order() {
+        let recorder = RecordingProgressHandler::new();
+        assert!(recorder.is_empty());
+        recorder.on_packages_resolved("MSVC", &[]);
+        recorder.on_start("MSVC", 2, 30);
+        recorder.on_file_complete("cached.vsix", "cached");
+        recorder.on_file_start("a.vsix", 20);
+        recorder.on_message("1/2 files");
+        recorder.on_progress(20);
+        recorder.on_file_complete("a.vsix", "downloaded");
+        recorder.on_complete(1, 1);
+        recorder.on_start("Windows SDK", 0, 0);
+        recorder.on_error("offline");
+        assert_eq!(recorder.check_order(), Ok(()));
+
+        let events = recorder.take_events();
+        assert_eq!(events.len(), 10);
+        assert!(events.windows(2).all(|w| w[0].at <= w[1].at));
+        assert!(matches!(
+            events[5].event,
+            DownloadEvent::Progress { bytes: 20 }
+        ));
+        assert!(recorder.is_empty());
+    }
+
+    #[test]
+    fn test_check_order_violations() {
+        let early = RecordingProgressHandler::new();
+        early.on_progress(1);
+        assert_eq!(
+            early.check_order(),
+            Err(
+                "event 0: on_progress outside a run (before on_start or after on_complete / on_error)"
+                    .to_string()
+            )
+        );
+
+        let twice = RecordingProgressHandler::new();
+        twice.on_start("MSVC", 0, 0);
+        twice.on_complete(0, 0);
+        twice.on_complete(0, 0);
+        assert_eq!(
+            twice.check_order(),
+            Err("event 2: on_complete without a running download".to_string())
+        );
+
+        let overlapping = RecordingProgressHandler::new();
+        overlapping.on_start("MSVC", 0, 0);
+        overlapping.on_packages_resolved("Windows SDK", &[]);
+        assert!(overlapping.check_order().is_err());
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "call order violated: on_file_start outside a run")]
+    fn test_order_checking_handler_asserts() {
+        let handler = OrderCheckingHandler::wrap(Arc::new(NoopProgressHandler));
+        handler.on_start("MSVC", 1, 10);
+        handler.on_complete(1, 0);
+        handler.on_file_start("late.vsix", 10);
+    }
+}
@@ -0,0 +1,232 @@
+//! Quarantine for payloads that failed hash verification
+//!
+//! When a payload does not match its manifest hash in enforce mode, the file
+//! is moved to `<target_dir>/quarantine/` instead of being deleted, together
+//! with a `<file>.json` [`QuarantineRecord`] describing where it came from.
+//! This keeps the evidence for bug reports (`msvc-kit diagnostics collect`)
+//! about corrupted mirrors, proxies rewriting responses, or manifest errors.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::PackagePayload;
+use crate::error::Result;
+
+/// Name of the quarantine directory below the target directory
+pub const QUARANTINE_DIR_NAME: &str = "quarantine";
+
+/// Response headers kept in a [`QuarantineRecord`]
+///
+/// Records end up in diagnostics bundles attached to public issues, so
+/// anything that may carry cookies or tokens is dropped.
+pub const RECORDED_HEADERS: &[&str] = &[
+    "etag",
+    "last-modified",
+    "content-length",
+    "content-type",
+    "server",
+    "age",
+    "x-cache",
+];
+
+/// Metadata written next to a quarantined payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuarantineRecord {
+    /// Payload file name from the manifest
+    pub file_name: String,
+    /// Package id of the payload (empty if unknown)
+    #[serde(default)]
+    pub package_id: String,
+    /// URL from the manifest
+    pub url: String,
+    /// URL the payload was served from, after redirects, without query string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub final_url: Option<String>,
+    /// SHA256 from the manifest
+    pub expected: String,
+    /// SHA256 of the quarantined file
+    pub actual: String,
+    /// Size of the quarantined file in bytes
+    pub size: u64,
+    /// Size from the manifest
+    pub expected_size: u64,
+    /// Response headers of the download (see [`RECORDED_HEADERS`]), if it
+    /// happened in this run
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// When the file was quarantined
+    pub quarantined_at: DateTime<Utc>,
+    /// Name of the quarantined file in the quarantine directory
+    pub quarantined_file: String,
+}
+
+/// A payload that failed verification, with the response it came from
+pub(crate) struct QuarantineRequest<'a> {
+    pub payload: &'a PackagePayload,
+    pub package_id: String,
+    pub expected: &'a str,
+    pub actual: &'a str,
+    pub final_url: Option<String>,
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Move `path` into `quarantine_dir` and write its metadata
+///
+/// The file is named `<actual hash prefix>-<file name>`, so different bad
+/// copies of the same payload are kept side by side. Returns the new path.
+pub(crate) async fn quarantine_payload(
+    quarantine_dir: &Path,
+    path: &Path,
+    request: QuarantineRequest<'_>,
+) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(quarantine_dir).await?;
+    let prefix: String = request.actual.chars().take(12).collect();
+    let quarantined_file = format!("{}-{}", prefix, request.payload.file_name);
+    let target = quarantine_dir.join(&quarantined_file);
+
+    let size = tokio::fs::metadata(path).await?.len();
+    if tokio::fs::rename(path, &target).await.is_err() {
+        // Different volume (shared cache): copy, then remove the original
+        tokio::fs::copy(path, &target).await?;
+        tokio::fs::remove_file(path).await?;
+    }
+
+    let record = QuarantineRecord {
+        file_name: request.payload.file_name.clone(),
+        package_id: request.package_id,
+        url: request.payload.url.clone(),
+        final_url: request.final_url.as_deref().map(redact_url),
+        expected: request.expected.to_string(),
+        actual: request.actual.to_string(),
+        size,
+        expected_size: request.payload.size,
+        headers: request
+            .headers
+            .into_iter()
+            .filter(|(name, _)| RECORDED_HEADERS.contains(&name.to_ascii_lowercase().as_str()))
+            .collect(),
+        quarantined_at: Utc::now(),
+        quarantined_file,
+    };
+    let metadata = serde_json::to_vec_pretty(&record)?;
+    tokio::fs::write(
+        target.with_file_name(format!("{}.json", record.quarantined_file)),
+        metadata,
+    )
+    .await?;
+
+    tracing::warn!(
+        "Quarantined {} (expected {}, got {}) to {}",
+        record.file_name,
+        record.expected,
+        record.actual,
+        target.display()
+    );
+    Ok(target)
+}
+
+/// Records of the payloads quarantined in `target_dir`, oldest first
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::downloader::list_quarantine;
+///
+/// for record in list_quarantine("C:/msvc-kit".as_ref())? {
+///     println!("{} from {}: got {}", record.file_name, record.url, record.actual);
+/// }
+/// # Ok::<(), msvc_kit::MsvcKitError>(())
+/// ```
+pub fn list_quarantine(target_dir: &Path) -> Result<Vec<QuarantineRecord>> {
+    let dir = target_dir.join(QUARANTINE_DIR_NAME);
+    let mut records = Vec::new();
+    for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let record: QuarantineRecord = serde_json::from_slice(&std::fs::read(&path)?)?;
+            records.push(record);
+        }
+    }
+    records.sort_by_key(|r| r.quarantined_at);
+    Ok(records)
+}
+
+/// `url` without credentials, query string and fragment
+///
+/// Redirects to signed CDN URLs carry access tokens in the query string.
+fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            let _ = parsed.set_username("");
+            let _ = parsed.set_password(None);
+            parsed.set_query(None);
+            parsed.set_fragment(None);
+            parsed.to_string()
+        }
+        Err(_) => url.split(['?', '#']).next().unwrap_or_default().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_quarantine_payload() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("payload.cab");
+        tokio::fs::write(&file, b"corrupted").await.unwrap();
+        let payload = PackagePayload {
+            file_name: "payload.cab".to_string(),
+            url: "https://example.com/payload.cab".to_string(),
+            size: 4,
+            sha256: Some("aa".repeat(32)),
+            mirrors: Vec::new(),
+        };
+
+        let quarantine_dir = temp_dir.path().join(QUARANTINE_DIR_NAME);
+        let headers = BTreeMap::from([
+            ("etag".to_string(), "\"abc\"".to_string()),
+            ("set-cookie".to_string(), "session=secret".to_string()),
+            ("x-amz-request-id".to_string(), "123".to_string()),
+        ]);
+        let target = quarantine_payload(
+            &quarantine_dir,
+            &file,
+            QuarantineRequest {
+                payload: &payload,
+                package_id: "Microsoft.VC.Tools".to_string(),
+                expected: &"aa".repeat(32),
+                actual: &"bb".repeat(32),
+                final_url: Some(
+                    "https://cdn.example.com/payload.cab?sig=secret&se=2026#frag".to_string(),
+                ),
+                headers,
+            },
+        )
+        .await
+        .unwrap();
+
+        assert!(!file.exists());
+        assert_eq!(target, quarantine_dir.join("bbbbbbbbbbbb-payload.cab"));
+        assert_eq!(std::fs::read(&target).unwrap(), b"corrupted");
+
+        let records = list_quarantine(temp_dir.path()).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].package_id, "Microsoft.VC.Tools");
+        assert_eq!(records[0].size, 9);
+        assert_eq!(records[0].expected_size, 4);
+        assert_eq!(records[0].headers.len(), 1);
+        assert_eq!(records[0].headers["etag"], "\"abc\"");
+        assert_eq!(
+            records[0].final_url.as_deref(),
+            Some("https://cdn.example.com/payload.cab")
+        );
+        assert!(list_quarantine(&temp_dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+}
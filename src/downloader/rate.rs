@@ -0,0 +1,108 @@
+//! Bandwidth limit shared by all transfers of a download
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Credit a limiter may build up while idle, so it cannot burst unbounded
+const MAX_BURST: Duration = Duration::from_secs(1);
+
+/// Throttles transfers to an average number of bytes per second
+///
+/// All parallel transfers of a download share one limiter, so the limit
+/// applies to the total bandwidth.
+#[derive(Debug)]
+pub(crate) struct RateLimiter {
+    bytes_per_sec: u64,
+    /// Start of the current window and bytes consumed since
+    window: Mutex<(Instant, u64)>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(bytes_per_sec: u64) -> Self {
+        Self {
+            bytes_per_sec: bytes_per_sec.max(1),
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Delay after consuming `bytes` to stay below the limit
+    fn reserve(&self, bytes: u64, now: Instant) -> Duration {
+        let mut window = self.window.lock().unwrap();
+        let (start, consumed) = *window;
+        let due = start + Duration::from_secs_f64(consumed as f64 / self.bytes_per_sec as f64);
+        if due + MAX_BURST < now {
+            *window = (now.checked_sub(MAX_BURST).unwrap_or(now), 0);
+        }
+        window.1 += bytes;
+        let due = window.0 + Duration::from_secs_f64(window.1 as f64 / self.bytes_per_sec as f64);
+        due.saturating_duration_since(now)
+    }
+
+    /// Wait until `bytes` more may be transferred
+    pub(crate) async fn acquire(&self, bytes: usize) {
+        let delay = self.reserve(bytes as u64, Instant::now());
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Parse a transfer rate such as `500K`, `2M` or `1.5MB/s` into bytes per second
+///
+/// Suffixes are binary (`K` = 1024). A plain number is bytes per second.
+pub fn parse_rate(value: &str) -> std::result::Result<u64, String> {
+    let trimmed = value.trim();
+    let lower = trimmed.to_lowercase();
+    let lower = lower.strip_suffix("/s").unwrap_or(&lower);
+    let lower = lower.strip_suffix('b').unwrap_or(lower);
+    let (number, multiplier) = match lower.char_indices().last() {
+        Some((i, 'k')) => (&lower[..i], 1u64 << 10),
+        Some((i, 'm')) => (&lower[..i], 1 << 20),
+        Some((i, 'g')) => (&lower[..i], 1 << 30),
+        _ => (lower, 1),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|n| n.is_finite() && *n >= 0.0)
+        .map(|n| (n * multiplier as f64) as u64)
+        .ok_or_else(|| {
+            format!(
+                "Invalid rate: {} (expected e.g. 500K, 2M or 1G bytes per second)",
+                trimmed
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("1024"), Ok(1024));
+        assert_eq!(parse_rate("500K"), Ok(500 * 1024));
+        assert_eq!(parse_rate("2m"), Ok(2 * 1024 * 1024));
+        assert_eq!(parse_rate("1.5MB/s"), Ok(3 * 512 * 1024));
+        assert_eq!(parse_rate("0"), Ok(0));
+        assert!(parse_rate("fast").is_err());
+        assert!(parse_rate("-1M").is_err());
+    }
+
+    #[test]
+    fn test_rate_limiter_reserve() {
+        let limiter = RateLimiter::new(1000);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve(0, start), Duration::ZERO);
+        let delay = limiter.reserve(500, start);
+        assert!(delay > Duration::from_millis(450) && delay <= Duration::from_millis(500));
+        let delay = limiter.reserve(500, start);
+        assert!(delay > Duration::from_millis(950) && delay <= Duration::from_millis(1000));
+
+        // Idle time only earns up to MAX_BURST of credit
+        let later = start + Duration::from_secs(60);
+        assert_eq!(limiter.reserve(1000, later), Duration::ZERO);
+        assert!(limiter.reserve(1000, later) > Duration::from_millis(900));
+    }
+}
@@ -0,0 +1,410 @@
+//! How requested versions were matched against the manifest
+//!
+//! A request for MSVC `14.44` installs the newest `14.44.x` build, and an SDK
+//! build number such as `26100` selects `10.0.26100.0`; [`MATCH_HOST`] selects
+//! the SDK matching the running Windows build. The downloaders
+//! describe each such decision in a [`ResolutionReport`], passed to
+//! [`ProgressHandler::on_version_resolved`](super::ProgressHandler::on_version_resolved)
+//! before downloading and kept on the returned
+//! [`InstallInfo`](crate::installer::InstallInfo).
+//!
+//! The matching rules are those of [`DefaultVersionResolver`]; embedders with
+//! their own version policy set a [`VersionResolver`] on
+//! [`DownloadOptions`](super::DownloadOptions).
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::VsManifest;
+use crate::error::{MsvcKitError, Result};
+use crate::warning::{Warning, WarningCode};
+
+/// SDK version request selecting the SDK for the running Windows build
+///
+/// Picks the SDK whose build number equals the host's Windows build, or the
+/// newest one below it, so the headers expose the API surface of that build.
+pub const MATCH_HOST: &str = "match-host";
+
+/// How a requested version matched a manifest version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchKind {
+    /// No version was requested; the newest one was selected
+    Latest,
+    /// The requested version is listed as is
+    Exact,
+    /// The requested version is a prefix of the selected one (`14.44` -> `14.44.35207`)
+    Prefix,
+    /// An SDK build number selected the full version (`26100` -> `10.0.26100.0`)
+    BuildNumber,
+    /// The selected version only contains the requested one
+    Fallback,
+    /// The requested version is not listed in the manifest and was used as given
+    Unlisted,
+    /// No version was requested; the stable channel selected a tested one
+    Stable,
+    /// [`MATCH_HOST`] selected the SDK for the running Windows build
+    HostBuild,
+}
+
+impl MatchKind {
+    /// Whether the selected version may differ from what the user expected
+    pub fn is_inexact(&self) -> bool {
+        !matches!(
+            self,
+            MatchKind::Latest | MatchKind::Exact | MatchKind::Stable | MatchKind::HostBuild
+        )
+    }
+}
+
+impl std::fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchKind::Latest => write!(f, "latest"),
+            MatchKind::Exact => write!(f, "exact match"),
+            MatchKind::Prefix => write!(f, "prefix match"),
+            MatchKind::BuildNumber => write!(f, "build number match"),
+            MatchKind::Fallback => write!(f, "fallback"),
+            MatchKind::Unlisted => write!(f, "not in manifest"),
+            MatchKind::Stable => write!(f, "known good"),
+            MatchKind::HostBuild => write!(f, "host build match"),
+        }
+    }
+}
+
+/// Requested and selected version of one component
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolutionReport {
+    /// Component name ("MSVC", "Windows SDK")
+    pub component: String,
+    /// Version that was requested (None = latest)
+    pub requested: Option<String>,
+    /// Version used to select packages, e.g. `14.44` or `10.0.26100.0`
+    pub resolved: String,
+    /// Full build version, e.g. `14.44.35207`, if the manifest lists it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub full_version: Option<String>,
+    /// How the requested version matched
+    pub kind: MatchKind,
+}
+
+impl std::fmt::Display for ResolutionReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let selected = self.full_version.as_deref().unwrap_or(&self.resolved);
+        let unpinned = if self.kind == MatchKind::Stable {
+            "stable"
+        } else {
+            "latest"
+        };
+        write!(
+            f,
+            "{} {} -> {} ({})",
+            self.component,
+            self.requested.as_deref().unwrap_or(unpinned),
+            selected,
+            self.kind
+        )
+    }
+}
+
+/// How `selected` matches `requested`
+fn match_kind(requested: Option<&str>, selected: &str) -> MatchKind {
+    let Some(requested) = requested else {
+        return MatchKind::Latest;
+    };
+    if selected.eq_ignore_ascii_case(requested) {
+        MatchKind::Exact
+    } else if selected.starts_with(&format!("{}.", requested)) {
+        MatchKind::Prefix
+    } else if selected.split('.').nth(2) == Some(requested) {
+        MatchKind::BuildNumber
+    } else {
+        MatchKind::Fallback
+    }
+}
+
+/// Report for MSVC packages selected by the version prefix `resolved`
+pub(crate) fn resolve_msvc(
+    manifest: &VsManifest,
+    requested: Option<&str>,
+    resolved: &str,
+) -> ResolutionReport {
+    let full_version = manifest.resolve_msvc_version(resolved);
+    let kind = match (&full_version, requested) {
+        (Some(full), _) => match_kind(requested, full),
+        (None, Some(_)) => MatchKind::Unlisted,
+        (None, None) => MatchKind::Latest,
+    };
+    ResolutionReport {
+        component: "MSVC".to_string(),
+        requested: requested.map(str::to_string),
+        resolved: resolved.to_string(),
+        full_version,
+        kind,
+    }
+}
+
+impl ResolutionReport {
+    /// Warning for a version that was not found as requested
+    ///
+    /// Only [`MatchKind::Fallback`] and [`MatchKind::Unlisted`] warn; prefix
+    /// and build number matches select what was asked for.
+    pub fn warning(&self) -> Option<Warning> {
+        matches!(self.kind, MatchKind::Fallback | MatchKind::Unlisted).then(|| {
+            Warning::new(WarningCode::FallbackVersion, self.to_string())
+                .with_context(&self.component)
+        })
+    }
+
+    /// Mark a version picked by the stable channel rather than requested
+    pub(crate) fn chosen_by_stable_channel(mut self) -> Self {
+        self.requested = None;
+        self.kind = MatchKind::Stable;
+        self
+    }
+}
+
+/// Report for the Windows SDK version selected for `requested` (None = latest)
+///
+/// Returns None if the manifest lists no SDK and none was requested, or if
+/// [`MATCH_HOST`] was requested and no SDK fits the host's Windows build.
+pub(crate) fn resolve_sdk(
+    manifest: &VsManifest,
+    requested: Option<&str>,
+) -> Option<ResolutionReport> {
+    if requested.is_some_and(is_match_host) {
+        return resolve_sdk_for_build(manifest, host_windows_build()?);
+    }
+    let (resolved, kind) = match requested {
+        None => {
+            let latest = manifest.get_latest_sdk_version()?;
+            (latest, MatchKind::Latest)
+        }
+        Some(requested) => match manifest.resolve_sdk_version(requested) {
+            Some(version) => {
+                let kind = match_kind(Some(requested), &version);
+                (version, kind)
+            }
+            None => (requested.to_string(), MatchKind::Unlisted),
+        },
+    };
+    Some(ResolutionReport {
+        component: "Windows SDK".to_string(),
+        requested: requested.map(str::to_string),
+        full_version: Some(resolved.clone()).filter(|_| kind != MatchKind::Unlisted),
+        resolved,
+        kind,
+    })
+}
+
+/// Report for the newest SDK whose build number is at most `build`
+pub(crate) fn resolve_sdk_for_build(manifest: &VsManifest, build: u32) -> Option<ResolutionReport> {
+    let resolved = manifest.sdk_version_for_build(build)?;
+    Some(ResolutionReport {
+        component: "Windows SDK".to_string(),
+        requested: Some(MATCH_HOST.to_string()),
+        full_version: Some(resolved.clone()),
+        resolved,
+        kind: MatchKind::HostBuild,
+    })
+}
+
+/// Error for an SDK request that matches nothing in the manifest
+fn sdk_not_found(manifest: &VsManifest, requested: Option<&str>) -> MsvcKitError {
+    let available = manifest.list_sdk_versions();
+    let message = match requested
+        .filter(|r| is_match_host(r))
+        .map(|_| host_windows_build())
+    {
+        Some(None) => {
+            return MsvcKitError::UnsupportedPlatform(
+                "Cannot determine the Windows build for --sdk-version match-host".to_string(),
+            )
+        }
+        Some(Some(build)) => format!("No Windows SDK for Windows build {} or older", build),
+        None => "No Windows SDK version found".to_string(),
+    };
+    MsvcKitError::VersionNotFound(format!("{}. Available: {:?}", message, available))
+}
+
+/// Picks the MSVC and Windows SDK versions to install from a manifest
+///
+/// `requested` is the version from [`DownloadOptions`](super::DownloadOptions),
+/// or the one chosen by the stable channel, and None when neither is set. The
+/// downloaders select packages for [`ResolutionReport::resolved`], so it must
+/// be a version the manifest lists. Implementations usually delegate to
+/// [`DefaultVersionResolver`] and adjust its answer.
+///
+/// # Example
+///
+/// ```rust
+/// use std::sync::Arc;
+/// use msvc_kit::downloader::{DefaultVersionResolver, ResolutionReport, VersionResolver, VsManifest};
+/// use msvc_kit::DownloadOptions;
+///
+/// /// Never install an MSVC newer than the one QA approved
+/// struct QaApproved;
+///
+/// impl VersionResolver for QaApproved {
+///     fn resolve_msvc(
+///         &self,
+///         manifest: &VsManifest,
+///         requested: Option<&str>,
+///     ) -> msvc_kit::Result<ResolutionReport> {
+///         DefaultVersionResolver.resolve_msvc(manifest, requested.or(Some("14.42")))
+///     }
+///
+///     fn resolve_sdk(
+///         &self,
+///         manifest: &VsManifest,
+///         requested: Option<&str>,
+///     ) -> msvc_kit::Result<ResolutionReport> {
+///         DefaultVersionResolver.resolve_sdk(manifest, requested)
+///     }
+/// }
+///
+/// let options = DownloadOptions::builder()
+///     .version_resolver(Arc::new(QaApproved))
+///     .build();
+/// ```
+pub trait VersionResolver: Send + Sync {
+    /// MSVC toolset version to install for `requested`
+    fn resolve_msvc(
+        &self,
+        manifest: &VsManifest,
+        requested: Option<&str>,
+    ) -> Result<ResolutionReport>;
+
+    /// Windows SDK version to install for `requested`
+    fn resolve_sdk(
+        &self,
+        manifest: &VsManifest,
+        requested: Option<&str>,
+    ) -> Result<ResolutionReport>;
+}
+
+/// Type alias for boxed version resolver
+pub type BoxedVersionResolver = Arc<dyn VersionResolver>;
+
+/// The built-in version matching
+///
+/// MSVC requests are version prefixes (`14.44` selects the newest
+/// `14.44.x`); SDK requests are full versions, prefixes, build numbers
+/// (`26100`) or [`MATCH_HOST`]. Without a request the newest version is used.
+/// A requested version missing from the manifest is used as given
+/// ([`MatchKind::Unlisted`]), so the package lookup reports what is missing.
+/// Fails with [`MsvcKitError::VersionNotFound`] if nothing was requested and
+/// the manifest lists no version, or if no SDK fits the host build.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultVersionResolver;
+
+impl VersionResolver for DefaultVersionResolver {
+    fn resolve_msvc(
+        &self,
+        manifest: &VsManifest,
+        requested: Option<&str>,
+    ) -> Result<ResolutionReport> {
+        let version = requested
+            .map(str::to_string)
+            .or_else(|| manifest.get_latest_msvc_version())
+            .ok_or_else(|| {
+                MsvcKitError::VersionNotFound(format!(
+                    "No MSVC version found. Available: {:?}",
+                    manifest.list_msvc_versions()
+                ))
+            })?;
+        Ok(resolve_msvc(manifest, requested, &version))
+    }
+
+    fn resolve_sdk(
+        &self,
+        manifest: &VsManifest,
+        requested: Option<&str>,
+    ) -> Result<ResolutionReport> {
+        resolve_sdk(manifest, requested).ok_or_else(|| sdk_not_found(manifest, requested))
+    }
+}
+
+/// Whether `requested` is [`MATCH_HOST`]
+pub fn is_match_host(requested: &str) -> bool {
+    requested.trim().eq_ignore_ascii_case(MATCH_HOST)
+}
+
+/// Build number of the running Windows, e.g. `22631`
+///
+/// Read from `CurrentBuildNumber` under
+/// `HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion`; None on other
+/// platforms.
+#[cfg(windows)]
+pub fn host_windows_build() -> Option<u32> {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion")
+        .and_then(|key| key.get_value::<String, _>("CurrentBuildNumber"))
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Build number of the running Windows, e.g. `22631`
+///
+/// Read from `CurrentBuildNumber` under
+/// `HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion`; None on other
+/// platforms.
+#[cfg(not(windows))]
+pub fn host_windows_build() -> Option<u32> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_kind() {
+        assert_eq!(match_kind(None, "14.44.35207"), MatchKind::Latest);
+        assert_eq!(
+            match_kind(Some("14.44.35207"), "14.44.35207"),
+            MatchKind::Exact
+        );
+        assert_eq!(match_kind(Some("14.44"), "14.44.35207"), MatchKind::Prefix);
+        assert_eq!(
+            match_kind(Some("26100"), "10.0.26100.0"),
+            MatchKind::BuildNumber
+        );
+        assert_eq!(
+            match_kind(Some("0.26100"), "10.0.26100.0"),
+            MatchKind::Fallback
+        );
+        assert!(MatchKind::Prefix.is_inexact());
+        assert!(!MatchKind::Exact.is_inexact());
+    }
+
+    #[test]
+    fn test_resolution_report_display() {
+        let report = ResolutionReport {
+            component: "MSVC".to_string(),
+            requested: Some("14.44".to_string()),
+            resolved: "14.44".to_string(),
+            full_version: Some("14.44.35207".to_string()),
+            kind: MatchKind::Prefix,
+        };
+        assert_eq!(
+            report.to_string(),
+            "MSVC 14.44 -> 14.44.35207 (prefix match)"
+        );
+
+        assert!(report.warning().is_none());
+
+        let stable = report.chosen_by_stable_channel();
+        assert_eq!(
+            stable.to_string(),
+            "MSVC stable -> 14.44.35207 (known good)"
+        );
+        assert!(!stable.kind.is_inexact());
+    }
+}
@@ -0,0 +1,199 @@
+//! Resuming interrupted downloads
+//!
+//! Every download run records its totals and throughput in the download
+//! index (see [`RunProgress`]). A restarted run starts from the payloads
+//! already on disk; [`ResumeSummary`] tells how far the previous run got and,
+//! from the throughput it measured, how long the rest should take, before
+//! the new run has measured anything itself.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::index::{DownloadIndex, RunProgress};
+use crate::error::Result;
+
+/// Download subdirectories and the component name their runs are recorded under
+const COMPONENT_DIRS: &[(&str, &str)] = &[("msvc", "MSVC"), ("sdk", "Windows SDK")];
+
+/// Progress of a download that is being (or can be) resumed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeSummary {
+    /// Component name, e.g. `MSVC`
+    pub component: String,
+    /// Directory holding the payloads and `index.db`
+    pub download_dir: PathBuf,
+    /// Payloads in the run
+    pub total_files: usize,
+    /// Payloads already on disk
+    pub completed_files: usize,
+    /// Bytes of all payloads in the run
+    pub total_bytes: u64,
+    /// Bytes of the payloads already on disk
+    pub completed_bytes: u64,
+    /// Throughput measured by earlier runs, in bytes per second
+    pub bytes_per_sec: Option<u64>,
+}
+
+impl ResumeSummary {
+    /// Summary of a recorded run
+    pub fn from_run(download_dir: impl Into<PathBuf>, run: &RunProgress) -> Self {
+        Self {
+            component: run.component.clone(),
+            download_dir: download_dir.into(),
+            total_files: run.total_files,
+            completed_files: run.completed_files,
+            total_bytes: run.total_bytes,
+            completed_bytes: run.completed_bytes,
+            bytes_per_sec: (run.bytes_per_sec > 0).then_some(run.bytes_per_sec),
+        }
+    }
+
+    /// Share of the bytes already on disk, from 0 to 100
+    pub fn percent(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 100.0;
+        }
+        self.completed_bytes as f64 * 100.0 / self.total_bytes as f64
+    }
+
+    /// Bytes still to download
+    pub fn remaining_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.completed_bytes)
+    }
+
+    /// Time the remaining bytes take at the throughput of earlier runs
+    pub fn eta(&self) -> Option<Duration> {
+        self.bytes_per_sec
+            .map(|rate| Duration::from_secs(self.remaining_bytes().div_ceil(rate)))
+    }
+}
+
+impl fmt::Display for ResumeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = |bytes: u64| humansize::format_size(bytes, humansize::BINARY);
+        write!(
+            f,
+            "resuming: {:.0}% already downloaded ({} of {})",
+            self.percent().floor(),
+            size(self.completed_bytes),
+            size(self.total_bytes)
+        )?;
+        if let Some(eta) = self.eta() {
+            write!(f, ", about {} left", format_duration(eta))?;
+        }
+        Ok(())
+    }
+}
+
+/// `1h 05m`, `3m 20s` or `45s`
+pub(crate) fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match (secs / 3600, secs / 60 % 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {:02}s", m, s),
+        (h, m, _) => format!("{}h {:02}m", h, m),
+    }
+}
+
+/// Interrupted downloads below `target_dir` that the next run resumes
+///
+/// Lists every download directory whose last run stopped before all of its
+/// payloads were on disk. Directories whose index cannot be opened (e.g.
+/// because another process is downloading into them) are skipped.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::downloader::resume_summary;
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// for summary in resume_summary("C:/msvc-kit".as_ref()).await? {
+///     println!("{}: {}", summary.component, summary);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub async fn resume_summary(target_dir: &Path) -> Result<Vec<ResumeSummary>> {
+    let mut summaries = Vec::new();
+    for (subdir, component) in COMPONENT_DIRS {
+        let Ok(mut dirs) = tokio::fs::read_dir(target_dir.join("downloads").join(subdir)).await
+        else {
+            continue;
+        };
+        while let Some(dir) = dirs.next_entry().await? {
+            let index_path = dir.path().join("index.db");
+            if !index_path.is_file() {
+                continue;
+            }
+            let run = match DownloadIndex::read_run_progress(&index_path, component).await {
+                Ok(run) => run,
+                Err(e) => {
+                    tracing::debug!("Skipping {:?}: {}", index_path, e);
+                    None
+                }
+            };
+            if let Some(run) = run.filter(|r| !r.is_finished()) {
+                summaries.push(ResumeSummary::from_run(dir.path(), &run));
+            }
+        }
+    }
+    summaries.sort_by(|a, b| a.download_dir.cmp(&b.download_dir));
+    Ok(summaries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn run(completed_files: usize, completed_bytes: u64, bytes_per_sec: u64) -> RunProgress {
+        RunProgress {
+            component: "MSVC".to_string(),
+            total_files: 10,
+            total_bytes: 1000,
+            completed_files,
+            completed_bytes,
+            bytes_per_sec,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_summary_display() {
+        let summary = ResumeSummary::from_run("downloads/msvc/14_44_x64_x64", &run(6, 620, 2));
+        assert_eq!(summary.eta(), Some(Duration::from_secs(190)));
+        assert_eq!(
+            summary.to_string(),
+            "resuming: 62% already downloaded (620 B of 1000 B), about 3m 10s left"
+        );
+
+        let summary = ResumeSummary::from_run("dir", &run(6, 620, 0));
+        assert_eq!(summary.eta(), None);
+        assert!(!summary.to_string().contains("left"));
+    }
+
+    #[tokio::test]
+    async fn test_resume_summary() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let msvc = temp_dir.path().join("downloads/msvc/14_44_x64_x64");
+        let sdk = temp_dir.path().join("downloads/sdk/26100_x64");
+
+        let mut finished = run(10, 1000, 2);
+        finished.component = "Windows SDK".to_string();
+        for (dir, run) in [(&msvc, run(6, 620, 2)), (&sdk, finished)] {
+            let mut index = DownloadIndex::load(&dir.join("index.db")).await.unwrap();
+            index.record_run_progress(&run).await.unwrap();
+        }
+
+        let summaries = resume_summary(temp_dir.path()).await.unwrap();
+        assert_eq!(summaries.len(), 1);
+        assert_eq!(summaries[0].download_dir, msvc);
+        assert_eq!(summaries[0].completed_files, 6);
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(resume_summary(empty.path()).await.unwrap().is_empty());
+    }
+}
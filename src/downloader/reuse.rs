@@ -0,0 +1,307 @@
+//! Reuse of an existing equivalent install
+//!
+//! Provisioning scripts run `download` on every CI job, usually for a version
+//! that is already installed. Every download records an [`InstallStamp`]
+//! listing the payloads it selected. When the same component, version,
+//! architecture and package selection is requested again, and the receipts
+//! of all those payloads show their files on disk, the download returns the
+//! existing install right away, without loading the manifest, and marks it
+//! [`InstallInfo::reused`](crate::installer::InstallInfo::reused).
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::DownloadOptions;
+use crate::error::Result;
+use crate::installer::{missing_files, InstallInfo, RECEIPT_DIR_NAME};
+use crate::version::{is_msvc_installed, is_sdk_installed, Architecture};
+
+/// File name suffix of install stamps in the receipt directory
+pub const INSTALL_STAMP_SUFFIX: &str = ".install.json";
+
+/// What a download of one component selected, stored next to the package receipts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallStamp {
+    /// Component type (msvc, sdk)
+    pub component: String,
+    /// Version as requested, e.g. `14.44` (None = latest)
+    pub requested: Option<String>,
+    /// Version the request resolved to
+    pub version: String,
+    /// Target architecture
+    pub arch: Architecture,
+    /// Options affecting which packages and files were selected
+    pub selection: String,
+    /// Payload file names, each with a receipt once extracted
+    pub packages: Vec<String>,
+}
+
+impl InstallStamp {
+    /// Stamp of a download that produced `info` with `options`
+    pub(crate) fn new(
+        options: &DownloadOptions,
+        requested: Option<String>,
+        info: &InstallInfo,
+    ) -> Self {
+        Self {
+            component: info.component_type.clone(),
+            requested,
+            version: info.version.clone(),
+            arch: info.arch,
+            selection: selection_key(options),
+            packages: info
+                .downloaded_files
+                .iter()
+                .filter_map(|f| f.file_name().and_then(|n| n.to_str()))
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+
+    /// Path of the stamp for `component` `version` `arch` under `install_dir`
+    pub fn path_for(
+        install_dir: &Path,
+        component: &str,
+        version: &str,
+        arch: Architecture,
+    ) -> PathBuf {
+        install_dir.join(RECEIPT_DIR_NAME).join(format!(
+            "{}-{}-{}{}",
+            component, version, arch, INSTALL_STAMP_SUFFIX
+        ))
+    }
+
+    /// Write the stamp as JSON
+    pub async fn write(&self, install_dir: &Path) -> Result<()> {
+        let path = Self::path_for(install_dir, &self.component, &self.version, self.arch);
+        tokio::fs::create_dir_all(install_dir.join(RECEIPT_DIR_NAME)).await?;
+        tokio::fs::write(path, serde_json::to_vec_pretty(self)?).await?;
+        Ok(())
+    }
+
+    /// Stamps of `component` `arch` under `install_dir`
+    async fn list(install_dir: &Path, component: &str, arch: Architecture) -> Vec<Self> {
+        let mut stamps = Vec::new();
+        let Ok(mut entries) = tokio::fs::read_dir(install_dir.join(RECEIPT_DIR_NAME)).await else {
+            return stamps;
+        };
+        let prefix = format!("{}-", component);
+        let suffix = format!("-{}{}", arch, INSTALL_STAMP_SUFFIX);
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !name.starts_with(&prefix) || !name.ends_with(&suffix) {
+                continue;
+            }
+            if let Ok(bytes) = tokio::fs::read(entry.path()).await {
+                if let Ok(stamp) = serde_json::from_slice::<Self>(&bytes) {
+                    stamps.push(stamp);
+                }
+            }
+        }
+        stamps
+    }
+
+    fn is_installed(&self, install_dir: &Path) -> bool {
+        match self.component.as_str() {
+            "msvc" => is_msvc_installed(install_dir, &self.version),
+            "sdk" => is_sdk_installed(install_dir, &self.version),
+            _ => false,
+        }
+    }
+}
+
+/// Options that change which packages or files a download selects
+fn selection_key(options: &DownloadOptions) -> String {
+    let sorted = |items: Vec<String>| {
+        let mut items = items;
+        items.sort();
+        items
+    };
+    format!(
+        "channel={:?};host={:?};headers_only={};components={:?};platforms={:?};\
+         exclude={:?};only={:?};sdk_installers={:?};filters={:?}",
+        options.channel,
+        options.host_arch,
+        options.headers_only,
+        sorted(
+            options
+                .include_components
+                .iter()
+                .map(|c| format!("{:?}", c))
+                .collect()
+        ),
+        sorted(
+            options
+                .app_platforms
+                .iter()
+                .map(|p| format!("{:?}", p))
+                .collect()
+        ),
+        options.exclude_patterns,
+        options.only_patterns,
+        options.sdk_installer_patterns,
+        options.effective_extract_filters(),
+    )
+}
+
+/// Existing install of `component` matching `requested` and `options`, if intact
+///
+/// Only an explicitly requested version qualifies: what "latest" means can
+/// only be answered by the manifest. A custom
+/// [`VersionResolver`](super::VersionResolver) decides versions itself, so
+/// it disables reuse too.
+pub(crate) async fn find_reusable(
+    options: &DownloadOptions,
+    component: &str,
+    requested: Option<&str>,
+) -> Option<InstallInfo> {
+    let requested = requested?;
+    if options.version_resolver.is_some() || options.layout_dir.is_some() {
+        return None;
+    }
+    let install_dir = &options.target_dir;
+    let selection = selection_key(options);
+
+    for stamp in InstallStamp::list(install_dir, component, options.arch).await {
+        let same_version =
+            stamp.requested.as_deref() == Some(requested) || stamp.version == requested;
+        if !same_version
+            || stamp.selection != selection
+            || stamp.packages.is_empty()
+            || !stamp.is_installed(install_dir)
+        {
+            continue;
+        }
+        if !missing_files(install_dir, &stamp.packages).await.is_empty() {
+            tracing::debug!(
+                "{} {} is installed but incomplete, downloading again",
+                component,
+                stamp.version
+            );
+            continue;
+        }
+        tracing::info!(
+            "Reusing existing {} {} install in {}",
+            component,
+            stamp.version,
+            install_dir.display()
+        );
+        return Some(InstallInfo {
+            component_type: component.to_string(),
+            version: stamp.version,
+            install_path: install_dir.clone(),
+            downloaded_files: Vec::new(),
+            arch: options.arch,
+            extract_filters: options.effective_extract_filters(),
+            cleanup: options.auto_cleanup,
+            resolution: None,
+            warnings: Vec::new(),
+            reused: true,
+        });
+    }
+    None
+}
+
+/// Record the download that produced `info`, logging rather than failing on errors
+pub(crate) async fn record_install(
+    options: &DownloadOptions,
+    requested: Option<String>,
+    info: &InstallInfo,
+) {
+    let stamp = InstallStamp::new(options, requested, info);
+    if let Err(e) = stamp.write(&options.target_dir).await {
+        tracing::warn!("Failed to write install stamp: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::{PackageReceipt, ReceiptEntry};
+
+    async fn installed(root: &Path, options: &DownloadOptions) -> InstallInfo {
+        let file = "VC/Tools/MSVC/14.44.34823/include/vector";
+        std::fs::create_dir_all(root.join(file).parent().unwrap()).unwrap();
+        std::fs::write(root.join(file), b"x").unwrap();
+        std::fs::create_dir_all(root.join(RECEIPT_DIR_NAME)).unwrap();
+        let receipt = PackageReceipt {
+            package: "crt.vsix".to_string(),
+            files: vec![ReceiptEntry {
+                path: file.to_string(),
+                sha256: String::new(),
+                size: 1,
+            }],
+        };
+        receipt
+            .write(&PackageReceipt::path_for(root, "crt.vsix"))
+            .await
+            .unwrap();
+
+        let info = InstallInfo {
+            component_type: "msvc".to_string(),
+            version: "14.44.34823".to_string(),
+            install_path: root.to_path_buf(),
+            downloaded_files: vec![root.join("downloads/msvc/crt.vsix")],
+            arch: options.arch,
+            extract_filters: Vec::new(),
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        };
+        record_install(options, Some("14.44".to_string()), &info).await;
+        info
+    }
+
+    #[tokio::test]
+    async fn test_find_reusable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let options = DownloadOptions {
+            target_dir: root.to_path_buf(),
+            ..Default::default()
+        };
+        assert!(find_reusable(&options, "msvc", Some("14.44"))
+            .await
+            .is_none());
+        installed(root, &options).await;
+
+        let info = find_reusable(&options, "msvc", Some("14.44"))
+            .await
+            .unwrap();
+        assert!(info.reused);
+        assert_eq!(info.version, "14.44.34823");
+        assert!(info.downloaded_files.is_empty());
+        assert!(find_reusable(&options, "msvc", Some("14.44.34823"))
+            .await
+            .is_some());
+
+        // Latest, another version, component, architecture or selection
+        assert!(find_reusable(&options, "msvc", None).await.is_none());
+        assert!(find_reusable(&options, "msvc", Some("14.43"))
+            .await
+            .is_none());
+        assert!(find_reusable(&options, "sdk", Some("14.44"))
+            .await
+            .is_none());
+        let arm64 = DownloadOptions {
+            arch: Architecture::Arm64,
+            ..options.clone()
+        };
+        assert!(find_reusable(&arm64, "msvc", Some("14.44")).await.is_none());
+        let headers = DownloadOptions {
+            headers_only: true,
+            ..options.clone()
+        };
+        assert!(find_reusable(&headers, "msvc", Some("14.44"))
+            .await
+            .is_none());
+
+        // A file listed in a receipt went missing
+        std::fs::remove_file(root.join("VC/Tools/MSVC/14.44.34823/include/vector")).unwrap();
+        assert!(find_reusable(&options, "msvc", Some("14.44"))
+            .await
+            .is_none());
+    }
+}
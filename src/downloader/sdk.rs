@@ -1,14 +1,19 @@
 //! Windows SDK download functionality
 
 use async_trait::async_trait;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 
-use super::http::create_http_client;
+use super::headers::{cab_name, filter_payloads, is_sdk_header_installer, referenced_cabs};
+use super::manifest::{Package, VsManifest};
 use super::traits::{ComponentDownloader, ComponentType};
 use super::{
-    common::CommonDownloader, DownloadOptions, DownloadPreview, PackagePreview, VsManifest,
+    check_target_dir, common::CommonDownloader, reuse, DownloadOptions, DownloadPreview,
+    PackagePreview, SizeEstimator,
 };
 use crate::error::{MsvcKitError, Result};
-use crate::installer::InstallInfo;
+use crate::installer::{missing_cabinets, InstallInfo};
+use crate::warning::Warning;
 
 /// Windows SDK downloader
 pub struct SdkDownloader {
@@ -18,10 +23,7 @@ pub struct SdkDownloader {
 impl SdkDownloader {
     /// Create a new SDK downloader
     pub fn new(options: DownloadOptions) -> Self {
-        let client = options
-            .http_client
-            .clone()
-            .unwrap_or_else(create_http_client);
+        let client = options.client();
         let progress_handler = options.progress_handler.clone();
         let cache_manager = options.cache_manager.clone();
 
@@ -36,39 +38,50 @@ impl SdkDownloader {
         Self { downloader }
     }
 
+    /// SDK version requested explicitly or by the stable channel
+    ///
+    /// The flag is set when the stable channel chose the version.
+    async fn requested_version(&self, manifest: &VsManifest) -> Result<(Option<String>, bool)> {
+        if let Some(version) = &self.downloader.options.sdk_version {
+            return Ok((Some(version.clone()), false));
+        }
+        let stable = self.downloader.stable_combo(manifest).await?;
+        let from_stable = stable.is_some();
+        Ok((stable.map(|combo| combo.sdk), from_stable))
+    }
+
     /// Preview what would be downloaded (dry-run mode)
     pub async fn preview(&self) -> Result<DownloadPreview> {
-        let manifest = VsManifest::fetch().await?;
+        let manifest = self.downloader.load_manifest().await?;
 
-        let available_versions = manifest.list_sdk_versions();
+        let (requested, _) = self.requested_version(&manifest).await?;
         let version = self
             .downloader
-            .options
-            .sdk_version
-            .clone()
-            .or_else(|| manifest.get_latest_sdk_version())
-            .ok_or_else(|| {
-                MsvcKitError::VersionNotFound(format!(
-                    "No Windows SDK version found. Available: {:?}",
-                    available_versions
-                ))
-            })?;
+            .version_resolver()
+            .resolve_sdk(&manifest, requested.as_deref())?
+            .resolved;
 
         let target_arch = self.downloader.options.arch.to_string();
-        let packages = manifest.find_sdk_packages(&version, &target_arch);
+        let mut packages = manifest.find_sdk_packages(&version, &target_arch);
+
+        // CABs referenced by the selected installers are only known after
+        // downloading them, so the preview lists the installers alone
+        if self.selects_installers() {
+            packages = packages
+                .iter()
+                .map(|p| self.selected_installers(p))
+                .collect();
+        }
 
         let file_count: usize = packages.iter().map(|p| p.payloads.len()).sum();
         let total_size: u64 = packages.iter().map(|p| p.total_size).sum();
+        let installed_size = SizeEstimator::new()
+            .refine_from_install(&self.downloader.options.target_dir, &packages)
+            .await
+            .estimate_total(&packages);
 
-        let package_previews: Vec<PackagePreview> = packages
-            .iter()
-            .map(|p| PackagePreview {
-                id: p.id.clone(),
-                version: p.version.clone(),
-                file_count: p.payloads.len(),
-                size: p.total_size,
-            })
-            .collect();
+        let package_previews: Vec<PackagePreview> =
+            packages.iter().map(PackagePreview::from).collect();
 
         Ok(DownloadPreview {
             component: "Windows SDK".to_string(),
@@ -76,7 +89,9 @@ impl SdkDownloader {
             package_count: packages.len(),
             file_count,
             total_size,
+            installed_size,
             packages: package_previews,
+            host_arch: None,
         })
     }
 
@@ -101,32 +116,44 @@ impl SdkDownloader {
                 install_path: self.downloader.options.target_dir.clone(),
                 downloaded_files: vec![],
                 arch: self.downloader.options.arch,
+                extract_filters: self.downloader.options.effective_extract_filters(),
+                cleanup: self.downloader.options.auto_cleanup,
+                resolution: None,
+                warnings: Vec::new(),
+                reused: false,
             });
         }
 
-        // Use custom cache dir if a cache_manager was injected
-        let cache_dir = self.downloader.manifest_cache_dir();
-        let manifest = VsManifest::fetch_with_cache_dir(&cache_dir).await?;
+        // Fail before downloading if nothing can be extracted there
+        check_target_dir(&self.downloader.options.target_dir)?;
+
+        // An equivalent install is already there; skip the manifest entirely
+        let options = &self.downloader.options;
+        if let Some(info) =
+            reuse::find_reusable(options, "sdk", options.sdk_version.as_deref()).await
+        {
+            return Ok(info);
+        }
+
+        // Uses the custom cache dir if a cache_manager was injected
+        let manifest = self.downloader.load_manifest().await?;
 
         // List available versions for debugging
         let available_versions = manifest.list_sdk_versions();
         tracing::debug!("Available SDK versions: {:?}", available_versions);
 
-        // Determine version to download
-        let version = self
+        // Determine version to download; build numbers and prefixes resolve
+        // to the full version listed in the manifest
+        let (requested, from_stable) = self.requested_version(&manifest).await?;
+        let mut resolution = self
             .downloader
-            .options
-            .sdk_version
-            .clone()
-            .or_else(|| manifest.get_latest_sdk_version())
-            .ok_or_else(|| {
-                MsvcKitError::VersionNotFound(format!(
-                    "No Windows SDK version found. Available: {:?}",
-                    available_versions
-                ))
-            })?;
-
-        tracing::info!("Selected Windows SDK version: {}", version);
+            .version_resolver()
+            .resolve_sdk(&manifest, requested.as_deref())?;
+        if from_stable {
+            resolution = resolution.chosen_by_stable_channel();
+        }
+        self.downloader.report_resolution(&resolution);
+        let version = resolution.resolved.clone();
 
         // Determine target architecture
         let target_arch = self.downloader.options.arch.to_string();
@@ -174,29 +201,198 @@ impl SdkDownloader {
         );
 
         // Download all packages
-        let downloaded_files = self
-            .downloader
-            .download_packages(&packages, &download_dir, "Windows SDK")
-            .await?;
+        let mut warnings: Vec<Warning> = resolution.warning().into_iter().collect();
+        let mut downloaded_files = if self.selects_installers() {
+            self.download_installers(&packages, &download_dir, &mut warnings)
+                .await?
+        } else {
+            let (files, report) = self
+                .downloader
+                .download_packages_with_report(&packages, &download_dir, "Windows SDK")
+                .await?;
+            warnings.extend(report.warnings());
+            files
+        };
+        self.ensure_cabinets(
+            &packages,
+            &download_dir,
+            &mut downloaded_files,
+            &mut warnings,
+        )
+        .await?;
 
         tracing::info!("Downloaded {} SDK packages", downloaded_files.len());
 
         // Return InstallInfo with target_dir as install_path (not extracted yet)
-        Ok(InstallInfo {
+        let info = InstallInfo {
             component_type: "sdk".to_string(),
             version,
             install_path: self.downloader.options.target_dir.clone(),
             downloaded_files,
             arch: self.downloader.options.arch,
+            extract_filters: self.downloader.options.effective_extract_filters(),
+            cleanup: self.downloader.options.auto_cleanup,
+            resolution: Some(resolution),
+            warnings,
+            reused: false,
+        };
+        let options = &self.downloader.options;
+        reuse::record_install(options, options.sdk_version.clone(), &info).await;
+        Ok(info)
+    }
+
+    /// Whether only some installers are downloaded (headers-only mode or
+    /// installer patterns)
+    fn selects_installers(&self) -> bool {
+        let options = &self.downloader.options;
+        options.headers_only || !options.sdk_installer_patterns.is_empty()
+    }
+
+    /// Copy of `package` keeping the installers selected by the options
+    fn selected_installers(&self, package: &Package) -> Package {
+        let options = &self.downloader.options;
+        let patterns: Vec<String> = options
+            .sdk_installer_patterns
+            .iter()
+            .map(|p| p.trim().to_lowercase())
+            .collect();
+        filter_payloads(package, |payload| {
+            let name = payload.file_name.to_lowercase();
+            name.ends_with(".msi")
+                && (!options.headers_only || is_sdk_header_installer(&payload.file_name))
+                && (patterns.is_empty() || patterns.iter().any(|p| name.contains(p)))
         })
     }
 
+    /// Download only the selected installers and the CABs they reference
+    ///
+    /// Hash mismatches kept in warn mode are added to `warnings`.
+    async fn download_installers(
+        &self,
+        packages: &[Package],
+        download_dir: &Path,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<Vec<PathBuf>> {
+        let installers: Vec<Package> = packages
+            .iter()
+            .map(|p| self.selected_installers(p))
+            .collect();
+        if installers.iter().all(|p| p.payloads.is_empty()) {
+            return Err(MsvcKitError::ComponentNotFound(format!(
+                "No Windows SDK installers match {:?}",
+                self.downloader.options.sdk_installer_patterns
+            )));
+        }
+        let label = if self.downloader.options.headers_only {
+            "Windows SDK headers"
+        } else {
+            "Windows SDK"
+        };
+        let (mut files, report) = self
+            .downloader
+            .download_packages_with_report(&installers, download_dir, label)
+            .await?;
+        warnings.extend(report.warnings());
+
+        let mut msi_contents = Vec::with_capacity(files.len());
+        for file in &files {
+            msi_contents.push(tokio::fs::read(file).await?);
+        }
+        let cabs: Vec<Package> = packages
+            .iter()
+            .map(|p| referenced_cabs(p, &msi_contents))
+            .collect();
+        tracing::info!(
+            "{} selected installers reference {} CAB files",
+            files.len(),
+            cabs.iter().map(|p| p.payloads.len()).sum::<usize>()
+        );
+
+        let (cab_files, report) = self
+            .downloader
+            .download_packages_with_report(&cabs, download_dir, label)
+            .await?;
+        warnings.extend(report.warnings());
+        files.extend(cab_files);
+        Ok(files)
+    }
+
+    /// Make sure every downloaded MSI has the cabinets it lists next to it
+    ///
+    /// Cabinets left out by the package selection are downloaded into the
+    /// MSI's directory. Cabinets the manifest does not have fail with their
+    /// names before anything is extracted.
+    async fn ensure_cabinets(
+        &self,
+        packages: &[Package],
+        download_dir: &Path,
+        files: &mut Vec<PathBuf>,
+        warnings: &mut Vec<Warning>,
+    ) -> Result<()> {
+        let mut incomplete = Vec::new();
+        for msi in files.iter().filter(|f| is_msi(f)) {
+            match missing_cabinets(msi) {
+                Ok(cabinets) if !cabinets.is_empty() => incomplete.push((msi.clone(), cabinets)),
+                Ok(_) => {}
+                Err(e) => tracing::debug!("Cannot read the cabinets of {:?}: {}", msi, e),
+            }
+        }
+        if incomplete.is_empty() {
+            return Ok(());
+        }
+
+        let wanted: HashSet<String> = incomplete
+            .iter()
+            .flat_map(|(_, cabinets)| cabinets.iter().map(|c| c.to_lowercase()))
+            .collect();
+        let cabs: Vec<Package> = packages
+            .iter()
+            .map(|p| {
+                filter_payloads(p, |payload| {
+                    wanted.contains(&cab_name(&payload.file_name).to_lowercase())
+                })
+            })
+            .collect();
+        if cabs.iter().any(|p| !p.payloads.is_empty()) {
+            tracing::info!(
+                "Downloading {} cabinet files the SDK installers need",
+                cabs.iter().map(|p| p.payloads.len()).sum::<usize>()
+            );
+            let (cab_files, report) = self
+                .downloader
+                .download_packages_with_report(&cabs, download_dir, "Windows SDK")
+                .await?;
+            warnings.extend(report.warnings());
+            files.extend(cab_files);
+        }
+
+        for (msi, _) in incomplete {
+            let cabinets = missing_cabinets(&msi)?;
+            if !cabinets.is_empty() {
+                return Err(MsvcKitError::MissingCabinets {
+                    installer: msi
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    cabinets,
+                });
+            }
+        }
+        Ok(())
+    }
+
     /// Download Windows SDK components
     pub async fn download(&self) -> Result<InstallInfo> {
         self.download_impl().await
     }
 }
 
+/// Error for an SDK request the manifest cannot satisfy
+fn is_msi(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|e| e.eq_ignore_ascii_case("msi"))
+}
+
 #[async_trait]
 impl ComponentDownloader for SdkDownloader {
     async fn download(&self) -> Result<InstallInfo> {
@@ -238,4 +434,55 @@ mod tests {
         let cache_dir = downloader.downloader.manifest_cache_dir();
         assert_eq!(cache_dir, temp_dir.path().join("manifests"));
     }
+
+    #[test]
+    fn selected_installers_honours_patterns() {
+        use crate::downloader::PackagePayload;
+
+        let payload = |name: &str| PackagePayload {
+            file_name: name.to_string(),
+            url: format!("https://example.com/{}", name),
+            size: 1,
+            sha256: None,
+            mirrors: Vec::new(),
+        };
+        let package = Package {
+            id: "Win11SDK_10.0.26100".to_string(),
+            version: "10.0.26100.1".to_string(),
+            package_type: "Exe".to_string(),
+            chip: None,
+            payloads: vec![
+                payload("Installers\\Universal CRT Headers Libraries and Sources-x86_en-us.msi"),
+                payload("Installers\\Windows SDK for Windows Store Apps Headers-x86_en-us.msi"),
+                payload("Installers\\Windows SDK Desktop Libs x64-x86_en-us.msi"),
+                payload("Installers\\0a1b2c.cab"),
+            ],
+            total_size: 4,
+            install_size: 0,
+            optional: false,
+        };
+        let names = |options: DownloadOptions| -> Vec<String> {
+            let downloader = SdkDownloader::new(options);
+            assert!(downloader.selects_installers());
+            downloader
+                .selected_installers(&package)
+                .payloads
+                .into_iter()
+                .map(|p| p.file_name)
+                .collect()
+        };
+
+        let ucrt = names(
+            DownloadOptions::builder()
+                .sdk_installer_pattern("Universal CRT")
+                .build(),
+        );
+        assert_eq!(ucrt.len(), 1);
+        assert!(ucrt[0].contains("Universal CRT"));
+
+        let headers = names(DownloadOptions::builder().headers_only(true).build());
+        assert_eq!(headers.len(), 2);
+
+        assert!(!SdkDownloader::new(DownloadOptions::default()).selects_installers());
+    }
 }
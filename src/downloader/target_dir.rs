@@ -0,0 +1,144 @@
+//! Up-front checks of the install directory
+//!
+//! A target directory with a trailing space, a reserved device name or too
+//! long a prefix is accepted by `create_dir_all` but fails deep inside
+//! extraction, after the downloads. [`check_target_path`] rejects such paths
+//! when the options are validated; [`check_target_dir`] additionally makes
+//! sure files can be created there and runs before downloading and
+//! extracting.
+
+use std::path::{Component, Path};
+
+use crate::error::OptionsError;
+use crate::preflight::{long_paths_enabled, LONGEST_RELATIVE_PATH, MAX_PATH};
+
+/// Device names Windows reserves in every directory, with or without extension
+pub const RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Longest `target_dir` that keeps the deepest extracted path below `MAX_PATH`
+/// (leaving room for the separator and the terminating NUL)
+const MAX_TARGET_LEN: usize = MAX_PATH - 2 - LONGEST_RELATIVE_PATH;
+
+/// Name of the file created and removed by the writability probe
+const PROBE_FILE: &str = ".msvc-kit-write-test";
+
+/// Check that extracted files can be placed below `dir`, without touching the disk
+///
+/// Rejects path components ending in a space or dot and reserved device
+/// names (both are silently altered or refused by Windows), and, unless long
+/// paths are enabled, directories too long for the deepest extracted path to
+/// stay below `MAX_PATH`.
+pub fn check_target_path(dir: &Path) -> Result<(), OptionsError> {
+    for component in dir.components() {
+        let Component::Normal(name) = component else {
+            continue;
+        };
+        let name = name.to_string_lossy();
+        let reason = if name.ends_with(' ') || name.ends_with('.') {
+            format!("'{}' ends with a space or dot", name)
+        } else if is_reserved_name(&name) {
+            format!("'{}' is a reserved device name on Windows", name)
+        } else {
+            continue;
+        };
+        return Err(OptionsError::InvalidTargetDir {
+            path: dir.display().to_string(),
+            reason,
+        });
+    }
+
+    let length = std::path::absolute(dir)
+        .unwrap_or_else(|_| dir.to_path_buf())
+        .as_os_str()
+        .len();
+    if path_too_long(long_paths_enabled(), length) {
+        return Err(OptionsError::TargetDirTooLong {
+            length,
+            limit: MAX_TARGET_LEN,
+        });
+    }
+    Ok(())
+}
+
+/// [`check_target_path`], then create `dir` and probe that files can be written
+pub fn check_target_dir(dir: &Path) -> Result<(), OptionsError> {
+    check_target_path(dir)?;
+
+    let probe = dir.join(PROBE_FILE);
+    std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe))
+        .map_err(|e| OptionsError::TargetDirNotWritable {
+            path: dir.display().to_string(),
+            reason: e.to_string(),
+        })
+}
+
+/// Whether `name` is a reserved device name, e.g. `nul` or `COM1.txt`
+fn is_reserved_name(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or(name).trim_end();
+    RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+}
+
+/// Whether a root of `length` characters pushes the deepest path past `MAX_PATH`
+fn path_too_long(long_paths_enabled: bool, length: usize) -> bool {
+    !long_paths_enabled && length > MAX_TARGET_LEN
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_target_path() {
+        assert!(check_target_path(Path::new("C:/msvc-kit")).is_ok());
+        assert!(check_target_path(Path::new("./tools/msvc-kit")).is_ok());
+
+        for dir in [
+            "tools /msvc-kit",
+            "tools/msvc-kit.",
+            "tools/NUL",
+            "con.d/msvc-kit",
+        ] {
+            assert!(
+                matches!(
+                    check_target_path(Path::new(dir)),
+                    Err(OptionsError::InvalidTargetDir { .. })
+                ),
+                "{}",
+                dir
+            );
+        }
+    }
+
+    #[test]
+    fn test_path_too_long() {
+        assert!(!path_too_long(false, 20));
+        assert!(!path_too_long(false, MAX_TARGET_LEN));
+        assert!(path_too_long(false, MAX_TARGET_LEN + 1));
+        assert!(path_too_long(false, 120));
+        assert!(!path_too_long(true, 300));
+    }
+
+    #[test]
+    fn test_check_target_dir_probe() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("msvc-kit");
+        check_target_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+        assert!(!dir.join(PROBE_FILE).exists());
+
+        // A file where the directory should be
+        let file = temp_dir.path().join("file");
+        std::fs::write(&file, b"").unwrap();
+        assert!(matches!(
+            check_target_dir(&file.join("msvc-kit")),
+            Err(OptionsError::TargetDirNotWritable { .. })
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Importing Visual Studio `.vsconfig` files
+//!
+//! A `.vsconfig` file lists the workload and component ids of a Visual
+//! Studio installation. [`import_vsconfig`] maps the C++-relevant ids onto
+//! msvc-kit's selection ([`MsvcComponent`]s, target architectures and the
+//! Windows SDK version); IDE-only and unsupported ids are reported as ignored.
+
+use std::path::Path;
+
+use serde::Deserialize;
+
+use super::MsvcComponent;
+use crate::error::{MsvcKitError, Result};
+use crate::version::Architecture;
+
+/// Ids covered by msvc-kit's default download (compilers, CRT, SDK tooling)
+const CORE_IDS: &[&str] = &[
+    "microsoft.visualstudio.workload.vctools",
+    "microsoft.visualstudio.workload.nativedesktop",
+    "microsoft.visualstudio.component.vc.corebuildtools",
+    "microsoft.visualstudio.component.vc.coreide",
+    "microsoft.visualstudio.component.vc.tools.x86.x64",
+    "microsoft.visualstudio.component.windows10sdk",
+    "microsoft.visualstudio.component.vc.redist.msm",
+];
+
+/// Raw contents of a `.vsconfig` file
+#[derive(Debug, Deserialize)]
+struct VsConfigFile {
+    #[serde(default)]
+    components: Vec<String>,
+}
+
+/// msvc-kit selection derived from a `.vsconfig` file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VsConfigImport {
+    /// Optional components to include
+    pub components: Vec<MsvcComponent>,
+    /// Target architectures with tools in the file, besides x64/x86
+    pub architectures: Vec<Architecture>,
+    /// Newest Windows SDK listed, e.g. `10.0.22621.0`
+    pub sdk_version: Option<String>,
+    /// Ids without an msvc-kit equivalent (IDE features, other toolchains)
+    pub ignored: Vec<String>,
+}
+
+/// Windows SDK version for a `Windows1xSDK.<build>` component id
+fn sdk_version(id: &str) -> Option<String> {
+    let build = id
+        .strip_prefix("microsoft.visualstudio.component.windows10sdk.")
+        .or_else(|| id.strip_prefix("microsoft.visualstudio.component.windows11sdk."))?;
+    build
+        .chars()
+        .all(|c| c.is_ascii_digit())
+        .then(|| format!("10.0.{}.0", build))
+}
+
+/// What a single lowercase component id selects
+fn map_id(id: &str, import: &mut VsConfigImport) -> bool {
+    let vc = id.strip_prefix("microsoft.visualstudio.component.vc.");
+    let components: &[MsvcComponent] = match vc {
+        Some("atl" | "atl.arm64" | "atl.arm") => &[MsvcComponent::Atl],
+        Some("atlmfc" | "mfc.arm64" | "mfc.arm") => &[MsvcComponent::Atl, MsvcComponent::Mfc],
+        Some(
+            "atl.spectre"
+            | "atlmfc.spectre"
+            | "runtimes.x86.x64.spectre"
+            | "runtimes.arm64.spectre"
+            | "runtimes.arm.spectre"
+            | "mfc.arm64.spectre",
+        ) => &[MsvcComponent::Spectre],
+        Some("asan") => &[MsvcComponent::Asan],
+        Some("cli.support") => &[MsvcComponent::Cli],
+        Some("modules.x86.x64") => &[MsvcComponent::Modules],
+        Some("redist.14.latest") => &[MsvcComponent::Redist],
+        Some("tools.arm64" | "tools.arm64ec") => {
+            import.architectures.push(Architecture::Arm64);
+            &[]
+        }
+        Some("tools.arm") => {
+            import.architectures.push(Architecture::Arm);
+            &[]
+        }
+        _ if id == "microsoft.visualstudio.componentgroup.uwp.vc"
+            || id.starts_with("microsoft.visualstudio.component.uwp.vc") =>
+        {
+            &[MsvcComponent::Uwp]
+        }
+        _ if CORE_IDS.contains(&id) => &[],
+        _ => match sdk_version(id) {
+            Some(version) => {
+                if import.sdk_version.as_ref().is_none_or(|v| *v < version) {
+                    import.sdk_version = Some(version);
+                }
+                &[]
+            }
+            None => return false,
+        },
+    };
+    for component in components {
+        if !import.components.contains(component) {
+            import.components.push(component.clone());
+        }
+    }
+    true
+}
+
+/// Map the component ids of a `.vsconfig` document onto msvc-kit's selection
+pub fn parse_vsconfig(content: &str) -> Result<VsConfigImport> {
+    let file: VsConfigFile = serde_json::from_str(content.trim_start_matches('\u{feff}'))?;
+    let mut import = VsConfigImport::default();
+    for id in file.components {
+        if !map_id(&id.to_lowercase(), &mut import) {
+            import.ignored.push(id);
+        }
+    }
+    import.architectures.dedup();
+    Ok(import)
+}
+
+/// Read a `.vsconfig` file and map it onto msvc-kit's selection
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::downloader::import_vsconfig;
+/// use msvc_kit::DownloadOptions;
+///
+/// let import = import_vsconfig("project/.vsconfig".as_ref())?;
+/// for id in &import.ignored {
+///     eprintln!("Ignoring {}", id);
+/// }
+/// let options = DownloadOptions::builder()
+///     .include_components(import.components)
+///     .build();
+/// # Ok::<(), msvc_kit::MsvcKitError>(())
+/// ```
+pub fn import_vsconfig(path: &Path) -> Result<VsConfigImport> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| MsvcKitError::Config(format!("Failed to read {}: {}", path.display(), e)))?;
+    parse_vsconfig(&content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vsconfig() {
+        let content = r#"{
+            "version": "1.0",
+            "components": [
+                "Microsoft.VisualStudio.Workload.VCTools",
+                "Microsoft.VisualStudio.Component.VC.Tools.x86.x64",
+                "Microsoft.VisualStudio.Component.VC.Tools.ARM64",
+                "Microsoft.VisualStudio.Component.VC.ATLMFC",
+                "Microsoft.VisualStudio.Component.VC.ATL",
+                "Microsoft.VisualStudio.Component.VC.Runtimes.x86.x64.Spectre",
+                "Microsoft.VisualStudio.Component.Windows10SDK.19041",
+                "Microsoft.VisualStudio.Component.Windows11SDK.22621",
+                "Microsoft.VisualStudio.Component.VC.CMake.Project",
+                "Microsoft.VisualStudio.Component.IntelliCode"
+            ]
+        }"#;
+
+        let import = parse_vsconfig(content).unwrap();
+        assert_eq!(
+            import.components,
+            vec![
+                MsvcComponent::Atl,
+                MsvcComponent::Mfc,
+                MsvcComponent::Spectre
+            ]
+        );
+        assert_eq!(import.architectures, vec![Architecture::Arm64]);
+        assert_eq!(import.sdk_version.as_deref(), Some("10.0.22621.0"));
+        assert_eq!(
+            import.ignored,
+            vec![
+                "Microsoft.VisualStudio.Component.VC.CMake.Project".to_string(),
+                "Microsoft.VisualStudio.Component.IntelliCode".to_string(),
+            ]
+        );
+        assert!(parse_vsconfig("not json").is_err());
+    }
+}
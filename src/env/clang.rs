@@ -0,0 +1,95 @@
+//! Clang-compatible argument lists
+//!
+//! bindgen and clang-based tools do not read `INCLUDE`, so the MSVC and SDK
+//! include directories have to be passed on the command line. `-imsvc` marks
+//! them as system headers the same way clang-cl treats `INCLUDE`.
+
+use super::MsvcEnvironment;
+use crate::query::target_triple;
+
+/// Arguments for the clang driver (e.g. bindgen's `clang_args`)
+///
+/// Returns `--target=<triple>` followed by an `-imsvc <path>` pair for each
+/// include path, in `INCLUDE` order.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::env::clang_args;
+/// use msvc_kit::{setup_environment_with_arch, Architecture, InstallInfo};
+///
+/// # fn run(msvc: &InstallInfo, sdk: &InstallInfo) -> msvc_kit::Result<()> {
+/// let env = setup_environment_with_arch(msvc, Some(sdk), Architecture::host(), msvc.arch)?;
+/// let args = clang_args(&env);
+/// // bindgen::Builder::default().clang_args(&args)
+/// # Ok(())
+/// # }
+/// ```
+pub fn clang_args(env: &MsvcEnvironment) -> Vec<String> {
+    let mut args = vec![format!("--target={}", target_triple(env.arch))];
+    for path in &env.include_paths {
+        args.push("-imsvc".to_string());
+        args.push(path.display().to_string());
+    }
+    args
+}
+
+/// Arguments for clang-cl locating the toolchain without environment variables
+///
+/// Returns `/vctoolsdir`, `/winsdkdir` and `/winsdkversion`, letting clang-cl
+/// derive the include and library directories itself.
+pub fn clang_cl_args(env: &MsvcEnvironment) -> Vec<String> {
+    vec![
+        "/vctoolsdir".to_string(),
+        env.vc_tools_install_dir.display().to_string(),
+        "/winsdkdir".to_string(),
+        env.windows_sdk_dir.display().to_string(),
+        "/winsdkversion".to_string(),
+        env.windows_sdk_version.clone(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::Architecture;
+    use std::path::PathBuf;
+
+    fn sample_env() -> MsvcEnvironment {
+        MsvcEnvironment {
+            vc_install_dir: PathBuf::from("C:/msvc-kit/VC"),
+            vc_tools_install_dir: PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823"),
+            vc_tools_version: "14.44.34823".to_string(),
+            windows_sdk_dir: PathBuf::from("C:/msvc-kit/Windows Kits/10"),
+            windows_sdk_version: "10.0.26100.0".to_string(),
+            include_paths: vec![
+                PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823/include"),
+                PathBuf::from("C:/msvc-kit/Windows Kits/10/Include/10.0.26100.0/ucrt"),
+            ],
+            lib_paths: vec![],
+            bin_paths: vec![],
+            arch: Architecture::Arm64,
+            host_arch: Architecture::X64,
+        }
+    }
+
+    #[test]
+    fn test_clang_args() {
+        let args = clang_args(&sample_env());
+        assert_eq!(args[0], "--target=aarch64-pc-windows-msvc");
+        assert_eq!(
+            &args[1..3],
+            ["-imsvc", "C:/msvc-kit/VC/Tools/MSVC/14.44.34823/include"]
+        );
+        assert_eq!(args[3], "-imsvc");
+        assert_eq!(args.len(), 5);
+    }
+
+    #[test]
+    fn test_clang_cl_args() {
+        let args = clang_cl_args(&sample_env());
+        assert_eq!(args[0], "/vctoolsdir");
+        assert_eq!(args[3], "C:/msvc-kit/Windows Kits/10");
+        assert_eq!(args[5], "10.0.26100.0");
+    }
+}
@@ -0,0 +1,140 @@
+//! dotenv and direnv exports
+//!
+//! Per-project environment loaders pick up the toolchain from a file in the
+//! project directory instead of an activated shell: [`dotenv`] writes a `.env`
+//! file for dotenv-style loaders (docker compose, python-dotenv, the
+//! `dotenvy` crate, editors), [`direnv`] a `use_msvc` function for direnv.
+
+use super::{get_env_changes, profile_commands, EnvChange, MsvcEnvironment};
+use crate::scripts::ShellType;
+
+/// `.env` file activating `env`
+///
+/// One `KEY=VALUE` line per variable, sorted by name. Values are
+/// single-quoted, which every dotenv loader takes literally, so backslashes
+/// in Windows paths need no escaping. Values containing a `'`, and the
+/// `INCLUDE`, `LIB` and `PATH` lists, are double-quoted with `\`, `"` and `$`
+/// escaped; the lists end in `;${NAME}` so loaders that expand variables keep
+/// the existing entries after msvc-kit's.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::env::dotenv;
+/// use msvc_kit::{setup_environment_with_arch, Architecture, InstallInfo};
+///
+/// # fn run(msvc: &InstallInfo, sdk: &InstallInfo) -> msvc_kit::Result<()> {
+/// let env = setup_environment_with_arch(msvc, Some(sdk), Architecture::host(), msvc.arch)?;
+/// std::fs::write(".env", dotenv(&env))?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn dotenv(env: &MsvcEnvironment) -> String {
+    let mut changes = get_env_changes(env);
+    changes.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let mut out = String::new();
+    for change in &changes {
+        let value = match change {
+            EnvChange::Set { value, .. } if !value.contains('\'') => format!("'{}'", value),
+            EnvChange::Set { value, .. } => format!("\"{}\"", escape_double_quoted(value)),
+            EnvChange::Prepend { name, entries } => format!(
+                "\"{};${{{}}}\"",
+                escape_double_quoted(&entries.join(";")),
+                name
+            ),
+        };
+        out.push_str(&format!("{}={}\n", change.name(), value));
+    }
+    out
+}
+
+/// direnv `use_msvc` function activating `env`
+///
+/// Save the output as `~/.config/direnv/lib/msvc-kit.sh` and add `use msvc`
+/// to a project's `.envrc`, or paste it into the `.envrc` followed by a
+/// `use_msvc` line. The body is the bash activation of [`profile_commands`],
+/// so `PATH` entries are in MSYS form and the path lists are prepended.
+pub fn direnv(env: &MsvcEnvironment) -> String {
+    let mut out = format!(
+        "# MSVC {} / Windows SDK {} ({}), generated by msvc-kit\nuse_msvc() {{\n",
+        env.vc_tools_version, env.windows_sdk_version, env.arch
+    );
+    for line in profile_commands(&get_env_changes(env), ShellType::Bash).lines() {
+        out.push_str("  ");
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Escape `value` for a double-quoted dotenv value
+fn escape_double_quoted(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '"' | '$' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::Architecture;
+    use std::path::PathBuf;
+
+    fn sample_env() -> MsvcEnvironment {
+        MsvcEnvironment {
+            vc_install_dir: PathBuf::from(r"C:\msvc-kit\VC"),
+            vc_tools_install_dir: PathBuf::from(r"C:\msvc-kit\VC\Tools\MSVC\14.44.34823"),
+            vc_tools_version: "14.44.34823".to_string(),
+            windows_sdk_dir: PathBuf::from(r"C:\msvc-kit\Windows Kits\10"),
+            windows_sdk_version: "10.0.26100.0".to_string(),
+            include_paths: vec![PathBuf::from(r"C:\msvc-kit\include")],
+            lib_paths: vec![PathBuf::from(r"C:\msvc-kit\lib")],
+            bin_paths: vec![PathBuf::from(r"C:\msvc-kit\bin")],
+            arch: Architecture::X64,
+            host_arch: Architecture::X64,
+        }
+    }
+
+    #[test]
+    fn test_dotenv() {
+        let out = dotenv(&sample_env());
+        let lines: Vec<&str> = out.lines().collect();
+        let mut sorted = lines.clone();
+        sorted.sort();
+        assert_eq!(lines, sorted);
+
+        assert!(lines.contains(&r"VCToolsVersion='14.44.34823'"));
+        assert!(lines.contains(&r"VCINSTALLDIR='C:\msvc-kit\VC'"));
+        assert!(lines.contains(&r#"INCLUDE="C:\\msvc-kit\\include;${INCLUDE}""#));
+        assert!(lines.contains(&r#"PATH="C:\\msvc-kit\\bin;${PATH}""#));
+    }
+
+    #[test]
+    fn test_dotenv_escaping() {
+        let mut env = sample_env();
+        env.vc_install_dir = PathBuf::from(r"C:\it's $HOME");
+        let out = dotenv(&env);
+        assert!(out.contains(r#"VCINSTALLDIR="C:\\it's \$HOME""#));
+        assert_eq!(escape_double_quoted("a\"b\nc"), r#"a\"b\nc"#);
+    }
+
+    #[test]
+    fn test_direnv() {
+        let out = direnv(&sample_env());
+        assert!(out.starts_with("# MSVC 14.44.34823 / Windows SDK 10.0.26100.0 (x64)"));
+        assert!(out.contains("use_msvc() {\n  export "));
+        assert!(out.contains(r#"  export PATH="/c/msvc-kit/bin:$PATH""#));
+        assert!(out.ends_with("}\n"));
+    }
+}
@@ -4,7 +4,14 @@
 //! the MSVC toolchain to work correctly, including compatibility with
 //! Rust's cc-rs crate.
 
+mod clang;
+mod dotenv;
+mod profile;
+mod sdk_registry;
 mod setup;
+mod shortcut;
+mod snapshot;
+mod tool;
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -12,11 +19,35 @@ use std::path::{Path, PathBuf};
 
 use crate::error::Result;
 use crate::installer::InstallInfo;
-use crate::version::Architecture;
+use crate::version::{select_host_arch, Architecture, HostArchSelection};
 
+pub use clang::{clang_args, clang_cl_args};
+pub use dotenv::{direnv, dotenv};
+pub use profile::{
+    default_profile_name, default_profiles_dir, list_profiles, profile_commands, profile_path,
+    read_profile, write_profile, PROFILE_EXTENSION,
+};
 pub use setup::{
-    apply_environment, generate_activation_script, generate_all_activation_scripts,
-    save_activation_script, setup_environment,
+    apply_environment, detect_active_environment, detect_active_environment_from,
+    generate_activation_script, generate_all_activation_scripts, save_activation_script,
+    setup_environment_with_arch, ActiveVsEnvironment, EnvAction, EnvPlan, EnvPlanEntry,
+    ACTIVE_ENV_MARKERS,
+};
+
+#[allow(deprecated)]
+pub use setup::setup_environment;
+
+pub use sdk_registry::{
+    is_registered_root, kits_root_value, register_sdk, registered_kits_root, unregister_sdk,
+    CREATED_BY_VALUE, INSTALLED_ROOTS_KEY, KITS_ROOT_VALUE,
+};
+
+pub use shortcut::{create_prompt_shortcut, prompt_shortcut_name};
+
+pub use tool::ToolKind;
+
+pub use snapshot::{
+    apply_environment_with_snapshot, restore, snapshot_current, snapshot_vars, EnvSnapshot,
 };
 
 #[cfg(windows)]
@@ -61,10 +92,25 @@ pub struct MsvcEnvironment {
 
 impl MsvcEnvironment {
     /// Create a new MSVC environment from install info
+    ///
+    /// The target architecture is taken from `msvc_info.arch`.
     pub fn from_install_info(
         msvc_info: &InstallInfo,
         sdk_info: Option<&InstallInfo>,
         host_arch: Architecture,
+    ) -> Result<Self> {
+        Self::from_install_info_with_arch(msvc_info, sdk_info, host_arch, msvc_info.arch)
+    }
+
+    /// Create a new MSVC environment for an explicit host/target pair
+    ///
+    /// Selects `bin/Host<host>/<target>` tools and `<target>` libraries, e.g.
+    /// x64-hosted tools emitting arm64 binaries.
+    pub fn from_install_info_with_arch(
+        msvc_info: &InstallInfo,
+        sdk_info: Option<&InstallInfo>,
+        host_arch: Architecture,
+        arch: Architecture,
     ) -> Result<Self> {
         let base_dir = msvc_info
             .install_path
@@ -89,8 +135,6 @@ impl MsvcEnvironment {
             )
         };
 
-        let arch = msvc_info.arch;
-
         // Build include paths
         let include_paths = Self::build_include_paths(
             &vc_tools_install_dir,
@@ -169,6 +213,9 @@ impl MsvcEnvironment {
     }
 
     /// Build binary paths
+    ///
+    /// Same order as `vcvarsall.bat`: the `Host<host>/<target>` tools, then
+    /// for cross builds the host-native tools, then the host SDK tools.
     fn build_bin_paths(
         vc_tools_dir: &Path,
         sdk_dir: &Path,
@@ -176,71 +223,65 @@ impl MsvcEnvironment {
         host_arch: Architecture,
         target_arch: Architecture,
     ) -> Vec<PathBuf> {
-        let host_dir = host_arch.msvc_host_dir();
-        let target_dir = target_arch.msvc_target_dir();
+        let host_bin = vc_tools_dir.join("bin").join(host_arch.msvc_host_dir());
 
-        vec![
-            // MSVC binaries
-            vc_tools_dir.join("bin").join(host_dir).join(target_dir),
-            // Windows SDK binaries
-            sdk_dir
-                .join("bin")
-                .join(sdk_version)
-                .join(target_arch.to_string()),
-        ]
+        // MSVC binaries
+        let mut paths = vec![host_bin.join(target_arch.msvc_target_dir())];
+        // Cross compilers load some DLLs (mspdbcore.dll, ...) from the native tools
+        if host_arch != target_arch {
+            paths.push(host_bin.join(host_arch.msvc_target_dir()));
+        }
+        // Windows SDK binaries (rc.exe, mt.exe) run on the host
+        paths.extend(sdk_bin_dirs(
+            &sdk_dir.join("bin").join(sdk_version),
+            host_arch,
+            target_arch,
+        ));
+        paths
     }
 
     /// Check if cl.exe is available in the configured paths
     pub fn has_cl_exe(&self) -> bool {
-        self.bin_paths.iter().any(|p| p.join("cl.exe").exists())
+        self.tool_path(ToolKind::Cl).is_some()
     }
 
-    /// Get the path to cl.exe
-    pub fn cl_exe_path(&self) -> Option<PathBuf> {
+    /// Get the path to a tool, searching the binary paths in order
+    pub fn tool_path(&self, tool: ToolKind) -> Option<PathBuf> {
+        let exe = tool.exe_name();
         self.bin_paths
             .iter()
-            .map(|p| p.join("cl.exe"))
+            .map(|p| p.join(&exe))
             .find(|p| p.exists())
     }
 
+    /// Get the path to cl.exe
+    pub fn cl_exe_path(&self) -> Option<PathBuf> {
+        self.tool_path(ToolKind::Cl)
+    }
+
     /// Get the path to link.exe
     pub fn link_exe_path(&self) -> Option<PathBuf> {
-        self.bin_paths
-            .iter()
-            .map(|p| p.join("link.exe"))
-            .find(|p| p.exists())
+        self.tool_path(ToolKind::Link)
     }
 
     /// Get the path to lib.exe (static library manager)
     pub fn lib_exe_path(&self) -> Option<PathBuf> {
-        self.bin_paths
-            .iter()
-            .map(|p| p.join("lib.exe"))
-            .find(|p| p.exists())
+        self.tool_path(ToolKind::Lib)
     }
 
     /// Get the path to ml64.exe (MASM assembler)
     pub fn ml64_exe_path(&self) -> Option<PathBuf> {
-        self.bin_paths
-            .iter()
-            .map(|p| p.join("ml64.exe"))
-            .find(|p| p.exists())
+        self.tool_path(ToolKind::Ml64)
     }
 
     /// Get the path to nmake.exe
     pub fn nmake_exe_path(&self) -> Option<PathBuf> {
-        self.bin_paths
-            .iter()
-            .map(|p| p.join("nmake.exe"))
-            .find(|p| p.exists())
+        self.tool_path(ToolKind::Nmake)
     }
 
     /// Get the path to rc.exe (resource compiler)
     pub fn rc_exe_path(&self) -> Option<PathBuf> {
-        self.bin_paths
-            .iter()
-            .map(|p| p.join("rc.exe"))
-            .find(|p| p.exists())
+        self.tool_path(ToolKind::Rc)
     }
 
     /// Get all tool paths as a struct for easy access
@@ -328,6 +369,9 @@ pub struct ToolPaths {
 ///
 /// Returns all environment variables needed for MSVC toolchain,
 /// formatted for use with cc-rs and other build tools.
+///
+/// `INCLUDE`, `LIB` and `PATH` only contain msvc-kit's own entries; use
+/// [`get_env_changes`] to merge them with the existing values.
 pub fn get_env_vars(env: &MsvcEnvironment) -> HashMap<String, String> {
     let mut vars = HashMap::new();
 
@@ -351,6 +395,10 @@ pub fn get_env_vars(env: &MsvcEnvironment) -> HashMap<String, String> {
         "WindowsSDKVersion".to_string(),
         format!("{}\\", env.windows_sdk_version),
     );
+    vars.insert(
+        "WindowsSDKLibVersion".to_string(),
+        format!("{}\\", env.windows_sdk_version),
+    );
     vars.insert(
         "WindowsSdkBinPath".to_string(),
         env.windows_sdk_dir
@@ -360,6 +408,13 @@ pub fn get_env_vars(env: &MsvcEnvironment) -> HashMap<String, String> {
             .to_string(),
     );
 
+    // Universal CRT (part of the Windows SDK), looked up by cc-rs and CMake
+    vars.insert(
+        "UniversalCRTSdkDir".to_string(),
+        env.windows_sdk_dir.display().to_string(),
+    );
+    vars.insert("UCRTVersion".to_string(), env.windows_sdk_version.clone());
+
     // INCLUDE path
     let include = env
         .include_paths
@@ -388,13 +443,148 @@ pub fn get_env_vars(env: &MsvcEnvironment) -> HashMap<String, String> {
     vars.insert("PATH".to_string(), path);
 
     // Platform information
-    vars.insert("Platform".to_string(), env.arch.to_string());
+    vars.insert(
+        "Platform".to_string(),
+        env.arch.msbuild_platform().to_string(),
+    );
     vars.insert("VSCMD_ARG_HOST_ARCH".to_string(), env.host_arch.to_string());
     vars.insert("VSCMD_ARG_TGT_ARCH".to_string(), env.arch.to_string());
 
     vars
 }
 
+/// Variables whose values are path lists merged with any existing value
+pub const PREPEND_VARS: &[&str] = &["INCLUDE", "LIB", "PATH"];
+
+/// A single change needed to activate an MSVC environment
+///
+/// Unlike [`get_env_vars`], which only returns msvc-kit's own values,
+/// `EnvChange` tells callers whether a value replaces the variable or must be
+/// put in front of what is already there (as `INCLUDE`, `LIB` and `PATH` are).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum EnvChange {
+    /// Replace the variable with `value`
+    Set {
+        /// Variable name
+        name: String,
+        /// New value
+        value: String,
+    },
+    /// Put `entries` in front of the existing `;`-separated value
+    Prepend {
+        /// Variable name
+        name: String,
+        /// Path entries, in priority order
+        entries: Vec<String>,
+    },
+}
+
+impl EnvChange {
+    /// Name of the variable this change applies to
+    pub fn name(&self) -> &str {
+        match self {
+            EnvChange::Set { name, .. } | EnvChange::Prepend { name, .. } => name,
+        }
+    }
+
+    /// Value of the variable after applying this change to `current`
+    ///
+    /// For [`EnvChange::Prepend`], existing entries are kept after the new
+    /// ones; entries already present are not duplicated (compared
+    /// case-insensitively, like Windows paths) and empty entries are dropped.
+    pub fn merge(&self, current: Option<&str>) -> String {
+        match self {
+            EnvChange::Set { value, .. } => value.clone(),
+            EnvChange::Prepend { entries, .. } => {
+                let mut merged: Vec<&str> = Vec::new();
+                let existing = current.unwrap_or_default().split(';');
+                for entry in entries.iter().map(String::as_str).chain(existing) {
+                    if !entry.is_empty() && !merged.iter().any(|m| m.eq_ignore_ascii_case(entry)) {
+                        merged.push(entry);
+                    }
+                }
+                merged.join(";")
+            }
+        }
+    }
+}
+
+/// Get the environment changes needed to activate `env`
+///
+/// Returns the same variables as [`get_env_vars`], with `INCLUDE`, `LIB` and
+/// `PATH` expressed as [`EnvChange::Prepend`] so callers merge them with the
+/// existing values instead of overwriting them. `Set` changes come first,
+/// sorted by name, followed by the prepends.
+pub fn get_env_changes(env: &MsvcEnvironment) -> Vec<EnvChange> {
+    let mut sets: Vec<EnvChange> = get_env_vars(env)
+        .into_iter()
+        .filter(|(name, _)| !PREPEND_VARS.contains(&name.as_str()))
+        .map(|(name, value)| EnvChange::Set { name, value })
+        .collect();
+    sets.sort_by(|a, b| a.name().cmp(b.name()));
+
+    let entries = |paths: &[PathBuf]| paths.iter().map(|p| p.display().to_string()).collect();
+    sets.extend([
+        EnvChange::Prepend {
+            name: "INCLUDE".to_string(),
+            entries: entries(&env.include_paths),
+        },
+        EnvChange::Prepend {
+            name: "LIB".to_string(),
+            entries: entries(&env.lib_paths),
+        },
+        EnvChange::Prepend {
+            name: "PATH".to_string(),
+            entries: entries(&env.bin_paths),
+        },
+    ]);
+    sets
+}
+
+/// Whether tools built for `arch` run on `host`
+fn runs_on(host: Architecture, arch: Architecture) -> bool {
+    host.runnable_hosts().contains(&arch)
+}
+
+/// Host architecture of the tools to use from the toolset in `vc_tools_dir`
+///
+/// Prefers native tools (`bin/Hostarm64` on Windows on ARM) and falls back
+/// to tools that run under emulation, see [`select_host_arch`].
+pub fn detect_host_arch(vc_tools_dir: &Path, target_arch: Architecture) -> HostArchSelection {
+    select_host_arch(None, Architecture::native_host(), |host| {
+        vc_tools_dir
+            .join("bin")
+            .join(host.msvc_host_dir())
+            .join(target_arch.msvc_target_dir())
+            .is_dir()
+    })
+}
+
+/// Windows SDK tool directories below `sdk_bin` (`bin/<version>`) to put on `PATH`
+///
+/// Not every SDK ships rc.exe and mt.exe for every architecture, so the
+/// target, host and x86 directories are probed in that order, skipping
+/// tools the host cannot run, and all that exist are returned. Falls back
+/// to the host directory if none exists.
+pub(crate) fn sdk_bin_dirs(
+    sdk_bin: &Path,
+    host_arch: Architecture,
+    target_arch: Architecture,
+) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for arch in [target_arch, host_arch, Architecture::X86] {
+        let dir = sdk_bin.join(arch.to_string());
+        if runs_on(host_arch, arch) && dir.is_dir() && !dirs.contains(&dir) {
+            dirs.push(dir);
+        }
+    }
+    if dirs.is_empty() {
+        dirs.push(sdk_bin.join(host_arch.to_string()));
+    }
+    dirs
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -420,4 +610,167 @@ mod tests {
         assert!(vars.contains_key("LIB"));
         assert!(vars.contains_key("PATH"));
     }
+
+    #[test]
+    fn test_env_host_target_combinations() {
+        let msvc = InstallInfo {
+            component_type: "msvc".to_string(),
+            version: "14.44.34823".to_string(),
+            install_path: PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823"),
+            downloaded_files: vec![],
+            arch: Architecture::X64,
+            extract_filters: vec![],
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        };
+        let sdk = InstallInfo {
+            component_type: "sdk".to_string(),
+            version: "10.0.26100.0".to_string(),
+            install_path: PathBuf::from("C:/msvc-kit/Windows Kits/10"),
+            downloaded_files: vec![],
+            arch: Architecture::X64,
+            extract_filters: vec![],
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        };
+        let tools = PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823/bin");
+        let sdk_bin = PathBuf::from("C:/msvc-kit/Windows Kits/10/bin/10.0.26100.0");
+
+        let cases = [
+            (Architecture::X64, Architecture::X64, "x64"),
+            (Architecture::X64, Architecture::X86, "Win32"),
+            (Architecture::X86, Architecture::X86, "Win32"),
+            (Architecture::X86, Architecture::X64, "x64"),
+        ];
+        for (host, arch, platform) in cases {
+            let env = MsvcEnvironment::from_install_info_with_arch(&msvc, Some(&sdk), host, arch)
+                .unwrap();
+            let host_dir = tools.join(host.msvc_host_dir());
+
+            let mut expected = vec![host_dir.join(arch.to_string())];
+            if host != arch {
+                expected.push(host_dir.join(host.to_string()));
+            }
+            expected.push(sdk_bin.join(host.to_string()));
+            assert_eq!(env.bin_paths, expected, "{} -> {}", host, arch);
+            assert!(env.lib_paths.iter().all(|p| p.ends_with(arch.to_string())));
+
+            let vars = get_env_vars(&env);
+            assert_eq!(vars["Platform"], platform);
+            assert_eq!(vars["VSCMD_ARG_HOST_ARCH"], host.to_string());
+            assert_eq!(vars["VSCMD_ARG_TGT_ARCH"], arch.to_string());
+        }
+    }
+
+    #[test]
+    fn test_env_change_merge() {
+        let prepend = EnvChange::Prepend {
+            name: "PATH".to_string(),
+            entries: vec!["C:\\msvc\\bin".to_string(), "C:\\sdk\\bin".to_string()],
+        };
+        assert_eq!(prepend.merge(None), "C:\\msvc\\bin;C:\\sdk\\bin");
+        assert_eq!(
+            prepend.merge(Some("C:\\Windows;c:\\MSVC\\bin;")),
+            "C:\\msvc\\bin;C:\\sdk\\bin;C:\\Windows"
+        );
+
+        let set = EnvChange::Set {
+            name: "Platform".to_string(),
+            value: "x64".to_string(),
+        };
+        assert_eq!(set.merge(Some("x86")), "x64");
+    }
+
+    #[test]
+    fn test_get_env_changes() {
+        let env = MsvcEnvironment {
+            vc_install_dir: PathBuf::from("C:\\VC"),
+            vc_tools_install_dir: PathBuf::from("C:\\VC\\Tools\\MSVC\\14.40"),
+            vc_tools_version: "14.40.33807".to_string(),
+            windows_sdk_dir: PathBuf::from("C:\\Windows Kits\\10"),
+            windows_sdk_version: "10.0.22621.0".to_string(),
+            include_paths: vec![PathBuf::from("C:\\include")],
+            lib_paths: vec![PathBuf::from("C:\\lib")],
+            bin_paths: vec![PathBuf::from("C:\\bin")],
+            arch: Architecture::X64,
+            host_arch: Architecture::X64,
+        };
+
+        let changes = get_env_changes(&env);
+        assert_eq!(changes.len(), get_env_vars(&env).len());
+        assert!(changes.contains(&EnvChange::Prepend {
+            name: "LIB".to_string(),
+            entries: vec!["C:\\lib".to_string()],
+        }));
+        assert!(changes
+            .iter()
+            .any(|c| matches!(c, EnvChange::Set { name, .. } if name == "VCToolsVersion")));
+
+        let json = serde_json::to_value(&changes[changes.len() - 1]).unwrap();
+        assert_eq!(json["op"], "prepend");
+        assert_eq!(json["name"], "PATH");
+    }
+
+    #[test]
+    fn test_detect_host_arch() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let tools = temp_dir.path().join("VC/Tools/MSVC/14.44.34823");
+        std::fs::create_dir_all(tools.join("bin/Hostx64/x64")).unwrap();
+
+        let selection = detect_host_arch(&tools, Architecture::X64);
+        assert_eq!(selection.native, Architecture::native_host());
+        if selection
+            .native
+            .runnable_hosts()
+            .contains(&Architecture::X64)
+        {
+            // Native on x64, emulated on Windows on ARM without Hostarm64 tools
+            assert_eq!(selection.arch, Architecture::X64);
+        }
+
+        std::fs::create_dir_all(tools.join("bin/Hostarm64/x64")).unwrap();
+        if selection.native == Architecture::Arm64 {
+            let selection = detect_host_arch(&tools, Architecture::X64);
+            assert_eq!(selection.arch, Architecture::Arm64);
+        }
+    }
+
+    #[test]
+    fn test_sdk_bin_dirs_fallback() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sdk_bin = temp_dir.path().join("bin").join("10.0.26100.0");
+
+        // Nothing installed: the host directory
+        assert_eq!(
+            sdk_bin_dirs(&sdk_bin, Architecture::X64, Architecture::Arm64),
+            vec![sdk_bin.join("x64")]
+        );
+
+        // rc.exe only shipped for x86
+        std::fs::create_dir_all(sdk_bin.join("x86")).unwrap();
+        assert_eq!(
+            sdk_bin_dirs(&sdk_bin, Architecture::X64, Architecture::Arm64),
+            vec![sdk_bin.join("x86")]
+        );
+
+        // Target before host; arm64 tools do not run on an x64 host
+        std::fs::create_dir_all(sdk_bin.join("x64")).unwrap();
+        std::fs::create_dir_all(sdk_bin.join("arm64")).unwrap();
+        assert_eq!(
+            sdk_bin_dirs(&sdk_bin, Architecture::X64, Architecture::Arm64),
+            vec![sdk_bin.join("x64"), sdk_bin.join("x86")]
+        );
+        assert_eq!(
+            sdk_bin_dirs(&sdk_bin, Architecture::Arm64, Architecture::X64),
+            vec![
+                sdk_bin.join("x64"),
+                sdk_bin.join("arm64"),
+                sdk_bin.join("x86")
+            ]
+        );
+    }
 }
@@ -0,0 +1,252 @@
+//! Named environment profiles
+//!
+//! A profile is a `.env` file holding the variables of one installed toolset
+//! (e.g. `14.44-x64.env`) in `~/.msvc-kit/profiles`. Projects switch between
+//! toolsets by activating a profile instead of re-running `setup`, much like
+//! `rustup` toolchains.
+
+use std::path::{Path, PathBuf};
+
+use super::{get_env_vars, EnvChange, MsvcEnvironment, PREPEND_VARS};
+use crate::error::{MsvcKitError, Result};
+use crate::scripts::ShellType;
+use crate::winpath::to_msys;
+
+/// Extension of profile files
+pub const PROFILE_EXTENSION: &str = "env";
+
+/// Default profile directory: `%USERPROFILE%\.msvc-kit\profiles`
+///
+/// `$HOME/.msvc-kit/profiles` outside Windows. `MSVC_KIT_PROFILES_DIR`
+/// overrides it.
+pub fn default_profiles_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("MSVC_KIT_PROFILES_DIR") {
+        return PathBuf::from(dir);
+    }
+    let home = std::env::var("USERPROFILE")
+        .or_else(|_| std::env::var("HOME"))
+        .unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home).join(".msvc-kit").join("profiles")
+}
+
+/// Default profile name of an environment: `<major>.<minor>-<arch>`
+///
+/// Cross environments also name the host, e.g. `14.44-x64_arm64`.
+pub fn default_profile_name(env: &MsvcEnvironment) -> String {
+    let version: Vec<&str> = env.vc_tools_version.splitn(3, '.').take(2).collect();
+    let arch = if env.host_arch == env.arch {
+        env.arch.to_string()
+    } else {
+        format!("{}_{}", env.host_arch, env.arch)
+    };
+    format!("{}-{}", version.join("."), arch)
+}
+
+fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(MsvcKitError::Config(format!(
+            "Invalid profile name '{}' (use letters, digits, '.', '-' and '_')",
+            name
+        )))
+    }
+}
+
+/// Path of the profile `name` in `dir`
+pub fn profile_path(dir: &Path, name: &str) -> PathBuf {
+    dir.join(format!("{}.{}", name, PROFILE_EXTENSION))
+}
+
+/// Write `env` as profile `name` into `dir`
+///
+/// Overwrites an existing profile of the same name. Returns the file path.
+pub fn write_profile(dir: &Path, name: &str, env: &MsvcEnvironment) -> Result<PathBuf> {
+    check_name(name)?;
+    let mut vars: Vec<(String, String)> = get_env_vars(env).into_iter().collect();
+    vars.sort();
+
+    let mut content = format!(
+        "# msvc-kit profile {}\n# MSVC {}, SDK {}, host {}, target {}\n",
+        name, env.vc_tools_version, env.windows_sdk_version, env.host_arch, env.arch
+    );
+    for (key, value) in vars {
+        content.push_str(&format!("{}={}\n", key, value));
+    }
+
+    std::fs::create_dir_all(dir)?;
+    let path = profile_path(dir, name);
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Read profile `name` from `dir` as environment changes
+///
+/// `INCLUDE`, `LIB` and `PATH` become [`EnvChange::Prepend`], so activating a
+/// profile keeps the existing entries.
+pub fn read_profile(dir: &Path, name: &str) -> Result<Vec<EnvChange>> {
+    check_name(name)?;
+    let path = profile_path(dir, name);
+    if !path.exists() {
+        return Err(MsvcKitError::Config(format!(
+            "Profile '{}' not found in {}",
+            name,
+            dir.display()
+        )));
+    }
+    let content = std::fs::read_to_string(&path)?;
+    Ok(parse_profile(&content))
+}
+
+fn parse_profile(content: &str) -> Vec<EnvChange> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| {
+            let name = name.trim().to_string();
+            if PREPEND_VARS.contains(&name.as_str()) {
+                let entries = value
+                    .split(';')
+                    .filter(|e| !e.is_empty())
+                    .map(String::from)
+                    .collect();
+                EnvChange::Prepend { name, entries }
+            } else {
+                EnvChange::Set {
+                    name,
+                    value: value.to_string(),
+                }
+            }
+        })
+        .collect()
+}
+
+/// Names of the profiles in `dir`, sorted
+pub fn list_profiles(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|e| e == PROFILE_EXTENSION))
+        .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(String::from))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Shell commands applying `changes` to the current session
+///
+/// Meant to be evaluated by the shell, e.g.
+/// `msvc-kit profile activate 14.44-x64 --shell powershell | Invoke-Expression`.
+pub fn profile_commands(changes: &[EnvChange], shell: ShellType) -> String {
+    let mut out = String::new();
+    for change in changes {
+        let line = match (change, shell) {
+            (EnvChange::Set { name, value }, ShellType::Cmd) => {
+                format!("set \"{}={}\"", name, value)
+            }
+            (EnvChange::Set { name, value }, ShellType::PowerShell) => {
+                format!("$env:{} = '{}'", name, value.replace('\'', "''"))
+            }
+            (EnvChange::Set { name, value }, ShellType::Bash) => {
+                format!("export {}='{}'", name, value.replace('\'', r"'\''"))
+            }
+            (EnvChange::Prepend { name, entries }, ShellType::Cmd) => {
+                format!("set \"{}={};%{}%\"", name, entries.join(";"), name)
+            }
+            (EnvChange::Prepend { name, entries }, ShellType::PowerShell) => format!(
+                "$env:{} = '{};' + $env:{}",
+                name,
+                entries.join(";").replace('\'', "''"),
+                name
+            ),
+            (EnvChange::Prepend { name, entries }, ShellType::Bash) if name == "PATH" => {
+                let entries: Vec<String> = entries.iter().map(|e| to_msys(e)).collect();
+                format!("export PATH=\"{}:$PATH\"", entries.join(":"))
+            }
+            (EnvChange::Prepend { name, entries }, ShellType::Bash) => {
+                format!("export {}=\"{};${{{}:-}}\"", name, entries.join(";"), name)
+            }
+        };
+        out.push_str(&line);
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::Architecture;
+
+    fn test_env() -> MsvcEnvironment {
+        MsvcEnvironment {
+            vc_install_dir: PathBuf::from(r"C:\msvc\VC"),
+            vc_tools_install_dir: PathBuf::from(r"C:\msvc\VC\Tools\MSVC\14.44.34823"),
+            vc_tools_version: "14.44.34823".to_string(),
+            windows_sdk_dir: PathBuf::from(r"C:\msvc\Windows Kits\10"),
+            windows_sdk_version: "10.0.26100.0".to_string(),
+            include_paths: vec![PathBuf::from(r"C:\msvc\include")],
+            lib_paths: vec![PathBuf::from(r"C:\msvc\lib")],
+            bin_paths: vec![PathBuf::from(r"C:\msvc\bin")],
+            arch: Architecture::X64,
+            host_arch: Architecture::X64,
+        }
+    }
+
+    #[test]
+    fn test_profile_roundtrip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let env = test_env();
+        let name = default_profile_name(&env);
+        assert_eq!(name, "14.44-x64");
+
+        let path = write_profile(temp_dir.path(), &name, &env).unwrap();
+        assert!(path.ends_with("14.44-x64.env"));
+        assert_eq!(list_profiles(temp_dir.path()), vec![name.clone()]);
+
+        let changes = read_profile(temp_dir.path(), &name).unwrap();
+        assert!(changes.contains(&EnvChange::Set {
+            name: "VCToolsVersion".to_string(),
+            value: "14.44.34823".to_string(),
+        }));
+        assert!(changes.contains(&EnvChange::Prepend {
+            name: "PATH".to_string(),
+            entries: vec![r"C:\msvc\bin".to_string()],
+        }));
+
+        assert!(read_profile(temp_dir.path(), "missing").is_err());
+        assert!(write_profile(temp_dir.path(), "../escape", &env).is_err());
+    }
+
+    #[test]
+    fn test_profile_commands() {
+        let changes = vec![
+            EnvChange::Set {
+                name: "VCToolsVersion".to_string(),
+                value: "14.44.34823".to_string(),
+            },
+            EnvChange::Prepend {
+                name: "PATH".to_string(),
+                entries: vec![r"C:\msvc\bin".to_string()],
+            },
+        ];
+        assert_eq!(
+            profile_commands(&changes, ShellType::Cmd),
+            "set \"VCToolsVersion=14.44.34823\"\nset \"PATH=C:\\msvc\\bin;%PATH%\"\n"
+        );
+        assert_eq!(
+            profile_commands(&changes, ShellType::PowerShell),
+            "$env:VCToolsVersion = '14.44.34823'\n$env:PATH = 'C:\\msvc\\bin;' + $env:PATH\n"
+        );
+        assert!(profile_commands(&changes, ShellType::Bash)
+            .contains("export PATH=\"/c/msvc/bin:$PATH\""));
+    }
+}
@@ -0,0 +1,222 @@
+//! Windows SDK registration in the registry
+//!
+//! Tools that predate environment variables (older CMake generators, MSBuild
+//! property sheets, installers of third-party SDKs) find the Windows SDK via
+//! `KitsRoot10` under `HKLM\SOFTWARE\Microsoft\Windows Kits\Installed Roots`
+//! and the version subkeys next to it. [`register_sdk`] writes those entries
+//! for an msvc-kit install, the way the Windows SDK installer does;
+//! [`unregister_sdk`] removes them again. Both need an elevated prompt.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{MsvcKitError, Result};
+
+/// Registry key the Windows SDK installer records its kit root under
+///
+/// The installer writes it to both the native and the 32-bit (`WOW6432Node`)
+/// registry view, and so does [`register_sdk`].
+pub const INSTALLED_ROOTS_KEY: &str = r"SOFTWARE\Microsoft\Windows Kits\Installed Roots";
+
+/// Value holding the Windows 10/11 kit root
+pub const KITS_ROOT_VALUE: &str = "KitsRoot10";
+
+/// Value marking a version subkey as created by [`register_sdk`]
+///
+/// [`unregister_sdk`] only deletes subkeys carrying it, so versions
+/// registered by the Windows SDK installer survive.
+pub const CREATED_BY_VALUE: &str = "CreatedByMsvcKit";
+
+/// `KitsRoot10` value for a kit root, with the trailing backslash the SDK installer writes
+pub fn kits_root_value(kits_root: &Path) -> String {
+    let root = std::path::absolute(kits_root).unwrap_or_else(|_| kits_root.to_path_buf());
+    let root = root.to_string_lossy().replace('/', "\\");
+    format!("{}\\", root.trim_end_matches('\\'))
+}
+
+/// Whether a `KitsRoot10` value points at `kits_root`
+pub fn is_registered_root(value: &str, kits_root: &Path) -> bool {
+    value
+        .trim_end_matches('\\')
+        .eq_ignore_ascii_case(kits_root_value(kits_root).trim_end_matches('\\'))
+}
+
+/// Register `kits_root` (`<install>\Windows Kits\10`) and its SDK `versions`
+///
+/// Writes `KitsRoot10` and one subkey per version (e.g. `10.0.26100.0`) in
+/// both registry views. Fails without touching the registry if `KitsRoot10`
+/// already points at another kit root that exists, so an SDK installed by
+/// the Windows SDK installer is never hidden; unregister or uninstall that
+/// one first.
+pub fn register_sdk(kits_root: &Path, versions: &[String]) -> Result<()> {
+    for current in registered_kits_roots() {
+        if !is_registered_root(&current, kits_root) && PathBuf::from(&current).is_dir() {
+            return Err(MsvcKitError::EnvSetup(format!(
+                "{} is already registered as the Windows SDK root",
+                current
+            )));
+        }
+    }
+    write_registration(&kits_root_value(kits_root), versions)
+}
+
+/// Remove the registration of `kits_root` written by [`register_sdk`]
+///
+/// Returns `false` without touching the registry if `KitsRoot10` is missing
+/// or points at another kit root in every view. Version subkeys that existed
+/// before [`register_sdk`] are kept.
+pub fn unregister_sdk(kits_root: &Path, versions: &[String]) -> Result<bool> {
+    if !registered_kits_roots()
+        .iter()
+        .any(|current| is_registered_root(current, kits_root))
+    {
+        return Ok(false);
+    }
+    remove_registration(kits_root, versions)?;
+    Ok(true)
+}
+
+/// Current `KitsRoot10` value, if any (the 32-bit view first)
+pub fn registered_kits_root() -> Option<String> {
+    registered_kits_roots().into_iter().next()
+}
+
+/// Registry views written by the Windows SDK installer
+#[cfg(windows)]
+const REGISTRY_VIEWS: [u32; 2] = [
+    winreg::enums::KEY_WOW64_32KEY,
+    winreg::enums::KEY_WOW64_64KEY,
+];
+
+/// `KitsRoot10` values of all registry views that have one
+#[cfg(windows)]
+fn registered_kits_roots() -> Vec<String> {
+    REGISTRY_VIEWS
+        .into_iter()
+        .filter_map(|view| {
+            open_installed_roots(winreg::enums::KEY_READ | view)
+                .ok()?
+                .get_value(KITS_ROOT_VALUE)
+                .ok()
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn write_registration(root: &str, versions: &[String]) -> Result<()> {
+    use winreg::enums::{RegDisposition, HKEY_LOCAL_MACHINE, KEY_ALL_ACCESS};
+    use winreg::RegKey;
+
+    for view in REGISTRY_VIEWS {
+        let (key, _) = RegKey::predef(HKEY_LOCAL_MACHINE)
+            .create_subkey_with_flags(INSTALLED_ROOTS_KEY, KEY_ALL_ACCESS | view)
+            .map_err(registry_error)?;
+        key.set_value(KITS_ROOT_VALUE, &root.to_string())
+            .map_err(registry_error)?;
+        for version in versions {
+            let (subkey, disposition) = key
+                .create_subkey_with_flags(version, KEY_ALL_ACCESS | view)
+                .map_err(registry_error)?;
+            if matches!(disposition, RegDisposition::REG_CREATED_NEW_KEY) {
+                subkey
+                    .set_value(CREATED_BY_VALUE, &1u32)
+                    .map_err(registry_error)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn remove_registration(kits_root: &Path, versions: &[String]) -> Result<()> {
+    use winreg::enums::KEY_ALL_ACCESS;
+
+    for view in REGISTRY_VIEWS {
+        let key = match open_installed_roots(KEY_ALL_ACCESS | view) {
+            Ok(key) => key,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(registry_error(e)),
+        };
+        let current: Option<String> = key.get_value(KITS_ROOT_VALUE).ok();
+        if current.is_some_and(|current| is_registered_root(&current, kits_root)) {
+            key.delete_value(KITS_ROOT_VALUE).map_err(registry_error)?;
+        }
+        for version in versions {
+            let created = key
+                .open_subkey_with_flags(version, winreg::enums::KEY_READ | view)
+                .is_ok_and(|subkey| subkey.get_raw_value(CREATED_BY_VALUE).is_ok());
+            if !created {
+                continue;
+            }
+            match key.delete_subkey_all(version) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(registry_error(e))
+                }
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn open_installed_roots(access: u32) -> std::io::Result<winreg::RegKey> {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey_with_flags(INSTALLED_ROOTS_KEY, access)
+}
+
+#[cfg(windows)]
+fn registry_error(e: std::io::Error) -> MsvcKitError {
+    if e.kind() == std::io::ErrorKind::PermissionDenied {
+        MsvcKitError::EnvSetup(format!(
+            "Access to HKLM\\{} denied; run from an elevated prompt",
+            INSTALLED_ROOTS_KEY
+        ))
+    } else {
+        MsvcKitError::EnvSetup(format!(
+            "Failed to update HKLM\\{}: {}",
+            INSTALLED_ROOTS_KEY, e
+        ))
+    }
+}
+
+#[cfg(not(windows))]
+fn registered_kits_roots() -> Vec<String> {
+    Vec::new()
+}
+
+#[cfg(not(windows))]
+fn write_registration(_root: &str, _versions: &[String]) -> Result<()> {
+    Err(MsvcKitError::UnsupportedPlatform(
+        "SDK registration is only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(not(windows))]
+fn remove_registration(_kits_root: &Path, _versions: &[String]) -> Result<()> {
+    Err(MsvcKitError::UnsupportedPlatform(
+        "SDK registration is only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kits_root_value() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let kits_root = temp_dir.path().join("Windows Kits").join("10");
+        let value = kits_root_value(&kits_root);
+        assert!(value.ends_with("10\\"));
+        assert!(!value.contains('/'));
+
+        assert!(is_registered_root(&value, &kits_root));
+        assert!(is_registered_root(value.trim_end_matches('\\'), &kits_root));
+        assert!(!is_registered_root(
+            r"C:\Program Files (x86)\Windows Kits\10\",
+            &kits_root
+        ));
+    }
+}
@@ -1,7 +1,10 @@
 //! Environment setup and activation script generation
 
+use std::fmt;
 use std::path::PathBuf;
 
+use serde::{Deserialize, Serialize};
+
 use crate::error::{MsvcKitError, Result};
 use crate::installer::InstallInfo;
 use crate::scripts::{
@@ -9,36 +12,328 @@ use crate::scripts::{
 };
 use crate::version::Architecture;
 
-use super::{get_env_vars, MsvcEnvironment};
+use super::{get_env_changes, EnvChange, MsvcEnvironment};
 
 /// Setup MSVC environment from installation info
 ///
 /// Creates an `MsvcEnvironment` configuration from the provided
-/// installation information.
+/// installation information, using the native host tools if installed (see
+/// [`detect_host_arch`](super::detect_host_arch)) and `msvc_info.arch` as
+/// target.
+#[deprecated(
+    since = "0.2.11",
+    note = "host and target are implicit; use `setup_environment_with_arch`"
+)]
 pub fn setup_environment(
     msvc_info: &InstallInfo,
     sdk_info: Option<&InstallInfo>,
 ) -> Result<MsvcEnvironment> {
-    let host_arch = Architecture::host();
-    MsvcEnvironment::from_install_info(msvc_info, sdk_info, host_arch)
+    let host_arch = super::detect_host_arch(&msvc_info.install_path, msvc_info.arch).arch;
+    setup_environment_with_arch(msvc_info, sdk_info, host_arch, msvc_info.arch)
+}
+
+/// Setup MSVC environment for an explicit host and target architecture
+///
+/// Use this for cross-compilation, e.g. x64-hosted tools emitting arm64
+/// binaries on a Windows-on-ARM box. The host tools
+/// (`bin/Host<host>/<target>`) must have been installed.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::{setup_environment_with_arch, Architecture, InstallInfo};
+///
+/// # fn run(msvc: &InstallInfo, sdk: &InstallInfo) -> msvc_kit::Result<()> {
+/// let env = setup_environment_with_arch(msvc, Some(sdk), Architecture::X64, Architecture::Arm64)?;
+/// assert_eq!(env.arch, Architecture::Arm64);
+/// # Ok(())
+/// # }
+/// ```
+pub fn setup_environment_with_arch(
+    msvc_info: &InstallInfo,
+    sdk_info: Option<&InstallInfo>,
+    host_arch: Architecture,
+    target_arch: Architecture,
+) -> Result<MsvcEnvironment> {
+    MsvcEnvironment::from_install_info_with_arch(msvc_info, sdk_info, host_arch, target_arch)
+}
+
+/// What a planned change does to a variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvAction {
+    /// The variable is replaced
+    Set,
+    /// Entries are put in front of the existing value
+    Prepend,
+    /// The variable already has the new value
+    Unchanged,
+}
+
+impl fmt::Display for EnvAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvAction::Set => write!(f, "set"),
+            EnvAction::Prepend => write!(f, "prepend"),
+            EnvAction::Unchanged => write!(f, "unchanged"),
+        }
+    }
+}
+
+/// One variable of an [`EnvPlan`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvPlanEntry {
+    /// Variable name
+    pub name: String,
+    /// What happens to the variable
+    pub action: EnvAction,
+    /// Current value (`None` = unset)
+    pub old: Option<String>,
+    /// Value after the change
+    pub new: String,
+}
+
+/// The changes activating an environment would make, before making them
+///
+/// [`apply_environment`] and [`write_to_registry`] apply a plan, so the plan
+/// shows exactly what they do. `setup --plan` prints it.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvPlan {
+    /// Planned variables, in the order of [`get_env_changes`]
+    pub entries: Vec<EnvPlanEntry>,
+}
+
+impl EnvPlan {
+    /// Plan `env` against current values returned by `current`
+    pub fn new(env: &MsvcEnvironment, current: impl Fn(&str) -> Option<String>) -> Self {
+        let entries = get_env_changes(env)
+            .into_iter()
+            .map(|change| {
+                let old = current(change.name());
+                let new = change.merge(old.as_deref());
+                let action = if old.as_deref() == Some(new.as_str()) {
+                    EnvAction::Unchanged
+                } else if matches!(change, EnvChange::Prepend { .. }) {
+                    EnvAction::Prepend
+                } else {
+                    EnvAction::Set
+                };
+                EnvPlanEntry {
+                    name: change.name().to_string(),
+                    action,
+                    old,
+                    new,
+                }
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Plan `env` against the current process environment
+    pub fn for_process(env: &MsvcEnvironment) -> Self {
+        Self::new(env, |name| std::env::var(name).ok())
+    }
+
+    /// Plan `env` against the user environment in the registry
+    #[cfg(windows)]
+    pub fn for_registry(env: &MsvcEnvironment) -> Result<Self> {
+        let env_key = open_environment_key()?;
+        Ok(Self::new(env, |name| {
+            env_key.get_value(registry_name(name)).ok()
+        }))
+    }
+
+    /// Plan `env` against the user environment in the registry
+    #[cfg(not(windows))]
+    pub fn for_registry(_env: &MsvcEnvironment) -> Result<Self> {
+        Err(MsvcKitError::UnsupportedPlatform(
+            "Registry operations are only supported on Windows".to_string(),
+        ))
+    }
+
+    /// Entries that change their variable
+    pub fn changes(&self) -> impl Iterator<Item = &EnvPlanEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.action != EnvAction::Unchanged)
+    }
+}
+
+/// Shorten long values (PATH) for the table view
+fn shorten(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        value.to_string()
+    } else {
+        let head: String = value.chars().take(max - 3).collect();
+        format!("{}...", head)
+    }
+}
+
+impl fmt::Display for EnvPlan {
+    /// Table with one row per variable; values are shortened, use JSON for
+    /// the full values
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let width = self
+            .entries
+            .iter()
+            .map(|e| e.name.len())
+            .max()
+            .unwrap_or(0)
+            .max(8);
+        writeln!(
+            f,
+            "{:<width$}  {:<9}  {:<40}  NEW",
+            "VARIABLE", "ACTION", "OLD"
+        )?;
+        for entry in &self.entries {
+            let old = entry
+                .old
+                .as_deref()
+                .map_or("(unset)".to_string(), |v| shorten(v, 40));
+            writeln!(
+                f,
+                "{:<width$}  {:<9}  {:<40}  {}",
+                entry.name,
+                entry.action.to_string(),
+                old,
+                shorten(&entry.new, 60)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Variables whose presence marks a shell as an activated developer prompt
+pub const ACTIVE_ENV_MARKERS: [&str; 2] = ["VSCMD_VER", "VCToolsInstallDir"];
+
+/// Variables of an activated environment carried over by `setup --reuse`
+const ACTIVE_ENV_VARS: [&str; 16] = [
+    "VSCMD_VER",
+    "VSCMD_ARG_HOST_ARCH",
+    "VSCMD_ARG_TGT_ARCH",
+    "VSINSTALLDIR",
+    "VCINSTALLDIR",
+    "VCToolsInstallDir",
+    "VCToolsVersion",
+    "WindowsSdkDir",
+    "WindowsSDKVersion",
+    "WindowsSDKLibVersion",
+    "UniversalCRTSdkDir",
+    "UCRTVersion",
+    "INCLUDE",
+    "LIB",
+    "LIBPATH",
+    "PATH",
+];
+
+/// A Visual Studio (or msvc-kit) environment already active in the shell
+///
+/// Activating a second toolchain on top of it prepends a second set of
+/// `INCLUDE`, `LIB` and `PATH` entries, mixing headers and libraries of both.
+/// `setup` refuses to do so unless asked to layer (`--force`) or to keep the
+/// active one (`--reuse`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ActiveVsEnvironment {
+    /// `VSCMD_VER`, set by `VsDevCmd.bat` / `vcvarsall.bat`
+    pub vscmd_ver: Option<String>,
+    /// `VCToolsInstallDir`
+    pub vc_tools_install_dir: Option<PathBuf>,
+    /// `VCToolsVersion`
+    pub vc_tools_version: Option<String>,
+    /// `VSCMD_ARG_TGT_ARCH`
+    pub target_arch: Option<String>,
+    /// Toolchain variables that are set, in a fixed order
+    pub vars: Vec<(String, String)>,
+}
+
+impl ActiveVsEnvironment {
+    /// Whether `env` is the toolchain that is already active
+    ///
+    /// Re-activating the same toolchain does not mix anything, so callers
+    /// only need to warn when this is `false`.
+    pub fn matches(&self, env: &MsvcEnvironment) -> bool {
+        let same_dir = |dir: &PathBuf| {
+            let normalize = |p: &std::path::Path| {
+                p.display()
+                    .to_string()
+                    .replace('/', "\\")
+                    .trim_end_matches('\\')
+                    .to_lowercase()
+            };
+            normalize(dir) == normalize(&env.vc_tools_install_dir)
+        };
+        self.vc_tools_install_dir.as_ref().is_some_and(same_dir)
+    }
+
+    /// The active variables as changes, for re-emitting them as a script
+    pub fn changes(&self) -> Vec<EnvChange> {
+        self.vars
+            .iter()
+            .map(|(name, value)| EnvChange::Set {
+                name: name.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for ActiveVsEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.vscmd_ver {
+            Some(ver) => write!(f, "Visual Studio {} developer environment", ver)?,
+            None => write!(f, "MSVC environment")?,
+        }
+        if let Some(version) = &self.vc_tools_version {
+            write!(f, " (VC tools {}", version)?;
+            if let Some(arch) = &self.target_arch {
+                write!(f, ", target {}", arch)?;
+            }
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+/// Detect an activated developer environment in the current process
+pub fn detect_active_environment() -> Option<ActiveVsEnvironment> {
+    detect_active_environment_from(|name| std::env::var(name).ok())
+}
+
+/// Detect an activated developer environment from variables returned by `lookup`
+///
+/// Returns `None` unless one of [`ACTIVE_ENV_MARKERS`] is set to a non-empty
+/// value.
+pub fn detect_active_environment_from(
+    lookup: impl Fn(&str) -> Option<String>,
+) -> Option<ActiveVsEnvironment> {
+    let get = |name: &str| lookup(name).filter(|v| !v.trim().is_empty());
+    if ACTIVE_ENV_MARKERS.iter().all(|name| get(name).is_none()) {
+        return None;
+    }
+
+    Some(ActiveVsEnvironment {
+        vscmd_ver: get("VSCMD_VER"),
+        vc_tools_install_dir: get("VCToolsInstallDir").map(PathBuf::from),
+        vc_tools_version: get("VCToolsVersion"),
+        target_arch: get("VSCMD_ARG_TGT_ARCH"),
+        vars: ACTIVE_ENV_VARS
+            .iter()
+            .filter_map(|name| get(name).map(|value| (name.to_string(), value)))
+            .collect(),
+    })
 }
 
 /// Apply environment variables to the current process
 ///
 /// This sets the environment variables in the current process,
-/// allowing subsequent commands to use the MSVC toolchain.
+/// allowing subsequent commands to use the MSVC toolchain. `INCLUDE`,
+/// `LIB` and `PATH` are prepended to rather than overwritten.
+/// Use [`apply_environment_with_snapshot`](super::apply_environment_with_snapshot)
+/// to be able to undo the changes.
 pub fn apply_environment(env: &MsvcEnvironment) -> Result<()> {
-    let vars = get_env_vars(env);
-
-    for (key, value) in vars {
-        if key == "PATH" {
-            // Prepend to existing PATH
-            let current_path = std::env::var("PATH").unwrap_or_default();
-            let new_path = format!("{};{}", value, current_path);
-            std::env::set_var("PATH", new_path);
-        } else {
-            std::env::set_var(&key, &value);
-        }
+    // INCLUDE, LIB and PATH are merged with the existing values
+    for entry in EnvPlan::for_process(env).changes() {
+        std::env::set_var(&entry.name, &entry.new);
     }
 
     Ok(())
@@ -96,6 +391,25 @@ pub async fn save_activation_script(
 /// Write environment variables to Windows registry (user level)
 #[cfg(windows)]
 pub fn write_to_registry(env: &MsvcEnvironment) -> Result<()> {
+    let env_key = open_environment_key()?;
+    let plan = EnvPlan::new(env, |name| env_key.get_value(registry_name(name)).ok());
+
+    for entry in plan.changes() {
+        let name = registry_name(&entry.name);
+        env_key
+            .set_value(name, &entry.new)
+            .map_err(|e| MsvcKitError::EnvSetup(format!("Failed to set {}: {}", name, e)))?;
+    }
+
+    // Broadcast environment change
+    broadcast_environment_change();
+
+    Ok(())
+}
+
+/// Open `HKCU\Environment` for reading and writing
+#[cfg(windows)]
+fn open_environment_key() -> Result<winreg::RegKey> {
     use winreg::enums::*;
     use winreg::RegKey;
 
@@ -103,32 +417,17 @@ pub fn write_to_registry(env: &MsvcEnvironment) -> Result<()> {
     let (env_key, _) = hkcu
         .create_subkey("Environment")
         .map_err(|e| MsvcKitError::EnvSetup(format!("Failed to open registry: {}", e)))?;
+    Ok(env_key)
+}
 
-    let vars = get_env_vars(env);
-
-    for (key, value) in vars {
-        if key == "PATH" {
-            // Append to existing PATH
-            let current: String = env_key.get_value("Path").unwrap_or_default();
-            let new_path = if current.is_empty() {
-                value
-            } else {
-                format!("{};{}", value, current)
-            };
-            env_key
-                .set_value("Path", &new_path)
-                .map_err(|e| MsvcKitError::EnvSetup(format!("Failed to set PATH: {}", e)))?;
-        } else {
-            env_key
-                .set_value(&key, &value)
-                .map_err(|e| MsvcKitError::EnvSetup(format!("Failed to set {}: {}", key, e)))?;
-        }
+/// The user PATH is stored as "Path"
+#[cfg(windows)]
+fn registry_name(name: &str) -> &str {
+    if name == "PATH" {
+        "Path"
+    } else {
+        name
     }
-
-    // Broadcast environment change
-    broadcast_environment_change();
-
-    Ok(())
 }
 
 #[cfg(windows)]
@@ -223,27 +522,118 @@ mod tests {
     }
 
     #[test]
-    fn test_apply_environment() {
+    fn test_env_plan() {
         let env = sample_env();
+        let plan = EnvPlan::new(&env, |name| match name {
+            "PATH" => Some("C:/Windows".to_string()),
+            "VCToolsVersion" => Some("14.40.0".to_string()),
+            _ => None,
+        });
+
+        let entry = |name: &str| plan.entries.iter().find(|e| e.name == name).unwrap();
+        assert_eq!(entry("VCToolsVersion").action, EnvAction::Unchanged);
+        assert_eq!(entry("WindowsSDKVersion").action, EnvAction::Set);
+        assert_eq!(entry("WindowsSDKVersion").old, None);
+
+        let path = entry("PATH");
+        assert_eq!(path.action, EnvAction::Prepend);
+        assert_eq!(path.old.as_deref(), Some("C:/Windows"));
+        assert_eq!(path.new, "C:/toolchain/bin1;C:/toolchain/bin2;C:/Windows");
+
+        assert!(plan.changes().all(|e| e.name != "VCToolsVersion"));
+        let table = plan.to_string();
+        assert!(table.starts_with("VARIABLE"));
+        assert!(table.contains("(unset)"));
+    }
+
+    #[test]
+    fn test_detect_active_environment() {
+        assert!(detect_active_environment_from(|_| None).is_none());
+        assert!(detect_active_environment_from(|name| match name {
+            "VSCMD_VER" => Some(String::new()),
+            _ => None,
+        })
+        .is_none());
+
+        let vars = |name: &str| match name {
+            "VSCMD_VER" => Some("17.14.0".to_string()),
+            "VSCMD_ARG_TGT_ARCH" => Some("x64".to_string()),
+            "VCToolsInstallDir" => Some("C:\\toolchain\\VC\\Tools\\MSVC\\14.40.0\\".to_string()),
+            "VCToolsVersion" => Some("14.40.0".to_string()),
+            "PATH" => Some("C:/Windows".to_string()),
+            _ => None,
+        };
+        let active = detect_active_environment_from(vars).unwrap();
+        assert_eq!(active.vscmd_ver.as_deref(), Some("17.14.0"));
+        assert_eq!(
+            active.to_string(),
+            "Visual Studio 17.14.0 developer environment (VC tools 14.40.0, target x64)"
+        );
+        assert_eq!(active.vars.len(), 5);
+        assert_eq!(active.vars.last().unwrap().0, "PATH");
+        assert!(active
+            .changes()
+            .iter()
+            .all(|c| matches!(c, EnvChange::Set { .. })));
+
+        let mut env = sample_env();
+        assert!(active.matches(&env));
+        env.vc_tools_install_dir = PathBuf::from("C:/toolchain/VC/Tools/MSVC/14.44.0");
+        assert!(!active.matches(&env));
+    }
 
-        // Save original PATH
+    #[test]
+    fn test_apply_environment() {
+        let env = sample_env();
         let original_path = std::env::var("PATH").ok();
 
-        // Apply environment
-        apply_environment(&env).unwrap();
+        // The snapshot covers every variable apply_environment sets
+        let snapshot = crate::env::apply_environment_with_snapshot(&env).unwrap();
 
         // Verify some env vars are set
         assert!(std::env::var("VCToolsVersion").is_ok());
         assert!(std::env::var("WindowsSDKVersion").is_ok());
 
-        // Restore original PATH if it existed
-        if let Some(path) = original_path {
-            std::env::set_var("PATH", path);
+        // Existing PATH entries are kept after the toolchain ones
+        let path = std::env::var("PATH").unwrap();
+        assert!(path.starts_with("C:/toolchain/bin1;C:/toolchain/bin2"));
+        if let Some(ref original) = original_path {
+            assert!(path.ends_with(original.as_str()));
         }
 
-        // Clean up test env vars
-        std::env::remove_var("VCToolsVersion");
-        std::env::remove_var("WindowsSDKVersion");
+        crate::env::restore(&snapshot);
+        assert_eq!(std::env::var("PATH").ok(), original_path);
+        assert_eq!(
+            std::env::var("VCToolsVersion").ok().as_deref(),
+            snapshot.get("VCToolsVersion")
+        );
+    }
+
+    #[test]
+    fn test_setup_environment_with_arch() {
+        let msvc = InstallInfo {
+            component_type: "msvc".to_string(),
+            version: "14.40.0".to_string(),
+            install_path: PathBuf::from("C:/toolchain/VC/Tools/MSVC/14.40.0"),
+            downloaded_files: vec![],
+            arch: Architecture::X64,
+            extract_filters: vec![],
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        };
+
+        let env = setup_environment_with_arch(&msvc, None, Architecture::X64, Architecture::Arm64)
+            .unwrap();
+        assert_eq!(env.host_arch, Architecture::X64);
+        assert_eq!(env.arch, Architecture::Arm64);
+        assert!(env.bin_paths[0].ends_with("bin/Hostx64/arm64"));
+        assert!(env.lib_paths[0].ends_with("lib/arm64"));
+
+        let script = generate_activation_script(&env, ShellType::Bash).unwrap();
+        assert!(script.contains("VSCMD_ARG_HOST_ARCH=\"x64\""));
+        assert!(script.contains("VSCMD_ARG_TGT_ARCH=\"arm64\""));
     }
 
     #[test]
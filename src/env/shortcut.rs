@@ -0,0 +1,94 @@
+//! Developer prompt shortcuts
+//!
+//! Creates `.lnk` files that open `cmd.exe` with an activation script already
+//! run, like the "x64 Native Tools Command Prompt" shortcuts installed by
+//! Visual Studio.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{MsvcKitError, Result};
+use crate::version::Architecture;
+
+/// Display name of the developer prompt for an architecture pair
+///
+/// Follows the Visual Studio naming: "msvc-kit x64 Native Tools Prompt" when
+/// host and target match, "msvc-kit x64_arm64 Cross Tools Prompt" otherwise.
+pub fn prompt_shortcut_name(host_arch: Architecture, arch: Architecture) -> String {
+    if host_arch == arch {
+        format!("msvc-kit {} Native Tools Prompt", arch)
+    } else {
+        format!("msvc-kit {}_{} Cross Tools Prompt", host_arch, arch)
+    }
+}
+
+/// Create a shortcut in `dir` opening cmd with `script` run
+///
+/// The shortcut runs `cmd.exe /k "<script>"` with the script's directory as
+/// working directory. Returns the path of the `.lnk` file.
+pub fn create_prompt_shortcut(dir: &Path, name: &str, script: &Path) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let link = dir.join(format!("{}.lnk", name));
+    write_shortcut(&link, name, script)?;
+    Ok(link)
+}
+
+#[cfg(windows)]
+fn write_shortcut(link: &Path, description: &str, script: &Path) -> Result<()> {
+    use windows::core::{Interface, HSTRING};
+    use windows::Win32::System::Com::{
+        CoCreateInstance, CoInitializeEx, CoUninitialize, IPersistFile, CLSCTX_INPROC_SERVER,
+        COINIT_APARTMENTTHREADED,
+    };
+    use windows::Win32::UI::Shell::{IShellLinkW, ShellLink};
+
+    let to_error = |e: windows::core::Error| {
+        MsvcKitError::EnvSetup(format!("Failed to create shortcut: {}", e))
+    };
+
+    let comspec = std::env::var("ComSpec").unwrap_or_else(|_| "cmd.exe".to_string());
+    let arguments = format!("/k \"{}\"", script.display());
+    let working_dir = script.parent().unwrap_or(script);
+
+    unsafe {
+        // S_FALSE (already initialized) is fine; only a real failure stops us
+        let initialized = CoInitializeEx(None, COINIT_APARTMENTTHREADED);
+        initialized.ok().map_err(to_error)?;
+
+        let result = (|| -> windows::core::Result<()> {
+            let shell_link: IShellLinkW = CoCreateInstance(&ShellLink, None, CLSCTX_INPROC_SERVER)?;
+            shell_link.SetPath(&HSTRING::from(comspec.as_str()))?;
+            shell_link.SetArguments(&HSTRING::from(arguments.as_str()))?;
+            shell_link.SetWorkingDirectory(&HSTRING::from(working_dir.as_os_str()))?;
+            shell_link.SetDescription(&HSTRING::from(description))?;
+            let file: IPersistFile = shell_link.cast()?;
+            file.Save(&HSTRING::from(link.as_os_str()), true)
+        })();
+
+        CoUninitialize();
+        result.map_err(to_error)
+    }
+}
+
+#[cfg(not(windows))]
+fn write_shortcut(_link: &Path, _description: &str, _script: &Path) -> Result<()> {
+    Err(MsvcKitError::UnsupportedPlatform(
+        "Shortcuts are only supported on Windows".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_shortcut_name() {
+        assert_eq!(
+            prompt_shortcut_name(Architecture::X64, Architecture::X64),
+            "msvc-kit x64 Native Tools Prompt"
+        );
+        assert_eq!(
+            prompt_shortcut_name(Architecture::X64, Architecture::Arm64),
+            "msvc-kit x64_arm64 Cross Tools Prompt"
+        );
+    }
+}
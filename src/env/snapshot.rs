@@ -0,0 +1,140 @@
+//! Process environment snapshots
+//!
+//! [`apply_environment`](super::apply_environment) mutates the current
+//! process environment. Taking a snapshot first lets tests and embedding tools
+//! undo those changes with [`restore`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::{apply_environment, get_env_vars, MsvcEnvironment};
+use crate::error::Result;
+
+/// Captured process environment variables
+///
+/// A snapshot either covers the whole environment ([`snapshot_current`]) or a
+/// fixed set of variables ([`snapshot_vars`]). Restoring a scoped snapshot
+/// only touches the variables it covers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvSnapshot {
+    /// Captured values; variables unset at capture time are absent
+    pub vars: BTreeMap<String, String>,
+    /// Variables covered by a scoped snapshot (`None` = whole environment)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<BTreeSet<String>>,
+}
+
+impl EnvSnapshot {
+    /// Value a variable had when the snapshot was taken
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.vars.get(key).map(String::as_str)
+    }
+
+    /// Whether the snapshot covers `key`
+    pub fn covers(&self, key: &str) -> bool {
+        self.scope.as_ref().is_none_or(|s| s.contains(key))
+    }
+}
+
+/// Capture every (UTF-8) environment variable of the current process
+pub fn snapshot_current() -> EnvSnapshot {
+    EnvSnapshot {
+        vars: current_vars(),
+        scope: None,
+    }
+}
+
+/// Capture only the given variables, remembering which were unset
+pub fn snapshot_vars<I, K>(keys: I) -> EnvSnapshot
+where
+    I: IntoIterator<Item = K>,
+    K: AsRef<str>,
+{
+    let scope: BTreeSet<String> = keys.into_iter().map(|k| k.as_ref().to_string()).collect();
+    let vars = scope
+        .iter()
+        .filter_map(|k| std::env::var(k).ok().map(|v| (k.clone(), v)))
+        .collect();
+    EnvSnapshot {
+        vars,
+        scope: Some(scope),
+    }
+}
+
+/// Restore the process environment to a snapshot
+///
+/// Covered variables are reset to their captured value, or removed if they
+/// were unset when the snapshot was taken.
+pub fn restore(snapshot: &EnvSnapshot) {
+    let keys: BTreeSet<String> = match snapshot.scope {
+        Some(ref scope) => scope.clone(),
+        None => current_vars()
+            .into_keys()
+            .chain(snapshot.vars.keys().cloned())
+            .collect(),
+    };
+
+    for key in keys {
+        match snapshot.vars.get(&key) {
+            Some(value) => {
+                if std::env::var(&key).ok().as_deref() != Some(value.as_str()) {
+                    std::env::set_var(&key, value);
+                }
+            }
+            None => std::env::remove_var(&key),
+        }
+    }
+}
+
+/// Apply an MSVC environment, returning a snapshot that undoes it
+///
+/// The snapshot covers exactly the variables `apply_environment` sets.
+pub fn apply_environment_with_snapshot(env: &MsvcEnvironment) -> Result<EnvSnapshot> {
+    let snapshot = snapshot_vars(get_env_vars(env).keys());
+    apply_environment(env)?;
+    Ok(snapshot)
+}
+
+fn current_vars() -> BTreeMap<String, String> {
+    std::env::vars_os()
+        .filter_map(|(k, v)| Some((k.into_string().ok()?, v.into_string().ok()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoped_snapshot_restore() {
+        let set = "MSVC_KIT_SNAPSHOT_TEST_SET";
+        let unset = "MSVC_KIT_SNAPSHOT_TEST_UNSET";
+        std::env::set_var(set, "original");
+        std::env::remove_var(unset);
+
+        let snapshot = snapshot_vars([set, unset]);
+        assert_eq!(snapshot.get(set), Some("original"));
+        assert!(snapshot.covers(unset));
+        assert!(!snapshot.covers("PATH"));
+
+        std::env::set_var(set, "changed");
+        std::env::set_var(unset, "added");
+        restore(&snapshot);
+
+        assert_eq!(std::env::var(set).unwrap(), "original");
+        assert!(std::env::var(unset).is_err());
+        std::env::remove_var(set);
+    }
+
+    #[test]
+    fn test_snapshot_serialization() {
+        let snapshot = snapshot_vars(["MSVC_KIT_SNAPSHOT_TEST_SERDE"]);
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed: EnvSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+
+        let full = snapshot_current();
+        assert!(full.scope.is_none());
+        assert!(full.covers("ANY_VARIABLE"));
+    }
+}
@@ -0,0 +1,115 @@
+//! Executables provided by an MSVC installation
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A tool executable from the MSVC toolset or the Windows SDK
+///
+/// Serialized as its lowercase name (`"cl"`, `"link"`, ...), which is also
+/// the key used in [`QueryResult::tools`](crate::QueryResult::tools) output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ToolKind {
+    /// C/C++ compiler
+    Cl,
+    /// Linker
+    Link,
+    /// Static library manager
+    Lib,
+    /// MASM assembler for x64
+    Ml64,
+    /// MASM assembler for x86
+    Ml,
+    /// Make utility
+    Nmake,
+    /// Resource compiler (Windows SDK)
+    Rc,
+    /// Manifest tool (Windows SDK)
+    Mt,
+    /// COFF binary dumper
+    Dumpbin,
+    /// COFF binary editor
+    Editbin,
+}
+
+impl ToolKind {
+    /// Every known tool
+    pub const ALL: [ToolKind; 10] = [
+        ToolKind::Cl,
+        ToolKind::Link,
+        ToolKind::Lib,
+        ToolKind::Ml64,
+        ToolKind::Ml,
+        ToolKind::Nmake,
+        ToolKind::Rc,
+        ToolKind::Mt,
+        ToolKind::Dumpbin,
+        ToolKind::Editbin,
+    ];
+
+    /// Lowercase tool name, e.g. `cl`
+    pub fn name(self) -> &'static str {
+        match self {
+            ToolKind::Cl => "cl",
+            ToolKind::Link => "link",
+            ToolKind::Lib => "lib",
+            ToolKind::Ml64 => "ml64",
+            ToolKind::Ml => "ml",
+            ToolKind::Nmake => "nmake",
+            ToolKind::Rc => "rc",
+            ToolKind::Mt => "mt",
+            ToolKind::Dumpbin => "dumpbin",
+            ToolKind::Editbin => "editbin",
+        }
+    }
+
+    /// Executable file name, e.g. `cl.exe`
+    pub fn exe_name(self) -> String {
+        format!("{}.exe", self.name())
+    }
+}
+
+impl fmt::Display for ToolKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for ToolKind {
+    type Err = String;
+
+    /// Parse a tool name, with or without `.exe` (case-insensitive)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lower = s.trim().to_lowercase();
+        let name = lower.strip_suffix(".exe").unwrap_or(&lower);
+        ToolKind::ALL
+            .into_iter()
+            .find(|tool| tool.name() == name)
+            .ok_or_else(|| format!("Unknown tool: {}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_kind_roundtrip() {
+        for tool in ToolKind::ALL {
+            assert_eq!(tool.to_string().parse::<ToolKind>(), Ok(tool));
+            assert_eq!(tool.exe_name().parse::<ToolKind>(), Ok(tool));
+        }
+        assert_eq!("CL.EXE".parse::<ToolKind>(), Ok(ToolKind::Cl));
+        assert!("gcc".parse::<ToolKind>().is_err());
+    }
+
+    #[test]
+    fn test_tool_kind_serializes_as_name() {
+        let tools: std::collections::HashMap<_, _> = [(ToolKind::Ml64, "ml64.exe")].into();
+        let json = serde_json::to_string(&tools).unwrap();
+        assert_eq!(json, r#"{"ml64":"ml64.exe"}"#);
+        let back: std::collections::HashMap<ToolKind, String> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(back[&ToolKind::Ml64], "ml64.exe");
+    }
+}
@@ -54,6 +54,19 @@ pub enum MsvcKitError {
     #[error("CAB extraction error: {0}")]
     Cab(String),
 
+    /// Extracting one file of a package payload failed
+    #[error("Failed to extract {entry} from {archive} to {}: {source}", destination.display())]
+    Extraction {
+        /// Payload file name
+        archive: String,
+        /// Path of the file inside the payload
+        entry: String,
+        /// Where the file was being written
+        destination: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
     /// Configuration errors
     #[error("Configuration error: {0}")]
     Config(String),
@@ -90,11 +103,162 @@ pub enum MsvcKitError {
     #[error("Download cancelled by user")]
     Cancelled,
 
+    /// Offline mode forbids network access and the required data is not cached
+    #[error("Offline mode: {0}")]
+    Offline(String),
+
+    /// Offline mode: payloads required for the install are not in the download cache
+    #[error(
+        "Offline mode: {} payload(s) for {component} are not cached: {}",
+        files.len(),
+        files.join(", ")
+    )]
+    OfflinePayloadsMissing {
+        component: String,
+        files: Vec<String>,
+    },
+
+    /// An MSI installer needs external cabinets that are not next to it
+    #[error(
+        "{installer} needs {} cabinet file(s) that are not next to it: {}",
+        cabinets.len(),
+        cabinets.join(", ")
+    )]
+    MissingCabinets {
+        installer: String,
+        cabinets: Vec<String>,
+    },
+
+    /// The MSVC toolset ships no compiler for the target architecture
+    ///
+    /// Raised for 32-bit ARM with toolsets that dropped it; `supported` lists
+    /// the targets the toolset does have.
+    #[error(
+        "MSVC {toolset} has no compiler targeting {arch}; supported targets: {}",
+        supported.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ")
+    )]
+    UnsupportedArchForToolset {
+        toolset: String,
+        arch: crate::version::Architecture,
+        supported: Vec<crate::version::Architecture>,
+    },
+
+    /// Packages extracted the same path with different contents (strict mode)
+    #[error(
+        "{} file conflict(s) between packages: {}",
+        .0.len(),
+        .0.iter().map(|c| c.to_string()).collect::<Vec<_>>().join("; ")
+    )]
+    FileConflicts(Vec<crate::installer::FileConflict>),
+
+    /// A download exceeded `overall_timeout` or `per_file_timeout`
+    ///
+    /// Transfers still in flight were cancelled and recorded as partial in the
+    /// download index. `report` covers the payloads finished before expiry and
+    /// `pending` lists the ones that were not, so callers can decide to retry.
+    #[error(
+        "{} timed out after {limit:?}; {} payload(s) not downloaded",
+        file.as_ref().map(|f| format!("Download of {}", f)).unwrap_or_else(|| "Download".to_string()),
+        pending.len()
+    )]
+    DownloadTimeout {
+        /// File that exceeded `per_file_timeout` (None = `overall_timeout`)
+        file: Option<String>,
+        /// Limit that expired
+        limit: std::time::Duration,
+        /// Payloads not downloaded
+        pending: Vec<String>,
+        /// Progress made before the timeout
+        report: Box<crate::downloader::DownloadReport>,
+    },
+
+    /// A post-install script failed or timed out
+    #[error("Post-install script {script} failed: {reason}")]
+    HookScript { script: String, reason: String },
+
+    /// Invalid download options
+    #[error("Invalid options: {0}")]
+    InvalidOptions(#[from] OptionsError),
+
     /// Generic error with message
     #[error("{0}")]
     Other(String),
 }
 
+/// Validation errors reported by [`DownloadOptionsBuilder::try_build`](crate::DownloadOptionsBuilder::try_build)
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum OptionsError {
+    /// `parallel_downloads` is zero
+    #[error("parallel_downloads must be at least 1")]
+    ZeroParallelDownloads,
+
+    /// `target_dir` is empty
+    #[error("target_dir must not be empty")]
+    EmptyTargetDir,
+
+    /// `target_dir` contains a name Windows cannot create as given
+    #[error("target_dir {path} cannot be used: {reason}")]
+    InvalidTargetDir { path: String, reason: String },
+
+    /// Extracted paths below `target_dir` would exceed `MAX_PATH`
+    #[error(
+        "target_dir is {length} characters long, but at most {limit} leave room for the \
+         deepest extracted path within MAX_PATH; use a shorter directory or set LongPathsEnabled"
+    )]
+    TargetDirTooLong { length: usize, limit: usize },
+
+    /// Files cannot be created in `target_dir`
+    #[error("target_dir {path} is not writable: {reason}")]
+    TargetDirNotWritable { path: String, reason: String },
+
+    /// A timeout is zero and would cancel every download
+    #[error("timeouts must be greater than zero")]
+    ZeroTimeout,
+
+    /// `max_download_rate` is zero and would stall every download
+    #[error("max_download_rate must be greater than zero")]
+    ZeroDownloadRate,
+
+    /// `chunked_downloads` asks for zero connections per payload
+    #[error("chunked download connections must be greater than zero")]
+    ZeroConnections,
+
+    /// An alternate endpoint is not an http(s) URL
+    #[error("alternate endpoint must be an http(s) URL: {0}")]
+    InvalidEndpoint(String),
+
+    /// A target triple does not map to a supported architecture
+    #[error("{0}")]
+    UnsupportedTarget(String),
+
+    /// A version was set to an empty string
+    #[error("{component} version must not be empty")]
+    EmptyVersion { component: String },
+
+    /// An exclude pattern is empty and would exclude every package
+    #[error("exclude pattern must not be empty (it would exclude every package)")]
+    EmptyExcludePattern,
+
+    /// An only pattern is empty
+    #[error("only pattern must not be empty")]
+    EmptyOnlyPattern,
+
+    /// The only patterns leave no compiler tools package to download
+    #[error(
+        "only patterns select no compiler tools package for {host} -> {target} \
+         (the result cannot build); set allow_broken_selection to download anyway"
+    )]
+    BrokenSelection { host: String, target: String },
+
+    /// An extract filter is empty
+    #[error("extract filter must not be empty")]
+    EmptyExtractFilter,
+
+    /// An exclude pattern removes every package of an included component
+    #[error("component '{component}' is included but exclude pattern '{pattern}' removes it")]
+    ConflictingPattern { component: String, pattern: String },
+}
+
 /// Result type alias for msvc-kit operations
 pub type Result<T> = std::result::Result<T, MsvcKitError>;
 
@@ -0,0 +1,356 @@
+//! C API for non-Rust embedders
+//!
+//! Exposes querying, environment variables and downloads as `extern "C"`
+//! functions so CMake plugins, Python (`ctypes`/`cffi`) and Node (`ffi-napi`)
+//! tooling can embed msvc-kit instead of spawning the CLI and scraping its
+//! output. The declarations are in `include/msvc_kit.h`; build the library
+//! with:
+//!
+//! ```bash
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! Conventions:
+//!
+//! - Strings are UTF-8 and NUL-terminated. Structured results are JSON.
+//! - Returned strings are owned by the caller and released with
+//!   [`msvc_kit_string_free`].
+//! - Functions returning `int` return 0 on success and -1 on failure;
+//!   functions returning a string return NULL on failure. The reason is
+//!   available from [`msvc_kit_last_error`] on the same thread.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::downloader::{DownloadOptions, ProgressHandler};
+use crate::error::{MsvcKitError, Result};
+use crate::query::{query_installation, QueryOptions, QueryResult};
+use crate::version::Architecture;
+
+/// Progress callback of [`msvc_kit_download`]
+///
+/// Receives one JSON event object (see [`msvc_kit_download`]) and the
+/// `user_data` pointer given to the download. The event string is only valid
+/// during the call.
+pub type MsvcKitProgressCallback =
+    Option<unsafe extern "C" fn(event: *const c_char, user_data: *mut c_void)>;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: &str) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(message));
+}
+
+/// Run `f`, recording its error (or panic) as the thread's last error
+fn guard<T>(f: impl FnOnce() -> Result<T>) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(e)) => {
+            set_last_error(&e.to_string());
+            None
+        }
+        Err(_) => {
+            set_last_error("msvc-kit panicked");
+            None
+        }
+    }
+}
+
+/// Read an optional C string argument
+///
+/// # Safety
+///
+/// `ptr` must be NULL or point to a NUL-terminated string.
+unsafe fn read_str<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(Some)
+        .map_err(|_| MsvcKitError::Other(format!("{} is not valid UTF-8", name)))
+}
+
+fn into_c_string(s: String) -> Result<*mut c_char> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|_| MsvcKitError::Other("Result contains a NUL byte".to_string()))
+}
+
+/// Query the installation in `install_dir` for `arch` (NULL = host)
+///
+/// # Safety
+///
+/// Both pointers must be NULL or point to NUL-terminated strings.
+unsafe fn query(install_dir: *const c_char, arch: *const c_char) -> Result<QueryResult> {
+    let install_dir = read_str(install_dir, "install_dir")?
+        .ok_or_else(|| MsvcKitError::Other("install_dir is NULL".to_string()))?;
+    let arch = match read_str(arch, "arch")? {
+        Some(arch) => arch.parse::<Architecture>().map_err(MsvcKitError::Other)?,
+        None => Architecture::host(),
+    };
+    query_installation(&QueryOptions {
+        install_dir: PathBuf::from(install_dir),
+        arch,
+        ..Default::default()
+    })
+}
+
+/// Format environment variables as sorted `KEY=value` lines
+fn env_blob<'a>(vars: impl IntoIterator<Item = (&'a String, &'a String)>) -> String {
+    let mut vars: Vec<_> = vars.into_iter().collect();
+    vars.sort();
+    vars.iter()
+        .map(|(key, value)| format!("{}={}\n", key, value))
+        .collect()
+}
+
+/// Version of the library, e.g. `"0.2.10"`
+///
+/// The string is static and must not be freed.
+#[no_mangle]
+pub extern "C" fn msvc_kit_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Message of the last failed call on this thread, or NULL
+///
+/// The string stays valid until the next failing call on the same thread and
+/// must not be freed.
+#[no_mangle]
+pub extern "C" fn msvc_kit_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| {
+        e.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Release a string returned by this library
+///
+/// # Safety
+///
+/// `s` must be NULL or a string returned by an msvc-kit function that has not
+/// been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn msvc_kit_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Query an installation and return the [`QueryResult`] as JSON
+///
+/// `arch` is a target architecture (`"x64"`, `"x86"`, `"arm64"`, ...) or NULL
+/// for the host architecture.
+///
+/// # Safety
+///
+/// `install_dir` must point to a NUL-terminated string; `arch` must be NULL or
+/// point to one.
+#[no_mangle]
+pub unsafe extern "C" fn msvc_kit_query_installation(
+    install_dir: *const c_char,
+    arch: *const c_char,
+) -> *mut c_char {
+    guard(|| {
+        let result = query(install_dir, arch)?;
+        into_c_string(serde_json::to_string(&result)?)
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Environment variables of an installation as `KEY=value` lines
+///
+/// Lines are sorted by key and each ends with `\n`. Arguments are those of
+/// [`msvc_kit_query_installation`].
+///
+/// # Safety
+///
+/// See [`msvc_kit_query_installation`].
+#[no_mangle]
+pub unsafe extern "C" fn msvc_kit_get_env_vars(
+    install_dir: *const c_char,
+    arch: *const c_char,
+) -> *mut c_char {
+    guard(|| {
+        let result = query(install_dir, arch)?;
+        into_c_string(env_blob(&result.env_vars))
+    })
+    .unwrap_or(std::ptr::null_mut())
+}
+
+/// Progress handler forwarding events to a C callback as JSON
+struct CallbackProgress {
+    callback: unsafe extern "C" fn(*const c_char, *mut c_void),
+    user_data: *mut c_void,
+}
+
+// The caller of `msvc_kit_download` guarantees that the callback may be
+// invoked from any thread with its `user_data`.
+unsafe impl Send for CallbackProgress {}
+unsafe impl Sync for CallbackProgress {}
+
+impl CallbackProgress {
+    fn emit(&self, event: serde_json::Value) {
+        if let Ok(event) = CString::new(event.to_string()) {
+            unsafe { (self.callback)(event.as_ptr(), self.user_data) };
+        }
+    }
+}
+
+impl ProgressHandler for CallbackProgress {
+    fn on_start(&self, component: &str, total_files: usize, total_bytes: u64) {
+        self.emit(json!({
+            "event": "start",
+            "component": component,
+            "total_files": total_files,
+            "total_bytes": total_bytes,
+        }));
+    }
+
+    fn on_file_start(&self, file_name: &str, file_size: u64) {
+        self.emit(json!({"event": "file_start", "file_name": file_name, "size": file_size}));
+    }
+
+    fn on_progress(&self, bytes: u64) {
+        self.emit(json!({"event": "progress", "bytes": bytes}));
+    }
+
+    fn on_file_complete(&self, file_name: &str, outcome: &str) {
+        self.emit(json!({"event": "file_complete", "file_name": file_name, "outcome": outcome}));
+    }
+
+    fn on_complete(&self, downloaded: usize, skipped: usize) {
+        self.emit(json!({"event": "complete", "downloaded": downloaded, "skipped": skipped}));
+    }
+
+    fn on_error(&self, error: &str) {
+        self.emit(json!({"event": "error", "message": error}));
+    }
+
+    fn on_message(&self, message: &str) {
+        self.emit(json!({"event": "message", "message": message}));
+    }
+}
+
+/// Download and extract MSVC and the Windows SDK, blocking until done
+///
+/// `options_json` is a serialized [`DownloadOptions`]; missing fields take
+/// their defaults, e.g. `{"target_dir": "C:/msvc-kit", "arch": "x64"}`.
+///
+/// `callback` (may be NULL) receives JSON events with an `"event"` key of
+/// `start`, `file_start`, `progress`, `file_complete`, `complete`, `error` or
+/// `message`. It is called from worker threads.
+///
+/// On success, `*result_json` (if not NULL) receives
+/// `{"msvc": InstallInfo, "sdk": InstallInfo}`, to be released with
+/// [`msvc_kit_string_free`].
+///
+/// # Safety
+///
+/// `options_json` must point to a NUL-terminated string, `result_json` must be
+/// NULL or valid for writes, and `callback` must be safe to call from any
+/// thread with `user_data` until this function returns.
+#[no_mangle]
+pub unsafe extern "C" fn msvc_kit_download(
+    options_json: *const c_char,
+    callback: MsvcKitProgressCallback,
+    user_data: *mut c_void,
+    result_json: *mut *mut c_char,
+) -> c_int {
+    let result = guard(|| {
+        let json = read_str(options_json, "options_json")?
+            .ok_or_else(|| MsvcKitError::Other("options_json is NULL".to_string()))?;
+        let mut options: DownloadOptions = serde_json::from_str(json)?;
+        if let Some(callback) = callback {
+            options.progress_handler = Some(Arc::new(CallbackProgress {
+                callback,
+                user_data,
+            }));
+        }
+        options.validate()?;
+
+        let runtime = tokio::runtime::Runtime::new()?;
+        let (msvc, sdk) = runtime.block_on(async {
+            let mut msvc = crate::download_msvc(&options).await?;
+            crate::extract_and_finalize_msvc_with_report(&mut msvc, false).await?;
+            let sdk = crate::download_sdk(&options).await?;
+            crate::extract_and_finalize_sdk_with_report(&sdk, false).await?;
+            Ok::<_, MsvcKitError>((msvc, sdk))
+        })?;
+
+        if !result_json.is_null() {
+            *result_json = into_c_string(json!({"msvc": msvc, "sdk": sdk}).to_string())?;
+        }
+        Ok(())
+    });
+    match result {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn last_error() -> String {
+        let ptr = msvc_kit_last_error();
+        assert!(!ptr.is_null());
+        unsafe { CStr::from_ptr(ptr) }.to_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_version() {
+        let version = unsafe { CStr::from_ptr(msvc_kit_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    fn test_env_blob_sorted_lines() {
+        let vars: std::collections::HashMap<String, String> = [
+            ("PATH".to_string(), "C:\\bin".to_string()),
+            ("INCLUDE".to_string(), "C:\\inc".to_string()),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(env_blob(&vars), "INCLUDE=C:\\inc\nPATH=C:\\bin\n");
+    }
+
+    #[test]
+    fn test_query_errors_set_last_error() {
+        let result = unsafe { msvc_kit_query_installation(std::ptr::null(), std::ptr::null()) };
+        assert!(result.is_null());
+        assert!(last_error().contains("install_dir"));
+
+        let dir = CString::new("msvc-kit").unwrap();
+        let arch = CString::new("sparc").unwrap();
+        let result = unsafe { msvc_kit_get_env_vars(dir.as_ptr(), arch.as_ptr()) };
+        assert!(result.is_null());
+        assert!(last_error().contains("sparc"));
+    }
+
+    #[test]
+    fn test_download_rejects_invalid_options() {
+        let options = CString::new("{not json").unwrap();
+        let code = unsafe {
+            msvc_kit_download(
+                options.as_ptr(),
+                None,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+        assert_eq!(code, -1);
+        assert!(!last_error().is_empty());
+    }
+}
@@ -0,0 +1,336 @@
+//! Removing installed versions, caches and leftovers
+//!
+//! `clean` first collects everything it would remove, so callers can show the
+//! list (dry run) or ask for confirmation before anything is deleted. Removing
+//! a whole installation is refused unless the directory looks like one.
+
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::downloader::is_part_file;
+use crate::error::{MsvcKitError, Result};
+use crate::query::dir_size;
+
+/// Prefix of the staging directories used while extracting MSI installers
+const STAGING_PREFIX: &str = ".msvc-kit-staging-";
+
+/// Options for [`clean`]
+#[derive(Debug, Clone, Default)]
+pub struct CleanOptions {
+    /// Remove the whole installation directory
+    pub all: bool,
+    /// MSVC version to remove
+    pub msvc_version: Option<String>,
+    /// Windows SDK version to remove
+    pub sdk_version: Option<String>,
+    /// Remove the download cache (`downloads/`)
+    pub cache: bool,
+    /// Remove orphaned temporary files left by interrupted runs
+    pub orphans: bool,
+    /// Only report what would be removed
+    pub dry_run: bool,
+}
+
+/// Result of [`clean`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanReport {
+    /// Removed (or, in dry-run mode, removable) paths
+    pub removed: Vec<PathBuf>,
+    /// Bytes freed (or that would be freed)
+    pub bytes_freed: u64,
+    /// Requested versions that are not installed
+    pub not_found: Vec<String>,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+}
+
+/// Whether `dir` looks like an msvc-kit installation (has `VC/` or `Windows Kits/`)
+pub fn is_msvc_kit_install(dir: &Path) -> bool {
+    dir.join("VC").is_dir() || dir.join("Windows Kits").is_dir()
+}
+
+/// Temporary files left behind by interrupted downloads and extractions
+///
+/// Finds `*.partial` payload copies and the `*.partN` parts of ranged
+/// downloads under `downloads/`, and MSI staging directories in the
+/// installation root.
+pub fn find_orphans(install_dir: &Path) -> Vec<PathBuf> {
+    let mut orphans: Vec<PathBuf> = std::fs::read_dir(install_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(STAGING_PREFIX))
+        .map(|e| e.path())
+        .collect();
+
+    let mut pending = vec![install_dir.join("downloads")];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_type().is_ok_and(|t| t.is_dir()) {
+                pending.push(path);
+            } else if path.extension().is_some_and(|e| e == "partial") || is_part_file(&path) {
+                orphans.push(path);
+            }
+        }
+    }
+    orphans.sort();
+    orphans
+}
+
+/// Refuse a version that is not a single plain path component
+///
+/// Versions are joined onto the installation directory, so `..`, separators
+/// or an absolute path would point the removal outside of it.
+fn check_version(kind: &str, version: &str) -> Result<()> {
+    let mut components = Path::new(version).components();
+    let plain = !version.contains(['/', '\\'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none();
+    if plain {
+        Ok(())
+    } else {
+        Err(MsvcKitError::InstallPath(format!(
+            "invalid {} version {:?}: must be a plain directory name; refusing to remove it",
+            kind, version
+        )))
+    }
+}
+
+fn path_size(path: &Path) -> u64 {
+    if path.is_dir() {
+        dir_size(path)
+    } else {
+        path.metadata().map(|m| m.len()).unwrap_or(0)
+    }
+}
+
+/// Remove installed versions, the download cache or orphaned temporary files
+///
+/// With `options.all`, the whole directory is removed; this fails with
+/// [`MsvcKitError::InstallPath`] if the directory has neither `VC/` nor
+/// `Windows Kits/`, so a misconfigured `install_dir` is never wiped.
+/// Versions that are not plain directory names (`..`, paths) are rejected
+/// the same way.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{clean, CleanOptions};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let options = CleanOptions {
+///         cache: true,
+///         orphans: true,
+///         dry_run: true,
+///         ..Default::default()
+///     };
+///     let report = clean("C:/msvc-kit".as_ref(), &options).await?;
+///     println!("Would free {} bytes", report.bytes_freed);
+///     Ok(())
+/// }
+/// ```
+pub async fn clean(install_dir: &Path, options: &CleanOptions) -> Result<CleanReport> {
+    let mut report = CleanReport {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+    if let Some(version) = &options.msvc_version {
+        check_version("MSVC", version)?;
+    }
+    if let Some(version) = &options.sdk_version {
+        check_version("Windows SDK", version)?;
+    }
+    if !install_dir.exists() {
+        return Ok(report);
+    }
+
+    if options.all {
+        if !is_msvc_kit_install(install_dir) {
+            return Err(MsvcKitError::InstallPath(format!(
+                "{} does not look like an msvc-kit installation (no VC/ or Windows Kits/); refusing to remove it",
+                install_dir.display()
+            )));
+        }
+        report.removed.push(install_dir.to_path_buf());
+    } else {
+        if let Some(version) = &options.msvc_version {
+            let path = install_dir
+                .join("VC")
+                .join("Tools")
+                .join("MSVC")
+                .join(version);
+            if path.exists() {
+                report.removed.push(path);
+            } else {
+                report.not_found.push(format!("MSVC {}", version));
+            }
+        }
+
+        if let Some(version) = &options.sdk_version {
+            let sdk_root = install_dir.join("Windows Kits").join("10");
+            if sdk_root.join("Include").join(version).exists() {
+                report.removed.extend(
+                    ["Include", "Lib", "bin"]
+                        .iter()
+                        .map(|subdir| sdk_root.join(subdir).join(version))
+                        .filter(|p| p.exists()),
+                );
+            } else {
+                report.not_found.push(format!("Windows SDK {}", version));
+            }
+        }
+
+        let downloads = install_dir.join("downloads");
+        if options.cache && downloads.exists() {
+            report.removed.push(downloads);
+        }
+        if options.orphans {
+            // Orphans inside a removed cache are already covered
+            let orphans = find_orphans(install_dir);
+            report.removed.extend(
+                orphans
+                    .into_iter()
+                    .filter(|p| !(options.cache && p.starts_with(install_dir.join("downloads")))),
+            );
+        }
+    }
+
+    report.bytes_freed = report.removed.iter().map(|p| path_size(p)).sum();
+    if options.dry_run {
+        return Ok(report);
+    }
+
+    for path in &report.removed {
+        if path.is_dir() {
+            tokio::fs::remove_dir_all(path).await?;
+        } else {
+            tokio::fs::remove_file(path).await?;
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clean_all_requires_install() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(temp_dir.path().join("important.txt"), "data").unwrap();
+
+        let options = CleanOptions {
+            all: true,
+            ..Default::default()
+        };
+        let result = clean(temp_dir.path(), &options).await;
+        assert!(matches!(result, Err(MsvcKitError::InstallPath(_))));
+        assert!(temp_dir.path().join("important.txt").exists());
+    }
+
+    #[tokio::test]
+    async fn test_clean_rejects_path_versions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("msvc-kit");
+        std::fs::create_dir_all(root.join("VC/Tools/MSVC")).unwrap();
+        std::fs::create_dir_all(root.join("Windows Kits/10/Include")).unwrap();
+        std::fs::write(temp_dir.path().join("important.txt"), "data").unwrap();
+
+        let outside = temp_dir.path().to_string_lossy().to_string();
+        for version in [
+            "..",
+            "../../../..",
+            ".",
+            "",
+            "14.44/..",
+            "a\\b",
+            outside.as_str(),
+        ] {
+            for options in [
+                CleanOptions {
+                    msvc_version: Some(version.to_string()),
+                    ..Default::default()
+                },
+                CleanOptions {
+                    sdk_version: Some(version.to_string()),
+                    ..Default::default()
+                },
+            ] {
+                let result = clean(&root, &options).await;
+                assert!(
+                    matches!(result, Err(MsvcKitError::InstallPath(_))),
+                    "{:?} accepted",
+                    version
+                );
+            }
+        }
+        assert!(temp_dir.path().join("important.txt").exists());
+        assert!(root.join("VC/Tools/MSVC").exists());
+    }
+
+    #[tokio::test]
+    async fn test_clean_orphans_dry_run() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("VC/Tools/MSVC/14.44.34823")).unwrap();
+        std::fs::create_dir_all(root.join("downloads/msvc/14_44_x64_x64")).unwrap();
+        std::fs::write(root.join("downloads/msvc/14_44_x64_x64/a.vsix"), "ok").unwrap();
+        std::fs::write(root.join("downloads/msvc/14_44_x64_x64/b.partial"), "xx").unwrap();
+        std::fs::create_dir_all(root.join(".msvc-kit-staging-sdk")).unwrap();
+
+        assert_eq!(find_orphans(root).len(), 2);
+
+        let options = CleanOptions {
+            msvc_version: Some("14.44.34823".to_string()),
+            sdk_version: Some("10.0.26100.0".to_string()),
+            orphans: true,
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = clean(root, &options).await.unwrap();
+        assert_eq!(report.removed.len(), 3);
+        assert_eq!(
+            report.not_found,
+            vec!["Windows SDK 10.0.26100.0".to_string()]
+        );
+        assert!(root.join("downloads/msvc/14_44_x64_x64/b.partial").exists());
+
+        let report = clean(
+            root,
+            &CleanOptions {
+                dry_run: false,
+                ..options
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.removed.len(), 3);
+        assert!(!root.join("VC/Tools/MSVC/14.44.34823").exists());
+        assert!(!root.join("downloads/msvc/14_44_x64_x64/b.partial").exists());
+        assert!(root.join("downloads/msvc/14_44_x64_x64/a.vsix").exists());
+    }
+
+    #[test]
+    fn test_find_orphans_chunk_parts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let downloads = temp_dir.path().join("downloads/sdk/10_0_26100_0_x64");
+        std::fs::create_dir_all(&downloads).unwrap();
+        for name in ["big.msi", "big.msi.part0", "big.msi.part1", "a.cab"] {
+            std::fs::write(downloads.join(name), "x").unwrap();
+        }
+
+        assert_eq!(
+            find_orphans(temp_dir.path()),
+            vec![
+                downloads.join("big.msi.part0"),
+                downloads.join("big.msi.part1")
+            ]
+        );
+    }
+}
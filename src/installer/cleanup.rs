@@ -0,0 +1,281 @@
+//! Removing downloaded payloads once they are installed
+//!
+//! A payload is only deleted when the download index and the install receipts
+//! show it is no longer needed: it finished downloading, its hash was
+//! verified, and its package was extracted into the install directory.
+//! Anything else (partial downloads, unverified payloads, packages skipped by
+//! a failed extraction) is kept.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::receipt::PackageReceipt;
+use super::InstallInfo;
+use crate::downloader::{DownloadIndex, DownloadStatus, IndexEntry};
+use crate::error::Result;
+
+/// Directory levels above a payload searched for its `index.db`
+///
+/// Payload file names may contain subdirectories (`Installers\x.msi`).
+const INDEX_SEARCH_DEPTH: usize = 3;
+
+/// What to do with downloaded payloads after a successful extraction
+///
+/// Written as `keep`, `remove-after-extract` or `<days>d` (e.g. `7d`) on the
+/// command line and in `MSVC_KIT_AUTO_CLEANUP`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CleanupPolicy {
+    /// Keep all downloads
+    #[default]
+    Keep,
+    /// Delete payloads as soon as their package is extracted
+    RemoveAfterExtract,
+    /// Delete extracted payloads downloaded more than this many days ago
+    KeepDays(u32),
+}
+
+impl std::fmt::Display for CleanupPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CleanupPolicy::Keep => write!(f, "keep"),
+            CleanupPolicy::RemoveAfterExtract => write!(f, "remove-after-extract"),
+            CleanupPolicy::KeepDays(days) => write!(f, "{}d", days),
+        }
+    }
+}
+
+impl std::str::FromStr for CleanupPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let value = s.trim().to_lowercase();
+        match value.as_str() {
+            "keep" => Ok(CleanupPolicy::Keep),
+            "remove-after-extract" | "remove" => Ok(CleanupPolicy::RemoveAfterExtract),
+            _ => value
+                .strip_suffix('d')
+                .and_then(|days| days.parse().ok())
+                .map(CleanupPolicy::KeepDays)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown cleanup policy: {} (expected keep, remove-after-extract or <days>d)",
+                        s
+                    )
+                }),
+        }
+    }
+}
+
+/// Downloads removed by [`cleanup_extracted_downloads`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupReport {
+    /// Deleted payload files
+    pub removed: Vec<PathBuf>,
+    /// Bytes reclaimed by deleting them
+    pub bytes_reclaimed: u64,
+}
+
+/// Whether an index entry allows deleting its payload under `policy`
+fn is_removable(entry: &IndexEntry, policy: CleanupPolicy) -> bool {
+    if entry.status != DownloadStatus::Completed || !entry.hash_verified {
+        return false;
+    }
+    match policy {
+        CleanupPolicy::Keep => false,
+        CleanupPolicy::RemoveAfterExtract => true,
+        CleanupPolicy::KeepDays(days) => {
+            Utc::now() - entry.updated_at >= Duration::days(i64::from(days))
+        }
+    }
+}
+
+/// Directory holding the `index.db` that tracks `file`
+pub(crate) fn find_index_dir(file: &Path) -> Option<PathBuf> {
+    file.ancestors()
+        .skip(1)
+        .take(INDEX_SEARCH_DEPTH)
+        .find(|dir| dir.join("index.db").is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Delete the downloads of `info` that its cleanup policy allows
+///
+/// Called by the `extract_and_finalize_*` functions after a successful
+/// extraction. Payloads without an index entry, a verified hash or an
+/// extraction receipt in `info.install_path` are kept. Removed payloads are
+/// dropped from the index so the next run downloads them again.
+pub async fn cleanup_extracted_downloads(info: &InstallInfo) -> Result<CleanupReport> {
+    let mut report = CleanupReport::default();
+    if info.cleanup == CleanupPolicy::Keep {
+        return Ok(report);
+    }
+
+    let mut by_index: HashMap<PathBuf, Vec<&PathBuf>> = HashMap::new();
+    for file in &info.downloaded_files {
+        match find_index_dir(file) {
+            Some(dir) => by_index.entry(dir).or_default().push(file),
+            None => tracing::debug!("Keeping {:?}: not tracked by a download index", file),
+        }
+    }
+
+    for (dir, files) in by_index {
+        let mut index = DownloadIndex::load(&dir.join("index.db")).await?;
+        let entries = index.entries().await?;
+
+        for file in files {
+            let Some(entry) = entries
+                .iter()
+                .find(|e| e.local_path == *file || dir.join(&e.file_name) == *file)
+            else {
+                continue;
+            };
+            let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if !PackageReceipt::path_for(&info.install_path, name).exists()
+                || !is_removable(entry, info.cleanup)
+            {
+                continue;
+            }
+
+            let size = match tokio::fs::metadata(file).await {
+                Ok(meta) => meta.len(),
+                Err(_) => continue,
+            };
+            if let Err(e) = tokio::fs::remove_file(file).await {
+                tracing::warn!("Failed to remove download {:?}: {}", file, e);
+                continue;
+            }
+            index.remove(&entry.file_name).await?;
+            report.bytes_reclaimed += size;
+            report.removed.push(file.clone());
+        }
+    }
+
+    if !report.removed.is_empty() {
+        tracing::info!(
+            "Removed {} extracted downloads ({} bytes)",
+            report.removed.len(),
+            report.bytes_reclaimed
+        );
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::PackagePayload;
+    use crate::version::Architecture;
+
+    #[test]
+    fn test_policy_parse_and_display() {
+        for policy in [
+            CleanupPolicy::Keep,
+            CleanupPolicy::RemoveAfterExtract,
+            CleanupPolicy::KeepDays(7),
+        ] {
+            assert_eq!(policy.to_string().parse::<CleanupPolicy>(), Ok(policy));
+        }
+        assert_eq!(
+            "remove".parse::<CleanupPolicy>(),
+            Ok(CleanupPolicy::RemoveAfterExtract)
+        );
+        assert!("7".parse::<CleanupPolicy>().is_err());
+        assert!("soon".parse::<CleanupPolicy>().is_err());
+    }
+
+    async fn setup(
+        download_dir: &Path,
+        name: &str,
+        sha256: Option<&str>,
+        computed: Option<&str>,
+    ) -> PathBuf {
+        let path = download_dir.join(name);
+        tokio::fs::write(&path, b"payload").await.unwrap();
+        let payload = PackagePayload {
+            file_name: name.to_string(),
+            url: format!("https://example.com/{}", name),
+            size: 7,
+            sha256: sha256.map(str::to_string),
+            mirrors: Vec::new(),
+        };
+        let mut index = DownloadIndex::load(&download_dir.join("index.db"))
+            .await
+            .unwrap();
+        index
+            .mark_completed(&payload, path.clone(), computed.map(str::to_string))
+            .await
+            .unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_only_removes_verified_extracted_payloads() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let download_dir = temp_dir.path().join("downloads");
+        let install_dir = temp_dir.path().join("install");
+        tokio::fs::create_dir_all(&download_dir).await.unwrap();
+        tokio::fs::create_dir_all(install_dir.join(crate::installer::RECEIPT_DIR_NAME))
+            .await
+            .unwrap();
+
+        let verified = setup(&download_dir, "a.vsix", Some("aa"), Some("aa")).await;
+        let mismatched = setup(&download_dir, "b.vsix", Some("aa"), Some("bb")).await;
+        let not_extracted = setup(&download_dir, "c.vsix", Some("cc"), Some("cc")).await;
+        let untracked = download_dir.join("d.vsix");
+        tokio::fs::write(&untracked, b"payload").await.unwrap();
+        for name in ["a.vsix", "b.vsix", "d.vsix"] {
+            tokio::fs::write(PackageReceipt::path_for(&install_dir, name), b"ok")
+                .await
+                .unwrap();
+        }
+
+        let mut info = InstallInfo {
+            component_type: "msvc".to_string(),
+            version: "14.44".to_string(),
+            install_path: install_dir,
+            downloaded_files: vec![
+                verified.clone(),
+                mismatched.clone(),
+                not_extracted.clone(),
+                untracked.clone(),
+            ],
+            arch: Architecture::X64,
+            extract_filters: vec![],
+            cleanup: CleanupPolicy::Keep,
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        };
+        assert!(cleanup_extracted_downloads(&info)
+            .await
+            .unwrap()
+            .removed
+            .is_empty());
+
+        info.cleanup = CleanupPolicy::KeepDays(30);
+        assert!(cleanup_extracted_downloads(&info)
+            .await
+            .unwrap()
+            .removed
+            .is_empty());
+
+        info.cleanup = CleanupPolicy::RemoveAfterExtract;
+        let report = cleanup_extracted_downloads(&info).await.unwrap();
+        assert_eq!(report.removed, vec![verified.clone()]);
+        assert_eq!(report.bytes_reclaimed, 7);
+        assert!(!verified.exists());
+        assert!(mismatched.exists() && not_extracted.exists() && untracked.exists());
+
+        let index = DownloadIndex::load(&download_dir.join("index.db"))
+            .await
+            .unwrap();
+        assert!(index.get_entry("a.vsix").await.unwrap().is_none());
+        assert!(index.get_entry("b.vsix").await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,118 @@
+//! Adding optional components to an existing install
+//!
+//! A build that suddenly needs Spectre-mitigated libraries or ATL should not
+//! require reinstalling the toolset. [`add_components`] downloads only the
+//! packages of the requested [`MsvcComponent`]s for the installed toolset and
+//! extracts them into the same tree.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::extract_and_finalize_msvc_with_report;
+use super::repair::installed_msvc_prefix;
+use crate::downloader::{download_msvc, DownloadOptions, MsvcComponent};
+use crate::error::{MsvcKitError, Result};
+
+/// Result of [`add_components`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddComponentsReport {
+    /// MSVC toolset the components were added to
+    pub msvc_version: String,
+    /// Added components, e.g. `spectre`
+    pub components: Vec<String>,
+    /// Payloads downloaded and extracted
+    pub payloads: Vec<String>,
+}
+
+/// Add optional MSVC components to the install in `install_dir`
+///
+/// The toolset defaults to the newest installed one; set
+/// `options.msvc_version` to extend another. Only the packages of
+/// `components` are downloaded, for `options.arch`; `options.target_dir`,
+/// `include_components` and `only_patterns` are replaced, the other options
+/// (cache, verification, offline mode, ...) are used as given.
+///
+/// Fails with [`MsvcKitError::ComponentNotFound`] if the toolset has no
+/// packages for the components.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::add_components;
+/// use msvc_kit::{DownloadOptions, MsvcComponent};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let options = DownloadOptions::default();
+///     let report = add_components("C:/msvc-kit".as_ref(), [MsvcComponent::Spectre], &options).await?;
+///     println!("Added {} payloads to MSVC {}", report.payloads.len(), report.msvc_version);
+///     Ok(())
+/// }
+/// ```
+pub async fn add_components(
+    install_dir: &Path,
+    components: impl IntoIterator<Item = MsvcComponent>,
+    options: &DownloadOptions,
+) -> Result<AddComponentsReport> {
+    let components: Vec<MsvcComponent> = components.into_iter().collect();
+    if components.is_empty() {
+        return Err(MsvcKitError::Config("No components to add".to_string()));
+    }
+
+    let mut opts = options.clone();
+    opts.target_dir = install_dir.to_path_buf();
+    if opts.msvc_version.is_none() {
+        opts.msvc_version = Some(installed_msvc_prefix(install_dir)?);
+    }
+    opts.headers_only = false;
+    opts.include_components = components.iter().cloned().collect();
+    opts.only_patterns = components.iter().map(|c| c.id_fragment()).collect();
+    opts.allow_broken_selection = true;
+
+    let mut info = download_msvc(&opts).await?;
+    let names: Vec<String> = components.iter().map(|c| c.to_string()).collect();
+    if info.downloaded_files.is_empty() {
+        return Err(MsvcKitError::ComponentNotFound(format!(
+            "No {} packages for MSVC {} ({})",
+            names.join(", "),
+            info.version,
+            opts.arch
+        )));
+    }
+
+    let payloads = info
+        .downloaded_files
+        .iter()
+        .filter_map(|f| f.file_name().and_then(|n| n.to_str()))
+        .map(str::to_string)
+        .collect();
+    extract_and_finalize_msvc_with_report(&mut info, false).await?;
+
+    Ok(AddComponentsReport {
+        msvc_version: info.version,
+        components: names,
+        payloads,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_add_components_requires_install() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let options = DownloadOptions::default();
+
+        let err = add_components(temp_dir.path(), [], &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MsvcKitError::Config(_)));
+
+        let err = add_components(temp_dir.path(), [MsvcComponent::Spectre], &options)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, MsvcKitError::ComponentNotFound(_)));
+    }
+}
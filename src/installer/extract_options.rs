@@ -0,0 +1,128 @@
+//! Options for extracting individual payloads
+//!
+//! [`ExtractOptions`] controls what happens to files that already exist,
+//! which members are extracted, and lets callers observe every member as it
+//! is handled. With `list_only`, nothing is written and the members that
+//! would be extracted are only reported.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::filter::ExtractFilter;
+
+/// What to do when an extracted file already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverwritePolicy {
+    /// Replace the existing file (default)
+    #[default]
+    Overwrite,
+    /// Keep the existing file and skip the member
+    Skip,
+    /// Fail with [`MsvcKitError::Extraction`](crate::MsvcKitError::Extraction)
+    Error,
+}
+
+/// How an archive member was handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryAction {
+    /// The file was written
+    Extracted,
+    /// The file already existed and was kept ([`OverwritePolicy::Skip`])
+    Skipped,
+    /// The file would be written (`list_only`)
+    Listed,
+}
+
+/// An archive member handled during extraction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractEntry {
+    /// Payload file name
+    pub archive: String,
+    /// Path relative to the target directory, using forward slashes
+    pub path: String,
+    /// Destination of the file
+    pub destination: PathBuf,
+    /// Uncompressed size in bytes, if known
+    pub size: u64,
+    /// What happened to the member
+    pub action: EntryAction,
+}
+
+/// Callback invoked for every extracted, skipped or listed member
+///
+/// Called from the extraction thread, so it should return quickly.
+pub type EntryCallback = Arc<dyn Fn(&ExtractEntry) + Send + Sync>;
+
+/// Options for [`extract_package_with_options`](super::extract_package_with_options)
+/// and the per-format `extract_*_with_options` functions
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{
+///     extract_package_with_options, ExtractFilter, ExtractOptions, OverwritePolicy,
+/// };
+/// use std::path::Path;
+/// use std::sync::Arc;
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// let options = ExtractOptions {
+///     overwrite: OverwritePolicy::Skip,
+///     filters: ExtractFilter::new(["**/include/**"]),
+///     on_entry: Some(Arc::new(|entry| println!("{:?} {}", entry.action, entry.path))),
+///     ..Default::default()
+/// };
+/// let outcome =
+///     extract_package_with_options(Path::new("headers.vsix"), Path::new("out"), &options)
+///         .await?;
+/// println!("{} members", outcome.entries.len());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ExtractOptions {
+    /// Handling of files that already exist
+    pub overwrite: OverwritePolicy,
+    /// Only members matching these patterns are extracted
+    pub filters: ExtractFilter,
+    /// Called for every member after it has been handled
+    pub on_entry: Option<EntryCallback>,
+    /// Only report the members, without writing anything
+    pub list_only: bool,
+}
+
+impl std::fmt::Debug for ExtractOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExtractOptions")
+            .field("overwrite", &self.overwrite)
+            .field("filters", &self.filters)
+            .field("on_entry", &self.on_entry.is_some())
+            .field("list_only", &self.list_only)
+            .finish()
+    }
+}
+
+impl ExtractOptions {
+    /// Options extracting only members matching `filters`
+    pub fn filtered(filters: ExtractFilter) -> Self {
+        Self {
+            filters,
+            ..Default::default()
+        }
+    }
+
+    /// Whether members must be handled one by one
+    ///
+    /// External MSI tools extract everything at once; anything beyond the
+    /// defaults requires extracting to a staging directory first.
+    pub(crate) fn needs_staging(&self) -> bool {
+        !self.filters.is_empty()
+            || self.overwrite != OverwritePolicy::Overwrite
+            || self.on_entry.is_some()
+            || self.list_only
+    }
+}
@@ -5,10 +5,14 @@ use std::fs::File;
 use std::io::{Read, Write};
 use std::path::Path;
 use std::sync::Mutex;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use sha2::{Digest, Sha256};
 
+use super::extract_options::{EntryAction, ExtractEntry, ExtractOptions, OverwritePolicy};
+use super::msi_media::missing_cabinets;
+use super::receipt::{ExtractWarning, ReceiptEntry};
 use crate::constants::{extraction as ext_const, progress as progress_const};
 use crate::error::{MsvcKitError, Result};
 
@@ -48,28 +52,172 @@ pub(crate) fn progress_style_items() -> ProgressStyle {
         .progress_chars("##-")
 }
 
+/// Files written by extracting one payload, plus non-fatal problems
+#[derive(Debug, Clone, Default)]
+pub struct Extracted {
+    /// Files written, for the package receipt
+    pub files: Vec<ReceiptEntry>,
+    /// Members extracted, skipped or listed, in archive order
+    ///
+    /// Empty for MSI payloads extracted with default options, since the
+    /// external tool writes the files itself.
+    pub entries: Vec<ExtractEntry>,
+    /// Problems that did not fail the extraction
+    pub warnings: Vec<ExtractWarning>,
+}
+
+impl Extracted {
+    /// Record a handled member and pass it to the callback
+    fn record(&mut self, options: &ExtractOptions, entry: ExtractEntry) {
+        if let Some(on_entry) = &options.on_entry {
+            on_entry(&entry);
+        }
+        self.entries.push(entry);
+    }
+}
+
+/// Receipt path for an archive member: forward slashes, no leading separator
+fn receipt_path(name: &str) -> String {
+    name.replace('\\', "/").trim_start_matches('/').to_string()
+}
+
+fn archive_name(archive: &Path) -> String {
+    archive
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| archive.display().to_string())
+}
+
+/// Attach the payload, member and destination to a per-file IO error
+fn file_error(
+    archive: &Path,
+    entry: &str,
+    destination: &Path,
+    source: std::io::Error,
+) -> MsvcKitError {
+    MsvcKitError::Extraction {
+        archive: archive_name(archive),
+        entry: entry.to_string(),
+        destination: destination.to_path_buf(),
+        source,
+    }
+}
+
+/// How to handle a member before writing it, or `None` to write it
+///
+/// Fails if the file exists and the policy is [`OverwritePolicy::Error`].
+fn pre_write_action(
+    options: &ExtractOptions,
+    archive: &Path,
+    entry: &str,
+    out_path: &Path,
+) -> Result<Option<EntryAction>> {
+    if options.list_only {
+        return Ok(Some(EntryAction::Listed));
+    }
+    if out_path.symlink_metadata().is_err() {
+        return Ok(None);
+    }
+    match options.overwrite {
+        OverwritePolicy::Overwrite => Ok(None),
+        OverwritePolicy::Skip => Ok(Some(EntryAction::Skipped)),
+        OverwritePolicy::Error => Err(file_error(
+            archive,
+            entry,
+            out_path,
+            std::io::Error::new(std::io::ErrorKind::AlreadyExists, "file already exists"),
+        )),
+    }
+}
+
+/// Copy one archive member to `out_path`, returning the file, its SHA256 and size
+fn write_member(
+    reader: &mut dyn Read,
+    out_path: &Path,
+    pb: Option<&ProgressBar>,
+) -> std::io::Result<(File, String, u64)> {
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut out_file = File::create(out_path)?;
+    let mut hasher = Sha256::new();
+    let mut size = 0u64;
+    let mut buffer = [0u8; ext_const::EXTRACT_BUFFER_SIZE];
+    loop {
+        let n = reader.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        out_file.write_all(&buffer[..n])?;
+        hasher.update(&buffer[..n]);
+        size += n as u64;
+        if let Some(pb) = pb {
+            pb.inc(n as u64);
+        }
+    }
+    Ok((out_file, hex::encode(hasher.finalize()), size))
+}
+
+/// Modification time of a ZIP member
+///
+/// ZIP (DOS) timestamps carry no time zone; they are taken as UTC.
+fn zip_time(time: zip::DateTime) -> Option<SystemTime> {
+    let date = chrono::NaiveDate::from_ymd_opt(
+        time.year().into(),
+        time.month().into(),
+        time.day().into(),
+    )?;
+    let time = date.and_hms_opt(
+        time.hour().into(),
+        time.minute().into(),
+        time.second().into(),
+    )?;
+    Some(time.and_utc().into())
+}
+
 /// Extract a VSIX file (which is a ZIP archive) with optional progress bar
+///
+/// Only files matching `options.filters` are written. Returns the files
+/// written, for the package receipt.
 pub(crate) async fn extract_vsix_with_progress(
     vsix_path: &Path,
     target_dir: &Path,
     show_progress: bool,
-) -> Result<()> {
+    options: &ExtractOptions,
+) -> Result<Extracted> {
     let vsix_path = vsix_path.to_path_buf();
     let target_dir = target_dir.to_path_buf();
+    let options = options.clone();
 
-    tokio::task::spawn_blocking(move || extract_vsix_sync(&vsix_path, &target_dir, show_progress))
-        .await
-        .map_err(|e| MsvcKitError::Other(format!("Task join error: {}", e)))??;
-
-    Ok(())
+    tokio::task::spawn_blocking(move || {
+        extract_vsix_sync(&vsix_path, &target_dir, show_progress, &options)
+    })
+    .await
+    .map_err(|e| MsvcKitError::Other(format!("Task join error: {}", e)))?
 }
 
 /// Extract a VSIX file (which is a ZIP archive) with progress bar
 pub async fn extract_vsix(vsix_path: &Path, target_dir: &Path) -> Result<()> {
-    extract_vsix_with_progress(vsix_path, target_dir, inner_progress_enabled()).await
+    extract_vsix_with_options(vsix_path, target_dir, &ExtractOptions::default()).await?;
+    Ok(())
 }
 
-fn extract_vsix_sync(vsix_path: &Path, target_dir: &Path, show_progress: bool) -> Result<()> {
+/// Extract a VSIX file (which is a ZIP archive) as configured by `options`
+pub async fn extract_vsix_with_options(
+    vsix_path: &Path,
+    target_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<Extracted> {
+    extract_vsix_with_progress(vsix_path, target_dir, inner_progress_enabled(), options).await
+}
+
+fn extract_vsix_sync(
+    vsix_path: &Path,
+    target_dir: &Path,
+    show_progress: bool,
+    options: &ExtractOptions,
+) -> Result<Extracted> {
+    let filter = &options.filters;
     // Pre-compute total bytes for progress bar (skip metadata files)
     let total_bytes = {
         let file = File::open(vsix_path)?;
@@ -81,6 +229,9 @@ fn extract_vsix_sync(vsix_path: &Path, target_dir: &Path, show_progress: bool) -
             if name.starts_with('[') || name == "extension.vsixmanifest" || file.is_dir() {
                 continue;
             }
+            if !filter.matches(name.strip_prefix("Contents/").unwrap_or(name)) {
+                continue;
+            }
             total = total.saturating_add(file.size());
         }
         total
@@ -103,6 +254,7 @@ fn extract_vsix_sync(vsix_path: &Path, target_dir: &Path, show_progress: bool) -
 
     let file = File::open(vsix_path)?;
     let mut archive = zip::ZipArchive::new(file)?;
+    let mut extracted = Extracted::default();
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
@@ -122,54 +274,227 @@ fn extract_vsix_sync(vsix_path: &Path, target_dir: &Path, show_progress: bool) -
         }
 
         if file.is_dir() {
-            std::fs::create_dir_all(&out_path)?;
+            // Directories are created on demand for matching files when filtering
+            if filter.is_empty() && !options.list_only {
+                std::fs::create_dir_all(&out_path)
+                    .map_err(|e| file_error(vsix_path, &name, &out_path, e))?;
+            }
             continue;
         }
-
-        if let Some(parent) = out_path.parent() {
-            std::fs::create_dir_all(parent)?;
+        if !filter.matches(relative_path) {
+            continue;
         }
 
-        let mut out_file = File::create(&out_path)?;
-        let mut buffer = [0u8; ext_const::EXTRACT_BUFFER_SIZE];
-        loop {
-            let n = file.read(&mut buffer)?;
-            if n == 0 {
-                break;
-            }
-            out_file.write_all(&buffer[..n])?;
+        let path = receipt_path(relative_path);
+        if let Some(action) = pre_write_action(options, vsix_path, &name, &out_path)? {
             if let Some(pb) = pb.as_ref() {
-                pb.inc(n as u64);
+                pb.inc(file.size());
+            }
+            extracted.record(
+                options,
+                ExtractEntry {
+                    archive: archive_name(vsix_path),
+                    path,
+                    destination: out_path,
+                    size: file.size(),
+                    action,
+                },
+            );
+            continue;
+        }
+
+        let (out_file, sha256, size) = write_member(&mut file, &out_path, pb.as_ref())
+            .map_err(|e| file_error(vsix_path, &name, &out_path, e))?;
+        if let Some(modified) = file.last_modified().and_then(zip_time) {
+            if let Err(e) = out_file.set_modified(modified) {
+                extracted.warnings.push(ExtractWarning {
+                    package: archive_name(vsix_path),
+                    path: Some(relative_path.to_string()),
+                    message: format!("failed to set modification time: {}", e),
+                });
             }
         }
+
+        extracted.record(
+            options,
+            ExtractEntry {
+                archive: archive_name(vsix_path),
+                path: path.clone(),
+                destination: out_path,
+                size,
+                action: EntryAction::Extracted,
+            },
+        );
+        extracted.files.push(ReceiptEntry { path, sha256, size });
     }
 
     if let Some(pb) = pb {
         pb.finish_with_message("Extracted");
     }
-    Ok(())
+    Ok(extracted)
 }
 
 /// Extract an MSI file
 ///
 /// On Windows, uses msiexec. On other platforms, attempts to use msitools.
+/// Fails with [`MsvcKitError::MissingCabinets`] before running either if
+/// external cabinets listed by the MSI are not next to it.
+/// The external tool writes the files itself, so no receipt entries are returned.
+/// A staging directory that cannot be removed is reported as a warning.
+///
+/// The tools cannot extract a subset, so with filters, an overwrite policy
+/// other than the default, an entry callback or `list_only`, the MSI is
+/// extracted to a staging directory and the files are moved over one by one.
 pub(crate) async fn extract_msi_with_progress(
     msi_path: &Path,
     target_dir: &Path,
     show_progress: bool,
-) -> Result<()> {
+    options: &ExtractOptions,
+) -> Result<Extracted> {
     let msi_path = msi_path.to_path_buf();
     let target_dir = target_dir.to_path_buf();
+    let options = options.clone();
 
-    tokio::task::spawn_blocking(move || extract_msi_sync(&msi_path, &target_dir, show_progress))
-        .await
-        .map_err(|e| MsvcKitError::Other(format!("Task join error: {}", e)))??;
+    tokio::task::spawn_blocking(move || {
+        check_cabinets(&msi_path)?;
+        if !options.needs_staging() {
+            extract_msi_sync(&msi_path, &target_dir, show_progress)?;
+            return Ok(Extracted::default());
+        }
+        let stem = msi_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let staging = target_dir.join(format!(".msvc-kit-staging-{}", stem));
+        std::fs::create_dir_all(&staging)?;
+        let mut extracted = Extracted::default();
+        let result = extract_msi_sync(&msi_path, &staging, show_progress).and_then(|_| {
+            let mut staged = Staged {
+                msi_path: &msi_path,
+                staging: &staging,
+                target_dir: &target_dir,
+                options: &options,
+                extracted: &mut extracted,
+            };
+            staged.move_matching(&staging)
+        });
+        let cleanup = std::fs::remove_dir_all(&staging);
+        result?;
+
+        if let Err(e) = cleanup {
+            extracted.warnings.push(ExtractWarning {
+                package: archive_name(&msi_path),
+                path: None,
+                message: format!(
+                    "failed to remove staging directory {}: {}",
+                    staging.display(),
+                    e
+                ),
+            });
+        }
+        Ok(extracted)
+    })
+    .await
+    .map_err(|e| MsvcKitError::Other(format!("Task join error: {}", e)))?
+}
 
-    Ok(())
+/// Fail with the names of the cabinets the MSI needs that are not next to it
+///
+/// An MSI whose tables cannot be read is left to the extraction tool.
+fn check_cabinets(msi_path: &Path) -> Result<()> {
+    match missing_cabinets(msi_path) {
+        Ok(cabinets) if !cabinets.is_empty() => Err(MsvcKitError::MissingCabinets {
+            installer: archive_name(msi_path),
+            cabinets,
+        }),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            tracing::debug!("Cannot read the cabinets of {:?}: {}", msi_path, e);
+            Ok(())
+        }
+    }
 }
 
 pub async fn extract_msi(msi_path: &Path, target_dir: &Path) -> Result<()> {
-    extract_msi_with_progress(msi_path, target_dir, inner_progress_enabled()).await
+    extract_msi_with_options(msi_path, target_dir, &ExtractOptions::default()).await?;
+    Ok(())
+}
+
+/// Extract an MSI file as configured by `options`
+///
+/// Requires `msiexec` on Windows and `msiextract` (msitools) elsewhere.
+pub async fn extract_msi_with_options(
+    msi_path: &Path,
+    target_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<Extracted> {
+    extract_msi_with_progress(msi_path, target_dir, inner_progress_enabled(), options).await
+}
+
+/// An MSI extracted to a staging directory, moved over file by file
+struct Staged<'a> {
+    msi_path: &'a Path,
+    staging: &'a Path,
+    target_dir: &'a Path,
+    options: &'a ExtractOptions,
+    extracted: &'a mut Extracted,
+}
+
+impl Staged<'_> {
+    /// Move files under `dir` matching the filters from staging to the target
+    fn move_matching(&mut self, dir: &Path) -> Result<()> {
+        let mut paths = std::fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.sort();
+        for path in paths {
+            if path.is_dir() {
+                self.move_matching(&path)?;
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(self.staging) else {
+                continue;
+            };
+            let relative_str = receipt_path(&relative.to_string_lossy());
+            if !self.options.filters.matches(&relative_str) {
+                continue;
+            }
+            let out_path = self.target_dir.join(relative);
+            let action =
+                match pre_write_action(self.options, self.msi_path, &relative_str, &out_path)? {
+                    Some(action) => action,
+                    None => {
+                        if let Some(parent) = out_path.parent() {
+                            std::fs::create_dir_all(parent)?;
+                        }
+                        if std::fs::rename(&path, &out_path).is_err() {
+                            std::fs::copy(&path, &out_path).map_err(|e| {
+                                file_error(self.msi_path, &relative_str, &out_path, e)
+                            })?;
+                        }
+                        EntryAction::Extracted
+                    }
+                };
+            let size = std::fs::metadata(if action == EntryAction::Extracted {
+                &out_path
+            } else {
+                &path
+            })
+            .map(|m| m.len())
+            .unwrap_or(0);
+            self.extracted.record(
+                self.options,
+                ExtractEntry {
+                    archive: archive_name(self.msi_path),
+                    path: relative_str,
+                    destination: out_path,
+                    size,
+                    action,
+                },
+            );
+        }
+        Ok(())
+    }
 }
 
 fn extract_msi_sync(msi_path: &Path, target_dir: &Path, show_progress: bool) -> Result<()> {
@@ -315,35 +640,56 @@ fn extract_msi_sync(msi_path: &Path, target_dir: &Path, show_progress: bool) ->
 }
 
 /// Extract a CAB file with a simple file-count progress bar
+///
+/// Only files matching `options.filters` are written. Returns the files
+/// written, for the package receipt.
 pub(crate) async fn extract_cab_with_progress(
     cab_path: &Path,
     target_dir: &Path,
     show_progress: bool,
-) -> Result<()> {
+    options: &ExtractOptions,
+) -> Result<Extracted> {
     let cab_path = cab_path.to_path_buf();
     let target_dir = target_dir.to_path_buf();
+    let options = options.clone();
 
-    tokio::task::spawn_blocking(move || extract_cab_sync(&cab_path, &target_dir, show_progress))
-        .await
-        .map_err(|e| MsvcKitError::Other(format!("Task join error: {}", e)))??;
+    tokio::task::spawn_blocking(move || {
+        extract_cab_sync(&cab_path, &target_dir, show_progress, &options)
+    })
+    .await
+    .map_err(|e| MsvcKitError::Other(format!("Task join error: {}", e)))?
+}
 
+pub async fn extract_cab(cab_path: &Path, target_dir: &Path) -> Result<()> {
+    extract_cab_with_options(cab_path, target_dir, &ExtractOptions::default()).await?;
     Ok(())
 }
 
-pub async fn extract_cab(cab_path: &Path, target_dir: &Path) -> Result<()> {
-    extract_cab_with_progress(cab_path, target_dir, inner_progress_enabled()).await
+/// Extract a CAB file as configured by `options`
+pub async fn extract_cab_with_options(
+    cab_path: &Path,
+    target_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<Extracted> {
+    extract_cab_with_progress(cab_path, target_dir, inner_progress_enabled(), options).await
 }
 
-fn extract_cab_sync(cab_path: &Path, target_dir: &Path, show_progress: bool) -> Result<()> {
+fn extract_cab_sync(
+    cab_path: &Path,
+    target_dir: &Path,
+    show_progress: bool,
+    options: &ExtractOptions,
+) -> Result<Extracted> {
     let file = File::open(cab_path)?;
     let cabinet = cab::Cabinet::new(file)
         .map_err(|e| MsvcKitError::Cab(format!("Failed to open CAB: {}", e)))?;
 
     // Collect file names first by iterating through folders
-    let file_names: Vec<String> = cabinet
+    let file_names: Vec<(String, u64)> = cabinet
         .folder_entries()
         .flat_map(|folder| folder.file_entries())
-        .map(|entry| entry.name().to_string())
+        .map(|entry| (entry.name().to_string(), entry.uncompressed_size().into()))
+        .filter(|(name, _)| options.filters.matches(name))
         .collect();
 
     let total_files = file_names.len() as u64;
@@ -367,37 +713,59 @@ fn extract_cab_sync(cab_path: &Path, target_dir: &Path, show_progress: bool) ->
     // This is a limitation of the crate, not an efficiency issue we can fix here.
     // A future optimization would be to use a different CAB library or implement
     // streaming extraction.
-    for (idx, name) in file_names.iter().enumerate() {
+    let mut extracted = Extracted {
+        files: Vec::with_capacity(file_names.len()),
+        ..Default::default()
+    };
+    for (idx, (name, entry_size)) in file_names.iter().enumerate() {
         let out_path = target_dir.join(name);
 
-        if let Some(parent) = out_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
         if let Some(pb) = pb.as_ref() {
             pb.set_message(format!("{} ({}/{})", name, idx + 1, total_files));
         }
 
+        if let Some(action) = pre_write_action(options, cab_path, name, &out_path)? {
+            if let Some(pb) = pb.as_ref() {
+                pb.inc(1);
+            }
+            extracted.record(
+                options,
+                ExtractEntry {
+                    archive: archive_name(cab_path),
+                    path: receipt_path(name),
+                    destination: out_path,
+                    size: *entry_size,
+                    action,
+                },
+            );
+            continue;
+        }
+
         // Re-open cabinet to read the file (cab crate limitation)
         let file = File::open(cab_path)?;
         let mut cabinet = cab::Cabinet::new(file)
             .map_err(|e| MsvcKitError::Cab(format!("Failed to open CAB: {}", e)))?;
 
-        let mut reader = cabinet
+        let (_, sha256, size) = cabinet
             .read_file(name)
-            .map_err(|e| MsvcKitError::Cab(format!("Failed to read file {}: {}", name, e)))?;
-
-        let mut out_file = File::create(&out_path)?;
-        let mut buffer = [0u8; ext_const::EXTRACT_BUFFER_SIZE];
-        loop {
-            let n = reader
-                .read(&mut buffer)
-                .map_err(|e| MsvcKitError::Cab(format!("Failed to read file content: {}", e)))?;
-            if n == 0 {
-                break;
-            }
-            out_file.write_all(&buffer[..n])?;
-        }
+            .and_then(|mut reader| write_member(&mut reader, &out_path, None))
+            .map_err(|e| file_error(cab_path, name, &out_path, e))?;
+
+        extracted.record(
+            options,
+            ExtractEntry {
+                archive: archive_name(cab_path),
+                path: receipt_path(name),
+                destination: out_path,
+                size,
+                action: EntryAction::Extracted,
+            },
+        );
+        extracted.files.push(ReceiptEntry {
+            path: receipt_path(name),
+            sha256,
+            size,
+        });
 
         if let Some(pb) = pb.as_ref() {
             pb.inc(1);
@@ -407,7 +775,7 @@ fn extract_cab_sync(cab_path: &Path, target_dir: &Path, show_progress: bool) ->
     if let Some(pb) = pb {
         pb.finish_with_message("CAB extracted");
     }
-    Ok(())
+    Ok(extracted)
 }
 
 /// Determine the extraction method based on file extension
@@ -428,9 +796,191 @@ pub fn get_extractor(path: &Path) -> Option<fn(&Path, &Path) -> Result<()>> {
 mod tests {
     use super::*;
 
+    use crate::installer::ExtractFilter;
+    use std::sync::Arc;
     #[allow(unused_imports)]
     use tempfile::TempDir;
 
+    #[test]
+    fn test_receipt_path() {
+        assert_eq!(receipt_path("VC/include/x.h"), "VC/include/x.h");
+        assert_eq!(receipt_path("\\Lib\\um\\x64\\k.lib"), "Lib/um/x64/k.lib");
+    }
+
+    #[test]
+    fn test_vsix_extraction_returns_receipt_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let vsix = temp_dir.path().join("test.vsix");
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&vsix).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("extension.vsixmanifest", options).unwrap();
+            zip.write_all(b"<PackageManifest/>").unwrap();
+            zip.start_file("Contents/VC/include/x.h", options).unwrap();
+            zip.write_all(b"hello world").unwrap();
+            zip.finish().unwrap();
+        }
+
+        let out = temp_dir.path().join("out");
+        let extracted = extract_vsix_sync(&vsix, &out, false, &ExtractOptions::default()).unwrap();
+        assert_eq!(
+            extracted.files,
+            vec![ReceiptEntry {
+                path: "VC/include/x.h".to_string(),
+                sha256: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+                    .to_string(),
+                size: 11,
+            }]
+        );
+        assert!(out.join("VC/include/x.h").is_file());
+    }
+
+    #[test]
+    fn test_vsix_extraction_filtered() {
+        let temp_dir = TempDir::new().unwrap();
+        let vsix = temp_dir.path().join("test.vsix");
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&vsix).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("Contents/VC/include/x.h", options).unwrap();
+            zip.write_all(b"header").unwrap();
+            zip.start_file("Contents/VC/bin/cl.exe", options).unwrap();
+            zip.write_all(b"binary").unwrap();
+            zip.finish().unwrap();
+        }
+
+        let out = temp_dir.path().join("out");
+        let options = ExtractOptions::filtered(ExtractFilter::new(["**/include/**"]));
+        let extracted = extract_vsix_sync(&vsix, &out, false, &options).unwrap();
+        assert_eq!(extracted.files.len(), 1);
+        assert!(out.join("VC/include/x.h").is_file());
+        assert!(!out.join("VC/bin").exists());
+    }
+
+    #[test]
+    fn test_vsix_extraction_error_context() {
+        let temp_dir = TempDir::new().unwrap();
+        let vsix = temp_dir.path().join("broken.vsix");
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&vsix).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("Contents/VC/include/x.h", options).unwrap();
+            zip.write_all(b"header").unwrap();
+            zip.finish().unwrap();
+        }
+
+        // A directory where the file should go makes the write fail
+        let out = temp_dir.path().join("out");
+        std::fs::create_dir_all(out.join("VC/include/x.h")).unwrap();
+        let err = extract_vsix_sync(&vsix, &out, false, &ExtractOptions::default()).unwrap_err();
+        match err {
+            MsvcKitError::Extraction {
+                archive,
+                entry,
+                destination,
+                ..
+            } => {
+                assert_eq!(archive, "broken.vsix");
+                assert_eq!(entry, "Contents/VC/include/x.h");
+                assert_eq!(destination, out.join("VC/include/x.h"));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_vsix_extraction_options() {
+        let temp_dir = TempDir::new().unwrap();
+        let vsix = temp_dir.path().join("test.vsix");
+        {
+            let mut zip = zip::ZipWriter::new(File::create(&vsix).unwrap());
+            let options = zip::write::SimpleFileOptions::default();
+            zip.start_file("Contents/VC/include/x.h", options).unwrap();
+            zip.write_all(b"new").unwrap();
+            zip.start_file("Contents/VC/include/y.h", options).unwrap();
+            zip.write_all(b"new").unwrap();
+            zip.finish().unwrap();
+        }
+        let out = temp_dir.path().join("out");
+        std::fs::create_dir_all(out.join("VC/include")).unwrap();
+        std::fs::write(out.join("VC/include/x.h"), "old").unwrap();
+
+        // Listing writes nothing but reports every member to the callback
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let callback_seen = seen.clone();
+        let options = ExtractOptions {
+            list_only: true,
+            on_entry: Some(Arc::new(move |entry: &ExtractEntry| {
+                callback_seen.lock().unwrap().push(entry.path.clone())
+            })),
+            ..Default::default()
+        };
+        let listed = extract_vsix_sync(&vsix, &out, false, &options).unwrap();
+        assert!(listed.files.is_empty());
+        assert!(listed
+            .entries
+            .iter()
+            .all(|e| e.action == EntryAction::Listed && e.size == 3));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["VC/include/x.h", "VC/include/y.h"]
+        );
+        assert!(!out.join("VC/include/y.h").exists());
+
+        let options = ExtractOptions {
+            overwrite: OverwritePolicy::Skip,
+            ..Default::default()
+        };
+        let extracted = extract_vsix_sync(&vsix, &out, false, &options).unwrap();
+        let actions: Vec<EntryAction> = extracted.entries.iter().map(|e| e.action).collect();
+        assert_eq!(actions, vec![EntryAction::Skipped, EntryAction::Extracted]);
+        assert_eq!(extracted.files.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(out.join("VC/include/x.h")).unwrap(),
+            "old"
+        );
+
+        let options = ExtractOptions {
+            overwrite: OverwritePolicy::Error,
+            ..Default::default()
+        };
+        let err = extract_vsix_sync(&vsix, &out, false, &options).unwrap_err();
+        assert!(
+            matches!(err, MsvcKitError::Extraction { entry, .. } if entry == "Contents/VC/include/x.h")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_msi_extraction_missing_cabinets() {
+        let temp_dir = TempDir::new().unwrap();
+        let msi = temp_dir
+            .path()
+            .join("Windows SDK Desktop Libs x64-x86_en-us.msi");
+        std::fs::write(
+            &msi,
+            crate::installer::msi_media::synthetic_msi(&[
+                Some("0a1b2c3d.cab"),
+                Some("ffeeddcc.cab"),
+            ]),
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("0a1b2c3d.cab"), b"").unwrap();
+
+        let err = extract_msi(&msi, &temp_dir.path().join("out"))
+            .await
+            .unwrap_err();
+        match err {
+            MsvcKitError::MissingCabinets {
+                installer,
+                cabinets,
+            } => {
+                assert_eq!(installer, "Windows SDK Desktop Libs x64-x86_en-us.msi");
+                assert_eq!(cabinets, vec!["ffeeddcc.cab"]);
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
     #[test]
     fn test_get_extractor() {
         assert!(get_extractor(Path::new("test.vsix")).is_some());
@@ -0,0 +1,112 @@
+//! Glob filters for partial extraction
+//!
+//! Patterns are matched case-insensitively against the path of each archive
+//! member relative to the install directory, using `/` as separator:
+//!
+//! - `*` matches any run of characters within one path segment
+//! - `?` matches a single character
+//! - `**` as a whole segment matches any number of segments (including none)
+//!
+//! For example `**/include/**` keeps every header tree, and
+//! `VC/Tools/MSVC/*/lib/x64/*.lib` keeps the x64 MSVC import libraries.
+
+/// Set of glob patterns selecting which files to extract
+///
+/// An empty filter matches every path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtractFilter {
+    patterns: Vec<Vec<String>>,
+}
+
+impl ExtractFilter {
+    /// Create a filter from glob patterns; blank patterns are ignored
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let patterns = patterns
+            .into_iter()
+            .filter_map(|p| {
+                let p = normalize(p.as_ref());
+                (!p.is_empty()).then(|| p.split('/').map(str::to_string).collect())
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    /// Whether the filter has no patterns (and thus matches everything)
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Whether a path (relative to the install directory) should be extracted
+    pub fn matches(&self, path: &str) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        let path = normalize(path);
+        let segments: Vec<&str> = path.split('/').collect();
+        self.patterns.iter().any(|pattern| {
+            let pattern: Vec<&str> = pattern.iter().map(String::as_str).collect();
+            match_segments(&pattern, &segments)
+        })
+    }
+}
+
+/// Lowercase, forward slashes, no leading or trailing separator
+fn normalize(path: &str) -> String {
+    path.trim()
+        .replace('\\', "/")
+        .trim_matches('/')
+        .to_lowercase()
+}
+
+fn match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| match_segments(rest, &path[skip..])),
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                match_wildcard(first.as_bytes(), segment.as_bytes())
+                    && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+fn match_wildcard(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => text.is_empty(),
+        Some((b'*', rest)) => (0..=text.len()).any(|skip| match_wildcard(rest, &text[skip..])),
+        Some((b'?', rest)) => !text.is_empty() && match_wildcard(rest, &text[1..]),
+        Some((c, rest)) => text.first() == Some(c) && match_wildcard(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_filter_matches() {
+        let filter = ExtractFilter::new(["**/include/**", "VC/Tools/MSVC/*/lib/x64/*.lib"]);
+        assert!(filter.matches("VC/Tools/MSVC/14.44.34823/include/vector"));
+        assert!(filter.matches("Windows Kits\\10\\Include\\10.0.26100.0\\ucrt\\stdio.h"));
+        assert!(filter.matches("VC/Tools/MSVC/14.44.34823/lib/x64/msvcrt.lib"));
+        assert!(!filter.matches("VC/Tools/MSVC/14.44.34823/lib/x64/onecore/msvcrt.lib"));
+        assert!(!filter.matches("VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64/cl.exe"));
+
+        let single = ExtractFilter::new(["*.h?"]);
+        assert!(single.matches("a.hh"));
+        assert!(!single.matches("dir/a.hh"));
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        let filter = ExtractFilter::new(["", "  "]);
+        assert!(filter.is_empty());
+        assert!(filter.matches("anything/at/all.txt"));
+    }
+}
@@ -0,0 +1,327 @@
+//! `post-install.d` script execution
+//!
+//! Runs every executable in a directory after an install or bundle, passing
+//! the install paths and versions as `MSVC_KIT_*` environment variables. This
+//! is the CLI counterpart of [`PostInstallHook`](super::PostInstallHook) for
+//! teams that prefer shell scripts over Rust.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{HookFailurePolicy, PostInstallConfig};
+use crate::error::{MsvcKitError, Result};
+use crate::version::Architecture;
+
+/// Description of the install passed to scripts
+#[derive(Debug, Clone)]
+pub struct HookScriptContext {
+    /// Command that triggered the scripts ("install" or "bundle")
+    pub event: String,
+    /// Root directory of the install or bundle
+    pub install_dir: PathBuf,
+    /// Installed MSVC version, if MSVC was installed
+    pub msvc_version: Option<String>,
+    /// Installed Windows SDK version, if the SDK was installed
+    pub sdk_version: Option<String>,
+    /// Target architecture
+    pub arch: Architecture,
+    /// Host architecture
+    pub host_arch: Architecture,
+}
+
+impl HookScriptContext {
+    /// Environment variables exported to every script
+    pub fn env_vars(&self) -> HashMap<String, String> {
+        let mut vars = HashMap::new();
+        vars.insert("MSVC_KIT_EVENT".to_string(), self.event.clone());
+        vars.insert(
+            "MSVC_KIT_INSTALL_DIR".to_string(),
+            self.install_dir.display().to_string(),
+        );
+        if let Some(ref version) = self.msvc_version {
+            vars.insert("MSVC_KIT_MSVC_VERSION".to_string(), version.clone());
+            vars.insert(
+                "MSVC_KIT_VC_TOOLS_DIR".to_string(),
+                self.install_dir
+                    .join("VC")
+                    .join("Tools")
+                    .join("MSVC")
+                    .join(version)
+                    .display()
+                    .to_string(),
+            );
+        }
+        if let Some(ref version) = self.sdk_version {
+            vars.insert("MSVC_KIT_SDK_VERSION".to_string(), version.clone());
+            vars.insert(
+                "MSVC_KIT_SDK_DIR".to_string(),
+                self.install_dir
+                    .join("Windows Kits")
+                    .join("10")
+                    .display()
+                    .to_string(),
+            );
+        }
+        vars.insert("MSVC_KIT_ARCH".to_string(), self.arch.to_string());
+        vars.insert("MSVC_KIT_HOST_ARCH".to_string(), self.host_arch.to_string());
+        vars
+    }
+}
+
+/// Result of running one script
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookScriptOutcome {
+    /// Script path
+    pub script: PathBuf,
+    /// Exit code (None if killed or timed out)
+    pub exit_code: Option<i32>,
+    /// Whether the script was killed after the timeout
+    pub timed_out: bool,
+}
+
+impl HookScriptOutcome {
+    /// Whether the script exited successfully
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Scripts in `dir` in the order they run
+///
+/// On Windows these are `.exe`, `.bat`, `.cmd` and `.ps1` files; elsewhere,
+/// files with an executable bit. Hidden files are skipped.
+pub fn list_hook_scripts(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut scripts: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && is_hook_script(p))
+        .collect();
+    scripts.sort();
+    scripts
+}
+
+fn is_hook_script(path: &Path) -> bool {
+    let hidden = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_none_or(|n| n.starts_with('.'));
+    if hidden {
+        return false;
+    }
+
+    #[cfg(windows)]
+    {
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("")
+            .to_lowercase();
+        matches!(extension.as_str(), "exe" | "bat" | "cmd" | "ps1")
+    }
+    #[cfg(not(windows))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+    }
+}
+
+fn script_command(script: &Path) -> tokio::process::Command {
+    let extension = script
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match extension.as_str() {
+        "ps1" => {
+            let mut cmd = tokio::process::Command::new("powershell");
+            cmd.args(["-NoProfile", "-ExecutionPolicy", "Bypass", "-File"])
+                .arg(script);
+            cmd
+        }
+        "bat" | "cmd" => {
+            let mut cmd = tokio::process::Command::new("cmd");
+            cmd.arg("/C").arg(script);
+            cmd
+        }
+        _ => tokio::process::Command::new(script),
+    }
+}
+
+/// Run the scripts of the configured `post-install.d` directory
+///
+/// Scripts run one at a time with `ctx`'s variables added to the inherited
+/// environment and the scripts directory as working directory. Failures and
+/// timeouts are handled according to `config.on_failure`; with
+/// [`HookFailurePolicy::Fail`] the first failure returns
+/// [`MsvcKitError::HookScript`].
+pub async fn run_hook_scripts(
+    config: &PostInstallConfig,
+    ctx: &HookScriptContext,
+) -> Result<Vec<HookScriptOutcome>> {
+    let dir = config.scripts_dir();
+    let scripts = list_hook_scripts(&dir);
+    let vars = ctx.env_vars();
+    let timeout = Duration::from_secs(config.timeout_secs.max(1));
+
+    let mut outcomes = Vec::with_capacity(scripts.len());
+    for script in scripts {
+        tracing::info!("Running post-install script {}", script.display());
+        let mut child = script_command(&script)
+            .envs(&vars)
+            .current_dir(&dir)
+            .kill_on_drop(true)
+            .spawn()?;
+
+        let outcome = match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => HookScriptOutcome {
+                script: script.clone(),
+                exit_code: status?.code(),
+                timed_out: false,
+            },
+            Err(_) => {
+                let _ = child.kill().await;
+                HookScriptOutcome {
+                    script: script.clone(),
+                    exit_code: None,
+                    timed_out: true,
+                }
+            }
+        };
+
+        if !outcome.success() {
+            let reason = if outcome.timed_out {
+                format!("timed out after {}s", timeout.as_secs())
+            } else {
+                match outcome.exit_code {
+                    Some(code) => format!("exit code {}", code),
+                    None => "terminated by signal".to_string(),
+                }
+            };
+            match config.on_failure {
+                HookFailurePolicy::Fail => {
+                    return Err(MsvcKitError::HookScript {
+                        script: script.display().to_string(),
+                        reason,
+                    });
+                }
+                HookFailurePolicy::Warn => {
+                    tracing::warn!("Post-install script {} {}", script.display(), reason);
+                }
+                HookFailurePolicy::Ignore => {}
+            }
+        }
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(not(windows))]
+    fn write_script(dir: &Path, name: &str, body: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        std::fs::write(&path, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    fn context() -> HookScriptContext {
+        HookScriptContext {
+            event: "install".to_string(),
+            install_dir: PathBuf::from("/opt/msvc-kit"),
+            msvc_version: Some("14.44.34823".to_string()),
+            sdk_version: None,
+            arch: Architecture::X64,
+            host_arch: Architecture::X64,
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_run_hook_scripts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let out = temp_dir.path().join("out.txt");
+        write_script(
+            temp_dir.path(),
+            "10-record.sh",
+            &format!(
+                "echo \"$MSVC_KIT_EVENT $MSVC_KIT_MSVC_VERSION\" > {}",
+                out.display()
+            ),
+        );
+        std::fs::write(temp_dir.path().join("README"), "not executable").unwrap();
+
+        let config = PostInstallConfig {
+            scripts_dir: Some(temp_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let outcomes = run_hook_scripts(&config, &context()).await.unwrap();
+        assert_eq!(outcomes.len(), 1);
+        assert!(outcomes[0].success());
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap().trim(),
+            "install 14.44.34823"
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[tokio::test]
+    async fn test_run_hook_scripts_failure_policy() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        write_script(temp_dir.path(), "10-fail.sh", "exit 3");
+        write_script(temp_dir.path(), "20-ok.sh", "exit 0");
+
+        let mut config = PostInstallConfig {
+            scripts_dir: Some(temp_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let err = run_hook_scripts(&config, &context()).await.unwrap_err();
+        assert!(matches!(err, MsvcKitError::HookScript { .. }));
+
+        config.on_failure = HookFailurePolicy::Warn;
+        let outcomes = run_hook_scripts(&config, &context()).await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].exit_code, Some(3));
+        assert!(outcomes[1].success());
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn test_run_hook_scripts_cmd() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            temp_dir.path().join("10-record.cmd"),
+            "@echo off\r\n\
+             echo %MSVC_KIT_EVENT% %MSVC_KIT_MSVC_VERSION% %MSVC_KIT_ARCH%> out.txt\r\n\
+             exit /b 3\r\n",
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("20-ok.bat"), "@exit /b 0\r\n").unwrap();
+        std::fs::write(temp_dir.path().join("README"), "not a script").unwrap();
+
+        let mut config = PostInstallConfig {
+            scripts_dir: Some(temp_dir.path().to_path_buf()),
+            ..Default::default()
+        };
+        let err = run_hook_scripts(&config, &context()).await.unwrap_err();
+        assert!(matches!(err, MsvcKitError::HookScript { .. }));
+        assert_eq!(
+            std::fs::read_to_string(temp_dir.path().join("out.txt"))
+                .unwrap()
+                .trim(),
+            "install 14.44.34823 x64"
+        );
+
+        config.on_failure = HookFailurePolicy::Warn;
+        let outcomes = run_hook_scripts(&config, &context()).await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[0].exit_code, Some(3));
+        assert!(outcomes[1].success());
+    }
+}
@@ -0,0 +1,119 @@
+//! Post-install hooks
+//!
+//! A [`PostInstallHook`] runs after packages have been extracted and the
+//! install has been finalized, so integrators can patch files, write their own
+//! configuration or register the toolchain elsewhere without re-implementing
+//! the install flow.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::InstallInfo;
+use crate::env::MsvcEnvironment;
+use crate::error::Result;
+
+/// Action run after a component has been installed
+///
+/// Hooks run once per installed component (MSVC, then the Windows SDK), in
+/// registration order. An error aborts the remaining hooks and is returned
+/// to the caller.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use async_trait::async_trait;
+/// use msvc_kit::installer::{InstallInfo, PostInstallHook};
+/// use msvc_kit::{DownloadOptions, MsvcEnvironment};
+///
+/// struct RecordToolchain;
+///
+/// #[async_trait]
+/// impl PostInstallHook for RecordToolchain {
+///     async fn run(&self, info: &InstallInfo, env: &MsvcEnvironment) -> msvc_kit::Result<()> {
+///         if info.component_type == "msvc" {
+///             println!("cl.exe: {:?}", env.cl_exe_path());
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let options = DownloadOptions::builder()
+///     .post_install_hook(Arc::new(RecordToolchain))
+///     .build();
+/// ```
+#[async_trait]
+pub trait PostInstallHook: Send + Sync {
+    /// Run the hook for one installed component
+    async fn run(&self, info: &InstallInfo, env: &MsvcEnvironment) -> Result<()>;
+}
+
+/// Type alias for boxed post-install hook
+pub type BoxedPostInstallHook = Arc<dyn PostInstallHook>;
+
+/// Run `hooks` for each installed component
+pub async fn run_post_install_hooks(
+    hooks: &[BoxedPostInstallHook],
+    infos: &[&InstallInfo],
+    env: &MsvcEnvironment,
+) -> Result<()> {
+    for info in infos {
+        for hook in hooks {
+            tracing::debug!("Running post-install hook for {}", info.component_type);
+            hook.run(info, env).await?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::Architecture;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    struct Recorder(Mutex<Vec<String>>);
+
+    #[async_trait]
+    impl PostInstallHook for Recorder {
+        async fn run(&self, info: &InstallInfo, env: &MsvcEnvironment) -> Result<()> {
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{}@{}", info.component_type, env.arch));
+            Ok(())
+        }
+    }
+
+    fn info(component_type: &str) -> InstallInfo {
+        InstallInfo {
+            component_type: component_type.to_string(),
+            version: "1.0".to_string(),
+            install_path: PathBuf::from("C:/msvc-kit"),
+            downloaded_files: vec![],
+            arch: Architecture::X64,
+            extract_filters: vec![],
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_post_install_hooks() {
+        let msvc = info("msvc");
+        let sdk = info("sdk");
+        let env = MsvcEnvironment::from_install_info(&msvc, Some(&sdk), Architecture::X64).unwrap();
+
+        let recorder = Arc::new(Recorder(Mutex::new(Vec::new())));
+        let hooks: Vec<BoxedPostInstallHook> = vec![recorder.clone()];
+        run_post_install_hooks(&hooks, &[&msvc, &sdk], &env)
+            .await
+            .unwrap();
+
+        assert_eq!(*recorder.0.lock().unwrap(), ["msvc@x64", "sdk@x64"]);
+    }
+}
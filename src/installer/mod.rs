@@ -1,35 +1,116 @@
 //! Installation and extraction functionality
 
+mod clean;
+mod cleanup;
+mod components;
+mod extract_options;
 mod extractor;
+mod filter;
+mod hook_scripts;
+mod hooks;
+mod msi_media;
+mod prune;
+mod receipt;
+mod redist;
+mod relocate;
+mod repair;
+mod sync;
 
 use futures::{stream, StreamExt};
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::constants::{extraction as ext_const, progress as progress_const};
-use crate::error::Result;
+use crate::downloader::{check_target_dir, DownloadIndex, ResolutionReport};
+use crate::error::{MsvcKitError, Result};
 use crate::version::Architecture;
-
-pub use extractor::{extract_cab, extract_msi, extract_vsix, get_extractor};
+use crate::warning::Warning;
+
+pub use clean::{clean, find_orphans, is_msvc_kit_install, CleanOptions, CleanReport};
+use cleanup::find_index_dir;
+pub use cleanup::{cleanup_extracted_downloads, CleanupPolicy, CleanupReport};
+pub use components::{add_components, AddComponentsReport};
+pub use extract_options::{
+    EntryAction, EntryCallback, ExtractEntry, ExtractOptions, OverwritePolicy,
+};
+pub use extractor::{
+    extract_cab, extract_cab_with_options, extract_msi, extract_msi_with_options, extract_vsix,
+    extract_vsix_with_options, get_extractor, Extracted,
+};
 use extractor::{
     extract_cab_with_progress, extract_msi_with_progress, extract_vsix_with_progress,
     inner_progress_enabled,
 };
+pub use filter::ExtractFilter;
+pub use hook_scripts::{list_hook_scripts, run_hook_scripts, HookScriptContext, HookScriptOutcome};
+pub use hooks::{run_post_install_hooks, BoxedPostInstallHook, PostInstallHook};
+pub use msi_media::{external_cabinets, missing_cabinets};
+pub use prune::{prune, PruneOptions, PruneReport};
+pub use receipt::{
+    detect_conflicts, invalidate_extraction, ExtractWarning, FileConflict, InstallReport,
+    PackageReceipt, ReceiptEntry, RECEIPT_DIR_NAME,
+};
+pub use redist::{copy_redist, redist_dir, RedistOptions, RedistReport};
+pub use relocate::{relocate, RelocateReport};
+pub(crate) use repair::missing_files;
+pub use repair::{repair_component, ComponentRepairReport, RepairComponent};
+pub use sync::{
+    sync_toolchains, SyncAction, SyncOptions, SyncReport, ToolchainSpec, ToolchainStatus,
+    ToolchainsFile, TOOLCHAINS_FILE,
+};
 
 /// Extract a package based on its file extension
 pub async fn extract_package(file: &Path, target_dir: &Path) -> Result<()> {
-    extract_package_with_progress(file, target_dir, inner_progress_enabled()).await
+    extract_package_filtered(file, target_dir, &ExtractFilter::default()).await
+}
+
+/// Extract only the files of a package matching `filter`
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{extract_package_filtered, ExtractFilter};
+/// use std::path::Path;
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// let filter = ExtractFilter::new(["**/include/**"]);
+/// extract_package_filtered(Path::new("headers.vsix"), Path::new("C:/msvc-kit"), &filter).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn extract_package_filtered(
+    file: &Path,
+    target_dir: &Path,
+    filter: &ExtractFilter,
+) -> Result<()> {
+    extract_package_with_options(file, target_dir, &ExtractOptions::filtered(filter.clone()))
+        .await?;
+    Ok(())
+}
+
+/// Extract a package as configured by `options`, based on its file extension
+///
+/// Returns the files written and every member that was extracted, skipped
+/// or listed. Unknown file types are skipped with a logged warning.
+pub async fn extract_package_with_options(
+    file: &Path,
+    target_dir: &Path,
+    options: &ExtractOptions,
+) -> Result<Extracted> {
+    extract_package_with_progress(file, target_dir, inner_progress_enabled(), options).await
 }
 
 async fn extract_package_with_progress(
     file: &Path,
     target_dir: &Path,
     show_progress: bool,
-) -> Result<()> {
+    options: &ExtractOptions,
+) -> Result<Extracted> {
     let extension = file
         .extension()
         .and_then(|e| e.to_str())
@@ -37,22 +118,71 @@ async fn extract_package_with_progress(
         .to_lowercase();
 
     match extension.as_str() {
-        "vsix" | "zip" => extract_vsix_with_progress(file, target_dir, show_progress).await,
-        "msi" => extract_msi_with_progress(file, target_dir, show_progress).await,
-        "cab" => extract_cab_with_progress(file, target_dir, show_progress).await,
+        "vsix" | "zip" => {
+            extract_vsix_with_progress(file, target_dir, show_progress, options).await
+        }
+        "msi" => extract_msi_with_progress(file, target_dir, show_progress, options).await,
+        "cab" => extract_cab_with_progress(file, target_dir, show_progress, options).await,
         _ => {
             tracing::warn!("Unknown file type: {:?}, skipping extraction", file);
-            Ok(())
+            Ok(Extracted {
+                warnings: vec![ExtractWarning {
+                    package: file
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    path: None,
+                    message: "unknown file type, not extracted".to_string(),
+                }],
+                ..Default::default()
+            })
         }
     }
 }
 
 /// Extract multiple packages with a unified progress bar (parallel extraction)
+///
+/// File conflicts between packages are logged as warnings; use
+/// [`extract_packages_with_report`] to inspect them or fail on them.
 pub async fn extract_packages_with_progress(
     files: &[PathBuf],
     target_dir: &Path,
     label: &str,
 ) -> Result<()> {
+    extract_packages_with_report(files, target_dir, label, false).await?;
+    Ok(())
+}
+
+/// Extract multiple packages and report file conflicts between them
+///
+/// Every extracted package gets a receipt listing the files it wrote. Receipts
+/// of previously extracted packages take part in conflict detection as well.
+/// With `strict`, any conflict fails with [`MsvcKitError::FileConflicts`].
+/// Conflicts are found from the receipts, so this happens after the later
+/// package has overwritten the file; its receipt is removed again, so the
+/// next run extracts it and checks once more.
+///
+/// A file that cannot be written fails with [`MsvcKitError::Extraction`]
+/// naming the payload, the file and its destination; no receipt is written
+/// for that payload, so the next run extracts it again. Non-fatal problems
+/// are collected in [`InstallReport::warnings`].
+pub async fn extract_packages_with_report(
+    files: &[PathBuf],
+    target_dir: &Path,
+    label: &str,
+    strict: bool,
+) -> Result<InstallReport> {
+    extract_packages_filtered(files, target_dir, label, strict, &ExtractFilter::default()).await
+}
+
+/// Like [`extract_packages_with_report`], extracting only files matching `filter`
+pub async fn extract_packages_filtered(
+    files: &[PathBuf],
+    target_dir: &Path,
+    label: &str,
+    strict: bool,
+    filter: &ExtractFilter,
+) -> Result<InstallReport> {
     let total = files.len() as u64;
     let pb = ProgressBar::new_spinner();
     pb.set_draw_target(ProgressDrawTarget::stderr_with_hz(4));
@@ -64,9 +194,10 @@ pub async fn extract_packages_with_progress(
     pb.enable_steady_tick(Duration::from_millis(progress_const::PROGRESS_TICK_MS));
     pb.set_message(format!("{} extracting 0/{} files", label, total));
 
-    // cache marker dir
-    let marker_dir = target_dir.join(".msvc-kit-extracted");
+    // cache marker dir (markers double as package receipts)
+    let marker_dir = target_dir.join(RECEIPT_DIR_NAME);
     tokio::fs::create_dir_all(&marker_dir).await.ok();
+    let options = ExtractOptions::filtered(filter.clone());
 
     // Determine parallel extraction count (use CPU cores, capped by constant)
     let num_cpus = std::thread::available_parallelism()
@@ -114,61 +245,161 @@ pub async fn extract_packages_with_progress(
     let label = label.to_string();
     let pb = Arc::new(pb);
 
-    let results: Vec<Result<PathBuf>> = stream::iter(files_to_extract.into_iter())
-        .map(|file| {
-            let target_dir = target_dir.clone();
-            let marker_dir = marker_dir.clone();
-            let extracted_count = extracted_count.clone();
-            let skipped_count = skipped_count.clone();
-            let pb = pb.clone();
-            let label = label.clone();
-            let total = total as usize;
-
-            async move {
-                let name = file
-                    .file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                // Extract the package
-                extract_package_with_progress(&file, &target_dir, false).await?;
-
-                // Mark as extracted
-                let marker = marker_dir.join(format!("{}.done", name));
-                let _ = tokio::fs::write(&marker, b"ok").await;
-
-                // Update progress
-                let done = extracted_count.fetch_add(1, Ordering::Relaxed) + 1;
-                let skip = skipped_count.load(Ordering::Relaxed);
-                pb.set_message(format!(
-                    "{} extracting {}/{} (done {}, cached {})",
-                    label,
-                    done + skip,
-                    total,
-                    done,
-                    skip
-                ));
-
-                Ok(file)
-            }
-        })
-        .buffer_unordered(parallel_count)
-        .collect()
-        .await;
+    let results: Vec<Result<(PackageReceipt, Vec<ExtractWarning>)>> =
+        stream::iter(files_to_extract.into_iter())
+            .map(|file| {
+                let target_dir = target_dir.clone();
+                let marker_dir = marker_dir.clone();
+                let extracted_count = extracted_count.clone();
+                let skipped_count = skipped_count.clone();
+                let pb = pb.clone();
+                let label = label.clone();
+                let options = options.clone();
+                let total = total as usize;
+
+                async move {
+                    let name = file
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("unknown")
+                        .to_string();
+
+                    // Extract the package; a failure returns before the marker is written
+                    let Extracted {
+                        files,
+                        mut warnings,
+                        ..
+                    } = extract_package_with_progress(&file, &target_dir, false, &options).await?;
+
+                    // Mark as extracted, recording the files it owns
+                    let receipt = PackageReceipt {
+                        package: name.clone(),
+                        files,
+                    };
+                    let marker = marker_dir.join(format!("{}.done", name));
+                    if let Err(e) = receipt.write(&marker).await {
+                        warnings.push(ExtractWarning {
+                            package: name.clone(),
+                            path: None,
+                            message: format!("failed to write receipt {}: {}", marker.display(), e),
+                        });
+                    }
+
+                    // Update progress
+                    let done = extracted_count.fetch_add(1, Ordering::Relaxed) + 1;
+                    let skip = skipped_count.load(Ordering::Relaxed);
+                    pb.set_message(format!(
+                        "{} extracting {}/{} (done {}, cached {})",
+                        label,
+                        done + skip,
+                        total,
+                        done,
+                        skip
+                    ));
+
+                    Ok((receipt, warnings))
+                }
+            })
+            .buffer_unordered(parallel_count)
+            .collect()
+            .await;
 
     // Check for errors
+    let mut extracted = HashMap::new();
     for result in results {
-        result?;
+        let (receipt, warnings) = result?;
+        extracted.insert(receipt.package.clone(), (receipt, warnings));
     }
 
+    // Detect conflicts in input order so the report is stable across runs
+    let mut receipts = Vec::with_capacity(files.len());
+    let mut warnings = Vec::new();
+    for file in files {
+        let name = file
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown");
+        if let Some((receipt, package_warnings)) = extracted.remove(name) {
+            receipts.push(receipt);
+            warnings.extend(package_warnings);
+        } else if let Some(receipt) =
+            PackageReceipt::read(&marker_dir.join(format!("{}.done", name))).await
+        {
+            receipts.push(receipt);
+        }
+    }
+    let mut conflicts = detect_conflicts(&receipts);
+    if strict {
+        for conflict in &conflicts {
+            let marker = PackageReceipt::path_for(&target_dir, &conflict.second_package);
+            tokio::fs::remove_file(&marker).await.ok();
+        }
+    }
+    resolve_package_ids(files, &mut conflicts).await;
+
     let final_extracted = extracted_count.load(Ordering::Relaxed);
     let final_skipped = skipped_count.load(Ordering::Relaxed);
     pb.finish_with_message(format!(
         "{} extraction done ({} extracted, {} cached)",
         label, final_extracted, final_skipped
     ));
-    Ok(())
+
+    for conflict in &conflicts {
+        tracing::warn!("File conflict: {}", conflict);
+    }
+    for warning in &warnings {
+        tracing::warn!("Extraction warning: {}", warning);
+    }
+    if strict && !conflicts.is_empty() {
+        return Err(MsvcKitError::FileConflicts(conflicts));
+    }
+
+    Ok(InstallReport {
+        extracted: final_extracted,
+        cached: final_skipped,
+        conflicts,
+        warnings,
+        cleanup: Default::default(),
+    })
+}
+
+/// Replace payload file names in `conflicts` with the ids of their packages
+///
+/// Package ids come from the download indexes next to `files`; payloads the
+/// index does not know (downloads made by older versions) keep their name.
+async fn resolve_package_ids(files: &[PathBuf], conflicts: &mut [FileConflict]) {
+    if conflicts.is_empty() {
+        return;
+    }
+
+    let index_dirs: HashSet<PathBuf> = files.iter().filter_map(|f| find_index_dir(f)).collect();
+    let mut package_ids: HashMap<String, String> = HashMap::new();
+    for dir in index_dirs {
+        let packages = match DownloadIndex::load(&dir.join("index.db")).await {
+            Ok(index) => index.packages().await,
+            Err(e) => Err(e),
+        };
+        match packages {
+            Ok(packages) => {
+                for package in packages {
+                    for payload in package.payloads {
+                        package_ids
+                            .entry(payload)
+                            .or_insert_with(|| package.id.clone());
+                    }
+                }
+            }
+            Err(e) => tracing::debug!("Cannot read package ids from {:?}: {}", dir, e),
+        }
+    }
+
+    for conflict in conflicts {
+        for package in [&mut conflict.first_package, &mut conflict.second_package] {
+            if let Some(id) = package_ids.get(package.as_str()) {
+                *package = id.clone();
+            }
+        }
+    }
 }
 
 /// Information about an installed component
@@ -188,6 +419,34 @@ pub struct InstallInfo {
 
     /// Target architecture
     pub arch: Architecture,
+
+    /// Glob patterns limiting which files are extracted (empty = all files)
+    ///
+    /// See [`ExtractFilter`] for the pattern syntax.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extract_filters: Vec<String>,
+
+    /// What to do with the downloaded files once they are extracted
+    #[serde(default)]
+    pub cleanup: CleanupPolicy,
+
+    /// How the downloaded version was selected (None = not downloaded here)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolution: Option<ResolutionReport>,
+
+    /// Non-fatal problems found while downloading and extracting
+    ///
+    /// [`extract_and_finalize_msvc`] adds the extraction warnings itself;
+    /// [`extract_and_finalize_sdk_with_report`] only borrows the info, so
+    /// callers add [`InstallReport::to_warnings`] of its report.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
+
+    /// Whether an existing equivalent install was returned instead of downloading
+    ///
+    /// See [`InstallStamp`](crate::downloader::InstallStamp).
+    #[serde(default)]
+    pub reused: bool,
 }
 
 impl InstallInfo {
@@ -250,7 +509,7 @@ impl InstallInfo {
 
     /// Export install info to JSON
     pub fn to_json(&self) -> serde_json::Value {
-        serde_json::json!({
+        let mut value = serde_json::json!({
             "component_type": self.component_type,
             "version": self.version,
             "install_path": self.install_path,
@@ -260,7 +519,17 @@ impl InstallInfo {
             "arch": self.arch.to_string(),
             "is_valid": self.is_valid(),
             "total_size": self.total_size(),
-        })
+        });
+        if let Some(resolution) = &self.resolution {
+            value["resolution"] = serde_json::json!(resolution);
+        }
+        if self.reused {
+            value["reused"] = serde_json::json!(true);
+        }
+        if !self.warnings.is_empty() {
+            value["warnings"] = serde_json::json!(self.warnings);
+        }
+        value
     }
 }
 
@@ -271,36 +540,90 @@ impl InstallInfo {
 /// 2. Scans for the MSVC version directory to get the full version number
 /// 3. Updates InstallInfo with the complete version and correct paths
 pub async fn extract_and_finalize_msvc(info: &mut InstallInfo) -> Result<()> {
+    extract_and_finalize_msvc_with_report(info, false).await?;
+    Ok(())
+}
+
+/// Like [`extract_and_finalize_msvc`], returning the install report
+///
+/// With `strict`, file conflicts between packages fail the install.
+pub async fn extract_and_finalize_msvc_with_report(
+    info: &mut InstallInfo,
+    strict: bool,
+) -> Result<InstallReport> {
     let target_dir = &info.install_path;
+    check_target_dir(target_dir)?;
 
     tracing::info!("Extracting MSVC packages to {:?}", target_dir);
 
     // Extract all packages
-    extract_packages_with_progress(&info.downloaded_files, target_dir, "MSVC").await?;
+    let filter = ExtractFilter::new(&info.extract_filters);
+    let report =
+        extract_packages_filtered(&info.downloaded_files, target_dir, "MSVC", strict, &filter)
+            .await?;
 
     // Find the actual MSVC version directory and extract the full version number
     let vc_tools_path = target_dir.join("VC").join("Tools").join("MSVC");
     if vc_tools_path.exists() {
-        // Find the version directory - this contains the full version number (e.g., 14.44.34823)
+        // Version directories are named after the full version (e.g., 14.44.34823)
+        let mut names = Vec::new();
         let mut entries = tokio::fs::read_dir(&vc_tools_path).await?;
         while let Some(entry) = entries.next_entry().await? {
             if entry.file_type().await?.is_dir() {
-                let dir_name = entry.file_name();
-                if let Some(name) = dir_name.to_str() {
-                    // The directory name is the full version (e.g., "14.44.34823")
-                    info.version = name.to_string();
-                    tracing::info!(
-                        "Found MSVC version directory: {} (full version: {})",
-                        entry.path().display(),
-                        info.version
-                    );
-                    break;
+                if let Some(name) = entry.file_name().to_str() {
+                    names.push(name.to_string());
                 }
             }
         }
+        let full_version = info
+            .resolution
+            .as_ref()
+            .and_then(|r| r.full_version.as_deref());
+        if let Some(name) = select_msvc_version_dir(&names, &info.version, full_version) {
+            tracing::info!(
+                "Found MSVC version directory: {} (full version: {})",
+                vc_tools_path.join(&name).display(),
+                name
+            );
+            info.version = name;
+        }
     }
 
-    Ok(())
+    info.warnings.extend(report.to_warnings());
+    Ok(with_cleanup(info, report).await)
+}
+
+/// Version directory under `VC/Tools/MSVC` installed for `version`
+///
+/// Other toolsets may already be installed side by side, so the directory
+/// must match the resolved full version or the requested prefix; the newest
+/// match wins. Falls back to the newest directory.
+fn select_msvc_version_dir(
+    names: &[String],
+    version: &str,
+    full_version: Option<&str>,
+) -> Option<String> {
+    let newest = |candidates: Vec<&String>| {
+        candidates
+            .into_iter()
+            .max_by_key(|name| {
+                name.split('.')
+                    .map(|part| part.parse::<u64>().unwrap_or(0))
+                    .collect::<Vec<_>>()
+            })
+            .cloned()
+    };
+    if let Some(full) = full_version.filter(|full| names.iter().any(|n| n == full)) {
+        return Some(full.to_string());
+    }
+    let prefix = format!("{}.", version);
+    newest(
+        names
+            .iter()
+            .filter(|n| *n == version || n.starts_with(&prefix))
+            .collect(),
+    )
+    .or_else(|| newest(names.iter().collect()))
 }
 
 /// Extract SDK packages and finalize InstallInfo
@@ -309,51 +632,80 @@ pub async fn extract_and_finalize_msvc(info: &mut InstallInfo) -> Result<()> {
 /// 1. Extracts downloaded packages to the target directory
 /// 2. Verifies the SDK installation path
 pub async fn extract_and_finalize_sdk(info: &InstallInfo) -> Result<()> {
+    extract_and_finalize_sdk_with_report(info, false).await?;
+    Ok(())
+}
+
+/// Like [`extract_and_finalize_sdk`], returning the install report
+///
+/// With `strict`, file conflicts between packages fail the install.
+pub async fn extract_and_finalize_sdk_with_report(
+    info: &InstallInfo,
+    strict: bool,
+) -> Result<InstallReport> {
     let target_dir = &info.install_path;
+    check_target_dir(target_dir)?;
 
     tracing::info!("Extracting Windows SDK packages to {:?}", target_dir);
 
     // Extract all packages
-    extract_packages_with_progress(&info.downloaded_files, target_dir, "Windows SDK").await?;
+    let filter = ExtractFilter::new(&info.extract_filters);
+    let report = extract_packages_filtered(
+        &info.downloaded_files,
+        target_dir,
+        "Windows SDK",
+        strict,
+        &filter,
+    )
+    .await?;
+
+    Ok(with_cleanup(info, report).await)
+}
 
-    Ok(())
+/// Apply the cleanup policy of `info` after a successful extraction
+///
+/// Cleanup failures only cost disk space, so they are logged rather than
+/// failing the install.
+async fn with_cleanup(info: &InstallInfo, mut report: InstallReport) -> InstallReport {
+    match cleanup_extracted_downloads(info).await {
+        Ok(cleanup) => report.cleanup = cleanup,
+        Err(e) => tracing::warn!("Failed to clean up downloads: {}", e),
+    }
+    report
 }
 
 /// Install MSVC components from downloaded files
 ///
-/// This is a legacy function that extracts packages to install_path.
-/// For new code, use extract_and_finalize_msvc() instead.
+/// Legacy wrapper around [`extract_and_finalize_msvc`]; the finalized full
+/// version (e.g. `14.44.34823`) is only visible through that function, so
+/// callers should switch to it.
+#[deprecated(
+    since = "0.2.11",
+    note = "does not report the full MSVC version; use `extract_and_finalize_msvc`"
+)]
 pub async fn install_msvc(info: &InstallInfo) -> Result<PathBuf> {
-    tracing::info!(
-        "Installing MSVC {} to {:?}",
-        info.version,
-        info.install_path
-    );
-
+    tracing::warn!("install_msvc is deprecated; use extract_and_finalize_msvc");
+    let mut info = info.clone();
     tokio::fs::create_dir_all(&info.install_path).await?;
-    extract_packages_with_progress(&info.downloaded_files, &info.install_path, "MSVC").await?;
-
-    Ok(info.install_path.clone())
+    extract_and_finalize_msvc(&mut info).await?;
+    Ok(info.install_path)
 }
 
 /// Install Windows SDK components from downloaded files
 ///
-/// This is a legacy function that extracts packages to install_path.
-/// For new code, use extract_and_finalize_sdk() instead.
+/// Legacy wrapper around [`extract_and_finalize_sdk`].
+#[deprecated(since = "0.2.11", note = "use `extract_and_finalize_sdk`")]
 pub async fn install_sdk(info: &InstallInfo) -> Result<PathBuf> {
-    tracing::info!(
-        "Installing Windows SDK {} to {:?}",
-        info.version,
-        info.install_path
-    );
-
+    tracing::warn!("install_sdk is deprecated; use extract_and_finalize_sdk");
     tokio::fs::create_dir_all(&info.install_path).await?;
-    extract_packages_with_progress(&info.downloaded_files, &info.install_path, "SDK").await?;
-
+    extract_and_finalize_sdk(info).await?;
     Ok(info.install_path.clone())
 }
 
 /// Clean up downloaded files after installation
+///
+/// Deletes every file in `info.downloaded_files`, extracted or not. See
+/// [`cleanup_extracted_downloads`] for removing only installed payloads.
 pub async fn cleanup_downloads(info: &InstallInfo) -> Result<()> {
     for file in &info.downloaded_files {
         if file.exists() {
@@ -362,3 +714,26 @@ pub async fn cleanup_downloads(info: &InstallInfo) -> Result<()> {
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_select_msvc_version_dir() {
+        let names: Vec<String> = ["14.43.34808", "14.44.34823", "14.44.35207", "14.50.35717"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let select = |version, full| select_msvc_version_dir(&names, version, full);
+        assert_eq!(select("14.44", None).as_deref(), Some("14.44.35207"));
+        assert_eq!(
+            select("14.44", Some("14.44.34823")).as_deref(),
+            Some("14.44.34823")
+        );
+        assert_eq!(select("14.43.34808", None).as_deref(), Some("14.43.34808"));
+        assert_eq!(select("14.99", None).as_deref(), Some("14.50.35717"));
+        assert_eq!(select_msvc_version_dir(&[], "14.44", None), None);
+    }
+}
@@ -0,0 +1,666 @@
+//! External cabinets of MSI installers
+//!
+//! Windows SDK installers keep their files in `.cab` payloads that have to
+//! sit next to the `.msi` while it is extracted; `msiexec /a` and
+//! `msiextract` fail with a bare status code when one of them is missing.
+//! The cabinets an MSI needs are listed in its `Media` table, which
+//! [`external_cabinets`] reads straight from the compound file, without
+//! Windows Installer.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::error::{MsvcKitError, Result};
+
+/// Compound file signature
+const SIGNATURE: [u8; 8] = [0xD0, 0xCF, 0x11, 0xE0, 0xA1, 0xB1, 0x1A, 0xE1];
+
+/// Last sector of a chain
+const END_OF_CHAIN: u32 = 0xFFFF_FFFE;
+
+/// Sector ids above this mark free or special sectors
+const MAX_REGULAR_SECTOR: u32 = 0xFFFF_FFFA;
+
+/// Directory entry types
+const STORAGE_STREAM: u8 = 2;
+const STORAGE_ROOT: u8 = 5;
+
+/// `_Columns` type bit of string columns
+const MSITYPE_STRING: u16 = 0x0800;
+
+/// Cabinet files an MSI expects next to it, as listed in its `Media` table
+///
+/// Cabinets embedded in the MSI (`#name`) are left out. Fails if `msi` is
+/// not a Windows Installer database.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::external_cabinets;
+///
+/// let msi = std::fs::read("Windows SDK Desktop Headers x64-x86_en-us.msi").unwrap();
+/// for cabinet in external_cabinets(&msi).unwrap() {
+///     println!("{}", cabinet);
+/// }
+/// ```
+pub fn external_cabinets(msi: &[u8]) -> Result<Vec<String>> {
+    let file = CompoundFile::parse(msi).ok_or_else(|| invalid("not a compound file"))?;
+    let streams = file.root_streams();
+    let stream = |name: &str| match streams.get(name) {
+        Some(entry) => file
+            .stream(entry)
+            .ok_or_else(|| invalid(&format!("{} is truncated", name))),
+        None => Ok(Vec::new()),
+    };
+
+    let (strings, ref_size) = read_strings(&stream("!_StringPool")?, &stream("!_StringData")?)
+        .ok_or_else(|| invalid("unreadable string pool"))?;
+    let string = |id: u32| strings.get(id as usize).map(String::as_str).unwrap_or("");
+
+    let media = stream("!Media")?;
+    if media.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Column layout of the Media table: (number, name, width)
+    let columns = read_table(&stream("!_Columns")?, &[ref_size, 2, ref_size, 2])
+        .ok_or_else(|| invalid("unreadable _Columns table"))?;
+    let mut media_columns: Vec<(u32, &str, usize)> = columns
+        .iter()
+        .filter(|row| string(row[0]) == "Media")
+        .map(|row| {
+            let column_type = (row[3] ^ 0x8000) as u16;
+            let width = if column_type & MSITYPE_STRING != 0 {
+                ref_size
+            } else if column_type & 0xff <= 2 {
+                2
+            } else {
+                4
+            };
+            (row[1] ^ 0x8000, string(row[2]), width)
+        })
+        .collect();
+    media_columns.sort_by_key(|column| column.0);
+    let cabinet = media_columns
+        .iter()
+        .position(|column| column.1 == "Cabinet")
+        .ok_or_else(|| invalid("Media table without Cabinet column"))?;
+    let widths: Vec<usize> = media_columns.iter().map(|column| column.2).collect();
+
+    let rows = read_table(&media, &widths).ok_or_else(|| invalid("unreadable Media table"))?;
+    let mut seen = HashSet::new();
+    Ok(rows
+        .iter()
+        .map(|row| string(row[cabinet]))
+        .filter(|name| !name.is_empty() && !name.starts_with('#'))
+        .filter(|name| seen.insert(name.to_lowercase()))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Cabinets the MSI at `msi_path` needs that are not in its directory
+///
+/// File names are compared case-insensitively, as Windows does.
+pub fn missing_cabinets(msi_path: &Path) -> Result<Vec<String>> {
+    let cabinets = external_cabinets(&std::fs::read(msi_path)?)?;
+    let dir = msi_path.parent().unwrap_or(Path::new("."));
+    let present: HashSet<String> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_lowercase())
+        .collect();
+    Ok(cabinets
+        .into_iter()
+        .filter(|cabinet| !present.contains(&cabinet.to_lowercase()))
+        .collect())
+}
+
+fn invalid(reason: &str) -> MsvcKitError {
+    MsvcKitError::Other(format!("Invalid MSI: {}", reason))
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(
+        data.get(offset..offset + 2)?.try_into().ok()?,
+    ))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        data.get(offset..offset + 4)?.try_into().ok()?,
+    ))
+}
+
+fn u32s(data: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    data.chunks_exact(4)
+        .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+}
+
+/// Entry of a compound file directory
+#[derive(Debug)]
+struct DirEntry {
+    /// Name with MSI stream name compression undone
+    name: String,
+    kind: u8,
+    left: u32,
+    right: u32,
+    child: u32,
+    start: u32,
+    size: u64,
+}
+
+impl DirEntry {
+    fn parse(raw: &[u8], sector_size: usize) -> Self {
+        let name_units = (u16_at(raw, 0x40).unwrap_or(0) as usize / 2)
+            .saturating_sub(1)
+            .min(31);
+        let units: Vec<u16> = (0..name_units).filter_map(|i| u16_at(raw, i * 2)).collect();
+        let mut size = u32_at(raw, 0x78).unwrap_or(0) as u64;
+        // Version 3 files (512 byte sectors) may leave garbage in the high half
+        if sector_size != 512 {
+            size |= (u32_at(raw, 0x7C).unwrap_or(0) as u64) << 32;
+        }
+        Self {
+            name: decode_stream_name(&units),
+            kind: raw[0x42],
+            left: u32_at(raw, 0x44).unwrap_or(u32::MAX),
+            right: u32_at(raw, 0x48).unwrap_or(u32::MAX),
+            child: u32_at(raw, 0x4C).unwrap_or(u32::MAX),
+            start: u32_at(raw, 0x74).unwrap_or(END_OF_CHAIN),
+            size,
+        }
+    }
+}
+
+/// Read-only view of an OLE compound file (the container format of MSI files)
+struct CompoundFile<'a> {
+    data: &'a [u8],
+    sector_size: usize,
+    mini_sector_size: usize,
+    mini_cutoff: u64,
+    fat: Vec<u32>,
+    mini_fat: Vec<u32>,
+    mini_stream: Vec<u8>,
+    entries: Vec<DirEntry>,
+}
+
+impl<'a> CompoundFile<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if data.get(..8)? != SIGNATURE {
+            return None;
+        }
+        let sector_shift = u16_at(data, 0x1E)?;
+        let mini_sector_shift = u16_at(data, 0x20)?;
+        if !matches!(sector_shift, 9 | 12) || mini_sector_shift != 6 {
+            return None;
+        }
+        let sector_size = 1usize << sector_shift;
+
+        // The header holds the first 109 FAT sector ids, DIFAT sectors the rest
+        let mut fat_sectors: Vec<u32> = u32s(data.get(0x4C..0x200)?).collect();
+        let mut difat = u32_at(data, 0x44)?;
+        for _ in 0..u32_at(data, 0x48)? {
+            if difat > MAX_REGULAR_SECTOR {
+                break;
+            }
+            let sector = sector(data, sector_size, difat)?;
+            fat_sectors.extend(u32s(sector.get(..sector_size - 4)?));
+            difat = u32_at(sector, sector_size - 4)?;
+        }
+
+        let mut fat = Vec::new();
+        for id in fat_sectors
+            .into_iter()
+            .filter(|&id| id <= MAX_REGULAR_SECTOR)
+        {
+            fat.extend(u32s(sector(data, sector_size, id)?));
+        }
+
+        let mut file = Self {
+            data,
+            sector_size,
+            mini_sector_size: 1 << mini_sector_shift,
+            mini_cutoff: u32_at(data, 0x38)? as u64,
+            fat,
+            mini_fat: Vec::new(),
+            mini_stream: Vec::new(),
+            entries: Vec::new(),
+        };
+        file.entries = file
+            .read_chain(u32_at(data, 0x30)?, None)?
+            .chunks_exact(128)
+            .map(|raw| DirEntry::parse(raw, sector_size))
+            .collect();
+        file.mini_fat = u32s(&file.read_chain(u32_at(data, 0x3C)?, None)?).collect();
+
+        let root = file.entries.first().filter(|e| e.kind == STORAGE_ROOT)?;
+        file.mini_stream = file.read_chain(root.start, Some(root.size))?;
+        Some(file)
+    }
+
+    /// Concatenated sectors of a FAT chain, cut to `size`
+    fn read_chain(&self, start: u32, size: Option<u64>) -> Option<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut id = start;
+        // A chain never visits more sectors than the FAT has (guards against loops)
+        for _ in 0..=self.fat.len() {
+            if id == END_OF_CHAIN || id > MAX_REGULAR_SECTOR {
+                return truncated(data, size);
+            }
+            data.extend_from_slice(sector(self.data, self.sector_size, id)?);
+            id = *self.fat.get(id as usize)?;
+        }
+        None
+    }
+
+    /// Concatenated sectors of a mini FAT chain, cut to `size`
+    fn read_mini_chain(&self, start: u32, size: u64) -> Option<Vec<u8>> {
+        let mut data = Vec::new();
+        let mut id = start;
+        for _ in 0..=self.mini_fat.len() {
+            if id == END_OF_CHAIN || id > MAX_REGULAR_SECTOR {
+                return truncated(data, Some(size));
+            }
+            let offset = id as usize * self.mini_sector_size;
+            data.extend_from_slice(
+                self.mini_stream
+                    .get(offset..offset + self.mini_sector_size)?,
+            );
+            id = *self.mini_fat.get(id as usize)?;
+        }
+        None
+    }
+
+    /// Contents of a stream
+    fn stream(&self, entry: &DirEntry) -> Option<Vec<u8>> {
+        if entry.size == 0 {
+            Some(Vec::new())
+        } else if entry.size < self.mini_cutoff {
+            self.read_mini_chain(entry.start, entry.size)
+        } else {
+            self.read_chain(entry.start, Some(entry.size))
+        }
+    }
+
+    /// Streams directly in the root storage, by name
+    fn root_streams(&self) -> HashMap<String, &DirEntry> {
+        let mut streams = HashMap::new();
+        let mut pending = vec![self.entries[0].child];
+        let mut visited = 0;
+        while let Some(entry) = pending.pop().and_then(|id| self.entries.get(id as usize)) {
+            visited += 1;
+            if visited > self.entries.len() {
+                break;
+            }
+            pending.extend([entry.left, entry.right]);
+            if entry.kind == STORAGE_STREAM {
+                streams.insert(entry.name.clone(), entry);
+            }
+        }
+        streams
+    }
+}
+
+/// Sector `id` of a compound file (the last one may be cut short)
+fn sector(data: &[u8], sector_size: usize, id: u32) -> Option<&[u8]> {
+    let start = (id as usize + 1).checked_mul(sector_size)?;
+    data.get(start..data.len().min(start + sector_size))
+        .filter(|s| !s.is_empty())
+}
+
+fn truncated(mut data: Vec<u8>, size: Option<u64>) -> Option<Vec<u8>> {
+    if let Some(size) = size {
+        if (data.len() as u64) < size {
+            return None;
+        }
+        data.truncate(size as usize);
+    }
+    Some(data)
+}
+
+/// Undo the compression MSI applies to stream names
+///
+/// Two characters of `[0-9A-Za-z._]` are packed into one UTF-16 unit from
+/// `0x3800`, single ones from `0x4800`; `0x4840` prefixes table streams and
+/// becomes `!`.
+fn decode_stream_name(units: &[u16]) -> String {
+    let mut name = String::new();
+    for &unit in units {
+        match unit {
+            0x3800..=0x47FF => {
+                let packed = unit - 0x3800;
+                name.push(stream_name_char(packed & 0x3F));
+                name.push(stream_name_char(packed >> 6));
+            }
+            0x4800..=0x483F => name.push(stream_name_char(unit - 0x4800)),
+            0x4840 => name.push('!'),
+            _ => name.push(char::from_u32(unit as u32).unwrap_or(char::REPLACEMENT_CHARACTER)),
+        }
+    }
+    name
+}
+
+fn stream_name_char(value: u16) -> char {
+    let value = value as u8;
+    match value {
+        0..=9 => (b'0' + value) as char,
+        10..=35 => (b'A' + value - 10) as char,
+        36..=61 => (b'a' + value - 36) as char,
+        62 => '.',
+        _ => '_',
+    }
+}
+
+/// Strings by id (id 0 is the null string) and the size of string references
+fn read_strings(pool: &[u8], data: &[u8]) -> Option<(Vec<String>, usize)> {
+    let words: Vec<u16> = pool
+        .chunks_exact(2)
+        .map(|c| u16::from_le_bytes([c[0], c[1]]))
+        .collect();
+    // The high bit of the code page entry selects 3 byte references
+    let ref_size = if *words.get(1)? & 0x8000 != 0 { 3 } else { 2 };
+
+    let mut strings = vec![String::new()];
+    let mut offset = 0;
+    let mut i = 1;
+    while i < words.len() / 2 {
+        let (length, refs) = (words[i * 2] as usize, words[i * 2 + 1]);
+        let length = match (length, refs) {
+            (0, 0) => {
+                strings.push(String::new());
+                i += 1;
+                continue;
+            }
+            // Strings over 64K span two entries
+            (0, _) => {
+                let length =
+                    *words.get(i * 2 + 2)? as usize | (*words.get(i * 2 + 3)? as usize) << 16;
+                i += 2;
+                length
+            }
+            _ => {
+                i += 1;
+                length
+            }
+        };
+        let bytes = data.get(offset..offset + length)?;
+        strings.push(String::from_utf8_lossy(bytes).into_owned());
+        offset += length;
+    }
+    Some((strings, ref_size))
+}
+
+/// Rows of a table stored column by column, given the byte width of each column
+fn read_table(stream: &[u8], widths: &[usize]) -> Option<Vec<Vec<u32>>> {
+    let row_size: usize = widths.iter().sum();
+    if row_size == 0 || !stream.len().is_multiple_of(row_size) {
+        return None;
+    }
+    let mut rows = vec![Vec::with_capacity(widths.len()); stream.len() / row_size];
+    let mut cells = stream;
+    for &width in widths {
+        for row in rows.iter_mut() {
+            let (cell, rest) = cells.split_at(width);
+            row.push(cell.iter().rev().fold(0, |value, &b| value << 8 | b as u32));
+            cells = rest;
+        }
+    }
+    Some(rows)
+}
+
+/// Minimal MSI with a `Media` table listing `cabinets` (`None` for no cabinet)
+#[cfg(test)]
+pub(crate) fn synthetic_msi(cabinets: &[Option<&str>]) -> Vec<u8> {
+    let mut strings: Vec<String> = Vec::new();
+    let mut id = |s: &str| -> u32 {
+        match strings.iter().position(|existing| existing == s) {
+            Some(i) => i as u32 + 1,
+            None => {
+                strings.push(s.to_string());
+                strings.len() as u32
+            }
+        }
+    };
+
+    // _Columns: Table, Number, Name, Type (column by column)
+    let schema = [
+        ("File", 1, "File", 0x1D48),
+        ("Media", 1, "DiskId", 0x2502),
+        ("Media", 2, "LastSequence", 0x0504),
+        ("Media", 3, "DiskPrompt", 0x1F40),
+        ("Media", 4, "Cabinet", 0x1DFF),
+        ("Media", 5, "VolumeLabel", 0x1F20),
+        ("Media", 6, "Source", 0x1C48),
+    ];
+    let rows: Vec<[u32; 4]> = schema
+        .iter()
+        .map(|(table, number, name, kind)| [id(table), number ^ 0x8000, id(name), kind ^ 0x8000])
+        .collect();
+    let columns = column_major(&rows, &[2, 2, 2, 2]);
+
+    let rows: Vec<[u32; 6]> = cabinets
+        .iter()
+        .enumerate()
+        .map(|(i, cabinet)| {
+            let disk = i as u32 + 1;
+            let cabinet = cabinet.map_or(0, &mut id);
+            [disk ^ 0x8000, (disk * 100) ^ 0x8000_0000, 0, cabinet, 0, 0]
+        })
+        .collect();
+    let media = column_major(&rows, &[2, 4, 2, 2, 2, 2]);
+
+    let mut pool = 1252u32.to_le_bytes().to_vec();
+    for s in &strings {
+        pool.extend((s.len() as u16).to_le_bytes());
+        pool.extend(1u16.to_le_bytes());
+    }
+    let data: Vec<u8> = strings.concat().into_bytes();
+
+    compound_file(&[
+        ("!_StringPool", pool),
+        ("!_StringData", data),
+        ("!_Columns", columns),
+        ("!Media", media),
+    ])
+}
+
+#[cfg(test)]
+fn column_major<const N: usize>(rows: &[[u32; N]], widths: &[usize; N]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (column, &width) in widths.iter().enumerate() {
+        for row in rows {
+            out.extend(&row[column].to_le_bytes()[..width]);
+        }
+    }
+    out
+}
+
+/// Version 3 compound file with every stream in the mini stream
+#[cfg(test)]
+fn compound_file(streams: &[(&str, Vec<u8>)]) -> Vec<u8> {
+    const FREE: u32 = 0xFFFF_FFFF;
+    let chain = |fat: &mut Vec<u32>, start: usize, count: usize| {
+        for i in 0..count {
+            let next = if i + 1 < count {
+                (start + i + 1) as u32
+            } else {
+                END_OF_CHAIN
+            };
+            fat[start + i] = next;
+        }
+    };
+
+    let mut mini_stream = Vec::new();
+    let mut mini_fat = Vec::new();
+    let mut starts = Vec::new();
+    for (_, data) in streams {
+        let count = data.len().div_ceil(64);
+        starts.push(if count == 0 {
+            END_OF_CHAIN
+        } else {
+            mini_fat.len() as u32
+        });
+        let start = mini_fat.len();
+        mini_fat.resize(start + count, FREE);
+        chain(&mut mini_fat, start, count);
+        mini_stream.extend(data);
+        mini_stream.resize(mini_fat.len() * 64, 0);
+    }
+
+    // Sector 0 is the FAT, then the directory, mini FAT and mini stream
+    let dir_sectors = (streams.len() + 1).div_ceil(4);
+    let mini_fat_sectors = (mini_fat.len() * 4).div_ceil(512);
+    let mini_fat_start = 1 + dir_sectors;
+    let mini_stream_start = mini_fat_start + mini_fat_sectors;
+    let total = mini_stream_start + mini_stream.len().div_ceil(512);
+    let mut fat = vec![FREE; 128];
+    fat[0] = 0xFFFF_FFFD;
+    chain(&mut fat, 1, dir_sectors);
+    chain(&mut fat, mini_fat_start, mini_fat_sectors);
+    chain(&mut fat, mini_stream_start, total - mini_stream_start);
+
+    let mut file = vec![0u8; 512 * (total + 1)];
+    let mut put =
+        |offset: usize, bytes: &[u8]| file[offset..offset + bytes.len()].copy_from_slice(bytes);
+    put(0, &SIGNATURE);
+    put(0x18, &0x3Eu16.to_le_bytes());
+    put(0x1A, &3u16.to_le_bytes());
+    put(0x1C, &0xFFFEu16.to_le_bytes());
+    put(0x1E, &9u16.to_le_bytes());
+    put(0x20, &6u16.to_le_bytes());
+    put(0x2C, &1u32.to_le_bytes());
+    put(0x30, &1u32.to_le_bytes());
+    put(0x38, &4096u32.to_le_bytes());
+    put(0x3C, &(mini_fat_start as u32).to_le_bytes());
+    put(0x40, &(mini_fat_sectors as u32).to_le_bytes());
+    put(0x44, &END_OF_CHAIN.to_le_bytes());
+    // The only FAT sector is sector 0
+    put(0x4C, &0u32.to_le_bytes());
+    for i in 1..109 {
+        put(0x4C + i * 4, &FREE.to_le_bytes());
+    }
+    for (i, next) in fat.iter().enumerate() {
+        put(512 + i * 4, &next.to_le_bytes());
+    }
+
+    let mut entry =
+        |index: usize, name: &[u16], kind: u8, right: u32, child: u32, start: u32, size: usize| {
+            let offset = 512 * 2 + index * 128;
+            for (i, unit) in name.iter().enumerate() {
+                put(offset + i * 2, &unit.to_le_bytes());
+            }
+            put(offset + 0x40, &((name.len() as u16 + 1) * 2).to_le_bytes());
+            put(offset + 0x42, &[kind, 1]);
+            put(offset + 0x44, &FREE.to_le_bytes());
+            put(offset + 0x48, &right.to_le_bytes());
+            put(offset + 0x4C, &child.to_le_bytes());
+            put(offset + 0x74, &start.to_le_bytes());
+            put(offset + 0x78, &(size as u32).to_le_bytes());
+        };
+    let root: Vec<u16> = "Root Entry".encode_utf16().collect();
+    entry(
+        0,
+        &root,
+        STORAGE_ROOT,
+        FREE,
+        1,
+        mini_stream_start as u32,
+        mini_stream.len(),
+    );
+    for (i, (name, data)) in streams.iter().enumerate() {
+        let right = if i + 1 < streams.len() {
+            i as u32 + 2
+        } else {
+            FREE
+        };
+        entry(
+            i + 1,
+            &encode_stream_name(name),
+            STORAGE_STREAM,
+            right,
+            FREE,
+            starts[i],
+            data.len(),
+        );
+    }
+    for (i, next) in mini_fat.iter().enumerate() {
+        put(512 * (mini_fat_start + 1) + i * 4, &next.to_le_bytes());
+    }
+    put(512 * (mini_stream_start + 1), &mini_stream);
+    file
+}
+
+#[cfg(test)]
+fn encode_stream_name(name: &str) -> Vec<u16> {
+    let value = |c: u8| -> u16 {
+        match c {
+            b'0'..=b'9' => (c - b'0') as u16,
+            b'A'..=b'Z' => (c - b'A' + 10) as u16,
+            b'a'..=b'z' => (c - b'a' + 36) as u16,
+            b'.' => 62,
+            _ => 63,
+        }
+    };
+    let (prefix, rest) = match name.strip_prefix('!') {
+        Some(rest) => (vec![0x4840], rest),
+        None => (Vec::new(), name),
+    };
+    let mut units = prefix;
+    for pair in rest.as_bytes().chunks(2) {
+        units.push(match pair {
+            [a, b] => 0x3800 + value(*a) + (value(*b) << 6),
+            [a] => 0x4800 + value(*a),
+            _ => unreachable!(),
+        });
+    }
+    units
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_stream_name() {
+        assert_eq!(
+            decode_stream_name(&encode_stream_name("!_StringPool")),
+            "!_StringPool"
+        );
+        assert_eq!(decode_stream_name(&encode_stream_name("!Media")), "!Media");
+        let plain: Vec<u16> = "\u{5}SummaryInformation".encode_utf16().collect();
+        assert_eq!(decode_stream_name(&plain), "\u{5}SummaryInformation");
+    }
+
+    #[test]
+    fn test_external_cabinets() {
+        let msi = synthetic_msi(&[
+            Some("0a1b2c3d.cab"),
+            None,
+            Some("#embedded.cab"),
+            Some("ffeeddcc.cab"),
+            Some("0A1B2C3D.cab"),
+        ]);
+        assert_eq!(
+            external_cabinets(&msi).unwrap(),
+            vec!["0a1b2c3d.cab".to_string(), "ffeeddcc.cab".to_string()]
+        );
+
+        assert!(external_cabinets(&synthetic_msi(&[])).unwrap().is_empty());
+        assert!(external_cabinets(b"not an msi").is_err());
+    }
+
+    #[test]
+    fn test_missing_cabinets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let msi_path = temp_dir
+            .path()
+            .join("Windows SDK Desktop Headers x64-x86_en-us.msi");
+        std::fs::write(
+            &msi_path,
+            synthetic_msi(&[Some("0a1b2c3d.cab"), Some("ffeeddcc.cab")]),
+        )
+        .unwrap();
+        std::fs::write(temp_dir.path().join("0A1B2C3D.CAB"), b"").unwrap();
+
+        assert_eq!(missing_cabinets(&msi_path).unwrap(), vec!["ffeeddcc.cab"]);
+    }
+}
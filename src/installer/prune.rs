@@ -0,0 +1,443 @@
+//! Pruning unused architectures and components from an existing install
+//!
+//! Files are matched by their path inside `VC/` and `Windows Kits/`, so
+//! pruning works for any install regardless of how it was created. Receipts
+//! are updated so later conflict checks and disk usage stay accurate.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::receipt::{PackageReceipt, RECEIPT_DIR_NAME};
+use crate::downloader::MsvcComponent;
+use crate::error::{MsvcKitError, Result};
+use crate::version::Architecture;
+
+const ALL_ARCHES: [Architecture; 4] = [
+    Architecture::X64,
+    Architecture::X86,
+    Architecture::Arm64,
+    Architecture::Arm,
+];
+
+/// Options for pruning an installation
+#[derive(Debug, Clone)]
+pub struct PruneOptions {
+    /// Target architectures to keep (empty = keep all)
+    pub keep_arches: Vec<Architecture>,
+    /// Host architecture whose `Host<arch>` tool directories are always kept
+    pub host_arch: Architecture,
+    /// Optional MSVC components to remove
+    pub remove_components: Vec<MsvcComponent>,
+    /// Only report what would be removed
+    pub dry_run: bool,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self {
+            keep_arches: Vec::new(),
+            host_arch: Architecture::host(),
+            remove_components: Vec::new(),
+            dry_run: false,
+        }
+    }
+}
+
+/// Result of a prune
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PruneReport {
+    /// Removed (or, in dry-run mode, removable) files relative to the install root
+    pub removed: Vec<PathBuf>,
+    /// Bytes freed (or that would be freed)
+    pub bytes_freed: u64,
+    /// Receipts that were rewritten
+    pub receipts_updated: usize,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+}
+
+/// Remove files for other architectures and unneeded components
+///
+/// Only `VC/` and `Windows Kits/` are touched; downloads and receipts are left
+/// in place (receipts are rewritten to drop the removed files).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{prune, PruneOptions};
+/// use msvc_kit::{Architecture, MsvcComponent};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let options = PruneOptions {
+///         keep_arches: vec![Architecture::X64],
+///         remove_components: vec![MsvcComponent::Mfc],
+///         dry_run: true,
+///         ..Default::default()
+///     };
+///     let report = prune("C:/msvc-kit".as_ref(), &options).await?;
+///     println!("Would free {} bytes", report.bytes_freed);
+///     Ok(())
+/// }
+/// ```
+pub async fn prune(install_dir: &Path, options: &PruneOptions) -> Result<PruneReport> {
+    if !install_dir.exists() {
+        return Err(MsvcKitError::InstallPath(format!(
+            "Installation directory not found: {}",
+            install_dir.display()
+        )));
+    }
+
+    let mut report = PruneReport {
+        dry_run: options.dry_run,
+        ..Default::default()
+    };
+
+    let mut pending: Vec<PathBuf> = ["VC", "Windows Kits"]
+        .iter()
+        .map(|d| install_dir.join(d))
+        .filter(|p| p.is_dir())
+        .collect();
+
+    while let Some(current) = pending.pop() {
+        let mut entries = tokio::fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let Ok(rel) = path.strip_prefix(install_dir) else {
+                continue;
+            };
+            if should_prune(&to_rel_string(rel), options) {
+                report.bytes_freed += entry.metadata().await?.len();
+                report.removed.push(rel.to_path_buf());
+            }
+        }
+    }
+    report.removed.sort();
+
+    if options.dry_run {
+        return Ok(report);
+    }
+
+    for rel in &report.removed {
+        let path = install_dir.join(rel);
+        tokio::fs::remove_file(&path).await?;
+        remove_empty_parents(&path, install_dir).await;
+    }
+
+    report.receipts_updated = update_receipts(install_dir, &report.removed).await?;
+    Ok(report)
+}
+
+fn to_rel_string(rel: &Path) -> String {
+    rel.components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Remove now-empty directories up to (not including) the install root
+async fn remove_empty_parents(file: &Path, install_dir: &Path) {
+    let mut dir = file.parent();
+    while let Some(d) = dir {
+        if d == install_dir || tokio::fs::remove_dir(d).await.is_err() {
+            break;
+        }
+        dir = d.parent();
+    }
+}
+
+/// Drop removed files from every receipt, returning how many were rewritten
+async fn update_receipts(install_dir: &Path, removed: &[PathBuf]) -> Result<usize> {
+    let receipt_dir = install_dir.join(RECEIPT_DIR_NAME);
+    if removed.is_empty() || !receipt_dir.is_dir() {
+        return Ok(0);
+    }
+
+    let removed: HashSet<String> = removed
+        .iter()
+        .map(|p| to_rel_string(p).to_lowercase())
+        .collect();
+
+    let mut updated = 0;
+    let mut entries = tokio::fs::read_dir(&receipt_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let Some(mut receipt) = PackageReceipt::read(&path).await else {
+            continue;
+        };
+        let before = receipt.files.len();
+        receipt
+            .files
+            .retain(|f| !removed.contains(&f.path.to_lowercase()));
+        if receipt.files.len() != before {
+            receipt.write(&path).await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Whether a path (relative, forward slashes) should be removed
+fn should_prune(rel: &str, options: &PruneOptions) -> bool {
+    let lower = rel.to_lowercase();
+    let segments: Vec<&str> = lower.split('/').collect();
+
+    pruned_by_arch(&segments, options)
+        || options
+            .remove_components
+            .iter()
+            .any(|c| pruned_by_component(&lower, &segments, c, &options.remove_components))
+}
+
+fn arch_from_segment(segment: &str) -> Option<Architecture> {
+    ALL_ARCHES
+        .into_iter()
+        .find(|a| a.msvc_target_dir() == segment)
+}
+
+/// Architecture directories live under `lib`/`bin` trees in both MSVC
+/// (`lib/x86`, `bin/Hostx64/arm64`) and the SDK (`Lib/<ver>/um/x86`)
+///
+/// The host-native MSVC tools (`bin/Host<host>/<host>`) and the SDK
+/// `bin/<ver>/<arch>` tools (rc.exe, mt.exe) run on the host and are needed
+/// for every target, so they are never pruned.
+fn pruned_by_arch(segments: &[&str], options: &PruneOptions) -> bool {
+    if options.keep_arches.is_empty() {
+        return false;
+    }
+
+    // Skip the file name itself; only directories denote an architecture
+    let dirs = &segments[..segments.len() - 1];
+    let Some(tree) = dirs.iter().position(|s| *s == "lib" || *s == "bin") else {
+        return false;
+    };
+
+    if dirs[tree] == "bin" {
+        if dirs.first() == Some(&"windows kits") {
+            return false;
+        }
+        if let [host_dir, target, ..] = &dirs[tree + 1..] {
+            let host = host_dir.strip_prefix("host").and_then(arch_from_segment);
+            if host == Some(options.host_arch) && arch_from_segment(target) == host {
+                return false;
+            }
+        }
+    }
+
+    dirs[tree + 1..].iter().any(|segment| {
+        if let Some(host) = segment.strip_prefix("host") {
+            return arch_from_segment(host)
+                .is_some_and(|a| a != options.host_arch && !options.keep_arches.contains(&a));
+        }
+        arch_from_segment(segment).is_some_and(|a| !options.keep_arches.contains(&a))
+    })
+}
+
+fn pruned_by_component(
+    lower: &str,
+    segments: &[&str],
+    component: &MsvcComponent,
+    all: &[MsvcComponent],
+) -> bool {
+    let file_name = segments.last().copied().unwrap_or_default();
+    let in_atlmfc = segments.contains(&"atlmfc");
+
+    match component {
+        MsvcComponent::Spectre => segments.contains(&"spectre"),
+        MsvcComponent::Mfc => {
+            in_atlmfc
+                && (all.contains(&MsvcComponent::Atl)
+                    || segments.contains(&"mfc")
+                    || ["afx", "mfc", "uafx", "nafx"]
+                        .iter()
+                        .any(|p| file_name.starts_with(p)))
+        }
+        MsvcComponent::Atl => {
+            in_atlmfc
+                && (all.contains(&MsvcComponent::Mfc)
+                    || segments.contains(&"atl")
+                    || file_name.starts_with("atl"))
+        }
+        MsvcComponent::Asan => file_name.contains("clang_rt.asan"),
+        MsvcComponent::Uwp => {
+            segments.contains(&"lib") && (segments.contains(&"store") || segments.contains(&"uwp"))
+        }
+        MsvcComponent::Cli => segments.contains(&"msclr") || file_name.starts_with("msvcmrt"),
+        MsvcComponent::Modules => segments.contains(&"modules"),
+        MsvcComponent::Redist => lower.starts_with("vc/redist/"),
+        MsvcComponent::Custom(pattern) => lower.contains(&pattern.to_lowercase()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::ReceiptEntry;
+
+    const MSVC: &str = "VC/Tools/MSVC/14.44.34823";
+
+    fn keep_x64() -> PruneOptions {
+        PruneOptions {
+            keep_arches: vec![Architecture::X64],
+            host_arch: Architecture::X64,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_should_prune_arch() {
+        let opts = keep_x64();
+        let cases = [
+            ("bin/Hostx64/x64/cl.exe", false),
+            ("bin/Hostx64/arm64/cl.exe", true),
+            ("bin/Hostx86/x64/cl.exe", true),
+            ("lib/x64/libcmt.lib", false),
+            ("lib/x86/libcmt.lib", true),
+            ("lib/onecore/arm64/libcmt.lib", true),
+            ("include/x86/not_an_arch_dir.h", false),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                should_prune(&format!("{}/{}", MSVC, path), &opts),
+                expected,
+                "{}",
+                path
+            );
+        }
+
+        assert!(should_prune(
+            "Windows Kits/10/Lib/10.0.26100.0/um/x86/kernel32.lib",
+            &opts
+        ));
+        assert!(!should_prune(
+            "Windows Kits/10/Lib/10.0.26100.0/um/x64/kernel32.lib",
+            &opts
+        ));
+        // A file named like an architecture is not an architecture directory
+        assert!(!should_prune(
+            "Windows Kits/10/bin/10.0.26100.0/x64/x86",
+            &opts
+        ));
+    }
+
+    #[test]
+    fn test_should_prune_keeps_host_tools() {
+        let opts = PruneOptions {
+            keep_arches: vec![Architecture::Arm64],
+            host_arch: Architecture::X64,
+            ..Default::default()
+        };
+        let cases = [
+            // Cross compilers load mspdbcore.dll from the host-native tools
+            ("bin/Hostx64/x64/mspdbcore.dll", false),
+            ("bin/Hostx64/arm64/cl.exe", false),
+            ("bin/Hostx64/x86/cl.exe", true),
+            ("bin/Hostx86/x86/cl.exe", true),
+            ("lib/x64/libcmt.lib", true),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(
+                should_prune(&format!("{}/{}", MSVC, path), &opts),
+                expected,
+                "{}",
+                path
+            );
+        }
+
+        // SDK bin directories hold host tools, including the x86 rc/mt fallback
+        for arch in ["x64", "x86", "arm64"] {
+            assert!(!should_prune(
+                &format!("Windows Kits/10/bin/10.0.26100.0/{}/rc.exe", arch),
+                &opts
+            ));
+        }
+        assert!(!should_prune(
+            "Windows Kits/10/bin/10.0.26100.0/x86/mt.exe",
+            &keep_x64()
+        ));
+        assert!(should_prune(
+            "Windows Kits/10/Lib/10.0.26100.0/um/x64/kernel32.lib",
+            &opts
+        ));
+    }
+
+    #[test]
+    fn test_should_prune_components() {
+        let opts = PruneOptions {
+            remove_components: vec![MsvcComponent::Mfc, MsvcComponent::Spectre],
+            ..Default::default()
+        };
+        assert!(should_prune(
+            &format!("{}/atlmfc/include/afxwin.h", MSVC),
+            &opts
+        ));
+        assert!(should_prune(
+            &format!("{}/atlmfc/lib/x64/mfc140.lib", MSVC),
+            &opts
+        ));
+        assert!(!should_prune(
+            &format!("{}/atlmfc/include/atlbase.h", MSVC),
+            &opts
+        ));
+        assert!(should_prune(
+            &format!("{}/lib/x64/spectre/libcmt.lib", MSVC),
+            &opts
+        ));
+        assert!(!should_prune(
+            &format!("{}/lib/x64/libcmt.lib", MSVC),
+            &opts
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_prune_dry_run_and_apply() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let keep = root.join(MSVC).join("lib/x64/libcmt.lib");
+        let drop = root.join(MSVC).join("lib/x86/libcmt.lib");
+        for path in [&keep, &drop] {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, b"0123456789").unwrap();
+        }
+
+        std::fs::create_dir_all(root.join(RECEIPT_DIR_NAME)).unwrap();
+        let receipt_path = PackageReceipt::path_for(root, "crt.vsix");
+        let entry = |p: &str| ReceiptEntry {
+            path: format!("{}/{}", MSVC, p),
+            sha256: "00".to_string(),
+            size: 10,
+        };
+        PackageReceipt {
+            package: "crt.vsix".to_string(),
+            files: vec![entry("lib/x64/libcmt.lib"), entry("lib/x86/libcmt.lib")],
+        }
+        .write(&receipt_path)
+        .await
+        .unwrap();
+
+        let mut opts = keep_x64();
+        opts.dry_run = true;
+        let report = prune(root, &opts).await.unwrap();
+        assert_eq!(report.bytes_freed, 10);
+        assert_eq!(report.removed.len(), 1);
+        assert!(drop.exists());
+
+        opts.dry_run = false;
+        let report = prune(root, &opts).await.unwrap();
+        assert_eq!(report.receipts_updated, 1);
+        assert!(keep.exists());
+        assert!(!drop.exists());
+        assert!(!drop.parent().unwrap().exists());
+
+        let receipt = PackageReceipt::read(&receipt_path).await.unwrap();
+        assert_eq!(receipt.files, vec![entry("lib/x64/libcmt.lib")]);
+    }
+}
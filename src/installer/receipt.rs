@@ -0,0 +1,368 @@
+//! Extraction receipts and file-conflict detection
+//!
+//! After a payload is extracted, a receipt listing every file it wrote (with
+//! size and SHA256) is stored in the extraction marker directory. Receipts
+//! record which package owns each file, so conflicts between packages that
+//! ship the same path with different contents can be reported instead of the
+//! last-extracted package silently winning.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::filter::ExtractFilter;
+use crate::error::Result;
+use crate::warning::{Warning, WarningCode};
+
+/// Directory (under the install root) holding extraction markers and receipts
+pub const RECEIPT_DIR_NAME: &str = ".msvc-kit-extracted";
+
+/// A file written by extracting a package
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceiptEntry {
+    /// Path relative to the install root, using forward slashes
+    pub path: String,
+    /// Lowercase hex SHA256 of the extracted contents
+    pub sha256: String,
+    /// File size in bytes
+    pub size: u64,
+}
+
+/// Files written by extracting a single package payload
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackageReceipt {
+    /// Payload file name the files were extracted from
+    pub package: String,
+    /// Extracted files
+    pub files: Vec<ReceiptEntry>,
+}
+
+impl PackageReceipt {
+    /// Path of the receipt (extraction marker) for `package` under `install_dir`
+    pub fn path_for(install_dir: &Path, package: &str) -> std::path::PathBuf {
+        install_dir
+            .join(RECEIPT_DIR_NAME)
+            .join(format!("{}.done", package))
+    }
+
+    /// Read a receipt, returning `None` if it is missing or predates receipts
+    ///
+    /// Markers written by older versions only contain `ok` and carry no file list.
+    pub async fn read(path: &Path) -> Option<Self> {
+        let bytes = tokio::fs::read(path).await.ok()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Write the receipt as JSON
+    pub async fn write(&self, path: &Path) -> Result<()> {
+        tokio::fs::write(path, serde_json::to_vec(self)?).await?;
+        Ok(())
+    }
+
+    /// Total size of the extracted files in bytes
+    pub fn total_size(&self) -> u64 {
+        self.files.iter().map(|f| f.size).sum()
+    }
+}
+
+/// Drop the extraction markers of payloads matching `pattern`, forcing them to be extracted again
+///
+/// `pattern` is a case-insensitive glob over payload file names (`*crt*`,
+/// `*.msi`); an empty pattern matches every payload. Extracted files are
+/// left in place and overwritten by the next extraction. Returns the
+/// invalidated payload names, sorted.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::invalidate_extraction;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let payloads = invalidate_extraction("C:/msvc-kit".as_ref(), "*crt*").await?;
+///     println!("{} payloads will be extracted again", payloads.len());
+///     Ok(())
+/// }
+/// ```
+pub async fn invalidate_extraction(target_dir: &Path, pattern: &str) -> Result<Vec<String>> {
+    let marker_dir = target_dir.join(RECEIPT_DIR_NAME);
+    if !marker_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let filter = ExtractFilter::new([pattern]);
+    let mut invalidated = Vec::new();
+    let mut entries = tokio::fs::read_dir(&marker_dir).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name();
+        let Some(package) = file_name.to_str().and_then(|n| n.strip_suffix(".done")) else {
+            continue;
+        };
+        if filter.matches(package) {
+            tokio::fs::remove_file(entry.path()).await?;
+            invalidated.push(package.to_string());
+        }
+    }
+    invalidated.sort();
+    Ok(invalidated)
+}
+
+/// Two packages that extracted the same path with different contents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileConflict {
+    /// Conflicting path relative to the install root
+    pub path: String,
+    /// Package that wrote the path first
+    ///
+    /// [`detect_conflicts`] fills in the payload file names of the receipts;
+    /// [`extract_packages_with_report`](super::extract_packages_with_report)
+    /// reports package ids where the download index knows them.
+    pub first_package: String,
+    /// SHA256 written by `first_package`
+    pub first_sha256: String,
+    /// Package listed later that wrote the same path (see `first_package`)
+    pub second_package: String,
+    /// SHA256 written by `second_package`
+    pub second_sha256: String,
+}
+
+impl std::fmt::Display for FileConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({} sha256:{} vs {} sha256:{})",
+            self.path,
+            self.first_package,
+            short_hash(&self.first_sha256),
+            self.second_package,
+            short_hash(&self.second_sha256)
+        )
+    }
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..12).unwrap_or(hash)
+}
+
+/// A non-fatal problem while extracting a package
+///
+/// The package is still considered extracted, e.g. when a file's timestamp
+/// could not be preserved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractWarning {
+    /// Payload file name
+    pub package: String,
+    /// Affected path inside the package, if the problem concerns one file
+    pub path: Option<String>,
+    /// What went wrong
+    pub message: String,
+}
+
+impl std::fmt::Display for ExtractWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.path {
+            Some(path) => write!(f, "{}: {}: {}", self.package, path, self.message),
+            None => write!(f, "{}: {}", self.package, self.message),
+        }
+    }
+}
+
+/// Summary of extracting a set of packages
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallReport {
+    /// Packages extracted in this run
+    pub extracted: usize,
+    /// Packages skipped because they were already extracted
+    pub cached: usize,
+    /// Paths written by more than one package with different contents
+    pub conflicts: Vec<FileConflict>,
+    /// Non-fatal problems, in input order
+    #[serde(default)]
+    pub warnings: Vec<ExtractWarning>,
+    /// Downloads removed afterwards by the cleanup policy
+    #[serde(default)]
+    pub cleanup: super::CleanupReport,
+}
+
+impl InstallReport {
+    /// Whether no file conflicts were detected
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+
+    /// Conflicts and extraction problems as [`Warning`]s
+    pub fn to_warnings(&self) -> Vec<Warning> {
+        let conflicts = self.conflicts.iter().map(|c| {
+            Warning::new(
+                WarningCode::FileConflict,
+                format!(
+                    "written by {} and {} with different contents",
+                    c.first_package, c.second_package
+                ),
+            )
+            .with_context(&c.path)
+        });
+        let problems = self.warnings.iter().map(|w| {
+            let context = match &w.path {
+                Some(path) => format!("{}: {}", w.package, path),
+                None => w.package.clone(),
+            };
+            Warning::new(WarningCode::Extraction, &w.message).with_context(context)
+        });
+        conflicts.chain(problems).collect()
+    }
+}
+
+/// Find paths owned by several receipts with differing contents
+///
+/// Receipts are processed in order, so `second_package` is always the one
+/// listed later. Paths are compared case-insensitively, matching
+/// the Windows file system. Identical duplicates are not conflicts.
+pub fn detect_conflicts(receipts: &[PackageReceipt]) -> Vec<FileConflict> {
+    let mut owners: HashMap<String, (&str, &ReceiptEntry)> = HashMap::new();
+    let mut conflicts = Vec::new();
+
+    for receipt in receipts {
+        for entry in &receipt.files {
+            let key = entry.path.to_lowercase();
+            if let Some((owner, previous)) = owners.get(&key) {
+                if *owner != receipt.package && previous.sha256 != entry.sha256 {
+                    conflicts.push(FileConflict {
+                        path: entry.path.clone(),
+                        first_package: owner.to_string(),
+                        first_sha256: previous.sha256.clone(),
+                        second_package: receipt.package.clone(),
+                        second_sha256: entry.sha256.clone(),
+                    });
+                }
+            }
+            owners.insert(key, (&receipt.package, entry));
+        }
+    }
+
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(package: &str, files: &[(&str, &str)]) -> PackageReceipt {
+        PackageReceipt {
+            package: package.to_string(),
+            files: files
+                .iter()
+                .map(|(path, sha)| ReceiptEntry {
+                    path: path.to_string(),
+                    sha256: sha.to_string(),
+                    size: 1,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_detect_conflicts() {
+        let receipts = vec![
+            receipt(
+                "a.vsix",
+                &[("include/x.h", "aaa"), ("include/same.h", "111")],
+            ),
+            receipt(
+                "b.vsix",
+                &[("Include/X.h", "bbb"), ("include/same.h", "111")],
+            ),
+        ];
+
+        let conflicts = detect_conflicts(&receipts);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, "Include/X.h");
+        assert_eq!(conflicts[0].first_package, "a.vsix");
+        assert_eq!(conflicts[0].first_sha256, "aaa");
+        assert_eq!(conflicts[0].second_package, "b.vsix");
+        assert_eq!(conflicts[0].second_sha256, "bbb");
+    }
+
+    #[test]
+    fn test_report_to_warnings() {
+        let receipts = vec![
+            receipt("a.vsix", &[("x.h", "aaa")]),
+            receipt("b.vsix", &[("x.h", "bbb")]),
+        ];
+        let report = InstallReport {
+            conflicts: detect_conflicts(&receipts),
+            warnings: vec![ExtractWarning {
+                package: "c.bin".to_string(),
+                path: None,
+                message: "unknown file type, not extracted".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        let warnings = report.to_warnings();
+        assert_eq!(warnings.len(), 2);
+        assert_eq!(warnings[0].code, WarningCode::FileConflict);
+        assert_eq!(warnings[0].context.as_deref(), Some("x.h"));
+        assert_eq!(warnings[1].code, WarningCode::Extraction);
+        assert_eq!(warnings[1].context.as_deref(), Some("c.bin"));
+    }
+
+    #[test]
+    fn test_no_conflict_within_one_package() {
+        let receipts = vec![receipt("a.vsix", &[("x.h", "1"), ("x.h", "2")])];
+        assert!(detect_conflicts(&receipts).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_receipt_roundtrip_and_legacy_marker() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(RECEIPT_DIR_NAME)).unwrap();
+
+        let path = PackageReceipt::path_for(temp_dir.path(), "a.vsix");
+        let original = receipt("a.vsix", &[("include/x.h", "aaa")]);
+        original.write(&path).await.unwrap();
+        assert_eq!(PackageReceipt::read(&path).await, Some(original));
+
+        std::fs::write(&path, b"ok").unwrap();
+        assert!(PackageReceipt::read(&path).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_invalidate_extraction() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        assert!(invalidate_extraction(root, "*").await.unwrap().is_empty());
+
+        std::fs::create_dir_all(root.join(RECEIPT_DIR_NAME)).unwrap();
+        for package in [
+            "Microsoft.VC.14.44.CRT.Headers.base.vsix",
+            "Microsoft.VC.14.44.Tools.HostX64.TargetX64.base.vsix",
+            "Universal CRT Headers Libraries and Sources-x86_en-us.msi",
+        ] {
+            std::fs::write(PackageReceipt::path_for(root, package), b"ok").unwrap();
+        }
+        let stamp = root
+            .join(RECEIPT_DIR_NAME)
+            .join("msvc-14.44.34823-x64.install.json");
+        std::fs::write(&stamp, b"{}").unwrap();
+
+        let invalidated = invalidate_extraction(root, "*crt*").await.unwrap();
+        assert_eq!(
+            invalidated,
+            vec![
+                "Microsoft.VC.14.44.CRT.Headers.base.vsix",
+                "Universal CRT Headers Libraries and Sources-x86_en-us.msi",
+            ]
+        );
+        assert!(!PackageReceipt::path_for(root, &invalidated[0]).exists());
+        assert!(PackageReceipt::path_for(
+            root,
+            "Microsoft.VC.14.44.Tools.HostX64.TargetX64.base.vsix"
+        )
+        .exists());
+
+        assert_eq!(invalidate_extraction(root, "").await.unwrap().len(), 1);
+        assert!(stamp.exists());
+    }
+}
@@ -0,0 +1,308 @@
+//! Copying the VC++ runtime next to an application
+//!
+//! Applications built with the dynamic CRT (`/MD`) need `vcruntime140.dll`
+//! and friends at run time. Instead of requiring the VC++ Redistributable
+//! installer on the target machine, the DLLs can be deployed app-locally.
+//! [`copy_redist`] copies them from the installed Redist component
+//! (`VC/Redist/MSVC/<version>`), which is downloaded with the `redist`
+//! component.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{MsvcKitError, Result};
+use crate::query::newest_version_dir;
+use crate::version::Architecture;
+
+/// Runtime DLLs every C++ application built with `/MD` may need
+const CORE_DLLS: &[&str] = &[
+    "vcruntime140",
+    "vcruntime140_1",
+    "msvcp140",
+    "msvcp140_1",
+    "msvcp140_2",
+    "msvcp140_atomic_wait",
+    "msvcp140_codecvt_ids",
+];
+
+/// Runtime DLLs only needed by some applications
+const OPTIONAL_DLLS: &[&str] = &["concrt140", "vccorlib140"];
+
+/// Options for [`copy_redist`]
+#[derive(Debug, Clone, Default)]
+pub struct RedistOptions {
+    /// Architecture of the application
+    pub arch: Architecture,
+    /// Copy the debug runtime (`vcruntime140d.dll`, ...) instead
+    ///
+    /// The debug runtime may not be redistributed; use it for testing only.
+    pub debug: bool,
+    /// Built binaries whose imports select the DLLs to copy
+    ///
+    /// Without binaries, the core runtime (`vcruntime140*`, `msvcp140*`) is
+    /// copied.
+    pub binaries: Vec<PathBuf>,
+    /// Also copy `concrt140` and `vccorlib140` when no binaries are given
+    pub all: bool,
+    /// Only report what would be copied
+    pub dry_run: bool,
+}
+
+/// Result of [`copy_redist`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RedistReport {
+    /// Redist version the DLLs were taken from
+    pub version: String,
+    /// Directory the DLLs were taken from
+    pub source_dir: PathBuf,
+    /// Copied (or, in dry-run mode, selected) DLLs at their destination
+    pub copied: Vec<PathBuf>,
+}
+
+/// Directory holding the runtime DLLs for `arch`
+///
+/// `VC/Redist/MSVC/<version>/<arch>/Microsoft.VC<toolset>.CRT`, or
+/// `debug_nonredist/<arch>/Microsoft.VC<toolset>.DebugCRT` for the debug runtime.
+pub fn redist_dir(
+    install_dir: &Path,
+    arch: Architecture,
+    debug: bool,
+) -> Result<(String, PathBuf)> {
+    let root = install_dir.join("VC").join("Redist").join("MSVC");
+    let version = newest_version_dir(&root, |_| true).ok_or_else(|| {
+        MsvcKitError::ComponentNotFound(format!(
+            "VC++ redistributable not found in {} (install it with --include-component redist)",
+            root.display()
+        ))
+    })?;
+
+    let (parent, suffix) = if debug {
+        (
+            root.join(&version)
+                .join("debug_nonredist")
+                .join(arch.to_string()),
+            ".DebugCRT",
+        )
+    } else {
+        (root.join(&version).join(arch.to_string()), ".CRT")
+    };
+    std::fs::read_dir(&parent)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .find(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            path.is_dir() && name.starts_with("Microsoft.VC") && name.ends_with(suffix)
+        })
+        .map(|dir| (version, dir))
+        .ok_or_else(|| {
+            MsvcKitError::ComponentNotFound(format!(
+                "No {} runtime for {} in {}",
+                if debug { "debug" } else { "release" },
+                arch,
+                parent.display()
+            ))
+        })
+}
+
+/// DLL name for `base`, e.g. `msvcp140d.dll` with the debug runtime
+fn dll_name(base: &str, debug: bool) -> String {
+    let suffix = if debug { "d" } else { "" };
+    // Named variants take the suffix first: msvcp140d_atomic_wait.dll, but msvcp140_1d.dll
+    match base.strip_prefix("msvcp140_") {
+        Some(variant) if !variant.chars().all(|c| c.is_ascii_digit()) => {
+            format!("msvcp140{}_{}.dll", suffix, variant)
+        }
+        _ => format!("{}{}.dll", base, suffix),
+    }
+}
+
+/// Whether `haystack` contains `needle`, ignoring ASCII case
+fn contains_ignore_case(haystack: &[u8], needle: &str) -> bool {
+    let needle = needle.as_bytes();
+    haystack
+        .windows(needle.len())
+        .any(|window| window.eq_ignore_ascii_case(needle))
+}
+
+/// DLLs from `available` referenced by name in any of `binaries`
+///
+/// The runtime DLLs import `vcruntime140*` themselves, so those are added
+/// whenever anything else is selected.
+fn imported_dlls(binaries: &[PathBuf], available: &[String]) -> Result<Vec<String>> {
+    let mut selected: Vec<String> = Vec::new();
+    for binary in binaries {
+        let bytes = std::fs::read(binary)?;
+        for dll in available {
+            if !selected.contains(dll) && contains_ignore_case(&bytes, dll) {
+                selected.push(dll.clone());
+            }
+        }
+    }
+    if !selected.is_empty() {
+        for dll in available.iter().filter(|d| d.starts_with("vcruntime140")) {
+            if !selected.contains(dll) {
+                selected.push(dll.clone());
+            }
+        }
+    }
+    Ok(selected)
+}
+
+/// Copy the VC++ runtime DLLs an application needs into `dest`
+///
+/// With `options.binaries`, only DLLs whose names appear in the binaries
+/// (imports and delay-load imports) are copied, plus the `vcruntime140*`
+/// DLLs they depend on. DLLs missing from the Redist directory (e.g.
+/// `vcruntime140_1.dll` on x86) are skipped.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{copy_redist, RedistOptions};
+/// use msvc_kit::Architecture;
+///
+/// let options = RedistOptions {
+///     arch: Architecture::X64,
+///     binaries: vec!["target/release/app.exe".into()],
+///     ..Default::default()
+/// };
+/// let report = copy_redist("C:/msvc-kit".as_ref(), "target/release".as_ref(), &options)?;
+/// for dll in &report.copied {
+///     println!("{}", dll.display());
+/// }
+/// # Ok::<(), msvc_kit::MsvcKitError>(())
+/// ```
+pub fn copy_redist(
+    install_dir: &Path,
+    dest: &Path,
+    options: &RedistOptions,
+) -> Result<RedistReport> {
+    let (version, source_dir) = redist_dir(install_dir, options.arch, options.debug)?;
+    let available: Vec<String> = CORE_DLLS
+        .iter()
+        .chain(OPTIONAL_DLLS)
+        .map(|base| dll_name(base, options.debug))
+        .filter(|name| source_dir.join(name).is_file())
+        .collect();
+
+    let selected = if options.binaries.is_empty() {
+        let wanted: Vec<String> = CORE_DLLS
+            .iter()
+            .chain(if options.all { OPTIONAL_DLLS } else { &[] })
+            .map(|base| dll_name(base, options.debug))
+            .collect();
+        available
+            .into_iter()
+            .filter(|name| wanted.contains(name))
+            .collect()
+    } else {
+        imported_dlls(&options.binaries, &available)?
+    };
+
+    let mut report = RedistReport {
+        version,
+        source_dir,
+        copied: selected.iter().map(|name| dest.join(name)).collect(),
+    };
+    report.copied.sort();
+    if options.dry_run {
+        return Ok(report);
+    }
+
+    std::fs::create_dir_all(dest)?;
+    for name in &selected {
+        std::fs::copy(report.source_dir.join(name), dest.join(name))?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_redist(root: &Path) -> PathBuf {
+        std::fs::create_dir_all(root.join("VC/Redist/MSVC/v143")).unwrap();
+        let crt = root.join("VC/Redist/MSVC/14.44.35112/x64/Microsoft.VC143.CRT");
+        let debug =
+            root.join("VC/Redist/MSVC/14.44.35112/debug_nonredist/x64/Microsoft.VC143.DebugCRT");
+        std::fs::create_dir_all(&crt).unwrap();
+        std::fs::create_dir_all(&debug).unwrap();
+        for base in CORE_DLLS.iter().chain(OPTIONAL_DLLS) {
+            std::fs::write(crt.join(dll_name(base, false)), base).unwrap();
+            std::fs::write(debug.join(dll_name(base, true)), base).unwrap();
+        }
+        crt
+    }
+
+    #[test]
+    fn test_dll_name() {
+        assert_eq!(dll_name("vcruntime140_1", false), "vcruntime140_1.dll");
+        assert_eq!(dll_name("vcruntime140_1", true), "vcruntime140_1d.dll");
+        assert_eq!(
+            dll_name("msvcp140_atomic_wait", true),
+            "msvcp140d_atomic_wait.dll"
+        );
+        assert_eq!(dll_name("msvcp140_2", true), "msvcp140_2d.dll");
+        assert_eq!(dll_name("concrt140", true), "concrt140d.dll");
+    }
+
+    #[test]
+    fn test_copy_redist_from_binary_imports() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fake_redist(root);
+        let app = root.join("app");
+        std::fs::create_dir_all(&app).unwrap();
+        std::fs::write(app.join("app.exe"), b"MZ....KERNEL32.dll\0MSVCP140.dll\0").unwrap();
+
+        let options = RedistOptions {
+            arch: Architecture::X64,
+            binaries: vec![app.join("app.exe")],
+            ..Default::default()
+        };
+        let report = copy_redist(root, &app, &options).unwrap();
+        assert_eq!(report.version, "14.44.35112");
+        assert_eq!(
+            report.copied,
+            vec![
+                app.join("msvcp140.dll"),
+                app.join("vcruntime140.dll"),
+                app.join("vcruntime140_1.dll"),
+            ]
+        );
+        assert!(app.join("vcruntime140_1.dll").is_file());
+        assert!(!app.join("concrt140.dll").exists());
+    }
+
+    #[test]
+    fn test_copy_redist_debug_all_dry_run() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        fake_redist(root);
+        let dest = root.join("out");
+
+        let options = RedistOptions {
+            arch: Architecture::X64,
+            debug: true,
+            all: true,
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = copy_redist(root, &dest, &options).unwrap();
+        assert_eq!(report.copied.len(), CORE_DLLS.len() + OPTIONAL_DLLS.len());
+        assert!(report.copied.contains(&dest.join("vccorlib140d.dll")));
+        assert!(!dest.exists());
+
+        let options = RedistOptions {
+            arch: Architecture::Arm64,
+            ..Default::default()
+        };
+        assert!(matches!(
+            copy_redist(root, &dest, &options),
+            Err(MsvcKitError::ComponentNotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,417 @@
+//! Moving an existing installation to another directory
+//!
+//! Extracted files and receipts only use paths relative to the install root,
+//! but activation scripts and the download indexes record absolute paths.
+//! [`relocate`] moves the tree and rewrites those, so the moved install works
+//! without downloading or extracting anything again.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::clean::is_msvc_kit_install;
+use crate::downloader::DownloadIndex;
+use crate::env::{default_profiles_dir, list_profiles, profile_path, ToolKind};
+use crate::error::{MsvcKitError, Result};
+use crate::query::{query_installation, QueryOptions};
+use crate::version::Architecture;
+
+/// Extensions of activation scripts generated with absolute paths
+const SCRIPT_EXTENSIONS: &[&str] = &["bat", "cmd", "ps1", "psm1", "sh"];
+
+/// Result of [`relocate`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RelocateReport {
+    /// Previous installation directory
+    pub from: PathBuf,
+    /// New installation directory
+    pub to: PathBuf,
+    /// Whether the tree was copied and deleted (different volumes) instead of renamed
+    pub copied: bool,
+    /// Activation scripts whose absolute paths were rewritten
+    pub scripts_rewritten: Vec<PathBuf>,
+    /// Download index entries pointing into the new directory
+    pub index_entries_updated: usize,
+    /// Tools found at the new location
+    pub tools: HashMap<ToolKind, PathBuf>,
+    /// Problems found while validating the new location
+    pub warnings: Vec<String>,
+}
+
+/// Move the installation at `from` to `to`
+///
+/// `to` must not exist or be an empty directory. The tree is renamed when
+/// possible and copied otherwise (e.g. from `C:` to `D:`). Afterwards,
+/// activation scripts (in the root and the `bin/<arch>` wrappers) and the
+/// download indexes are rewritten and the tool paths are checked; problems,
+/// including profiles that still point at `from`, are listed in
+/// [`RelocateReport::warnings`].
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::relocate;
+/// use msvc_kit::ToolKind;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let report = relocate("C:/msvc-kit".as_ref(), "D:/msvc-kit".as_ref()).await?;
+///     println!("cl.exe: {:?}", report.tools.get(&ToolKind::Cl));
+///     Ok(())
+/// }
+/// ```
+pub async fn relocate(from: &Path, to: &Path) -> Result<RelocateReport> {
+    let from = std::path::absolute(from)?;
+    let to = std::path::absolute(to)?;
+    check_paths(&from, &to)?;
+
+    let mut report = RelocateReport {
+        from: from.clone(),
+        to: to.clone(),
+        ..Default::default()
+    };
+
+    if let Some(parent) = to.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    if to.exists() {
+        // An empty target directory is replaced
+        tokio::fs::remove_dir(&to).await?;
+    }
+    if tokio::fs::rename(&from, &to).await.is_err() {
+        let (src, dst) = (from.clone(), to.clone());
+        tokio::task::spawn_blocking(move || copy_tree(&src, &dst))
+            .await
+            .map_err(|e| MsvcKitError::Other(e.to_string()))??;
+        tokio::fs::remove_dir_all(&from).await?;
+        report.copied = true;
+    }
+
+    report.scripts_rewritten = rewrite_scripts(&from, &to).await?;
+    report.index_entries_updated = rebase_indexes(&from, &to).await?;
+    report
+        .warnings
+        .extend(stale_profile_warnings(&default_profiles_dir(), &from));
+
+    match query_installation(&QueryOptions::builder().install_dir(&to).build()) {
+        Ok(result) => {
+            if result.msvc.is_some() && result.tool(ToolKind::Cl).is_none() {
+                report
+                    .warnings
+                    .push("cl.exe not found in the new location".to_string());
+            }
+            report.tools = result.tools;
+        }
+        Err(e) => report.warnings.push(e.to_string()),
+    }
+    Ok(report)
+}
+
+fn check_paths(from: &Path, to: &Path) -> Result<()> {
+    if !is_msvc_kit_install(from) {
+        return Err(MsvcKitError::InstallPath(format!(
+            "{} does not look like an msvc-kit installation (no VC/ or Windows Kits/)",
+            from.display()
+        )));
+    }
+    if to.starts_with(from) || from.starts_with(to) {
+        return Err(MsvcKitError::InstallPath(format!(
+            "Cannot move {} into {}",
+            from.display(),
+            to.display()
+        )));
+    }
+    let not_empty = std::fs::read_dir(to).is_ok_and(|mut entries| entries.next().is_some());
+    if to.is_file() || not_empty {
+        return Err(MsvcKitError::InstallPath(format!(
+            "{} already exists and is not empty",
+            to.display()
+        )));
+    }
+    Ok(())
+}
+
+fn copy_tree(from: &Path, to: &Path) -> Result<()> {
+    std::fs::create_dir_all(to)?;
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Spellings of `from` and `to` that scripts and profiles may use
+fn path_replacements(from: &Path, to: &Path) -> Vec<(String, String)> {
+    let (from, to) = (from.display().to_string(), to.display().to_string());
+    let mut replacements = vec![
+        (from.clone(), to.clone()),
+        (from.replace('\\', "/"), to.replace('\\', "/")),
+        (from.replace('/', "\\"), to.replace('/', "\\")),
+    ];
+    replacements.dedup();
+    replacements
+}
+
+/// Whether `c` can continue a path component, e.g. `2` in `C:\msvc-kit2`
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '-' | '_' | '.')
+}
+
+/// Byte offsets of the occurrences of `old` in `content` that are a whole path
+///
+/// The match must not continue a longer name on either side, so
+/// `C:\msvc-kit` matches `C:\msvc-kit\VC` but not `C:\msvc-kit2\VC`.
+fn path_matches<'a>(content: &'a str, old: &'a str) -> impl Iterator<Item = usize> + 'a {
+    content.match_indices(old).filter_map(move |(start, _)| {
+        let before = content[..start].chars().next_back();
+        let after = content[start + old.len()..].chars().next();
+        let bounded = !before.is_some_and(is_name_char) && !after.is_some_and(is_name_char);
+        bounded.then_some(start)
+    })
+}
+
+/// Replace whole-path occurrences of `old` with `new`
+fn replace_path(content: &str, old: &str, new: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for start in path_matches(content, old).collect::<Vec<_>>() {
+        out.push_str(&content[last..start]);
+        out.push_str(new);
+        last = start + old.len();
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// Activation scripts generated into an install root
+///
+/// Scripts in the root (`setup.bat`, `MsvcKit.psm1`, ...) and the
+/// `bin/<arch>` wrappers of msvc-wine style layouts.
+async fn script_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut scripts = Vec::new();
+    let mut entries = tokio::fs::read_dir(root).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let is_script = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| SCRIPT_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        if is_script && entry.file_type().await?.is_file() {
+            scripts.push(path);
+        }
+    }
+
+    if let Ok(mut arch_dirs) = tokio::fs::read_dir(root.join("bin")).await {
+        while let Some(arch_dir) = arch_dirs.next_entry().await? {
+            let is_arch = arch_dir
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.parse::<Architecture>().is_ok());
+            if !is_arch || !arch_dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut entries = tokio::fs::read_dir(arch_dir.path()).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_type().await?.is_file() {
+                    scripts.push(entry.path());
+                }
+            }
+        }
+    }
+    Ok(scripts)
+}
+
+/// Replace the old root in the activation scripts of the new root
+async fn rewrite_scripts(from: &Path, to: &Path) -> Result<Vec<PathBuf>> {
+    let replacements = path_replacements(from, to);
+
+    let mut rewritten = Vec::new();
+    for path in script_files(to).await? {
+        // Binary files are not scripts
+        let Ok(content) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        let mut updated = content.clone();
+        for (old, new) in &replacements {
+            updated = replace_path(&updated, old, new);
+        }
+        if updated != content {
+            tokio::fs::write(&path, updated).await?;
+            rewritten.push(path);
+        }
+    }
+    rewritten.sort();
+    Ok(rewritten)
+}
+
+/// Warnings for profiles in `profiles_dir` that still point at `from`
+///
+/// Profiles belong to the user rather than the install, so they are not
+/// rewritten; `msvc-kit profile save` records them again.
+fn stale_profile_warnings(profiles_dir: &Path, from: &Path) -> Vec<String> {
+    let spellings: Vec<String> = path_replacements(from, from)
+        .into_iter()
+        .map(|(old, _)| old)
+        .collect();
+    list_profiles(profiles_dir)
+        .into_iter()
+        .filter(|name| {
+            std::fs::read_to_string(profile_path(profiles_dir, name)).is_ok_and(|content| {
+                spellings
+                    .iter()
+                    .any(|old| path_matches(&content, old).next().is_some())
+            })
+        })
+        .map(|name| {
+            format!(
+                "profile '{}' still points at {}; save it again",
+                name,
+                from.display()
+            )
+        })
+        .collect()
+}
+
+/// Point the download indexes under `to/downloads` at the moved payloads
+async fn rebase_indexes(from: &Path, to: &Path) -> Result<usize> {
+    let mut updated = 0;
+    let mut pending = vec![to.join("downloads")];
+    while let Some(current) = pending.pop() {
+        let Ok(mut entries) = tokio::fs::read_dir(&current).await else {
+            continue;
+        };
+        while let Some(entry) = entries.next_entry().await? {
+            let path = entry.path();
+            if entry.file_type().await?.is_dir() {
+                pending.push(path);
+            } else if path.file_name().is_some_and(|n| n == "index.db") {
+                let mut index = DownloadIndex::load(&path).await?;
+                updated += index.rebase(from, to).await?;
+            }
+        }
+    }
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_relocate_rewrites_scripts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let from = temp_dir.path().join("old");
+        let to = temp_dir.path().join("new");
+        std::fs::create_dir_all(from.join("VC/Tools/MSVC/14.44.34823")).unwrap();
+        std::fs::write(
+            from.join("prompt-x64_x64.bat"),
+            format!("set \"VCINSTALLDIR={}\\VC\"", from.display()),
+        )
+        .unwrap();
+        std::fs::write(from.join("notes.txt"), from.display().to_string()).unwrap();
+
+        let report = relocate(&from, &to).await.unwrap();
+        assert!(!from.exists());
+        assert!(to.join("VC/Tools/MSVC/14.44.34823").is_dir());
+        assert_eq!(
+            report.scripts_rewritten,
+            vec![to.join("prompt-x64_x64.bat")]
+        );
+
+        let script = std::fs::read_to_string(to.join("prompt-x64_x64.bat")).unwrap();
+        assert!(script.contains(&to.display().to_string()));
+        // Only scripts are rewritten
+        let notes = std::fs::read_to_string(to.join("notes.txt")).unwrap();
+        assert_eq!(notes, from.display().to_string());
+    }
+
+    #[test]
+    fn test_replace_path_on_boundaries() {
+        let content = r"set PATH=C:\msvc-kit\VC;C:\msvc-kit2\VC;C:\msvc-kit.old;C:\msvc-kit";
+        assert_eq!(
+            replace_path(content, r"C:\msvc-kit", r"D:\msvc"),
+            r"set PATH=D:\msvc\VC;C:\msvc-kit2\VC;C:\msvc-kit.old;D:\msvc"
+        );
+        assert_eq!(
+            replace_path(
+                "\"/opt/msvc\" /home/opt/msvc /opt/msvc/bin",
+                "/opt/msvc",
+                "/srv/msvc"
+            ),
+            "\"/srv/msvc\" /home/opt/msvc /srv/msvc/bin"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_relocate_rewrites_wrappers() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let from = temp_dir.path().join("old");
+        let to = temp_dir.path().join("new");
+        std::fs::create_dir_all(from.join("VC")).unwrap();
+        std::fs::create_dir_all(from.join("bin/x64")).unwrap();
+        std::fs::write(from.join("bin/x64/cl"), format!("{}/vc/cl", from.display())).unwrap();
+        std::fs::write(
+            from.join("setup.sh"),
+            format!("A={}\nB={}2\n", from.display(), from.display()),
+        )
+        .unwrap();
+
+        let report = relocate(&from, &to).await.unwrap();
+        assert_eq!(
+            report.scripts_rewritten,
+            vec![to.join("bin/x64/cl"), to.join("setup.sh")]
+        );
+        let setup = std::fs::read_to_string(to.join("setup.sh")).unwrap();
+        assert_eq!(
+            setup,
+            format!("A={}\nB={}2\n", to.display(), from.display())
+        );
+    }
+
+    #[test]
+    fn test_stale_profile_warnings() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let from = temp_dir.path().join("old");
+        let profiles = temp_dir.path().join("profiles");
+        std::fs::create_dir_all(&profiles).unwrap();
+        std::fs::write(
+            profile_path(&profiles, "14.44-x64"),
+            format!("VCINSTALLDIR={}/VC\n", from.display()),
+        )
+        .unwrap();
+        std::fs::write(
+            profile_path(&profiles, "other"),
+            format!("VCINSTALLDIR={}2/VC\n", from.display()),
+        )
+        .unwrap();
+
+        let warnings = stale_profile_warnings(&profiles, &from);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("14.44-x64"));
+        assert!(stale_profile_warnings(&temp_dir.path().join("missing"), &from).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_relocate_rejects_bad_targets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let from = temp_dir.path().join("old");
+        std::fs::create_dir_all(from.join("VC")).unwrap();
+        let busy = temp_dir.path().join("busy");
+        std::fs::create_dir_all(&busy).unwrap();
+        std::fs::write(busy.join("file"), "x").unwrap();
+
+        for to in [busy, from.join("nested")] {
+            let result = relocate(&from, &to).await;
+            assert!(matches!(result, Err(MsvcKitError::InstallPath(_))));
+        }
+        let result = relocate(temp_dir.path(), &temp_dir.path().join("other")).await;
+        assert!(matches!(result, Err(MsvcKitError::InstallPath(_))));
+        assert!(from.join("VC").is_dir());
+    }
+}
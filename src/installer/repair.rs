@@ -0,0 +1,344 @@
+//! Reinstalling one component of an existing install
+//!
+//! When part of an install is deleted or damaged (say the UCRT headers),
+//! [`repair_component`] downloads only the packages of that component for
+//! the installed versions, drops their receipts so they are extracted again
+//! over the damaged files, and checks the receipts afterwards.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::receipt::PackageReceipt;
+use super::{extract_and_finalize_msvc_with_report, extract_and_finalize_sdk_with_report};
+use crate::downloader::{download_msvc, download_sdk, DownloadOptions};
+use crate::error::{MsvcKitError, Result};
+use crate::version::{list_installed_msvc, list_installed_sdk};
+
+/// A slice of an install that [`repair_component`] can reinstall
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepairComponent {
+    /// MSVC CRT packages and the Windows SDK Universal CRT
+    Crt,
+    /// Compiler and linker tools
+    Tools,
+    /// Active Template Library
+    Atl,
+    /// Microsoft Foundation Classes
+    Mfc,
+    /// Every MSVC package
+    Msvc,
+    /// Every Windows SDK installer
+    Sdk,
+}
+
+impl RepairComponent {
+    /// Every component, in repair order
+    pub const ALL: [RepairComponent; 6] = [
+        RepairComponent::Crt,
+        RepairComponent::Tools,
+        RepairComponent::Atl,
+        RepairComponent::Mfc,
+        RepairComponent::Msvc,
+        RepairComponent::Sdk,
+    ];
+
+    /// MSVC package ID patterns, `None` if no MSVC package is involved
+    /// (empty = all packages)
+    fn msvc_patterns(self) -> Option<&'static [&'static str]> {
+        match self {
+            RepairComponent::Crt => Some(&[".crt."]),
+            RepairComponent::Tools => Some(&[".tools."]),
+            RepairComponent::Atl => Some(&[".atl."]),
+            RepairComponent::Mfc => Some(&[".mfc."]),
+            RepairComponent::Msvc => Some(&[]),
+            RepairComponent::Sdk => None,
+        }
+    }
+
+    /// Windows SDK installer patterns, `None` if no SDK installer is involved
+    /// (empty = all installers)
+    fn sdk_patterns(self) -> Option<&'static [&'static str]> {
+        match self {
+            RepairComponent::Crt => Some(&["universal crt"]),
+            RepairComponent::Sdk => Some(&[]),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RepairComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RepairComponent::Crt => write!(f, "crt"),
+            RepairComponent::Tools => write!(f, "tools"),
+            RepairComponent::Atl => write!(f, "atl"),
+            RepairComponent::Mfc => write!(f, "mfc"),
+            RepairComponent::Msvc => write!(f, "msvc"),
+            RepairComponent::Sdk => write!(f, "sdk"),
+        }
+    }
+}
+
+impl std::str::FromStr for RepairComponent {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "crt" | "ucrt" => Ok(RepairComponent::Crt),
+            "tools" => Ok(RepairComponent::Tools),
+            "atl" => Ok(RepairComponent::Atl),
+            "mfc" => Ok(RepairComponent::Mfc),
+            "msvc" => Ok(RepairComponent::Msvc),
+            "sdk" => Ok(RepairComponent::Sdk),
+            _ => Err(format!(
+                "Unknown component: {} (expected crt, tools, atl, mfc, msvc or sdk)",
+                s
+            )),
+        }
+    }
+}
+
+/// Result of [`repair_component`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentRepairReport {
+    /// The repaired component
+    pub component: RepairComponent,
+    /// Installed MSVC version the packages were taken from
+    pub msvc_version: Option<String>,
+    /// Installed Windows SDK version the installers were taken from
+    pub sdk_version: Option<String>,
+    /// Payloads extracted again
+    pub payloads: Vec<String>,
+    /// Files listed in the new receipts that are still missing
+    pub missing: Vec<PathBuf>,
+}
+
+impl ComponentRepairReport {
+    /// Whether every reinstalled file is on disk
+    pub fn is_repaired(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Reinstall `component` into `options.target_dir`
+///
+/// MSVC and SDK versions default to the newest installed ones; set
+/// `options.msvc_version` / `options.sdk_version` to repair another. Package
+/// selection (`only_patterns`, `sdk_installer_patterns`) is replaced by the
+/// component's packages; the other options (architecture, cache, offline
+/// mode, ...) are used as given.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{repair_component, RepairComponent};
+/// use msvc_kit::DownloadOptions;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let options = DownloadOptions::builder().target_dir("C:/msvc-kit").build();
+///     let report = repair_component(RepairComponent::Crt, &options).await?;
+///     println!("{} payloads, repaired: {}", report.payloads.len(), report.is_repaired());
+///     Ok(())
+/// }
+/// ```
+pub async fn repair_component(
+    component: RepairComponent,
+    options: &DownloadOptions,
+) -> Result<ComponentRepairReport> {
+    let install_dir = options.target_dir.clone();
+    let mut report = ComponentRepairReport {
+        component,
+        msvc_version: None,
+        sdk_version: None,
+        payloads: Vec::new(),
+        missing: Vec::new(),
+    };
+
+    if let Some(patterns) = component.msvc_patterns() {
+        let version = match &options.msvc_version {
+            Some(version) => version.clone(),
+            None => installed_msvc_prefix(&install_dir)?,
+        };
+        let mut opts = options.clone();
+        opts.msvc_version = Some(version);
+        opts.only_patterns = patterns.iter().map(|p| p.to_string()).collect();
+        opts.allow_broken_selection = true;
+
+        let mut info = download_msvc(&opts).await?;
+        let names = force_reextract(&install_dir, &info.downloaded_files).await?;
+        extract_and_finalize_msvc_with_report(&mut info, false).await?;
+        report
+            .missing
+            .extend(missing_files(&install_dir, &names).await);
+        report.payloads.extend(names);
+        report.msvc_version = Some(info.version);
+    }
+
+    if let Some(patterns) = component.sdk_patterns() {
+        let version = match &options.sdk_version {
+            Some(version) => version.clone(),
+            None => list_installed_sdk(&install_dir)
+                .into_iter()
+                .next()
+                .map(|v| v.version)
+                .ok_or_else(|| {
+                    MsvcKitError::ComponentNotFound(format!(
+                        "No Windows SDK installed in {}",
+                        install_dir.display()
+                    ))
+                })?,
+        };
+        let mut opts = options.clone();
+        opts.sdk_version = Some(version);
+        opts.sdk_installer_patterns = patterns.iter().map(|p| p.to_string()).collect();
+
+        let info = download_sdk(&opts).await?;
+        let names = force_reextract(&install_dir, &info.downloaded_files).await?;
+        extract_and_finalize_sdk_with_report(&info, false).await?;
+        report
+            .missing
+            .extend(missing_files(&install_dir, &names).await);
+        report.payloads.extend(names);
+        report.sdk_version = Some(info.version);
+    }
+
+    Ok(report)
+}
+
+/// `major.minor` prefix of the newest installed MSVC toolset
+///
+/// The install directory holds the full toolset version; the manifest uses
+/// the prefix.
+pub(super) fn installed_msvc_prefix(install_dir: &Path) -> Result<String> {
+    let installed = list_installed_msvc(install_dir);
+    let version = installed.first().ok_or_else(|| {
+        MsvcKitError::ComponentNotFound(format!(
+            "No MSVC toolset installed in {}",
+            install_dir.display()
+        ))
+    })?;
+    let prefix: Vec<&str> = version.version.splitn(3, '.').take(2).collect();
+    Ok(prefix.join("."))
+}
+
+/// Drop the receipts of `files` so extraction does not skip them
+async fn force_reextract(install_dir: &Path, files: &[PathBuf]) -> Result<Vec<String>> {
+    let mut names = Vec::with_capacity(files.len());
+    for file in files {
+        let Some(name) = file.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let receipt = PackageReceipt::path_for(install_dir, name);
+        if receipt.exists() {
+            tokio::fs::remove_file(&receipt).await?;
+        }
+        names.push(name.to_string());
+    }
+    Ok(names)
+}
+
+/// Files listed in the receipts of `names` that are not on disk
+///
+/// A payload without a receipt after extraction counts as missing.
+pub(crate) async fn missing_files(install_dir: &Path, names: &[String]) -> Vec<PathBuf> {
+    let mut missing = Vec::new();
+    for name in names {
+        let path = PackageReceipt::path_for(install_dir, name);
+        match PackageReceipt::read(&path).await {
+            Some(receipt) => missing.extend(
+                receipt
+                    .files
+                    .iter()
+                    .map(|f| PathBuf::from(&f.path))
+                    .filter(|p| !install_dir.join(p).exists()),
+            ),
+            None => missing.push(path),
+        }
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::installer::ReceiptEntry;
+
+    #[test]
+    fn test_component_parse_and_display() {
+        for component in RepairComponent::ALL {
+            assert_eq!(
+                component.to_string().parse::<RepairComponent>(),
+                Ok(component)
+            );
+        }
+        assert_eq!("UCRT".parse::<RepairComponent>(), Ok(RepairComponent::Crt));
+        assert!("spectre".parse::<RepairComponent>().is_err());
+    }
+
+    #[test]
+    fn test_component_selection() {
+        assert_eq!(RepairComponent::Crt.msvc_patterns(), Some(&[".crt."][..]));
+        assert_eq!(
+            RepairComponent::Crt.sdk_patterns(),
+            Some(&["universal crt"][..])
+        );
+        assert!(RepairComponent::Tools.sdk_patterns().is_none());
+        assert!(RepairComponent::Sdk.msvc_patterns().is_none());
+        assert_eq!(RepairComponent::Msvc.msvc_patterns(), Some(&[][..]));
+    }
+
+    #[tokio::test]
+    async fn test_force_reextract_and_verify() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("Windows Kits/10/Include/10.0.26100.0/ucrt")).unwrap();
+        std::fs::write(
+            root.join("Windows Kits/10/Include/10.0.26100.0/ucrt/stdio.h"),
+            "",
+        )
+        .unwrap();
+
+        let receipt = PackageReceipt {
+            package: "ucrt.msi".to_string(),
+            files: vec![
+                ReceiptEntry {
+                    path: "Windows Kits/10/Include/10.0.26100.0/ucrt/stdio.h".to_string(),
+                    sha256: "00".to_string(),
+                    size: 0,
+                },
+                ReceiptEntry {
+                    path: "Windows Kits/10/Include/10.0.26100.0/ucrt/corecrt.h".to_string(),
+                    sha256: "00".to_string(),
+                    size: 0,
+                },
+            ],
+        };
+        std::fs::create_dir_all(root.join(crate::installer::RECEIPT_DIR_NAME)).unwrap();
+        let receipt_path = PackageReceipt::path_for(root, "ucrt.msi");
+        receipt.write(&receipt_path).await.unwrap();
+
+        let names = vec!["ucrt.msi".to_string()];
+        assert_eq!(
+            missing_files(root, &names).await,
+            vec![PathBuf::from(
+                "Windows Kits/10/Include/10.0.26100.0/ucrt/corecrt.h"
+            )]
+        );
+
+        let downloads = [root.join("downloads/sdk/26100_x64/Installers/ucrt.msi")];
+        assert_eq!(force_reextract(root, &downloads).await.unwrap(), names);
+        assert!(!receipt_path.exists());
+        assert_eq!(missing_files(root, &names).await, vec![receipt_path]);
+    }
+
+    #[test]
+    fn test_installed_msvc_prefix() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(installed_msvc_prefix(temp_dir.path()).is_err());
+        std::fs::create_dir_all(temp_dir.path().join("VC/Tools/MSVC/14.44.34823")).unwrap();
+        assert_eq!(installed_msvc_prefix(temp_dir.path()).unwrap(), "14.44");
+    }
+}
@@ -0,0 +1,433 @@
+//! Installing the toolchains listed in a `toolchains.toml`
+//!
+//! A toolchains file declares every MSVC / Windows SDK pair an install
+//! directory should hold:
+//!
+//! ```toml
+//! prune = true
+//!
+//! [[toolchain]]
+//! name = "prod"
+//! msvc = "14.38"
+//! sdk = "10.0.22621.0"
+//!
+//! [[toolchain]]
+//! name = "next"
+//! msvc = "14.44"
+//! sdk = "26100"
+//! arch = "arm64"
+//! ```
+//!
+//! [`sync_toolchains`] installs the missing ones and, with pruning enabled,
+//! removes installed versions no toolchain refers to. Every missing package
+//! is downloaded before anything is extracted, and nothing is removed unless
+//! all toolchains are installed, so a failed download leaves the directory
+//! as it was.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    clean, extract_and_finalize_msvc, extract_and_finalize_sdk, CleanOptions, InstallInfo,
+};
+use crate::downloader::{download_msvc, download_sdk, DownloadOptions};
+use crate::error::{MsvcKitError, Result};
+use crate::version::{list_installed_msvc, list_installed_sdk, Architecture};
+
+/// Default name of a toolchains file
+pub const TOOLCHAINS_FILE: &str = "toolchains.toml";
+
+/// One toolchain in a [`ToolchainsFile`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolchainSpec {
+    /// Name used in reports, e.g. `prod`
+    pub name: String,
+    /// MSVC version or prefix, e.g. `14.38`
+    pub msvc: String,
+    /// Windows SDK version or build number, e.g. `10.0.22621.0` or `22621`
+    pub sdk: String,
+    /// Target architecture (default: the architecture of the download options)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<Architecture>,
+}
+
+/// Contents of a `toolchains.toml`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolchainsFile {
+    /// Remove installed versions that no toolchain refers to
+    #[serde(default)]
+    pub prune: bool,
+    /// Required toolchains
+    #[serde(default, rename = "toolchain")]
+    pub toolchains: Vec<ToolchainSpec>,
+}
+
+impl ToolchainsFile {
+    /// Parse a toolchains file
+    ///
+    /// Fails with [`MsvcKitError::Config`] if no toolchain is listed or a
+    /// name is used twice.
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: Self = toml::from_str(content)?;
+        if file.toolchains.is_empty() {
+            return Err(MsvcKitError::Config(
+                "No [[toolchain]] entries in toolchains file".to_string(),
+            ));
+        }
+        for (i, spec) in file.toolchains.iter().enumerate() {
+            if file.toolchains[..i].iter().any(|s| s.name == spec.name) {
+                return Err(MsvcKitError::Config(format!(
+                    "Toolchain '{}' is listed twice",
+                    spec.name
+                )));
+            }
+        }
+        Ok(file)
+    }
+
+    /// Read and parse a toolchains file
+    pub async fn load(path: &Path) -> Result<Self> {
+        let content = tokio::fs::read_to_string(path).await.map_err(|e| {
+            MsvcKitError::Config(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&content)
+    }
+}
+
+/// What [`sync_toolchains`] did with a toolchain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SyncAction {
+    /// Already installed
+    Present,
+    /// Installed by this run
+    Installed,
+    /// Missing; would be installed (dry run)
+    WouldInstall,
+}
+
+/// Status of one toolchain after [`sync_toolchains`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolchainStatus {
+    /// Toolchain name
+    pub name: String,
+    /// Target architecture
+    pub arch: Architecture,
+    /// What was done
+    pub action: SyncAction,
+    /// Full MSVC version on disk (`None` if not installed)
+    pub msvc_version: Option<String>,
+    /// Full Windows SDK version on disk (`None` if not installed)
+    pub sdk_version: Option<String>,
+}
+
+/// Options for [`sync_toolchains`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SyncOptions {
+    /// Remove unlisted versions even if the file does not ask for it
+    pub prune: bool,
+    /// Only report what would be installed and removed
+    pub dry_run: bool,
+}
+
+/// Result of [`sync_toolchains`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SyncReport {
+    /// Status of each toolchain, in file order
+    pub toolchains: Vec<ToolchainStatus>,
+    /// Removed (or, in dry-run mode, removable) versions, e.g. `MSVC 14.38.33130`
+    pub removed: Vec<String>,
+    /// Bytes freed (or that would be freed) by pruning
+    pub bytes_freed: u64,
+    /// Whether this was a dry run
+    pub dry_run: bool,
+}
+
+impl SyncReport {
+    /// Whether the run installed or removed anything (or would have)
+    pub fn changed(&self) -> bool {
+        !self.removed.is_empty()
+            || self
+                .toolchains
+                .iter()
+                .any(|t| t.action != SyncAction::Present)
+    }
+}
+
+/// Whether an installed MSVC version is selected by `spec` (exact or prefix)
+fn msvc_matches(installed: &str, spec: &str) -> bool {
+    installed == spec || installed.starts_with(&format!("{}.", spec))
+}
+
+/// Whether an installed SDK version is selected by `spec` (exact or build number)
+fn sdk_matches(installed: &str, spec: &str) -> bool {
+    installed == spec || installed.split('.').nth(2) == Some(spec)
+}
+
+/// Newest installed MSVC version matching `spec` that has libraries for `arch`
+fn installed_msvc(install_dir: &Path, spec: &str, arch: Architecture) -> Option<String> {
+    list_installed_msvc(install_dir)
+        .into_iter()
+        .map(|v| v.version)
+        .find(|v| {
+            msvc_matches(v, spec)
+                && install_dir
+                    .join("VC/Tools/MSVC")
+                    .join(v)
+                    .join("lib")
+                    .join(arch.msvc_target_dir())
+                    .is_dir()
+        })
+}
+
+/// Newest installed SDK version matching `spec` that has libraries for `arch`
+fn installed_sdk(install_dir: &Path, spec: &str, arch: Architecture) -> Option<String> {
+    list_installed_sdk(install_dir)
+        .into_iter()
+        .map(|v| v.version)
+        .find(|v| {
+            sdk_matches(v, spec)
+                && install_dir
+                    .join("Windows Kits/10/Lib")
+                    .join(v)
+                    .join("um")
+                    .join(arch.msvc_target_dir())
+                    .is_dir()
+        })
+}
+
+/// Install the toolchains of `file` into `options.target_dir`
+///
+/// Each toolchain's MSVC and SDK version and architecture replace those of
+/// `options`; the other options (cache, verification, offline mode, ...)
+/// are used as given.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::installer::{sync_toolchains, SyncOptions, ToolchainsFile};
+/// use msvc_kit::DownloadOptions;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let file = ToolchainsFile::load("toolchains.toml".as_ref()).await?;
+///     let options = DownloadOptions::builder().target_dir("C:/msvc-kit").build();
+///     let report = sync_toolchains(&file, &options, &SyncOptions::default()).await?;
+///     println!("{}", serde_json::to_string_pretty(&report)?);
+///     Ok(())
+/// }
+/// ```
+pub async fn sync_toolchains(
+    file: &ToolchainsFile,
+    options: &DownloadOptions,
+    sync: &SyncOptions,
+) -> Result<SyncReport> {
+    let install_dir = options.target_dir.clone();
+    let mut report = SyncReport {
+        dry_run: sync.dry_run,
+        ..Default::default()
+    };
+
+    // Download everything missing before extracting anything
+    let mut pending: Vec<(usize, Option<InstallInfo>, Option<InstallInfo>)> = Vec::new();
+    for (i, spec) in file.toolchains.iter().enumerate() {
+        let arch = spec.arch.unwrap_or(options.arch);
+        let msvc_version = installed_msvc(&install_dir, &spec.msvc, arch);
+        let sdk_version = installed_sdk(&install_dir, &spec.sdk, arch);
+        let action = match (&msvc_version, &sdk_version) {
+            (Some(_), Some(_)) => SyncAction::Present,
+            _ if sync.dry_run => SyncAction::WouldInstall,
+            _ => SyncAction::Installed,
+        };
+
+        if action == SyncAction::Installed {
+            let mut opts = options.clone();
+            opts.arch = arch;
+            opts.msvc_version = Some(spec.msvc.clone());
+            opts.sdk_version = Some(spec.sdk.clone());
+            let msvc = match &msvc_version {
+                Some(_) => None,
+                None => Some(download_msvc(&opts).await?),
+            };
+            let sdk = match &sdk_version {
+                Some(_) => None,
+                None => Some(download_sdk(&opts).await?),
+            };
+            pending.push((i, msvc, sdk));
+        }
+
+        report.toolchains.push(ToolchainStatus {
+            name: spec.name.clone(),
+            arch,
+            action,
+            msvc_version,
+            sdk_version,
+        });
+    }
+
+    for (i, msvc, sdk) in pending {
+        let status = &mut report.toolchains[i];
+        if let Some(mut info) = msvc {
+            extract_and_finalize_msvc(&mut info).await?;
+            status.msvc_version = Some(info.version);
+        }
+        if let Some(info) = sdk {
+            extract_and_finalize_sdk(&info).await?;
+            status.sdk_version = Some(info.version);
+        }
+    }
+
+    if file.prune || sync.prune {
+        prune_unlisted(&install_dir, file, sync.dry_run, &mut report).await?;
+    }
+    Ok(report)
+}
+
+/// Remove installed MSVC and SDK versions that no toolchain refers to
+async fn prune_unlisted(
+    install_dir: &Path,
+    file: &ToolchainsFile,
+    dry_run: bool,
+    report: &mut SyncReport,
+) -> Result<()> {
+    let mut options = Vec::new();
+    for version in list_installed_msvc(install_dir) {
+        if !file
+            .toolchains
+            .iter()
+            .any(|t| msvc_matches(&version.version, &t.msvc))
+        {
+            report.removed.push(format!("MSVC {}", version.version));
+            options.push(CleanOptions {
+                msvc_version: Some(version.version),
+                dry_run,
+                ..Default::default()
+            });
+        }
+    }
+    for version in list_installed_sdk(install_dir) {
+        if !file
+            .toolchains
+            .iter()
+            .any(|t| sdk_matches(&version.version, &t.sdk))
+        {
+            report
+                .removed
+                .push(format!("Windows SDK {}", version.version));
+            options.push(CleanOptions {
+                sdk_version: Some(version.version),
+                dry_run,
+                ..Default::default()
+            });
+        }
+    }
+
+    for options in &options {
+        report.bytes_freed += clean(install_dir, options).await?.bytes_freed;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+prune = true
+
+[[toolchain]]
+name = "prod"
+msvc = "14.38"
+sdk = "10.0.22621.0"
+
+[[toolchain]]
+name = "next"
+msvc = "14.44"
+sdk = "26100"
+arch = "arm64"
+"#;
+
+    fn install(root: &Path, msvc: &str, sdk: &str, arch: &str) {
+        std::fs::create_dir_all(root.join("VC/Tools/MSVC").join(msvc).join("lib").join(arch))
+            .unwrap();
+        std::fs::create_dir_all(root.join("Windows Kits/10/Include").join(sdk)).unwrap();
+        std::fs::create_dir_all(
+            root.join("Windows Kits/10/Lib")
+                .join(sdk)
+                .join("um")
+                .join(arch),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_parse_toolchains_file() {
+        let file = ToolchainsFile::parse(EXAMPLE).unwrap();
+        assert!(file.prune);
+        assert_eq!(file.toolchains.len(), 2);
+        assert_eq!(file.toolchains[0].arch, None);
+        assert_eq!(file.toolchains[1].arch, Some(Architecture::Arm64));
+
+        assert!(ToolchainsFile::parse("prune = true").is_err());
+        let duplicate = r#"
+[[toolchain]]
+name = "a"
+msvc = "14.44"
+sdk = "26100"
+
+[[toolchain]]
+name = "a"
+msvc = "14.43"
+sdk = "26100"
+"#;
+        assert!(ToolchainsFile::parse(duplicate).is_err());
+    }
+
+    #[test]
+    fn test_version_matching() {
+        assert!(msvc_matches("14.38.33130", "14.38"));
+        assert!(msvc_matches("14.38.33130", "14.38.33130"));
+        assert!(!msvc_matches("14.38.33130", "14.3"));
+        assert!(sdk_matches("10.0.22621.0", "22621"));
+        assert!(sdk_matches("10.0.22621.0", "10.0.22621.0"));
+        assert!(!sdk_matches("10.0.22621.0", "2262"));
+    }
+
+    #[tokio::test]
+    async fn test_sync_dry_run() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        install(root, "14.38.33130", "10.0.22621.0", "x64");
+        install(root, "14.40.33807", "10.0.22000.0", "x64");
+        // 14.44 is installed, but not for arm64
+        install(root, "14.44.34823", "10.0.26100.0", "x64");
+
+        let file = ToolchainsFile::parse(EXAMPLE).unwrap();
+        let options = DownloadOptions::builder()
+            .target_dir(root)
+            .arch(Architecture::X64)
+            .build();
+        let sync = SyncOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = sync_toolchains(&file, &options, &sync).await.unwrap();
+
+        assert!(report.changed());
+        assert_eq!(report.toolchains[0].action, SyncAction::Present);
+        assert_eq!(
+            report.toolchains[0].msvc_version.as_deref(),
+            Some("14.38.33130")
+        );
+        assert_eq!(report.toolchains[1].action, SyncAction::WouldInstall);
+        assert_eq!(report.toolchains[1].arch, Architecture::Arm64);
+        assert_eq!(report.toolchains[1].msvc_version, None);
+        assert_eq!(
+            report.removed,
+            vec!["MSVC 14.40.33807", "Windows SDK 10.0.22000.0"]
+        );
+        // Nothing was removed
+        assert!(root.join("VC/Tools/MSVC/14.40.33807").exists());
+    }
+}
@@ -84,34 +84,62 @@
 pub mod bundle;
 pub mod config;
 pub mod constants;
+pub mod diagnostics;
 pub mod downloader;
 pub mod env;
 pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod installer;
+pub mod license;
+pub mod preflight;
+pub mod prelude;
+#[cfg(feature = "python")]
+mod python;
 pub mod query;
+pub mod sbom;
 pub mod scripts;
+#[cfg(feature = "serve")]
+pub mod serve;
 pub mod version;
+pub mod warning;
+pub mod winpath;
 
 // Re-export main types and functions
 pub use config::{load_config, save_config, MsvcKitConfig};
 pub use downloader::{
-    download_all, download_msvc, download_sdk, list_available_versions, AvailableVersions,
-    BoxedCacheManager, BoxedProgressHandler, CacheManager, ComponentDownloader, ComponentType,
+    download_all, download_msvc, download_msvc_stream, download_sdk, download_sdk_stream,
+    list_available_versions, AppPlatform, AvailableVersions, BoxedCacheManager,
+    BoxedProgressHandler, CacheManager, ComponentDownloader, ComponentType, DownloadEvent,
     DownloadOptions, DownloadOptionsBuilder, FileSystemCacheManager, MsvcComponent,
-    ProgressHandler,
+    MsvcVersionSelector, ProgressHandler, ReleaseChannel, ResolutionReport, VerifyMode,
+};
+#[allow(deprecated)]
+pub use env::setup_environment;
+pub use env::{
+    get_env_changes, get_env_vars, setup_environment_with_arch, EnvChange, MsvcEnvironment,
+    ToolKind, ToolPaths,
+};
+pub use error::{MsvcKitError, OptionsError, Result};
+pub use installer::{
+    extract_and_finalize_msvc, extract_and_finalize_msvc_with_report, extract_and_finalize_sdk,
+    extract_and_finalize_sdk_with_report, BoxedPostInstallHook, CleanupPolicy, ExtractWarning,
+    FileConflict, InstallInfo, InstallReport, PostInstallHook,
 };
-pub use env::{get_env_vars, setup_environment, MsvcEnvironment, ToolPaths};
-pub use error::{MsvcKitError, Result};
-pub use installer::{extract_and_finalize_msvc, extract_and_finalize_sdk, InstallInfo};
 pub use query::{
     query_installation, ComponentInfo, QueryComponent, QueryOptions, QueryOptionsBuilder,
     QueryProperty, QueryResult,
 };
 pub use scripts::{
     generate_absolute_scripts, generate_portable_scripts, generate_script, save_scripts,
-    GeneratedScripts, ScriptContext, ShellType,
+    scripts_for_install, GeneratedScripts, Locale, ScriptContext, ShellType,
+    POWERSHELL_MODULE_FILE,
 };
 pub use version::{Architecture, MsvcVersion, SdkVersion};
+pub use warning::{Warning, WarningCode};
 
 // Re-export bundle types
-pub use bundle::{create_bundle, discover_bundle, BundleLayout, BundleOptions, BundleResult};
+pub use bundle::{
+    create_bundle, create_multi_arch_bundles, discover_bundle, BundleLayout, BundleOptions,
+    BundleResult, BundleStats,
+};
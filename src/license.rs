@@ -0,0 +1,106 @@
+//! Microsoft license acceptance
+//!
+//! The MSVC compiler and Windows SDK are subject to the Visual Studio license
+//! terms. When they are accepted, a `license-acceptance.json` record is
+//! written next to the installed or bundled toolchain so audits can tell who
+//! accepted the terms, when, and with which msvc-kit version.
+//!
+//! Acceptance can come from `--accept-license`, the `license_accepted` config
+//! key or the `MSVC_KIT_ACCEPT_LICENSE` environment variable. Wrappers that
+//! show their own license prompt call [`accept_license`] directly.
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::MsvcKitConfig;
+use crate::error::Result;
+
+/// Visual Studio license terms
+pub const LICENSE_URL: &str = "https://visualstudio.microsoft.com/license-terms/";
+
+/// File name of the acceptance record
+pub const LICENSE_ACCEPTANCE_FILE: &str = "license-acceptance.json";
+
+/// Record of the license terms being accepted
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LicenseAcceptance {
+    /// When the terms were accepted
+    pub accepted_at: DateTime<Utc>,
+    /// User that accepted the terms
+    pub user: String,
+    /// msvc-kit version that recorded the acceptance
+    pub msvc_kit_version: String,
+    /// License terms that were accepted
+    pub license_url: String,
+}
+
+impl LicenseAcceptance {
+    /// Acceptance by the current user, now
+    pub fn now() -> Self {
+        let user = std::env::var("USERNAME")
+            .or_else(|_| std::env::var("USER"))
+            .unwrap_or_else(|_| "unknown".to_string());
+        Self {
+            accepted_at: Utc::now(),
+            user,
+            msvc_kit_version: env!("CARGO_PKG_VERSION").to_string(),
+            license_url: LICENSE_URL.to_string(),
+        }
+    }
+}
+
+/// Whether the license was accepted through the config or environment
+///
+/// `MSVC_KIT_ACCEPT_LICENSE` accepts `1`, `true` or `yes`.
+pub fn is_license_accepted(config: &MsvcKitConfig) -> bool {
+    config.license_accepted
+        || std::env::var("MSVC_KIT_ACCEPT_LICENSE")
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+}
+
+/// Accept the license terms for the toolchain in `dir`
+///
+/// Writes `license-acceptance.json` into `dir` and returns the record.
+pub fn accept_license(dir: &Path) -> Result<LicenseAcceptance> {
+    let acceptance = LicenseAcceptance::now();
+    std::fs::create_dir_all(dir)?;
+    let content = serde_json::to_string_pretty(&acceptance)?;
+    std::fs::write(dir.join(LICENSE_ACCEPTANCE_FILE), content)?;
+    Ok(acceptance)
+}
+
+/// Read the acceptance record of `dir`, if any
+pub fn read_license_acceptance(dir: &Path) -> Option<LicenseAcceptance> {
+    let content = std::fs::read_to_string(dir.join(LICENSE_ACCEPTANCE_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_license_roundtrip() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        assert!(read_license_acceptance(temp_dir.path()).is_none());
+
+        let acceptance = accept_license(temp_dir.path()).unwrap();
+        assert_eq!(acceptance.license_url, LICENSE_URL);
+        assert_eq!(acceptance.msvc_kit_version, env!("CARGO_PKG_VERSION"));
+
+        let read = read_license_acceptance(temp_dir.path()).unwrap();
+        assert_eq!(read, acceptance);
+    }
+
+    #[test]
+    fn test_is_license_accepted_from_config() {
+        let config = MsvcKitConfig {
+            license_accepted: true,
+            ..Default::default()
+        };
+        assert!(is_license_accepted(&config));
+    }
+}
@@ -0,0 +1,383 @@
+//! First-run checks before downloading a toolchain
+//!
+//! Downloads fail late and far from the cause when the CDN is blocked, the
+//! disk fills up, or paths exceed `MAX_PATH` during extraction. [`run`]
+//! checks these prerequisites up front and returns a [`PreflightReport`];
+//! `msvc-kit init` prints it before writing the initial configuration.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::constants::VS_CHANNEL_URL;
+use crate::downloader::HttpClientConfig;
+
+/// Free space recommended for an x64 MSVC + SDK install with its downloads
+pub const RECOMMENDED_FREE_SPACE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Length of the longest path below the install root, with some headroom
+///
+/// E.g. `VC/Tools/MSVC/<version>/atlmfc/include/...` and the SDK's
+/// `Windows Kits/10/Include/<version>/winrt/wrl/...` headers.
+pub(crate) const LONGEST_RELATIVE_PATH: usize = 170;
+
+/// Windows `MAX_PATH`
+pub(crate) const MAX_PATH: usize = 260;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    /// The prerequisite is met
+    Pass,
+    /// Downloads may work, but something needs attention
+    Warn,
+    /// Downloads will fail
+    Fail,
+    /// Not checked
+    Skipped,
+}
+
+impl std::fmt::Display for CheckStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStatus::Pass => write!(f, "pass"),
+            CheckStatus::Warn => write!(f, "warn"),
+            CheckStatus::Fail => write!(f, "fail"),
+            CheckStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// Result of one prerequisite check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    /// Short name, e.g. `network` or `disk-space`
+    pub name: String,
+    /// Outcome
+    pub status: CheckStatus,
+    /// What was found, or how to fix it
+    pub detail: String,
+}
+
+impl PreflightCheck {
+    fn new(name: &str, status: CheckStatus, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// All checks run by [`run`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreflightReport {
+    /// Checks in the order they ran
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// Whether no check failed
+    pub fn is_ready(&self) -> bool {
+        self.checks.iter().all(|c| c.status != CheckStatus::Fail)
+    }
+}
+
+/// What [`run`] checks
+#[derive(Debug, Clone)]
+pub struct PreflightOptions {
+    /// Install directory
+    pub install_dir: PathBuf,
+    /// Cache directory, if different from the install directory
+    pub cache_dir: Option<PathBuf>,
+    /// Check connectivity to the Visual Studio CDN
+    pub check_network: bool,
+    /// Free space below which the disk check fails, in bytes
+    pub required_space: u64,
+}
+
+impl Default for PreflightOptions {
+    fn default() -> Self {
+        Self {
+            install_dir: PathBuf::new(),
+            cache_dir: None,
+            check_network: true,
+            required_space: RECOMMENDED_FREE_SPACE,
+        }
+    }
+}
+
+/// Check the prerequisites for downloading into `options.install_dir`
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::preflight::{self, PreflightOptions};
+///
+/// # async fn run() {
+/// let report = preflight::run(&PreflightOptions {
+///     install_dir: "C:/msvc-kit".into(),
+///     ..Default::default()
+/// })
+/// .await;
+/// for check in &report.checks {
+///     println!("{:>8} {}: {}", check.status, check.name, check.detail);
+/// }
+/// # }
+/// ```
+pub async fn run(options: &PreflightOptions) -> PreflightReport {
+    let mut checks = Vec::new();
+
+    checks.push(if options.check_network {
+        check_network(VS_CHANNEL_URL).await
+    } else {
+        PreflightCheck::new("network", CheckStatus::Skipped, "not requested")
+    });
+
+    checks.push(check_writable("install-dir", &options.install_dir));
+    if let Some(cache_dir) = &options.cache_dir {
+        checks.push(check_writable("cache-dir", cache_dir));
+    }
+
+    checks.push(check_disk_space(
+        &options.install_dir,
+        options.required_space,
+    ));
+    checks.push(check_long_paths(&options.install_dir));
+
+    PreflightReport { checks }
+}
+
+/// Whether the channel manifest can be reached
+async fn check_network(url: &str) -> PreflightCheck {
+    let client = HttpClientConfig::default()
+        .connect_timeout(Duration::from_secs(10))
+        .timeout(Duration::from_secs(30))
+        .build();
+    match client.get(url).send().await {
+        Ok(response) if response.status().is_success() => PreflightCheck::new(
+            "network",
+            CheckStatus::Pass,
+            format!(
+                "reached {}",
+                response.url().host_str().unwrap_or(response.url().as_str())
+            ),
+        ),
+        Ok(response) => PreflightCheck::new(
+            "network",
+            CheckStatus::Fail,
+            format!("{} returned HTTP {}", response.url(), response.status()),
+        ),
+        Err(e) => PreflightCheck::new(
+            "network",
+            CheckStatus::Fail,
+            format!(
+                "cannot reach {}: {} (check proxy settings, or use --offline with a cache)",
+                url, e
+            ),
+        ),
+    }
+}
+
+/// Whether files can be created in `dir`, creating it if needed
+fn check_writable(name: &str, dir: &Path) -> PreflightCheck {
+    let probe = dir.join(".msvc-kit-write-test");
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+    match result {
+        Ok(()) => PreflightCheck::new(name, CheckStatus::Pass, dir.display().to_string()),
+        Err(e) => PreflightCheck::new(
+            name,
+            CheckStatus::Fail,
+            format!("cannot write to {}: {}", dir.display(), e),
+        ),
+    }
+}
+
+/// Whether the volume of `dir` has at least `required` bytes free
+fn check_disk_space(dir: &Path, required: u64) -> PreflightCheck {
+    let gib = |bytes: u64| bytes as f64 / (1024.0 * 1024.0 * 1024.0);
+    match free_space(dir) {
+        Some(free) if free >= required => PreflightCheck::new(
+            "disk-space",
+            CheckStatus::Pass,
+            format!("{:.1} GiB free", gib(free)),
+        ),
+        Some(free) => PreflightCheck::new(
+            "disk-space",
+            CheckStatus::Fail,
+            format!(
+                "{:.1} GiB free, {:.1} GiB recommended",
+                gib(free),
+                gib(required)
+            ),
+        ),
+        None => PreflightCheck::new(
+            "disk-space",
+            CheckStatus::Warn,
+            format!("could not determine free space of {}", dir.display()),
+        ),
+    }
+}
+
+/// Status of the long-path check for an install root of `root_len` characters
+fn long_path_status(long_paths_enabled: bool, root_len: usize) -> (CheckStatus, String) {
+    let longest = root_len + 1 + LONGEST_RELATIVE_PATH;
+    if long_paths_enabled {
+        (CheckStatus::Pass, "long paths are enabled".to_string())
+    } else if longest < MAX_PATH {
+        (
+            CheckStatus::Warn,
+            format!(
+                "long paths are disabled; paths stay below MAX_PATH ({} characters)",
+                longest
+            ),
+        )
+    } else {
+        (
+            CheckStatus::Fail,
+            format!(
+                "long paths are disabled and paths may reach {} characters; \
+                 use a shorter install dir or set LongPathsEnabled",
+                longest
+            ),
+        )
+    }
+}
+
+/// Whether extracted paths fit within `MAX_PATH`
+fn check_long_paths(install_dir: &Path) -> PreflightCheck {
+    if !cfg!(windows) {
+        return PreflightCheck::new("long-paths", CheckStatus::Pass, "no MAX_PATH limit");
+    }
+    let root = std::path::absolute(install_dir).unwrap_or_else(|_| install_dir.to_path_buf());
+    let (status, detail) = long_path_status(long_paths_enabled(), root.as_os_str().len());
+    PreflightCheck::new("long-paths", status, detail)
+}
+
+/// `HKLM\SYSTEM\CurrentControlSet\Control\FileSystem\LongPathsEnabled`
+#[cfg(windows)]
+pub(crate) fn long_paths_enabled() -> bool {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"SYSTEM\CurrentControlSet\Control\FileSystem")
+        .and_then(|key| key.get_value::<u32, _>("LongPathsEnabled"))
+        .is_ok_and(|value| value == 1)
+}
+
+#[cfg(not(windows))]
+pub(crate) fn long_paths_enabled() -> bool {
+    true
+}
+
+/// Nearest existing ancestor of `dir`
+fn existing_ancestor(dir: &Path) -> Option<&Path> {
+    dir.ancestors().find(|p| p.is_dir())
+}
+
+/// Bytes available to the current user on the volume of `dir`
+#[cfg(windows)]
+fn free_space(dir: &Path) -> Option<u64> {
+    use windows::core::HSTRING;
+    use windows::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let dir = existing_ancestor(dir)?;
+    let mut available = 0u64;
+    unsafe {
+        GetDiskFreeSpaceExW(
+            &HSTRING::from(dir.as_os_str()),
+            Some(&mut available),
+            None,
+            None,
+        )
+    }
+    .ok()?;
+    Some(available)
+}
+
+/// Bytes available to the current user on the volume of `dir`
+#[cfg(not(windows))]
+fn free_space(dir: &Path) -> Option<u64> {
+    let dir = existing_ancestor(dir)?;
+    let output = std::process::Command::new("df")
+        .arg("-Pk")
+        .arg(dir)
+        .output()
+        .ok()?;
+    parse_df_available(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Available bytes from POSIX `df -Pk` output
+#[cfg(not(windows))]
+fn parse_df_available(output: &str) -> Option<u64> {
+    let kib: u64 = output
+        .lines()
+        .nth(1)?
+        .split_whitespace()
+        .nth(3)?
+        .parse()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_path_status() {
+        assert_eq!(long_path_status(true, 300).0, CheckStatus::Pass);
+        assert_eq!(long_path_status(false, 20).0, CheckStatus::Warn);
+        assert_eq!(long_path_status(false, 120).0, CheckStatus::Fail);
+    }
+
+    #[tokio::test]
+    async fn test_run_without_network() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let install_dir = temp_dir.path().join("msvc-kit");
+        let report = run(&PreflightOptions {
+            install_dir: install_dir.clone(),
+            check_network: false,
+            required_space: 1,
+            ..Default::default()
+        })
+        .await;
+
+        let status = |name: &str| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.status)
+        };
+        assert_eq!(status("network"), Some(CheckStatus::Skipped));
+        assert_eq!(status("install-dir"), Some(CheckStatus::Pass));
+        assert_eq!(status("disk-space"), Some(CheckStatus::Pass));
+        assert!(report.is_ready());
+        assert!(install_dir.is_dir());
+        assert!(!install_dir.join(".msvc-kit-write-test").exists());
+
+        let report = run(&PreflightOptions {
+            install_dir,
+            check_network: false,
+            required_space: u64::MAX,
+            ..Default::default()
+        })
+        .await;
+        assert!(!report.is_ready());
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_parse_df_available() {
+        let output = "Filesystem 1024-blocks Used Available Capacity Mounted on\n\
+                      /dev/sda1 1000 400 600 40% /\n";
+        assert_eq!(parse_df_available(output), Some(600 * 1024));
+        assert_eq!(parse_df_available(""), None);
+    }
+}
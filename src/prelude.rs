@@ -0,0 +1,61 @@
+//! Stable public API
+//!
+//! Everything re-exported here follows semver: it only changes incompatibly
+//! in a new major (or, before 1.0, minor) release, which CI checks with
+//! `cargo semver-checks`. Embedders such as vx should import from the prelude:
+//!
+//! ```rust,no_run
+//! use msvc_kit::prelude::*;
+//!
+//! # async fn run() -> Result<()> {
+//! let options = DownloadOptions::builder()
+//!     .target_dir("C:/msvc-kit")
+//!     .arch(Architecture::X64)
+//!     .build();
+//! let mut msvc = download_msvc(&options).await?;
+//! extract_and_finalize_msvc(&mut msvc).await?;
+//!
+//! let result = query_installation(&QueryOptions::builder().install_dir("C:/msvc-kit").build())?;
+//! println!("cl.exe: {:?}", result.tool_path("cl"));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Low-level building blocks (manifest structs, `CommonDownloader` and the
+//! concrete downloaders) are not part of this surface. They are hidden from the
+//! docs unless the `unstable` feature is enabled and may change in any release:
+//!
+//! ```rust,compile_fail
+//! use msvc_kit::prelude::*;
+//!
+//! let _manifest: VsManifest = unimplemented!();
+//! ```
+//!
+//! ```rust,compile_fail
+//! use msvc_kit::prelude::*;
+//!
+//! let _downloader: CommonDownloader = unimplemented!();
+//! ```
+
+pub use crate::bundle::{create_bundle, BundleLayout, BundleOptions, BundleResult};
+pub use crate::config::{load_config, save_config, MsvcKitConfig};
+pub use crate::downloader::{
+    download_all, download_msvc, download_sdk, list_available_versions, AvailableVersions,
+    BoxedProgressHandler, DownloadOptions, DownloadOptionsBuilder, MsvcComponent,
+    MsvcVersionSelector, ProgressHandler, ReleaseChannel, VerifyMode,
+};
+pub use crate::env::{
+    get_env_vars, setup_environment_with_arch, MsvcEnvironment, ToolKind, ToolPaths,
+};
+pub use crate::error::{MsvcKitError, OptionsError, Result};
+pub use crate::installer::{
+    extract_and_finalize_msvc, extract_and_finalize_msvc_with_report, extract_and_finalize_sdk,
+    extract_and_finalize_sdk_with_report, CleanupPolicy, InstallInfo, InstallReport,
+};
+pub use crate::query::{
+    query_installation, ComponentInfo, QueryComponent, QueryOptions, QueryOptionsBuilder,
+    QueryProperty, QueryResult,
+};
+pub use crate::scripts::{generate_script, ShellType};
+pub use crate::version::{Architecture, MsvcVersion, SdkVersion};
+pub use crate::warning::{Warning, WarningCode};
@@ -0,0 +1,246 @@
+//! Python bindings
+//!
+//! Built as the `msvc_kit` extension module with
+//! `maturin build --release` (see `pyproject.toml`), so Python build tools
+//! such as SCons or conan recipes can provision MSVC without spawning the CLI.
+//!
+//! ```python
+//! import asyncio
+//! import msvc_kit
+//!
+//! options = msvc_kit.DownloadOptions(target_dir="C:/msvc-kit", arch="x64")
+//! installed = asyncio.run(msvc_kit.download_all(options))
+//! print(installed["msvc"]["version"])
+//!
+//! result = msvc_kit.query_installation(msvc_kit.QueryOptions(install_dir="C:/msvc-kit"))
+//! print(result["env_vars"]["INCLUDE"])
+//! ```
+//!
+//! Results are plain `dict`s with the JSON layout of the Rust types. Downloads
+//! return asyncio awaitables driven by a shared tokio runtime.
+
+use std::path::PathBuf;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use serde::Serialize;
+
+use crate::downloader::MsvcComponent;
+use crate::error::MsvcKitError;
+use crate::query::QueryComponent;
+use crate::version::Architecture;
+
+fn to_py_err(e: MsvcKitError) -> PyErr {
+    match e {
+        MsvcKitError::InvalidOptions(e) => PyValueError::new_err(e.to_string()),
+        other => PyRuntimeError::new_err(other.to_string()),
+    }
+}
+
+fn parse<T: std::str::FromStr<Err = String>>(value: &str) -> PyResult<T> {
+    value.parse().map_err(PyValueError::new_err)
+}
+
+/// Convert a serializable value to Python objects via `json.loads`
+fn to_py(py: Python<'_>, value: &impl Serialize) -> PyResult<PyObject> {
+    let json = serde_json::to_string(value).map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+    Ok(py.import("json")?.call_method1("loads", (json,))?.unbind())
+}
+
+/// Options for `download_all`
+#[pyclass(name = "DownloadOptions", module = "msvc_kit")]
+#[derive(Clone)]
+pub struct PyDownloadOptions {
+    inner: crate::DownloadOptions,
+}
+
+#[pymethods]
+impl PyDownloadOptions {
+    #[new]
+    #[pyo3(signature = (
+        target_dir = None,
+        arch = None,
+        host_arch = None,
+        msvc_version = None,
+        sdk_version = None,
+        components = Vec::new(),
+        parallel_downloads = None,
+        verify_hashes = true,
+        offline = false,
+        dry_run = false,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        target_dir: Option<PathBuf>,
+        arch: Option<&str>,
+        host_arch: Option<&str>,
+        msvc_version: Option<String>,
+        sdk_version: Option<String>,
+        components: Vec<String>,
+        parallel_downloads: Option<usize>,
+        verify_hashes: bool,
+        offline: bool,
+        dry_run: bool,
+    ) -> PyResult<Self> {
+        let mut builder = crate::DownloadOptions::builder()
+            .verify_hashes(verify_hashes)
+            .offline(offline)
+            .dry_run(dry_run);
+        if let Some(dir) = target_dir {
+            builder = builder.target_dir(dir);
+        }
+        if let Some(arch) = arch {
+            builder = builder.arch(parse::<Architecture>(arch)?);
+        }
+        if let Some(arch) = host_arch {
+            builder = builder.host_arch(parse::<Architecture>(arch)?);
+        }
+        if let Some(version) = msvc_version {
+            builder = builder.msvc_version(version);
+        }
+        if let Some(version) = sdk_version {
+            builder = builder.sdk_version(version);
+        }
+        for component in components {
+            builder = builder.include_component(parse::<MsvcComponent>(&component)?);
+        }
+        if let Some(count) = parallel_downloads {
+            builder = builder.parallel_downloads(count);
+        }
+        let inner = builder
+            .try_build()
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    /// Load options from the JSON layout of the Rust `DownloadOptions`
+    #[staticmethod]
+    fn from_json(json: &str) -> PyResult<Self> {
+        let inner = serde_json::from_str(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+
+    /// Serialize the options to JSON
+    fn to_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner).map_err(|e| PyRuntimeError::new_err(e.to_string()))
+    }
+
+    #[getter]
+    fn target_dir(&self) -> PathBuf {
+        self.inner.target_dir.clone()
+    }
+
+    #[getter]
+    fn arch(&self) -> String {
+        self.inner.arch.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DownloadOptions(target_dir={:?}, arch='{}')",
+            self.inner.target_dir, self.inner.arch
+        )
+    }
+}
+
+/// Options for `query_installation`
+#[pyclass(name = "QueryOptions", module = "msvc_kit")]
+#[derive(Clone)]
+pub struct PyQueryOptions {
+    inner: crate::QueryOptions,
+}
+
+#[pymethods]
+impl PyQueryOptions {
+    #[new]
+    #[pyo3(signature = (
+        install_dir,
+        arch = None,
+        component = "all",
+        msvc_version = None,
+        sdk_version = None,
+        all_versions = false,
+    ))]
+    fn new(
+        install_dir: PathBuf,
+        arch: Option<&str>,
+        component: &str,
+        msvc_version: Option<String>,
+        sdk_version: Option<String>,
+        all_versions: bool,
+    ) -> PyResult<Self> {
+        let mut builder = crate::QueryOptions::builder()
+            .install_dir(install_dir)
+            .component(parse::<QueryComponent>(component)?)
+            .all_versions(all_versions);
+        if let Some(arch) = arch {
+            builder = builder.arch(parse::<Architecture>(arch)?);
+        }
+        if let Some(version) = msvc_version {
+            builder = builder.msvc_version(version);
+        }
+        if let Some(version) = sdk_version {
+            builder = builder.sdk_version(version);
+        }
+        Ok(Self {
+            inner: builder.build(),
+        })
+    }
+
+    #[getter]
+    fn install_dir(&self) -> PathBuf {
+        self.inner.install_dir.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "QueryOptions(install_dir={:?}, arch='{}', component='{}')",
+            self.inner.install_dir, self.inner.arch, self.inner.component
+        )
+    }
+}
+
+/// Query an installation; returns the `QueryResult` as a dict
+#[pyfunction]
+fn query_installation(py: Python<'_>, options: &PyQueryOptions) -> PyResult<PyObject> {
+    let result = py
+        .allow_threads(|| crate::query_installation(&options.inner))
+        .map_err(to_py_err)?;
+    to_py(py, &result)
+}
+
+/// Download MSVC and the Windows SDK
+///
+/// Returns an awaitable resolving to `{"msvc": InstallInfo, "sdk": InstallInfo}`.
+/// Packages are extracted unless `extract` is false.
+#[pyfunction]
+#[pyo3(signature = (options, extract = true))]
+fn download_all<'py>(
+    py: Python<'py>,
+    options: &PyDownloadOptions,
+    extract: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let options = options.inner.clone();
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let (mut msvc, sdk) = crate::download_all(&options).await.map_err(to_py_err)?;
+        if extract && !options.dry_run {
+            crate::extract_and_finalize_msvc_with_report(&mut msvc, false)
+                .await
+                .map_err(to_py_err)?;
+            crate::extract_and_finalize_sdk_with_report(&sdk, false)
+                .await
+                .map_err(to_py_err)?;
+        }
+        Python::with_gil(|py| to_py(py, &serde_json::json!({"msvc": msvc, "sdk": sdk})))
+    })
+}
+
+#[pymodule]
+fn msvc_kit(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("__version__", env!("CARGO_PKG_VERSION"))?;
+    m.add_class::<PyDownloadOptions>()?;
+    m.add_class::<PyQueryOptions>()?;
+    m.add_function(wrap_pyfunction!(query_installation, m)?)?;
+    m.add_function(wrap_pyfunction!(download_all, m)?)?;
+    Ok(())
+}
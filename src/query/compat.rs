@@ -0,0 +1,197 @@
+//! MSVC / Windows SDK compatibility checks
+//!
+//! Newer MSVC toolsets are only tested against the SDKs offered by the
+//! matching Visual Studio installer. Very old SDKs lack declarations the
+//! toolset's STL and runtime headers rely on, so pinning such a combination
+//! usually fails at compile time rather than at download time.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// How well an MSVC toolset and a Windows SDK work together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompatStatus {
+    /// The combination is offered by the Visual Studio installer
+    Supported,
+    /// The combination is not offered by Visual Studio but usually works
+    Untested,
+    /// The SDK is too old for the toolset's headers
+    Unsupported,
+}
+
+impl fmt::Display for CompatStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompatStatus::Supported => write!(f, "supported"),
+            CompatStatus::Untested => write!(f, "untested"),
+            CompatStatus::Unsupported => write!(f, "unsupported"),
+        }
+    }
+}
+
+/// Result of [`check_compat`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompatReport {
+    /// MSVC version that was checked
+    pub msvc_version: String,
+    /// Windows SDK version that was checked
+    pub sdk_version: String,
+    /// Compatibility of the pair
+    pub status: CompatStatus,
+    /// Why the pair is not supported
+    pub reason: Option<String>,
+    /// Oldest SDK supported by the toolset, if known
+    pub min_sdk: Option<String>,
+}
+
+impl CompatReport {
+    /// Whether the pair is supported
+    pub fn is_supported(&self) -> bool {
+        self.status == CompatStatus::Supported
+    }
+}
+
+/// One row of the compatibility table
+struct CompatRule {
+    /// Oldest MSVC version the row applies to
+    msvc: &'static str,
+    /// Visual Studio release shipping the toolsets
+    visual_studio: &'static str,
+    /// Oldest SDK offered by that Visual Studio installer
+    min_sdk: &'static str,
+    /// SDKs older than this are unsupported
+    unsupported_below: &'static str,
+}
+
+/// Compatibility table, newest toolsets first
+const COMPAT_TABLE: &[CompatRule] = &[
+    CompatRule {
+        msvc: "14.40",
+        visual_studio: "Visual Studio 2022 17.10+",
+        min_sdk: "10.0.19041.0",
+        unsupported_below: "10.0.18362.0",
+    },
+    CompatRule {
+        msvc: "14.30",
+        visual_studio: "Visual Studio 2022",
+        min_sdk: "10.0.18362.0",
+        unsupported_below: "10.0.17763.0",
+    },
+    CompatRule {
+        msvc: "14.20",
+        visual_studio: "Visual Studio 2019",
+        min_sdk: "10.0.17763.0",
+        unsupported_below: "10.0.17134.0",
+    },
+];
+
+fn parse(version: &str) -> Option<Vec<u32>> {
+    version.split('.').map(|p| p.parse().ok()).collect()
+}
+
+/// Check whether an MSVC toolset and a Windows SDK work together
+///
+/// Versions may be prefixes (`14.44`) or full versions (`14.44.34823`,
+/// `10.0.26100.0`). A lone SDK build number (`26100`) means
+/// `10.0.<build>.0`. Unknown or unparsable versions are reported as
+/// [`CompatStatus::Untested`].
+pub fn check_compat(msvc_version: &str, sdk_version: &str) -> CompatReport {
+    let mut report = CompatReport {
+        msvc_version: msvc_version.to_string(),
+        sdk_version: sdk_version.to_string(),
+        status: CompatStatus::Supported,
+        reason: None,
+        min_sdk: None,
+    };
+
+    let (Some(msvc), Some(sdk)) = (parse(msvc_version), parse(sdk_version)) else {
+        report.status = CompatStatus::Untested;
+        report.reason = Some("version could not be parsed".to_string());
+        return report;
+    };
+    let sdk = match sdk[..] {
+        [build] if build > 10 => vec![10, 0, build, 0],
+        _ => sdk,
+    };
+
+    if sdk.first() != Some(&10) {
+        report.status = CompatStatus::Unsupported;
+        report.reason = Some("only Windows 10/11 SDKs (10.0.x) are supported".to_string());
+        return report;
+    }
+
+    let Some(rule) = COMPAT_TABLE
+        .iter()
+        .find(|rule| parse(rule.msvc).is_some_and(|min| msvc >= min))
+    else {
+        report.status = CompatStatus::Untested;
+        report.reason = Some(format!(
+            "MSVC {} predates the compatibility table",
+            msvc_version
+        ));
+        return report;
+    };
+
+    report.min_sdk = Some(rule.min_sdk.to_string());
+    if parse(rule.unsupported_below).is_some_and(|min| sdk < min) {
+        report.status = CompatStatus::Unsupported;
+        report.reason = Some(format!(
+            "SDK {} is older than {}, the oldest SDK the {} headers build with",
+            sdk_version, rule.unsupported_below, rule.visual_studio
+        ));
+    } else if parse(rule.min_sdk).is_some_and(|min| sdk < min) {
+        report.status = CompatStatus::Untested;
+        report.reason = Some(format!(
+            "{} offers SDK {} or newer",
+            rule.visual_studio, rule.min_sdk
+        ));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_compat() {
+        let report = check_compat("14.44", "10.0.26100.0");
+        assert!(report.is_supported());
+        assert_eq!(report.min_sdk.as_deref(), Some("10.0.19041.0"));
+
+        let report = check_compat("14.44.34823", "10.0.18362.0");
+        assert_eq!(report.status, CompatStatus::Untested);
+
+        let report = check_compat("14.44", "10.0.17763.0");
+        assert_eq!(report.status, CompatStatus::Unsupported);
+        assert!(report.reason.unwrap().contains("10.0.18362.0"));
+
+        // The same SDK is fine for an older toolset
+        assert!(check_compat("14.29", "10.0.17763.0").is_supported());
+    }
+
+    #[test]
+    fn test_check_compat_unknown_versions() {
+        assert_eq!(
+            check_compat("latest", "10.0.26100.0").status,
+            CompatStatus::Untested
+        );
+        assert_eq!(
+            check_compat("14.16", "10.0.17763.0").status,
+            CompatStatus::Untested
+        );
+        assert_eq!(
+            check_compat("14.44", "8.1").status,
+            CompatStatus::Unsupported
+        );
+        assert_eq!(
+            check_compat("14.44", "26100").status,
+            CompatStatus::Supported
+        );
+        assert_eq!(
+            check_compat("14.44", "17134").status,
+            CompatStatus::Unsupported
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Sub-component details of an installed MSVC toolset or Windows SDK
+//!
+//! Optional parts (ATL/MFC, ASAN, Spectre libraries) are only present when
+//! they were requested at download time, and the UCRT and redistributable
+//! versions do not always match the component version. These are discovered
+//! from the install tree rather than from the manifest.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::version::Architecture;
+
+/// Architectures probed for per-arch libraries
+const ARCHES: [Architecture; 4] = [
+    Architecture::X64,
+    Architecture::X86,
+    Architecture::Arm64,
+    Architecture::Arm,
+];
+
+/// Optional parts and sub-component versions of an installed component
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentDetails {
+    /// Universal CRT version (SDK; `Include/<version>/ucrt`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ucrt_version: Option<String>,
+
+    /// VC++ redistributable version (MSVC; `VC/Redist/MSVC/<version>`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redist_version: Option<String>,
+
+    /// ATL/MFC version, if `atlmfc` is installed (MSVC)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atlmfc_version: Option<String>,
+
+    /// ATL headers are installed (MSVC)
+    #[serde(default)]
+    pub atl: bool,
+
+    /// MFC headers are installed (MSVC)
+    #[serde(default)]
+    pub mfc: bool,
+
+    /// AddressSanitizer runtime version, if installed (MSVC)
+    ///
+    /// The runtime ships with the toolset, so this is the toolset version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub asan_version: Option<String>,
+
+    /// Architectures with Spectre-mitigated libraries (MSVC; `lib/spectre/<arch>`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spectre_arches: Vec<Architecture>,
+
+    /// Architectures with AddressSanitizer runtime libraries (MSVC; `lib/<arch>/clang_rt.asan*`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub asan_arches: Vec<Architecture>,
+
+    /// Architectures with target libraries (MSVC `lib/<arch>`, SDK `Lib/<version>/um/<arch>`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub target_arches: Vec<Architecture>,
+}
+
+/// Architectures for which `dir` joined with the architecture name is a directory
+fn arch_dirs(dir: &Path) -> Vec<Architecture> {
+    ARCHES
+        .into_iter()
+        .filter(|arch| dir.join(arch.to_string()).is_dir())
+        .collect()
+}
+
+/// Newest subdirectory of `dir` whose name starts with a digit and that `accept`s
+pub(crate) fn newest_version_dir(dir: &Path, accept: impl Fn(&Path) -> bool) -> Option<String> {
+    let mut versions: Vec<String> = std::fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|e| e.path().is_dir() && accept(&e.path()))
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.starts_with(|c: char| c.is_ascii_digit()))
+        .collect();
+    versions.sort();
+    versions.pop()
+}
+
+/// Details of the MSVC toolset `version` at `tools_path` (`VC/Tools/MSVC/<version>`)
+pub(crate) fn msvc_details(
+    install_dir: &Path,
+    tools_path: &Path,
+    version: &str,
+) -> ComponentDetails {
+    let atlmfc = tools_path.join("atlmfc");
+    let atl = atlmfc.join("include").join("atlbase.h").is_file();
+    let mfc = atlmfc.join("include").join("afxwin.h").is_file();
+
+    let asan_arches: Vec<Architecture> = ARCHES
+        .into_iter()
+        .filter(|arch| {
+            std::fs::read_dir(tools_path.join("lib").join(arch.to_string()))
+                .into_iter()
+                .flatten()
+                .flatten()
+                .any(|e| e.file_name().to_string_lossy().starts_with("clang_rt.asan"))
+        })
+        .collect();
+
+    ComponentDetails {
+        redist_version: newest_version_dir(
+            &install_dir.join("VC").join("Redist").join("MSVC"),
+            |_| true,
+        ),
+        atlmfc_version: (atl || mfc).then(|| version.to_string()),
+        atl,
+        mfc,
+        asan_version: (!asan_arches.is_empty()).then(|| version.to_string()),
+        spectre_arches: arch_dirs(&tools_path.join("lib").join("spectre")),
+        asan_arches,
+        target_arches: arch_dirs(&tools_path.join("lib")),
+        ..Default::default()
+    }
+}
+
+/// Details of the SDK `version` at `sdk_path` (`Windows Kits/10`)
+///
+/// The UCRT normally ships with the same SDK version; when the selected SDK
+/// has none, the newest installed UCRT is reported.
+pub(crate) fn sdk_details(sdk_path: &Path, version: &str) -> ComponentDetails {
+    let include = sdk_path.join("Include");
+    let ucrt_version = if include.join(version).join("ucrt").is_dir() {
+        Some(version.to_string())
+    } else {
+        newest_version_dir(&include, |dir| dir.join("ucrt").is_dir())
+    };
+    ComponentDetails {
+        ucrt_version,
+        target_arches: arch_dirs(&sdk_path.join("Lib").join(version).join("um")),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_msvc_details() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let tools = root.join("VC/Tools/MSVC/14.44.34823");
+        std::fs::create_dir_all(tools.join("atlmfc/include")).unwrap();
+        std::fs::write(tools.join("atlmfc/include/atlbase.h"), "").unwrap();
+        std::fs::create_dir_all(tools.join("lib/x64")).unwrap();
+        std::fs::write(tools.join("lib/x64/clang_rt.asan_dynamic-x86_64.lib"), "").unwrap();
+        std::fs::create_dir_all(tools.join("lib/spectre/arm64")).unwrap();
+        std::fs::create_dir_all(root.join("VC/Redist/MSVC/14.44.35112")).unwrap();
+        std::fs::create_dir_all(root.join("VC/Redist/MSVC/v143")).unwrap();
+
+        let details = msvc_details(root, &tools, "14.44.34823");
+        assert_eq!(details.redist_version.as_deref(), Some("14.44.35112"));
+        assert!(details.atl);
+        assert!(!details.mfc);
+        assert_eq!(details.atlmfc_version.as_deref(), Some("14.44.34823"));
+        assert_eq!(details.asan_version.as_deref(), Some("14.44.34823"));
+        assert_eq!(details.spectre_arches, vec![Architecture::Arm64]);
+        assert_eq!(details.asan_arches, vec![Architecture::X64]);
+        assert_eq!(details.target_arches, vec![Architecture::X64]);
+        assert_eq!(details.ucrt_version, None);
+    }
+
+    #[test]
+    fn test_sdk_details_ucrt_fallback() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let sdk = temp_dir.path();
+        std::fs::create_dir_all(sdk.join("Include/10.0.22621.0/ucrt")).unwrap();
+        std::fs::create_dir_all(sdk.join("Include/10.0.26100.0/um")).unwrap();
+        std::fs::create_dir_all(sdk.join("Lib/10.0.26100.0/um/arm64")).unwrap();
+
+        let details = sdk_details(sdk, "10.0.22621.0");
+        assert_eq!(details.ucrt_version.as_deref(), Some("10.0.22621.0"));
+        let details = sdk_details(sdk, "10.0.26100.0");
+        assert_eq!(details.ucrt_version.as_deref(), Some("10.0.22621.0"));
+        assert_eq!(details.target_arches, vec![Architecture::Arm64]);
+        assert!(msvc_details(sdk, sdk, "14.44").spectre_arches.is_empty());
+    }
+}
@@ -0,0 +1,462 @@
+//! Differences between two installed versions of a component
+//!
+//! Compares the install trees of two MSVC toolsets (`VC/Tools/MSVC/<version>`)
+//! or Windows SDKs (every `Windows Kits/10/*/<version>` directory): headers
+//! and libraries that were added, removed or changed in size, the file
+//! versions of the tools, and the total size. Nothing is hashed, so even
+//! full toolsets are compared in seconds.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::QueryComponent;
+use crate::error::{MsvcKitError, Result};
+use crate::version::{list_installed_msvc, list_installed_sdk};
+
+/// Signature of the `VS_FIXEDFILEINFO` block in the version resource of a PE file
+const FIXED_FILE_INFO_SIGNATURE: [u8; 4] = [0xBD, 0x04, 0xEF, 0xFE];
+
+/// Size and path of the files of a version, by relative path
+type FileMap = BTreeMap<String, (u64, PathBuf)>;
+
+/// Files added, removed or changed between two versions (relative paths)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileChanges {
+    /// Only in the newer version
+    pub added: Vec<String>,
+    /// Only in the older version
+    pub removed: Vec<String>,
+    /// In both versions, with a different size
+    pub changed: Vec<String>,
+}
+
+impl FileChanges {
+    /// Whether nothing was added, removed or changed
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// File version of a tool in the two versions (`None` if the tool is missing)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolChange {
+    /// Executable name, e.g. `cl.exe`
+    pub name: String,
+    /// File version in the older version, e.g. `19.43.34808.0`
+    pub from: Option<String>,
+    /// File version in the newer version
+    pub to: Option<String>,
+}
+
+/// What changed on disk between two installed versions of a component
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionDiff {
+    /// Compared component ([`QueryComponent::Msvc`] or [`QueryComponent::Sdk`])
+    pub component: QueryComponent,
+    /// Older version, e.g. `14.43.34808`
+    pub from: String,
+    /// Newer version
+    pub to: String,
+    /// Bytes on disk of the older version
+    pub from_bytes: u64,
+    /// Bytes on disk of the newer version
+    pub to_bytes: u64,
+    /// Headers (files below an `include` directory)
+    pub headers: FileChanges,
+    /// Import and static libraries (`*.lib`)
+    pub libs: FileChanges,
+    /// Tools whose file version differs, or that exist in one version only
+    pub tools: Vec<ToolChange>,
+}
+
+impl VersionDiff {
+    /// Size difference in bytes (negative if the newer version is smaller)
+    pub fn size_delta(&self) -> i64 {
+        self.to_bytes as i64 - self.from_bytes as i64
+    }
+
+    /// Export the diff to JSON
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+impl fmt::Display for VersionDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = |bytes: u64| humansize::format_size(bytes, humansize::BINARY);
+        let name = match self.component {
+            QueryComponent::Sdk => "Windows SDK",
+            _ => "MSVC",
+        };
+        writeln!(f, "{} {} -> {}", name, self.from, self.to)?;
+        let sign = if self.size_delta() < 0 { "-" } else { "+" };
+        writeln!(
+            f,
+            "Size: {} -> {} ({}{})",
+            size(self.from_bytes),
+            size(self.to_bytes),
+            sign,
+            size(self.size_delta().unsigned_abs())
+        )?;
+
+        if !self.tools.is_empty() {
+            writeln!(f, "Tools:")?;
+            for tool in &self.tools {
+                let version = |v: &Option<String>| v.clone().unwrap_or_else(|| "-".to_string());
+                writeln!(
+                    f,
+                    "  {}: {} -> {}",
+                    tool.name,
+                    version(&tool.from),
+                    version(&tool.to)
+                )?;
+            }
+        }
+
+        for (title, changes) in [("Headers", &self.headers), ("Libraries", &self.libs)] {
+            writeln!(
+                f,
+                "{}: {} added, {} removed, {} changed",
+                title,
+                changes.added.len(),
+                changes.removed.len(),
+                changes.changed.len()
+            )?;
+            for path in &changes.added {
+                writeln!(f, "  + {}", path)?;
+            }
+            for path in &changes.removed {
+                writeln!(f, "  - {}", path)?;
+            }
+            for path in &changes.changed {
+                writeln!(f, "  ~ {}", path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compare two installed versions of a component
+///
+/// Versions may be given as prefixes (`14.43`) or, for the SDK, as build
+/// numbers (`26100`); the newest installed match is used. With
+/// [`QueryComponent::All`], the versions are looked up among the MSVC
+/// toolsets first, then among the SDKs.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::query::{diff_versions, QueryComponent};
+/// use std::path::Path;
+///
+/// let diff = diff_versions(Path::new("C:/msvc-kit"), QueryComponent::Msvc, "14.43", "14.44")?;
+/// println!("{} headers added", diff.headers.added.len());
+/// print!("{}", diff);
+/// # Ok::<(), msvc_kit::MsvcKitError>(())
+/// ```
+pub fn diff_versions(
+    install_dir: &Path,
+    component: QueryComponent,
+    from: &str,
+    to: &str,
+) -> Result<VersionDiff> {
+    let msvc: Vec<String> = list_installed_msvc(install_dir)
+        .into_iter()
+        .map(|v| v.version)
+        .collect();
+    let sdk: Vec<String> = list_installed_sdk(install_dir)
+        .into_iter()
+        .map(|v| v.version)
+        .collect();
+
+    let component = match component {
+        QueryComponent::All
+            if resolve_version(&msvc, from).is_none() || resolve_version(&msvc, to).is_none() =>
+        {
+            QueryComponent::Sdk
+        }
+        QueryComponent::All => QueryComponent::Msvc,
+        other => other,
+    };
+    let (installed, name) = match component {
+        QueryComponent::Sdk => (&sdk, "Windows SDK"),
+        _ => (&msvc, "MSVC"),
+    };
+    let resolve = |requested: &str| {
+        resolve_version(installed, requested).ok_or_else(|| {
+            MsvcKitError::VersionNotFound(format!(
+                "{} {} is not installed in {} (installed: {})",
+                name,
+                requested,
+                install_dir.display(),
+                installed.join(", ")
+            ))
+        })
+    };
+    let (from, to) = (resolve(from)?, resolve(to)?);
+
+    let files = |version: &str| {
+        let mut files = BTreeMap::new();
+        match component {
+            QueryComponent::Sdk => {
+                let kits = install_dir.join("Windows Kits").join("10");
+                for dir in std::fs::read_dir(&kits).into_iter().flatten().flatten() {
+                    let root = dir.path().join(version);
+                    if root.is_dir() {
+                        let prefix = format!("{}/", dir.file_name().to_string_lossy());
+                        collect_files(&root, &prefix, &mut files);
+                    }
+                }
+            }
+            _ => {
+                let root = install_dir
+                    .join("VC")
+                    .join("Tools")
+                    .join("MSVC")
+                    .join(version);
+                collect_files(&root, "", &mut files);
+            }
+        }
+        files
+    };
+    let (old, new) = (files(&from), files(&to));
+
+    Ok(VersionDiff {
+        component,
+        from_bytes: old.values().map(|(size, _)| size).sum(),
+        to_bytes: new.values().map(|(size, _)| size).sum(),
+        from,
+        to,
+        headers: file_changes(&old, &new, is_header),
+        libs: file_changes(&old, &new, |path| path.to_lowercase().ends_with(".lib")),
+        tools: tool_changes(&old, &new),
+    })
+}
+
+/// Installed version matching `requested` exactly, as a prefix or as SDK build number
+///
+/// `installed` is sorted newest first, so the newest match wins.
+fn resolve_version(installed: &[String], requested: &str) -> Option<String> {
+    let prefix = format!("{}.", requested);
+    installed
+        .iter()
+        .find(|v| *v == requested)
+        .or_else(|| {
+            installed
+                .iter()
+                .find(|v| v.starts_with(&prefix) || v.split('.').nth(2) == Some(requested))
+        })
+        .cloned()
+}
+
+/// Size and path of every file below `root`, by relative path (`/`-separated)
+fn collect_files(root: &Path, prefix: &str, files: &mut FileMap) {
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(meta) = path.symlink_metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                pending.push(path);
+            } else if meta.is_file() {
+                let Ok(relative) = path.strip_prefix(root) else {
+                    continue;
+                };
+                let relative = relative.to_string_lossy().replace('\\', "/");
+                files.insert(format!("{}{}", prefix, relative), (meta.len(), path));
+            }
+        }
+    }
+}
+
+/// Whether a relative path lies below an `include` directory
+fn is_header(path: &str) -> bool {
+    let mut dirs = path.split('/').rev().skip(1);
+    dirs.any(|dir| dir.eq_ignore_ascii_case("include"))
+}
+
+fn file_changes(old: &FileMap, new: &FileMap, select: impl Fn(&str) -> bool) -> FileChanges {
+    let mut changes = FileChanges::default();
+    for (path, (size, _)) in new.iter().filter(|(path, _)| select(path)) {
+        match old.get(path) {
+            None => changes.added.push(path.clone()),
+            Some((old_size, _)) if old_size != size => changes.changed.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    changes.removed = old
+        .keys()
+        .filter(|path| select(path) && !new.contains_key(*path))
+        .cloned()
+        .collect();
+    changes
+}
+
+/// Tools whose file version differs, by executable name
+fn tool_changes(old: &FileMap, new: &FileMap) -> Vec<ToolChange> {
+    let (old, new) = (tools_by_name(old), tools_by_name(new));
+
+    let mut names: Vec<&String> = old.keys().chain(new.keys()).collect();
+    names.sort();
+    names.dedup();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let from = old.get(name).map(|p| file_version(p).unwrap_or_default());
+            let to = new.get(name).map(|p| file_version(p).unwrap_or_default());
+            (from != to).then(|| ToolChange {
+                name: name.clone(),
+                from,
+                to,
+            })
+        })
+        .collect()
+}
+
+/// Executables by lowercase file name
+///
+/// Executables appear once per host/target directory; the first path in
+/// sorted order stands for all of them.
+fn tools_by_name(files: &FileMap) -> BTreeMap<String, &Path> {
+    let mut tools = BTreeMap::new();
+    for (relative, (_, path)) in files {
+        if relative.to_lowercase().ends_with(".exe") {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            tools.entry(name.to_lowercase()).or_insert(path.as_path());
+        }
+    }
+    tools
+}
+
+/// File version from the version resource of a PE file, e.g. `19.44.34823.0`
+fn file_version(path: &Path) -> Option<String> {
+    let data = std::fs::read(path).ok()?;
+    let start = data
+        .windows(4)
+        .position(|w| w == FIXED_FILE_INFO_SIGNATURE)?;
+    // dwSignature, dwStrucVersion, dwFileVersionMS, dwFileVersionLS
+    let word = |i: usize| -> Option<u32> {
+        let offset = start + i * 4;
+        Some(u32::from_le_bytes(
+            data.get(offset..offset + 4)?.try_into().ok()?,
+        ))
+    };
+    let (ms, ls) = (word(2)?, word(3)?);
+    Some(format!(
+        "{}.{}.{}.{}",
+        ms >> 16,
+        ms & 0xFFFF,
+        ls >> 16,
+        ls & 0xFFFF
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, contents: &[u8]) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, contents).unwrap();
+    }
+
+    /// Bytes with a `VS_FIXEDFILEINFO` block for `major.minor.build.0`
+    fn exe(major: u32, minor: u32, build: u32) -> Vec<u8> {
+        let mut data = b"MZ padding".to_vec();
+        data.extend(FIXED_FILE_INFO_SIGNATURE);
+        data.extend(0x0001_0000u32.to_le_bytes());
+        data.extend((major << 16 | minor).to_le_bytes());
+        data.extend((build << 16).to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn test_resolve_version() {
+        let installed = vec![
+            "14.44.34823".to_string(),
+            "14.43.34808".to_string(),
+            "10.0.26100.0".to_string(),
+        ];
+        assert_eq!(resolve_version(&installed, "14.43").unwrap(), "14.43.34808");
+        assert_eq!(
+            resolve_version(&installed, "14.44.34823").unwrap(),
+            "14.44.34823"
+        );
+        assert_eq!(
+            resolve_version(&installed, "26100").unwrap(),
+            "10.0.26100.0"
+        );
+        assert!(resolve_version(&installed, "14.4").is_none());
+    }
+
+    #[test]
+    fn test_diff_msvc_versions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let msvc = temp_dir.path().join("VC").join("Tools").join("MSVC");
+        let (old, new) = (msvc.join("14.43.34808"), msvc.join("14.44.34823"));
+
+        write(&old.join("include").join("vector"), b"old");
+        write(&new.join("include").join("vector"), b"newer");
+        write(&old.join("include").join("removed.h"), b"x");
+        write(&new.join("include").join("__msvc_new.hpp"), b"x");
+        write(&old.join("lib").join("x64").join("libcmt.lib"), b"lib");
+        write(&new.join("lib").join("x64").join("libcmt.lib"), b"lib");
+        let cl = Path::new("bin").join("Hostx64").join("x64").join("cl.exe");
+        write(&old.join(&cl), &exe(19, 43, 34808));
+        write(&new.join(&cl), &exe(19, 44, 34823));
+
+        let diff = diff_versions(temp_dir.path(), QueryComponent::All, "14.43", "14.44").unwrap();
+        assert_eq!(diff.component, QueryComponent::Msvc);
+        assert_eq!(diff.to, "14.44.34823");
+        assert_eq!(diff.headers.added, vec!["include/__msvc_new.hpp"]);
+        assert_eq!(diff.headers.removed, vec!["include/removed.h"]);
+        assert_eq!(diff.headers.changed, vec!["include/vector"]);
+        assert!(diff.libs.is_empty());
+        assert_eq!(
+            diff.tools,
+            vec![ToolChange {
+                name: "cl.exe".to_string(),
+                from: Some("19.43.34808.0".to_string()),
+                to: Some("19.44.34823.0".to_string()),
+            }]
+        );
+        // vector grew by 2, one 1-byte header added and one removed
+        assert_eq!(diff.size_delta(), 2);
+
+        let text = diff.to_string();
+        assert!(text.contains("cl.exe: 19.43.34808.0 -> 19.44.34823.0"));
+        assert!(text.contains("Headers: 1 added, 1 removed, 1 changed"));
+        assert!(text.contains("  + include/__msvc_new.hpp"));
+        assert!(text.contains("  - include/removed.h"));
+        assert!(text.contains("  ~ include/vector"));
+    }
+
+    #[test]
+    fn test_diff_sdk_versions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let kits = temp_dir.path().join("Windows Kits").join("10");
+        write(&kits.join("Include/10.0.22621.0/um/windows.h"), b"a");
+        write(&kits.join("Include/10.0.26100.0/um/windows.h"), b"a");
+        write(&kits.join("Lib/10.0.22621.0/um/x64/kernel32.lib"), b"a");
+        write(&kits.join("Lib/10.0.26100.0/um/x64/kernel32.lib"), b"abc");
+        write(&kits.join("Lib/10.0.26100.0/um/x64/onecore.lib"), b"a");
+
+        let diff = diff_versions(temp_dir.path(), QueryComponent::All, "22621", "26100").unwrap();
+        assert_eq!(diff.component, QueryComponent::Sdk);
+        assert!(diff.headers.is_empty());
+        assert_eq!(diff.libs.added, vec!["Lib/um/x64/onecore.lib"]);
+        assert_eq!(diff.libs.changed, vec!["Lib/um/x64/kernel32.lib"]);
+
+        assert!(matches!(
+            diff_versions(temp_dir.path(), QueryComponent::Sdk, "22621", "19041"),
+            Err(MsvcKitError::VersionNotFound(_))
+        ));
+    }
+}
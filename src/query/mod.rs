@@ -30,14 +30,35 @@
 //! # Ok::<(), msvc_kit::MsvcKitError>(())
 //! ```
 
+mod compat;
+mod details;
+mod diff;
+mod probe;
+mod sources;
+mod usage;
+
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use crate::env::{get_env_vars, MsvcEnvironment};
+use crate::env::{detect_host_arch, get_env_vars, MsvcEnvironment, ToolKind};
 use crate::error::{MsvcKitError, Result};
 use crate::installer::InstallInfo;
-use crate::version::{list_installed_msvc, list_installed_sdk, Architecture};
+use crate::version::{
+    list_installed_msvc, list_installed_sdk, Architecture, MsvcVersion, SdkVersion,
+};
+use crate::warning::{Warning, WarningCode};
+
+pub use compat::{check_compat, CompatReport, CompatStatus};
+pub(crate) use details::newest_version_dir;
+pub use details::ComponentDetails;
+pub use diff::{diff_versions, FileChanges, ToolChange, VersionDiff};
+pub use probe::{
+    probe_compiler, probe_runtime_libraries, target_triple, CompilerProbe, RuntimeLibraries,
+};
+pub use sources::{find_visual_studio, system_windows_kits_root};
+pub(crate) use usage::dir_size;
+pub use usage::{disk_usage, disk_usage_with_cache_dir, DiskUsageReport, VersionUsage};
 
 /// Which component to query
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -150,6 +171,22 @@ pub struct QueryOptions {
 
     /// Specific SDK version to query (None = latest installed)
     pub sdk_version: Option<String>,
+
+    /// Also describe every installed version in
+    /// [`QueryResult::msvc_versions`] and [`QueryResult::sdk_versions`]
+    pub all_versions: bool,
+
+    /// Take MSVC from this directory instead of `install_dir`
+    ///
+    /// A Visual Studio installation (see [`find_visual_studio`]), another
+    /// msvc-kit directory, or its `VC/Tools/MSVC` subdirectory.
+    pub msvc_dir: Option<PathBuf>,
+
+    /// Take the Windows SDK from this directory instead of `install_dir`
+    ///
+    /// An msvc-kit directory or a kit root such as
+    /// `C:\Program Files (x86)\Windows Kits\10` (see [`system_windows_kits_root`]).
+    pub sdk_dir: Option<PathBuf>,
 }
 
 impl Default for QueryOptions {
@@ -161,6 +198,9 @@ impl Default for QueryOptions {
             property: QueryProperty::default(),
             msvc_version: None,
             sdk_version: None,
+            all_versions: false,
+            msvc_dir: None,
+            sdk_dir: None,
         }
     }
 }
@@ -170,6 +210,22 @@ impl QueryOptions {
     pub fn builder() -> QueryOptionsBuilder {
         QueryOptionsBuilder::default()
     }
+
+    /// Directory MSVC is looked up in (`msvc_dir` or `install_dir`)
+    pub fn msvc_root(&self) -> PathBuf {
+        match &self.msvc_dir {
+            Some(dir) => sources::msvc_root(dir),
+            None => self.install_dir.clone(),
+        }
+    }
+
+    /// Directory the Windows SDK is looked up in (`sdk_dir` or `install_dir`)
+    pub fn sdk_root(&self) -> PathBuf {
+        match &self.sdk_dir {
+            Some(dir) => sources::sdk_root(dir),
+            None => self.install_dir.clone(),
+        }
+    }
 }
 
 /// Builder for QueryOptions
@@ -215,6 +271,24 @@ impl QueryOptionsBuilder {
         self
     }
 
+    /// Describe every installed version, not just the selected one
+    pub fn all_versions(mut self, all: bool) -> Self {
+        self.options.all_versions = all;
+        self
+    }
+
+    /// Take MSVC from another directory, e.g. a Visual Studio installation
+    pub fn msvc_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.msvc_dir = Some(dir.into());
+        self
+    }
+
+    /// Take the Windows SDK from another directory, e.g. the system kit root
+    pub fn sdk_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.options.sdk_dir = Some(dir.into());
+        self
+    }
+
     /// Build the query options
     pub fn build(self) -> QueryOptions {
         self.options
@@ -238,11 +312,23 @@ pub struct QueryResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sdk: Option<ComponentInfo>,
 
+    /// Every installed MSVC version, newest first ([`QueryOptions::all_versions`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub msvc_versions: Vec<ComponentInfo>,
+
+    /// Every installed Windows SDK version, newest first ([`QueryOptions::all_versions`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sdk_versions: Vec<ComponentInfo>,
+
     /// Merged environment variables for the full toolchain
     pub env_vars: HashMap<String, String>,
 
-    /// Tool executable paths
-    pub tools: HashMap<String, PathBuf>,
+    /// Tool executable paths, serialized with tool names (`"cl"`, ...) as keys
+    pub tools: HashMap<ToolKind, PathBuf>,
+
+    /// Non-fatal problems, e.g. a queried component that is not installed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<Warning>,
 }
 
 /// Information about a single installed component
@@ -265,12 +351,23 @@ pub struct ComponentInfo {
 
     /// Binary paths
     pub bin_paths: Vec<PathBuf>,
+
+    /// Optional parts and sub-component versions (UCRT, redist, ATL/MFC, ...)
+    #[serde(default)]
+    pub details: ComponentDetails,
 }
 
 impl QueryResult {
     /// Get the path to a specific tool by name (e.g., "cl", "link", "lib", "rc")
+    ///
+    /// Returns `None` for names that are not a [`ToolKind`].
     pub fn tool_path(&self, name: &str) -> Option<&PathBuf> {
-        self.tools.get(name)
+        self.tool(name.parse().ok()?)
+    }
+
+    /// Get the path to a specific tool
+    pub fn tool(&self, tool: ToolKind) -> Option<&PathBuf> {
+        self.tools.get(&tool)
     }
 
     /// Get a specific environment variable value
@@ -322,6 +419,73 @@ impl QueryResult {
         paths
     }
 
+    /// Find a header in the include paths, searched in order like `cl` does
+    ///
+    /// `name` may contain subdirectories (e.g. `"sys/stat.h"`). Matching falls
+    /// back to a case-insensitive lookup, as on the Windows file system.
+    pub fn find_header(&self, name: &str) -> Option<PathBuf> {
+        find_in_paths(&self.all_include_paths(), name)
+    }
+
+    /// Find a library in the lib paths, searched in order like `link` does
+    pub fn find_library(&self, name: &str) -> Option<PathBuf> {
+        find_in_paths(&self.all_lib_paths(), name)
+    }
+
+    /// Whether the MSVC toolset has Spectre-mitigated libraries for `arch`
+    pub fn has_spectre_libs(&self, arch: Architecture) -> bool {
+        self.msvc
+            .as_ref()
+            .is_some_and(|m| m.details.spectre_arches.contains(&arch))
+    }
+
+    /// Whether the MSVC toolset has the AddressSanitizer runtime for `arch`
+    pub fn has_asan(&self, arch: Architecture) -> bool {
+        self.msvc
+            .as_ref()
+            .is_some_and(|m| m.details.asan_arches.contains(&arch))
+    }
+
+    /// Whether ATL or MFC is installed
+    pub fn has_atlmfc(&self) -> bool {
+        self.msvc
+            .as_ref()
+            .is_some_and(|m| m.details.atlmfc_version.is_some())
+    }
+
+    /// Whether code for `arch` can be linked
+    ///
+    /// The MSVC toolset needs libraries for `arch`, and so does the SDK if
+    /// one was queried.
+    pub fn has_target(&self, arch: Architecture) -> bool {
+        let has = |c: &ComponentInfo| c.details.target_arches.contains(&arch);
+        self.msvc.as_ref().is_some_and(has) && self.sdk.as_ref().is_none_or(has)
+    }
+
+    /// Whether ARM64 code can be linked, see [`has_target`](Self::has_target)
+    pub fn has_arm64_target(&self) -> bool {
+        self.has_target(Architecture::Arm64)
+    }
+
+    /// Environment of the queried MSVC toolset and Windows SDK
+    ///
+    /// With [`QueryOptions::msvc_dir`] or [`QueryOptions::sdk_dir`] set, the
+    /// components may come from different installations, e.g. MSVC from
+    /// Visual Studio and the SDK from msvc-kit. The host architecture
+    /// defaults to the one [`detect_host_arch`] picks.
+    ///
+    /// Fails with [`MsvcKitError::ComponentNotFound`] if no MSVC toolset was queried.
+    pub fn environment(&self, host_arch: Option<Architecture>) -> Result<MsvcEnvironment> {
+        let msvc = self.msvc.as_ref().ok_or_else(|| {
+            MsvcKitError::ComponentNotFound(format!(
+                "No MSVC installation found in: {}",
+                self.install_dir.display()
+            ))
+        })?;
+        let arch = self.arch.parse().map_err(MsvcKitError::Other)?;
+        component_environment(msvc, self.sdk.as_ref(), arch, host_arch)
+    }
+
     /// Export as JSON value
     pub fn to_json(&self) -> serde_json::Value {
         serde_json::to_value(self).unwrap_or_default()
@@ -341,23 +505,78 @@ impl QueryResult {
             output.push_str("\nMSVC Compiler:\n");
             output.push_str(&format!("  Version: {}\n", msvc.version));
             output.push_str(&format!("  Path: {}\n", msvc.install_path.display()));
+            let details = &msvc.details;
+            if let Some(ref redist) = details.redist_version {
+                output.push_str(&format!("  Redist: {}\n", redist));
+            }
+            if let Some(ref atlmfc) = details.atlmfc_version {
+                let parts: Vec<&str> = [(details.atl, "ATL"), (details.mfc, "MFC")]
+                    .into_iter()
+                    .filter_map(|(present, name)| present.then_some(name))
+                    .collect();
+                output.push_str(&format!("  ATL/MFC: {} ({})\n", atlmfc, parts.join(", ")));
+            }
+            if let Some(ref asan) = details.asan_version {
+                output.push_str(&format!("  ASAN runtime: {}\n", asan));
+            }
+            if !details.spectre_arches.is_empty() {
+                let arches: Vec<String> = details
+                    .spectre_arches
+                    .iter()
+                    .map(|a| a.to_string())
+                    .collect();
+                output.push_str(&format!("  Spectre libraries: {}\n", arches.join(", ")));
+            }
         }
 
         if let Some(ref sdk) = self.sdk {
             output.push_str("\nWindows SDK:\n");
             output.push_str(&format!("  Version: {}\n", sdk.version));
             output.push_str(&format!("  Path: {}\n", sdk.install_path.display()));
+            if let Some(ref ucrt) = sdk.details.ucrt_version {
+                output.push_str(&format!("  UCRT: {}\n", ucrt));
+            }
+        }
+
+        for (title, versions, selected) in [
+            ("MSVC", &self.msvc_versions, self.msvc_version()),
+            ("Windows SDK", &self.sdk_versions, self.sdk_version()),
+        ] {
+            if versions.is_empty() {
+                continue;
+            }
+            output.push_str(&format!("\nInstalled {} versions:\n", title));
+            for info in versions {
+                let marker = if Some(info.version.as_str()) == selected {
+                    " (selected)"
+                } else {
+                    ""
+                };
+                output.push_str(&format!(
+                    "  {}{}: {}\n",
+                    info.version,
+                    marker,
+                    info.install_path.display()
+                ));
+            }
         }
 
         if !self.tools.is_empty() {
             output.push_str("\nTools:\n");
             let mut sorted_tools: Vec<_> = self.tools.iter().collect();
-            sorted_tools.sort_by_key(|(k, _)| k.as_str());
+            sorted_tools.sort_by_key(|(k, _)| k.name());
             for (name, path) in sorted_tools {
                 output.push_str(&format!("  {}: {}\n", name, path.display()));
             }
         }
 
+        if !self.warnings.is_empty() {
+            output.push_str("\nWarnings:\n");
+            for warning in &self.warnings {
+                output.push_str(&format!("  {}\n", warning));
+            }
+        }
+
         output
     }
 }
@@ -391,58 +610,80 @@ impl QueryResult {
 /// ```
 pub fn query_installation(options: &QueryOptions) -> Result<QueryResult> {
     let install_dir = &options.install_dir;
-
-    if !install_dir.exists() {
-        return Err(MsvcKitError::InstallPath(format!(
-            "Installation directory not found: {}",
-            install_dir.display()
-        )));
+    let msvc_root = options.msvc_root();
+    let sdk_root = options.sdk_root();
+    let wants_msvc = options.component != QueryComponent::Sdk;
+    let wants_sdk = options.component != QueryComponent::Msvc;
+
+    // Only the roots actually queried have to exist
+    for (wanted, root) in [(wants_msvc, &msvc_root), (wants_sdk, &sdk_root)] {
+        if wanted && !root.exists() {
+            return Err(MsvcKitError::InstallPath(format!(
+                "Installation directory not found: {}",
+                root.display()
+            )));
+        }
     }
 
     // Discover installed MSVC versions
-    let msvc_info = if options.component != QueryComponent::Sdk {
-        find_msvc_component(install_dir, options.arch, options.msvc_version.as_deref())?
+    let msvc_info = if wants_msvc {
+        find_msvc_component(&msvc_root, options.arch, options.msvc_version.as_deref())?
     } else {
         None
     };
 
     // Discover installed SDK versions
-    let sdk_info = if options.component != QueryComponent::Msvc {
-        find_sdk_component(install_dir, options.arch, options.sdk_version.as_deref())?
+    let sdk_info = if wants_sdk {
+        find_sdk_component(&sdk_root, options.arch, options.sdk_version.as_deref())?
     } else {
         None
     };
 
     if msvc_info.is_none() && sdk_info.is_none() {
+        let searched = if msvc_root == sdk_root {
+            msvc_root.display().to_string()
+        } else {
+            format!("{} and {}", msvc_root.display(), sdk_root.display())
+        };
         return Err(MsvcKitError::ComponentNotFound(format!(
             "No installed components found in: {}",
-            install_dir.display()
+            searched
         )));
     }
 
+    // One of the two components may be missing; say so instead of failing
+    let mut warnings = Vec::new();
+    for (wanted, info, name, root, version) in [
+        (
+            wants_msvc,
+            &msvc_info,
+            "MSVC",
+            &msvc_root,
+            &options.msvc_version,
+        ),
+        (
+            wants_sdk,
+            &sdk_info,
+            "Windows SDK",
+            &sdk_root,
+            &options.sdk_version,
+        ),
+    ] {
+        if wanted && info.is_none() {
+            let message = match version {
+                Some(version) => format!("{} {} is not installed", name, version),
+                None => format!("no {} installation found", name),
+            };
+            warnings.push(
+                Warning::new(WarningCode::MissingComponent, message)
+                    .with_context(root.display().to_string()),
+            );
+        }
+    }
+
     // Build environment from discovered components
     let (env_vars, tools) = if let Some(ref msvc) = msvc_info {
-        let msvc_install_info = InstallInfo {
-            component_type: "msvc".to_string(),
-            version: msvc.version.clone(),
-            install_path: msvc.install_path.clone(),
-            downloaded_files: vec![],
-            arch: options.arch,
-        };
-
-        let sdk_install_info = sdk_info.as_ref().map(|sdk| InstallInfo {
-            component_type: "sdk".to_string(),
-            version: sdk.version.clone(),
-            install_path: sdk.install_path.clone(),
-            downloaded_files: vec![],
-            arch: options.arch,
-        });
-
-        let env = MsvcEnvironment::from_install_info(
-            &msvc_install_info,
-            sdk_install_info.as_ref(),
-            Architecture::host(),
-        )?;
+        let env = component_environment(msvc, sdk_info.as_ref(), options.arch, None)?;
 
         let vars = get_env_vars(&env);
         let tools = build_tool_map(&env);
@@ -452,16 +693,100 @@ pub fn query_installation(options: &QueryOptions) -> Result<QueryResult> {
         (HashMap::new(), HashMap::new())
     };
 
+    let (msvc_versions, sdk_versions) = if options.all_versions {
+        let msvc_versions = if wants_msvc {
+            list_installed_msvc(&msvc_root)
+                .iter()
+                .map(|v| msvc_component(&msvc_root, options.arch, v))
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        let sdk_versions = if wants_sdk {
+            list_installed_sdk(&sdk_root)
+                .iter()
+                .map(|v| sdk_component(options.arch, v))
+                .collect::<Result<_>>()?
+        } else {
+            Vec::new()
+        };
+        (msvc_versions, sdk_versions)
+    } else {
+        (Vec::new(), Vec::new())
+    };
+
     Ok(QueryResult {
         install_dir: install_dir.clone(),
         arch: options.arch.to_string(),
         msvc: msvc_info,
         sdk: sdk_info,
+        msvc_versions,
+        sdk_versions,
         env_vars,
         tools,
+        warnings,
     })
 }
 
+/// Build the environment of a queried MSVC toolset and SDK
+///
+/// The host architecture defaults to the one [`detect_host_arch`] picks.
+fn component_environment(
+    msvc: &ComponentInfo,
+    sdk: Option<&ComponentInfo>,
+    arch: Architecture,
+    host_arch: Option<Architecture>,
+) -> Result<MsvcEnvironment> {
+    let install_info = |info: &ComponentInfo| InstallInfo {
+        component_type: info.component_type.clone(),
+        version: info.version.clone(),
+        install_path: info.install_path.clone(),
+        downloaded_files: vec![],
+        arch,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
+    };
+    let host_arch = host_arch.unwrap_or_else(|| detect_host_arch(&msvc.install_path, arch).arch);
+    MsvcEnvironment::from_install_info_with_arch(
+        &install_info(msvc),
+        sdk.map(install_info).as_ref(),
+        host_arch,
+        arch,
+    )
+}
+
+/// Return the first `dir/name` that exists as a file
+fn find_in_paths(dirs: &[&PathBuf], name: &str) -> Option<PathBuf> {
+    let parts: Vec<&str> = name.split(['/', '\\']).filter(|p| !p.is_empty()).collect();
+    if parts.is_empty() {
+        return None;
+    }
+
+    dirs.iter().find_map(|dir| {
+        let exact = parts.iter().fold(dir.to_path_buf(), |p, part| p.join(part));
+        if exact.is_file() {
+            return Some(exact);
+        }
+        resolve_case_insensitive(dir, &parts).filter(|p| p.is_file())
+    })
+}
+
+/// Resolve `parts` under `dir`, matching each component case-insensitively
+fn resolve_case_insensitive(dir: &Path, parts: &[&str]) -> Option<PathBuf> {
+    let mut current = dir.to_path_buf();
+    for part in parts {
+        let entry = std::fs::read_dir(&current)
+            .ok()?
+            .flatten()
+            .find(|e| e.file_name().to_string_lossy().eq_ignore_ascii_case(part))?;
+        current = entry.path();
+    }
+    Some(current)
+}
+
 /// Find MSVC component in the installation directory
 fn find_msvc_component(
     install_dir: &Path,
@@ -486,6 +811,15 @@ fn find_msvc_component(
         &msvc_versions[0] // Already sorted, first = latest
     };
 
+    msvc_component(install_dir, arch, version).map(Some)
+}
+
+/// Describe an installed MSVC version
+fn msvc_component(
+    install_dir: &Path,
+    arch: Architecture,
+    version: &MsvcVersion,
+) -> Result<ComponentInfo> {
     let install_path = version.install_path.clone().ok_or_else(|| {
         MsvcKitError::InstallPath(format!(
             "MSVC install path not found for {}",
@@ -494,17 +828,23 @@ fn find_msvc_component(
     })?;
 
     let arch_str = arch.to_string();
-    let host_dir = arch.msvc_host_dir();
-    let target_dir = arch.msvc_target_dir();
+    // Same host as the environment built by `query_installation`
+    let host = detect_host_arch(&install_path, arch).arch;
+    let host_bin = install_path.join("bin").join(host.msvc_host_dir());
+    let mut bin_paths = vec![host_bin.join(arch.msvc_target_dir())];
+    if host != arch {
+        bin_paths.push(host_bin.join(host.msvc_target_dir()));
+    }
 
-    Ok(Some(ComponentInfo {
+    Ok(ComponentInfo {
         component_type: "msvc".to_string(),
         version: version.version.clone(),
         install_path: install_path.clone(),
         include_paths: vec![install_path.join("include")],
         lib_paths: vec![install_path.join("lib").join(&arch_str)],
-        bin_paths: vec![install_path.join("bin").join(host_dir).join(target_dir)],
-    }))
+        bin_paths,
+        details: details::msvc_details(install_dir, &install_path, &version.version),
+    })
 }
 
 /// Find SDK component in the installation directory
@@ -531,6 +871,11 @@ fn find_sdk_component(
         &sdk_versions[0] // Already sorted, first = latest
     };
 
+    sdk_component(arch, version).map(Some)
+}
+
+/// Describe an installed Windows SDK version
+fn sdk_component(arch: Architecture, version: &SdkVersion) -> Result<ComponentInfo> {
     let install_path = version.install_path.clone().ok_or_else(|| {
         MsvcKitError::InstallPath(format!(
             "SDK install path not found for {}",
@@ -541,7 +886,7 @@ fn find_sdk_component(
     let arch_str = arch.to_string();
     let ver = &version.version;
 
-    Ok(Some(ComponentInfo {
+    Ok(ComponentInfo {
         component_type: "sdk".to_string(),
         version: ver.clone(),
         install_path: install_path.clone(),
@@ -564,37 +909,21 @@ fn find_sdk_component(
                 .join("um")
                 .join(&arch_str),
         ],
-        bin_paths: vec![install_path.join("bin").join(ver).join(&arch_str)],
-    }))
+        bin_paths: crate::env::sdk_bin_dirs(
+            &install_path.join("bin").join(ver),
+            Architecture::native_host(),
+            arch,
+        ),
+        details: details::sdk_details(&install_path, ver),
+    })
 }
 
-/// Build a map of tool name -> tool path from MsvcEnvironment
-fn build_tool_map(env: &MsvcEnvironment) -> HashMap<String, PathBuf> {
-    let mut tools = HashMap::new();
-
-    let tool_queries = [
-        ("cl", "cl.exe"),
-        ("link", "link.exe"),
-        ("lib", "lib.exe"),
-        ("ml64", "ml64.exe"),
-        ("nmake", "nmake.exe"),
-        ("rc", "rc.exe"),
-        ("mt", "mt.exe"),
-        ("dumpbin", "dumpbin.exe"),
-        ("editbin", "editbin.exe"),
-    ];
-
-    for (name, exe) in &tool_queries {
-        for bin_path in &env.bin_paths {
-            let full_path = bin_path.join(exe);
-            if full_path.exists() {
-                tools.insert(name.to_string(), full_path);
-                break;
-            }
-        }
-    }
-
-    tools
+/// Build a map of tool -> tool path from MsvcEnvironment
+fn build_tool_map(env: &MsvcEnvironment) -> HashMap<ToolKind, PathBuf> {
+    ToolKind::ALL
+        .into_iter()
+        .filter_map(|tool| Some((tool, env.tool_path(tool)?)))
+        .collect()
 }
 
 #[cfg(test)]
@@ -699,6 +1028,7 @@ mod tests {
                 bin_paths: vec![PathBuf::from(
                     "C:/msvc-kit/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64",
                 )],
+                details: ComponentDetails::default(),
             }),
             sdk: Some(ComponentInfo {
                 component_type: "sdk".to_string(),
@@ -713,7 +1043,10 @@ mod tests {
                 bin_paths: vec![PathBuf::from(
                     "C:/msvc-kit/Windows Kits/10/bin/10.0.26100.0/x64",
                 )],
+                details: ComponentDetails::default(),
             }),
+            msvc_versions: vec![],
+            sdk_versions: vec![],
             env_vars: {
                 let mut m = HashMap::new();
                 m.insert("INCLUDE".to_string(), "C:/include".to_string());
@@ -723,17 +1056,20 @@ mod tests {
             tools: {
                 let mut m = HashMap::new();
                 m.insert(
-                    "cl".to_string(),
+                    ToolKind::Cl,
                     PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64/cl.exe"),
                 );
                 m
             },
+            warnings: Vec::new(),
         };
 
         assert_eq!(result.msvc_version(), Some("14.44.34823"));
         assert_eq!(result.sdk_version(), Some("10.0.26100.0"));
         assert!(result.tool_path("cl").is_some());
         assert!(result.tool_path("nonexistent").is_none());
+        assert!(result.tool(ToolKind::Cl).is_some());
+        assert!(result.tool(ToolKind::Link).is_none());
         assert_eq!(result.env_var("INCLUDE"), Some(&"C:/include".to_string()));
         assert_eq!(result.all_include_paths().len(), 2);
         assert_eq!(result.all_lib_paths().len(), 2);
@@ -746,8 +1082,11 @@ mod tests {
             arch: "x64".to_string(),
             msvc: None,
             sdk: None,
+            msvc_versions: vec![],
+            sdk_versions: vec![],
             env_vars: HashMap::new(),
             tools: HashMap::new(),
+            warnings: Vec::new(),
         };
 
         let json = result.to_json();
@@ -767,15 +1106,73 @@ mod tests {
                 include_paths: vec![],
                 lib_paths: vec![],
                 bin_paths: vec![],
+                details: ComponentDetails {
+                    atlmfc_version: Some("14.44.34823".to_string()),
+                    atl: true,
+                    mfc: true,
+                    spectre_arches: vec![Architecture::X64, Architecture::Arm64],
+                    ..Default::default()
+                },
             }),
             sdk: None,
+            msvc_versions: vec![],
+            sdk_versions: vec![],
             env_vars: HashMap::new(),
             tools: HashMap::new(),
+            warnings: Vec::new(),
         };
 
         let summary = result.format_summary();
         assert!(summary.contains("14.44.34823"));
         assert!(summary.contains("x64"));
+        assert!(summary.contains("ATL/MFC: 14.44.34823 (ATL, MFC)"));
+        assert!(summary.contains("Spectre libraries: x64, arm64"));
+    }
+
+    #[test]
+    fn test_query_result_capabilities() {
+        let component = |details: ComponentDetails| ComponentInfo {
+            component_type: String::new(),
+            version: String::new(),
+            install_path: PathBuf::new(),
+            include_paths: vec![],
+            lib_paths: vec![],
+            bin_paths: vec![],
+            details,
+        };
+        let mut result = QueryResult {
+            install_dir: PathBuf::from("C:/msvc-kit"),
+            arch: "x64".to_string(),
+            msvc: Some(component(ComponentDetails {
+                spectre_arches: vec![Architecture::X64],
+                asan_arches: vec![Architecture::X64, Architecture::X86],
+                target_arches: vec![Architecture::X64, Architecture::Arm64],
+                ..Default::default()
+            })),
+            sdk: Some(component(ComponentDetails {
+                target_arches: vec![Architecture::X64],
+                ..Default::default()
+            })),
+            msvc_versions: vec![],
+            sdk_versions: vec![],
+            env_vars: HashMap::new(),
+            tools: HashMap::new(),
+            warnings: Vec::new(),
+        };
+
+        assert!(result.has_spectre_libs(Architecture::X64));
+        assert!(!result.has_spectre_libs(Architecture::Arm64));
+        assert!(result.has_asan(Architecture::X86));
+        assert!(!result.has_atlmfc());
+        assert!(result.has_target(Architecture::X64));
+        // The SDK has no ARM64 libraries
+        assert!(!result.has_arm64_target());
+
+        result.sdk = None;
+        assert!(result.has_arm64_target());
+        result.msvc = None;
+        assert!(!result.has_target(Architecture::X64));
+        assert!(!result.has_asan(Architecture::X64));
     }
 
     #[test]
@@ -0,0 +1,314 @@
+//! Compiler capability probing
+//!
+//! Runs the installed `cl.exe` to report its exact version, default and
+//! supported `/std` values, and checks which CRT import/static libraries are
+//! present for the target architecture. Build systems can consume the
+//! resulting [`CompilerProbe`] instead of re-discovering this themselves.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::env::{get_env_vars, MsvcEnvironment};
+use crate::error::{MsvcKitError, Result};
+use crate::version::Architecture;
+
+/// `/std` values probed, in ascending order
+const STD_CANDIDATES: &[&str] = &[
+    "c++14",
+    "c++17",
+    "c++20",
+    "c++latest",
+    "c11",
+    "c17",
+    "clatest",
+];
+
+/// Source whose preprocessed output is the value of `_MSVC_LANG` (C++ only)
+const STD_PROBE_SOURCE: &str = "_MSVC_LANG\n";
+
+/// Presence of the CRT libraries for the target architecture
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeLibraries {
+    /// `msvcrt.lib` (release, dynamic CRT, `/MD`)
+    pub msvcrt: bool,
+    /// `msvcrtd.lib` (debug, dynamic CRT, `/MDd`)
+    pub msvcrtd: bool,
+    /// `libcmt.lib` (release, static CRT, `/MT`)
+    pub libcmt: bool,
+    /// `libcmtd.lib` (debug, static CRT, `/MTd`)
+    pub libcmtd: bool,
+    /// `ucrt.lib` (Universal CRT, from the Windows SDK)
+    pub ucrt: bool,
+    /// `ucrtd.lib` (debug Universal CRT)
+    pub ucrtd: bool,
+}
+
+impl RuntimeLibraries {
+    /// Whether release builds with `/MD` can link
+    pub fn dynamic_release(&self) -> bool {
+        self.msvcrt && self.ucrt
+    }
+
+    /// Whether debug builds with `/MDd` can link
+    pub fn dynamic_debug(&self) -> bool {
+        self.msvcrtd && self.ucrtd
+    }
+
+    /// Whether release builds with `/MT` can link
+    pub fn static_release(&self) -> bool {
+        self.libcmt && self.ucrt
+    }
+
+    /// Whether debug builds with `/MTd` can link
+    pub fn static_debug(&self) -> bool {
+        self.libcmtd && self.ucrtd
+    }
+}
+
+/// Result of probing the installed compiler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompilerProbe {
+    /// Path to the probed `cl.exe`
+    pub cl_path: PathBuf,
+    /// Compiler version from the banner (e.g. "19.44.34823")
+    pub version: Option<String>,
+    /// Full compiler banner line
+    pub banner: String,
+    /// Target architecture
+    pub arch: Architecture,
+    /// LLVM/Rust target triple (e.g. "x86_64-pc-windows-msvc")
+    pub target_triple: String,
+    /// Clang target including the MSVC version (e.g. "x86_64-pc-windows-msvc19.44.34823")
+    pub clang_target: Option<String>,
+    /// Value of `_MSVC_LANG` without `/std` (e.g. 201402)
+    pub default_cpp_standard: Option<u32>,
+    /// `/std` values accepted by this compiler
+    pub std_versions: Vec<String>,
+    /// CRT libraries available for the target architecture
+    pub runtime: RuntimeLibraries,
+}
+
+impl CompilerProbe {
+    /// Whether `/std:<value>` is supported
+    pub fn supports_std(&self, value: &str) -> bool {
+        self.std_versions
+            .iter()
+            .any(|v| v.eq_ignore_ascii_case(value))
+    }
+
+    /// Export the probe to JSON
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// LLVM/Rust target triple for an MSVC target architecture
+pub fn target_triple(arch: Architecture) -> &'static str {
+    match arch {
+        Architecture::X64 => "x86_64-pc-windows-msvc",
+        Architecture::X86 => "i686-pc-windows-msvc",
+        Architecture::Arm64 => "aarch64-pc-windows-msvc",
+        Architecture::Arm => "thumbv7a-pc-windows-msvc",
+    }
+}
+
+/// Check which CRT libraries are present in the environment's lib paths
+///
+/// This does not run the compiler, so it also works on non-Windows hosts.
+pub fn probe_runtime_libraries(env: &MsvcEnvironment) -> RuntimeLibraries {
+    let has = |name: &str| env.lib_paths.iter().any(|dir| dir.join(name).is_file());
+    RuntimeLibraries {
+        msvcrt: has("msvcrt.lib"),
+        msvcrtd: has("msvcrtd.lib"),
+        libcmt: has("libcmt.lib"),
+        libcmtd: has("libcmtd.lib"),
+        ucrt: has("ucrt.lib"),
+        ucrtd: has("ucrtd.lib"),
+    }
+}
+
+/// Probe the compiler described by `env`
+///
+/// Runs `cl /Bv` for the version banner and preprocesses a one-line source
+/// with each `/std` candidate. Requires `cl.exe` to be runnable on this host.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::query::probe_compiler;
+/// use msvc_kit::{setup_environment_with_arch, Architecture, InstallInfo};
+///
+/// # fn run(msvc: &InstallInfo) -> msvc_kit::Result<()> {
+/// let env = setup_environment_with_arch(msvc, None, Architecture::host(), msvc.arch)?;
+/// let probe = probe_compiler(&env)?;
+/// println!("cl {:?}, /MD available: {}", probe.version, probe.runtime.dynamic_release());
+/// # Ok(())
+/// # }
+/// ```
+pub fn probe_compiler(env: &MsvcEnvironment) -> Result<CompilerProbe> {
+    let cl_path = env.cl_exe_path().ok_or_else(|| {
+        MsvcKitError::ComponentNotFound("cl.exe not found in the MSVC bin paths".to_string())
+    })?;
+
+    let work_dir = std::env::temp_dir().join(format!("msvc-kit-probe-{}", std::process::id()));
+    std::fs::create_dir_all(&work_dir)?;
+    let result = run_probe(env, &cl_path, &work_dir);
+    let _ = std::fs::remove_dir_all(&work_dir);
+    result
+}
+
+fn run_probe(env: &MsvcEnvironment, cl_path: &Path, work_dir: &Path) -> Result<CompilerProbe> {
+    let env_vars = get_env_vars(env);
+    let run = |args: &[&str]| -> Result<(bool, String)> {
+        let output = Command::new(cl_path)
+            .args(args)
+            .envs(&env_vars)
+            .current_dir(work_dir)
+            .output()?;
+        let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok((output.status.success(), text))
+    };
+
+    // `/Bv` prints the banner and pass versions; cl exits non-zero without inputs
+    let (_, bv_output) = run(&["/Bv"])?;
+    let banner = bv_output
+        .lines()
+        .find(|l| l.contains("Compiler Version"))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let version = parse_banner_version(&banner);
+
+    // C++ standards are probed with a .cpp source, C standards with a .c one
+    let cpp_source = work_dir.join("probe.cpp");
+    let c_source = work_dir.join("probe.c");
+    std::fs::write(&cpp_source, STD_PROBE_SOURCE)?;
+    std::fs::write(&c_source, STD_PROBE_SOURCE)?;
+    let cpp_arg = cpp_source.to_string_lossy().into_owned();
+    let c_arg = c_source.to_string_lossy().into_owned();
+
+    let (ok, output) = run(&["/nologo", "/EP", &cpp_arg])?;
+    let default_cpp_standard = ok.then(|| parse_msvc_lang(&output)).flatten();
+
+    let mut std_versions = Vec::new();
+    for candidate in STD_CANDIDATES {
+        let flag = format!("/std:{}", candidate);
+        let source_arg = if candidate.starts_with("c++") {
+            &cpp_arg
+        } else {
+            &c_arg
+        };
+        let (ok, output) = run(&["/nologo", "/EP", &flag, source_arg])?;
+        if ok && !is_unknown_option_warning(&output) {
+            std_versions.push(candidate.to_string());
+        }
+    }
+
+    let triple = target_triple(env.arch);
+    Ok(CompilerProbe {
+        cl_path: cl_path.to_path_buf(),
+        clang_target: version.as_ref().map(|v| format!("{}{}", triple, v)),
+        version,
+        banner,
+        arch: env.arch,
+        target_triple: triple.to_string(),
+        default_cpp_standard,
+        std_versions,
+        runtime: probe_runtime_libraries(env),
+    })
+}
+
+/// Extract "19.44.34823" from "... Compiler Version 19.44.34823 for x64"
+fn parse_banner_version(banner: &str) -> Option<String> {
+    let (_, rest) = banner.split_once("Version ")?;
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+/// Parse the preprocessed `_MSVC_LANG` value (e.g. "201402L")
+fn parse_msvc_lang(output: &str) -> Option<u32> {
+    output
+        .lines()
+        .map(str::trim)
+        .find_map(|l| l.trim_end_matches('L').parse().ok())
+}
+
+/// cl accepts unknown `/std` values with warning D9002 and exit code 0
+fn is_unknown_option_warning(output: &str) -> bool {
+    output.contains("D9002")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_banner_version() {
+        let banner = "Microsoft (R) C/C++ Optimizing Compiler Version 19.44.34823 for x64";
+        assert_eq!(
+            parse_banner_version(banner),
+            Some("19.44.34823".to_string())
+        );
+        assert_eq!(parse_banner_version(""), None);
+    }
+
+    #[test]
+    fn test_parse_msvc_lang() {
+        assert_eq!(parse_msvc_lang("probe.cpp\n201402L\n"), Some(201402));
+        assert_eq!(parse_msvc_lang("_MSVC_LANG\n"), None);
+    }
+
+    #[test]
+    fn test_unknown_option_warning() {
+        assert!(is_unknown_option_warning(
+            "cl : Command line warning D9002 : ignoring unknown option '/std:c++99'"
+        ));
+        assert!(!is_unknown_option_warning("201703L"));
+    }
+
+    #[test]
+    fn test_target_triple() {
+        assert_eq!(target_triple(Architecture::X64), "x86_64-pc-windows-msvc");
+        assert_eq!(
+            target_triple(Architecture::Arm64),
+            "aarch64-pc-windows-msvc"
+        );
+    }
+
+    #[test]
+    fn test_probe_runtime_libraries() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let msvc_lib = temp_dir.path().join("msvc");
+        let sdk_lib = temp_dir.path().join("ucrt");
+        std::fs::create_dir_all(&msvc_lib).unwrap();
+        std::fs::create_dir_all(&sdk_lib).unwrap();
+        std::fs::write(msvc_lib.join("msvcrt.lib"), "").unwrap();
+        std::fs::write(msvc_lib.join("libcmt.lib"), "").unwrap();
+        std::fs::write(sdk_lib.join("ucrt.lib"), "").unwrap();
+
+        let env = MsvcEnvironment {
+            vc_install_dir: PathBuf::new(),
+            vc_tools_install_dir: PathBuf::new(),
+            vc_tools_version: "14.44.34823".to_string(),
+            windows_sdk_dir: PathBuf::new(),
+            windows_sdk_version: "10.0.26100.0".to_string(),
+            include_paths: vec![],
+            lib_paths: vec![msvc_lib, sdk_lib],
+            bin_paths: vec![],
+            arch: Architecture::X64,
+            host_arch: Architecture::X64,
+        };
+
+        let runtime = probe_runtime_libraries(&env);
+        assert!(runtime.dynamic_release());
+        assert!(runtime.static_release());
+        assert!(!runtime.dynamic_debug());
+        assert!(!runtime.static_debug());
+
+        assert!(matches!(
+            probe_compiler(&env),
+            Err(MsvcKitError::ComponentNotFound(_))
+        ));
+    }
+}
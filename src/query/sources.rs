@@ -0,0 +1,142 @@
+//! Component roots outside the msvc-kit install directory
+//!
+//! Hybrid setups take MSVC from a Visual Studio installation and the Windows
+//! SDK from msvc-kit (or the reverse). [`QueryOptions::msvc_dir`] and
+//! [`QueryOptions::sdk_dir`] accept the roots those installers use; this
+//! module locates them and maps them onto the msvc-kit layout.
+//!
+//! [`QueryOptions::msvc_dir`]: super::QueryOptions::msvc_dir
+//! [`QueryOptions::sdk_dir`]: super::QueryOptions::sdk_dir
+
+use std::path::{Path, PathBuf};
+
+/// Newest Visual Studio installation with the MSVC build tools, via `vswhere`
+///
+/// Returns the installation path, e.g.
+/// `C:\Program Files\Microsoft Visual Studio\2022\BuildTools`, or `None` if
+/// `vswhere.exe` is missing or finds no installation with the x86/x64 tools.
+pub fn find_visual_studio() -> Option<PathBuf> {
+    let program_files =
+        std::env::var_os("ProgramFiles(x86)").or_else(|| std::env::var_os("ProgramFiles"))?;
+    let vswhere = PathBuf::from(program_files)
+        .join("Microsoft Visual Studio")
+        .join("Installer")
+        .join("vswhere.exe");
+    if !vswhere.is_file() {
+        return None;
+    }
+
+    let output = std::process::Command::new(vswhere)
+        .args([
+            "-latest",
+            "-products",
+            "*",
+            "-requires",
+            "Microsoft.VisualStudio.Component.VC.Tools.x86.x64",
+            "-property",
+            "installationPath",
+            "-utf8",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!path.is_empty()).then(|| PathBuf::from(path))
+}
+
+/// Windows 10/11 SDK root installed by the Windows SDK installer
+///
+/// Read from `KitsRoot10` under `HKLM\SOFTWARE\Microsoft\Windows Kits\Installed Roots`,
+/// falling back to `%ProgramFiles(x86)%\Windows Kits\10`. `None` if neither exists.
+pub fn system_windows_kits_root() -> Option<PathBuf> {
+    registry_kits_root()
+        .or_else(|| {
+            std::env::var_os("ProgramFiles(x86)")
+                .map(|p| PathBuf::from(p).join("Windows Kits").join("10"))
+        })
+        .filter(|p| p.join("Include").is_dir())
+}
+
+#[cfg(windows)]
+fn registry_kits_root() -> Option<PathBuf> {
+    use winreg::enums::{HKEY_LOCAL_MACHINE, KEY_READ, KEY_WOW64_32KEY};
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey_with_flags(
+            r"SOFTWARE\Microsoft\Windows Kits\Installed Roots",
+            KEY_READ | KEY_WOW64_32KEY,
+        )
+        .and_then(|key| key.get_value::<String, _>("KitsRoot10"))
+        .ok()
+        .map(PathBuf::from)
+}
+
+#[cfg(not(windows))]
+fn registry_kits_root() -> Option<PathBuf> {
+    None
+}
+
+/// Root holding `VC/Tools/MSVC` for a user-supplied MSVC directory
+///
+/// Accepts a Visual Studio installation or msvc-kit directory as is, and
+/// `.../VC/Tools/MSVC` itself.
+pub(crate) fn msvc_root(dir: &Path) -> PathBuf {
+    if dir.join("VC").join("Tools").join("MSVC").is_dir() {
+        return dir.to_path_buf();
+    }
+    let ends_with_msvc = dir
+        .file_name()
+        .is_some_and(|n| n.eq_ignore_ascii_case("MSVC"))
+        && dir.join("..").join("..").join("..").join("VC").is_dir();
+    match dir.ancestors().nth(3) {
+        Some(root) if ends_with_msvc => root.to_path_buf(),
+        _ => dir.to_path_buf(),
+    }
+}
+
+/// Root holding `Windows Kits/10` for a user-supplied SDK directory
+///
+/// Accepts an msvc-kit directory as is, and a kit root such as
+/// `C:\Program Files (x86)\Windows Kits\10`, whose grandparent is returned.
+pub(crate) fn sdk_root(dir: &Path) -> PathBuf {
+    if dir.join("Windows Kits").join("10").is_dir() {
+        return dir.to_path_buf();
+    }
+    let is_kit_root = dir.file_name().is_some_and(|n| n == "10")
+        && dir
+            .parent()
+            .and_then(|p| p.file_name())
+            .is_some_and(|n| n.eq_ignore_ascii_case("Windows Kits"));
+    match dir.ancestors().nth(2) {
+        Some(root) if is_kit_root => root.to_path_buf(),
+        _ => dir.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_component_roots() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let vs = temp_dir.path().join("BuildTools");
+        let msvc = vs.join("VC").join("Tools").join("MSVC");
+        std::fs::create_dir_all(msvc.join("14.44.34823")).unwrap();
+        let kits = temp_dir.path().join("Windows Kits").join("10");
+        std::fs::create_dir_all(kits.join("Include")).unwrap();
+
+        assert_eq!(msvc_root(&vs), vs);
+        assert_eq!(msvc_root(&msvc), vs);
+        assert_eq!(sdk_root(temp_dir.path()), temp_dir.path());
+        assert_eq!(sdk_root(&kits), temp_dir.path());
+
+        // Unknown layouts are used as given
+        let other = temp_dir.path().join("other");
+        assert_eq!(msvc_root(&other), other);
+        assert_eq!(sdk_root(&other), other);
+    }
+}
@@ -0,0 +1,228 @@
+//! Disk usage breakdown for an installation directory
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::downloader::cache::default_manifest_cache_dir;
+use crate::error::{MsvcKitError, Result};
+use crate::version::{list_installed_msvc, list_installed_sdk};
+
+/// Bytes used by one installed component version
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionUsage {
+    /// Component version (e.g. "14.44.34823" or "10.0.26100.0")
+    pub version: String,
+    /// Bytes on disk
+    pub bytes: u64,
+}
+
+/// Where the bytes of an installation go
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiskUsageReport {
+    /// Installation root that was scanned
+    pub install_dir: PathBuf,
+    /// Per MSVC version (`VC/Tools/MSVC/<version>`)
+    pub msvc: Vec<VersionUsage>,
+    /// Per Windows SDK version (every `Windows Kits/10/*/<version>` directory)
+    pub sdk: Vec<VersionUsage>,
+    /// Downloaded payloads (`<install_dir>/downloads`)
+    pub downloads: u64,
+    /// Manifest cache
+    pub cache: u64,
+    /// Everything else under the installation root
+    pub other: u64,
+}
+
+impl DiskUsageReport {
+    /// Total bytes across all MSVC versions
+    pub fn msvc_total(&self) -> u64 {
+        self.msvc.iter().map(|v| v.bytes).sum()
+    }
+
+    /// Total bytes across all SDK versions
+    pub fn sdk_total(&self) -> u64 {
+        self.sdk.iter().map(|v| v.bytes).sum()
+    }
+
+    /// Total bytes of every category
+    pub fn total(&self) -> u64 {
+        self.msvc_total() + self.sdk_total() + self.downloads + self.cache + self.other
+    }
+
+    /// Export the report to JSON
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
+/// Compute the disk usage of an installation, broken down by component
+///
+/// The manifest cache is measured at its default location; use
+/// [`disk_usage_with_cache_dir`] when a custom cache directory is configured.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::query::disk_usage;
+///
+/// let usage = disk_usage("C:/msvc-kit")?;
+/// for msvc in &usage.msvc {
+///     println!("MSVC {}: {} bytes", msvc.version, msvc.bytes);
+/// }
+/// println!("Downloads: {} bytes", usage.downloads);
+/// # Ok::<(), msvc_kit::MsvcKitError>(())
+/// ```
+pub fn disk_usage(install_dir: impl AsRef<Path>) -> Result<DiskUsageReport> {
+    disk_usage_with_cache_dir(install_dir.as_ref(), &default_manifest_cache_dir())
+}
+
+/// Compute the disk usage of an installation using a specific cache directory
+pub fn disk_usage_with_cache_dir(install_dir: &Path, cache_dir: &Path) -> Result<DiskUsageReport> {
+    if !install_dir.exists() {
+        return Err(MsvcKitError::InstallPath(format!(
+            "Installation directory not found: {}",
+            install_dir.display()
+        )));
+    }
+
+    let msvc: Vec<VersionUsage> = list_installed_msvc(install_dir)
+        .into_iter()
+        .filter_map(|v| {
+            let path = v.install_path?;
+            Some(VersionUsage {
+                version: v.version,
+                bytes: dir_size(&path),
+            })
+        })
+        .collect();
+
+    let kits_dir = install_dir.join("Windows Kits").join("10");
+    let sdk: Vec<VersionUsage> = list_installed_sdk(install_dir)
+        .into_iter()
+        .map(|v| {
+            let bytes = subdirs(&kits_dir)
+                .iter()
+                .map(|d| dir_size(&d.join(&v.version)))
+                .sum();
+            VersionUsage {
+                version: v.version,
+                bytes,
+            }
+        })
+        .collect();
+
+    let downloads = dir_size(&install_dir.join("downloads"));
+    let cache = dir_size(cache_dir);
+
+    // Whatever is not attributed to a category (cache only if it lives inside)
+    let cache_inside = if cache_dir.starts_with(install_dir) {
+        cache
+    } else {
+        0
+    };
+    let attributed = msvc.iter().map(|v| v.bytes).sum::<u64>()
+        + sdk.iter().map(|v| v.bytes).sum::<u64>()
+        + downloads
+        + cache_inside;
+    let other = dir_size(install_dir).saturating_sub(attributed);
+
+    Ok(DiskUsageReport {
+        install_dir: install_dir.to_path_buf(),
+        msvc,
+        sdk,
+        downloads,
+        cache,
+        other,
+    })
+}
+
+/// Immediate subdirectories of `dir`
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Total size of regular files under `path` (symlinks are not followed)
+pub(crate) fn dir_size(path: &Path) -> u64 {
+    let mut total = 0u64;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                pending.push(entry.path());
+            } else if meta.is_file() {
+                total += meta.len();
+            }
+        }
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(path: &Path, len: usize) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, vec![0u8; len]).unwrap();
+    }
+
+    #[test]
+    fn test_disk_usage_breakdown() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("install");
+        let msvc = root.join("VC").join("Tools").join("MSVC");
+        let kits = root.join("Windows Kits").join("10");
+
+        write(&msvc.join("14.44.34823").join("bin").join("cl.exe"), 100);
+        write(&msvc.join("14.43.34808").join("lib").join("a.lib"), 50);
+        write(&kits.join("Include").join("10.0.26100.0").join("w.h"), 30);
+        write(&kits.join("Lib").join("10.0.26100.0").join("k.lib"), 20);
+        write(&root.join("downloads").join("msvc").join("p.vsix"), 40);
+        write(&root.join(".msvc-kit-extracted").join("p.vsix.done"), 5);
+        let cache_dir = root.join("cache");
+        write(&cache_dir.join("channel.json"), 7);
+
+        let usage = disk_usage_with_cache_dir(&root, &cache_dir).unwrap();
+        assert_eq!(
+            usage.msvc,
+            vec![
+                VersionUsage {
+                    version: "14.44.34823".to_string(),
+                    bytes: 100
+                },
+                VersionUsage {
+                    version: "14.43.34808".to_string(),
+                    bytes: 50
+                },
+            ]
+        );
+        assert_eq!(usage.sdk_total(), 50);
+        assert_eq!(usage.downloads, 40);
+        assert_eq!(usage.cache, 7);
+        assert_eq!(usage.other, 5);
+        assert_eq!(usage.total(), 252);
+    }
+
+    #[test]
+    fn test_disk_usage_missing_dir() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let missing = temp_dir.path().join("missing");
+        assert!(disk_usage_with_cache_dir(&missing, &missing).is_err());
+    }
+}
@@ -0,0 +1,440 @@
+//! Software bill of materials for an installed toolchain
+//!
+//! Every download directory (`downloads/msvc/...`, `downloads/sdk/...`) keeps
+//! a download index recording the Microsoft packages that were fetched and the
+//! SHA256 of each payload. [`export`] turns those records into a CycloneDX 1.5
+//! or SPDX 2.3 JSON document, so security teams can track the packages that
+//! compose a build toolchain.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::downloader::{compute_hash, DownloadIndex};
+use crate::error::{MsvcKitError, Result};
+use crate::license::LICENSE_URL;
+
+/// SPDX license reference of the Visual Studio license terms
+pub const LICENSE_REF: &str = "LicenseRef-Microsoft-Visual-Studio";
+
+/// SBOM document format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 JSON
+    #[default]
+    CycloneDx,
+    /// SPDX 2.3 JSON
+    Spdx,
+}
+
+impl std::fmt::Display for SbomFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SbomFormat::CycloneDx => write!(f, "cyclonedx"),
+            SbomFormat::Spdx => write!(f, "spdx"),
+        }
+    }
+}
+
+impl std::str::FromStr for SbomFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "cyclonedx" | "cdx" => Ok(SbomFormat::CycloneDx),
+            "spdx" => Ok(SbomFormat::Spdx),
+            _ => Err(format!(
+                "Unknown SBOM format: {} (expected cyclonedx or spdx)",
+                s
+            )),
+        }
+    }
+}
+
+/// A downloaded payload of an [`SbomPackage`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomPayload {
+    /// Payload file name
+    pub file_name: String,
+    /// Download URL
+    pub url: String,
+    /// SHA256 of the downloaded file (or the manifest hash if not computed)
+    pub sha256: Option<String>,
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// A Microsoft package that is part of the installation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SbomPackage {
+    /// Package ID from the manifest; the payload file name for downloads
+    /// that predate package records
+    pub id: String,
+    /// Package version (empty if unknown)
+    pub version: String,
+    /// Download directory the package came from (`msvc` or `sdk`)
+    pub component: String,
+    /// Downloaded payloads
+    pub payloads: Vec<SbomPayload>,
+}
+
+/// Download index databases below `downloads/`, sorted
+pub(crate) fn find_indexes(install_dir: &Path) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut pending = vec![install_dir.join("downloads")];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir).into_iter().flatten().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if entry.file_name() == "index.db" {
+                found.push(path);
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Packages recorded in the download indexes of `install_dir`
+///
+/// Packages downloaded for several architectures are listed once. Payloads
+/// without a package record (downloads made by older versions) are listed as
+/// packages of their own, named after the file.
+pub async fn collect_packages(install_dir: &Path) -> Result<Vec<SbomPackage>> {
+    let indexes = find_indexes(install_dir);
+    if indexes.is_empty() {
+        return Err(MsvcKitError::ComponentNotFound(format!(
+            "No download index found in {}",
+            install_dir.join("downloads").display()
+        )));
+    }
+
+    let mut packages: BTreeMap<(String, String), SbomPackage> = BTreeMap::new();
+    for index_path in indexes {
+        let component = index_path
+            .strip_prefix(install_dir.join("downloads"))
+            .ok()
+            .and_then(|rel| rel.components().next())
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let index = DownloadIndex::load(&index_path).await?;
+        let mut payloads: BTreeMap<String, SbomPayload> = index
+            .entries()
+            .await?
+            .into_iter()
+            .map(|entry| {
+                let payload = SbomPayload {
+                    file_name: entry.file_name.clone(),
+                    url: entry.url,
+                    sha256: entry.computed_hash.or(entry.sha256),
+                    size: entry.size,
+                };
+                (entry.file_name, payload)
+            })
+            .collect();
+
+        for package in index.packages().await? {
+            let sbom = packages
+                .entry((package.id.clone(), package.version.clone()))
+                .or_insert_with(|| SbomPackage {
+                    id: package.id,
+                    version: package.version,
+                    component: component.clone(),
+                    payloads: Vec::new(),
+                });
+            for file_name in &package.payloads {
+                if let Some(payload) = payloads.remove(file_name) {
+                    if !sbom.payloads.contains(&payload) {
+                        sbom.payloads.push(payload);
+                    }
+                }
+            }
+        }
+        for (file_name, payload) in payloads {
+            packages
+                .entry((file_name.clone(), String::new()))
+                .or_insert_with(|| SbomPackage {
+                    id: file_name,
+                    version: String::new(),
+                    component: component.clone(),
+                    payloads: vec![payload],
+                });
+        }
+    }
+    Ok(packages.into_values().collect())
+}
+
+/// Export the SBOM of the installation at `install_dir` as a JSON document
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::sbom::{export, SbomFormat};
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// let document = export("C:/msvc-kit".as_ref(), SbomFormat::CycloneDx).await?;
+/// std::fs::write("msvc-kit.cdx.json", document)?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn export(install_dir: &Path, format: SbomFormat) -> Result<String> {
+    let packages = collect_packages(install_dir).await?;
+    let document = render(&packages, format, Utc::now());
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Build the document for `packages`
+fn render(packages: &[SbomPackage], format: SbomFormat, now: DateTime<Utc>) -> Value {
+    match format {
+        SbomFormat::CycloneDx => cyclonedx(packages, now),
+        SbomFormat::Spdx => spdx(packages, now),
+    }
+}
+
+/// Name-based UUID for the document, from the package list and time
+fn document_uuid(packages: &[SbomPackage], now: DateTime<Utc>) -> String {
+    let ids: Vec<String> = packages
+        .iter()
+        .map(|p| format!("{}@{}", p.id, p.version))
+        .collect();
+    let hash = compute_hash(format!("{}\n{}", now.to_rfc3339(), ids.join("\n")).as_bytes());
+    // Version 5 and RFC 4122 variant bits
+    format!(
+        "{}-{}-5{}-{:x}{}-{}",
+        &hash[0..8],
+        &hash[8..12],
+        &hash[13..16],
+        (u8::from_str_radix(&hash[16..17], 16).unwrap_or(0) & 0x3) | 0x8,
+        &hash[17..20],
+        &hash[20..32]
+    )
+}
+
+fn timestamp(now: DateTime<Utc>) -> String {
+    now.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn cyclonedx(packages: &[SbomPackage], now: DateTime<Utc>) -> Value {
+    let hashes = |payload: &SbomPayload| -> Vec<Value> {
+        payload
+            .sha256
+            .iter()
+            .map(|sha256| json!({ "alg": "SHA-256", "content": sha256.to_lowercase() }))
+            .collect()
+    };
+    let components: Vec<Value> = packages
+        .iter()
+        .map(|package| {
+            let bom_ref = format!("{}@{}", package.id, package.version);
+            let mut component = json!({
+                "type": "library",
+                "bom-ref": bom_ref,
+                "name": package.id,
+                "supplier": { "name": "Microsoft Corporation" },
+                "licenses": [{ "license": { "name": LICENSE_REF, "url": LICENSE_URL } }],
+                "externalReferences": package.payloads.iter()
+                    .map(|p| json!({ "type": "distribution", "url": p.url }))
+                    .collect::<Vec<_>>(),
+                "properties": [{ "name": "msvc-kit:component", "value": package.component }],
+            });
+            if !package.version.is_empty() {
+                component["version"] = json!(package.version);
+            }
+            match package.payloads.as_slice() {
+                [payload] => component["hashes"] = json!(hashes(payload)),
+                payloads => {
+                    component["components"] = payloads
+                        .iter()
+                        .map(|p| {
+                            json!({
+                                "type": "file",
+                                "bom-ref": format!("{}/{}", bom_ref, p.file_name),
+                                "name": p.file_name,
+                                "hashes": hashes(p),
+                            })
+                        })
+                        .collect();
+                }
+            }
+            component
+        })
+        .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "serialNumber": format!("urn:uuid:{}", document_uuid(packages, now)),
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp(now),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "msvc-kit",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }]
+            },
+        },
+        "components": components,
+    })
+}
+
+fn spdx(packages: &[SbomPackage], now: DateTime<Utc>) -> Value {
+    let spdx_packages: Vec<Value> = packages
+        .iter()
+        .enumerate()
+        .map(|(i, package)| {
+            let mut entry = json!({
+                "SPDXID": format!("SPDXRef-Package-{}", i + 1),
+                "name": package.id,
+                "supplier": "Organization: Microsoft Corporation",
+                "downloadLocation": package.payloads.first()
+                    .map(|p| p.url.as_str())
+                    .unwrap_or("NOASSERTION"),
+                "filesAnalyzed": false,
+                "checksums": package.payloads.iter()
+                    .filter_map(|p| p.sha256.as_ref())
+                    .map(|sha256| json!({
+                        "algorithm": "SHA256",
+                        "checksumValue": sha256.to_lowercase(),
+                    }))
+                    .collect::<Vec<_>>(),
+                "licenseConcluded": "NOASSERTION",
+                "licenseDeclared": LICENSE_REF,
+                "copyrightText": "NOASSERTION",
+                "comment": format!("msvc-kit component: {}", package.component),
+            });
+            if !package.version.is_empty() {
+                entry["versionInfo"] = json!(package.version);
+            }
+            entry
+        })
+        .collect();
+    let relationships: Vec<Value> = (1..=packages.len())
+        .map(|i| {
+            json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": format!("SPDXRef-Package-{}", i),
+            })
+        })
+        .collect();
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "msvc-kit-toolchain",
+        "documentNamespace": format!(
+            "https://github.com/loonghao/msvc-kit/sbom/{}",
+            document_uuid(packages, now)
+        ),
+        "creationInfo": {
+            "created": timestamp(now),
+            "creators": [format!("Tool: msvc-kit-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": spdx_packages,
+        "relationships": relationships,
+        "hasExtractedLicensingInfos": [{
+            "licenseId": LICENSE_REF,
+            "name": "Microsoft Visual Studio License Terms",
+            "extractedText": format!("See {}", LICENSE_URL),
+            "seeAlsos": [LICENSE_URL],
+        }],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::downloader::{DownloadStatus, IndexEntry, IndexedPackage};
+
+    fn entry(dir: &Path, name: &str) -> IndexEntry {
+        IndexEntry {
+            file_name: name.to_string(),
+            url: format!("https://download.example.com/{}", name),
+            size: 4,
+            sha256: Some("AB".repeat(32)),
+            computed_hash: Some("ab".repeat(32)),
+            local_path: dir.join(name),
+            status: DownloadStatus::Completed,
+            bytes_downloaded: 4,
+            hash_verified: true,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_collect_packages_and_render() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let msvc = root.join("downloads/msvc/14_44_x64_x64");
+        {
+            let mut index = DownloadIndex::load(&msvc.join("index.db")).await.unwrap();
+            for name in ["headers.vsix", "tools.vsix", "tools2.vsix", "orphan.vsix"] {
+                index.upsert_entry(&entry(&msvc, name)).await.unwrap();
+            }
+            for (id, payloads) in [
+                ("Microsoft.VC.14.44.CRT.Headers", vec!["headers.vsix"]),
+                (
+                    "Microsoft.VC.14.44.Tools",
+                    vec!["tools.vsix", "tools2.vsix"],
+                ),
+            ] {
+                let package = IndexedPackage {
+                    id: id.to_string(),
+                    version: "14.44.34823".to_string(),
+                    chip: None,
+                    payloads: payloads.into_iter().map(String::from).collect(),
+                };
+                index.record_package(&package).await.unwrap();
+            }
+        }
+
+        let packages = collect_packages(root).await.unwrap();
+        let ids: Vec<&str> = packages.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            [
+                "Microsoft.VC.14.44.CRT.Headers",
+                "Microsoft.VC.14.44.Tools",
+                "orphan.vsix"
+            ]
+        );
+        assert_eq!(packages[1].payloads.len(), 2);
+        assert_eq!(packages[0].component, "msvc");
+        assert_eq!(packages[0].payloads[0].sha256, Some("ab".repeat(32)));
+
+        let now = Utc::now();
+        let cdx = render(&packages, SbomFormat::CycloneDx, now);
+        assert_eq!(cdx["bomFormat"], "CycloneDX");
+        let serial = cdx["serialNumber"].as_str().unwrap();
+        assert_eq!(serial.len(), "urn:uuid:".len() + 36);
+        assert_eq!(&serial[23..24], "5");
+        assert_eq!(cdx["components"][0]["version"], "14.44.34823");
+        assert_eq!(cdx["components"][0]["hashes"][0]["alg"], "SHA-256");
+        assert_eq!(cdx["components"][1]["components"][1]["name"], "tools2.vsix");
+        assert!(cdx["components"][2].get("version").is_none());
+
+        let spdx = render(&packages, SbomFormat::Spdx, now);
+        assert_eq!(spdx["spdxVersion"], "SPDX-2.3");
+        assert_eq!(
+            spdx["packages"][1]["checksums"].as_array().unwrap().len(),
+            2
+        );
+        assert_eq!(spdx["packages"][0]["licenseDeclared"], LICENSE_REF);
+        assert_eq!(spdx["relationships"].as_array().unwrap().len(), 3);
+
+        assert!(matches!(
+            collect_packages(&root.join("missing")).await,
+            Err(MsvcKitError::ComponentNotFound(_))
+        ));
+        assert_eq!("cdx".parse::<SbomFormat>().unwrap(), SbomFormat::CycloneDx);
+        assert!("swid".parse::<SbomFormat>().is_err());
+    }
+}
@@ -16,9 +16,10 @@
 //! - **Absolute scripts**: Use absolute paths for installed environments
 
 use crate::error::{MsvcKitError, Result};
-use crate::version::Architecture;
+use crate::version::{list_installed_msvc, list_installed_sdk, Architecture};
+use crate::winpath::{self, UnixFlavor};
 use askama::Template;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Shell type for script generation
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -81,6 +82,66 @@ impl std::fmt::Display for ShellType {
     }
 }
 
+/// Language of the generated README and script messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    /// English
+    #[default]
+    En,
+    /// Simplified Chinese
+    ZhCn,
+}
+
+impl Locale {
+    /// Message printed once a script has activated the toolchain
+    fn activated_message(&self) -> &'static str {
+        match self {
+            Locale::En => "MSVC Toolchain activated",
+            Locale::ZhCn => "MSVC 工具链已激活",
+        }
+    }
+}
+
+impl std::fmt::Display for Locale {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Locale::En => write!(f, "en"),
+            Locale::ZhCn => write!(f, "zh-CN"),
+        }
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "en" | "en-us" => Ok(Locale::En),
+            "zh" | "zh-cn" | "zh-hans" => Ok(Locale::ZhCn),
+            _ => Err(format!("Unknown locale: {} (expected en or zh-CN)", s)),
+        }
+    }
+}
+
+impl serde::Serialize for Locale {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Locale {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
 /// Context for generating MSVC environment scripts
 ///
 /// This struct contains all the information needed to generate activation
@@ -100,6 +161,10 @@ pub struct ScriptContext {
     pub portable: bool,
     /// Root path (only used for absolute scripts)
     pub root: Option<PathBuf>,
+    /// Language of the README and script messages
+    pub locale: Locale,
+    /// How Bash scripts spell absolute paths (Git Bash or WSL)
+    pub unix_flavor: UnixFlavor,
 }
 
 impl ScriptContext {
@@ -117,6 +182,8 @@ impl ScriptContext {
             host_arch,
             portable: true,
             root: None,
+            locale: Locale::default(),
+            unix_flavor: UnixFlavor::default(),
         }
     }
 
@@ -135,9 +202,23 @@ impl ScriptContext {
             host_arch,
             portable: false,
             root: Some(root),
+            locale: Locale::default(),
+            unix_flavor: UnixFlavor::default(),
         }
     }
 
+    /// Use the given locale for the README and script messages
+    pub fn with_locale(mut self, locale: Locale) -> Self {
+        self.locale = locale;
+        self
+    }
+
+    /// Spell absolute paths in Bash scripts for the given shell flavor
+    pub fn with_unix_flavor(mut self, flavor: UnixFlavor) -> Self {
+        self.unix_flavor = flavor;
+        self
+    }
+
     /// Get the host architecture directory name (e.g., "Hostx64")
     pub fn host_arch_dir(&self) -> &'static str {
         self.host_arch.msvc_host_dir()
@@ -166,13 +247,7 @@ impl ScriptContext {
                 .expect("root path required for absolute scripts");
             match shell {
                 ShellType::Cmd | ShellType::PowerShell => root.to_string_lossy().to_string(),
-                ShellType::Bash => {
-                    // Convert Windows path to Unix-style for bash
-                    root.to_string_lossy()
-                        .replace('\\', "/")
-                        .replace("C:", "/c")
-                        .replace("D:", "/d")
-                }
+                ShellType::Bash => winpath::to_unix(&root.to_string_lossy(), self.unix_flavor),
             }
         }
     }
@@ -188,7 +263,12 @@ struct CmdScriptTemplate<'a> {
     sdk_version: &'a str,
     arch: String,
     host_arch: String,
+    host_arch_name: String,
     target_arch: String,
+    platform: &'static str,
+    cross: bool,
+    activated: &'a str,
+    utf8_banner: bool,
 }
 
 /// PowerShell script template (used for both portable and absolute)
@@ -199,7 +279,11 @@ struct PowerShellScriptTemplate<'a> {
     sdk_version: &'a str,
     arch: String,
     host_arch: String,
+    host_arch_name: String,
     target_arch: String,
+    platform: &'static str,
+    cross: bool,
+    activated: &'a str,
 }
 
 /// Bash script template (used for both portable and absolute)
@@ -210,7 +294,23 @@ struct BashScriptTemplate<'a> {
     sdk_version: &'a str,
     arch: String,
     host_arch: String,
+    host_arch_name: String,
     target_arch: String,
+    platform: &'static str,
+    cross: bool,
+    activated: &'a str,
+}
+
+/// PowerShell module template (used for both portable and absolute)
+#[derive(Template)]
+#[template(path = "MsvcKit.psm1.txt")]
+struct PowerShellModuleTemplate<'a> {
+    msvc_version: &'a str,
+    sdk_version: &'a str,
+    arch: String,
+    host_arch: String,
+    host_arch_name: String,
+    activated: &'a str,
 }
 
 /// README template
@@ -222,6 +322,15 @@ struct ReadmeTemplate<'a> {
     arch: String,
 }
 
+/// README template (Simplified Chinese)
+#[derive(Template)]
+#[template(path = "readme.zh-CN.txt")]
+struct ReadmeZhCnTemplate<'a> {
+    msvc_version: &'a str,
+    sdk_version: &'a str,
+    arch: String,
+}
+
 // ==================== Generated Scripts ====================
 
 /// Collection of generated scripts
@@ -235,6 +344,8 @@ pub struct GeneratedScripts {
     pub bash: String,
     /// README content (only for portable bundles)
     pub readme: Option<String>,
+    /// `MsvcKit.psm1` module content (None skips writing the module)
+    pub powershell_module: Option<String>,
 }
 
 impl GeneratedScripts {
@@ -254,35 +365,92 @@ impl GeneratedScripts {
 ///
 /// Creates scripts that use relative paths so the bundle can be moved anywhere.
 pub fn generate_portable_scripts(ctx: &ScriptContext) -> Result<GeneratedScripts> {
-    let cmd = render_cmd(ctx)?;
-    let powershell = render_powershell(ctx)?;
-    let bash = render_bash(ctx)?;
-    let readme = render_readme(ctx)?;
-
-    Ok(GeneratedScripts {
-        cmd,
-        powershell,
-        bash,
-        readme: Some(readme),
-    })
+    generate_scripts(ctx, true)
 }
 
 /// Generate activation scripts with absolute paths
 ///
 /// Creates scripts that use absolute paths from the provided context.
 pub fn generate_absolute_scripts(ctx: &ScriptContext) -> Result<GeneratedScripts> {
-    let cmd = render_cmd(ctx)?;
-    let powershell = render_powershell(ctx)?;
-    let bash = render_bash(ctx)?;
+    generate_scripts(ctx, false)
+}
 
+/// Render the scripts of every shell and the PowerShell module, plus the README if asked
+fn generate_scripts(ctx: &ScriptContext, with_readme: bool) -> Result<GeneratedScripts> {
     Ok(GeneratedScripts {
-        cmd,
-        powershell,
-        bash,
-        readme: None,
+        cmd: render_cmd(ctx)?,
+        powershell: render_powershell(ctx)?,
+        bash: render_bash(ctx)?,
+        readme: with_readme.then(|| render_readme(ctx)).transpose()?,
+        powershell_module: Some(render_powershell_module(ctx)?),
     })
 }
 
+/// Absolute script context for the newest MSVC toolset and Windows SDK in `install_dir`
+///
+/// The host architecture is the native one if its compiler for `arch` is
+/// installed, otherwise the best installed one (see
+/// [`detect_host_arch`](crate::env::detect_host_arch)).
+pub fn install_script_context(install_dir: &Path, arch: Architecture) -> Result<ScriptContext> {
+    let msvc = list_installed_msvc(install_dir)
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            MsvcKitError::ComponentNotFound(format!(
+                "No MSVC installation found in {}",
+                install_dir.display()
+            ))
+        })?;
+    let sdk = list_installed_sdk(install_dir)
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            MsvcKitError::ComponentNotFound(format!(
+                "No Windows SDK installation found in {}",
+                install_dir.display()
+            ))
+        })?;
+    let vc_tools_dir = msvc.install_path.clone().unwrap_or_else(|| {
+        install_dir
+            .join("VC")
+            .join("Tools")
+            .join("MSVC")
+            .join(&msvc.version)
+    });
+    let host_arch = crate::env::detect_host_arch(&vc_tools_dir, arch).arch;
+
+    Ok(ScriptContext::absolute(
+        install_dir.to_path_buf(),
+        msvc.version,
+        sdk.version,
+        arch,
+        host_arch,
+    ))
+}
+
+/// Activation scripts for every shell for an installed (non-bundle) toolchain
+///
+/// Picks the newest MSVC toolset and Windows SDK in `install_dir`, like
+/// `msvc-kit env --script` does, and renders them with absolute paths.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use msvc_kit::scripts::{save_scripts, scripts_for_install};
+/// use msvc_kit::Architecture;
+/// use std::path::Path;
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// let install_dir = Path::new("C:/msvc-kit");
+/// let scripts = scripts_for_install(install_dir, Architecture::X64)?;
+/// save_scripts(&scripts, install_dir, "activate").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn scripts_for_install(install_dir: &Path, arch: Architecture) -> Result<GeneratedScripts> {
+    generate_absolute_scripts(&install_script_context(install_dir, arch)?)
+}
+
 /// Generate a single script for the specified shell
 pub fn generate_script(ctx: &ScriptContext, shell: ShellType) -> Result<String> {
     match shell {
@@ -297,7 +465,29 @@ pub fn generate_absolute_script(ctx: &ScriptContext, shell: ShellType) -> Result
     generate_script(ctx, shell)
 }
 
+/// File name of the generated PowerShell module
+pub const POWERSHELL_MODULE_FILE: &str = "MsvcKit.psm1";
+
+/// File names [`save_scripts`] writes for `scripts`
+pub fn script_file_names(scripts: &GeneratedScripts, base_name: &str) -> Vec<String> {
+    let mut names = vec![
+        format!("{}.bat", base_name),
+        format!("{}.ps1", base_name),
+        format!("{}.sh", base_name),
+    ];
+    if scripts.readme.is_some() {
+        names.push("README.txt".to_string());
+    }
+    if scripts.powershell_module.is_some() {
+        names.push(POWERSHELL_MODULE_FILE.to_string());
+    }
+    names
+}
+
 /// Save scripts to a directory
+///
+/// Writes `<base_name>.bat`, `<base_name>.ps1` and `<base_name>.sh`, plus
+/// `README.txt` and `MsvcKit.psm1` when present.
 pub async fn save_scripts(
     scripts: &GeneratedScripts,
     output_dir: &std::path::Path,
@@ -314,7 +504,7 @@ pub async fn save_scripts(
     tokio::fs::write(&cmd_path, &scripts.cmd)
         .await
         .map_err(MsvcKitError::Io)?;
-    tokio::fs::write(&ps_path, &scripts.powershell)
+    tokio::fs::write(&ps_path, with_powershell_bom(&scripts.powershell))
         .await
         .map_err(MsvcKitError::Io)?;
     tokio::fs::write(&bash_path, &scripts.bash)
@@ -328,18 +518,43 @@ pub async fn save_scripts(
             .map_err(MsvcKitError::Io)?;
     }
 
+    if let Some(module) = &scripts.powershell_module {
+        let module_path = output_dir.join(POWERSHELL_MODULE_FILE);
+        tokio::fs::write(&module_path, with_powershell_bom(module))
+            .await
+            .map_err(MsvcKitError::Io)?;
+    }
+
     Ok(())
 }
 
+/// Prefix non-ASCII PowerShell content with a UTF-8 BOM
+///
+/// Windows PowerShell 5.1 reads BOM-less scripts as ANSI, so localized
+/// scripts would otherwise be garbled.
+fn with_powershell_bom(content: &str) -> String {
+    if content.is_ascii() {
+        content.to_string()
+    } else {
+        format!("\u{feff}{}", content)
+    }
+}
+
 // ==================== Internal Render Functions ====================
 
 fn render_cmd(ctx: &ScriptContext) -> Result<String> {
+    let activated = ctx.locale.activated_message();
     let template = CmdScriptTemplate {
         msvc_version: &ctx.msvc_version,
         sdk_version: &ctx.sdk_version,
         arch: ctx.arch.to_string(),
         host_arch: ctx.host_arch_dir().to_string(),
+        host_arch_name: ctx.host_arch.to_string(),
         target_arch: ctx.target_arch_dir().to_string(),
+        platform: ctx.arch.msbuild_platform(),
+        cross: ctx.host_arch != ctx.arch,
+        activated,
+        utf8_banner: !activated.is_ascii(),
     };
 
     let rendered = template
@@ -372,7 +587,11 @@ fn render_powershell(ctx: &ScriptContext) -> Result<String> {
         sdk_version: &ctx.sdk_version,
         arch: ctx.arch.to_string(),
         host_arch: ctx.host_arch_dir().to_string(),
+        host_arch_name: ctx.host_arch.to_string(),
         target_arch: ctx.target_arch_dir().to_string(),
+        platform: ctx.arch.msbuild_platform(),
+        cross: ctx.host_arch != ctx.arch,
+        activated: ctx.locale.activated_message(),
     };
 
     let rendered = template
@@ -397,13 +616,51 @@ fn render_powershell(ctx: &ScriptContext) -> Result<String> {
     }
 }
 
+fn render_powershell_module(ctx: &ScriptContext) -> Result<String> {
+    let template = PowerShellModuleTemplate {
+        msvc_version: &ctx.msvc_version,
+        sdk_version: &ctx.sdk_version,
+        arch: ctx.arch.to_string(),
+        host_arch: ctx.host_arch_dir().to_string(),
+        host_arch_name: ctx.host_arch.to_string(),
+        activated: ctx.locale.activated_message(),
+    };
+
+    let rendered = template.render().map_err(|e| {
+        MsvcKitError::Other(format!(
+            "Failed to render PowerShell module template: {}",
+            e
+        ))
+    })?;
+
+    // For absolute modules, replace $BundleRoot with actual path
+    if !ctx.portable {
+        let root = ctx.root_expr(ShellType::PowerShell);
+        Ok(rendered
+            .replace("$BundleRoot", &root)
+            .lines()
+            .filter(|line| {
+                !line.contains("$PSScriptRoot")
+                    && !line.contains("Get the directory where this script is located")
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    } else {
+        Ok(rendered)
+    }
+}
+
 fn render_bash(ctx: &ScriptContext) -> Result<String> {
     let template = BashScriptTemplate {
         msvc_version: &ctx.msvc_version,
         sdk_version: &ctx.sdk_version,
         arch: ctx.arch.to_string(),
         host_arch: ctx.host_arch_dir().to_string(),
+        host_arch_name: ctx.host_arch.to_string(),
         target_arch: ctx.target_arch_dir().to_string(),
+        platform: ctx.arch.msbuild_platform(),
+        cross: ctx.host_arch != ctx.arch,
+        activated: ctx.locale.activated_message(),
     };
 
     let rendered = template
@@ -432,15 +689,22 @@ fn render_bash(ctx: &ScriptContext) -> Result<String> {
 }
 
 fn render_readme(ctx: &ScriptContext) -> Result<String> {
-    let template = ReadmeTemplate {
-        msvc_version: &ctx.msvc_version,
-        sdk_version: &ctx.sdk_version,
-        arch: ctx.arch.to_string(),
+    let rendered = match ctx.locale {
+        Locale::En => ReadmeTemplate {
+            msvc_version: &ctx.msvc_version,
+            sdk_version: &ctx.sdk_version,
+            arch: ctx.arch.to_string(),
+        }
+        .render(),
+        Locale::ZhCn => ReadmeZhCnTemplate {
+            msvc_version: &ctx.msvc_version,
+            sdk_version: &ctx.sdk_version,
+            arch: ctx.arch.to_string(),
+        }
+        .render(),
     };
 
-    template
-        .render()
-        .map_err(|e| MsvcKitError::Other(format!("Failed to render README template: {}", e)))
+    rendered.map_err(|e| MsvcKitError::Other(format!("Failed to render README template: {}", e)))
 }
 
 #[cfg(test)]
@@ -498,6 +762,34 @@ mod tests {
         assert_eq!(ctx.root_expr(ShellType::Bash), "/c/msvc-kit");
     }
 
+    #[test]
+    fn test_absolute_bash_root_flavors() {
+        let ctx = |root: &str| {
+            ScriptContext::absolute(
+                PathBuf::from(root),
+                "14.44.34823",
+                "10.0.26100.0",
+                Architecture::X64,
+                Architecture::X64,
+            )
+        };
+
+        assert_eq!(
+            ctx("E:\\msvc-kit").root_expr(ShellType::Bash),
+            "/e/msvc-kit"
+        );
+        assert_eq!(
+            ctx("\\\\build-01\\tools\\msvc-kit").root_expr(ShellType::Bash),
+            "//build-01/tools/msvc-kit"
+        );
+        assert_eq!(
+            ctx("C:\\msvc-kit")
+                .with_unix_flavor(UnixFlavor::Wsl)
+                .root_expr(ShellType::Bash),
+            "/mnt/c/msvc-kit"
+        );
+    }
+
     #[test]
     fn test_generate_portable_scripts() {
         let ctx = ScriptContext::portable(
@@ -516,6 +808,71 @@ mod tests {
         assert!(scripts.readme.is_some());
     }
 
+    #[test]
+    fn test_generate_powershell_module() {
+        let portable = ScriptContext::portable(
+            "14.44.34823",
+            "10.0.26100.0",
+            Architecture::X64,
+            Architecture::X64,
+        );
+        let module = generate_portable_scripts(&portable)
+            .unwrap()
+            .powershell_module
+            .unwrap();
+        assert!(module.contains("function Enter-MsvcEnv"));
+        assert!(module.contains("function Exit-MsvcEnv"));
+        assert!(module.contains("[string]$Arch = \"x64\""));
+        assert!(module.contains("$BundleRoot = $PSScriptRoot"));
+        assert!(module.contains("Export-ModuleMember"));
+
+        let absolute = ScriptContext::absolute(
+            PathBuf::from("C:\\msvc-kit"),
+            "14.44.34823",
+            "10.0.26100.0",
+            Architecture::X64,
+            Architecture::X64,
+        );
+        let module = generate_absolute_scripts(&absolute)
+            .unwrap()
+            .powershell_module
+            .unwrap();
+        assert!(!module.contains("$PSScriptRoot"));
+        assert!(module.contains("C:\\msvc-kit\\VC\\Tools\\MSVC\\14.44.34823\\bin\\Hostx64\\$Arch"));
+    }
+
+    #[test]
+    fn test_generate_localized_scripts() {
+        let ctx = ScriptContext::portable(
+            "14.44.34823",
+            "10.0.26100.0",
+            Architecture::X64,
+            Architecture::X64,
+        )
+        .with_locale(Locale::ZhCn);
+
+        let scripts = generate_portable_scripts(&ctx).unwrap();
+
+        assert!(scripts.cmd.contains("chcp 65001"));
+        assert!(scripts.cmd.contains("MSVC 工具链已激活"));
+        assert!(scripts.powershell.contains("MSVC 工具链已激活"));
+        assert!(scripts.bash.contains("MSVC 工具链已激活"));
+        assert!(scripts.readme.unwrap().contains("14.44.34823"));
+
+        let english = generate_portable_scripts(&ctx.with_locale(Locale::En)).unwrap();
+        assert!(!english.cmd.contains("chcp"));
+        assert!(english.cmd.contains("MSVC Toolchain activated"));
+    }
+
+    #[test]
+    fn test_locale_from_str() {
+        assert_eq!("en".parse::<Locale>().unwrap(), Locale::En);
+        assert_eq!("zh-CN".parse::<Locale>().unwrap(), Locale::ZhCn);
+        assert_eq!("zh_cn".parse::<Locale>().unwrap(), Locale::ZhCn);
+        assert!("fr".parse::<Locale>().is_err());
+        assert_eq!(Locale::ZhCn.to_string(), "zh-CN");
+    }
+
     #[test]
     fn test_generate_absolute_scripts() {
         let ctx = ScriptContext::absolute(
@@ -538,6 +895,32 @@ mod tests {
         assert!(scripts.readme.is_none());
     }
 
+    #[test]
+    fn test_scripts_for_install() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path();
+        let msvc = root.join("VC").join("Tools").join("MSVC");
+        std::fs::create_dir_all(msvc.join("14.43.34808")).unwrap();
+        std::fs::create_dir_all(msvc.join("14.44.34823").join("bin/Hostx86/x86")).unwrap();
+        assert!(matches!(
+            scripts_for_install(root, Architecture::X86),
+            Err(MsvcKitError::ComponentNotFound(_))
+        ));
+
+        std::fs::create_dir_all(root.join("Windows Kits/10/Include/10.0.26100.0")).unwrap();
+        let ctx = install_script_context(root, Architecture::X86).unwrap();
+        assert_eq!(ctx.msvc_version, "14.44.34823");
+        assert_eq!(ctx.sdk_version, "10.0.26100.0");
+        assert_eq!(ctx.root.as_deref(), Some(root));
+        assert!(!ctx.portable);
+
+        let scripts = scripts_for_install(root, Architecture::X86).unwrap();
+        assert!(scripts.cmd.contains("14.44.34823"));
+        assert!(scripts.cmd.contains(&root.display().to_string()));
+        assert!(scripts.readme.is_none());
+        assert!(scripts.powershell_module.is_some());
+    }
+
     #[test]
     fn test_shell_type_display() {
         assert_eq!(format!("{}", ShellType::Cmd), "cmd");
@@ -552,6 +935,7 @@ mod tests {
             powershell: "ps content".to_string(),
             bash: "bash content".to_string(),
             readme: Some("readme content".to_string()),
+            powershell_module: None,
         };
 
         assert_eq!(scripts.get(ShellType::Cmd), "cmd content");
@@ -633,6 +1017,66 @@ mod tests {
         assert_eq!(ctx.target_arch_dir(), "x86");
     }
 
+    #[test]
+    fn test_scripts_host_target_combinations() {
+        let bin = |host: &str, target: &str| {
+            format!(
+                "%BUNDLE_ROOT%\\VC\\Tools\\MSVC\\14.44.34823\\bin\\{}\\{}",
+                host, target
+            )
+        };
+        let sdk_bin = |host: &str| {
+            format!(
+                "%BUNDLE_ROOT%\\Windows Kits\\10\\bin\\10.0.26100.0\\{}",
+                host
+            )
+        };
+        let cases = [
+            (Architecture::X64, Architecture::X64, "x64"),
+            (Architecture::X64, Architecture::X86, "Win32"),
+            (Architecture::X86, Architecture::X86, "Win32"),
+            (Architecture::X86, Architecture::X64, "x64"),
+        ];
+        for (host, arch, platform) in cases {
+            let ctx = ScriptContext::portable("14.44.34823", "10.0.26100.0", arch, host);
+            let scripts = generate_portable_scripts(&ctx).unwrap();
+            let host_dir = host.msvc_host_dir();
+
+            let mut expected = vec![bin(host_dir, &arch.to_string())];
+            if host != arch {
+                expected.push(bin(host_dir, &host.to_string()));
+            }
+            expected.push(sdk_bin(&host.to_string()));
+            assert!(scripts.cmd.contains("set \"PATH=%MSVC_KIT_PATH%;%PATH%\""));
+            let entries: Vec<&str> = scripts
+                .cmd
+                .lines()
+                .filter_map(|l| l.strip_prefix("set \"MSVC_KIT_PATH="))
+                .map(|l| l.trim_end_matches('"').rsplit(';').next().unwrap())
+                .collect();
+            assert_eq!(entries, expected, "{} -> {}", host, arch);
+
+            let lib = format!("Lib\\10.0.26100.0\\um\\{}", arch);
+            assert!(scripts.cmd.contains(&lib));
+            assert!(scripts
+                .cmd
+                .contains(&format!("set \"Platform={}\"", platform)));
+            assert!(scripts
+                .powershell
+                .contains(&format!("$env:Platform = \"{}\"", platform)));
+            assert!(scripts
+                .bash
+                .contains(&format!("export Platform=\"{}\"", platform)));
+            assert_eq!(
+                scripts.bash.contains(&format!(
+                    ":$BUNDLE_ROOT/VC/Tools/MSVC/14.44.34823/bin/{}/{}\"",
+                    host_dir, host
+                )),
+                host != arch
+            );
+        }
+    }
+
     #[test]
     fn test_d_drive_path_conversion() {
         let ctx = ScriptContext::absolute(
@@ -654,6 +1098,7 @@ mod tests {
             powershell: "Write-Host 'test'".to_string(),
             bash: "#!/bin/bash\necho test".to_string(),
             readme: Some("README content".to_string()),
+            powershell_module: Some("function Enter-MsvcEnv {}".to_string()),
         };
 
         save_scripts(&scripts, temp_dir.path(), "setup")
@@ -665,6 +1110,7 @@ mod tests {
         assert!(temp_dir.path().join("setup.ps1").exists());
         assert!(temp_dir.path().join("setup.sh").exists());
         assert!(temp_dir.path().join("README.txt").exists());
+        assert!(temp_dir.path().join(POWERSHELL_MODULE_FILE).exists());
 
         // Verify content
         let cmd_content = std::fs::read_to_string(temp_dir.path().join("setup.bat")).unwrap();
@@ -680,6 +1126,27 @@ mod tests {
         assert!(readme_content.contains("README content"));
     }
 
+    #[tokio::test]
+    async fn test_save_scripts_powershell_bom() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let scripts = GeneratedScripts {
+            cmd: "cmd".to_string(),
+            powershell: "Write-Host '已激活'".to_string(),
+            bash: "bash".to_string(),
+            readme: None,
+            powershell_module: None,
+        };
+
+        save_scripts(&scripts, temp_dir.path(), "setup")
+            .await
+            .unwrap();
+
+        let bytes = std::fs::read(temp_dir.path().join("setup.ps1")).unwrap();
+        assert!(bytes.starts_with(&[0xEF, 0xBB, 0xBF]));
+        let bash = std::fs::read(temp_dir.path().join("setup.sh")).unwrap();
+        assert_eq!(bash, b"bash");
+    }
+
     #[tokio::test]
     async fn test_save_scripts_without_readme() {
         let temp_dir = tempfile::tempdir().unwrap();
@@ -688,6 +1155,7 @@ mod tests {
             powershell: "ps".to_string(),
             bash: "bash".to_string(),
             readme: None,
+            powershell_module: None,
         };
 
         save_scripts(&scripts, temp_dir.path(), "activate")
@@ -698,6 +1166,7 @@ mod tests {
         assert!(temp_dir.path().join("activate.ps1").exists());
         assert!(temp_dir.path().join("activate.sh").exists());
         assert!(!temp_dir.path().join("README.txt").exists());
+        assert!(!temp_dir.path().join(POWERSHELL_MODULE_FILE).exists());
     }
 
     #[tokio::test]
@@ -710,6 +1179,7 @@ mod tests {
             powershell: "ps".to_string(),
             bash: "bash".to_string(),
             readme: None,
+            powershell_module: None,
         };
 
         save_scripts(&scripts, &nested_dir, "setup").await.unwrap();
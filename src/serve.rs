@@ -0,0 +1,696 @@
+//! Daemon serving toolchain requests over JSON-RPC
+//!
+//! `msvc-kit serve` keeps one long-lived [`Daemon`] that IDE plugins and build
+//! agents ask for toolchains instead of each running the CLI. All requests
+//! install into the daemon's directory, so they share one download cache,
+//! installs run one at a time, and a request identical to a running one joins
+//! that job rather than starting another.
+//!
+//! The protocol is JSON-RPC 2.0 with one message per line, over TCP or (on
+//! Windows) a named pipe:
+//!
+//! | Method    | Params            | Result                                  |
+//! |-----------|-------------------|-----------------------------------------|
+//! | `install` | [`InstallParams`] | [`JobStatus`] (final if `wait` is set)   |
+//! | `status`  | `{"id": <job>}`   | [`JobStatus`], or [`DaemonStatus`] without `id` |
+//! | `query`   | [`QueryParams`]   | [`QueryResult`]                         |
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, watch};
+
+use crate::downloader::{DownloadOptions, MsvcComponent, ProgressHandler};
+use crate::error::Result;
+use crate::query::{query_installation, QueryComponent, QueryOptions, QueryResult};
+use crate::version::Architecture;
+
+/// Default TCP address of the daemon
+pub const DEFAULT_TCP_ADDR: &str = "127.0.0.1:7421";
+
+/// Default named pipe of the daemon (Windows)
+pub const DEFAULT_PIPE_NAME: &str = r"\\.\pipe\msvc-kit";
+
+/// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// Where the daemon listens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// TCP socket, e.g. `tcp://127.0.0.1:7421`
+    Tcp(SocketAddr),
+    /// Named pipe, e.g. `pipe://msvc-kit` or `\\.\pipe\msvc-kit` (Windows only)
+    Pipe(String),
+}
+
+impl Default for Endpoint {
+    /// The named pipe on Windows, the loopback TCP address elsewhere
+    fn default() -> Self {
+        if cfg!(windows) {
+            Endpoint::Pipe(DEFAULT_PIPE_NAME.to_string())
+        } else {
+            Endpoint::Tcp(DEFAULT_TCP_ADDR.parse().unwrap())
+        }
+    }
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "tcp://{}", addr),
+            Endpoint::Pipe(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+impl std::str::FromStr for Endpoint {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        if let Some(name) = s.strip_prefix("pipe://") {
+            if name.is_empty() {
+                return Err("Empty pipe name".to_string());
+            }
+            return Ok(Endpoint::Pipe(format!(r"\\.\pipe\{}", name)));
+        }
+        if s.starts_with(r"\\.\pipe\") {
+            return Ok(Endpoint::Pipe(s.to_string()));
+        }
+        let addr = s.strip_prefix("tcp://").unwrap_or(s);
+        addr.parse().map(Endpoint::Tcp).map_err(|_| {
+            format!(
+                "Invalid endpoint '{}' (expected e.g. tcp://127.0.0.1:7421 or pipe://msvc-kit)",
+                s
+            )
+        })
+    }
+}
+
+/// Parameters of the `install` method
+///
+/// Unset fields take the values of the daemon's base options.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallParams {
+    /// MSVC version to install (None = latest)
+    pub msvc_version: Option<String>,
+    /// Windows SDK version to install (None = latest)
+    pub sdk_version: Option<String>,
+    /// Target architecture
+    pub arch: Option<Architecture>,
+    /// Host architecture
+    pub host_arch: Option<Architecture>,
+    /// Optional MSVC components (`spectre`, `mfc`, ...)
+    pub components: Vec<MsvcComponent>,
+    /// Skip MSVC
+    pub no_msvc: bool,
+    /// Skip the Windows SDK
+    pub no_sdk: bool,
+    /// Use cached manifests and payloads only
+    pub offline: bool,
+    /// Respond once the job has finished instead of when it is queued
+    #[serde(skip_serializing)]
+    pub wait: bool,
+}
+
+/// Parameters of the `query` method
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueryParams {
+    /// Target architecture (None = the daemon's)
+    pub arch: Option<Architecture>,
+    /// Which component to query
+    pub component: QueryComponent,
+    /// Specific MSVC version (None = latest installed)
+    pub msvc_version: Option<String>,
+    /// Specific SDK version (None = latest installed)
+    pub sdk_version: Option<String>,
+    /// Also list every installed version
+    pub all_versions: bool,
+}
+
+/// State of an install job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobState {
+    /// Waiting for the running install to finish
+    Queued,
+    /// Downloading or extracting
+    Running,
+    /// Installed
+    Completed,
+    /// Failed; see [`JobStatus::error`]
+    Failed,
+}
+
+impl JobState {
+    /// Whether the job has finished
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobState::Completed | JobState::Failed)
+    }
+}
+
+/// Progress of an install job, as returned by `install` and `status`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatus {
+    /// Job id
+    pub id: u64,
+    /// Current state
+    pub state: JobState,
+    /// What was requested
+    pub params: InstallParams,
+    /// Component being downloaded ("MSVC", "Windows SDK")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub component: Option<String>,
+    /// Files of the current component
+    pub total_files: usize,
+    /// Files of the current component that are done
+    pub completed_files: usize,
+    /// Bytes of the current component
+    pub total_bytes: u64,
+    /// Bytes transferred so far
+    pub transferred_bytes: u64,
+    /// Latest progress message
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Installed MSVC version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub msvc_version: Option<String>,
+    /// Installed Windows SDK version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sdk_version: Option<String>,
+    /// Why the job failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl JobStatus {
+    fn new(id: u64, params: InstallParams) -> Self {
+        Self {
+            id,
+            state: JobState::Queued,
+            params,
+            component: None,
+            total_files: 0,
+            completed_files: 0,
+            total_bytes: 0,
+            transferred_bytes: 0,
+            message: None,
+            msvc_version: None,
+            sdk_version: None,
+            error: None,
+        }
+    }
+}
+
+/// Result of `status` without a job id
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    /// msvc-kit version of the daemon
+    pub version: String,
+    /// Directory all jobs install into
+    pub install_dir: PathBuf,
+    /// Every job since the daemon started, oldest first
+    pub jobs: Vec<JobStatus>,
+}
+
+/// Progress handler publishing to a job's status
+struct JobProgress {
+    status: Arc<watch::Sender<JobStatus>>,
+}
+
+impl ProgressHandler for JobProgress {
+    fn on_start(&self, component: &str, total_files: usize, total_bytes: u64) {
+        self.status.send_modify(|s| {
+            s.component = Some(component.to_string());
+            s.total_files = total_files;
+            s.completed_files = 0;
+            s.total_bytes = total_bytes;
+            s.transferred_bytes = 0;
+        });
+    }
+
+    fn on_file_start(&self, _file_name: &str, _file_size: u64) {}
+
+    fn on_progress(&self, bytes: u64) {
+        self.status.send_modify(|s| s.transferred_bytes += bytes);
+    }
+
+    fn on_file_complete(&self, _file_name: &str, _outcome: &str) {
+        self.status.send_modify(|s| s.completed_files += 1);
+    }
+
+    fn on_complete(&self, _downloaded: usize, _skipped: usize) {}
+
+    fn on_error(&self, error: &str) {
+        self.status
+            .send_modify(|s| s.message = Some(error.to_string()));
+    }
+
+    fn on_message(&self, message: &str) {
+        self.status
+            .send_modify(|s| s.message = Some(message.to_string()));
+    }
+}
+
+/// Long-lived toolchain provider behind `msvc-kit serve`
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use std::sync::Arc;
+/// use msvc_kit::serve::{Daemon, Endpoint};
+/// use msvc_kit::DownloadOptions;
+///
+/// # async fn run() -> msvc_kit::Result<()> {
+/// let daemon = Arc::new(Daemon::new(
+///     DownloadOptions::builder().target_dir("C:/msvc-kit").build(),
+/// ));
+/// daemon.serve(&Endpoint::default()).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct Daemon {
+    /// Options every job starts from
+    base: DownloadOptions,
+    next_id: AtomicU64,
+    jobs: Mutex<BTreeMap<u64, watch::Receiver<JobStatus>>>,
+    /// Held by the running install
+    install_lock: tokio::sync::Mutex<()>,
+}
+
+impl Daemon {
+    /// Create a daemon installing into `base.target_dir`
+    ///
+    /// Jobs start from `base` (verification, parallelism, timeouts, ...);
+    /// `install` requests only choose versions, architectures and components.
+    pub fn new(base: DownloadOptions) -> Self {
+        Self {
+            base,
+            next_id: AtomicU64::new(1),
+            jobs: Mutex::new(BTreeMap::new()),
+            install_lock: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// Directory all jobs install into
+    pub fn install_dir(&self) -> &std::path::Path {
+        &self.base.target_dir
+    }
+
+    /// Status of job `id`
+    pub fn job(&self, id: u64) -> Option<JobStatus> {
+        let jobs = self.jobs.lock().unwrap();
+        jobs.get(&id).map(|rx| rx.borrow().clone())
+    }
+
+    /// Status of the daemon and all of its jobs
+    pub fn status(&self) -> DaemonStatus {
+        let jobs = self.jobs.lock().unwrap();
+        DaemonStatus {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            install_dir: self.base.target_dir.clone(),
+            jobs: jobs.values().map(|rx| rx.borrow().clone()).collect(),
+        }
+    }
+
+    /// Queue an install, or join an unfinished job with the same parameters
+    ///
+    /// Returns a receiver following the job's status.
+    pub fn install(self: &Arc<Self>, mut params: InstallParams) -> watch::Receiver<JobStatus> {
+        params.wait = false;
+        let mut jobs = self.jobs.lock().unwrap();
+        if let Some(rx) = jobs.values().find(|rx| {
+            let status = rx.borrow();
+            !status.state.is_finished() && status.params == params
+        }) {
+            return rx.clone();
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = watch::channel(JobStatus::new(id, params.clone()));
+        jobs.insert(id, rx.clone());
+        drop(jobs);
+
+        let daemon = Arc::clone(self);
+        tokio::spawn(async move {
+            let _guard = daemon.install_lock.lock().await;
+            tx.send_modify(|s| s.state = JobState::Running);
+            let tx = Arc::new(tx);
+            let result = daemon.run_install(&params, &tx).await;
+            tx.send_modify(|s| match result {
+                Ok(()) => s.state = JobState::Completed,
+                Err(e) => {
+                    s.state = JobState::Failed;
+                    s.error = Some(e.to_string());
+                }
+            });
+        });
+        rx
+    }
+
+    async fn run_install(
+        &self,
+        params: &InstallParams,
+        status: &Arc<watch::Sender<JobStatus>>,
+    ) -> Result<()> {
+        let mut options = self.base.clone();
+        options.msvc_version = params.msvc_version.clone();
+        options.sdk_version = params.sdk_version.clone();
+        options.arch = params.arch.unwrap_or(self.base.arch);
+        options.host_arch = params.host_arch.or(self.base.host_arch);
+        options
+            .include_components
+            .extend(params.components.iter().cloned());
+        options.offline |= params.offline;
+        options.progress_handler = Some(Arc::new(JobProgress {
+            status: Arc::clone(status),
+        }));
+        options.validate()?;
+
+        if !params.no_msvc {
+            let mut info = crate::download_msvc(&options).await?;
+            status.send_modify(|s| s.message = Some("Extracting MSVC packages".to_string()));
+            crate::extract_and_finalize_msvc_with_report(&mut info, false).await?;
+            status.send_modify(|s| s.msvc_version = Some(info.version.clone()));
+        }
+        if !params.no_sdk {
+            let info = crate::download_sdk(&options).await?;
+            status.send_modify(|s| s.message = Some("Extracting SDK packages".to_string()));
+            crate::extract_and_finalize_sdk_with_report(&info, false).await?;
+            status.send_modify(|s| s.sdk_version = Some(info.version.clone()));
+        }
+        Ok(())
+    }
+
+    /// Query the daemon's installation
+    pub fn query(&self, params: &QueryParams) -> Result<QueryResult> {
+        query_installation(&QueryOptions {
+            install_dir: self.base.target_dir.clone(),
+            arch: params.arch.unwrap_or(self.base.arch),
+            component: params.component,
+            msvc_version: params.msvc_version.clone(),
+            sdk_version: params.sdk_version.clone(),
+            all_versions: params.all_versions,
+            ..Default::default()
+        })
+    }
+
+    /// Handle one JSON-RPC request line and return the response line
+    ///
+    /// Returns None for notifications (requests without an id).
+    pub async fn handle(self: &Arc<Self>, line: &str) -> Option<String> {
+        let request: Value = match serde_json::from_str(line) {
+            Ok(request) => request,
+            Err(e) => return Some(error_response(Value::Null, PARSE_ERROR, &e.to_string())),
+        };
+        let id = request.get("id").cloned();
+        let method = request.get("method").and_then(Value::as_str).unwrap_or("");
+        let params = request.get("params").cloned().unwrap_or(Value::Null);
+        let result = self.dispatch(method, params).await;
+        let id = id?;
+        Some(match result {
+            Ok(result) => {
+                serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result}).to_string()
+            }
+            Err((code, message)) => error_response(id, code, &message),
+        })
+    }
+
+    async fn dispatch(
+        self: &Arc<Self>,
+        method: &str,
+        params: Value,
+    ) -> std::result::Result<Value, (i64, String)> {
+        fn parse<T: for<'de> Deserialize<'de> + Default>(
+            params: Value,
+        ) -> std::result::Result<T, (i64, String)> {
+            if params.is_null() {
+                return Ok(T::default());
+            }
+            serde_json::from_value(params).map_err(|e| (INVALID_PARAMS, e.to_string()))
+        }
+        let to_value = |v: std::result::Result<Value, serde_json::Error>| {
+            v.map_err(|e| (SERVER_ERROR, e.to_string()))
+        };
+
+        match method {
+            "install" => {
+                let params: InstallParams = parse(params)?;
+                let wait = params.wait;
+                let mut rx = self.install(params);
+                if wait {
+                    let _ = rx.wait_for(|s| s.state.is_finished()).await;
+                }
+                let status = rx.borrow().clone();
+                to_value(serde_json::to_value(status))
+            }
+            "status" => {
+                #[derive(Default, Deserialize)]
+                struct StatusParams {
+                    id: Option<u64>,
+                }
+                match parse::<StatusParams>(params)?.id {
+                    Some(id) => match self.job(id) {
+                        Some(status) => to_value(serde_json::to_value(status)),
+                        None => Err((INVALID_PARAMS, format!("Unknown job {}", id))),
+                    },
+                    None => to_value(serde_json::to_value(self.status())),
+                }
+            }
+            "query" => {
+                let params: QueryParams = parse(params)?;
+                let result = self
+                    .query(&params)
+                    .map_err(|e| (SERVER_ERROR, e.to_string()))?;
+                to_value(serde_json::to_value(result))
+            }
+            other => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", other))),
+        }
+    }
+
+    /// Answer requests from one client until it disconnects
+    ///
+    /// Requests are handled concurrently, so a waiting `install` does not
+    /// block `status` calls on the same connection.
+    pub async fn serve_connection<S>(self: Arc<Self>, stream: S) -> Result<()>
+    where
+        S: AsyncRead + AsyncWrite + Send + 'static,
+    {
+        let (reader, mut writer) = tokio::io::split(stream);
+        let (tx, mut rx) = mpsc::unbounded_channel::<String>();
+        let writer_task = tokio::spawn(async move {
+            while let Some(mut response) = rx.recv().await {
+                response.push('\n');
+                if writer.write_all(response.as_bytes()).await.is_err() {
+                    break;
+                }
+                let _ = writer.flush().await;
+            }
+        });
+
+        let mut lines = BufReader::new(reader).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let daemon = Arc::clone(&self);
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                if let Some(response) = daemon.handle(&line).await {
+                    let _ = tx.send(response);
+                }
+            });
+        }
+        drop(tx);
+        let _ = writer_task.await;
+        Ok(())
+    }
+
+    /// Accept clients on a bound TCP listener until the task is cancelled
+    pub async fn serve_tcp(self: Arc<Self>, listener: tokio::net::TcpListener) -> Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            tracing::debug!("Client connected from {}", peer);
+            let daemon = Arc::clone(&self);
+            tokio::spawn(async move {
+                if let Err(e) = daemon.serve_connection(stream).await {
+                    tracing::warn!("Connection from {} failed: {}", peer, e);
+                }
+            });
+        }
+    }
+
+    /// Listen on `endpoint` and serve clients until the task is cancelled
+    pub async fn serve(self: Arc<Self>, endpoint: &Endpoint) -> Result<()> {
+        match endpoint {
+            Endpoint::Tcp(addr) => {
+                if !addr.ip().is_loopback() {
+                    tracing::warn!(
+                        "Serving on {} without authentication; any host that can reach it may install toolchains",
+                        addr
+                    );
+                }
+                let listener = tokio::net::TcpListener::bind(addr).await?;
+                self.serve_tcp(listener).await
+            }
+            #[cfg(windows)]
+            Endpoint::Pipe(name) => {
+                use tokio::net::windows::named_pipe::ServerOptions;
+
+                let mut server = ServerOptions::new()
+                    .first_pipe_instance(true)
+                    .create(name)?;
+                loop {
+                    server.connect().await?;
+                    let client = std::mem::replace(&mut server, ServerOptions::new().create(name)?);
+                    let daemon = Arc::clone(&self);
+                    tokio::spawn(async move {
+                        if let Err(e) = daemon.serve_connection(client).await {
+                            tracing::warn!("Pipe connection failed: {}", e);
+                        }
+                    });
+                }
+            }
+            #[cfg(not(windows))]
+            Endpoint::Pipe(name) => Err(crate::MsvcKitError::UnsupportedPlatform(format!(
+                "named pipe {} (use a tcp:// endpoint)",
+                name
+            ))),
+        }
+    }
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> String {
+    serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": {"code": code, "message": message},
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daemon(dir: &std::path::Path) -> Arc<Daemon> {
+        Arc::new(Daemon::new(
+            DownloadOptions::builder()
+                .target_dir(dir)
+                .offline(true)
+                .build(),
+        ))
+    }
+
+    #[test]
+    fn test_endpoint_parse() {
+        assert_eq!(
+            "tcp://127.0.0.1:7421".parse::<Endpoint>(),
+            Ok(Endpoint::Tcp("127.0.0.1:7421".parse().unwrap()))
+        );
+        assert_eq!(
+            "[::1]:80".parse::<Endpoint>(),
+            Ok(Endpoint::Tcp("[::1]:80".parse().unwrap()))
+        );
+        assert_eq!(
+            "pipe://msvc-kit".parse::<Endpoint>(),
+            Ok(Endpoint::Pipe(DEFAULT_PIPE_NAME.to_string()))
+        );
+        assert_eq!(
+            DEFAULT_PIPE_NAME.parse::<Endpoint>().unwrap().to_string(),
+            DEFAULT_PIPE_NAME
+        );
+        assert!("localhost".parse::<Endpoint>().is_err());
+        assert!("pipe://".parse::<Endpoint>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_handle_errors() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let daemon = daemon(temp_dir.path());
+
+        let response: Value =
+            serde_json::from_str(&daemon.handle("not json").await.unwrap()).unwrap();
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+
+        let response = daemon
+            .handle(r#"{"jsonrpc":"2.0","id":1,"method":"build"}"#)
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["id"], 1);
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+
+        let response = daemon
+            .handle(r#"{"jsonrpc":"2.0","id":2,"method":"status","params":{"id":9}}"#)
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+
+        // Notifications get no response
+        assert!(daemon
+            .handle(r#"{"jsonrpc":"2.0","method":"status"}"#)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_install_offline_fails_and_is_listed() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let daemon = daemon(temp_dir.path());
+
+        let response = daemon
+            .handle(r#"{"jsonrpc":"2.0","id":"a","method":"install","params":{"msvc_version":"14.44","wait":true}}"#)
+            .await
+            .unwrap();
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["id"], "a");
+        assert_eq!(response["result"]["id"], 1);
+        assert_eq!(response["result"]["state"], "failed");
+        assert_eq!(response["result"]["params"]["msvc_version"], "14.44");
+        assert!(response["result"]["error"].is_string());
+
+        let status = daemon.status();
+        assert_eq!(status.install_dir, temp_dir.path());
+        assert_eq!(status.jobs.len(), 1);
+        assert_eq!(daemon.job(1).unwrap().state, JobState::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_serve_tcp() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let daemon = daemon(temp_dir.path());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(daemon.serve_tcp(listener));
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        writer
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":7,\"method\":\"status\"}\n")
+            .await
+            .unwrap();
+        let line = BufReader::new(reader)
+            .lines()
+            .next_line()
+            .await
+            .unwrap()
+            .unwrap();
+        let response: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["version"], env!("CARGO_PKG_VERSION"));
+        assert!(response["result"]["jobs"].as_array().unwrap().is_empty());
+        server.abort();
+    }
+}
@@ -35,6 +35,9 @@ impl std::str::FromStr for Architecture {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('-') {
+            return Self::from_rust_triple(s);
+        }
         match s.to_lowercase().as_str() {
             "x64" | "amd64" | "x86_64" => Ok(Architecture::X64),
             "x86" | "i686" | "i386" => Ok(Architecture::X86),
@@ -65,6 +68,67 @@ impl Architecture {
         return Architecture::X64; // Default fallback
     }
 
+    /// Architecture of the running Windows system
+    ///
+    /// Unlike [`host`](Self::host), which is fixed when msvc-kit is built,
+    /// this sees through emulation: an x64 build running on Windows on ARM
+    /// returns [`Architecture::Arm64`]. Falls back to `host()` elsewhere.
+    pub fn native_host() -> Self {
+        native_processor_architecture()
+            .and_then(|name| name.parse().ok())
+            .unwrap_or_else(Self::host)
+    }
+
+    /// Host architectures whose tools run on this one, best first
+    ///
+    /// Windows on ARM emulates x64 and x86 binaries; x64 Windows runs x86
+    /// binaries through WOW64.
+    pub fn runnable_hosts(&self) -> &'static [Architecture] {
+        match self {
+            Architecture::Arm64 => &[Architecture::Arm64, Architecture::X64, Architecture::X86],
+            Architecture::X64 => &[Architecture::X64, Architecture::X86],
+            Architecture::X86 => &[Architecture::X86],
+            Architecture::Arm => &[Architecture::Arm],
+        }
+    }
+
+    /// Map a Rust target triple to an architecture
+    ///
+    /// Accepts the MSVC targets, e.g. `x86_64-pc-windows-msvc`,
+    /// `i686-pc-windows-msvc`, `aarch64-pc-windows-msvc` or
+    /// `thumbv7a-uwp-windows-msvc`. `arm64ec-pc-windows-msvc` maps to
+    /// [`Architecture::Arm64`], whose toolchain builds ARM64EC code.
+    pub fn from_rust_triple(triple: &str) -> Result<Self, String> {
+        let lower = triple.to_lowercase();
+        let parts: Vec<&str> = lower.split('-').collect();
+        if parts.len() < 3 || !parts.contains(&"windows") || parts.last() != Some(&"msvc") {
+            return Err(format!(
+                "Unsupported target triple: {} (expected an MSVC target such as x86_64-pc-windows-msvc)",
+                triple
+            ));
+        }
+        match parts[0] {
+            "x86_64" => Ok(Architecture::X64),
+            "i686" | "i586" => Ok(Architecture::X86),
+            "aarch64" | "arm64ec" => Ok(Architecture::Arm64),
+            "thumbv7a" => Ok(Architecture::Arm),
+            arch => Err(format!(
+                "Unsupported architecture '{}' in target triple {}",
+                arch, triple
+            )),
+        }
+    }
+
+    /// Rust target triple of the architecture (`<arch>-pc-windows-msvc`)
+    pub fn rust_triple(&self) -> &'static str {
+        match self {
+            Architecture::X64 => "x86_64-pc-windows-msvc",
+            Architecture::X86 => "i686-pc-windows-msvc",
+            Architecture::Arm64 => "aarch64-pc-windows-msvc",
+            Architecture::Arm => "thumbv7a-pc-windows-msvc",
+        }
+    }
+
     /// Get the MSVC host directory name
     pub fn msvc_host_dir(&self) -> &'static str {
         match self {
@@ -84,6 +148,128 @@ impl Architecture {
             Architecture::Arm => "arm",
         }
     }
+
+    /// MSBuild `Platform` name of the architecture
+    ///
+    /// MSBuild projects call 32-bit x86 `Win32`; `Platform=x86` makes
+    /// `msbuild` fail with "The BaseOutputPath/OutputPath property is not set".
+    pub fn msbuild_platform(&self) -> &'static str {
+        match self {
+            Architecture::X64 => "x64",
+            Architecture::X86 => "Win32",
+            Architecture::Arm64 => "ARM64",
+            Architecture::Arm => "ARM",
+        }
+    }
+}
+
+/// `PROCESSOR_ARCHITECTURE` of the system (not of this process)
+///
+/// The process environment reports the emulated architecture, so the value
+/// is read from the system environment in the registry.
+#[cfg(windows)]
+fn native_processor_architecture() -> Option<String> {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    RegKey::predef(HKEY_LOCAL_MACHINE)
+        .open_subkey(r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment")
+        .and_then(|key| key.get_value("PROCESSOR_ARCHITECTURE"))
+        .ok()
+}
+
+#[cfg(not(windows))]
+fn native_processor_architecture() -> Option<String> {
+    None
+}
+
+/// How [`select_host_arch`] chose the host architecture of the MSVC tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostArchSource {
+    /// Set explicitly (`host_arch`, `--host-arch`)
+    Requested,
+    /// The system architecture, which the toolset provides tools for
+    Native,
+    /// No native tools; these run under emulation
+    Emulated,
+}
+
+impl fmt::Display for HostArchSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostArchSource::Requested => write!(f, "requested"),
+            HostArchSource::Native => write!(f, "native"),
+            HostArchSource::Emulated => write!(f, "emulated"),
+        }
+    }
+}
+
+/// Host architecture of the MSVC tools and why it was chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostArchSelection {
+    /// Host architecture of the selected tools (`bin/Host<arch>`)
+    pub arch: Architecture,
+    /// Architecture of the running system
+    pub native: Architecture,
+    /// How `arch` was chosen
+    pub source: HostArchSource,
+}
+
+impl fmt::Display for HostArchSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.source {
+            HostArchSource::Emulated => write!(f, "{} (emulated on {})", self.arch, self.native),
+            source => write!(f, "{} ({})", self.arch, source),
+        }
+    }
+}
+
+/// Choose the host architecture of the MSVC tools
+///
+/// A `requested` architecture always wins. Otherwise the native one is used
+/// if `has_tools` reports tools for it, then the first emulated one with
+/// tools (see [`Architecture::runnable_hosts`]): on Windows on ARM, `Hostarm64`
+/// tools are preferred and `Hostx64` is the fallback. If no candidate has
+/// tools, the native architecture is returned so the caller reports it.
+///
+/// # Example
+///
+/// ```rust
+/// use msvc_kit::version::{select_host_arch, Architecture, HostArchSource};
+///
+/// // An old toolset without Hostarm64 tools on Windows on ARM
+/// let selection = select_host_arch(None, Architecture::Arm64, |h| h == Architecture::X64);
+/// assert_eq!(selection.arch, Architecture::X64);
+/// assert_eq!(selection.source, HostArchSource::Emulated);
+/// ```
+pub fn select_host_arch(
+    requested: Option<Architecture>,
+    native: Architecture,
+    has_tools: impl Fn(Architecture) -> bool,
+) -> HostArchSelection {
+    if let Some(arch) = requested {
+        return HostArchSelection {
+            arch,
+            native,
+            source: HostArchSource::Requested,
+        };
+    }
+    let arch = native
+        .runnable_hosts()
+        .iter()
+        .copied()
+        .find(|arch| has_tools(*arch))
+        .unwrap_or(native);
+    HostArchSelection {
+        arch,
+        native,
+        source: if arch == native {
+            HostArchSource::Native
+        } else {
+            HostArchSource::Emulated
+        },
+    }
 }
 
 /// Marker trait for version types
@@ -355,6 +541,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_select_host_arch() {
+        let arm64_tools = |h: Architecture| h == Architecture::Arm64 || h == Architecture::X64;
+        let x64_tools = |h: Architecture| h == Architecture::X64;
+
+        let selection = select_host_arch(None, Architecture::Arm64, arm64_tools);
+        assert_eq!(selection.arch, Architecture::Arm64);
+        assert_eq!(selection.source, HostArchSource::Native);
+
+        let selection = select_host_arch(None, Architecture::Arm64, x64_tools);
+        assert_eq!(selection.arch, Architecture::X64);
+        assert_eq!(selection.source, HostArchSource::Emulated);
+        assert_eq!(selection.to_string(), "x64 (emulated on arm64)");
+
+        let selection = select_host_arch(Some(Architecture::X64), Architecture::Arm64, arm64_tools);
+        assert_eq!(selection.arch, Architecture::X64);
+        assert_eq!(selection.source, HostArchSource::Requested);
+
+        // Nothing installed: report the native architecture
+        let selection = select_host_arch(None, Architecture::X64, |_| false);
+        assert_eq!(selection.arch, Architecture::X64);
+        assert_eq!(selection.source, HostArchSource::Native);
+    }
+
     #[test]
     fn test_msvc_host_dir() {
         assert_eq!(Architecture::X64.msvc_host_dir(), "Hostx64");
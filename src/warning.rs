@@ -0,0 +1,107 @@
+//! Structured warnings
+//!
+//! Conditions that do not fail an operation (a version fallback, a payload
+//! kept despite a hash mismatch, a file two packages disagree on) used to be
+//! visible in the log only. They are now also collected as [`Warning`]s in
+//! [`InstallInfo`](crate::InstallInfo), [`QueryResult`](crate::QueryResult)
+//! and [`BundleResult`](crate::bundle::BundleResult), so automation can gate
+//! on them.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Kind of a [`Warning`], stable across releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningCode {
+    /// The requested version is not listed, or only loosely matched one that is
+    FallbackVersion,
+    /// A payload was kept although its hash did not match (verify mode `warn`)
+    UnverifiedFile,
+    /// Two packages wrote the same path with different contents
+    FileConflict,
+    /// A package was extracted incompletely or not at all
+    Extraction,
+    /// A queried component is not installed
+    MissingComponent,
+    /// An optional package failed and was left out of the install
+    OptionalPackageFailed,
+}
+
+impl fmt::Display for WarningCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WarningCode::FallbackVersion => write!(f, "fallback-version"),
+            WarningCode::UnverifiedFile => write!(f, "unverified-file"),
+            WarningCode::FileConflict => write!(f, "file-conflict"),
+            WarningCode::Extraction => write!(f, "extraction"),
+            WarningCode::MissingComponent => write!(f, "missing-component"),
+            WarningCode::OptionalPackageFailed => write!(f, "optional-package-failed"),
+        }
+    }
+}
+
+/// A non-fatal problem found during an operation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Warning {
+    /// What kind of problem this is
+    pub code: WarningCode,
+    /// Human-readable description
+    pub message: String,
+    /// What the warning is about, e.g. a payload file or path
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+impl Warning {
+    /// Create a warning without context
+    pub fn new(code: WarningCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            context: None,
+        }
+    }
+
+    /// Attach what the warning is about
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] ", self.code)?;
+        match &self.context {
+            Some(context) => write!(f, "{}: {}", context, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warning_display_and_json() {
+        let warning = Warning::new(WarningCode::UnverifiedFile, "hash mismatch kept")
+            .with_context("cab1.cab");
+        assert_eq!(
+            warning.to_string(),
+            "[unverified-file] cab1.cab: hash mismatch kept"
+        );
+
+        let json = serde_json::to_value(&warning).unwrap();
+        assert_eq!(json["code"], "unverified-file");
+        assert_eq!(json["context"], "cab1.cab");
+
+        let bare = Warning::new(WarningCode::MissingComponent, "no SDK");
+        assert_eq!(bare.to_string(), "[missing-component] no SDK");
+        assert!(serde_json::to_value(&bare)
+            .unwrap()
+            .get("context")
+            .is_none());
+    }
+}
@@ -0,0 +1,326 @@
+//! Converting Windows paths for Unix-style shells
+//!
+//! Bash scripts and profiles run either in an MSYS-based shell (Git Bash,
+//! MSYS2, Cygwin-style `/c/dir` paths) or in WSL (`/mnt/c/dir`). This module
+//! parses a Windows path once — drive letter, UNC share or `\\?\` verbatim
+//! prefix — and renders it for either flavor, instead of patching strings.
+//!
+//! ```rust
+//! use msvc_kit::winpath::{to_unix, UnixFlavor, WindowsPath};
+//!
+//! assert_eq!(to_unix(r"D:\msvc-kit", UnixFlavor::Msys), "/d/msvc-kit");
+//! assert_eq!(to_unix(r"D:\msvc-kit", UnixFlavor::Wsl), "/mnt/d/msvc-kit");
+//! assert_eq!(to_unix(r"\\server\share\kit", UnixFlavor::Msys), "//server/share/kit");
+//! assert_eq!(WindowsPath::parse("C:/msvc-kit/VC").to_windows(), r"C:\msvc-kit\VC");
+//! ```
+
+use std::fmt;
+
+/// How a Unix-style shell on Windows spells absolute paths
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum UnixFlavor {
+    /// Git Bash, MSYS2 and Cygwin-style shells: `/c/dir`
+    #[default]
+    Msys,
+    /// Windows Subsystem for Linux: `/mnt/c/dir`
+    Wsl,
+}
+
+impl fmt::Display for UnixFlavor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnixFlavor::Msys => write!(f, "msys"),
+            UnixFlavor::Wsl => write!(f, "wsl"),
+        }
+    }
+}
+
+impl std::str::FromStr for UnixFlavor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "msys" | "msys2" | "git-bash" | "cygwin" => Ok(UnixFlavor::Msys),
+            "wsl" => Ok(UnixFlavor::Wsl),
+            _ => Err(format!("Unknown path flavor: {} (expected msys or wsl)", s)),
+        }
+    }
+}
+
+/// Where a [`WindowsPath`] is anchored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathRoot {
+    /// `C:\...` (drive letter stored uppercase)
+    Drive(char),
+    /// `\\server\share\...`
+    Unc {
+        /// Server name
+        server: String,
+        /// Share name
+        share: String,
+    },
+    /// `\...`, relative to the current drive
+    CurrentDrive,
+    /// No root; relative to the current directory
+    Relative,
+}
+
+/// A Windows path split into its root and components
+///
+/// Both `\` and `/` are accepted as separators. A drive letter is only
+/// recognized at the very start, so `C:` inside a component is left alone.
+/// The `\\?\C:\` and `\\?\UNC\server\share\` verbatim forms are parsed like
+/// their plain equivalents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsPath {
+    /// Where the path is anchored
+    pub root: PathRoot,
+    /// Path components, without separators or empty entries
+    pub components: Vec<String>,
+}
+
+impl WindowsPath {
+    /// Parse a Windows path
+    pub fn parse(path: &str) -> Self {
+        let normalized = path.replace('/', "\\");
+        let rest = normalized
+            .strip_prefix(r"\\?\")
+            .or_else(|| normalized.strip_prefix(r"\\.\"));
+        let (root, tail) = match rest {
+            Some(rest) => match rest.strip_prefix(r"UNC\") {
+                Some(unc) => parse_unc(unc),
+                None => parse_local(rest),
+            },
+            None => match normalized.strip_prefix(r"\\") {
+                Some(unc) => parse_unc(unc),
+                None => parse_local(&normalized),
+            },
+        };
+
+        Self {
+            root,
+            components: split(tail),
+        }
+    }
+
+    /// Whether the path is anchored at a drive or share
+    pub fn is_absolute(&self) -> bool {
+        matches!(self.root, PathRoot::Drive(_) | PathRoot::Unc { .. })
+    }
+
+    /// Join the root and `components` with `sep`
+    fn render(&self, drive: impl Fn(char) -> String, unc: &str, sep: char) -> String {
+        let mut out = match &self.root {
+            PathRoot::Drive(letter) => drive(*letter),
+            PathRoot::Unc { server, share } => format!("{unc}{server}{sep}{share}"),
+            PathRoot::CurrentDrive => String::new(),
+            PathRoot::Relative => {
+                return self.components.join(&sep.to_string());
+            }
+        };
+        for component in &self.components {
+            out.push(sep);
+            out.push_str(component);
+        }
+        // `C:` alone means the current directory of drive C
+        if self.components.is_empty() && (out.is_empty() || out.ends_with(':')) {
+            out.push(sep);
+        }
+        out
+    }
+
+    /// Windows spelling with backslashes, e.g. `C:\dir` or `\\server\share\dir`
+    pub fn to_windows(&self) -> String {
+        self.render(|d| format!("{}:", d), r"\\", '\\')
+    }
+
+    /// Windows spelling with forward slashes, e.g. `C:/dir` (as CMake writes it)
+    pub fn to_forward_slashes(&self) -> String {
+        self.render(|d| format!("{}:", d), "//", '/')
+    }
+
+    /// Spelling for a Unix-style shell of the given flavor
+    ///
+    /// UNC shares become `//server/share/...`, which MSYS understands; WSL
+    /// cannot open them without a mount, so the same form is returned as the
+    /// closest equivalent.
+    pub fn to_unix(&self, flavor: UnixFlavor) -> String {
+        let drive = |d: char| match flavor {
+            UnixFlavor::Msys => format!("/{}", d.to_ascii_lowercase()),
+            UnixFlavor::Wsl => format!("/mnt/{}", d.to_ascii_lowercase()),
+        };
+        self.render(drive, "//", '/')
+    }
+
+    /// Parse a path written for a Unix-style shell back into a Windows path
+    ///
+    /// Accepts `/c/dir`, `/mnt/c/dir`, `/cygdrive/c/dir` and `//server/share`.
+    /// Returns `None` for Unix paths outside a drive (e.g. `/usr/bin`).
+    pub fn from_unix(path: &str) -> Option<Self> {
+        if let Some(unc) = path.strip_prefix("//") {
+            let unc = unc.replace('/', "\\");
+            let (root, tail) = parse_unc(&unc);
+            return Some(Self {
+                root,
+                components: split(tail),
+            });
+        }
+
+        let rest = path.strip_prefix('/')?;
+        let rest = rest
+            .strip_prefix("mnt/")
+            .or_else(|| rest.strip_prefix("cygdrive/"))
+            .unwrap_or(rest);
+        let (drive, tail) = rest.split_once('/').unwrap_or((rest, ""));
+        let mut chars = drive.chars();
+        match (chars.next(), chars.next()) {
+            (Some(letter), None) if letter.is_ascii_alphabetic() => Some(Self {
+                root: PathRoot::Drive(letter.to_ascii_uppercase()),
+                components: tail
+                    .split('/')
+                    .filter(|c| !c.is_empty())
+                    .map(str::to_string)
+                    .collect(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WindowsPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_windows())
+    }
+}
+
+/// `server\share\rest` -> (Unc root, rest)
+fn parse_unc(unc: &str) -> (PathRoot, &str) {
+    let mut parts = unc.splitn(3, '\\');
+    let server = parts.next().unwrap_or_default().to_string();
+    let share = parts.next().unwrap_or_default().to_string();
+    (
+        PathRoot::Unc { server, share },
+        parts.next().unwrap_or_default(),
+    )
+}
+
+/// `C:\rest`, `\rest` or `rest` -> (root, rest)
+fn parse_local(path: &str) -> (PathRoot, &str) {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        let letter = (bytes[0] as char).to_ascii_uppercase();
+        (PathRoot::Drive(letter), &path[2..])
+    } else if let Some(rest) = path.strip_prefix('\\') {
+        (PathRoot::CurrentDrive, rest)
+    } else {
+        (PathRoot::Relative, path)
+    }
+}
+
+fn split(tail: &str) -> Vec<String> {
+    tail.split('\\')
+        .filter(|c| !c.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Convert a Windows path for a Unix-style shell, see [`WindowsPath::to_unix`]
+pub fn to_unix(path: &str, flavor: UnixFlavor) -> String {
+    WindowsPath::parse(path).to_unix(flavor)
+}
+
+/// Convert a Windows path for Git Bash / MSYS2: `C:\dir` -> `/c/dir`
+pub fn to_msys(path: &str) -> String {
+    to_unix(path, UnixFlavor::Msys)
+}
+
+/// Convert a Windows path for WSL: `C:\dir` -> `/mnt/c/dir`
+pub fn to_wsl(path: &str) -> String {
+    to_unix(path, UnixFlavor::Wsl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drive_paths() {
+        let path = WindowsPath::parse(r"e:\Program Files\msvc-kit\");
+        assert_eq!(path.root, PathRoot::Drive('E'));
+        assert_eq!(path.components, ["Program Files", "msvc-kit"]);
+        assert_eq!(path.to_windows(), r"E:\Program Files\msvc-kit");
+        assert_eq!(path.to_forward_slashes(), "E:/Program Files/msvc-kit");
+        assert_eq!(path.to_unix(UnixFlavor::Msys), "/e/Program Files/msvc-kit");
+        assert_eq!(
+            path.to_unix(UnixFlavor::Wsl),
+            "/mnt/e/Program Files/msvc-kit"
+        );
+        assert_eq!(to_msys("C:/"), "/c");
+        assert_eq!(WindowsPath::parse("C:/").to_windows(), r"C:\");
+        assert_eq!(to_wsl(r"C:\"), "/mnt/c");
+    }
+
+    #[test]
+    fn test_drive_only_at_start() {
+        // The old string replacement turned this into `/c/tools//c/x`
+        assert_eq!(to_msys(r"C:\tools\C:\x"), "/c/tools/C:/x");
+        assert_eq!(to_msys(r"kits\C:"), "kits/C:");
+    }
+
+    #[test]
+    fn test_unc_and_verbatim_paths() {
+        let unc = WindowsPath::parse(r"\\build-01\tools\msvc-kit");
+        assert_eq!(
+            unc.root,
+            PathRoot::Unc {
+                server: "build-01".to_string(),
+                share: "tools".to_string()
+            }
+        );
+        assert_eq!(unc.to_windows(), r"\\build-01\tools\msvc-kit");
+        assert_eq!(unc.to_unix(UnixFlavor::Msys), "//build-01/tools/msvc-kit");
+
+        assert_eq!(to_msys(r"\\?\D:\msvc-kit"), "/d/msvc-kit");
+        assert_eq!(
+            WindowsPath::parse(r"\\?\UNC\build-01\tools\kit").to_windows(),
+            r"\\build-01\tools\kit"
+        );
+    }
+
+    #[test]
+    fn test_relative_paths() {
+        let path = WindowsPath::parse(r"VC\Tools");
+        assert!(!path.is_absolute());
+        assert_eq!(path.to_unix(UnixFlavor::Wsl), "VC/Tools");
+        assert_eq!(to_msys(r"\msvc-kit"), "/msvc-kit");
+    }
+
+    #[test]
+    fn test_from_unix() {
+        for unix in [
+            "/c/msvc-kit/VC",
+            "/mnt/c/msvc-kit/VC",
+            "/cygdrive/c/msvc-kit/VC",
+        ] {
+            assert_eq!(
+                WindowsPath::from_unix(unix).unwrap().to_windows(),
+                r"C:\msvc-kit\VC"
+            );
+        }
+        assert_eq!(
+            WindowsPath::from_unix("//server/share/kit")
+                .unwrap()
+                .to_windows(),
+            r"\\server\share\kit"
+        );
+        assert!(WindowsPath::from_unix("/usr/bin").is_none());
+        assert!(WindowsPath::from_unix("relative/path").is_none());
+    }
+
+    #[test]
+    fn test_flavor_parse() {
+        assert_eq!("WSL".parse::<UnixFlavor>(), Ok(UnixFlavor::Wsl));
+        assert_eq!("git-bash".parse::<UnixFlavor>(), Ok(UnixFlavor::Msys));
+        assert!("zsh".parse::<UnixFlavor>().is_err());
+    }
+}
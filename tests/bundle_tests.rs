@@ -1,6 +1,9 @@
 //! Bundle module tests
 
-use msvc_kit::bundle::{discover_bundle, generate_bundle_scripts, BundleLayout, BundleOptions};
+use msvc_kit::bundle::{
+    discover_bundle, generate_bundle_scripts, BundleLayout, BundleOptions, LayoutStyle,
+};
+use msvc_kit::scripts::Locale;
 use msvc_kit::version::Architecture;
 use std::path::PathBuf;
 
@@ -11,6 +14,7 @@ fn sample_layout() -> BundleLayout {
         sdk_version: "10.0.26100.0".to_string(),
         arch: Architecture::X64,
         host_arch: Architecture::X64,
+        style: LayoutStyle::Native,
     }
 }
 
@@ -198,6 +202,7 @@ fn test_bundle_layout_arm64_paths() {
         sdk_version: "10.0.26100.0".to_string(),
         arch: Architecture::Arm64,
         host_arch: Architecture::X64,
+        style: LayoutStyle::Native,
     };
 
     let bin_dir = layout.vc_bin_dir();
@@ -263,6 +268,11 @@ fn test_bundle_options_custom() {
         msvc_version: Some("14.44".to_string()),
         sdk_version: Some("10.0.26100.0".to_string()),
         parallel_downloads: 16,
+        post_install_hooks: vec![],
+        locale: Locale::default(),
+        offline: false,
+        dry_run: false,
+        layout_style: LayoutStyle::Native,
     };
 
     assert_eq!(opts.output_dir, PathBuf::from("C:/custom-bundle"));
@@ -291,6 +301,11 @@ fn test_bundle_options_clone() {
         msvc_version: Some("14.43".to_string()),
         sdk_version: None,
         parallel_downloads: 4,
+        post_install_hooks: vec![],
+        locale: Locale::default(),
+        offline: false,
+        dry_run: false,
+        layout_style: LayoutStyle::Native,
     };
 
     let cloned = opts.clone();
@@ -299,6 +314,32 @@ fn test_bundle_options_clone() {
     assert_eq!(cloned.msvc_version, opts.msvc_version);
 }
 
+#[test]
+fn test_bundle_options_json_roundtrip() {
+    let opts = BundleOptions {
+        output_dir: PathBuf::from("C:/test"),
+        arch: Architecture::Arm64,
+        msvc_version: Some("14.43".to_string()),
+        locale: Locale::ZhCn,
+        offline: true,
+        ..Default::default()
+    };
+
+    let json = serde_json::to_string(&opts).unwrap();
+    let parsed: BundleOptions = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.output_dir, opts.output_dir);
+    assert_eq!(parsed.arch, Architecture::Arm64);
+    assert_eq!(parsed.locale, Locale::ZhCn);
+    assert!(parsed.offline);
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+
+    let parsed: BundleOptions = serde_json::from_str("{}").unwrap();
+    assert_eq!(
+        parsed.parallel_downloads,
+        BundleOptions::default().parallel_downloads
+    );
+}
+
 // ============================================================================
 // discover_bundle Tests
 // ============================================================================
@@ -334,3 +375,68 @@ fn test_generate_bundle_scripts() {
     assert!(scripts.bash.contains("BASH_SOURCE"));
     assert!(scripts.readme.is_some());
 }
+
+// ============================================================================
+// LayoutStyle Tests
+// ============================================================================
+
+#[test]
+fn test_msvc_wine_layout_paths() {
+    let layout = sample_layout().with_style(LayoutStyle::MsvcWine);
+    let root = PathBuf::from("C:/msvc-bundle");
+
+    assert_eq!(layout.vc_dir(), root.join("vc"));
+    assert_eq!(
+        layout.vc_tools_dir(),
+        root.join("vc/tools/msvc/14.44.34823")
+    );
+    assert_eq!(layout.sdk_dir(), root.join("kits/10"));
+    assert_eq!(
+        layout.sdk_include_dir("um"),
+        root.join("kits/10/include/10.0.26100.0/um")
+    );
+    assert_eq!(
+        layout.sdk_lib_dir("ucrt"),
+        root.join("kits/10/lib/10.0.26100.0/ucrt/x64")
+    );
+    assert_eq!(
+        layout.cl_exe_path(),
+        root.join("vc/tools/msvc/14.44.34823/bin/Hostx64/x64/cl.exe")
+    );
+    assert_eq!(layout.to_json()["style"], "msvc-wine");
+}
+
+#[test]
+fn test_layout_style_parse() {
+    assert_eq!(
+        "native".parse::<LayoutStyle>().unwrap(),
+        LayoutStyle::Native
+    );
+    assert_eq!(
+        "msvc-wine".parse::<LayoutStyle>().unwrap(),
+        LayoutStyle::MsvcWine
+    );
+    assert!("wine".parse::<LayoutStyle>().is_err());
+    assert_eq!(LayoutStyle::MsvcWine.to_string(), "msvc-wine");
+    assert_eq!(LayoutStyle::default(), LayoutStyle::Native);
+}
+
+#[test]
+fn test_generate_msvc_wine_bundle_scripts() {
+    let layout = sample_layout().with_style(LayoutStyle::MsvcWine);
+    let scripts = generate_bundle_scripts(&layout).unwrap();
+
+    assert!(scripts
+        .cmd
+        .contains(r"%BUNDLE_ROOT%\vc\tools\msvc\14.44.34823\include"));
+    assert!(scripts
+        .powershell
+        .contains(r"$BundleRoot\kits\10\include\10.0.26100.0\ucrt"));
+    assert!(scripts
+        .bash
+        .contains("$BUNDLE_ROOT/kits/10/lib/10.0.26100.0/um/x64"));
+    for script in [&scripts.cmd, &scripts.powershell, &scripts.bash] {
+        assert!(!script.contains("Windows Kits"));
+        assert!(!script.contains("MSVC\\14") && !script.contains("MSVC/14"));
+    }
+}
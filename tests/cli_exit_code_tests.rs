@@ -135,6 +135,42 @@ fn test_config_command_exits_zero(#[case] args: &[&str]) {
     );
 }
 
+#[test]
+#[cfg(not(windows))]
+fn test_config_reset_with_broken_config_exits_zero() {
+    // `config --reset` must recover from a config file that no longer parses
+    let home = tempfile::tempdir().expect("Failed to create temp dir");
+    let config_dir = if cfg!(target_os = "macos") {
+        home.path()
+            .join("Library/Application Support/com.loonghao.msvc-kit")
+    } else {
+        home.path().join(".config/msvc-kit")
+    };
+    std::fs::create_dir_all(&config_dir).expect("Failed to create config dir");
+    let config_path = config_dir.join("config.toml");
+    std::fs::write(&config_path, "install_dir = [").expect("Failed to write config");
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "--reset"])
+        .env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join(".config"))
+        .output()
+        .expect("Failed to run msvc-kit config --reset");
+
+    assert!(
+        output.status.success(),
+        "Expected exit code 0 for config --reset with a broken config, got: {:?}\n{}",
+        output.status.code(),
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let content = std::fs::read_to_string(&config_path).expect("Failed to read config");
+    assert!(
+        toml::from_str::<toml::Value>(&content).is_ok(),
+        "Expected the config file to be rewritten, got: {}",
+        content
+    );
+}
+
 #[test]
 fn test_invalid_subcommand_exits_nonzero() {
     // Invalid subcommand should exit with non-zero code
@@ -3,6 +3,7 @@
 use msvc_kit::config::MsvcKitConfig;
 use msvc_kit::error::MsvcKitError;
 use msvc_kit::version::Architecture;
+use msvc_kit::VerifyMode;
 use std::path::PathBuf;
 
 // ============================================================================
@@ -12,7 +13,7 @@ use std::path::PathBuf;
 #[test]
 fn test_default_config() {
     let config = MsvcKitConfig::default();
-    assert!(config.verify_hashes);
+    assert_eq!(config.verify_mode, VerifyMode::Enforce);
     assert_eq!(config.parallel_downloads, 4);
     assert_eq!(config.default_arch, Architecture::X64);
     assert!(config.default_msvc_version.is_none());
@@ -26,9 +27,15 @@ fn test_config_serde() {
         default_msvc_version: Some("14.44".to_string()),
         default_sdk_version: Some("10.0.26100.0".to_string()),
         default_arch: Architecture::X86,
-        verify_hashes: false,
+        verify_mode: VerifyMode::Off,
+        hash_exemptions: Vec::new(),
+        alternate_endpoints: Vec::new(),
         parallel_downloads: 8,
         cache_dir: Some(PathBuf::from("C:/cache")),
+        license_accepted: false,
+        post_install: Default::default(),
+        shortcut: Default::default(),
+        tls_ca_file: None,
     };
 
     let toml_str = toml::to_string(&config).unwrap();
@@ -38,7 +45,7 @@ fn test_config_serde() {
     assert_eq!(parsed.default_msvc_version, config.default_msvc_version);
     assert_eq!(parsed.default_sdk_version, config.default_sdk_version);
     assert_eq!(parsed.default_arch, config.default_arch);
-    assert_eq!(parsed.verify_hashes, config.verify_hashes);
+    assert_eq!(parsed.verify_mode, config.verify_mode);
     assert_eq!(parsed.parallel_downloads, config.parallel_downloads);
 }
 
@@ -235,9 +242,15 @@ fn test_config_toml_roundtrip_all_fields() {
         default_msvc_version: Some("14.44".to_string()),
         default_sdk_version: Some("10.0.26100.0".to_string()),
         default_arch: Architecture::Arm64,
-        verify_hashes: false,
+        verify_mode: VerifyMode::Off,
+        hash_exemptions: Vec::new(),
+        alternate_endpoints: Vec::new(),
         parallel_downloads: 16,
         cache_dir: Some(PathBuf::from("C:/cache")),
+        license_accepted: false,
+        post_install: Default::default(),
+        shortcut: Default::default(),
+        tls_ca_file: None,
     };
 
     // Serialize to TOML string and back
@@ -248,7 +261,7 @@ fn test_config_toml_roundtrip_all_fields() {
     assert_eq!(restored.default_msvc_version, config.default_msvc_version);
     assert_eq!(restored.default_sdk_version, config.default_sdk_version);
     assert_eq!(restored.default_arch, config.default_arch);
-    assert_eq!(restored.verify_hashes, config.verify_hashes);
+    assert_eq!(restored.verify_mode, config.verify_mode);
     assert_eq!(restored.parallel_downloads, config.parallel_downloads);
     assert_eq!(restored.cache_dir, config.cache_dir);
 }
@@ -261,7 +274,7 @@ fn test_config_toml_roundtrip_minimal() {
     let toml_str = toml::to_string(&config).unwrap();
     let restored: MsvcKitConfig = toml::from_str(&toml_str).unwrap();
 
-    assert_eq!(restored.verify_hashes, config.verify_hashes);
+    assert_eq!(restored.verify_mode, config.verify_mode);
     assert_eq!(restored.parallel_downloads, config.parallel_downloads);
     assert_eq!(restored.default_arch, config.default_arch);
 }
@@ -284,7 +297,7 @@ cache_dir = "C:\\msvc-kit\\cache"
     assert_eq!(config.default_msvc_version, Some("14.40".to_string()));
     assert_eq!(config.default_sdk_version, Some("10.0.22621".to_string()));
     assert_eq!(config.default_arch, Architecture::X64);
-    assert!(config.verify_hashes);
+    assert_eq!(config.verify_mode, VerifyMode::Enforce);
     assert_eq!(config.parallel_downloads, 8);
     assert_eq!(config.cache_dir, Some(PathBuf::from("C:\\msvc-kit\\cache")));
 }
@@ -1,11 +1,15 @@
 //! Downloader module tests
 
 use msvc_kit::downloader::{
-    compute_hash, hashes_match, AvailableVersions, CacheManager, ComponentType, DownloadOptions,
-    DownloadPreview, FileSystemCacheManager, HttpClientConfig, NoopProgressHandler, PackagePreview,
-    ProgressHandler,
+    compute_hash, hashes_match, AppPlatform, AvailableVersions, CacheManager, ChunkedDownloads,
+    ComponentType, DownloadOptions, DownloadPreview, FileSystemCacheManager, HttpClientConfig,
+    MsvcComponent, NoopProgressHandler, PackagePreview, ProgressHandler,
 };
 use msvc_kit::version::Architecture;
+use msvc_kit::CleanupPolicy;
+use msvc_kit::OptionsError;
+use msvc_kit::VerifyMode;
+use msvc_kit::{MsvcVersionSelector, ReleaseChannel};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
@@ -19,7 +23,7 @@ fn test_download_options_default() {
     let options = DownloadOptions::default();
     assert!(options.msvc_version.is_none());
     assert!(options.sdk_version.is_none());
-    assert!(options.verify_hashes);
+    assert_eq!(options.verify_mode, VerifyMode::Enforce);
     assert_eq!(options.parallel_downloads, 4);
     assert_eq!(options.arch, Architecture::X64);
 }
@@ -41,10 +45,44 @@ fn test_download_options_custom() {
     assert_eq!(options.target_dir, PathBuf::from("C:/custom"));
     assert_eq!(options.arch, Architecture::Arm64);
     assert_eq!(options.host_arch, Some(Architecture::X64));
-    assert!(!options.verify_hashes);
+    assert_eq!(options.verify_mode, VerifyMode::Off);
     assert_eq!(options.parallel_downloads, 16);
 }
 
+#[test]
+fn test_download_options_json_roundtrip() {
+    let options = DownloadOptions::builder()
+        .msvc_version("14.44")
+        .target_dir("C:/custom")
+        .arch(Architecture::Arm64)
+        .verify_mode(VerifyMode::Warn)
+        .include_component(MsvcComponent::Spectre)
+        .include_component(MsvcComponent::Custom("Microsoft.VC.*.MFC".to_string()))
+        .app_platform(AppPlatform::Store)
+        .overall_timeout(Duration::from_secs(600))
+        .http_client(reqwest::Client::new())
+        .build();
+
+    let value = serde_json::to_value(&options).unwrap();
+    assert_eq!(value["overall_timeout"], 600);
+    assert!(value["per_file_timeout"].is_null());
+    assert!(value.get("http_client").is_none());
+
+    let parsed: DownloadOptions = serde_json::from_value(value.clone()).unwrap();
+    assert_eq!(parsed.msvc_version, Some("14.44".to_string()));
+    assert_eq!(parsed.arch, Architecture::Arm64);
+    assert_eq!(parsed.verify_mode, VerifyMode::Warn);
+    assert_eq!(parsed.include_components, options.include_components);
+    assert!(parsed.app_platforms.contains(&AppPlatform::Store));
+    assert_eq!(parsed.overall_timeout, Some(Duration::from_secs(600)));
+    assert!(parsed.http_client.is_none());
+
+    // Missing fields take their defaults
+    let parsed: DownloadOptions = serde_json::from_str(r#"{"sdk_version": "26100"}"#).unwrap();
+    assert_eq!(parsed.sdk_version, Some("26100".to_string()));
+    assert_eq!(parsed.parallel_downloads, 4);
+}
+
 #[test]
 fn test_builder_all_options() {
     let options = DownloadOptions::builder()
@@ -63,7 +101,7 @@ fn test_builder_all_options() {
     assert_eq!(options.target_dir, PathBuf::from("C:/custom"));
     assert_eq!(options.arch, Architecture::Arm64);
     assert_eq!(options.host_arch, Some(Architecture::X64));
-    assert!(!options.verify_hashes);
+    assert_eq!(options.verify_mode, VerifyMode::Off);
     assert_eq!(options.parallel_downloads, 16);
     assert!(options.dry_run);
 }
@@ -128,11 +166,245 @@ fn test_download_options_clone() {
     assert_eq!(cloned.sdk_version, options.sdk_version);
     assert_eq!(cloned.target_dir, options.target_dir);
     assert_eq!(cloned.arch, options.arch);
-    assert_eq!(cloned.verify_hashes, options.verify_hashes);
+    assert_eq!(cloned.verify_mode, options.verify_mode);
     assert_eq!(cloned.parallel_downloads, options.parallel_downloads);
     assert_eq!(cloned.dry_run, options.dry_run);
 }
 
+#[test]
+fn test_builder_build_clamps_invalid_values() {
+    let options = DownloadOptions::builder()
+        .target_dir("")
+        .parallel_downloads(0)
+        .exclude_pattern("")
+        .exclude_pattern(".uwp")
+        .build();
+
+    assert_eq!(options.parallel_downloads, 1);
+    assert_eq!(options.target_dir, PathBuf::from("msvc-kit"));
+    assert_eq!(options.exclude_patterns, vec![".uwp".to_string()]);
+}
+
+#[test]
+fn test_builder_try_build_validation() {
+    let valid = DownloadOptions::builder()
+        .target_dir("C:/test")
+        .include_component(MsvcComponent::Spectre)
+        .exclude_pattern(".uwp")
+        .try_build();
+    assert!(valid.is_ok());
+
+    let err = DownloadOptions::builder()
+        .parallel_downloads(0)
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::ZeroParallelDownloads);
+
+    let err = DownloadOptions::builder()
+        .target_dir("")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::EmptyTargetDir);
+
+    for dir in ["C:/tools /msvc-kit", "C:/msvc-kit.", "C:/tools/nul"] {
+        let err = DownloadOptions::builder()
+            .target_dir(dir)
+            .try_build()
+            .unwrap_err();
+        assert!(
+            matches!(err, OptionsError::InvalidTargetDir { .. }),
+            "{}",
+            dir
+        );
+    }
+
+    let err = DownloadOptions::builder()
+        .msvc_version(" ")
+        .try_build()
+        .unwrap_err();
+    assert!(matches!(err, OptionsError::EmptyVersion { .. }));
+
+    let err = DownloadOptions::builder()
+        .exclude_pattern("")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::EmptyExcludePattern);
+
+    let err = DownloadOptions::builder()
+        .only_pattern(" ")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::EmptyOnlyPattern);
+
+    let err = DownloadOptions::builder()
+        .include_component(MsvcComponent::Spectre)
+        .exclude_pattern("Spectre")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        OptionsError::ConflictingPattern {
+            component: "spectre".to_string(),
+            pattern: "spectre".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_builder_extract_filters() {
+    let options = DownloadOptions::builder()
+        .extract_filter("**/include/**")
+        .extract_filter(" ")
+        .build();
+    assert_eq!(options.extract_filters, vec!["**/include/**".to_string()]);
+
+    let err = DownloadOptions::builder()
+        .extract_filter("")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::EmptyExtractFilter);
+}
+
+#[test]
+fn test_builder_target_triple() {
+    let options = DownloadOptions::builder()
+        .target("i686-pc-windows-msvc")
+        .build();
+    assert_eq!(options.arch, Architecture::X86);
+
+    let err = DownloadOptions::builder()
+        .target("x86_64-unknown-linux-gnu")
+        .try_build()
+        .unwrap_err();
+    assert!(matches!(err, OptionsError::UnsupportedTarget(_)));
+}
+
+#[test]
+fn test_builder_timeouts() {
+    let options = DownloadOptions::builder()
+        .overall_timeout(Duration::from_secs(600))
+        .per_file_timeout(Duration::from_secs(60))
+        .build();
+    assert_eq!(options.overall_timeout, Some(Duration::from_secs(600)));
+    assert_eq!(options.per_file_timeout, Some(Duration::from_secs(60)));
+
+    let err = DownloadOptions::builder()
+        .per_file_timeout(Duration::ZERO)
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::ZeroTimeout);
+}
+
+#[test]
+fn test_builder_layout_dir() {
+    let options = DownloadOptions::builder()
+        .layout_dir("D:/vs-layout")
+        .build();
+    assert_eq!(options.layout_dir, Some(PathBuf::from("D:/vs-layout")));
+    assert!(DownloadOptions::builder().build().layout_dir.is_none());
+}
+
+#[test]
+fn test_builder_msvc_selector() {
+    let options = DownloadOptions::builder()
+        .msvc_selector(MsvcVersionSelector::Stable)
+        .build();
+    assert_eq!(options.channel, ReleaseChannel::Stable);
+    assert_eq!(options.msvc_selector(), MsvcVersionSelector::Stable);
+
+    let options = DownloadOptions::builder()
+        .channel(ReleaseChannel::Stable)
+        .msvc_version("14.44")
+        .build();
+    assert_eq!(
+        options.msvc_selector(),
+        MsvcVersionSelector::Version("14.44".to_string())
+    );
+
+    let options = DownloadOptions::builder()
+        .msvc_version("14.44")
+        .msvc_selector(MsvcVersionSelector::Latest)
+        .build();
+    assert_eq!(options.msvc_version, None);
+    assert_eq!(options.channel, ReleaseChannel::Latest);
+}
+
+#[test]
+fn test_builder_auto_cleanup() {
+    let options = DownloadOptions::builder()
+        .auto_cleanup(CleanupPolicy::KeepDays(7))
+        .build();
+    assert_eq!(options.auto_cleanup, CleanupPolicy::KeepDays(7));
+    assert_eq!(
+        DownloadOptions::builder().build().auto_cleanup,
+        CleanupPolicy::Keep
+    );
+}
+
+#[test]
+fn test_builder_sdk_installer_pattern() {
+    let options = DownloadOptions::builder()
+        .sdk_installer_pattern("universal crt")
+        .sdk_installer_pattern("  ")
+        .build();
+    assert_eq!(options.sdk_installer_patterns, vec!["universal crt"]);
+}
+
+#[test]
+fn test_builder_max_download_rate() {
+    let options = DownloadOptions::builder()
+        .max_download_rate(2 * 1024 * 1024)
+        .build();
+    assert_eq!(options.max_download_rate, Some(2 * 1024 * 1024));
+
+    let err = DownloadOptions::builder()
+        .max_download_rate(0)
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::ZeroDownloadRate);
+}
+
+#[test]
+fn test_builder_chunked_downloads() {
+    let options = DownloadOptions::builder()
+        .chunked_downloads(ChunkedDownloads {
+            connections: 8,
+            ..Default::default()
+        })
+        .build();
+    let chunked = options.chunked_downloads.unwrap();
+    assert_eq!(chunked.connections, 8);
+    assert_eq!(chunked.ranges(1 << 30).len(), 8);
+
+    let err = DownloadOptions::builder()
+        .chunked_downloads(ChunkedDownloads {
+            connections: 0,
+            ..Default::default()
+        })
+        .try_build()
+        .unwrap_err();
+    assert_eq!(err, OptionsError::ZeroConnections);
+}
+
+#[test]
+fn test_builder_alternate_endpoints() {
+    let options = DownloadOptions::builder()
+        .alternate_endpoint("https://mirror.corp.example/vs/")
+        .alternate_endpoints(vec!["http://10.0.0.5:8080".to_string()])
+        .try_build()
+        .unwrap();
+    assert_eq!(options.alternate_endpoints.len(), 2);
+
+    let err = DownloadOptions::builder()
+        .alternate_endpoint("mirror.corp.example")
+        .try_build()
+        .unwrap_err();
+    assert_eq!(
+        err,
+        OptionsError::InvalidEndpoint("mirror.corp.example".to_string())
+    );
+}
+
 // ============================================================================
 // DownloadPreview Tests
 // ============================================================================
@@ -145,7 +417,9 @@ fn test_download_preview_format() {
         package_count: 10,
         file_count: 100,
         total_size: 1024 * 1024 * 500,
+        installed_size: 0,
         packages: vec![],
+        host_arch: None,
     };
 
     let formatted = preview.format();
@@ -163,6 +437,7 @@ fn test_download_preview_format_with_packages() {
         package_count: 5,
         file_count: 250,
         total_size: 1024 * 1024 * 1024,
+        installed_size: 0,
         packages: vec![
             PackagePreview {
                 id: "Microsoft.Windows.SDK.Headers".to_string(),
@@ -177,6 +452,7 @@ fn test_download_preview_format_with_packages() {
                 size: 512 * 1024 * 1024,
             },
         ],
+        host_arch: None,
     };
 
     let formatted = preview.format();
@@ -194,7 +470,9 @@ fn test_download_preview_small_size() {
         package_count: 1,
         file_count: 1,
         total_size: 1024,
+        installed_size: 0,
         packages: vec![],
+        host_arch: None,
     };
 
     let formatted = preview.format();
@@ -211,7 +489,9 @@ fn test_download_preview_debug() {
         package_count: 1,
         file_count: 1,
         total_size: 1024,
+        installed_size: 0,
         packages: vec![],
+        host_arch: None,
     };
 
     let debug_str = format!("{:?}", preview);
@@ -227,12 +507,14 @@ fn test_download_preview_clone() {
         package_count: 2,
         file_count: 20,
         total_size: 2048,
+        installed_size: 0,
         packages: vec![PackagePreview {
             id: "pkg1".to_string(),
             version: "1.0".to_string(),
             file_count: 10,
             size: 1024,
         }],
+        host_arch: None,
     };
 
     let cloned = preview.clone();
@@ -242,6 +524,32 @@ fn test_download_preview_clone() {
     assert_eq!(cloned.packages.len(), preview.packages.len());
 }
 
+#[test]
+fn test_download_preview_json_roundtrip() {
+    let preview = DownloadPreview {
+        component: "SDK".to_string(),
+        version: "10.0.26100.0".to_string(),
+        package_count: 1,
+        file_count: 3,
+        total_size: 4096,
+        installed_size: 8192,
+        packages: vec![PackagePreview {
+            id: "Win11SDK_10.0.26100".to_string(),
+            version: "10.0.26100.0".to_string(),
+            file_count: 3,
+            size: 4096,
+        }],
+        host_arch: None,
+    };
+
+    let json = serde_json::to_string(&preview).unwrap();
+    let parsed: DownloadPreview = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.component, "SDK");
+    assert_eq!(parsed.installed_size, 8192);
+    assert_eq!(parsed.packages[0].id, "Win11SDK_10.0.26100");
+    assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+}
+
 // ============================================================================
 // PackagePreview Tests
 // ============================================================================
@@ -285,6 +593,7 @@ fn test_available_versions_debug() {
         sdk_versions: vec!["10.0.26100.0".to_string()],
         latest_msvc: Some("14.44".to_string()),
         latest_sdk: Some("10.0.26100.0".to_string()),
+        channel: Default::default(),
     };
 
     let debug_str = format!("{:?}", versions);
@@ -299,6 +608,7 @@ fn test_available_versions_clone() {
         sdk_versions: vec!["10.0.26100.0".to_string()],
         latest_msvc: Some("14.44".to_string()),
         latest_sdk: Some("10.0.26100.0".to_string()),
+        channel: Default::default(),
     };
 
     let cloned = versions.clone();
@@ -308,6 +618,23 @@ fn test_available_versions_clone() {
     assert_eq!(cloned.latest_sdk, versions.latest_sdk);
 }
 
+#[test]
+fn test_available_versions_json_roundtrip() {
+    let versions = AvailableVersions {
+        msvc_versions: vec!["14.44".to_string(), "14.43".to_string()],
+        sdk_versions: vec!["10.0.26100.0".to_string()],
+        latest_msvc: Some("14.44".to_string()),
+        latest_sdk: None,
+        channel: Default::default(),
+    };
+
+    let json = serde_json::to_string(&versions).unwrap();
+    let parsed: AvailableVersions = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.msvc_versions, versions.msvc_versions);
+    assert_eq!(parsed.latest_msvc, versions.latest_msvc);
+    assert!(parsed.latest_sdk.is_none());
+}
+
 // ============================================================================
 // ComponentType Tests
 // ============================================================================
@@ -16,7 +16,7 @@ use msvc_kit::downloader::{DownloadIndex, DownloadStatus, IndexEntry};
 use msvc_kit::env::{generate_activation_script, MsvcEnvironment};
 use msvc_kit::installer::InstallInfo;
 use msvc_kit::version::Architecture;
-use msvc_kit::{DownloadOptions, ShellType};
+use msvc_kit::{DownloadOptions, ShellType, VerifyMode};
 
 // ============================================================================
 // Download Index Tests
@@ -160,6 +160,116 @@ mod download_index_tests {
         let result = index.get_entry("nonexistent.vsix").await.unwrap();
         assert!(result.is_none());
     }
+
+    fn completed_entry(dir: &std::path::Path, name: &str, content: &[u8]) -> IndexEntry {
+        let hash = msvc_kit::downloader::compute_hash(content);
+        IndexEntry {
+            file_name: name.to_string(),
+            url: format!("https://example.com/{}", name),
+            size: content.len() as u64,
+            sha256: Some(hash.clone()),
+            computed_hash: Some(hash),
+            local_path: dir.join(name),
+            status: DownloadStatus::Completed,
+            bytes_downloaded: content.len() as u64,
+            hash_verified: true,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_download_index_export_import_relocated() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let export_path = source.path().join("index.json");
+
+        let mut index = DownloadIndex::load(&source.path().join("index.db"))
+            .await
+            .unwrap();
+        for (name, content) in [
+            ("intact.vsix", b"intact".as_slice()),
+            ("corrupt.vsix", b"original"),
+            ("missing.vsix", b"missing"),
+        ] {
+            std::fs::write(source.path().join(name), content).unwrap();
+            index
+                .upsert_entry(&completed_entry(source.path(), name, content))
+                .await
+                .unwrap();
+        }
+        assert_eq!(index.export(&export_path).await.unwrap(), 3);
+
+        // Relocate the downloads, corrupting and dropping one file each
+        std::fs::copy(
+            source.path().join("intact.vsix"),
+            target.path().join("intact.vsix"),
+        )
+        .unwrap();
+        std::fs::write(target.path().join("corrupt.vsix"), b"tampered").unwrap();
+
+        let mut imported = DownloadIndex::load(&target.path().join("index.db"))
+            .await
+            .unwrap();
+        let report = imported.import(&export_path).await.unwrap();
+        assert_eq!(report.imported, ["intact.vsix"]);
+        assert_eq!(report.downgraded, ["corrupt.vsix"]);
+        assert_eq!(report.missing, ["missing.vsix"]);
+
+        let intact = imported.get_entry("intact.vsix").await.unwrap().unwrap();
+        assert_eq!(intact.status, DownloadStatus::Completed);
+        assert_eq!(intact.local_path, target.path().join("intact.vsix"));
+        let corrupt = imported.get_entry("corrupt.vsix").await.unwrap().unwrap();
+        assert_eq!(corrupt.status, DownloadStatus::Partial);
+        assert!(imported.get_entry("missing.vsix").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_download_index_rebase() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let old_root = temp_dir.path().join("old");
+        let new_root = temp_dir.path().join("new");
+
+        let mut index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+            .await
+            .unwrap();
+        index
+            .upsert_entry(&completed_entry(&old_root.join("msvc"), "a.vsix", b"a"))
+            .await
+            .unwrap();
+        index
+            .upsert_entry(&completed_entry(temp_dir.path(), "b.vsix", b"b"))
+            .await
+            .unwrap();
+
+        assert_eq!(index.rebase(&old_root, &new_root).await.unwrap(), 1);
+        let moved = index.get_entry("a.vsix").await.unwrap().unwrap();
+        assert_eq!(moved.local_path, new_root.join("msvc").join("a.vsix"));
+        let other = index.get_entry("b.vsix").await.unwrap().unwrap();
+        assert_eq!(other.local_path, temp_dir.path().join("b.vsix"));
+    }
+
+    #[tokio::test]
+    async fn test_download_index_import_rejects_tampered_export() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let export_path = temp_dir.path().join("index.json");
+
+        let mut index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+            .await
+            .unwrap();
+        std::fs::write(temp_dir.path().join("a.vsix"), b"a").unwrap();
+        index
+            .upsert_entry(&completed_entry(temp_dir.path(), "a.vsix", b"a"))
+            .await
+            .unwrap();
+        index.export(&export_path).await.unwrap();
+
+        let content = std::fs::read_to_string(&export_path).unwrap();
+        std::fs::write(&export_path, content.replace("\"size\": 1", "\"size\": 2")).unwrap();
+        assert!(matches!(
+            index.import(&export_path).await,
+            Err(msvc_kit::MsvcKitError::HashMismatch { .. })
+        ));
+    }
 }
 
 // ============================================================================
@@ -176,6 +286,11 @@ mod env_generation_tests {
             install_path: PathBuf::from(format!("C:/test/{}", component_type)),
             downloaded_files: vec![],
             arch: Architecture::X64,
+            extract_filters: vec![],
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
         }
     }
 
@@ -271,9 +386,15 @@ mod config_persistence_tests {
             default_msvc_version: Some("14.44".to_string()),
             default_sdk_version: Some("10.0.26100.0".to_string()),
             default_arch: Architecture::Arm64,
-            verify_hashes: false,
+            verify_mode: VerifyMode::Off,
+            hash_exemptions: Vec::new(),
+            alternate_endpoints: Vec::new(),
             parallel_downloads: 16,
             cache_dir: Some(PathBuf::from("C:/cache")),
+            license_accepted: false,
+            post_install: Default::default(),
+            shortcut: Default::default(),
+            tls_ca_file: None,
         };
 
         // Serialize to TOML
@@ -288,7 +409,7 @@ mod config_persistence_tests {
         assert_eq!(loaded.default_msvc_version, config.default_msvc_version);
         assert_eq!(loaded.default_sdk_version, config.default_sdk_version);
         assert_eq!(loaded.default_arch, config.default_arch);
-        assert_eq!(loaded.verify_hashes, config.verify_hashes);
+        assert_eq!(loaded.verify_mode, config.verify_mode);
         assert_eq!(loaded.parallel_downloads, config.parallel_downloads);
     }
 }
@@ -357,6 +478,139 @@ mod extraction_tests {
         let extracted_file = extract_dir.join("dir1").join("dir2").join("file.txt");
         assert!(extracted_file.exists());
     }
+
+    fn write_vsix(path: &std::path::Path, entries: &[(&str, &[u8])]) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, content) in entries {
+            zip.start_file(*name, options).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_extract_packages_reports_conflicts() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let first = temp_dir.path().join("first.vsix");
+        let second = temp_dir.path().join("second.vsix");
+        let extract_dir = temp_dir.path().join("extracted");
+
+        write_vsix(
+            &first,
+            &[
+                ("Contents/include/conflict.h", b"one"),
+                ("Contents/include/shared.h", b"same"),
+            ],
+        );
+        write_vsix(
+            &second,
+            &[
+                ("Contents/include/conflict.h", b"two"),
+                ("Contents/include/shared.h", b"same"),
+            ],
+        );
+        let files = vec![first, second];
+
+        let report =
+            msvc_kit::installer::extract_packages_with_report(&files, &extract_dir, "Test", false)
+                .await
+                .unwrap();
+        assert_eq!(report.extracted, 2);
+        assert_eq!(report.conflicts.len(), 1);
+        let conflict = &report.conflicts[0];
+        assert_eq!(conflict.path, "include/conflict.h");
+        assert_eq!(conflict.first_package, "first.vsix");
+        assert_eq!(conflict.second_package, "second.vsix");
+        assert_ne!(conflict.first_sha256, conflict.second_sha256);
+
+        // Cached packages are checked through their receipts; strict mode fails
+        let err =
+            msvc_kit::installer::extract_packages_with_report(&files, &extract_dir, "Test", true)
+                .await
+                .unwrap_err();
+        assert!(matches!(err, msvc_kit::MsvcKitError::FileConflicts(ref c) if c.len() == 1));
+
+        // The later package is left unmarked, so the next run checks it again
+        let receipts = extract_dir.join(msvc_kit::installer::RECEIPT_DIR_NAME);
+        assert!(receipts.join("first.vsix.done").exists());
+        assert!(!receipts.join("second.vsix.done").exists());
+        let err =
+            msvc_kit::installer::extract_packages_with_report(&files, &extract_dir, "Test", true)
+                .await
+                .unwrap_err();
+        assert!(matches!(err, msvc_kit::MsvcKitError::FileConflicts(_)));
+    }
+
+    #[tokio::test]
+    async fn test_extract_packages_conflicts_name_package_ids() {
+        use msvc_kit::downloader::{DownloadIndex, IndexedPackage};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let first = temp_dir.path().join("first.vsix");
+        let second = temp_dir.path().join("second.vsix");
+        let extract_dir = temp_dir.path().join("extracted");
+        write_vsix(&first, &[("Contents/include/conflict.h", b"one")]);
+        write_vsix(&second, &[("Contents/include/conflict.h", b"two")]);
+
+        {
+            let mut index = DownloadIndex::load(&temp_dir.path().join("index.db"))
+                .await
+                .unwrap();
+            index
+                .record_package(&IndexedPackage {
+                    id: "Microsoft.VC.Test.First".to_string(),
+                    version: "1.0".to_string(),
+                    chip: None,
+                    payloads: vec!["first.vsix".to_string()],
+                })
+                .await
+                .unwrap();
+        }
+
+        let report = msvc_kit::installer::extract_packages_with_report(
+            &[first, second],
+            &extract_dir,
+            "Test",
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].first_package, "Microsoft.VC.Test.First");
+        // Unknown to the index: keeps the payload name
+        assert_eq!(report.conflicts[0].second_package, "second.vsix");
+    }
+
+    #[tokio::test]
+    async fn test_extract_packages_failure_skips_marker() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let good = temp_dir.path().join("good.vsix");
+        let bad = temp_dir.path().join("bad.vsix");
+        let extract_dir = temp_dir.path().join("extracted");
+
+        write_vsix(&good, &[("Contents/include/good.h", b"good")]);
+        write_vsix(&bad, &[("Contents/include/blocked.h", b"bad")]);
+        std::fs::create_dir_all(extract_dir.join("include/blocked.h")).unwrap();
+        let files = vec![good, bad];
+
+        let err =
+            msvc_kit::installer::extract_packages_with_report(&files, &extract_dir, "Test", false)
+                .await
+                .unwrap_err();
+        assert!(matches!(
+            err,
+            msvc_kit::MsvcKitError::Extraction { ref archive, ref entry, .. }
+                if archive == "bad.vsix" && entry == "Contents/include/blocked.h"
+        ));
+        assert!(err.to_string().contains("blocked.h"));
+
+        let receipts = extract_dir.join(msvc_kit::installer::RECEIPT_DIR_NAME);
+        assert!(receipts.join("good.vsix.done").exists());
+        assert!(!receipts.join("bad.vsix.done").exists());
+    }
 }
 
 // ============================================================================
@@ -373,16 +627,22 @@ mod download_options_builder_tests {
             default_msvc_version: Some("14.43".to_string()),
             default_sdk_version: Some("10.0.22621.0".to_string()),
             default_arch: Architecture::X86,
-            verify_hashes: false,
+            verify_mode: VerifyMode::Off,
+            hash_exemptions: Vec::new(),
+            alternate_endpoints: Vec::new(),
             parallel_downloads: 2,
             cache_dir: None,
+            license_accepted: false,
+            post_install: Default::default(),
+            shortcut: Default::default(),
+            tls_ca_file: None,
         };
 
         // Options can override config - use builder pattern
         let mut options = DownloadOptions::builder()
             .target_dir(&config.install_dir)
             .arch(config.default_arch)
-            .verify_hashes(config.verify_hashes)
+            .verify_mode(config.verify_mode)
             .parallel_downloads(config.parallel_downloads)
             .build();
         options.msvc_version = config.default_msvc_version.clone();
@@ -391,7 +651,7 @@ mod download_options_builder_tests {
         assert_eq!(options.msvc_version, Some("14.43".to_string()));
         assert_eq!(options.sdk_version, Some("10.0.22621.0".to_string()));
         assert_eq!(options.arch, Architecture::X86);
-        assert!(!options.verify_hashes);
+        assert_eq!(options.verify_mode, VerifyMode::Off);
         assert_eq!(options.parallel_downloads, 2);
     }
 
@@ -405,7 +665,7 @@ mod download_options_builder_tests {
             .target_dir("C:/custom")
             .arch(Architecture::Arm64)
             .host_arch(Architecture::X64)
-            .verify_hashes(config.verify_hashes)
+            .verify_mode(config.verify_mode)
             .parallel_downloads(8)
             .build();
 
@@ -474,7 +734,7 @@ mod network_tests {
         let options = DownloadOptions {
             target_dir: temp_dir.path().to_path_buf(),
             arch: Architecture::X64,
-            verify_hashes: true,
+            verify_mode: VerifyMode::Enforce,
             parallel_downloads: 4,
             ..Default::default()
         };
@@ -500,7 +760,12 @@ mod network_tests {
         assert!(!sdk_info.version.is_empty());
 
         // Setup environment
-        let env_result = msvc_kit::setup_environment(&msvc_info, Some(&sdk_info));
+        let env_result = msvc_kit::setup_environment_with_arch(
+            &msvc_info,
+            Some(&sdk_info),
+            msvc_kit::Architecture::host(),
+            msvc_info.arch,
+        );
         assert!(
             env_result.is_ok(),
             "Environment setup failed: {:?}",
@@ -171,6 +171,9 @@ mod windows_env_tests {
 
         assert_eq!(vars.get("VCToolsVersion").unwrap(), "14.44.33807");
         assert_eq!(vars.get("WindowsSDKVersion").unwrap(), "10.0.26100.0\\");
+        assert_eq!(vars.get("WindowsSDKLibVersion").unwrap(), "10.0.26100.0\\");
+        assert_eq!(vars.get("UCRTVersion").unwrap(), "10.0.26100.0");
+        assert_eq!(vars.get("UniversalCRTSdkDir"), vars.get("WindowsSdkDir"));
     }
 
     #[test]
@@ -308,6 +311,11 @@ fn create_test_install_info(component_type: &str) -> InstallInfo {
         install_path: PathBuf::from("C:/test/path"),
         downloaded_files: vec![],
         arch: Architecture::X64,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
     }
 }
 
@@ -340,6 +348,11 @@ fn test_install_info_bin_dir_sdk() {
         install_path: PathBuf::from("C:/test/sdk"),
         downloaded_files: vec![],
         arch: Architecture::X64,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
     };
     let bin_dir = info.bin_dir();
     assert!(bin_dir.to_string_lossy().contains("bin"));
@@ -354,6 +367,11 @@ fn test_install_info_bin_dir_unknown() {
         install_path: PathBuf::from("C:/test"),
         downloaded_files: vec![],
         arch: Architecture::X64,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
     };
     let bin_dir = info.bin_dir();
     assert!(bin_dir.to_string_lossy().contains("bin"));
@@ -374,6 +392,11 @@ fn test_install_info_include_dir_sdk() {
         install_path: PathBuf::from("C:/test/sdk"),
         downloaded_files: vec![],
         arch: Architecture::X64,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
     };
     let include_dir = info.include_dir();
     assert!(include_dir.to_string_lossy().contains("Include"));
@@ -396,6 +419,11 @@ fn test_install_info_lib_dir_sdk() {
         install_path: PathBuf::from("C:/test/sdk"),
         downloaded_files: vec![],
         arch: Architecture::X64,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
     };
     let lib_dir = info.lib_dir();
     assert!(lib_dir.to_string_lossy().contains("Lib"));
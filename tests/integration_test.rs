@@ -2,7 +2,7 @@
 
 use msvc_kit::config::MsvcKitConfig;
 use msvc_kit::version::Architecture;
-use msvc_kit::DownloadOptions;
+use msvc_kit::{DownloadOptions, VerifyMode};
 
 #[test]
 fn test_architecture_parsing() {
@@ -25,7 +25,7 @@ fn test_architecture_display() {
 #[test]
 fn test_default_config() {
     let config = MsvcKitConfig::default();
-    assert!(config.verify_hashes);
+    assert_eq!(config.verify_mode, VerifyMode::Enforce);
     assert_eq!(config.parallel_downloads, 4);
     assert_eq!(config.default_arch, Architecture::X64);
 }
@@ -35,7 +35,7 @@ fn test_download_options_default() {
     let options = DownloadOptions::default();
     assert!(options.msvc_version.is_none());
     assert!(options.sdk_version.is_none());
-    assert!(options.verify_hashes);
+    assert_eq!(options.verify_mode, VerifyMode::Enforce);
     assert_eq!(options.parallel_downloads, 4);
 }
 
@@ -1,9 +1,11 @@
 //! Query module integration tests
 
 use msvc_kit::query::{
-    query_installation, ComponentInfo, QueryComponent, QueryOptions, QueryProperty, QueryResult,
+    query_installation, ComponentDetails, ComponentInfo, QueryComponent, QueryOptions,
+    QueryProperty, QueryResult,
 };
 use msvc_kit::version::Architecture;
+use msvc_kit::{ToolKind, WarningCode};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tempfile::TempDir;
@@ -272,6 +274,7 @@ fn create_test_result() -> QueryResult {
             bin_paths: vec![PathBuf::from(
                 "C:/msvc-kit/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64",
             )],
+            details: ComponentDetails::default(),
         }),
         sdk: Some(ComponentInfo {
             component_type: "sdk".to_string(),
@@ -285,7 +288,10 @@ fn create_test_result() -> QueryResult {
                 "C:/msvc-kit/Windows Kits/10/Lib/10.0.26100.0/ucrt/x64",
             )],
             bin_paths: vec![],
+            details: ComponentDetails::default(),
         }),
+        msvc_versions: vec![],
+        sdk_versions: vec![],
         env_vars: {
             let mut m = HashMap::new();
             m.insert("INCLUDE".to_string(), "C:/include".to_string());
@@ -296,15 +302,16 @@ fn create_test_result() -> QueryResult {
         tools: {
             let mut m = HashMap::new();
             m.insert(
-                "cl".to_string(),
+                ToolKind::Cl,
                 PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64/cl.exe"),
             );
             m.insert(
-                "link".to_string(),
+                ToolKind::Link,
                 PathBuf::from("C:/msvc-kit/VC/Tools/MSVC/14.44.34823/bin/Hostx64/x64/link.exe"),
             );
             m
         },
+        warnings: Vec::new(),
     }
 }
 
@@ -328,6 +335,17 @@ fn test_query_result_tool_path() {
     assert!(result.tool_path("nonexistent").is_none());
 }
 
+#[test]
+fn test_query_result_tools_serialize_by_name() {
+    let result = create_test_result();
+    assert!(result.tool(ToolKind::Cl).is_some());
+    assert!(result.tool(ToolKind::Rc).is_none());
+
+    let json = serde_json::to_value(&result).unwrap();
+    assert!(json["tools"]["cl"].is_string());
+    assert!(json["tools"]["link"].is_string());
+}
+
 #[test]
 fn test_query_result_env_var() {
     let result = create_test_result();
@@ -406,9 +424,13 @@ fn test_query_result_no_msvc() {
             include_paths: vec![],
             lib_paths: vec![],
             bin_paths: vec![],
+            details: ComponentDetails::default(),
         }),
+        msvc_versions: vec![],
+        sdk_versions: vec![],
         env_vars: HashMap::new(),
         tools: HashMap::new(),
+        warnings: Vec::new(),
     };
 
     assert!(result.msvc_version().is_none());
@@ -429,10 +451,14 @@ fn test_query_result_no_sdk() {
             include_paths: vec![PathBuf::from("C:/include")],
             lib_paths: vec![PathBuf::from("C:/lib")],
             bin_paths: vec![],
+            details: ComponentDetails::default(),
         }),
         sdk: None,
+        msvc_versions: vec![],
+        sdk_versions: vec![],
         env_vars: HashMap::new(),
         tools: HashMap::new(),
+        warnings: Vec::new(),
     };
 
     assert!(result.sdk_version().is_none());
@@ -455,6 +481,7 @@ fn test_component_info_serialization() {
         include_paths: vec![PathBuf::from("C:/test/include")],
         lib_paths: vec![PathBuf::from("C:/test/lib")],
         bin_paths: vec![PathBuf::from("C:/test/bin")],
+        details: ComponentDetails::default(),
     };
 
     let json = serde_json::to_string(&info).unwrap();
@@ -514,6 +541,11 @@ fn test_query_with_mock_msvc_installation() {
     assert!(result.msvc.is_some());
     assert_eq!(result.msvc_version(), Some("14.44.34823"));
     assert!(result.sdk.is_none());
+
+    // The missing SDK is reported as a warning
+    assert_eq!(result.warnings.len(), 1);
+    assert_eq!(result.warnings[0].code, WarningCode::MissingComponent);
+    assert_eq!(result.to_json()["warnings"][0]["code"], "missing-component");
 }
 
 #[test]
@@ -551,6 +583,69 @@ fn test_query_with_mock_sdk_installation() {
     assert!(result.msvc.is_some());
     assert!(result.sdk.is_some());
     assert_eq!(result.sdk_version(), Some("10.0.26100.0"));
+    assert!(result.warnings.is_empty());
+
+    // Sub-component details come from the tree
+    let sdk = result.sdk.unwrap();
+    assert_eq!(sdk.details.ucrt_version.as_deref(), Some("10.0.26100.0"));
+    let msvc = result.msvc.unwrap();
+    assert_eq!(
+        msvc.details,
+        ComponentDetails {
+            target_arches: vec![Architecture::X64],
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn test_query_all_versions() {
+    let temp = TempDir::new().unwrap();
+    let msvc_root = temp.path().join("VC").join("Tools").join("MSVC");
+    for version in ["14.43.34808", "14.44.34823"] {
+        std::fs::create_dir_all(msvc_root.join(version).join("include")).unwrap();
+    }
+    for version in ["10.0.22621.0", "10.0.26100.0"] {
+        let include = temp
+            .path()
+            .join("Windows Kits")
+            .join("10")
+            .join("Include")
+            .join(version);
+        std::fs::create_dir_all(include.join("um")).unwrap();
+    }
+
+    let options = QueryOptions::builder()
+        .install_dir(temp.path())
+        .msvc_version("14.43")
+        .all_versions(true)
+        .build();
+    let result = query_installation(&options).unwrap();
+
+    // The selected versions still drive env and tools
+    assert_eq!(result.msvc_version(), Some("14.43.34808"));
+    assert_eq!(result.sdk_version(), Some("10.0.26100.0"));
+    let versions = |infos: &[ComponentInfo]| {
+        infos
+            .iter()
+            .map(|info| info.version.clone())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        versions(&result.msvc_versions),
+        vec!["14.44.34823", "14.43.34808"]
+    );
+    assert_eq!(
+        versions(&result.sdk_versions),
+        vec!["10.0.26100.0", "10.0.22621.0"]
+    );
+    assert!(result.format_summary().contains("14.43.34808 (selected)"));
+
+    // Not collected by default
+    let options = QueryOptions::builder().install_dir(temp.path()).build();
+    let result = query_installation(&options).unwrap();
+    assert!(result.msvc_versions.is_empty());
+    assert!(result.to_json().get("msvc_versions").is_none());
 }
 
 #[test]
@@ -705,6 +800,111 @@ fn test_query_result_env_vars_populated() {
     assert!(result.env_vars.contains_key("VCINSTALLDIR"));
 }
 
+#[test]
+fn test_query_hybrid_roots() {
+    let vs = TempDir::new().unwrap();
+    let kit = TempDir::new().unwrap();
+
+    // MSVC from a Visual Studio layout, SDK from an msvc-kit directory
+    let msvc_dir = vs
+        .path()
+        .join("VC")
+        .join("Tools")
+        .join("MSVC")
+        .join("14.44.34823");
+    std::fs::create_dir_all(msvc_dir.join("include")).unwrap();
+    std::fs::create_dir_all(msvc_dir.join("lib").join("x64")).unwrap();
+    std::fs::create_dir_all(msvc_dir.join("bin").join("Hostx64").join("x64")).unwrap();
+    let sdk_include = kit
+        .path()
+        .join("Windows Kits")
+        .join("10")
+        .join("Include")
+        .join("10.0.26100.0");
+    std::fs::create_dir_all(sdk_include.join("ucrt")).unwrap();
+
+    let options = QueryOptions::builder()
+        .install_dir("C:/this/path/does/not/exist/at/all")
+        .msvc_dir(vs.path().join("VC").join("Tools").join("MSVC"))
+        .sdk_dir(kit.path().join("Windows Kits").join("10"))
+        .arch(Architecture::X64)
+        .build();
+    assert_eq!(options.msvc_root(), vs.path());
+    assert_eq!(options.sdk_root(), kit.path());
+
+    let result = query_installation(&options).unwrap();
+    assert_eq!(result.msvc_version(), Some("14.44.34823"));
+    assert_eq!(result.sdk_version(), Some("10.0.26100.0"));
+    assert!(result.env_var("INCLUDE").unwrap().contains("ucrt"));
+
+    let env = result.environment(Some(Architecture::X64)).unwrap();
+    assert_eq!(env.vc_tools_version, "14.44.34823");
+    assert_eq!(env.windows_sdk_version, "10.0.26100.0");
+    assert!(env.vc_install_dir.starts_with(vs.path()));
+}
+
+#[test]
+fn test_query_result_find_header_and_library() {
+    let temp = TempDir::new().unwrap();
+
+    let msvc_version_dir = temp
+        .path()
+        .join("VC")
+        .join("Tools")
+        .join("MSVC")
+        .join("14.44.34823");
+    std::fs::create_dir_all(msvc_version_dir.join("include")).unwrap();
+    std::fs::create_dir_all(msvc_version_dir.join("lib").join("x64")).unwrap();
+    std::fs::create_dir_all(msvc_version_dir.join("bin").join("Hostx64").join("x64")).unwrap();
+    std::fs::write(msvc_version_dir.join("include").join("vcruntime.h"), "").unwrap();
+    std::fs::write(
+        msvc_version_dir.join("lib").join("x64").join("libcmt.lib"),
+        "",
+    )
+    .unwrap();
+
+    let sdk_root = temp.path().join("Windows Kits").join("10");
+    let um_include = sdk_root.join("Include").join("10.0.26100.0").join("um");
+    let um_lib = sdk_root
+        .join("Lib")
+        .join("10.0.26100.0")
+        .join("um")
+        .join("x64");
+    std::fs::create_dir_all(um_include.join("sys")).unwrap();
+    std::fs::create_dir_all(&um_lib).unwrap();
+    std::fs::write(um_include.join("Windows.h"), "").unwrap();
+    std::fs::write(um_include.join("sys").join("stat.h"), "").unwrap();
+    std::fs::write(um_lib.join("kernel32.Lib"), "").unwrap();
+
+    let options = QueryOptions::builder()
+        .install_dir(temp.path())
+        .arch(Architecture::X64)
+        .build();
+    let result = query_installation(&options).unwrap();
+
+    assert_eq!(
+        result.find_header("vcruntime.h"),
+        Some(msvc_version_dir.join("include").join("vcruntime.h"))
+    );
+    // Case-insensitive, like the Windows file system
+    assert_eq!(
+        result.find_header("windows.h"),
+        Some(um_include.join("Windows.h"))
+    );
+    assert_eq!(
+        result.find_header("sys/stat.h"),
+        Some(um_include.join("sys").join("stat.h"))
+    );
+    assert!(result.find_header("missing.h").is_none());
+
+    assert_eq!(
+        result.find_library("kernel32.lib"),
+        Some(um_lib.join("kernel32.Lib"))
+    );
+    assert!(result.find_library("libcmt.lib").is_some());
+    assert!(result.find_library("missing.lib").is_none());
+}
+
 // ============================================================================
 // JSON Serialization Round-trip Tests
 // ============================================================================
@@ -730,8 +930,11 @@ fn test_query_result_json_skip_serializing_none() {
         arch: "x64".to_string(),
         msvc: None,
         sdk: None,
+        msvc_versions: vec![],
+        sdk_versions: vec![],
         env_vars: HashMap::new(),
         tools: HashMap::new(),
+        warnings: Vec::new(),
     };
 
     let json_str = serde_json::to_string(&result).unwrap();
@@ -49,6 +49,11 @@ fn test_install_info_reexport() {
         install_path: PathBuf::new(),
         downloaded_files: vec![],
         arch: msvc_kit::Architecture::X64,
+        extract_filters: vec![],
+        cleanup: Default::default(),
+        resolution: None,
+        warnings: Vec::new(),
+        reused: false,
     };
 }
 
@@ -83,6 +88,11 @@ fn test_download_functions_exist() {
             install_path: std::path::PathBuf::new(),
             downloaded_files: vec![],
             arch: msvc_kit::Architecture::X64,
+            extract_filters: vec![],
+            cleanup: Default::default(),
+            resolution: None,
+            warnings: Vec::new(),
+            reused: false,
         })
     };
 }
@@ -128,3 +138,20 @@ fn test_bundle_types_reexport() {
     let _opts = msvc_kit::BundleOptions::default();
     assert_eq!(_opts.arch, msvc_kit::Architecture::X64);
 }
+
+#[test]
+fn test_prelude_stable_surface() {
+    use msvc_kit::prelude::*;
+
+    let options: DownloadOptions = DownloadOptions::builder().arch(Architecture::Arm64).build();
+    assert_eq!(options.arch, Architecture::Arm64);
+
+    let query: QueryOptions = QueryOptions::builder()
+        .component(QueryComponent::Msvc)
+        .build();
+    assert_eq!(query.component, QueryComponent::Msvc);
+
+    let _: fn(&QueryOptions) -> Result<QueryResult> = query_installation;
+    let _: Option<BoxedProgressHandler> = None;
+    let _: Option<MsvcKitError> = None;
+}
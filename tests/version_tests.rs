@@ -54,6 +54,42 @@ fn test_architecture_from_str_invalid() {
     assert!("x128".parse::<Architecture>().is_err());
 }
 
+#[test]
+fn test_architecture_from_rust_triple() {
+    let cases = [
+        ("x86_64-pc-windows-msvc", Architecture::X64),
+        ("x86_64-uwp-windows-msvc", Architecture::X64),
+        ("i686-pc-windows-msvc", Architecture::X86),
+        ("i586-pc-windows-msvc", Architecture::X86),
+        ("aarch64-pc-windows-msvc", Architecture::Arm64),
+        ("arm64ec-pc-windows-msvc", Architecture::Arm64),
+        ("thumbv7a-pc-windows-msvc", Architecture::Arm),
+    ];
+    for (triple, arch) in cases {
+        assert_eq!(Architecture::from_rust_triple(triple).unwrap(), arch);
+        assert_eq!(triple.parse::<Architecture>().unwrap(), arch);
+    }
+
+    assert!(Architecture::from_rust_triple("x86_64-pc-windows-gnu").is_err());
+    assert!(Architecture::from_rust_triple("x86_64-unknown-linux-gnu").is_err());
+    assert!(Architecture::from_rust_triple("riscv64-pc-windows-msvc").is_err());
+}
+
+#[test]
+fn test_architecture_rust_triple_roundtrip() {
+    for arch in [
+        Architecture::X64,
+        Architecture::X86,
+        Architecture::Arm64,
+        Architecture::Arm,
+    ] {
+        assert_eq!(
+            Architecture::from_rust_triple(arch.rust_triple()).unwrap(),
+            arch
+        );
+    }
+}
+
 #[test]
 fn test_architecture_display() {
     assert_eq!(Architecture::X64.to_string(), "x64");